@@ -2,6 +2,8 @@
 mod barycentric;
 /// Config to constrain batch data (decoded blob data)
 mod batch_data;
+/// Circuit proving the EIP-4844 blob commitment of a batch.
+mod blob_commitment;
 /// Config to constrain blob data (encoded batch data)
 mod blob_data;
 /// Circuit implementation of aggregation circuit.
@@ -23,5 +25,8 @@ pub(crate) use blob_data::BlobDataConfig;
 pub(crate) use decoder::{witgen, DecoderConfig, DecoderConfigArgs};
 pub(crate) use rlc::RlcConfig;
 
+pub use blob_commitment::{
+    BlobCommitmentCircuit, BlobCommitmentConfig, BLOB_COMMITMENT_INSTANCE_LEN,
+};
 pub use circuit::AggregationCircuit;
 pub use config::AggregationConfig;
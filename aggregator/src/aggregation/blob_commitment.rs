@@ -0,0 +1,440 @@
+use ark_std::{end_timer, start_timer};
+use eth_types::ToBigEndian;
+use halo2_base::{
+    gates::range::{RangeConfig, RangeStrategy},
+    Context, ContextParams,
+};
+use halo2_ecc::bigint::CRTInteger;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+use itertools::Itertools;
+use snark_verifier_sdk::CircuitExt;
+use zkevm_circuits::{
+    keccak_circuit::{KeccakCircuitConfig, KeccakCircuitConfigArgs},
+    table::{BitwiseOpTable, KeccakTable, Pow2Table, PowOfRandTable, RangeTable, U8Table},
+    util::{Challenges, SubCircuitConfig},
+};
+
+use crate::{
+    aggregation::{
+        batch_data::AssignedBatchDataExport,
+        witgen::{process, MultiBlockProcessResult},
+    },
+    blob::{BatchData, PointEvaluationAssignments, N_BYTES_U256},
+    constants::LOG_DEGREE,
+    core::{assign_keccak_table, constrain_flags},
+    param::ConfigParams,
+    AssignedBarycentricEvaluationConfig, BarycentricEvaluationConfig, BatchDataConfig,
+    BlobDataConfig, DecoderConfig, DecoderConfigArgs, RlcConfig,
+};
+
+/// The number of public inputs of the blob commitment circuit:
+/// - the blob versioned hash (32 elements)
+/// - the challenge point z, as big-endian bytes (32 elements)
+/// - the evaluation y = P(z), as big-endian bytes (32 elements)
+pub const BLOB_COMMITMENT_INSTANCE_LEN: usize = 3 * N_BYTES_U256;
+
+/// Configuration of the blob commitment circuit.
+#[derive(Clone, Debug)]
+pub struct BlobCommitmentConfig<const N_SNARKS: usize> {
+    /// Keccak circuit configurations
+    pub keccak_circuit_config: KeccakCircuitConfig<Fr>,
+    /// RLC config
+    pub rlc_config: RlcConfig,
+    /// The blob data's config.
+    pub blob_data_config: BlobDataConfig<N_SNARKS>,
+    /// The batch data's config.
+    pub batch_data_config: BatchDataConfig<N_SNARKS>,
+    /// The zstd decoder's config.
+    pub decoder_config: DecoderConfig<1024, 512>,
+    /// Config to do the barycentric evaluation on blob polynomial.
+    pub barycentric: BarycentricEvaluationConfig,
+    /// Instance for public input, see [`BLOB_COMMITMENT_INSTANCE_LEN`].
+    pub instance: Column<Instance>,
+}
+
+impl<const N_SNARKS: usize> BlobCommitmentConfig<N_SNARKS> {
+    /// Build a configuration from parameters.
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fr>,
+        params: &ConfigParams,
+        challenges: Challenges,
+    ) -> Self {
+        let challenges_expr = challenges.exprs(meta);
+
+        let keccak_table = KeccakTable::construct(meta);
+        let keccak_circuit_config = KeccakCircuitConfig::new(
+            meta,
+            KeccakCircuitConfigArgs {
+                keccak_table: keccak_table.clone(),
+                challenges: challenges_expr.clone(),
+            },
+        );
+
+        let rlc_config = RlcConfig::configure(meta, &keccak_table, challenges);
+
+        let range = RangeConfig::<Fr>::configure(
+            meta,
+            RangeStrategy::Vertical,
+            &params.num_advice,
+            &params.num_lookup_advice,
+            params.num_fixed,
+            params.lookup_bits,
+            0,
+            params.degree as usize,
+        );
+        let barycentric = BarycentricEvaluationConfig::construct(range);
+
+        let u8_table = U8Table::construct(meta);
+        let range_table = RangeTable::construct(meta);
+        let blob_data_config = BlobDataConfig::configure(meta, &challenges_expr, u8_table);
+        let batch_data_config = BatchDataConfig::configure(
+            meta,
+            &challenges_expr,
+            u8_table,
+            range_table,
+            &keccak_table,
+        );
+
+        let decoder_config = DecoderConfig::configure(
+            meta,
+            &challenges_expr,
+            DecoderConfigArgs {
+                pow_rand_table: PowOfRandTable::construct(meta, &challenges_expr),
+                pow2_table: Pow2Table::construct(meta),
+                u8_table,
+                range8: RangeTable::construct(meta),
+                range16: RangeTable::construct(meta),
+                range512: RangeTable::construct(meta),
+                range_block_len: RangeTable::construct(meta),
+                bitwise_op_table: BitwiseOpTable::construct(meta),
+            },
+        );
+
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        Self {
+            keccak_circuit_config,
+            rlc_config,
+            blob_data_config,
+            batch_data_config,
+            decoder_config,
+            barycentric,
+            instance,
+        }
+    }
+}
+
+/// Circuit proving the EIP-4844 data availability commitment of a batch.
+///
+/// It encodes the batch data into the blob, evaluates the blob polynomial at
+/// the challenge point z = keccak(metadata_digest || chunk_data_digests ||
+/// versioned_hash) using the barycentric formula, and exposes the versioned
+/// hash with z and y = P(z) as public inputs. The L1 contract compares the
+/// versioned hash against the blob of the commit tx and checks y with the
+/// point evaluation precompile.
+#[derive(Clone, Debug)]
+pub struct BlobCommitmentCircuit<const N_SNARKS: usize> {
+    /// The batch data committed to by the blob.
+    pub batch_data: BatchData<N_SNARKS>,
+    /// The blob polynomial with its challenge point and evaluation.
+    pub point_evaluation_assignments: PointEvaluationAssignments,
+}
+
+impl<const N_SNARKS: usize> BlobCommitmentCircuit<N_SNARKS> {
+    /// Build the circuit for the blob of `batch_data`.
+    pub fn new(batch_data: BatchData<N_SNARKS>) -> Self {
+        let point_evaluation_assignments = PointEvaluationAssignments::from(&batch_data);
+        Self {
+            batch_data,
+            point_evaluation_assignments,
+        }
+    }
+}
+
+impl<const N_SNARKS: usize> Circuit<Fr> for BlobCommitmentCircuit<N_SNARKS> {
+    type Config = (BlobCommitmentConfig<N_SNARKS>, Challenges);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(BatchData::default())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let challenges = Challenges::construct_p1(meta);
+        let config =
+            BlobCommitmentConfig::configure(meta, &ConfigParams::aggregation_param(), challenges);
+        (config, challenges)
+    }
+
+    fn synthesize(
+        &self,
+        (config, challenge): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let witness_time = start_timer!(|| "synthesize | Blob Commitment Circuit");
+        let challenges = challenge.values(&layouter);
+
+        // ==============================================
+        // step 1: barycentric evaluation of the blob polynomial
+        // ==============================================
+        config
+            .barycentric
+            .scalar
+            .range
+            .load_lookup_table(&mut layouter)?;
+
+        let mut first_pass = halo2_base::SKIP_FIRST_PASS;
+        let barycentric = layouter.assign_region(
+            || "barycentric evaluation",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(AssignedBarycentricEvaluationConfig::default());
+                }
+
+                let gate = &config.barycentric.scalar.range.gate;
+                let mut ctx = Context::new(
+                    region,
+                    ContextParams {
+                        max_rows: gate.max_rows,
+                        num_context_ids: 1,
+                        fixed_columns: gate.constants.clone(),
+                    },
+                );
+
+                let barycentric = config.barycentric.assign(
+                    &mut ctx,
+                    &self.point_evaluation_assignments.coefficients,
+                    self.point_evaluation_assignments.challenge_digest,
+                    self.point_evaluation_assignments.evaluation,
+                );
+
+                config.barycentric.scalar.range.finalize(&mut ctx);
+                ctx.print_stats(&["barycentric evaluation"]);
+
+                Ok(barycentric)
+            },
+        )?;
+
+        // ==============================================
+        // step 2: keccak digests of the batch data
+        // ==============================================
+        config
+            .keccak_circuit_config
+            .load_aux_tables(&mut layouter)?;
+        assign_keccak_table(
+            &config.keccak_circuit_config,
+            &mut layouter,
+            challenges,
+            &self.batch_data.preimages(),
+        )
+        .map_err(|e| {
+            log::error!("assign_keccak_table err {:#?}", e);
+            Error::ConstraintSystemFailure
+        })?;
+
+        let (num_valid_chunks, chunks_are_padding) = layouter.assign_region(
+            || "chunks are padding or not",
+            |mut region| -> Result<(AssignedCell<Fr, Fr>, Vec<AssignedCell<Fr, Fr>>), Error> {
+                let rlc_config = &config.rlc_config;
+                rlc_config.init(&mut region)?;
+                let mut offset = 0;
+
+                let chunks_are_valid = (0..N_SNARKS)
+                    .map(|i| {
+                        let is_valid = (i as u16) < self.batch_data.num_valid_chunks;
+                        rlc_config.load_private(
+                            &mut region,
+                            &Fr::from(is_valid as u64),
+                            &mut offset,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                let num_valid_chunks =
+                    constrain_flags(rlc_config, &mut region, &chunks_are_valid, &mut offset)?;
+                let chunks_are_padding = chunks_are_valid
+                    .iter()
+                    .map(|is_valid| rlc_config.not(&mut region, is_valid, &mut offset))
+                    .collect::<Result<Vec<_>, Error>>()?;
+
+                Ok((num_valid_chunks, chunks_are_padding))
+            },
+        )?;
+
+        // ==============================================
+        // step 3: blob data, batch data and the zstd decoding between them
+        // ==============================================
+        let batch_data_exports = assign_blob_commitment(
+            &config.blob_data_config,
+            &config.batch_data_config,
+            &config.decoder_config,
+            &config.rlc_config,
+            &mut layouter,
+            challenges,
+            &chunks_are_padding,
+            &self.batch_data,
+            &barycentric.barycentric_assignments,
+        )?;
+
+        layouter.assign_region(
+            || "num valid chunks check",
+            |mut region| -> Result<(), Error> {
+                region.constrain_equal(
+                    num_valid_chunks.cell(),
+                    batch_data_exports.num_valid_chunks.cell(),
+                )
+            },
+        )?;
+
+        // ==============================================
+        // step 4: expose the versioned hash, z and y
+        // ==============================================
+        let instance_cells = batch_data_exports
+            .versioned_hash
+            .iter()
+            .map(|cell| cell.cell())
+            .chain(barycentric.z_le.iter().rev().map(|cell| cell.cell()))
+            .chain(barycentric.y_le.iter().rev().map(|cell| cell.cell()))
+            .collect_vec();
+        assert_eq!(instance_cells.len(), BLOB_COMMITMENT_INSTANCE_LEN);
+        for (index, cell) in instance_cells.into_iter().enumerate() {
+            layouter.constrain_instance(cell, config.instance, index)?;
+        }
+
+        end_timer!(witness_time);
+
+        Ok(())
+    }
+}
+
+/// Assign the blob data, the batch data and the zstd decoding of the blob into the batch data,
+/// and constrain the decoder's encoded and decoded bytes to be the blob and batch bytes. Returns
+/// the exports of the batch data, which the caller binds to the rest of its circuit.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn assign_blob_commitment<const N_SNARKS: usize>(
+    blob_data_config: &BlobDataConfig<N_SNARKS>,
+    batch_data_config: &BatchDataConfig<N_SNARKS>,
+    decoder_config: &DecoderConfig<1024, 512>,
+    rlc_config: &RlcConfig,
+    layouter: &mut impl Layouter<Fr>,
+    challenges: Challenges<Value<Fr>>,
+    chunks_are_padding: &[AssignedCell<Fr, Fr>],
+    batch_data: &BatchData<N_SNARKS>,
+    barycentric_assignments: &[CRTInteger<Fr>],
+) -> Result<AssignedBatchDataExport, Error> {
+    let blob_data_exports = blob_data_config.assign(
+        layouter,
+        challenges,
+        rlc_config,
+        batch_data,
+        barycentric_assignments,
+    )?;
+
+    let batch_data_exports = batch_data_config.assign(
+        layouter,
+        challenges,
+        rlc_config,
+        chunks_are_padding,
+        batch_data,
+        barycentric_assignments,
+    )?;
+
+    let batch_bytes = batch_data.get_batch_data_bytes();
+    let encoded_batch_bytes = batch_data.get_encoded_batch_data_bytes();
+
+    let MultiBlockProcessResult {
+        witness_rows,
+        literal_bytes: decoded_literals,
+        fse_aux_tables,
+        block_info_arr,
+        sequence_info_arr,
+        address_table_rows: address_table_arr,
+        sequence_exec_results,
+    } = process(&encoded_batch_bytes, challenges.keccak_input());
+
+    // sanity check:
+    let (recovered_bytes, sequence_exec_info_arr) = sequence_exec_results.into_iter().fold(
+        (Vec::new(), Vec::new()),
+        |(mut out_byte, mut out_exec), res| {
+            out_byte.extend(res.recovered_bytes);
+            out_exec.push(res.exec_trace);
+            (out_byte, out_exec)
+        },
+    );
+    assert_eq!(
+        batch_bytes, recovered_bytes,
+        "original and recovered bytes mismatch"
+    );
+
+    let decoder_exports = decoder_config.assign(
+        layouter,
+        &batch_bytes,
+        &encoded_batch_bytes,
+        witness_rows,
+        decoded_literals,
+        fse_aux_tables,
+        block_info_arr,
+        sequence_info_arr,
+        address_table_arr,
+        sequence_exec_info_arr,
+        &challenges,
+        LOG_DEGREE, // TODO: configure k for aggregation circuit instead of hard-coded here.
+    )?;
+
+    layouter.assign_region(
+        || "blob decoding checks",
+        |mut region| -> Result<(), Error> {
+            // equate rlc (from blob data) with decoder's encoded_rlc
+            region.constrain_equal(
+                blob_data_exports.bytes_rlc.cell(),
+                decoder_exports.encoded_rlc.cell(),
+            )?;
+            // equate len(blob_bytes) with decoder's encoded_len
+            region.constrain_equal(
+                blob_data_exports.bytes_len.cell(),
+                decoder_exports.encoded_len.cell(),
+            )?;
+            // equate rlc (from batch data) with decoder's decoded_rlc
+            region.constrain_equal(
+                batch_data_exports.bytes_rlc.cell(),
+                decoder_exports.decoded_rlc.cell(),
+            )?;
+            // equate len(batch_data) with decoder's decoded_len
+            region.constrain_equal(
+                batch_data_exports.batch_data_len.cell(),
+                decoder_exports.decoded_len.cell(),
+            )?;
+
+            Ok(())
+        },
+    )?;
+
+    Ok(batch_data_exports)
+}
+
+impl<const N_SNARKS: usize> CircuitExt<Fr> for BlobCommitmentCircuit<N_SNARKS> {
+    fn num_instance(&self) -> Vec<usize> {
+        vec![BLOB_COMMITMENT_INSTANCE_LEN]
+    }
+
+    // 32 elements from the blob versioned hash
+    // 32 elements from z
+    // 32 elements from y
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        let versioned_hash = self.batch_data.get_versioned_hash();
+        let z = self.point_evaluation_assignments.challenge.to_be_bytes();
+        let y = self.point_evaluation_assignments.evaluation.to_be_bytes();
+        vec![versioned_hash
+            .as_bytes()
+            .iter()
+            .chain(z.iter())
+            .chain(y.iter())
+            .map(|&byte| Fr::from(byte as u64))
+            .collect()]
+    }
+}
@@ -1,5 +1,7 @@
-use crate::{blob::BatchData, witgen::MultiBlockProcessResult, LOG_DEGREE};
+use crate::blob::BatchData;
 use ark_std::{end_timer, start_timer};
+#[cfg(not(feature = "disable_proof_aggregation"))]
+use halo2_base::gates::GateInstructions;
 use halo2_base::{Context, ContextParams};
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
@@ -27,15 +29,15 @@ use snark_verifier_sdk::{CircuitExt, Snark, SnarkWitness};
 use zkevm_circuits::util::Challenges;
 
 use crate::{
-    aggregation::witgen::process,
     batch::BatchHash,
-    constants::{ACC_LEN, DIGEST_LEN},
+    batch_header::assign_batch_header_hash,
+    constants::{ACC_LEN, DIGEST_LEN, L1_QUEUE_INDEX_LEN, UNPROVEN_STEPS_LEN},
     core::{assign_batch_hashes, extract_proof_and_instances_with_pairing_check},
     util::parse_hash_digest_cells,
     AssignedBarycentricEvaluationConfig, ConfigParams,
 };
 
-use super::AggregationConfig;
+use super::{blob_commitment::assign_blob_commitment, AggregationConfig};
 
 /// Aggregation circuit that does not re-expose any public inputs from aggregated snarks
 #[derive(Clone)]
@@ -47,6 +49,8 @@ pub struct AggregationCircuit<const N_SNARKS: usize> {
     // the public instance for this circuit consists of
     // - an accumulator (12 elements)
     // - the batch's public_input_hash (32 elements)
+    // - the L1 message queue index before and after the batch (2 elements)
+    // - the batch header hash (32 elements)
     pub flattened_instances: Vec<Fr>,
     // accumulation scheme proof, private input
     pub as_proof: Value<Vec<u8>>,
@@ -73,7 +77,10 @@ impl<const N_SNARKS: usize> AggregationCircuit<N_SNARKS> {
             let chunk_hash_bytes = chunk.public_input_hash();
             let snark_hash_bytes = &snark.instances[0];
 
-            assert_eq!(snark_hash_bytes.len(), ACC_LEN + DIGEST_LEN);
+            assert_eq!(
+                snark_hash_bytes.len(),
+                ACC_LEN + DIGEST_LEN + L1_QUEUE_INDEX_LEN + UNPROVEN_STEPS_LEN
+            );
 
             for i in 0..DIGEST_LEN {
                 // for each snark,
@@ -95,12 +102,14 @@ impl<const N_SNARKS: usize> AggregationCircuit<N_SNARKS> {
         let (as_proof, acc_instances) =
             extract_proof_and_instances_with_pairing_check(params, snarks_with_padding, rng)?;
 
-        // extract batch's public input hash
+        // extract batch's public input hash, L1 message queue indexes and batch header hash
         let public_input_hash = &batch_hash.instances_exclude_acc()[0];
 
         // the public instance for this circuit consists of
         // - an accumulator (12 elements)
         // - the batch's public_input_hash (32 elements)
+        // - the L1 message queue index before and after the batch (2 elements)
+        // - the batch header hash (32 elements)
         let flattened_instances: Vec<Fr> =
             [acc_instances.as_slice(), public_input_hash.as_slice()].concat();
 
@@ -205,90 +214,118 @@ impl<const N_SNARKS: usize> Circuit<Fr> for AggregationCircuit<N_SNARKS> {
         };
 
         #[cfg(not(feature = "disable_proof_aggregation"))]
-        let (accumulator_instances, snark_inputs, barycentric) = {
+        let (accumulator_instances, snark_inputs, snark_l1_queue_indexes, barycentric) = {
             let mut first_pass = halo2_base::SKIP_FIRST_PASS;
 
-            let (accumulator_instances, snark_inputs, barycentric) = layouter.assign_region(
-                || "aggregation",
-                |region| {
-                    if first_pass {
-                        first_pass = false;
-                        return Ok((
-                            vec![],
-                            vec![],
-                            AssignedBarycentricEvaluationConfig::default(),
-                        ));
-                    }
+            let (accumulator_instances, snark_inputs, snark_l1_queue_indexes, barycentric) =
+                layouter.assign_region(
+                    || "aggregation",
+                    |region| {
+                        if first_pass {
+                            first_pass = false;
+                            return Ok((
+                                vec![],
+                                vec![],
+                                vec![],
+                                AssignedBarycentricEvaluationConfig::default(),
+                            ));
+                        }
 
-                    // stores accumulators for all snarks, including the padded ones
-                    let mut accumulator_instances: Vec<AssignedValue<Fr>> = vec![];
-                    // stores public inputs for all snarks, including the padded ones
-                    let mut snark_inputs: Vec<AssignedValue<Fr>> = vec![];
-                    let ctx = Context::new(
-                        region,
-                        ContextParams {
-                            max_rows: config.flex_gate().max_rows,
-                            num_context_ids: 1,
-                            fixed_columns: config.flex_gate().constants.clone(),
-                        },
-                    );
+                        // stores accumulators for all snarks, including the padded ones
+                        let mut accumulator_instances: Vec<AssignedValue<Fr>> = vec![];
+                        // stores public inputs for all snarks, including the padded ones
+                        let mut snark_inputs: Vec<AssignedValue<Fr>> = vec![];
+                        // stores the L1 message queue indexes before and after each snark
+                        let mut snark_l1_queue_indexes: Vec<AssignedValue<Fr>> = vec![];
+                        // stores the unproven steps flags for all snarks
+                        let mut unproven_steps: Vec<AssignedValue<Fr>> = vec![];
+                        let ctx = Context::new(
+                            region,
+                            ContextParams {
+                                max_rows: config.flex_gate().max_rows,
+                                num_context_ids: 1,
+                                fixed_columns: config.flex_gate().constants.clone(),
+                            },
+                        );
 
-                    let ecc_chip = config.ecc_chip();
-                    let loader = Halo2Loader::new(ecc_chip, ctx);
-
-                    //
-                    // extract the assigned values for
-                    // - instances which are the public inputs of each chunk (prefixed with 12
-                    //   instances from previous accumulators)
-                    // - new accumulator to be verified on chain
-                    //
-                    log::debug!("aggregation: assigning aggregation");
-                    let (assigned_aggregation_instances, acc) = aggregate::<Kzg<Bn256, Bdfg21>>(
-                        &self.svk,
-                        &loader,
-                        &self.snarks_with_padding,
-                        self.as_proof(),
-                    );
-                    for (i, e) in assigned_aggregation_instances[0].iter().enumerate() {
-                        log::trace!("{}-th instance: {:?}", i, e.value)
-                    }
+                        let ecc_chip = config.ecc_chip();
+                        let loader = Halo2Loader::new(ecc_chip, ctx);
+
+                        //
+                        // extract the assigned values for
+                        // - instances which are the public inputs of each chunk (prefixed with 12
+                        //   instances from previous accumulators)
+                        // - new accumulator to be verified on chain
+                        //
+                        log::debug!("aggregation: assigning aggregation");
+                        let (assigned_aggregation_instances, acc) = aggregate::<Kzg<Bn256, Bdfg21>>(
+                            &self.svk,
+                            &loader,
+                            &self.snarks_with_padding,
+                            self.as_proof(),
+                        );
+                        for (i, e) in assigned_aggregation_instances[0].iter().enumerate() {
+                            log::trace!("{}-th instance: {:?}", i, e.value)
+                        }
 
-                    // extract the following cells for later constraints
-                    // - the accumulators
-                    // - the public inputs from each snark
-                    accumulator_instances.extend(flatten_accumulator(acc).iter().copied());
-                    // the snark is not a fresh one, assigned_instances already contains an
-                    // accumulator so we want to skip the first 12 elements from the public
-                    // input
-                    snark_inputs.extend(
-                        assigned_aggregation_instances
-                            .iter()
-                            .flat_map(|instance_column| instance_column.iter().skip(ACC_LEN)),
-                    );
+                        // extract the following cells for later constraints
+                        // - the accumulators
+                        // - the public inputs from each snark
+                        accumulator_instances.extend(flatten_accumulator(acc).iter().copied());
+                        // the snark is not a fresh one, assigned_instances already contains an
+                        // accumulator so we want to skip the first 12 elements from the public
+                        // input, which ends with the L1 message queue index of the Tx circuit and
+                        // the unproven steps flag of the EVM circuit
+                        for instance_column in assigned_aggregation_instances.iter() {
+                            let (public_input_hash, rest) =
+                                instance_column[ACC_LEN..].split_at(DIGEST_LEN);
+                            let (l1_queue_indexes, flag) = rest.split_at(L1_QUEUE_INDEX_LEN);
+                            snark_inputs.extend(public_input_hash);
+                            snark_l1_queue_indexes.extend(l1_queue_indexes);
+                            unproven_steps.extend(flag);
+                        }
 
-                    loader.ctx_mut().print_stats(&["snark aggregation"]);
+                        loader.ctx_mut().print_stats(&["snark aggregation"]);
 
-                    let mut ctx = Rc::into_inner(loader).unwrap().into_ctx();
-                    log::debug!("aggregation: assigning barycentric");
-                    let barycentric = config.barycentric.assign(
-                        &mut ctx,
-                        &self.batch_hash.point_evaluation_assignments.coefficients,
-                        self.batch_hash
-                            .point_evaluation_assignments
-                            .challenge_digest,
-                        self.batch_hash.point_evaluation_assignments.evaluation,
-                    );
+                        let mut ctx = Rc::into_inner(loader).unwrap().into_ctx();
 
-                    ctx.print_stats(&["barycentric"]);
+                        // a batch only aggregates chunks fully proven by the EVM circuit
+                        for flag in unproven_steps.iter() {
+                            config
+                                .flex_gate()
+                                .assert_is_const(&mut ctx, flag, Fr::zero());
+                        }
+                        log::debug!("aggregation: assigning barycentric");
+                        let barycentric = config.barycentric.assign(
+                            &mut ctx,
+                            &self.batch_hash.point_evaluation_assignments.coefficients,
+                            self.batch_hash
+                                .point_evaluation_assignments
+                                .challenge_digest,
+                            self.batch_hash.point_evaluation_assignments.evaluation,
+                        );
 
-                    config.range().finalize(&mut ctx);
+                        ctx.print_stats(&["barycentric"]);
 
-                    Ok((accumulator_instances, snark_inputs, barycentric))
-                },
-            )?;
+                        config.range().finalize(&mut ctx);
+
+                        Ok((
+                            accumulator_instances,
+                            snark_inputs,
+                            snark_l1_queue_indexes,
+                            barycentric,
+                        ))
+                    },
+                )?;
 
             assert_eq!(snark_inputs.len(), N_SNARKS * DIGEST_LEN);
-            (accumulator_instances, snark_inputs, barycentric)
+            assert_eq!(snark_l1_queue_indexes.len(), N_SNARKS * L1_QUEUE_INDEX_LEN);
+            (
+                accumulator_instances,
+                snark_inputs,
+                snark_l1_queue_indexes,
+                barycentric,
+            )
         };
         end_timer!(timer);
         // ==============================================
@@ -328,6 +365,10 @@ impl<const N_SNARKS: usize> Circuit<Fr> for AggregationCircuit<N_SNARKS> {
                 .iter()
                 .map(|chunk| !chunk.is_padding)
                 .collect::<Vec<_>>();
+            let batch_header_preimage = self
+                .batch_hash
+                .batch_header()
+                .encode(self.batch_hash.batch_header_encoding());
             let assigned_batch_hash = assign_batch_hashes::<N_SNARKS>(
                 &config.keccak_circuit_config,
                 &config.rlc_config,
@@ -336,6 +377,7 @@ impl<const N_SNARKS: usize> Circuit<Fr> for AggregationCircuit<N_SNARKS> {
                 &chunks_are_valid,
                 self.batch_hash.number_of_valid_chunks,
                 &preimages,
+                &batch_header_preimage,
             )
             .map_err(|e| {
                 log::error!("assign_batch_hashes err {:#?}", e);
@@ -398,6 +440,82 @@ impl<const N_SNARKS: usize> Circuit<Fr> for AggregationCircuit<N_SNARKS> {
             },
         )?;
 
+        // the L1 message queue is popped continuously across the chunks, i.e. each valid chunk
+        // starts from the end index of the previous chunk, and a padding chunk repeats the indexes
+        // of the previous one
+        let (batch_start_l1_queue_index, batch_end_l1_queue_index) = layouter.assign_region(
+            || "l1 queue index checks",
+            |mut region| -> Result<_, Error> {
+                let rlc_config = &config.rlc_config;
+                let mut offset = 0;
+                rlc_config.init(&mut region)?;
+
+                let mut starts = Vec::with_capacity(N_SNARKS);
+                let mut ends = Vec::with_capacity(N_SNARKS);
+                for chunk in self.batch_hash.chunks_with_padding.iter() {
+                    starts.push(rlc_config.load_private(
+                        &mut region,
+                        &Fr::from(chunk.start_l1_queue_index),
+                        &mut offset,
+                    )?);
+                    ends.push(rlc_config.load_private(
+                        &mut region,
+                        &Fr::from(chunk.end_l1_queue_index),
+                        &mut offset,
+                    )?);
+                }
+
+                // the indexes are the ones exposed by the snarks
+                #[cfg(not(feature = "disable_proof_aggregation"))]
+                for (i, (start, end)) in starts.iter().zip_eq(ends.iter()).enumerate() {
+                    region.constrain_equal(
+                        start.cell(),
+                        snark_l1_queue_indexes[i * L1_QUEUE_INDEX_LEN].cell(),
+                    )?;
+                    region.constrain_equal(
+                        end.cell(),
+                        snark_l1_queue_indexes[i * L1_QUEUE_INDEX_LEN + 1].cell(),
+                    )?;
+                }
+
+                for i in 1..N_SNARKS {
+                    let is_padding = &assigned_batch_hash.chunks_are_padding[i];
+                    // start(i) == is_padding(i) ? start(i-1) : end(i-1)
+                    let expected_start = rlc_config.select(
+                        &mut region,
+                        &starts[i - 1],
+                        &ends[i - 1],
+                        is_padding,
+                        &mut offset,
+                    )?;
+                    region.constrain_equal(starts[i].cell(), expected_start.cell())?;
+                    // end(i) == end(i-1) if is_padding(i)
+                    rlc_config.conditional_enforce_equal(
+                        &mut region,
+                        &ends[i],
+                        &ends[i - 1],
+                        is_padding,
+                        &mut offset,
+                    )?;
+                }
+
+                Ok((starts[0].clone(), ends[N_SNARKS - 1].clone()))
+            },
+        )?;
+
+        // the batch header hashes the chunk pi hashes, which are the public inputs of the snarks,
+        // and the number of L1 messages popped by the batch
+        let batch_header_hash_digest = assign_batch_header_hash(
+            &config.rlc_config,
+            &mut layouter,
+            challenges,
+            &self.batch_hash.batch_header(),
+            self.batch_hash.batch_header_encoding(),
+            &chunk_pi_hash_digests,
+            &batch_start_l1_queue_index,
+            &batch_end_l1_queue_index,
+        )?;
+
         // ==============================================
         // step 4: assert public inputs to the aggregator circuit are correct
         // ==============================================
@@ -425,71 +543,44 @@ impl<const N_SNARKS: usize> Circuit<Fr> for AggregationCircuit<N_SNARKS> {
             )?;
         }
 
+        // L1 message queue indexes
+        for (index, cell) in [batch_start_l1_queue_index, batch_end_l1_queue_index]
+            .iter()
+            .enumerate()
+        {
+            layouter.constrain_instance(
+                cell.cell(),
+                config.instance,
+                index + ACC_LEN + DIGEST_LEN,
+            )?;
+        }
+
+        // batch header hash
+        for (index, cell) in batch_header_hash_digest.iter().enumerate() {
+            layouter.constrain_instance(
+                cell.cell(),
+                config.instance,
+                index + ACC_LEN + DIGEST_LEN + L1_QUEUE_INDEX_LEN,
+            )?;
+        }
+
         // blob data config
         {
-            let barycentric_assignments = &barycentric.barycentric_assignments;
             let challenge_le = &barycentric.z_le;
             let evaluation_le = &barycentric.y_le;
 
             let batch_data = BatchData::from(&self.batch_hash);
 
-            let blob_data_exports = config.blob_data_config.assign(
-                &mut layouter,
-                challenges,
+            let batch_data_exports = assign_blob_commitment(
+                &config.blob_data_config,
+                &config.batch_data_config,
+                &config.decoder_config,
                 &config.rlc_config,
-                &batch_data,
-                barycentric_assignments,
-            )?;
-
-            let batch_data_exports = config.batch_data_config.assign(
                 &mut layouter,
                 challenges,
-                &config.rlc_config,
                 &assigned_batch_hash.chunks_are_padding,
                 &batch_data,
-                barycentric_assignments,
-            )?;
-
-            let batch_bytes = batch_data.get_batch_data_bytes();
-            let encoded_batch_bytes = batch_data.get_encoded_batch_data_bytes();
-
-            let MultiBlockProcessResult {
-                witness_rows,
-                literal_bytes: decoded_literals,
-                fse_aux_tables,
-                block_info_arr,
-                sequence_info_arr,
-                address_table_rows: address_table_arr,
-                sequence_exec_results,
-            } = process(&encoded_batch_bytes, challenges.keccak_input());
-
-            // sanity check:
-            let (recovered_bytes, sequence_exec_info_arr) = sequence_exec_results.into_iter().fold(
-                (Vec::new(), Vec::new()),
-                |(mut out_byte, mut out_exec), res| {
-                    out_byte.extend(res.recovered_bytes);
-                    out_exec.push(res.exec_trace);
-                    (out_byte, out_exec)
-                },
-            );
-            assert_eq!(
-                batch_bytes, recovered_bytes,
-                "original and recovered bytes mismatch"
-            );
-
-            let decoder_exports = config.decoder_config.assign(
-                &mut layouter,
-                &batch_bytes,
-                &encoded_batch_bytes,
-                witness_rows,
-                decoded_literals,
-                fse_aux_tables,
-                block_info_arr,
-                sequence_info_arr,
-                address_table_arr,
-                sequence_exec_info_arr,
-                &challenges,
-                LOG_DEGREE, // TODO: configure k for aggregation circuit instead of hard-coded here.
+                &barycentric.barycentric_assignments,
             )?;
 
             layouter.assign_region(
@@ -539,27 +630,6 @@ impl<const N_SNARKS: usize> Circuit<Fr> for AggregationCircuit<N_SNARKS> {
                         region.constrain_equal(c.cell(), ec.cell())?;
                     }
 
-                    // equate rlc (from blob data) with decoder's encoded_rlc
-                    region.constrain_equal(
-                        blob_data_exports.bytes_rlc.cell(),
-                        decoder_exports.encoded_rlc.cell(),
-                    )?;
-                    // equate len(blob_bytes) with decoder's encoded_len
-                    region.constrain_equal(
-                        blob_data_exports.bytes_len.cell(),
-                        decoder_exports.encoded_len.cell(),
-                    )?;
-                    // equate rlc (from batch data) with decoder's decoded_rlc
-                    region.constrain_equal(
-                        batch_data_exports.bytes_rlc.cell(),
-                        decoder_exports.decoded_rlc.cell(),
-                    )?;
-                    // equate len(batch_data) with decoder's decoded_len
-                    region.constrain_equal(
-                        batch_data_exports.batch_data_len.cell(),
-                        decoder_exports.decoded_len.cell(),
-                    )?;
-
                     Ok(())
                 },
             )?;
@@ -575,11 +645,15 @@ impl<const N_SNARKS: usize> CircuitExt<Fr> for AggregationCircuit<N_SNARKS> {
     fn num_instance(&self) -> Vec<usize> {
         // 12 elements from accumulator
         // 32 elements from batch's public_input_hash
-        vec![ACC_LEN + DIGEST_LEN]
+        // 2 elements from the L1 message queue indexes before and after the batch
+        // 32 elements from the batch header hash
+        vec![ACC_LEN + DIGEST_LEN + L1_QUEUE_INDEX_LEN + DIGEST_LEN]
     }
 
     // 12 elements from accumulator
     // 32 elements from batch's public_input_hash
+    // 2 elements from the L1 message queue indexes before and after the batch
+    // 32 elements from the batch header hash
     fn instances(&self) -> Vec<Vec<Fr>> {
         vec![self.flattened_instances.clone()]
     }
@@ -6,6 +6,7 @@ use ethers_core::utils::keccak256;
 use gadgets::Field;
 
 use crate::{
+    batch_header::{BatchHeader, BatchHeaderEncoding},
     blob::{BatchData, PointEvaluationAssignments},
     chunk::ChunkInfo,
 };
@@ -38,6 +39,10 @@ pub struct BatchHash<const N_SNARKS: usize> {
     pub(crate) point_evaluation_assignments: PointEvaluationAssignments,
     /// The 4844 versioned hash for the blob.
     pub(crate) versioned_hash: H256,
+    /// The hash of the parent batch, hashed in the batch header.
+    pub(crate) parent_batch_hash: H256,
+    /// The encoding of the batch header.
+    pub(crate) batch_header_encoding: BatchHeaderEncoding,
 }
 
 impl<const N_SNARKS: usize> BatchHash<N_SNARKS> {
@@ -99,11 +104,23 @@ impl<const N_SNARKS: usize> BatchHash<N_SNARKS> {
                     chunks_with_padding[i + 1].tx_bytes_hash(),
                     chunks_with_padding[i].tx_bytes_hash(),
                 );
+                assert_eq!(
+                    chunks_with_padding[i + 1].start_l1_queue_index,
+                    chunks_with_padding[i].start_l1_queue_index
+                );
+                assert_eq!(
+                    chunks_with_padding[i + 1].end_l1_queue_index,
+                    chunks_with_padding[i].end_l1_queue_index
+                );
             } else {
                 assert_eq!(
                     chunks_with_padding[i].post_state_root,
                     chunks_with_padding[i + 1].prev_state_root,
                 );
+                assert_eq!(
+                    chunks_with_padding[i].end_l1_queue_index,
+                    chunks_with_padding[i + 1].start_l1_queue_index,
+                );
             }
         }
 
@@ -168,9 +185,46 @@ impl<const N_SNARKS: usize> BatchHash<N_SNARKS> {
             number_of_valid_chunks,
             point_evaluation_assignments,
             versioned_hash,
+            parent_batch_hash: H256::zero(),
+            batch_header_encoding: BatchHeaderEncoding::default(),
         }
     }
 
+    /// Set the parent batch hash and the encoding of the batch header.
+    pub fn with_batch_header(
+        mut self,
+        parent_batch_hash: H256,
+        batch_header_encoding: BatchHeaderEncoding,
+    ) -> Self {
+        self.parent_batch_hash = parent_batch_hash;
+        self.batch_header_encoding = batch_header_encoding;
+        self
+    }
+
+    /// The header of the batch. It hashes the public input hashes of all the N_SNARKS chunks,
+    /// including the padding ones, so that its encoding has a fixed length.
+    pub fn batch_header(&self) -> BatchHeader {
+        BatchHeader {
+            parent_batch_hash: self.parent_batch_hash,
+            chunk_pi_hashes: self
+                .chunks_with_padding
+                .iter()
+                .map(|chunk| chunk.public_input_hash())
+                .collect(),
+            l1_message_count: self.end_l1_queue_index() - self.start_l1_queue_index(),
+        }
+    }
+
+    /// The encoding of the batch header.
+    pub fn batch_header_encoding(&self) -> &BatchHeaderEncoding {
+        &self.batch_header_encoding
+    }
+
+    /// The hash of the batch header.
+    pub fn batch_header_hash(&self) -> H256 {
+        self.batch_header().hash(&self.batch_header_encoding)
+    }
+
     /// Return the blob polynomial and its evaluation at challenge
     pub fn point_evaluation_assignments(&self) -> PointEvaluationAssignments {
         self.point_evaluation_assignments.clone()
@@ -258,13 +312,34 @@ impl<const N_SNARKS: usize> BatchHash<N_SNARKS> {
     }
 
     /// Compute the public inputs for this circuit, excluding the accumulator.
-    /// Content: the public_input_hash
+    /// Content: the public_input_hash, the L1 message queue index before and after the batch,
+    /// the batch header hash
     pub(crate) fn instances_exclude_acc<F: Field>(&self) -> Vec<Vec<F>> {
         vec![self
             .public_input_hash
             .as_bytes()
             .iter()
             .map(|&x| F::from(x as u64))
+            .chain([
+                F::from(self.start_l1_queue_index()),
+                F::from(self.end_l1_queue_index()),
+            ])
+            .chain(
+                self.batch_header_hash()
+                    .as_bytes()
+                    .iter()
+                    .map(|&x| F::from(x as u64)),
+            )
             .collect()]
     }
+
+    /// The index of the L1 message queue before the first chunk of the batch
+    pub fn start_l1_queue_index(&self) -> u64 {
+        self.chunks_with_padding[0].start_l1_queue_index
+    }
+
+    /// The index of the L1 message queue after the last chunk of the batch
+    pub fn end_l1_queue_index(&self) -> u64 {
+        self.chunks_with_padding[N_SNARKS - 1].end_l1_queue_index
+    }
 }
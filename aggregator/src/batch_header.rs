@@ -0,0 +1,261 @@
+//! This module implements the hashing of a batch header with keccak. The layout of the header
+//! is given by a [`BatchHeaderEncoding`], so that the batch formats of different rollups can
+//! reuse the aggregation circuit.
+
+use eth_types::H256;
+use ethers_core::utils::keccak256;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, Value},
+    halo2curves::bn256::Fr,
+    plonk::Error,
+};
+use serde::{Deserialize, Serialize};
+use zkevm_circuits::util::Challenges;
+
+use crate::{RlcConfig, DIGEST_LEN};
+
+/// The number of bytes of the L1 message count that may be non-zero, so that the count
+/// accumulated from its bytes doesn't wrap around the field.
+const L1_MESSAGE_COUNT_MAX_BYTES: usize = 8;
+
+/// A field of a batch header.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BatchHeaderField {
+    /// Fixed bytes, e.g. the version of the encoding.
+    Constant(Vec<u8>),
+    /// The hash of the parent batch, 32 bytes.
+    ParentBatchHash,
+    /// The public input hashes of the chunks in the batch, 32 bytes each.
+    ChunkPiHashes,
+    /// The number of L1 messages in the batch, as `n_bytes` big-endian bytes.
+    L1MessageCount {
+        /// The number of bytes of the count.
+        n_bytes: usize,
+    },
+}
+
+/// Describes how a batch header is encoded into the preimage of its hash: the fields are
+/// concatenated in order.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchHeaderEncoding {
+    /// The fields of the header, in the order of their encoding.
+    pub fields: Vec<BatchHeaderField>,
+}
+
+impl Default for BatchHeaderEncoding {
+    /// parent_batch_hash || chunk[0].pi_hash || ... || chunk[k-1].pi_hash || l1_message_count
+    fn default() -> Self {
+        Self {
+            fields: vec![
+                BatchHeaderField::ParentBatchHash,
+                BatchHeaderField::ChunkPiHashes,
+                BatchHeaderField::L1MessageCount { n_bytes: 8 },
+            ],
+        }
+    }
+}
+
+/// A batch header, hashed by the aggregation circuit.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchHeader {
+    /// The hash of the parent batch.
+    pub parent_batch_hash: H256,
+    /// The public input hashes of the chunks in the batch.
+    pub chunk_pi_hashes: Vec<H256>,
+    /// The number of L1 messages in the batch.
+    pub l1_message_count: u64,
+}
+
+impl BatchHeader {
+    /// Encode the header with `encoding`.
+    ///
+    /// Panics if the L1 message count doesn't fit in its encoded bytes.
+    pub fn encode(&self, encoding: &BatchHeaderEncoding) -> Vec<u8> {
+        let mut bytes = vec![];
+        for field in encoding.fields.iter() {
+            match field {
+                BatchHeaderField::Constant(constant) => bytes.extend_from_slice(constant),
+                BatchHeaderField::ParentBatchHash => {
+                    bytes.extend_from_slice(self.parent_batch_hash.as_bytes())
+                }
+                BatchHeaderField::ChunkPiHashes => {
+                    for chunk_pi_hash in self.chunk_pi_hashes.iter() {
+                        bytes.extend_from_slice(chunk_pi_hash.as_bytes());
+                    }
+                }
+                BatchHeaderField::L1MessageCount { n_bytes } => {
+                    let count = self.l1_message_count.to_be_bytes();
+                    let (high, low) = count.split_at(count.len().saturating_sub(*n_bytes));
+                    assert!(
+                        high.iter().all(|&byte| byte == 0),
+                        "l1 message count {} overflows {} bytes",
+                        self.l1_message_count,
+                        n_bytes
+                    );
+                    bytes.extend(std::iter::repeat(0).take(n_bytes.saturating_sub(low.len())));
+                    bytes.extend_from_slice(low);
+                }
+            }
+        }
+        bytes
+    }
+
+    /// The hash of the header encoded with `encoding`.
+    pub fn hash(&self, encoding: &BatchHeaderEncoding) -> H256 {
+        H256(keccak256(self.encode(encoding)))
+    }
+}
+
+/// Assign the encoded `header` and constrain its keccak digest, in the aggregation circuit.
+///
+/// The bytes of the chunk public input hashes are copied from `chunk_pi_hash_digests`, i.e. the
+/// digests checked against the instances of the aggregated snarks, and the L1 message count is
+/// the difference of the L1 message queue indexes after and before the batch. The constant
+/// bytes and the parent batch hash are only bound by the digest, which the verifier recomputes
+/// from the header it commits to. Returns the cells of the digest.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn assign_batch_header_hash(
+    rlc_config: &RlcConfig,
+    layouter: &mut impl Layouter<Fr>,
+    challenges: Challenges<Value<Fr>>,
+    header: &BatchHeader,
+    encoding: &BatchHeaderEncoding,
+    chunk_pi_hash_digests: &[&Vec<AssignedCell<Fr, Fr>>],
+    start_l1_queue_index: &AssignedCell<Fr, Fr>,
+    end_l1_queue_index: &AssignedCell<Fr, Fr>,
+) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+    assert_eq!(
+        header.chunk_pi_hashes.len(),
+        chunk_pi_hash_digests.len(),
+        "the header doesn't hash the pi hash of every chunk"
+    );
+    let preimage = header.encode(encoding);
+    let digest = keccak256(&preimage);
+
+    layouter.assign_region(
+        || "batch header hash",
+        |mut region| -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+            rlc_config.init(&mut region)?;
+            let mut offset = 0;
+
+            let keccak_input_challenge =
+                rlc_config.read_challenge1(&mut region, challenges, &mut offset)?;
+            let evm_word_challenge =
+                rlc_config.read_challenge2(&mut region, challenges, &mut offset)?;
+            let one = {
+                let one = rlc_config.load_private(&mut region, &Fr::one(), &mut offset)?;
+                let one_cell = rlc_config.one_cell(one.cell().region_index);
+                region.constrain_equal(one.cell(), one_cell)?;
+                one
+            };
+            let two_hundred_and_fifty_six = {
+                let cell = rlc_config.load_private(&mut region, &Fr::from(256), &mut offset)?;
+                let fixed_cell =
+                    rlc_config.pow_of_two_hundred_and_fifty_six_cell(cell.cell().region_index, 1);
+                region.constrain_equal(cell.cell(), fixed_cell)?;
+                cell
+            };
+
+            let mut preimage_bytes = preimage.iter();
+            let mut load_byte = |region: &mut Region<Fr>, offset: &mut usize| {
+                let byte = preimage_bytes
+                    .next()
+                    .expect("the preimage is encoded with the same fields");
+                rlc_config.load_private(region, &Fr::from(*byte as u64), offset)
+            };
+            let mut preimage_cells = vec![];
+            for field in encoding.fields.iter() {
+                match field {
+                    BatchHeaderField::Constant(constant) => {
+                        for _ in constant.iter() {
+                            preimage_cells.push(load_byte(&mut region, &mut offset)?);
+                        }
+                    }
+                    BatchHeaderField::ParentBatchHash => {
+                        for _ in 0..DIGEST_LEN {
+                            preimage_cells.push(load_byte(&mut region, &mut offset)?);
+                        }
+                    }
+                    BatchHeaderField::ChunkPiHashes => {
+                        for chunk_pi_hash_digest in chunk_pi_hash_digests.iter() {
+                            for digest_cell in chunk_pi_hash_digest.iter() {
+                                let byte = load_byte(&mut region, &mut offset)?;
+                                region.constrain_equal(byte.cell(), digest_cell.cell())?;
+                                preimage_cells.push(byte);
+                            }
+                        }
+                    }
+                    BatchHeaderField::L1MessageCount { n_bytes } => {
+                        // the leading bytes are zero, the others are the big-endian count
+                        let n_zero_bytes = n_bytes.saturating_sub(L1_MESSAGE_COUNT_MAX_BYTES);
+                        let mut count =
+                            rlc_config.load_private(&mut region, &Fr::zero(), &mut offset)?;
+                        rlc_config.enforce_zero(&mut region, &count)?;
+                        for i in 0..*n_bytes {
+                            let byte = load_byte(&mut region, &mut offset)?;
+                            if i < n_zero_bytes {
+                                rlc_config.enforce_zero(&mut region, &byte)?;
+                            } else {
+                                count = rlc_config.mul_add(
+                                    &mut region,
+                                    &count,
+                                    &two_hundred_and_fifty_six,
+                                    &byte,
+                                    &mut offset,
+                                )?;
+                            }
+                            preimage_cells.push(byte);
+                        }
+                        let expected_count = rlc_config.sub(
+                            &mut region,
+                            end_l1_queue_index,
+                            start_l1_queue_index,
+                            &mut offset,
+                        )?;
+                        region.constrain_equal(count.cell(), expected_count.cell())?;
+                    }
+                }
+            }
+            assert_eq!(preimage_cells.len(), preimage.len());
+
+            let digest_cells = digest
+                .iter()
+                .map(|&byte| {
+                    rlc_config.load_private(&mut region, &Fr::from(byte as u64), &mut offset)
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            // the length of the preimage is fixed by the encoding, count its bytes so that the
+            // lookup can't use a hash of a zero-prefixed preimage instead.
+            let mut preimage_len =
+                rlc_config.load_private(&mut region, &Fr::zero(), &mut offset)?;
+            rlc_config.enforce_zero(&mut region, &preimage_len)?;
+            for _ in preimage_cells.iter() {
+                preimage_len = rlc_config.add(&mut region, &preimage_len, &one, &mut offset)?;
+            }
+
+            // an empty header only occurs with an empty encoding, whose rlc is 0.
+            let preimage_rlc = if preimage_cells.is_empty() {
+                preimage_len.clone()
+            } else {
+                rlc_config.rlc(
+                    &mut region,
+                    &preimage_cells,
+                    &keccak_input_challenge,
+                    &mut offset,
+                )?
+            };
+            let digest_rlc =
+                rlc_config.rlc(&mut region, &digest_cells, &evm_word_challenge, &mut offset)?;
+            rlc_config.lookup_keccak_rlcs(
+                &mut region,
+                &preimage_rlc,
+                &digest_rlc,
+                &preimage_len,
+                &mut offset,
+            )?;
+
+            Ok(digest_cells)
+        },
+    )
+}
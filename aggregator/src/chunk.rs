@@ -15,6 +15,7 @@ use zkevm_circuits::witness::Block;
 /// - the data hash of this chunk
 /// - the tx data hash of this chunk
 /// - flattened L2 tx bytes
+/// - the indexes of the L1 message queue before and after this chunk
 /// - if the chunk is padded (en empty but valid chunk that is padded for aggregation)
 pub struct ChunkInfo {
     /// Chain identifier
@@ -30,6 +31,12 @@ pub struct ChunkInfo {
     /// Flattened L2 tx bytes (RLP-signed) in this chunk.
     #[serde(with = "base64")]
     pub tx_bytes: Vec<u8>,
+    /// the index of the L1 message queue before this chunk
+    #[serde(default)]
+    pub start_l1_queue_index: u64,
+    /// the index of the L1 message queue after this chunk
+    #[serde(default)]
+    pub end_l1_queue_index: u64,
     /// if the chunk is a padded chunk
     pub is_padding: bool,
 }
@@ -76,6 +83,10 @@ impl ChunkInfo {
                     .chain(b_ctx.base_fee.to_be_bytes())
                     .chain(b_ctx.gas_limit.to_be_bytes())
                     .chain(num_txs.to_be_bytes())
+                    .chain(b_ctx.prev_randao.to_be_bytes())
+                    .chain(b_ctx.withdrawals_root.to_fixed_bytes())
+                    .chain(b_ctx.blob_gas_used.to_be_bytes())
+                    .chain(b_ctx.excess_blob_gas.to_be_bytes())
             }))
             // Tx Hashes (excluding L2 txs)
             .chain(block.txs
@@ -112,6 +123,8 @@ impl ChunkInfo {
             withdraw_root: H256(block.withdraw_root.to_be_bytes()),
             data_hash,
             tx_bytes: tx_bytes.to_vec(),
+            start_l1_queue_index: block.start_l1_queue_index,
+            end_l1_queue_index: total_l1_popped,
             is_padding,
         }
     }
@@ -140,6 +153,8 @@ impl ChunkInfo {
         r.fill_bytes(&mut withdraw_root);
         let mut data_hash = [0u8; 32];
         r.fill_bytes(&mut data_hash);
+        let start_l1_queue_index = r.gen_range(0..1000);
+        let end_l1_queue_index = start_l1_queue_index + r.gen_range(0..10);
 
         const N_TXS: usize = 10;
         const N_SENDERS: usize = 2;
@@ -189,6 +204,8 @@ impl ChunkInfo {
             withdraw_root: withdraw_root.into(),
             data_hash: data_hash.into(),
             tx_bytes: txs.iter().flat_map(|tx| tx.rlp_unsigned()).collect(),
+            start_l1_queue_index,
+            end_l1_queue_index,
             is_padding: false,
         }
     }
@@ -207,6 +224,8 @@ impl ChunkInfo {
             withdraw_root: previous_chunk.withdraw_root,
             data_hash: previous_chunk.data_hash,
             tx_bytes: previous_chunk.tx_bytes.clone(),
+            start_l1_queue_index: previous_chunk.start_l1_queue_index,
+            end_l1_queue_index: previous_chunk.end_l1_queue_index,
             is_padding: true,
         }
     }
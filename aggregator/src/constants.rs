@@ -54,6 +54,16 @@ pub(crate) const BATCH_VH_OFFSET: usize = 200;
 /// An decomposed accumulator consists of 12 field elements
 pub(crate) const ACC_LEN: usize = 12;
 
+/// The Tx circuit exposes the indexes of the L1 message queue before and after
+/// the chunk, after the public input hash of the chunk. The aggregation circuit
+/// exposes the ones before and after the batch, after the public input hash of
+/// the batch.
+pub(crate) const L1_QUEUE_INDEX_LEN: usize = 2;
+
+/// The EVM circuit exposes a flag for the steps it does not prove, after the
+/// L1 message queue index
+pub(crate) const UNPROVEN_STEPS_LEN: usize = 1;
+
 /// number of limbs when decomposing a field element in the ECC chip
 pub(crate) const LIMBS: usize = 3;
 /// number of bits in each limb in the ECC chip
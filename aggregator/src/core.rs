@@ -1,4 +1,4 @@
-use std::iter::repeat;
+use std::iter::{once, repeat};
 
 use ark_std::{end_timer, start_timer};
 use ethers_core::utils::keccak256;
@@ -348,6 +348,7 @@ pub(crate) struct AssignedBatchHash {
 // - batch's data_hash length is 32 * number_of_valid_snarks
 // 8. batch data hash is correct w.r.t. its RLCs
 // 9. is_final_cells are set correctly
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn assign_batch_hashes<const N_SNARKS: usize>(
     keccak_config: &KeccakCircuitConfig<Fr>,
     rlc_config: &RlcConfig,
@@ -356,9 +357,15 @@ pub(crate) fn assign_batch_hashes<const N_SNARKS: usize>(
     chunks_are_valid: &[bool],
     num_valid_chunks: usize,
     preimages: &[Vec<u8>],
+    batch_header_preimage: &[u8],
 ) -> Result<AssignedBatchHash, Error> {
-    // assign the hash table
-    assign_keccak_table(keccak_config, layouter, challenges, preimages)?;
+    // assign the hash table, including the batch header which is hashed after the batch hashes
+    let keccak_preimages = preimages
+        .iter()
+        .cloned()
+        .chain(once(batch_header_preimage.to_vec()))
+        .collect::<Vec<_>>();
+    assign_keccak_table(keccak_config, layouter, challenges, &keccak_preimages)?;
 
     // 1. batch_data_hash digest is reused for public input hash
     // 3. batch_data_hash and chunk[i].pi_hash use a same chunk[i].data_hash when chunk[i] is not
@@ -869,7 +876,7 @@ pub(crate) fn conditional_constraints<const N_SNARKS: usize>(
 /// - for the next elements, if the element is 1, the previous element must also be 1
 ///
 /// Return a cell for number of valid snarks
-fn constrain_flags(
+pub(crate) fn constrain_flags(
     rlc_config: &RlcConfig,
     region: &mut Region<Fr>,
     chunk_are_valid: &[AssignedCell<Fr, Fr>],
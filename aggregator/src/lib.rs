@@ -5,6 +5,8 @@ mod aggregation;
 /// This module implements `Batch` related data types.
 /// A batch is a list of chunk.
 mod batch;
+/// This module implements the hashing of a batch header.
+mod batch_header;
 /// blob struct and constants
 mod blob;
 // This module implements `Chunk` related data types.
@@ -27,6 +29,8 @@ mod tests;
 pub use self::core::extract_proof_and_instances_with_pairing_check;
 pub use aggregation::*;
 pub use batch::BatchHash;
+pub use batch_header::{BatchHeader, BatchHeaderEncoding, BatchHeaderField};
+pub use blob::{BatchData, PointEvaluationAssignments};
 pub use chunk::ChunkInfo;
 pub use compression::*;
 pub use constants::MAX_AGG_SNARKS;
@@ -1,5 +1,7 @@
 mod aggregation;
+mod batch_header;
 mod blob;
+mod blob_commitment;
 mod compression;
 mod mock_chunk;
 mod rlc;
@@ -130,9 +130,47 @@ fn test_aggregation_circuit_variadic() {
     assert_eq!(prover1.permutation(), prover2.permutation());
 }
 
+#[ignore = "it takes too much time"]
+#[test]
+fn test_aggregation_circuit_l1_queue_index_mismatch() {
+    let k = 20;
+
+    // the second chunk doesn't pop the L1 message queue from the end index of the first one
+    let circuit: AggregationCircuit<MAX_AGG_SNARKS> =
+        build_aggregation_circuit_with_proven_chunks(3, k, |chunks| {
+            chunks[1].start_l1_queue_index += 1;
+        });
+    let instance = circuit.instances();
+    let mock_prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
+    assert!(mock_prover.verify_par().is_err());
+}
+
+#[ignore = "it takes too much time"]
+#[test]
+fn test_aggregation_circuit_batch_header_hash_mismatch() {
+    let k = 20;
+
+    let circuit: AggregationCircuit<MAX_AGG_SNARKS> = build_new_aggregation_circuit(3, k);
+    let mut instance = circuit.instances();
+    // the batch header hash is the last 32 public inputs
+    *instance[0].last_mut().unwrap() += Fr::one();
+    let mock_prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
+    assert!(mock_prover.verify_par().is_err());
+}
+
 fn build_new_aggregation_circuit<const N_SNARKS: usize>(
+    num_real_chunks: usize,
+    k: u32,
+) -> AggregationCircuit<N_SNARKS> {
+    build_aggregation_circuit_with_proven_chunks(num_real_chunks, k, |_| {})
+}
+
+/// Build an aggregation circuit whose snarks prove the real chunks as modified by
+/// `modify_proven_chunks`, while the batch is built from the unmodified ones.
+fn build_aggregation_circuit_with_proven_chunks<const N_SNARKS: usize>(
     num_real_chunks: usize,
     _k: u32,
+    modify_proven_chunks: impl FnOnce(&mut [ChunkInfo]),
 ) -> AggregationCircuit<N_SNARKS> {
     // inner circuit: Mock circuit
     let k0 = 8;
@@ -145,6 +183,12 @@ fn build_new_aggregation_circuit<const N_SNARKS: usize>(
         .collect_vec();
     for i in 0..num_real_chunks - 1 {
         chunks_without_padding[i + 1].prev_state_root = chunks_without_padding[i].post_state_root;
+        let num_l1_popped = chunks_without_padding[i + 1].end_l1_queue_index
+            - chunks_without_padding[i + 1].start_l1_queue_index;
+        chunks_without_padding[i + 1].start_l1_queue_index =
+            chunks_without_padding[i].end_l1_queue_index;
+        chunks_without_padding[i + 1].end_l1_queue_index =
+            chunks_without_padding[i].end_l1_queue_index + num_l1_popped;
     }
     let padded_chunk =
         ChunkInfo::mock_padded_chunk_info_for_testing(&chunks_without_padding[num_real_chunks - 1]);
@@ -158,10 +202,11 @@ fn build_new_aggregation_circuit<const N_SNARKS: usize>(
     // real chunks
     // ==========================
     let real_snarks = {
-        let circuits = chunks_with_padding
-            .iter()
-            .take(num_real_chunks)
-            .map(|chunk| MockChunkCircuit::new(true, chunk.clone()))
+        let mut proven_chunks = chunks_with_padding[..num_real_chunks].to_vec();
+        modify_proven_chunks(&mut proven_chunks);
+        let circuits = proven_chunks
+            .into_iter()
+            .map(|chunk| MockChunkCircuit::new(true, chunk))
             .collect_vec();
         circuits
             .iter()
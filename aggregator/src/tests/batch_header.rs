@@ -0,0 +1,75 @@
+use crate::{BatchHash, BatchHeader, BatchHeaderEncoding, BatchHeaderField, ChunkInfo};
+use ark_std::test_rng;
+use eth_types::H256;
+use halo2_proofs::halo2curves::bn256::Fr;
+
+fn batch_header() -> BatchHeader {
+    BatchHeader {
+        parent_batch_hash: H256::repeat_byte(0xab),
+        chunk_pi_hashes: (1..=3).map(H256::repeat_byte).collect(),
+        l1_message_count: 0x0102,
+    }
+}
+
+fn custom_encoding() -> BatchHeaderEncoding {
+    BatchHeaderEncoding {
+        fields: vec![
+            BatchHeaderField::Constant(vec![0x01]),
+            BatchHeaderField::L1MessageCount { n_bytes: 2 },
+            BatchHeaderField::ChunkPiHashes,
+            BatchHeaderField::ParentBatchHash,
+        ],
+    }
+}
+
+#[test]
+fn batch_header_encoding() {
+    let header = batch_header();
+
+    let bytes = header.encode(&BatchHeaderEncoding::default());
+    assert_eq!(bytes.len(), 32 + 3 * 32 + 8);
+    assert_eq!(&bytes[..32], header.parent_batch_hash.as_bytes());
+    assert_eq!(&bytes[32 + 3 * 32..], &[0, 0, 0, 0, 0, 0, 1, 2]);
+
+    let bytes = header.encode(&custom_encoding());
+    assert_eq!(bytes.len(), 1 + 2 + 3 * 32 + 32);
+    assert_eq!(&bytes[..3], &[1, 1, 2]);
+    assert_eq!(&bytes[3..35], header.chunk_pi_hashes[0].as_bytes());
+}
+
+#[test]
+#[should_panic]
+fn batch_header_encoding_overflow() {
+    BatchHeader {
+        l1_message_count: 1 << 16,
+        ..batch_header()
+    }
+    .encode(&custom_encoding());
+}
+
+#[test]
+fn batch_hash_batch_header() {
+    let mut rng = test_rng();
+    let chunk = ChunkInfo::mock_random_chunk_info_for_testing(&mut rng);
+    let padded_chunk = ChunkInfo::mock_padded_chunk_info_for_testing(&chunk);
+    let batch_hash =
+        BatchHash::<3>::construct(&[chunk.clone(), padded_chunk.clone(), padded_chunk])
+            .with_batch_header(H256::repeat_byte(0xab), custom_encoding());
+
+    // the header hashes the pi hashes of the padding chunks too
+    let header = batch_hash.batch_header();
+    assert_eq!(header.parent_batch_hash, H256::repeat_byte(0xab));
+    assert_eq!(header.chunk_pi_hashes, vec![chunk.public_input_hash(); 3]);
+    assert_eq!(
+        header.l1_message_count,
+        chunk.end_l1_queue_index - chunk.start_l1_queue_index
+    );
+
+    // the batch header hash follows the L1 message queue indexes in the public inputs
+    let instances = batch_hash.instances_exclude_acc::<Fr>();
+    let header_hash = header.hash(&custom_encoding());
+    assert_eq!(instances[0].len(), 32 + 2 + 32);
+    for (instance, byte) in instances[0][32 + 2..].iter().zip(header_hash.as_bytes()) {
+        assert_eq!(*instance, Fr::from(*byte as u64));
+    }
+}
@@ -0,0 +1,32 @@
+use crate::{BatchData, BlobCommitmentCircuit, MAX_AGG_SNARKS};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use snark_verifier_sdk::CircuitExt;
+
+fn blob_commitment_circuit() -> BlobCommitmentCircuit<MAX_AGG_SNARKS> {
+    BlobCommitmentCircuit::new(BatchData::from(&vec![
+        vec![3, 100, 24, 30],
+        vec![],
+        vec![100; 300],
+        vec![200; 20],
+    ]))
+}
+
+#[test]
+fn blob_commitment_circuit_completeness() {
+    let circuit = blob_commitment_circuit();
+    let instances = circuit.instances();
+    let mock_prover =
+        MockProver::<Fr>::run(21, &circuit, instances).expect("failed to run mock prover");
+    assert_eq!(mock_prover.verify_par(), Ok(()));
+}
+
+#[test]
+fn blob_commitment_circuit_wrong_evaluation() {
+    let circuit = blob_commitment_circuit();
+    let mut instances = circuit.instances();
+    // the last element is the least-significant byte of y.
+    *instances[0].last_mut().unwrap() += Fr::one();
+    let mock_prover =
+        MockProver::<Fr>::run(21, &circuit, instances).expect("failed to run mock prover");
+    assert!(mock_prover.verify_par().is_err());
+}
@@ -12,7 +12,7 @@ use snark_verifier_sdk::CircuitExt;
 use zkevm_circuits::{table::KeccakTable, util::Challenges};
 
 use crate::{
-    constants::{ACC_LEN, DIGEST_LEN},
+    constants::{ACC_LEN, DIGEST_LEN, L1_QUEUE_INDEX_LEN, UNPROVEN_STEPS_LEN},
     ChunkInfo, RlcConfig, LOG_DEGREE,
 };
 
@@ -24,6 +24,8 @@ pub struct MockConfig {
     /// Instance for public input; stores
     /// - accumulator from aggregation (12 elements); if not fresh
     /// - batch_public_input_hash (32 elements)
+    /// - L1 message queue indexes before and after the chunk (2 elements)
+    /// - unproven steps flag (1 element)
     pub(crate) instance: Column<Instance>,
 }
 
@@ -31,8 +33,10 @@ pub struct MockConfig {
 /// A mock chunk circuit
 ///
 /// This mock chunk circuit simulates a zkEVM circuit.
-/// It's public inputs consists of 32 elements:
+/// It's public inputs consists of 35 elements:
 /// - public input hash
+/// - L1 message queue indexes before and after the chunk
+/// - unproven steps flag, always 0
 pub(crate) struct MockChunkCircuit {
     // This circuit has an accumulator if it has already gone through compression
     pub(crate) has_accumulator: bool,
@@ -108,15 +112,10 @@ impl Circuit<Fr> for MockChunkCircuit {
                 }
                 let mut cells = vec![];
                 let mut index = 0;
-                let acc_len = if self.has_accumulator { ACC_LEN } else { 0 };
-
-                for byte in iter::repeat(0)
-                    .take(acc_len)
-                    .chain(self.chunk.public_input_hash().as_bytes().iter().copied())
-                {
+                for value in self.instances()[0].iter() {
                     let cell = config
                         .rlc_config
-                        .load_private(&mut region, &Fr::from(byte as u64), &mut index)
+                        .load_private(&mut region, value, &mut index)
                         .unwrap();
                     cells.push(cell)
                 }
@@ -133,19 +132,32 @@ impl Circuit<Fr> for MockChunkCircuit {
 }
 
 impl CircuitExt<Fr> for MockChunkCircuit {
-    /// 32 elements from digest
+    /// 32 elements from digest, the L1 message queue indexes and the unproven
+    /// steps flag
     fn num_instance(&self) -> Vec<usize> {
         let acc_len = if self.has_accumulator { ACC_LEN } else { 0 };
-        vec![DIGEST_LEN + acc_len]
+        vec![DIGEST_LEN + L1_QUEUE_INDEX_LEN + UNPROVEN_STEPS_LEN + acc_len]
     }
 
-    /// return vec![acc | public input hash]
+    /// return vec![acc | public input hash | l1 queue indexes | unproven steps flag]
     fn instances(&self) -> Vec<Vec<Fr>> {
         let acc_len = if self.has_accumulator { ACC_LEN } else { 0 };
+        let l1_queue_indexes = [
+            self.chunk.start_l1_queue_index,
+            self.chunk.end_l1_queue_index,
+        ];
         vec![iter::repeat(0)
             .take(acc_len)
-            .chain(self.chunk.public_input_hash().as_bytes().iter().copied())
-            .map(|x| Fr::from(x as u64))
+            .chain(
+                self.chunk
+                    .public_input_hash()
+                    .as_bytes()
+                    .iter()
+                    .map(|&byte| byte as u64),
+            )
+            .chain(l1_queue_indexes)
+            .chain(iter::repeat(0).take(UNPROVEN_STEPS_LEN))
+            .map(Fr::from)
             .collect()]
     }
 }
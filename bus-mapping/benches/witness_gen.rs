@@ -0,0 +1,242 @@
+//! Witness generation throughput per opcode family.
+//!
+//! Every family runs a synthetic transaction which repeats a few opcodes of
+//! the family many times, and measures `CircuitInputBuilder::handle_block`
+//! on its trace. The traces are generated once, before measuring.
+//!
+//! On top of the criterion reports, a JSON baseline can be saved and compared
+//! against locally, without relying on the criterion output directory:
+//!
+//! ```sh
+//! WITNESS_GEN_SAVE_BASELINE=baseline.json cargo bench --bench witness_gen
+//! WITNESS_GEN_BASELINE=baseline.json cargo bench --bench witness_gen
+//! ```
+
+use bus_mapping::mock::BlockData;
+use criterion::{criterion_group, BatchSize, Criterion, Throughput};
+use eth_types::{bytecode, geth_types::GethData, Address, Bytecode, Word};
+use mock::{eth, TestContext, MOCK_ACCOUNTS};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    time::{Duration, Instant},
+};
+
+/// Number of times the opcodes of a family are repeated in its transaction.
+const REPETITIONS: usize = 100;
+/// Number of runs of each family when measuring the JSON baseline.
+const BASELINE_RUNS: usize = 10;
+
+/// Init code which deploys an empty contract.
+const EMPTY_INIT_CODE: [u8; 5] = [0x60, 0x00, 0x60, 0x00, 0xf3];
+
+/// A synthetic transaction exercising one opcode family.
+struct Family {
+    name: &'static str,
+    block: GethData,
+    steps: u64,
+}
+
+impl Family {
+    /// Trace `code`, run by the callee of the transaction. A contract which
+    /// returns immediately is deployed at `MOCK_ACCOUNTS[2]` for the calls.
+    fn new(name: &'static str, code: Bytecode) -> Self {
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(eth(10))
+                    .code(code);
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+                accs[2].address(MOCK_ACCOUNTS[2]).code(bytecode! {
+                    PUSH1(0)
+                    PUSH1(0)
+                    RETURN
+                });
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(10_000_000u64));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let steps = block
+            .geth_traces
+            .iter()
+            .map(|trace| trace.struct_logs.len() as u64)
+            .sum();
+
+        Self { name, block, steps }
+    }
+
+    fn handle_block(&self) {
+        let mut builder =
+            BlockData::new_from_geth_data(self.block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&self.block.eth_block, &self.block.geth_traces)
+            .unwrap();
+    }
+}
+
+fn repeat(f: impl Fn(&mut Bytecode, usize)) -> Bytecode {
+    let mut code = Bytecode::default();
+    for i in 0..REPETITIONS {
+        f(&mut code, i);
+    }
+    code.op_stop();
+    code
+}
+
+fn families() -> Vec<Family> {
+    vec![
+        Family::new(
+            "arithmetic",
+            repeat(|code, i| {
+                code.op_add(i, 3).op_pop();
+                code.op_mul(i, 3).op_pop();
+                code.op_sdiv(i, 3).op_pop();
+                code.op_exp(3, i).op_pop();
+            }),
+        ),
+        Family::new(
+            "memory",
+            repeat(|code, i| {
+                code.op_mstore(i * 32, i);
+                code.op_mload(i * 32).op_pop();
+                code.op_mstore8(i * 32 + 31, i);
+            }),
+        ),
+        Family::new(
+            "storage",
+            repeat(|code, i| {
+                code.op_sstore(i, i + 1);
+                code.op_sload(i).op_pop();
+            }),
+        ),
+        Family::new(
+            "call",
+            repeat(|code, _| {
+                code.op_call(0xffff, MOCK_ACCOUNTS[2], 0, 0, 0, 0, 0)
+                    .op_pop();
+            }),
+        ),
+        Family::new("create", {
+            let mut code = Bytecode::default();
+            code.op_mstore(0, Word::from_big_endian(&EMPTY_INIT_CODE));
+            code.append(&repeat(|code, _| {
+                code.op_create(0, 32 - EMPTY_INIT_CODE.len(), EMPTY_INIT_CODE.len())
+                    .op_pop();
+            }));
+            code
+        }),
+        Family::new(
+            "precompiles",
+            repeat(|code, i| {
+                code.op_mstore(0, i);
+                // identity
+                code.op_staticcall(0xffff, Address::from_low_u64_be(4), 0, 32, 32, 32)
+                    .op_pop();
+                // sha256
+                code.op_staticcall(0xffff, Address::from_low_u64_be(2), 0, 32, 32, 32)
+                    .op_pop();
+            }),
+        ),
+    ]
+}
+
+fn witness_gen_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("witness_gen");
+    for family in families() {
+        group.throughput(Throughput::Elements(family.steps));
+        group.bench_function(family.name, |b| {
+            b.iter_batched(
+                || BlockData::new_from_geth_data(family.block.clone()).new_circuit_input_builder(),
+                |mut builder| {
+                    builder
+                        .handle_block(&family.block.eth_block, &family.block.geth_traces)
+                        .unwrap()
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, witness_gen_benches);
+
+/// Measurement of one family in the JSON baseline.
+#[derive(Debug, Serialize, Deserialize)]
+struct BaselineEntry {
+    steps: u64,
+    median_ns: u128,
+    ns_per_step: f64,
+}
+
+fn measure_baseline() -> BTreeMap<String, BaselineEntry> {
+    families()
+        .into_iter()
+        .map(|family| {
+            let mut runs: Vec<Duration> = (0..BASELINE_RUNS)
+                .map(|_| {
+                    let start = Instant::now();
+                    family.handle_block();
+                    start.elapsed()
+                })
+                .collect();
+            runs.sort();
+            let median_ns = runs[runs.len() / 2].as_nanos();
+            let entry = BaselineEntry {
+                steps: family.steps,
+                median_ns,
+                ns_per_step: median_ns as f64 / family.steps as f64,
+            };
+            (family.name.to_string(), entry)
+        })
+        .collect()
+}
+
+fn compare_baseline(path: &str, current: &BTreeMap<String, BaselineEntry>) {
+    let baseline: BTreeMap<String, BaselineEntry> =
+        serde_json::from_slice(&fs::read(path).expect("read baseline")).expect("parse baseline");
+    println!("witness_gen baseline comparison against {path}:");
+    for (name, entry) in current {
+        match baseline.get(name) {
+            Some(base) => println!(
+                "{name:>12}: {:>10.1} ns/step, baseline {:>10.1} ns/step ({:+.1}%)",
+                entry.ns_per_step,
+                base.ns_per_step,
+                (entry.ns_per_step / base.ns_per_step - 1.0) * 100.0
+            ),
+            None => println!(
+                "{name:>12}: {:>10.1} ns/step, not in baseline",
+                entry.ns_per_step
+            ),
+        }
+    }
+}
+
+fn main() {
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+
+    let save_path = env::var("WITNESS_GEN_SAVE_BASELINE").ok();
+    let compare_path = env::var("WITNESS_GEN_BASELINE").ok();
+    if save_path.is_none() && compare_path.is_none() {
+        return;
+    }
+    let current = measure_baseline();
+    if let Some(path) = compare_path {
+        compare_baseline(&path, &current);
+    }
+    if let Some(path) = save_path {
+        fs::write(&path, serde_json::to_vec_pretty(&current).unwrap()).expect("write baseline");
+        println!("witness_gen baseline saved to {path}");
+    }
+}
@@ -2,54 +2,76 @@
 //! types from geth / web3 and outputs the circuit inputs.
 
 mod access;
+mod arena;
 mod block;
 mod builder_client;
 mod call;
 /// Curie hardfork
 pub mod curie;
+#[cfg(all(test, not(feature = "scroll")))]
+mod differential_tests;
+mod dry_run;
 mod execution;
 mod input_state_ref;
+#[cfg(test)]
+mod invariant_tests;
 #[cfg(feature = "scroll")]
 mod l2;
+mod stack_ops;
+mod trace_check;
 #[cfg(all(feature = "tracer-tests", feature = "enable-memory", test))]
 mod tracer_tests;
 mod transaction;
+mod unsupported;
 
 pub use self::block::Block;
 use crate::{
     error::Error,
-    evm::opcodes::{gen_associated_ops, gen_associated_steps},
-    operation::{self, CallContextField, Operation, RWCounter, StartOp, StorageOp, RW},
+    evm::opcodes::{gen_associated_ops, gen_associated_steps, is_supported_opcode},
+    operation::{
+        self, AccountField, CallContextField, OpEnum, PaddingOp, RWCounter, StartOp, StorageOp, RW,
+    },
 };
-pub use access::{Access, AccessSet, AccessValue, CodeSource};
-pub use block::{BlockContext, Blocks};
-pub use builder_client::{build_state_code_db, BuilderClient};
+pub use access::{
+    Access, AccessProvenance, AccessSet, AccessValue, CodeSource, FetchPolicy, PrestatePolicy,
+};
+pub use arena::WitnessArena;
+pub use block::{BlockContext, Blocks, BlocksCheckpoint};
+pub use builder_client::{build_state_code_db, BuilderCheckpoint, BuilderClient};
 pub use call::{Call, CallContext, CallKind};
 use core::fmt::Debug;
+pub use dry_run::BlockStats;
 use eth_types::{
     self,
-    evm_types::{GasCost, OpcodeId},
+    bytecode::Disassembly,
+    evm_types::{memory::MemoryRange, GasCost, OpcodeId},
     sign_types::get_dummy_tx,
     state_db::{CodeDB, StateDB},
-    EthBlock, GethExecTrace, Word, H256,
+    Address, EthBlock, GethExecTrace, ToBigEndian, Word, H256,
 };
 use ethers_core::utils::keccak256;
 pub use execution::{
     BigModExp, CopyAccessList, CopyBytes, CopyDataType, CopyEvent, CopyEventStepsBuilder, CopyStep,
-    EcAddOp, EcMulOp, EcPairingOp, EcPairingPair, ExecState, ExecStep, ExpEvent, ExpStep,
-    NumberOrHash, PrecompileEvent, PrecompileEvents, N_BYTES_PER_PAIR, N_PAIRING_PER_OP, SHA256,
+    CopySteps, EcAddOp, EcMulOp, EcPairingOp, EcPairingPair, ExecErrorContext, ExecState, ExecStep,
+    ExpEvent, ExpStep, NumberOrHash, PrecompileEvent, PrecompileEvents, N_BYTES_PER_PAIR,
+    N_PAIRING_PER_OP, SHA256,
 };
 pub use input_state_ref::CircuitInputStateRef;
 use itertools::Itertools;
 #[cfg(feature = "scroll")]
 use mpt_zktrie::state::ZktrieState;
-use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+pub use stack_ops::StackOpsBuilder;
+use std::collections::{BTreeMap, HashSet};
+pub use trace_check::{TraceCheckConfig, TraceCheckKind, TraceCheckLevel, TraceMismatch};
 pub use transaction::{
-    Transaction, TransactionContext, TxL1Fee, TX_L1_COMMIT_EXTRA_COST, TX_L1_FEE_PRECISION,
+    CallGasReport, L1FeeSchema, Transaction, TransactionContext, TxL1Fee, OP_L1_FEE_PRECISION,
+    TX_L1_COMMIT_EXTRA_COST, TX_L1_FEE_PRECISION,
 };
+pub use unsupported::{DowngradedTx, UnsupportedOpcodePolicy, UnsupportedReason};
 
 /// Setup parameters for ECC-related precompile calls.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct PrecompileEcParams {
     /// Maximum number of EcAdd ops supported in one block.
     pub ec_add: usize,
@@ -57,6 +79,14 @@ pub struct PrecompileEcParams {
     pub ec_mul: usize,
     /// Maximum number of EcPairing ops supported in one block.
     pub ec_pairing: usize,
+    /// Number of (G1, G2) pairs per EcPairing op, which is also the maximum number of pairs
+    /// accepted by one ecPairing call. Must be at least 2.
+    #[serde(default = "default_ec_pairing_pairs")]
+    pub ec_pairing_pairs: usize,
+}
+
+fn default_ec_pairing_pairs() -> usize {
+    N_PAIRING_PER_OP
 }
 
 impl Default for PrecompileEcParams {
@@ -65,16 +95,25 @@ impl Default for PrecompileEcParams {
             ec_add: 50,
             ec_mul: 50,
             ec_pairing: 2,
+            ec_pairing_pairs: N_PAIRING_PER_OP,
         }
     }
 }
 
+impl PrecompileEcParams {
+    /// Maximum number of input bytes accepted by one ecPairing call.
+    pub fn ec_pairing_max_input_len(&self) -> usize {
+        self.ec_pairing_pairs * N_BYTES_PER_PAIR
+    }
+}
+
 /// Circuit Setup Parameters
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct CircuitsParams {
     /// Maximum number of rw operations in the state circuit (RwTable length /
     /// number of rows). This must be at least the number of rw operations
-    /// + 1, in order to allocate at least a Start row.
+    /// + 1, in order to allocate the Start row. The remaining rows are
+    /// Padding rows.
     pub max_rws: usize,
     /// Maximum number of txs in the Tx Circuit
     pub max_txs: usize,
@@ -174,6 +213,17 @@ pub struct CircuitInputBuilder {
     pub mpt_init_state: Option<ZktrieState>,
 }
 
+/// Entries removed by [`CircuitInputBuilder::prune_witness`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    /// Number of codes removed from the CodeDB
+    pub codes: usize,
+    /// Total length of the codes removed from the CodeDB
+    pub code_bytes: usize,
+    /// Number of accounts removed from the StateDB
+    pub accounts: usize,
+}
+
 impl<'a> CircuitInputBuilder {
     /// Create a new CircuitInputBuilder from the given `eth_block` and
     /// `constants`.
@@ -227,26 +277,36 @@ impl<'a> CircuitInputBuilder {
         is_success: bool,
     ) -> Result<Transaction, Error> {
         let call_id = self.block_ctx.rwc.0;
+        let tx_index = eth_tx
+            .transaction_index
+            .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
+            .as_u64() as usize;
 
-        self.block_ctx.call_map.insert(
-            call_id,
-            (
-                eth_tx
-                    .transaction_index
-                    .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
-                    .as_u64() as usize,
-                0,
-            ),
-        );
+        self.block_ctx.call_map.insert(call_id, (tx_index, 0));
 
-        Transaction::new(
+        let mut tx = Transaction::new(
             call_id,
             self.block.chain_id(),
+            self.block.l1_fee_schema,
             &self.sdb,
             &mut self.code_db,
             eth_tx,
             is_success,
-        )
+        )?;
+        // Txs with the same call data share the call data section of the first
+        // one in the tx table.
+        if !tx.input.is_empty() {
+            let tx_id = tx_index + 1;
+            let call_data_tx_id = *self
+                .block
+                .call_data_tx_ids
+                .entry(H256(keccak256(&tx.input)))
+                .or_insert(tx_id);
+            if call_data_tx_id != tx_id {
+                tx.shared_call_data_tx_id = Some(call_data_tx_id);
+            }
+        }
+        Ok(tx)
     }
 
     /// Iterate over all generated CallContext RwCounterEndOfReversion
@@ -273,10 +333,92 @@ impl<'a> CircuitInputBuilder {
     /// make finalize actions on building, must called after
     /// all block trace have been input
     pub fn finalize_building(&mut self) -> Result<(), Error> {
+        for downgraded in &self.block.downgraded_txs {
+            log::warn!(
+                "{} tx {:?} of block {}: {:?}",
+                if downgraded.skipped {
+                    "skipped"
+                } else {
+                    "dummy gadget for"
+                },
+                downgraded.tx_hash,
+                downgraded.block_num,
+                downgraded.reason,
+            );
+        }
         self.set_value_ops_call_context_rwc_eor();
         self.set_end_block()
     }
 
+    /// Remove from the CodeDB the codes which are never executed, copied or
+    /// read by hash, and from the StateDB the accounts which are never read or
+    /// written, so that they don't enlarge the bytecode witness. Transactions
+    /// handled after the pruning could miss the removed entries, so this must
+    /// only be called once the last block of the chunk has been handled.
+    pub fn prune_witness(&mut self) -> PruneReport {
+        let container = &self.block.container;
+        let mut addresses: HashSet<Address> = container
+            .account
+            .iter()
+            .map(|op| op.op().address)
+            .chain(container.storage.iter().map(|op| op.op().address))
+            .chain(container.transient_storage.iter().map(|op| op.op().address))
+            .chain(
+                container
+                    .tx_access_list_account
+                    .iter()
+                    .map(|op| op.op().address),
+            )
+            .chain(
+                container
+                    .tx_access_list_account_storage
+                    .iter()
+                    .map(|op| op.op().address),
+            )
+            .collect();
+        let mut code_hashes: HashSet<H256> = container
+            .account
+            .iter()
+            .filter(|op| {
+                matches!(
+                    op.op().field,
+                    AccountField::CodeHash | AccountField::KeccakCodeHash
+                )
+            })
+            .flat_map(|op| [op.op().value, op.op().value_prev])
+            .map(|hash| H256::from(hash.to_be_bytes()))
+            .collect();
+        code_hashes.insert(CodeDB::empty_code_hash());
+        for tx in self.block.txs() {
+            addresses.insert(tx.from);
+            addresses.extend(tx.to);
+            code_hashes.extend(tx.calls().iter().map(|call| call.code_hash));
+        }
+        for event in &self.block.copy_events {
+            for (ty, id) in [
+                (event.src_type, &event.src_id),
+                (event.dst_type, &event.dst_id),
+            ] {
+                if let (CopyDataType::Bytecode, NumberOrHash::Hash(hash)) = (ty, id) {
+                    code_hashes.insert(*hash);
+                }
+            }
+        }
+
+        let mut report = PruneReport::default();
+        self.code_db.0.retain(|hash, code| {
+            let keep = code_hashes.contains(hash);
+            if !keep {
+                report.codes += 1;
+                report.code_bytes += code.len();
+            }
+            keep
+        });
+        report.accounts = self.sdb.retain_accounts(|addr, _| addresses.contains(addr));
+        log::debug!("pruned witness: {report:?}");
+        report
+    }
+
     /// Handle a block by handling each transaction to generate all the
     /// associated operations.
     pub fn handle_block(
@@ -288,12 +430,41 @@ impl<'a> CircuitInputBuilder {
         self.finalize_building()?;
         Ok(())
     }
+
+    /// Handle a block like [`Self::handle_block`], but only generate the
+    /// witness of the transactions for which `filter` returns true. The state
+    /// changes of the other transactions are still applied to the StateDB, so
+    /// the selected ones run against the same state as in the block. The
+    /// witness then starts from the state before the first selected
+    /// transaction, not from the state of the parent block.
+    pub fn handle_block_with_tx_filter(
+        &mut self,
+        eth_block: &EthBlock,
+        geth_traces: &[eth_types::GethExecTrace],
+        filter: impl Fn(&eth_types::Transaction, &eth_types::GethExecTrace) -> bool,
+    ) -> Result<(), Error> {
+        self.handle_block_inner_with_tx_filter(eth_block, geth_traces, filter)?;
+        self.finalize_building()?;
+        Ok(())
+    }
+
     /// Handle a block by handling each transaction to generate all the
     /// associated operations.
     pub fn handle_block_inner(
         &mut self,
         eth_block: &EthBlock,
         geth_traces: &[eth_types::GethExecTrace],
+    ) -> Result<(), Error> {
+        self.handle_block_inner_with_tx_filter(eth_block, geth_traces, |_, _| true)
+    }
+
+    /// Handle a block by generating the operations of the transactions
+    /// selected by `filter`, and only applying the state changes of the others.
+    pub fn handle_block_inner_with_tx_filter(
+        &mut self,
+        eth_block: &EthBlock,
+        geth_traces: &[eth_types::GethExecTrace],
+        filter: impl Fn(&eth_types::Transaction, &eth_types::GethExecTrace) -> bool,
     ) -> Result<(), Error> {
         // accumulates gas across all txs in the block
         log::info!(
@@ -301,7 +472,15 @@ impl<'a> CircuitInputBuilder {
             eth_block.number,
             eth_block.transactions.len()
         );
+        #[cfg(feature = "metrics")]
+        let rwc_start = self.block_ctx.rwc.0;
         for (tx_index, tx) in eth_block.transactions.iter().enumerate() {
+            let geth_trace = &geth_traces[tx_index];
+            if !filter(tx, geth_trace) {
+                log::info!("apply tx {:?} without witness", tx.hash);
+                self.apply_tx_without_witness(tx, geth_trace)?;
+                continue;
+            }
             let chunk_tx_idx = self.block.txs.len();
             if self.block.txs.len() >= self.block.circuits_params.max_txs {
                 log::error!(
@@ -313,7 +492,24 @@ impl<'a> CircuitInputBuilder {
                 );
                 return Err(Error::InternalError("tx num overflow"));
             }
-            let geth_trace = &geth_traces[tx_index];
+            if self.block.unsupported_opcode_policy == UnsupportedOpcodePolicy::SkipTx {
+                if let Some(step) = geth_trace.struct_logs.iter().find(|step| {
+                    step.error.is_none()
+                        && !is_supported_opcode(&self.block.opcode_registry, &step.op)
+                }) {
+                    log::warn!("skip tx {:?}: unsupported opcode {:?}", tx.hash, step.op);
+                    // the transactions which follow run on top of its state
+                    let reason = UnsupportedReason::Opcode(step.op);
+                    self.apply_tx_without_witness(tx, geth_trace)?;
+                    self.block.downgraded_txs.push(DowngradedTx {
+                        block_num: tx.block_number.unwrap_or_default().as_u64(),
+                        tx_hash: tx.hash,
+                        reason,
+                        skipped: true,
+                    });
+                    continue;
+                }
+            }
             log::info!(
                 "handling {}th tx(inner idx: {}): {:?} rwc {:?}, to: {:?}, input_len {:?}",
                 chunk_tx_idx,
@@ -326,7 +522,15 @@ impl<'a> CircuitInputBuilder {
             let mut tx = tx.clone();
             // Chunk can contain multi blocks, so transaction_index needs to be updated
             tx.transaction_index = Some(self.block.txs.len().into());
+            #[cfg(feature = "metrics")]
+            let tx_start = std::time::Instant::now();
             self.handle_tx(&tx, geth_trace)?;
+            #[cfg(feature = "metrics")]
+            {
+                crate::metrics::TX_HANDLE_SECONDS.observe(tx_start.elapsed().as_secs_f64());
+                crate::metrics::TXS_PROCESSED.inc();
+                crate::metrics::STEPS_PROCESSED.inc_by(geth_trace.struct_logs.len() as u64);
+            }
             log::debug!(
                 "after handle {}th tx: rwc {:?}, total gas {:?}",
                 chunk_tx_idx,
@@ -339,6 +543,38 @@ impl<'a> CircuitInputBuilder {
             "handle_block_inner, total gas {:?}",
             self.block_ctx.cumulative_gas_used
         );
+        #[cfg(feature = "metrics")]
+        crate::metrics::BLOCK_RW_OPS.observe((self.block_ctx.rwc.0 - rwc_start) as f64);
+        Ok(())
+    }
+
+    /// Apply the state changes of a transaction to the StateDB and CodeDB,
+    /// leaving the witness of the block unchanged.
+    fn apply_tx_without_witness(
+        &mut self,
+        eth_tx: &eth_types::Transaction,
+        geth_trace: &GethExecTrace,
+    ) -> Result<(), Error> {
+        let checkpoint = self.block.checkpoint();
+        let rwc = self.block_ctx.rwc;
+        // the transaction is not proven, so unsupported opcodes in it must not
+        // fail the build or be reported
+        let unsupported_opcode_policy = self.block.unsupported_opcode_policy;
+        self.block.unsupported_opcode_policy = UnsupportedOpcodePolicy::DummyGadget;
+        let tx_index = self.block.txs.len();
+        let mut tx = eth_tx.clone();
+        tx.transaction_index = Some(tx_index.into());
+        let result = self.handle_tx(&tx, geth_trace);
+        // only the gas used is kept in the block context, as it accounts for
+        // every transaction of the block
+        self.block.truncate(checkpoint);
+        self.block.unsupported_opcode_policy = unsupported_opcode_policy;
+        self.block_ctx.rwc = rwc;
+        self.block_ctx
+            .call_map
+            .retain(|_, (call_tx_index, _)| *call_tx_index < tx_index);
+        result?;
+        self.check_post_state(&geth_trace.account_after);
         Ok(())
     }
 
@@ -442,6 +678,7 @@ impl<'a> CircuitInputBuilder {
         log::debug!("tx_receipt num: {}", self.block.container.tx_receipt.len());
         log::debug!("tx_log num: {}", self.block.container.tx_log.len());
         log::debug!("start num: {}", self.block.container.start.len());
+        log::debug!("padding num: {}", self.block.container.padding.len());
     }
 
     /// Build the EndBlock step, fill needed rws like reading withdraw root
@@ -469,6 +706,11 @@ impl<'a> CircuitInputBuilder {
         let mut dummy_tx_ctx = TransactionContext::default();
         let mut state = self.state_ref(&mut dummy_tx, &mut dummy_tx_ctx);
 
+        // The EndBlock step is associated with the root call of the last tx,
+        // from which it reads the number of processed txs. A chunk without txs,
+        // e.g. made of empty blocks or of blocks whose txs are all skipped, has
+        // no call to read from: the EndBlock gadget then sees a rw_counter of 1
+        // and expects 0 txs, with the withdraw root read under tx id 0.
         let dummy_tx_id = state.block.txs.len();
         if let Some(call_id) = state.block.txs.last().map(|tx| tx.calls[0].call_id) {
             state.call_context_read(
@@ -507,15 +749,18 @@ impl<'a> CircuitInputBuilder {
             ),
         )?;
 
-        let mut push_op = |step: &mut ExecStep, rwc: RWCounter, rw: RW, op: StartOp| {
-            let op_ref = state.block.container.insert(Operation::new(rwc, rw, op));
+        let mut push_op = |step: &mut ExecStep, rwc: RWCounter, op: OpEnum| {
+            let op_ref = state
+                .block
+                .container
+                .insert_op_enum(rwc, RW::READ, false, op);
             step.bus_mapping_instance.push(op_ref);
         };
 
         let total_rws = state.block_ctx.rwc.0 - 1;
-        // 2 here means we need at least 2 StartOp in state circuit.
+        // 2 here means we need a Start and a Padding row in state circuit.
         let max_rws = if max_rws == 0 { total_rws + 2 } else { max_rws };
-        // We need at least 1 extra Start row
+        // We need 1 extra Start row
         #[allow(clippy::int_plus_one)]
         {
             if total_rws + 1 > max_rws {
@@ -529,13 +774,21 @@ impl<'a> CircuitInputBuilder {
                 }
             };
         }
-        push_op(&mut end_block_step, RWCounter(1), RW::READ, StartOp {});
-        push_op(
-            &mut end_block_step,
-            RWCounter(max_rws - total_rws),
-            RW::READ,
-            StartOp {},
-        );
+        push_op(&mut end_block_step, RWCounter(1), OpEnum::Start(StartOp {}));
+        // The rest of the table is filled with Padding rows, with rw_counter from
+        // total_rws + 1 to max_rws - 1.
+        if total_rws + 1 < max_rws {
+            push_op(
+                &mut end_block_step,
+                RWCounter(total_rws + 1),
+                OpEnum::Padding(PaddingOp {}),
+            );
+            push_op(
+                &mut end_block_step,
+                RWCounter(max_rws - 1),
+                OpEnum::Padding(PaddingOp {}),
+            );
+        }
 
         self.block.withdraw_root = withdraw_root;
         self.block.prev_withdraw_root = withdraw_root_before;
@@ -688,11 +941,27 @@ impl<'a> CircuitInputBuilder {
                 state_ref.call(),
                 state_ref.tx.calls()
             );
-            let exec_steps = gen_associated_ops(
+            let exec_steps = match gen_associated_ops(
                 &geth_step.op,
                 &mut state_ref,
                 &geth_trace.struct_logs[index..],
-            )?;
+            ) {
+                Ok(exec_steps) => exec_steps,
+                Err(err) => {
+                    let code = state_ref
+                        .call()
+                        .and_then(|call| state_ref.code(call.code_hash))
+                        .unwrap_or_default();
+                    let err = Error::StepError {
+                        pc: geth_step.pc.0,
+                        op: geth_step.op,
+                        context: Disassembly::new(&code).context(geth_step.pc.0, 8),
+                        source: Box::new(err),
+                    };
+                    log::error!("{err}");
+                    return Err(err);
+                }
+            };
             tx.steps_mut().extend(exec_steps);
         }
 
@@ -747,13 +1016,13 @@ pub fn get_create_init_code(call_ctx: &CallContext) -> Result<Vec<u8>, Error> {
     let offset = call_ctx.stack.nth_last(1)?.low_u64() as usize;
     let length = call_ctx.stack.nth_last(2)?.as_usize();
 
-    let mem_len = call_ctx.memory.0.len();
-    let mut result = vec![0u8; length];
-    if length > 0 && offset < mem_len {
-        let offset_end = offset.checked_add(length).unwrap().min(mem_len);
-        let copy_len = offset_end - offset;
-        result[..copy_len].copy_from_slice(&call_ctx.memory.0[offset..offset_end]);
-    }
+    let result = if length > 0 {
+        call_ctx
+            .memory
+            .read_chunk(MemoryRange::new_with_length(offset, length))
+    } else {
+        vec![]
+    };
     Ok(result)
 }
 
@@ -1,6 +1,11 @@
 use crate::operation::RW;
-use eth_types::{geth_types::GethData, Address, GethPrestateTrace, Word};
-use std::collections::{hash_map::Entry, HashMap, HashSet};
+#[cfg(feature = "enable-stack")]
+use eth_types::{evm_types::OpcodeId, GethExecTrace, ToAddress};
+use eth_types::{
+    geth_types::GethData, utils::is_precompiled, Address, EthBlock, GethPrestateTrace, Word,
+};
+use ethers_core::utils::get_contract_address;
+use std::collections::{hash_map::Entry, BTreeSet, HashMap, HashSet};
 
 /// State and Code Access with "keys/index" used in the access operation.
 #[derive(Debug, PartialEq, Eq)]
@@ -42,6 +47,89 @@ impl Access {
     }
 }
 
+/// Why an account is in an [`AccessSet`], from the most to the least
+/// specific.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AccessProvenance {
+    /// Created in the block, by a transaction or a CREATE/CREATE2
+    Created,
+    /// Precompiled contract
+    Precompile,
+    /// Coinbase of the block
+    Coinbase,
+    /// Recipient of a transaction
+    TxTo,
+    /// Sender of a transaction
+    TxFrom,
+    /// Touched by the execution, according to the traces
+    TraceTouched,
+}
+
+/// What is fetched of the state of an account before the block, from the
+/// most to the least inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FetchPolicy {
+    /// The account, its code and its accessed storage slots
+    Full,
+    /// The account only, without its code and storage
+    AccountOnly,
+    /// Nothing, the account being empty in the state
+    Skip,
+}
+
+/// [`FetchPolicy`] of the accounts of an [`AccessSet`] by provenance. An
+/// account with several provenances follows the most inclusive of their
+/// policies, so that an account needed in full for one reason isn't trimmed
+/// for another. [`AccessProvenance::TraceTouched`], which any account reached
+/// by the execution has, is only followed by the accounts without another
+/// provenance.
+///
+/// By default, the accounts created in the block and the precompiles are
+/// fetched without code and storage, which they can't have before the block,
+/// and the other accounts are fully fetched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrestatePolicy {
+    policies: HashMap<AccessProvenance, FetchPolicy>,
+}
+
+impl Default for PrestatePolicy {
+    fn default() -> Self {
+        Self {
+            policies: HashMap::from([
+                (AccessProvenance::Created, FetchPolicy::AccountOnly),
+                (AccessProvenance::Precompile, FetchPolicy::AccountOnly),
+            ]),
+        }
+    }
+}
+
+impl PrestatePolicy {
+    /// Set the policy of the accounts of `provenance`.
+    pub fn with(mut self, provenance: AccessProvenance, policy: FetchPolicy) -> Self {
+        self.policies.insert(provenance, policy);
+        self
+    }
+
+    /// Policy of the accounts of `provenance`.
+    pub fn get(&self, provenance: AccessProvenance) -> FetchPolicy {
+        self.policies
+            .get(&provenance)
+            .copied()
+            .unwrap_or(FetchPolicy::Full)
+    }
+
+    /// Policy of an account with `provenances`.
+    pub fn resolve(&self, provenances: &BTreeSet<AccessProvenance>) -> FetchPolicy {
+        provenances
+            .iter()
+            .filter(|provenance| **provenance != AccessProvenance::TraceTouched)
+            .map(|provenance| self.get(*provenance))
+            .min()
+            .or_else(|| provenances.first().map(|provenance| self.get(*provenance)))
+            .unwrap_or(FetchPolicy::Full)
+    }
+}
+
 /// State and Code Access set.
 #[derive(Debug, PartialEq, Eq, Default)]
 pub struct AccessSet {
@@ -49,16 +137,20 @@ pub struct AccessSet {
     pub state: HashMap<Address, HashSet<Word>>,
     /// Set of accounts code
     pub code: HashSet<Address>,
+    /// Provenances of each account
+    pub provenance: HashMap<Address, BTreeSet<AccessProvenance>>,
 }
 
 impl AccessSet {
     #[inline(always)]
-    pub(crate) fn add_account(&mut self, address: Address) {
+    pub(crate) fn add_account(&mut self, address: Address, provenance: AccessProvenance) {
         self.state.entry(address).or_default();
+        self.tag(address, provenance);
     }
 
     #[inline(always)]
     pub(crate) fn add_storage(&mut self, address: Address, key: Word) {
+        self.tag(address, AccessProvenance::TraceTouched);
         match self.state.entry(address) {
             Entry::Vacant(entry) => {
                 let mut storage = HashSet::new();
@@ -72,25 +164,74 @@ impl AccessSet {
     }
 
     #[inline(always)]
-    pub(crate) fn add_code(&mut self, address: Address) {
-        self.state.entry(address).or_default();
+    pub(crate) fn add_code(&mut self, address: Address, provenance: AccessProvenance) {
+        self.add_account(address, provenance);
         self.code.insert(address);
     }
 
+    /// Record a provenance of `address`, the precompiles being tagged as such
+    /// whatever their provenance.
+    fn tag(&mut self, address: Address, provenance: AccessProvenance) {
+        let provenances = self.provenance.entry(address).or_default();
+        provenances.insert(provenance);
+        if is_precompiled(&address) {
+            provenances.insert(AccessProvenance::Precompile);
+        }
+    }
+
+    /// Provenances of `address`, empty if it isn't in the set.
+    pub fn provenances(&self, address: &Address) -> impl Iterator<Item = AccessProvenance> + '_ {
+        self.provenance.get(address).into_iter().flatten().copied()
+    }
+
+    /// What to fetch of the state of `address` before the block, under
+    /// `policy`.
+    pub fn fetch_policy(&self, address: &Address, policy: &PrestatePolicy) -> FetchPolicy {
+        self.provenance
+            .get(address)
+            .map_or(FetchPolicy::Full, |provenances| policy.resolve(provenances))
+    }
+
     pub(crate) fn extend_from_access(&mut self, list: Vec<Access>) {
         for access in list {
             match access.value {
-                AccessValue::Account { address } => self.add_account(address),
+                AccessValue::Account { address } => {
+                    self.add_account(address, AccessProvenance::TraceTouched)
+                }
                 AccessValue::Storage { address, key } => self.add_storage(address, key),
-                AccessValue::Code { address } => self.add_code(address),
+                AccessValue::Code { address } => {
+                    self.add_code(address, AccessProvenance::TraceTouched)
+                }
+            }
+        }
+    }
+
+    /// Add the coinbase, the senders and recipients of the transactions of
+    /// `eth_block`, and the contracts created by its contract creation
+    /// transactions.
+    ///
+    /// The contracts created by a CREATE or CREATE2 are only known from the
+    /// call traces.
+    pub(crate) fn extend_from_block(&mut self, eth_block: &EthBlock) {
+        self.add_account(
+            eth_block.author.unwrap_or_default(),
+            AccessProvenance::Coinbase,
+        );
+        for tx in eth_block.transactions.iter() {
+            self.add_account(tx.from, AccessProvenance::TxFrom);
+            match tx.to {
+                Some(to) => self.add_code(to, AccessProvenance::TxTo),
+                None => self.add_account(
+                    get_contract_address(tx.from, tx.nonce),
+                    AccessProvenance::Created,
+                ),
             }
         }
     }
 
     pub(crate) fn extend_from_traces(&mut self, traces: &HashMap<Address, GethPrestateTrace>) {
         for (address, trace) in traces.iter() {
-            self.add_account(*address);
-            self.add_code(*address);
+            self.add_code(*address, AccessProvenance::TraceTouched);
             if let Some(ref storage) = trace.storage {
                 for key in storage.keys() {
                     self.add_storage(*address, *key);
@@ -100,13 +241,84 @@ impl AccessSet {
     }
 
     pub(crate) fn extend(&mut self, other: &mut Self) {
-        self.state.extend(other.state.drain());
+        for (address, keys) in other.state.drain() {
+            self.state.entry(address).or_default().extend(keys);
+        }
         self.code.extend(other.code.drain());
+        for (address, provenances) in other.provenance.drain() {
+            self.provenance
+                .entry(address)
+                .or_default()
+                .extend(provenances);
+        }
+    }
+
+    /// Derive the state accessed by the transactions of `eth_block` from the
+    /// call traces and the stack of the struct logs of their `traces`,
+    /// without relying on the prestateTracer.
+    #[cfg(feature = "enable-stack")]
+    pub fn from_call_trace(
+        eth_block: &EthBlock,
+        traces: &[GethExecTrace],
+    ) -> Result<Self, eth_types::Error> {
+        let mut access_set = AccessSet::default();
+        access_set.extend_from_block(eth_block);
+        for trace in traces {
+            access_set.extend_from_call_trace(trace)?;
+        }
+        Ok(access_set)
+    }
+
+    #[cfg(feature = "enable-stack")]
+    fn extend_from_call_trace(&mut self, trace: &GethExecTrace) -> Result<(), eth_types::Error> {
+        // storage address of each call frame
+        let mut storage_addresses = Vec::new();
+        let mut callers = Vec::<Address>::new();
+        for (depth, frame) in trace.call_trace.iter() {
+            callers.truncate(depth as usize - 1);
+            let to = frame.to().unwrap_or_default();
+            let call_type = frame.call_type()?;
+            // a failed create may target an existing account, e.g. on an
+            // address collision
+            if call_type.is_create() && frame.error().is_none() {
+                self.add_account(to, AccessProvenance::Created);
+            } else {
+                self.add_code(to, AccessProvenance::TraceTouched);
+            }
+            let address = match call_type {
+                OpcodeId::DELEGATECALL | OpcodeId::CALLCODE => {
+                    callers.last().copied().unwrap_or_default()
+                }
+                _ => to,
+            };
+            storage_addresses.push(address);
+            callers.push(address);
+        }
+
+        let step_frames = trace.call_trace.reconcile(&trace.struct_logs)?;
+        for (step, frame) in trace.struct_logs.iter().zip(step_frames) {
+            let Ok(stack_top) = step.stack.last() else {
+                continue;
+            };
+            match step.op {
+                OpcodeId::SLOAD | OpcodeId::SSTORE => {
+                    self.add_storage(storage_addresses[frame], stack_top);
+                }
+                OpcodeId::BALANCE | OpcodeId::SELFDESTRUCT => {
+                    self.add_account(stack_top.to_address(), AccessProvenance::TraceTouched);
+                }
+                OpcodeId::EXTCODESIZE | OpcodeId::EXTCODECOPY | OpcodeId::EXTCODEHASH => {
+                    self.add_code(stack_top.to_address(), AccessProvenance::TraceTouched);
+                }
+                _ => {}
+            }
+        }
+        Ok(())
     }
 
     pub(crate) fn from_geth_data(geth_data: &GethData) -> Self {
         let mut access_set = AccessSet::default();
-        access_set.add_account(geth_data.eth_block.author.unwrap());
+        access_set.extend_from_block(&geth_data.eth_block);
         for trace in geth_data.geth_traces.iter() {
             access_set.extend_from_traces(&trace.prestate);
         }
@@ -130,3 +342,169 @@ impl Default for CodeSource {
         Self::Tx
     }
 }
+
+#[cfg(all(test, feature = "enable-stack"))]
+mod tests {
+    use super::*;
+    use eth_types::{bytecode, Bytecode, ToWord};
+    use mock::{TestContext, MOCK_ACCOUNTS, MOCK_COINBASE};
+
+    #[test]
+    fn call_trace_access_covers_prestate() {
+        let code = bytecode! {
+            PUSH1(0x01)
+            SLOAD
+            PUSH1(0x02)
+            SSTORE
+            PUSH20(MOCK_ACCOUNTS[4].to_word())
+            BALANCE
+            POP
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+
+        let access_set = AccessSet::from_call_trace(&block.eth_block, &block.geth_traces).unwrap();
+        let contract = block.eth_block.transactions[0].to.unwrap();
+        assert_eq!(
+            access_set.state[&contract],
+            HashSet::from([Word::from(1), Word::from(2)])
+        );
+        assert!(access_set.code.contains(&contract));
+        assert!(access_set.state.contains_key(&MOCK_ACCOUNTS[4]));
+
+        for (address, keys) in AccessSet::from_geth_data(&block).state {
+            assert!(keys.is_subset(&access_set.state[&address]), "{address:?}");
+        }
+    }
+
+    #[test]
+    fn access_provenance() {
+        // call the identity precompile, then create an empty contract
+        let mut code = Bytecode::default();
+        code.op_call(0xffffu64, 4u64, 0u64, 0u64, 0u64, 0u64, 0u64)
+            .op_pop()
+            .op_create(0u64, 0u64, 0u64)
+            .op_pop()
+            .op_stop();
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let tx = &block.eth_block.transactions[0];
+        let access_set = AccessSet::from_call_trace(&block.eth_block, &block.geth_traces).unwrap();
+        let policy = PrestatePolicy::default();
+
+        let provenances = |address: &Address| access_set.provenances(address).collect::<Vec<_>>();
+        assert_eq!(provenances(&MOCK_COINBASE), [AccessProvenance::Coinbase]);
+        assert_eq!(provenances(&tx.from), [AccessProvenance::TxFrom]);
+        let contract = tx.to.unwrap();
+        assert_eq!(
+            provenances(&contract),
+            [AccessProvenance::TxTo, AccessProvenance::TraceTouched]
+        );
+        assert_eq!(
+            access_set.fetch_policy(&contract, &policy),
+            FetchPolicy::Full
+        );
+
+        let identity = Address::from_low_u64_be(4);
+        assert_eq!(
+            provenances(&identity),
+            [AccessProvenance::Precompile, AccessProvenance::TraceTouched]
+        );
+        assert_eq!(
+            access_set.fetch_policy(&identity, &policy),
+            FetchPolicy::AccountOnly
+        );
+
+        let created: Vec<_> = access_set
+            .provenance
+            .iter()
+            .filter(|(_, provenances)| provenances.contains(&AccessProvenance::Created))
+            .map(|(address, _)| *address)
+            .collect();
+        assert_eq!(created.len(), 1);
+        assert!(!access_set.code.contains(&created[0]));
+        assert_eq!(
+            access_set.fetch_policy(&created[0], &policy),
+            FetchPolicy::AccountOnly
+        );
+        let policy = policy.with(AccessProvenance::Created, FetchPolicy::Skip);
+        assert_eq!(
+            access_set.fetch_policy(&created[0], &policy),
+            FetchPolicy::Skip
+        );
+    }
+
+    #[test]
+    fn prestate_policy_resolve() {
+        use AccessProvenance::*;
+
+        let policy = PrestatePolicy::default();
+        let resolve = |policy: &PrestatePolicy, provenances: &[AccessProvenance]| {
+            policy.resolve(&provenances.iter().copied().collect())
+        };
+        assert_eq!(resolve(&policy, &[]), FetchPolicy::Full);
+        assert_eq!(resolve(&policy, &[TraceTouched]), FetchPolicy::Full);
+        assert_eq!(
+            resolve(&policy, &[Precompile, TraceTouched]),
+            FetchPolicy::AccountOnly
+        );
+        // the most inclusive policy wins over the most specific provenance
+        assert_eq!(
+            resolve(&policy, &[Created, TxTo, TraceTouched]),
+            FetchPolicy::Full
+        );
+
+        let policy = policy
+            .with(Created, FetchPolicy::Skip)
+            .with(TraceTouched, FetchPolicy::Skip);
+        assert_eq!(resolve(&policy, &[TraceTouched]), FetchPolicy::Skip);
+        assert_eq!(
+            resolve(&policy, &[Created, Precompile, TraceTouched]),
+            FetchPolicy::AccountOnly
+        );
+    }
+
+    #[test]
+    fn access_provenance_reverted_create() {
+        // the init code reverts, then the address it would have been
+        // deployed at is called
+        let init_code = bytecode! {
+            PUSH1(0x00)
+            PUSH1(0x00)
+            REVERT
+        }
+        .code();
+        let address = get_contract_address(MOCK_ACCOUNTS[0], 0u64);
+        let mut code = Bytecode::default();
+        code.op_mstore(0u64, Word::from_big_endian(&init_code))
+            .op_create(0u64, 32 - init_code.len(), init_code.len())
+            .op_pop()
+            .op_call(0xffffu64, address, 0u64, 0u64, 0u64, 0u64, 0u64)
+            .op_pop()
+            .op_stop();
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        // the create frame targets the address, and failed
+        assert!(block.geth_traces[0]
+            .call_trace
+            .iter()
+            .any(|(_, frame)| frame.call_type().unwrap() == OpcodeId::CREATE
+                && frame.to() == Some(address)
+                && frame.error().is_some()));
+        let access_set = AccessSet::from_call_trace(&block.eth_block, &block.geth_traces).unwrap();
+
+        assert_eq!(
+            access_set.provenances(&address).collect::<Vec<_>>(),
+            [AccessProvenance::TraceTouched]
+        );
+        assert!(access_set.code.contains(&address));
+        assert_eq!(
+            access_set.fetch_policy(&address, &PrestatePolicy::default()),
+            FetchPolicy::Full
+        );
+    }
+}
@@ -0,0 +1,365 @@
+//! Arena holding the large byte strings of the witness, such as the inputs of
+//! the SHA3 opcode, in a single contiguous buffer.
+//!
+//! With the `mmap-witness` feature, the buffer can be backed by a
+//! memory-mapped file, so that the OS can page it out instead of keeping the
+//! whole witness in memory while the circuits are being proven.
+
+#[cfg(feature = "mmap-witness")]
+use memmap2::MmapMut;
+use std::{fmt, io};
+#[cfg(feature = "mmap-witness")]
+use std::{
+    fs::{File, OpenOptions},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// Initial size of the file backing an arena.
+#[cfg(feature = "mmap-witness")]
+const MIN_FILE_CAPACITY: usize = 1 << 20;
+
+/// Append-only list of byte strings stored in a contiguous buffer.
+///
+/// The byte strings are borrowed from the arena, so they live as long as the
+/// [`Blocks`](super::Blocks) holding it. A clone of a file-backed arena shares
+/// its file until either of them is appended to, which then copies it to a new
+/// file in the same directory.
+#[derive(Clone, Default)]
+pub struct WitnessArena {
+    buf: ArenaBuf,
+    /// End offset of each byte string in the buffer
+    ends: Vec<usize>,
+}
+
+enum ArenaBuf {
+    Heap(Vec<u8>),
+    /// File shared by the clones of an arena, of which the first `len` bytes
+    /// belong to this one
+    #[cfg(feature = "mmap-witness")]
+    File {
+        file: Arc<FileBuf>,
+        len: usize,
+    },
+}
+
+impl Default for ArenaBuf {
+    fn default() -> Self {
+        Self::Heap(Vec::new())
+    }
+}
+
+impl Clone for ArenaBuf {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Heap(bytes) => Self::Heap(bytes.clone()),
+            #[cfg(feature = "mmap-witness")]
+            Self::File { file, len } => Self::File {
+                file: Arc::clone(file),
+                len: *len,
+            },
+        }
+    }
+}
+
+impl ArenaBuf {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Self::Heap(bytes) => bytes,
+            #[cfg(feature = "mmap-witness")]
+            Self::File { file, len } => &file.mmap[..*len],
+        }
+    }
+
+    fn truncate(&mut self, new_len: usize) {
+        match self {
+            Self::Heap(buf) => buf.truncate(new_len),
+            #[cfg(feature = "mmap-witness")]
+            Self::File { len, .. } => *len = (*len).min(new_len),
+        }
+    }
+
+    fn extend_from_slice(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match self {
+            Self::Heap(buf) => buf.extend_from_slice(bytes),
+            #[cfg(feature = "mmap-witness")]
+            Self::File { file, len } => {
+                let end = *len + bytes.len();
+                // the bytes past `len` may belong to a clone, so a shared file
+                // is copied before being written
+                if Arc::get_mut(file).is_none() {
+                    let mut copy = FileBuf::create(&file.dir, end)?;
+                    copy.mmap[..*len].copy_from_slice(&file.mmap[..*len]);
+                    *file = Arc::new(copy);
+                }
+                let file = Arc::get_mut(file).expect("the file is not shared");
+                file.reserve(end)?;
+                file.mmap[*len..end].copy_from_slice(bytes);
+                *len = end;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Buffer in a memory-mapped file, which is removed as soon as it's created
+/// so that it's only reachable through the mapping.
+#[cfg(feature = "mmap-witness")]
+struct FileBuf {
+    dir: PathBuf,
+    file: File,
+    mmap: MmapMut,
+}
+
+#[cfg(feature = "mmap-witness")]
+impl FileBuf {
+    fn create(dir: &Path, capacity: usize) -> io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = dir.join(format!(
+            "witness-arena-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        if let Err(e) = std::fs::remove_file(&path) {
+            log::warn!("witness arena file {path:?} can't be removed: {e}");
+        }
+        file.set_len(capacity.max(MIN_FILE_CAPACITY).next_power_of_two() as u64)?;
+        // SAFETY: the file was just created by us and is unlinked, so no other
+        // process modifies it while it's mapped.
+        let mmap = unsafe { MmapMut::map_mut(&file)? };
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file,
+            mmap,
+        })
+    }
+
+    /// Grow the file to hold at least `capacity` bytes.
+    fn reserve(&mut self, capacity: usize) -> io::Result<()> {
+        if capacity > self.mmap.len() {
+            self.file.set_len(capacity.next_power_of_two() as u64)?;
+            // SAFETY: see `create`.
+            self.mmap = unsafe { MmapMut::map_mut(&self.file)? };
+        }
+        Ok(())
+    }
+}
+
+impl WitnessArena {
+    /// Create an empty arena in memory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty arena backed by a memory-mapped file in `dir`.
+    #[cfg(feature = "mmap-witness")]
+    pub fn file_backed(dir: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            buf: ArenaBuf::File {
+                file: Arc::new(FileBuf::create(dir.as_ref(), MIN_FILE_CAPACITY)?),
+                len: 0,
+            },
+            ends: Vec::new(),
+        })
+    }
+
+    /// Create an empty arena backed like this one, by a file in the same
+    /// directory or in memory.
+    pub fn new_like(&self) -> io::Result<Self> {
+        match &self.buf {
+            ArenaBuf::Heap(_) => Ok(Self::new()),
+            #[cfg(feature = "mmap-witness")]
+            ArenaBuf::File { file, .. } => Self::file_backed(&file.dir),
+        }
+    }
+
+    /// Directory of the file backing the arena, if any.
+    #[cfg(feature = "mmap-witness")]
+    pub fn file_dir(&self) -> Option<&Path> {
+        match &self.buf {
+            ArenaBuf::Heap(_) => None,
+            ArenaBuf::File { file, .. } => Some(&file.dir),
+        }
+    }
+
+    /// Append a byte string, returning its index.
+    ///
+    /// Fails if a file-backed arena can't be grown, in which case the arena is
+    /// left unchanged.
+    pub fn push(&mut self, bytes: impl AsRef<[u8]>) -> io::Result<usize> {
+        self.buf.extend_from_slice(bytes.as_ref())?;
+        self.ends.push(self.buf.as_slice().len());
+        Ok(self.ends.len() - 1)
+    }
+
+    /// Append the byte strings of `iter`.
+    pub fn try_extend<T: AsRef<[u8]>>(
+        &mut self,
+        iter: impl IntoIterator<Item = T>,
+    ) -> io::Result<()> {
+        for bytes in iter {
+            self.push(bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Return the byte string at `index`.
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        let end = *self.ends.get(index)?;
+        let start = index.checked_sub(1).map_or(0, |prev| self.ends[prev]);
+        Some(&self.buf.as_slice()[start..end])
+    }
+
+    /// Iterate over the byte strings, in the order they were pushed.
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> + Clone + '_ {
+        (0..self.len()).map(move |index| self.get(index).expect("index in range"))
+    }
+
+    /// Number of byte strings.
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Return true if the arena holds no byte string.
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Total length of the byte strings.
+    pub fn total_bytes(&self) -> usize {
+        self.buf.as_slice().len()
+    }
+
+    /// Keep the first `len` byte strings, dropping the others.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len() {
+            return;
+        }
+        self.ends.truncate(len);
+        let total_bytes = self.ends.last().copied().unwrap_or_default();
+        self.buf.truncate(total_bytes);
+    }
+}
+
+impl fmt::Debug for WitnessArena {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WitnessArena")
+            .field("len", &self.len())
+            .field("total_bytes", &self.total_bytes())
+            .field("file_backed", &!matches!(self.buf, ArenaBuf::Heap(_)))
+            .finish()
+    }
+}
+
+impl PartialEq for WitnessArena {
+    fn eq(&self, other: &Self) -> bool {
+        self.ends == other.ends && self.buf.as_slice() == other.buf.as_slice()
+    }
+}
+
+impl Eq for WitnessArena {}
+
+/// Collect the byte strings in an arena in memory.
+impl<T: AsRef<[u8]>> FromIterator<T> for WitnessArena {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut bytes = Vec::new();
+        let ends = iter
+            .into_iter()
+            .map(|item| {
+                bytes.extend_from_slice(item.as_ref());
+                bytes.len()
+            })
+            .collect();
+        Self {
+            buf: ArenaBuf::Heap(bytes),
+            ends,
+        }
+    }
+}
+
+impl<T: AsRef<[u8]>> From<Vec<T>> for WitnessArena {
+    fn from(items: Vec<T>) -> Self {
+        items.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_arena(arena: &mut WitnessArena) {
+        assert!(arena.is_empty());
+        assert_eq!(arena.push([1u8, 2, 3]).unwrap(), 0);
+        assert_eq!(arena.push(Vec::<u8>::new()).unwrap(), 1);
+        assert_eq!(arena.push(vec![4u8; 100]).unwrap(), 2);
+
+        assert_eq!(arena.len(), 3);
+        assert_eq!(arena.total_bytes(), 103);
+        assert_eq!(arena.get(0), Some(&[1u8, 2, 3][..]));
+        assert_eq!(arena.get(1), Some(&[][..]));
+        assert_eq!(arena.get(2), Some(&[4u8; 100][..]));
+        assert_eq!(arena.get(3), None);
+        assert_eq!(
+            arena.iter().collect::<Vec<_>>(),
+            vec![&[1u8, 2, 3][..], &[][..], &[4u8; 100][..]]
+        );
+        assert_eq!(arena.clone(), *arena);
+
+        // a clone and the arena it was cloned from are appended to separately
+        let mut clone = arena.clone();
+        arena.truncate(1);
+        assert_eq!(clone.push([6u8, 7]).unwrap(), 3);
+        assert_eq!(arena.push([8u8]).unwrap(), 1);
+        assert_eq!(
+            clone.iter().collect::<Vec<_>>(),
+            vec![&[1u8, 2, 3][..], &[][..], &[4u8; 100][..], &[6u8, 7][..]]
+        );
+        assert_eq!(
+            arena.iter().collect::<Vec<_>>(),
+            vec![&[1u8, 2, 3][..], &[8u8][..]]
+        );
+        assert_eq!(arena.new_like().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn witness_arena_in_memory() {
+        check_arena(&mut WitnessArena::new());
+    }
+
+    #[test]
+    fn witness_arena_truncate() {
+        let mut arena: WitnessArena = [vec![1u8, 2, 3], vec![], vec![4u8; 100]]
+            .into_iter()
+            .collect();
+        arena.truncate(5);
+        assert_eq!(arena.len(), 3);
+        arena.truncate(1);
+        assert_eq!(arena.len(), 1);
+        assert_eq!(arena.total_bytes(), 3);
+        assert_eq!(arena.push([5u8]).unwrap(), 1);
+        assert_eq!(
+            arena.iter().collect::<Vec<_>>(),
+            vec![&[1u8, 2, 3][..], &[5u8][..]]
+        );
+    }
+
+    #[cfg(feature = "mmap-witness")]
+    #[test]
+    fn witness_arena_file_backed() {
+        let mut arena = WitnessArena::file_backed(std::env::temp_dir()).unwrap();
+        check_arena(&mut arena);
+        assert!(arena.new_like().unwrap().file_dir().is_some());
+        // grow past the initial size of the file
+        arena.push(vec![5u8; MIN_FILE_CAPACITY]).unwrap();
+        assert_eq!(arena.get(2), Some(&vec![5u8; MIN_FILE_CAPACITY][..]));
+        assert_eq!(arena.get(0), Some(&[1u8, 2, 3][..]));
+    }
+}
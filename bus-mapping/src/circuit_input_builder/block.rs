@@ -3,13 +3,15 @@
 use super::{
     execution::{ExecState, PrecompileEvent, PrecompileEvents},
     transaction::Transaction,
-    CircuitsParams, CopyEvent, ExecStep, ExpEvent,
+    CircuitsParams, CopyEvent, DowngradedTx, ExecStep, ExpEvent, L1FeeSchema, TraceCheckConfig,
+    TraceMismatch, UnsupportedOpcodePolicy, WitnessArena,
 };
 use crate::{
+    evm::OpcodeRegistry,
     operation::{OperationContainer, RWCounter},
     Error,
 };
-use eth_types::{Address, ToWord, Word, H256};
+use eth_types::{evm_types::ChainSpec, Address, ToWord, Word, H256, U64};
 use std::collections::{BTreeMap, HashMap};
 
 /// Context of a [`Block`] which can mutate in a [`Transaction`].
@@ -84,8 +86,10 @@ pub struct Block {
     pub number: Word,
     /// difficulty
     pub timestamp: Word,
-    /// gas limit
+    /// difficulty of the header, zero after the Merge
     pub difficulty: Word,
+    /// prevRandao (EIP-4399), the mix hash of the header
+    pub prev_randao: Word,
     /// base fee
     pub base_fee: Word,
     /// start l1 queue index
@@ -94,6 +98,13 @@ pub struct Block {
     pub parent_hash: H256,
     /// State root of this block
     pub state_root: H256,
+    /// Withdrawals root of this block (EIP-4895), zero before Shanghai
+    pub withdrawals_root: H256,
+    /// Blob gas used by the transactions of this block (EIP-4844), zero
+    /// before Cancun
+    pub blob_gas_used: u64,
+    /// Excess blob gas of this block (EIP-4844), zero before Cancun
+    pub excess_blob_gas: u64,
 }
 impl Block {
     /// Create a new block.
@@ -133,20 +144,44 @@ impl Block {
                 .low_u64()
                 .into(),
             timestamp: eth_block.timestamp,
-            difficulty: if eth_block.difficulty.is_zero() {
-                eth_block
-                    .mix_hash
-                    .unwrap_or_default()
-                    .to_fixed_bytes()
-                    .into()
-            } else {
-                eth_block.difficulty
-            },
+            difficulty: eth_block.difficulty,
+            prev_randao: eth_block
+                .mix_hash
+                .unwrap_or_default()
+                .to_fixed_bytes()
+                .into(),
             base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
             parent_hash: eth_block.parent_hash,
             state_root: eth_block.state_root,
+            withdrawals_root: eth_block.withdrawals_root.unwrap_or_default(),
+            blob_gas_used: header_u64_field(eth_block, "blobGasUsed")?,
+            excess_blob_gas: header_u64_field(eth_block, "excessBlobGas")?,
         })
     }
+
+    /// Value of the DIFFICULTY opcode, which returns prevRandao after the
+    /// Merge of the chain (EIP-4399).
+    pub fn difficulty_or_prev_randao(&self, chain_spec: &ChainSpec) -> Word {
+        if chain_spec.is_merge(self.number.as_u64()) {
+            self.prev_randao
+        } else {
+            self.difficulty
+        }
+    }
+}
+
+/// Read a header field which is not known to the ethers block type, and is
+/// left in its `other` fields. Missing fields default to zero.
+fn header_u64_field(
+    eth_block: &eth_types::Block<eth_types::Transaction>,
+    key: &str,
+) -> Result<u64, Error> {
+    eth_block
+        .other
+        .get_deserialized::<U64>(key)
+        .transpose()
+        .map(|value| value.unwrap_or_default().as_u64())
+        .map_err(|_| Error::EthTypeError(eth_types::Error::IncompleteBlock))
 }
 
 /// Circuit Input related to many blocks, or a `Chunk`.
@@ -164,10 +199,15 @@ pub struct Blocks {
     pub container: OperationContainer,
     /// Transactions contained in the block
     pub txs: Vec<Transaction>,
+    /// Id of the first tx holding each non-empty call data, keyed by the
+    /// keccak hash of the call data
+    pub call_data_tx_ids: HashMap<H256, usize>,
     /// Copy events in this block.
     pub copy_events: Vec<CopyEvent>,
+    /// Bytes of the copy events, see [`CopyBytes`](super::CopyBytes)
+    pub copy_bytes: WitnessArena,
     /// Inputs to the SHA3 opcode
-    pub sha3_inputs: Vec<Vec<u8>>,
+    pub sha3_inputs: WitnessArena,
     /// Block-wise steps
     pub block_steps: BlockSteps,
     /// Exponentiation events in the block.
@@ -180,6 +220,23 @@ pub struct Blocks {
     pub start_l1_queue_index: u64,
     /// IO to/from the precompiled contract calls.
     pub precompile_events: PrecompileEvents,
+    /// How opcodes and error states without circuit support are handled
+    pub unsupported_opcode_policy: UnsupportedOpcodePolicy,
+    /// Transactions handled in a degraded way because of unsupported opcodes
+    /// or error states
+    pub downgraded_txs: Vec<DowngradedTx>,
+    /// Strictness of the sanity checks of the reconstructed state against the
+    /// traces
+    pub trace_check: TraceCheckConfig,
+    /// Mismatches found by the trace sanity checks
+    pub trace_mismatches: Vec<TraceMismatch>,
+    /// How the L1 fee of the transactions is computed
+    pub l1_fee_schema: L1FeeSchema,
+    /// Call depth and stack limits of the chain, which must match the ones of
+    /// the EVM that produced the traces
+    pub chain_spec: ChainSpec,
+    /// Handlers of the custom opcodes of the chain
+    pub opcode_registry: OpcodeRegistry,
     /// circuit capacity counter
     copy_counter: usize,
 }
@@ -194,6 +251,23 @@ impl Blocks {
         }
     }
 
+    /// Move the large witness vectors, the bytes of the copy events and the
+    /// SHA3 inputs, to memory-mapped files in `dir`, so that they can be paged
+    /// out when the machine runs low on memory. Must be called before handling
+    /// the blocks to avoid copying the vectors.
+    #[cfg(feature = "mmap-witness")]
+    pub fn use_file_backed_witness(
+        &mut self,
+        dir: impl AsRef<std::path::Path>,
+    ) -> Result<(), Error> {
+        for arena in [&mut self.copy_bytes, &mut self.sha3_inputs] {
+            let mut file_backed = WitnessArena::file_backed(dir.as_ref())?;
+            file_backed.try_extend(arena.iter())?;
+            *arena = file_backed;
+        }
+        Ok(())
+    }
+
     /// Add a new block
     pub fn add_block(&mut self, block: Block) {
         log::debug!("add_block with number {}", block.number.as_u64());
@@ -249,12 +323,95 @@ impl Blocks {
         self.blocks.iter().next_back().map(|(k, _)| *k)
     }
 
+    /// Return the index of the L1 message queue after the L1 messages of the
+    /// chunk, whose nonce is their queue index. They must pop the queue in
+    /// order from `start_l1_queue_index`, possibly skipping up to
+    /// [`u16::MAX`] messages at once, which is constrained by the Tx Circuit.
+    pub fn end_l1_queue_index(&self) -> Result<u64, Error> {
+        let mut queue_index = self.start_l1_queue_index;
+        for tx in self.txs.iter().filter(|tx| tx.tx_type.is_l1_msg()) {
+            if tx.nonce < queue_index {
+                log::error!(
+                    "l1 msg {:?} with queue index {} popped after queue index {}",
+                    tx.hash,
+                    tx.nonce,
+                    queue_index
+                );
+                return Err(Error::InternalError("l1 msgs are not in queue order"));
+            }
+            if tx.nonce - queue_index > u64::from(u16::MAX) {
+                log::error!(
+                    "l1 msg {:?} with queue index {} skips the queue from queue index {}",
+                    tx.hash,
+                    tx.nonce,
+                    queue_index
+                );
+                return Err(Error::InternalError("l1 msgs skip too many queue indexes"));
+            }
+            queue_index = tx.nonce + 1;
+        }
+        Ok(queue_index)
+    }
+
     #[cfg(test)]
     pub fn txs_mut(&mut self) -> &mut Vec<Transaction> {
         &mut self.txs
     }
 }
 
+/// Lengths of the witness of [`Blocks`] taken by [`Blocks::checkpoint`], to
+/// drop the witness added afterwards with [`Blocks::truncate`].
+#[derive(Debug, Clone, Copy)]
+pub struct BlocksCheckpoint {
+    container: [usize; 13],
+    txs: usize,
+    copy_events: usize,
+    copy_counter: usize,
+    copy_bytes: usize,
+    sha3_inputs: usize,
+    exp_events: usize,
+    precompile_events: usize,
+    downgraded_txs: usize,
+    trace_mismatches: usize,
+}
+
+impl Blocks {
+    /// Take a checkpoint of the witness of the transactions handled so far.
+    pub fn checkpoint(&self) -> BlocksCheckpoint {
+        BlocksCheckpoint {
+            container: self.container.lens(),
+            txs: self.txs.len(),
+            copy_events: self.copy_events.len(),
+            copy_counter: self.copy_counter,
+            copy_bytes: self.copy_bytes.len(),
+            sha3_inputs: self.sha3_inputs.len(),
+            exp_events: self.exp_events.len(),
+            precompile_events: self.precompile_events.events.len(),
+            downgraded_txs: self.downgraded_txs.len(),
+            trace_mismatches: self.trace_mismatches.len(),
+        }
+    }
+
+    /// Drop the witness added after `checkpoint` was taken.
+    pub fn truncate(&mut self, checkpoint: BlocksCheckpoint) {
+        self.container.truncate(checkpoint.container);
+        self.txs.truncate(checkpoint.txs);
+        // the ids of the txs are their index + 1
+        self.call_data_tx_ids
+            .retain(|_, tx_id| *tx_id <= checkpoint.txs);
+        self.copy_events.truncate(checkpoint.copy_events);
+        self.copy_counter = checkpoint.copy_counter;
+        self.copy_bytes.truncate(checkpoint.copy_bytes);
+        self.sha3_inputs.truncate(checkpoint.sha3_inputs);
+        self.exp_events.truncate(checkpoint.exp_events);
+        self.precompile_events
+            .events
+            .truncate(checkpoint.precompile_events);
+        self.downgraded_txs.truncate(checkpoint.downgraded_txs);
+        self.trace_mismatches.truncate(checkpoint.trace_mismatches);
+    }
+}
+
 impl Blocks {
     /// Push a copy event to the block.
     pub fn add_copy_event(&mut self, event: CopyEvent) {
@@ -283,3 +440,333 @@ impl Blocks {
         self.precompile_events.events.push(event);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        l2_predeployed::message_queue::{ADDRESS as MESSAGE_QUEUE, WITHDRAW_TRIE_ROOT_SLOT},
+        mock::BlockData,
+        operation::{CallContextField, Target},
+    };
+    use eth_types::{
+        bytecode,
+        geth_types::{GethData, TxType},
+        state_db::CodeDB,
+    };
+    use mock::{
+        eth, test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS,
+    };
+
+    #[test]
+    fn block_cancun_header_fields() {
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! { STOP })
+            .unwrap()
+            .into();
+        let pre_cancun = block.eth_block;
+        let mut eth_block = pre_cancun.clone();
+        eth_block.withdrawals_root = Some(H256::repeat_byte(0xaa));
+        eth_block
+            .other
+            .insert("blobGasUsed".to_string(), serde_json::json!("0x20000"));
+        eth_block
+            .other
+            .insert("excessBlobGas".to_string(), serde_json::json!("0x40000"));
+
+        let block = Block::new(1, vec![], &eth_block).unwrap();
+        assert_eq!(block.withdrawals_root, H256::repeat_byte(0xaa));
+        assert_eq!(block.blob_gas_used, 0x20000);
+        assert_eq!(block.excess_blob_gas, 0x40000);
+
+        let block = Block::new(1, vec![], &pre_cancun).unwrap();
+        assert_eq!(block.withdrawals_root, H256::zero());
+        assert_eq!(block.blob_gas_used, 0);
+        assert_eq!(block.excess_blob_gas, 0);
+    }
+
+    #[test]
+    fn block_tx_filter_applies_skipped_state() {
+        // increments the counter in slot 0
+        let code = bytecode! {
+            PUSH1(0)
+            SLOAD
+            PUSH1(1)
+            ADD
+            PUSH1(0)
+            SSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<2, 2>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+                txs[1].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let contract = block.eth_block.transactions[0].to.unwrap();
+        let disputed = block.eth_block.transactions[1].hash;
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block_with_tx_filter(&block.eth_block, &block.geth_traces, |tx, _| {
+                tx.hash == disputed
+            })
+            .unwrap();
+
+        assert_eq!(builder.block.txs().len(), 1);
+        assert_eq!(builder.block.txs()[0].hash, disputed);
+        // the skipped transaction is not in the witness, but the selected one
+        // still ran on top of its state change
+        assert_eq!(
+            *builder.sdb.get_storage(&contract, &Word::zero()).1,
+            Word::from(2)
+        );
+    }
+
+    #[test]
+    fn blocks_truncate_to_checkpoint() {
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0)
+            SHA3
+            POP
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .input(vec![1u8, 2, 3].into());
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let checkpoint = builder.block.checkpoint();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        assert_eq!(builder.block.txs().len(), 1);
+        assert!(!builder.block.sha3_inputs.is_empty());
+        assert!(!builder.block.call_data_tx_ids.is_empty());
+
+        builder.block.truncate(checkpoint);
+        assert!(builder.block.txs().is_empty());
+        assert_eq!(builder.block.container.lens(), [0; 13]);
+        assert!(builder.block.sha3_inputs.is_empty());
+        assert!(builder.block.copy_events.is_empty());
+        assert!(builder.block.copy_bytes.is_empty());
+        assert!(builder.block.call_data_tx_ids.is_empty());
+        assert_eq!(builder.block.copy_counter, 0);
+    }
+
+    #[test]
+    fn block_prune_witness_drops_untouched_accounts() {
+        let code = bytecode! { PUSH1(0) PUSH1(0) SSTORE STOP };
+        let unused_code = bytecode! { PUSH1(1) STOP };
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(eth(10))
+                    .code(code.clone());
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(eth(10))
+                    .code(unused_code.clone());
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let report = builder.prune_witness();
+
+        assert_eq!(report.codes, 1);
+        assert_eq!(report.code_bytes, unused_code.code().len());
+        assert!(report.accounts >= 1);
+        assert!(builder.code_db.0.contains_key(&CodeDB::hash(&code.code())));
+        assert!(!builder
+            .code_db
+            .0
+            .contains_key(&CodeDB::hash(&unused_code.code())));
+        assert!(!builder.sdb.get_account(&MOCK_ACCOUNTS[2]).0);
+        assert!(builder.sdb.get_account(&MOCK_ACCOUNTS[0]).0);
+    }
+
+    /// Return the tx id under which the EndBlock step reads the withdraw root,
+    /// and the tx id it reads from the call context of the last tx, if any.
+    fn end_block_tx_ids(blocks: &Blocks) -> (usize, Option<Word>) {
+        let container = &blocks.container;
+        let mut withdraw_root_tx_id = None;
+        let mut call_context_tx_id = None;
+        for op_ref in &blocks.block_steps.end_block_step.bus_mapping_instance {
+            match op_ref.target() {
+                Target::Storage => {
+                    let op = container.storage[op_ref.as_usize()].op();
+                    if op.address == *MESSAGE_QUEUE && op.key == WITHDRAW_TRIE_ROOT_SLOT {
+                        withdraw_root_tx_id = Some(op.tx_id);
+                    }
+                }
+                Target::CallContext => {
+                    let op = container.call_context[op_ref.as_usize()].op();
+                    assert_eq!(op.field, CallContextField::TxId);
+                    call_context_tx_id = Some(op.value);
+                }
+                _ => {}
+            }
+        }
+        (withdraw_root_tx_id.unwrap(), call_context_tx_id)
+    }
+
+    #[test]
+    fn block_without_txs() {
+        let block: GethData = TestContext::<2, 0>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |_txs, _accs| {},
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        assert!(builder.block.txs().is_empty());
+        let block_steps = &builder.block.block_steps;
+        assert_eq!(block_steps.padding_step.rwc, RWCounter(1));
+        assert_eq!(block_steps.end_block_step.rwc, RWCounter(1));
+        assert_eq!(end_block_tx_ids(&builder.block), (0, None));
+        assert_eq!(builder.block.container.start.len(), 1);
+    }
+
+    #[test]
+    fn block_with_all_txs_skipped() {
+        let block: GethData = TestContext::<2, 2>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! { STOP }),
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+                txs[1].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block_with_tx_filter(&block.eth_block, &block.geth_traces, |_, _| false)
+            .unwrap();
+
+        // the block is proven like an empty one on top of the state of its txs
+        assert!(builder.block.txs().is_empty());
+        assert_eq!(builder.block.block_steps.end_block_step.rwc, RWCounter(1));
+        assert_eq!(end_block_tx_ids(&builder.block), (0, None));
+        assert_eq!(builder.sdb.get_account(&MOCK_ACCOUNTS[1]).1.nonce, 2.into());
+    }
+
+    #[test]
+    fn block_with_only_l1_msg_txs() {
+        let mut block: GethData = TestContext::<2, 2>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! { STOP }),
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+                txs[1].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        for tx in block.eth_block.transactions.iter_mut() {
+            tx.transaction_type = Some(0x7e.into());
+        }
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let txs = builder.block.txs();
+        assert_eq!(txs.len(), 2);
+        assert!(txs.iter().all(|tx| tx.tx_type.is_l1_msg()));
+        // the EndBlock step is still associated with the last tx
+        assert_eq!(
+            end_block_tx_ids(&builder.block),
+            (2, Some(Word::from(2u64)))
+        );
+    }
+
+    #[test]
+    fn block_l1_queue_index() {
+        let block: GethData = TestContext::<2, 3>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! { STOP }),
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+                txs[1].from(accs[1].address).to(accs[0].address);
+                txs[2].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        builder.block.start_l1_queue_index = 5;
+        assert_eq!(builder.block.end_l1_queue_index().unwrap(), 5);
+
+        // queue indexes 5, (skipped 6), 7 with an L2 tx in between
+        for (i, queue_index) in [(0, 5), (2, 7)] {
+            let tx = &mut builder.block.txs_mut()[i];
+            tx.tx_type = TxType::L1Msg;
+            tx.nonce = queue_index;
+        }
+        assert_eq!(builder.block.end_l1_queue_index().unwrap(), 8);
+
+        builder.block.txs_mut()[2].nonce = 5;
+        assert!(builder.block.end_l1_queue_index().is_err());
+        builder.block.start_l1_queue_index = 6;
+        builder.block.txs_mut()[2].nonce = 7;
+        assert!(builder.block.end_l1_queue_index().is_err());
+
+        // at most 2^16 - 1 skipped messages
+        builder.block.start_l1_queue_index = 0;
+        builder.block.txs_mut()[0].nonce = u64::from(u16::MAX);
+        builder.block.txs_mut()[2].nonce = u64::from(u16::MAX) + 1;
+        assert_eq!(
+            builder.block.end_l1_queue_index().unwrap(),
+            u64::from(u16::MAX) + 2
+        );
+        builder.block.txs_mut()[0].nonce = u64::from(u16::MAX) + 1;
+        builder.block.txs_mut()[2].nonce = u64::from(u16::MAX) + 2;
+        assert!(builder.block.end_l1_queue_index().is_err());
+    }
+}
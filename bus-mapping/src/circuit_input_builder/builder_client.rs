@@ -1,19 +1,35 @@
 use eth_types::{
     constants::SCROLL_COINBASE,
-    geth_types::{self, Account, BlockConstants},
+    evm_types::OpcodeId,
+    geth_types::{self, Account, BlockConstants, StateOverrides},
     state_db::{self, CodeDB, StateDB},
-    utils::hash_code_keccak,
-    Address, EthBlock, GethExecTrace, ToWord, Word, H256, KECCAK_CODE_HASH_EMPTY,
+    utils::{default_code_hasher, hash_code_keccak, CodeHasher},
+    Address, Bytes, EthBlock, GethExecTrace, ToWord, Word, H256, KECCAK_CODE_HASH_EMPTY,
 };
+use ethers_core::types::{NameOrAddress, TransactionRequest};
 use ethers_providers::JsonRpcClient;
 use external_tracer::TraceConfig;
 use hex::decode_to_slice;
 
-use super::{AccessSet, Block, Blocks, CircuitInputBuilder, CircuitsParams};
-use crate::{error::Error, rpc::GethClient};
+use super::{
+    AccessSet, Block, Blocks, CircuitInputBuilder, CircuitsParams, FetchPolicy, PrestatePolicy,
+};
+use crate::{
+    error::Error,
+    rpc::{BlockNumber, BlockOverrides, GethClient},
+};
 
-use std::str::FromStr;
-use std::{collections::HashMap, iter};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp,
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    iter,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 
 /// Struct that wraps a GethClient and contains methods to perform all the steps
 /// necessary to generate the circuit inputs for a block by querying geth for
@@ -22,12 +38,92 @@ pub struct BuilderClient<P: JsonRpcClient> {
     cli: GethClient<P>,
     chain_id: u64,
     circuits_params: CircuitsParams,
+    state_overrides: StateOverrides,
+    checkpoint: Option<(PathBuf, usize)>,
+    call_trace_access: bool,
+    prestate_policy: PrestatePolicy,
+    code_hasher: Arc<dyn CodeHasher>,
+}
+
+/// Snapshot of a multi blocks build taken at a transaction boundary, from
+/// which the build can be resumed with [`BuilderClient::resume_from`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuilderCheckpoint {
+    /// Block of the next transaction to handle
+    pub block_num: u64,
+    /// Index in its block of the next transaction to handle
+    pub tx_index: usize,
+    /// End (exclusive) of the block range being built
+    pub block_num_end: u64,
+    /// Accounts of the state after the handled transactions
+    pub accounts: HashMap<Address, state_db::Account>,
+    /// Accounts which are still empty but already touched
+    pub touched_accounts: HashSet<Address>,
+    /// Codes by code hash
+    pub codes: HashMap<H256, Bytes>,
+}
+
+impl BuilderCheckpoint {
+    fn new(
+        builder: &CircuitInputBuilder,
+        block_num: u64,
+        tx_index: usize,
+        block_num_end: u64,
+    ) -> Self {
+        Self {
+            block_num,
+            tx_index,
+            block_num_end,
+            accounts: builder.sdb.committed_accounts().clone(),
+            touched_accounts: builder.sdb.touched_accounts().clone(),
+            codes: builder
+                .code_db
+                .0
+                .iter()
+                .map(|(hash, code)| (*hash, code.clone().into()))
+                .collect(),
+        }
+    }
+
+    /// Load a checkpoint written during a build.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = File::open(path).map_err(Error::IoError)?;
+        serde_json::from_reader(BufReader::new(file)).map_err(Error::SerdeError)
+    }
+
+    /// Write the checkpoint to `path`, replacing the previous one only once
+    /// it is completely written.
+    fn save(&self, path: &Path) -> Result<(), Error> {
+        let tmp_path = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp_path).map_err(Error::IoError)?);
+        serde_json::to_writer(&mut writer, self).map_err(Error::SerdeError)?;
+        writer.flush().map_err(Error::IoError)?;
+        fs::rename(tmp_path, path).map_err(Error::IoError)
+    }
+
+    fn into_state_code_db(self, code_hasher: Arc<dyn CodeHasher>) -> (StateDB, CodeDB) {
+        let sdb = StateDB::from_committed(self.accounts, self.touched_accounts);
+        let mut code_db = CodeDB::with_hasher(code_hasher);
+        for (hash, code) in self.codes {
+            code_db.insert_with_hash(hash, code.to_vec());
+        }
+        (sdb, code_db)
+    }
 }
 
-/// Build a partial StateDB from step 3
+/// Build a partial StateDB from step 3, with the codes hashed by the
+/// [`default_code_hasher`](eth_types::utils::default_code_hasher).
 pub fn build_state_code_db(
     proofs: Vec<eth_types::EIP1186ProofResponse>,
     codes: HashMap<Address, Vec<u8>>,
+) -> (StateDB, CodeDB) {
+    build_state_code_db_with(proofs, codes, CodeDB::new())
+}
+
+fn build_state_code_db_with(
+    proofs: Vec<eth_types::EIP1186ProofResponse>,
+    codes: HashMap<Address, Vec<u8>>,
+    mut code_db: CodeDB,
 ) -> (StateDB, CodeDB) {
     let mut sdb = StateDB::new();
     for proof in proofs {
@@ -48,7 +144,6 @@ pub fn build_state_code_db(
         )
     }
 
-    let mut code_db = CodeDB::new();
     for (_address, code) in codes {
         code_db.insert(code.clone());
     }
@@ -67,9 +162,71 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             cli: client,
             chain_id,
             circuits_params,
+            state_overrides: StateOverrides::default(),
+            checkpoint: None,
+            call_trace_access: false,
+            prestate_policy: PrestatePolicy::default(),
+            code_hasher: default_code_hasher(),
         })
     }
 
+    /// Write a [`BuilderCheckpoint`] to `path` every `interval` transactions
+    /// handled by the multi blocks builds.
+    pub fn with_checkpoint(mut self, path: impl Into<PathBuf>, interval: usize) -> Self {
+        assert_ne!(interval, 0, "checkpoint interval must be positive");
+        self.checkpoint = Some((path.into(), interval));
+        self
+    }
+
+    /// Set the state overrides applied on top of the fetched prestate.
+    ///
+    /// Overriding the state of a block or a transaction requires the
+    /// `retrace-tx` feature, so that it is re-executed against the
+    /// overridden state instead of using the traces of the node.
+    pub fn with_state_overrides(mut self, state_overrides: StateOverrides) -> Self {
+        self.state_overrides = state_overrides;
+        self
+    }
+
+    /// Derive the state accesses of the blocks from the callTracer and the
+    /// struct logs instead of the prestateTracer, and fetch the state with
+    /// `eth_getProof` and `eth_getCode`, for nodes which don't ship the
+    /// prestateTracer.
+    ///
+    /// Only [`Self::gen_inputs`] and [`Self::gen_inputs_multi_blocks`] are
+    /// supported, without the `retrace-tx` feature, and the struct logs must
+    /// include the stack (`enable-stack` feature).
+    pub fn with_call_trace_access(mut self, call_trace_access: bool) -> Self {
+        self.call_trace_access = call_trace_access;
+        self
+    }
+
+    /// Set what is fetched of the accessed accounts by [`Self::get_state`],
+    /// depending on their provenance.
+    pub fn with_prestate_policy(mut self, prestate_policy: PrestatePolicy) -> Self {
+        self.prestate_policy = prestate_policy;
+        self
+    }
+
+    /// Set the hasher of the codes of the built [`CodeDB`]s and of the code
+    /// hashes of the fetched accounts, the
+    /// [`default_code_hasher`](eth_types::utils::default_code_hasher) by
+    /// default. The circuits only prove the code hashes of the default hasher.
+    pub fn with_code_hasher(mut self, code_hasher: Arc<dyn CodeHasher>) -> Self {
+        self.code_hasher = code_hasher;
+        self
+    }
+
+    fn check_no_state_overrides(&self) -> Result<(), Error> {
+        if self.state_overrides.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InternalError(
+                "state overrides require the retrace-tx feature",
+            ))
+        }
+    }
+
     /// Step 1. Query geth for Block, Txs, TxExecTraces, history block hashes
     /// and previous state root.
     pub async fn get_block(
@@ -77,7 +234,13 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         block_num: u64,
     ) -> Result<(EthBlock, Vec<eth_types::GethExecTrace>, Vec<Word>, Word), Error> {
         let eth_block = self.cli.get_block_by_number(block_num.into()).await?;
-        let geth_traces = self.cli.trace_block_by_number(block_num.into()).await?;
+        let geth_traces = if self.call_trace_access {
+            self.cli
+                .trace_block_by_number_without_prestate(block_num.into())
+                .await?
+        } else {
+            self.cli.trace_block_by_number(block_num.into()).await?
+        };
 
         // fetch up to 256 blocks
         let mut n_blocks = 0; // std::cmp::min(256, block_num as usize);
@@ -116,12 +279,11 @@ impl<P: JsonRpcClient> BuilderClient<P> {
 
     /// Step 2. Get State Accesses from TxExecTraces
     pub async fn get_state_accesses(&self, eth_block: &EthBlock) -> Result<AccessSet, Error> {
+        eth_block
+            .author
+            .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?;
         let mut access_set = AccessSet::default();
-        access_set.add_account(
-            eth_block
-                .author
-                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?,
-        );
+        access_set.extend_from_block(eth_block);
         let traces = self
             .cli
             .trace_block_prestate_by_hash(
@@ -137,8 +299,32 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         Ok(access_set)
     }
 
+    /// Step 2, when the prestateTracer is not available. Get State Accesses
+    /// from the call traces and the stack of the struct logs of the
+    /// TxExecTraces.
+    pub fn get_state_accesses_from_call_trace(
+        &self,
+        eth_block: &EthBlock,
+        geth_traces: &[eth_types::GethExecTrace],
+    ) -> Result<AccessSet, Error> {
+        eth_block
+            .author
+            .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?;
+        #[cfg(feature = "enable-stack")]
+        {
+            Ok(AccessSet::from_call_trace(eth_block, geth_traces)?)
+        }
+        #[cfg(not(feature = "enable-stack"))]
+        {
+            let _ = geth_traces;
+            Err(Error::InternalError(
+                "call trace accesses require the enable-stack feature",
+            ))
+        }
+    }
+
     /// Step 3. Query geth for all accounts, storage keys, and codes from
-    /// Accesses
+    /// Accesses, following the [`PrestatePolicy`] of the client.
     pub async fn get_state(
         &self,
         block_num: u64,
@@ -151,18 +337,25 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         Error,
     > {
         let mut proofs = Vec::new();
-        for (address, key_set) in access_set.state {
-            let mut keys: Vec<Word> = key_set.iter().cloned().collect();
+        for (address, key_set) in access_set.state.iter() {
+            let mut keys: Vec<Word> = match access_set.fetch_policy(address, &self.prestate_policy)
+            {
+                FetchPolicy::Full => key_set.iter().cloned().collect(),
+                FetchPolicy::AccountOnly => Vec::new(),
+                FetchPolicy::Skip => continue,
+            };
             keys.sort();
             let proof = self
                 .cli
-                .get_proof(address, keys, (block_num - 1).into())
+                .get_proof(*address, keys, (block_num - 1).into())
                 .await
                 .unwrap();
             proofs.push(proof);
         }
         let mut codes: HashMap<Address, Vec<u8>> = HashMap::new();
-        for address in access_set.code {
+        for address in access_set.code.iter().copied().filter(|address| {
+            access_set.fetch_policy(address, &self.prestate_policy) == FetchPolicy::Full
+        }) {
             let code = self
                 .cli
                 .get_code(address, (block_num - 1).into())
@@ -199,7 +392,7 @@ impl<P: JsonRpcClient> BuilderClient<P> {
                     let code_size =
                         Word::from(prestate.code.as_ref().map(|bt| bt.len()).unwrap_or(0));
                     let (code_hash, keccak_code_hash) = if let Some(bt) = prestate.code {
-                        let h = CodeDB::hash(&bt);
+                        let h = self.code_hasher.hash_code(&bt);
                         // only require for L2
                         let keccak_h = if cfg!(feature = "scroll") {
                             hash_code_keccak(&bt)
@@ -209,7 +402,7 @@ impl<P: JsonRpcClient> BuilderClient<P> {
                         code_set.insert(addr, Vec::from(bt.as_ref()));
                         (h, keccak_h)
                     } else {
-                        (CodeDB::empty_code_hash(), *KECCAK_CODE_HASH_EMPTY)
+                        (self.code_hasher.hash_code(&[]), *KECCAK_CODE_HASH_EMPTY)
                     };
 
                     (
@@ -259,7 +452,7 @@ impl<P: JsonRpcClient> BuilderClient<P> {
     pub async fn complete_prestate(
         &self,
         eth_block: &EthBlock,
-        mut proofs: Vec<eth_types::EIP1186ProofResponse>,
+        proofs: Vec<eth_types::EIP1186ProofResponse>,
     ) -> Result<Vec<eth_types::EIP1186ProofResponse>, Error> {
         // a hacking? since the coinbase address is not touch in prestate
         let coinbase_addr = eth_block
@@ -274,10 +467,22 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             "is not expected to access genesis block"
         );
 
+        self.complete_prestate_at(coinbase_addr, block_num.as_u64() - 1, proofs)
+            .await
+    }
+
+    /// Add the proof of the coinbase account, taken from the state after
+    /// `state_block`, if it is missing in `proofs`.
+    async fn complete_prestate_at(
+        &self,
+        coinbase_addr: Address,
+        state_block: u64,
+        mut proofs: Vec<eth_types::EIP1186ProofResponse>,
+    ) -> Result<Vec<eth_types::EIP1186ProofResponse>, Error> {
         if !proofs.iter().any(|pr| pr.address == coinbase_addr) {
             let coinbase_proof = self
                 .cli
-                .get_proof(coinbase_addr, Vec::new(), (block_num - 1).into())
+                .get_proof(coinbase_addr, Vec::new(), state_block.into())
                 .await?;
             proofs.push(coinbase_proof);
         }
@@ -286,10 +491,11 @@ impl<P: JsonRpcClient> BuilderClient<P> {
 
     /// Step 4. Build a partial StateDB from step 3
     pub fn build_state_code_db(
+        &self,
         proofs: Vec<eth_types::EIP1186ProofResponse>,
         codes: HashMap<Address, Vec<u8>>,
     ) -> (StateDB, CodeDB) {
-        build_state_code_db(proofs, codes)
+        build_state_code_db_with(proofs, codes, CodeDB::with_hasher(self.code_hasher.clone()))
     }
 
     /// Step 5. For each step in TxExecTraces, gen the associated ops and state
@@ -318,13 +524,65 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         sdb: StateDB,
         code_db: CodeDB,
         blocks_and_traces: &[(EthBlock, Vec<eth_types::GethExecTrace>)],
+    ) -> Result<CircuitInputBuilder, Error> {
+        self.gen_inputs_from_state_multi_blocks_at(sdb, code_db, blocks_and_traces, 0)
+    }
+
+    /// Same as [`Self::gen_inputs_from_state_multi_blocks`], skipping the
+    /// first `tx_index` transactions of the first block, which are already
+    /// applied to `sdb`.
+    fn gen_inputs_from_state_multi_blocks_at(
+        &self,
+        sdb: StateDB,
+        code_db: CodeDB,
+        blocks_and_traces: &[(EthBlock, Vec<eth_types::GethExecTrace>)],
+        tx_index: usize,
     ) -> Result<CircuitInputBuilder, Error> {
         let mut builder =
             CircuitInputBuilder::new_from_params(self.chain_id, self.circuits_params, sdb, code_db);
-        for (eth_block, geth_traces) in blocks_and_traces {
+        let block_num_end = match blocks_and_traces.last() {
+            Some((eth_block, _)) => {
+                eth_block
+                    .number
+                    .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
+                    .as_u64()
+                    + 1
+            }
+            None => 0,
+        };
+        for (i, (eth_block, geth_traces)) in blocks_and_traces.iter().enumerate() {
             let block = Block::new(self.chain_id, Default::default(), eth_block)?;
-            builder.block.blocks.insert(block.number.as_u64(), block);
-            builder.handle_block_inner(eth_block, geth_traces)?;
+            let block_num = block.number.as_u64();
+            builder.block.blocks.insert(block_num, block);
+            let first_tx = if i == 0 { tx_index } else { 0 };
+            let Some((path, interval)) = &self.checkpoint else {
+                if first_tx == 0 {
+                    builder.handle_block_inner(eth_block, geth_traces)?;
+                } else {
+                    let partial_block = EthBlock {
+                        transactions: eth_block.transactions[first_tx..].to_vec(),
+                        ..eth_block.clone()
+                    };
+                    builder.handle_block_inner(&partial_block, &geth_traces[first_tx..])?;
+                }
+                continue;
+            };
+            let tx_num = eth_block.transactions.len();
+            for begin in (first_tx..tx_num).step_by(*interval) {
+                let end = cmp::min(begin + interval, tx_num);
+                let partial_block = EthBlock {
+                    transactions: eth_block.transactions[begin..end].to_vec(),
+                    ..eth_block.clone()
+                };
+                builder.handle_block_inner(&partial_block, &geth_traces[begin..end])?;
+                let checkpoint = if end == tx_num {
+                    BuilderCheckpoint::new(&builder, block_num + 1, 0, block_num_end)
+                } else {
+                    BuilderCheckpoint::new(&builder, block_num, end, block_num_end)
+                };
+                checkpoint.save(path)?;
+                log::info!("checkpoint at block {block_num}, tx {end}");
+            }
         }
         builder.finalize_building()?;
         Ok(builder)
@@ -345,15 +603,27 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             self.get_block(block_num).await?;
 
         let builder = if cfg!(feature = "retrace-tx") {
+            if self.call_trace_access {
+                return Err(Error::InternalError(
+                    "call trace accesses are not supported with the retrace-tx feature",
+                ));
+            }
             let trace_config = self
                 .get_trace_config(&eth_block, geth_traces.iter(), false)
                 .await?;
 
             self.trace_to_builder(&eth_block, &trace_config)?
         } else {
-            let (proofs, codes) = self.get_pre_state(geth_traces.iter())?;
-            let proofs = self.complete_prestate(&eth_block, proofs).await?;
-            let (state_db, code_db) = Self::build_state_code_db(proofs, codes);
+            self.check_no_state_overrides()?;
+            let (proofs, codes) = if self.call_trace_access {
+                let access_set =
+                    self.get_state_accesses_from_call_trace(&eth_block, &geth_traces)?;
+                self.get_state(block_num, access_set).await?
+            } else {
+                let (proofs, codes) = self.get_pre_state(geth_traces.iter())?;
+                (self.complete_prestate(&eth_block, proofs).await?, codes)
+            };
+            let (state_db, code_db) = self.build_state_code_db(proofs, codes);
             if eth_block.transactions.len() > self.circuits_params.max_txs {
                 log::error!(
                     "max_txs too small: {} < {} for block {}",
@@ -384,21 +654,46 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         block_num_begin: u64,
         block_num_end: u64,
     ) -> Result<CircuitInputBuilder, Error> {
+        self.check_no_state_overrides()?;
         let mut blocks_and_traces = Vec::new();
         let mut access_set = AccessSet::default();
         for block_num in block_num_begin..block_num_end {
             let (eth_block, geth_traces, _, _) = self.get_block(block_num).await?;
-            let mut access_list = self.get_state_accesses(&eth_block).await?;
+            let mut access_list = if self.call_trace_access {
+                self.get_state_accesses_from_call_trace(&eth_block, &geth_traces)?
+            } else {
+                self.get_state_accesses(&eth_block).await?
+            };
             access_set.extend(&mut access_list);
             blocks_and_traces.push((eth_block, geth_traces));
         }
         let (proofs, codes) = self.get_state(block_num_begin, access_set).await?;
-        let (state_db, code_db) = Self::build_state_code_db(proofs, codes);
+        let (state_db, code_db) = self.build_state_code_db(proofs, codes);
         let builder =
             self.gen_inputs_from_state_multi_blocks(state_db, code_db, &blocks_and_traces)?;
         Ok(builder)
     }
 
+    /// Resume a multi blocks build from `checkpoint`, handling the remaining
+    /// transactions of the block range on top of the checkpointed state.
+    ///
+    /// The returned builder only holds the inputs of the remaining
+    /// transactions.
+    pub async fn resume_from(
+        &self,
+        checkpoint: BuilderCheckpoint,
+    ) -> Result<CircuitInputBuilder, Error> {
+        self.check_no_state_overrides()?;
+        let mut blocks_and_traces = Vec::new();
+        for block_num in checkpoint.block_num..checkpoint.block_num_end {
+            let (eth_block, geth_traces, _, _) = self.get_block(block_num).await?;
+            blocks_and_traces.push((eth_block, geth_traces));
+        }
+        let tx_index = checkpoint.tx_index;
+        let (state_db, code_db) = checkpoint.into_state_code_db(self.code_hasher.clone());
+        self.gen_inputs_from_state_multi_blocks_at(state_db, code_db, &blocks_and_traces, tx_index)
+    }
+
     /// Perform all the steps to generate the circuit inputs
     pub async fn gen_inputs_tx(&self, hash_str: &str) -> Result<CircuitInputBuilder, Error> {
         let mut hash: [u8; 32] = [0; 32];
@@ -410,41 +705,311 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         decode_to_slice(hash_str, &mut hash).unwrap();
         let tx_hash = H256::from(hash);
 
+        let (builder, _) = self.build_tx_inputs(tx_hash).await?;
+        Ok(builder)
+    }
+
+    /// Perform all the steps to generate the circuit inputs of the single
+    /// transaction `tx_hash`, proven in isolation from the rest of its block.
+    ///
+    /// The state before the transaction is fetched with the prestateTracer,
+    /// so the transaction does not need to be the first of its block. The
+    /// returned block keeps the header of the including block (coinbase,
+    /// base fee, gas limit...) with the transaction as its only one.
+    pub async fn build_tx_inputs(
+        &self,
+        tx_hash: H256,
+    ) -> Result<(CircuitInputBuilder, EthBlock), Error> {
         let mut tx: eth_types::Transaction = self.cli.get_tx_by_hash(tx_hash).await?;
+        let block_hash = tx
+            .block_hash
+            .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?;
         tx.transaction_index = Some(0.into());
-        let geth_trace = if cfg!(feature = "rpc-legacy-tracer") {
+        let mut geth_trace = if cfg!(feature = "rpc-legacy-tracer") {
             self.cli.trace_tx_by_hash_legacy(tx_hash).await
         } else {
             self.cli.trace_tx_by_hash(tx_hash).await
         }?;
-        let mut eth_block = self
-            .cli
-            .get_block_by_number(tx.block_number.unwrap().into())
+        // the prestate of the transaction itself, rather than the state
+        // before its block
+        geth_trace.prestate = self.cli.trace_tx_prestate_by_hash(tx_hash).await?;
+        let mut eth_block = self.cli.get_block_by_hash(block_hash).await?;
+        let history_hashes = self
+            .get_history_hashes(&eth_block, iter::once(&geth_trace))
             .await?;
 
-        eth_block.transactions = vec![tx.clone()];
+        eth_block.transactions = vec![tx];
 
         let builder = if cfg!(feature = "retrace-tx") {
-            let trace_config = self
+            let mut trace_config = self
                 .get_trace_config(&eth_block, iter::once(&geth_trace), true)
                 .await?;
+            trace_config.history_hashes = history_hashes;
 
             self.trace_to_builder(&eth_block, &trace_config)?
         } else {
+            self.check_no_state_overrides()?;
             let (proofs, codes) = self.get_pre_state(iter::once(&geth_trace))?;
             let proofs = self.complete_prestate(&eth_block, proofs).await?;
-            let (state_db, code_db) = Self::build_state_code_db(proofs, codes);
+            let (state_db, code_db) = self.build_state_code_db(proofs, codes);
             self.gen_inputs_from_state(
                 state_db,
                 code_db,
                 &eth_block,
                 &[geth_trace],
-                Default::default(),
+                history_hashes,
                 Default::default(),
             )?
         };
 
-        Ok(builder)
+        Ok((builder, eth_block))
+    }
+
+    /// Returns the hashes of the blocks before `eth_block`, up to 256 and
+    /// with its parent last, for the BLOCKHASH opcodes of `geth_traces`.
+    /// Only the parent hash is returned when no trace executes BLOCKHASH, or
+    /// with the scroll feature, where BLOCKHASH is derived from the block
+    /// number instead.
+    async fn get_history_hashes<'a>(
+        &self,
+        eth_block: &EthBlock,
+        mut geth_traces: impl Iterator<Item = &'a GethExecTrace>,
+    ) -> Result<Vec<Word>, Error> {
+        let uses_blockhash = geth_traces.any(|trace| {
+            trace
+                .struct_logs
+                .iter()
+                .any(|step| step.op == OpcodeId::BLOCKHASH)
+        });
+        let n_blocks = if cfg!(feature = "scroll") || !uses_blockhash {
+            1
+        } else {
+            let block_num = eth_block
+                .number
+                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?;
+            cmp::min(256, block_num.as_usize()).max(1)
+        };
+
+        let mut history_hashes = vec![Word::default(); n_blocks];
+        let mut next_hash = eth_block.parent_hash;
+        for index in (0..n_blocks).rev() {
+            history_hashes[index] = next_hash.to_word();
+            if index > 0 {
+                // TODO: consider replacing it with `eth_getHeaderByHash`, it's faster
+                next_hash = self.cli.get_block_by_hash(next_hash).await?.parent_hash;
+            }
+        }
+        Ok(history_hashes)
+    }
+
+    /// Perform all the steps to generate the circuit inputs for a simulated
+    /// call, executed on top of the state after block `block_num` and with
+    /// the state overrides applied, without submitting any transaction.
+    ///
+    /// Missing fields of `tx_request` are filled the way `eth_call` does:
+    /// the sender defaults to the zero address, the gas to the block gas
+    /// limit, the gas price and value to zero, and the nonce to the current
+    /// nonce of the sender. The simulated transaction carries no signature.
+    pub async fn gen_inputs_call(
+        &self,
+        mut tx_request: TransactionRequest,
+        block_num: u64,
+    ) -> Result<(CircuitInputBuilder, EthBlock), Error> {
+        let mut eth_block = self.cli.get_block_by_number(block_num.into()).await?;
+
+        let from = *tx_request.from.get_or_insert_with(Address::zero);
+        let gas = *tx_request.gas.get_or_insert(eth_block.gas_limit);
+        let gas_price = *tx_request.gas_price.get_or_insert_with(Word::zero);
+        let value = *tx_request.value.get_or_insert_with(Word::zero);
+        let to = match &tx_request.to {
+            Some(NameOrAddress::Address(addr)) => Some(*addr),
+            Some(NameOrAddress::Name(_)) => {
+                return Err(Error::InternalError("ens names are not supported"))
+            }
+            None => None,
+        };
+        let geth_trace = self
+            .cli
+            .trace_call(&tx_request, block_num.into(), &self.state_overrides, None)
+            .await?;
+
+        let nonce = tx_request.nonce.unwrap_or_else(|| {
+            geth_trace
+                .prestate
+                .get(&from)
+                .and_then(|acc| acc.nonce)
+                .unwrap_or_default()
+                .into()
+        });
+        let mut tx = eth_types::Transaction {
+            block_hash: eth_block.hash,
+            block_number: eth_block.number,
+            transaction_index: Some(0.into()),
+            from,
+            to,
+            nonce,
+            gas,
+            gas_price: Some(gas_price),
+            value,
+            input: tx_request.data.unwrap_or_default(),
+            // an EIP-155 `v` with an empty signature, so that the call is not
+            // mistaken for an L1 message
+            v: (self.chain_id * 2 + 35).into(),
+            chain_id: Some(self.chain_id.into()),
+            ..Default::default()
+        };
+        tx.hash = tx.hash();
+        eth_block.transactions = vec![tx];
+
+        let (proofs, codes) = self.get_pre_state(iter::once(&geth_trace))?;
+        let coinbase_addr = eth_block
+            .author
+            .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?;
+        let proofs = self
+            .complete_prestate_at(coinbase_addr, block_num, proofs)
+            .await?;
+        let (mut state_db, mut code_db) = self.build_state_code_db(proofs, codes);
+        // the traced prestate is already overridden, but the coinbase proof
+        // is fetched from the node as is
+        state_db.apply_overrides(&mut code_db, &self.state_overrides);
+        let builder = self.gen_inputs_from_state(
+            state_db,
+            code_db,
+            &eth_block,
+            &[geth_trace],
+            Default::default(),
+            Default::default(),
+        )?;
+
+        Ok((builder, eth_block))
+    }
+
+    /// Perform all the steps to generate the circuit inputs for a block that
+    /// is not sealed yet, made of `txs` in this order on top of the latest
+    /// block, so that a block builder can check a proposed ordering fits in
+    /// the circuits before sealing it.
+    ///
+    /// The transactions are traced one after the other with `debug_traceCall`,
+    /// each one on top of the state left by the previous ones, which is
+    /// carried over as state overrides from the prestateTracer run in diff
+    /// mode. The pending block has the number and the timestamp following
+    /// the latest block, and takes its coinbase, gas limit and base fee from
+    /// it. Its hash is unknown, and its state root is the one of the latest
+    /// block.
+    ///
+    /// The diff leaves out the state only read by the transactions: with the
+    /// `enable-stack` feature, the state they access is fetched at the latest
+    /// block following the call traces and the struct logs, else their
+    /// prestate is traced on its own.
+    ///
+    /// The access lists of the transactions are not passed to the node, and
+    /// the transactions are executed with their effective gas price.
+    pub async fn build_pending_block(
+        &self,
+        mut txs: Vec<eth_types::Transaction>,
+    ) -> Result<(CircuitInputBuilder, EthBlock), Error> {
+        let latest = self.cli.get_block_by_number(BlockNumber::Latest).await?;
+        let latest_num = latest
+            .number
+            .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?;
+        let latest_hash = latest
+            .hash
+            .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?;
+        let coinbase_addr = latest
+            .author
+            .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?;
+        let number = latest_num + 1;
+        let timestamp = latest.timestamp + 1;
+        let base_fee = latest.base_fee_per_gas.unwrap_or_default();
+        let block_overrides = BlockOverrides {
+            number: Some(number),
+            time: Some(timestamp.low_u64().into()),
+            base_fee: Some(base_fee),
+        };
+
+        let mut state_overrides = self.state_overrides.clone();
+        let mut geth_traces = Vec::with_capacity(txs.len());
+        for (index, tx) in txs.iter_mut().enumerate() {
+            let gas_price = match (tx.max_fee_per_gas, tx.max_priority_fee_per_gas) {
+                (Some(max_fee), Some(tip)) => cmp::min(max_fee, base_fee + tip),
+                _ => tx.gas_price.unwrap_or_default(),
+            };
+            let mut tx_request = TransactionRequest::new()
+                .from(tx.from)
+                .gas(tx.gas)
+                .gas_price(gas_price)
+                .value(tx.value)
+                .data(tx.input.clone())
+                .nonce(tx.nonce);
+            tx_request.to = tx.to.map(NameOrAddress::Address);
+
+            let (mut geth_trace, state_diff) = self
+                .cli
+                .trace_call_with_state_diff(
+                    &tx_request,
+                    latest_num.into(),
+                    &state_overrides,
+                    Some(&block_overrides),
+                )
+                .await?;
+            if !cfg!(feature = "enable-stack") {
+                geth_trace.prestate = self
+                    .cli
+                    .trace_call_prestate(
+                        &tx_request,
+                        latest_num.into(),
+                        &state_overrides,
+                        Some(&block_overrides),
+                    )
+                    .await?;
+            }
+            state_diff.carry_over(&mut state_overrides);
+            geth_traces.push(geth_trace);
+
+            tx.block_hash = None;
+            tx.block_number = Some(number);
+            tx.transaction_index = Some(index.into());
+            tx.gas_price = Some(gas_price);
+        }
+
+        let eth_block = EthBlock {
+            hash: None,
+            parent_hash: latest_hash,
+            number: Some(number),
+            timestamp,
+            gas_used: geth_traces
+                .iter()
+                .map(|trace| trace.gas.0)
+                .sum::<u64>()
+                .into(),
+            transactions: txs,
+            ..latest
+        };
+
+        let (proofs, codes) = if cfg!(feature = "enable-stack") {
+            let access_set = self.get_state_accesses_from_call_trace(&eth_block, &geth_traces)?;
+            self.get_state(number.as_u64(), access_set).await?
+        } else {
+            // the first trace touching an account has its state before the
+            // block
+            let (proofs, codes) = self.get_pre_state(geth_traces.iter())?;
+            let proofs = self
+                .complete_prestate_at(coinbase_addr, latest_num.as_u64(), proofs)
+                .await?;
+            (proofs, codes)
+        };
+        let (mut state_db, mut code_db) = self.build_state_code_db(proofs, codes);
+        state_db.apply_overrides(&mut code_db, &self.state_overrides);
+        let builder = self.gen_inputs_from_state(
+            state_db,
+            code_db,
+            &eth_block,
+            &geth_traces,
+            vec![latest_hash.to_word()],
+            Default::default(),
+        )?;
+
+        Ok((builder, eth_block))
     }
 
     async fn get_trace_config(
@@ -466,6 +1031,29 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         //let difficulty = eth_block.difficulty;
         let difficulty = Word::zero();
 
+        let mut accounts = proofs
+            .into_iter()
+            .map(|proof| {
+                let acc = Account {
+                    address: proof.address,
+                    nonce: proof.nonce,
+                    balance: proof.balance,
+                    code: codes
+                        .get(&proof.address)
+                        .cloned()
+                        .unwrap_or_default()
+                        .into(),
+                    storage: proof
+                        .storage_proof
+                        .into_iter()
+                        .map(|proof| (proof.key, proof.value))
+                        .collect(),
+                };
+                (proof.address, acc)
+            })
+            .collect();
+        geth_types::apply_state_overrides(&mut accounts, &self.state_overrides);
+
         Ok(TraceConfig {
             chain_id: self.chain_id,
             history_hashes: vec![eth_block.parent_hash.to_word()],
@@ -477,27 +1065,7 @@ impl<P: JsonRpcClient> BuilderClient<P> {
                 gas_limit: eth_block.gas_limit,
                 base_fee: eth_block.base_fee_per_gas.unwrap(),
             },
-            accounts: proofs
-                .into_iter()
-                .map(|proof| {
-                    let acc = Account {
-                        address: proof.address,
-                        nonce: proof.nonce,
-                        balance: proof.balance,
-                        code: codes
-                            .get(&proof.address)
-                            .cloned()
-                            .unwrap_or_default()
-                            .into(),
-                        storage: proof
-                            .storage_proof
-                            .into_iter()
-                            .map(|proof| (proof.key, proof.value))
-                            .collect(),
-                    };
-                    (proof.address, acc)
-                })
-                .collect(),
+            accounts,
             transactions: eth_block
                 .transactions
                 .iter()
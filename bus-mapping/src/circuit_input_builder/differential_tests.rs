@@ -0,0 +1,303 @@
+//! Differential tests executing the txs of a block both in revm and in the
+//! [`CircuitInputBuilder`], and comparing the receipts and the post state.
+//! A divergence means the builder (and so the circuits) encodes a semantic
+//! which differs from the EVM's.
+
+use super::CircuitInputBuilder;
+use crate::{
+    mock::BlockData,
+    operation::{TxReceiptField, RW},
+};
+use eth_types::{
+    bytecode,
+    geth_types::{GethData, TxType},
+    Address, Bytecode, ToBigEndian, Word,
+};
+use mock::test_ctx::{helpers::*, TestContext};
+use revm::{
+    db::{AccountState, CacheDB, EmptyDB},
+    primitives::{
+        AccountInfo, Address as RevmAddress, Bytecode as RevmBytecode, Bytes as RevmBytes, SpecId,
+        TransactTo, B256, U256 as RevmU256,
+    },
+    Evm,
+};
+
+fn to_revm_address(address: Address) -> RevmAddress {
+    RevmAddress::from(address.0)
+}
+
+fn to_revm_u256(word: Word) -> RevmU256 {
+    RevmU256::from_be_bytes(word.to_be_bytes())
+}
+
+fn from_revm_u256(value: RevmU256) -> Word {
+    Word::from_big_endian(&value.to_be_bytes::<32>())
+}
+
+/// Receipt of a tx: whether it succeeded, its cumulative gas used and its
+/// number of logs.
+#[derive(Debug, PartialEq, Eq)]
+struct Receipt {
+    is_success: bool,
+    cumulative_gas_used: u64,
+    log_length: u64,
+}
+
+/// Receipts of the txs written by the builder in the tx receipt operations.
+fn builder_receipts(builder: &CircuitInputBuilder) -> Vec<Receipt> {
+    let mut receipts = builder
+        .block
+        .txs
+        .iter()
+        .map(|_| Receipt {
+            is_success: false,
+            cumulative_gas_used: 0,
+            log_length: 0,
+        })
+        .collect::<Vec<_>>();
+    for op in builder.block.container.tx_receipt.iter() {
+        if op.rw() != RW::WRITE {
+            continue;
+        }
+        let op = op.op();
+        let receipt = &mut receipts[op.tx_id - 1];
+        match op.field {
+            TxReceiptField::PostStateOrStatus => receipt.is_success = op.value != 0,
+            TxReceiptField::CumulativeGasUsed => receipt.cumulative_gas_used = op.value,
+            TxReceiptField::LogLength => receipt.log_length = op.value,
+        }
+    }
+    receipts
+}
+
+/// Execute the txs of `block` in revm on top of the accounts of `block`, and
+/// check that the receipts and the post state match the builder's.
+fn check_against_revm(block: &GethData) {
+    let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+
+    let mut db = CacheDB::new(EmptyDB::default());
+    for account in block.accounts.iter() {
+        let address = to_revm_address(account.address);
+        let code = RevmBytecode::new_raw(RevmBytes::from(account.code.to_vec()));
+        db.insert_account_info(
+            address,
+            AccountInfo::new(
+                to_revm_u256(account.balance),
+                account.nonce.as_u64(),
+                code.hash_slow(),
+                code,
+            ),
+        );
+        for (slot, value) in account.storage.iter() {
+            db.insert_account_storage(address, to_revm_u256(*slot), to_revm_u256(*value))
+                .unwrap();
+        }
+    }
+
+    let block_ctx = builder.block.blocks.values().next().unwrap();
+    let mut evm = Evm::builder()
+        .with_db(db)
+        .with_spec_id(SpecId::CANCUN)
+        .modify_cfg_env(|cfg| cfg.chain_id = block_ctx.chain_id)
+        .modify_block_env(|env| {
+            env.number = to_revm_u256(block_ctx.number);
+            env.coinbase = to_revm_address(block_ctx.coinbase);
+            env.timestamp = to_revm_u256(block_ctx.timestamp);
+            env.gas_limit = RevmU256::from(block_ctx.gas_limit);
+            env.basefee = to_revm_u256(block_ctx.base_fee);
+            env.difficulty = to_revm_u256(block_ctx.difficulty);
+            env.prevrandao = Some(B256::from(block_ctx.prev_randao.to_be_bytes()));
+        })
+        .build();
+
+    let mut revm_receipts = vec![];
+    let mut cumulative_gas_used = 0;
+    for tx in builder.block.txs.iter() {
+        let tx_env = evm.tx_mut();
+        tx_env.caller = to_revm_address(tx.from);
+        tx_env.gas_limit = tx.gas;
+        tx_env.transact_to = match tx.to {
+            Some(to) => TransactTo::Call(to_revm_address(to)),
+            None => TransactTo::create(),
+        };
+        tx_env.value = to_revm_u256(tx.value);
+        tx_env.data = RevmBytes::from(tx.input.clone());
+        tx_env.nonce = Some(tx.nonce);
+        tx_env.chain_id = None;
+        if tx.tx_type == TxType::Eip1559 {
+            tx_env.gas_price = to_revm_u256(tx.gas_fee_cap);
+            tx_env.gas_priority_fee = Some(to_revm_u256(tx.gas_tip_cap));
+        } else {
+            tx_env.gas_price = to_revm_u256(tx.gas_price);
+            tx_env.gas_priority_fee = None;
+        }
+        tx_env.access_list = tx
+            .access_list
+            .iter()
+            .flat_map(|access_list| access_list.0.iter())
+            .map(|item| {
+                (
+                    to_revm_address(item.address),
+                    item.storage_keys
+                        .iter()
+                        .map(|key| RevmU256::from_be_bytes(key.0))
+                        .collect(),
+                )
+            })
+            .collect();
+
+        let result = evm.transact_commit().unwrap();
+        cumulative_gas_used += result.gas_used();
+        revm_receipts.push(Receipt {
+            is_success: result.is_success(),
+            cumulative_gas_used,
+            log_length: result.logs().len() as u64,
+        });
+    }
+    assert_eq!(
+        builder_receipts(&builder),
+        revm_receipts,
+        "receipts diverge"
+    );
+
+    for (address, revm_account) in evm.db().accounts.iter() {
+        let address = Address::from_slice(address.as_slice());
+        let account = builder.sdb.get_account(&address).1;
+        if matches!(revm_account.account_state, AccountState::NotExisting) {
+            assert!(account.is_empty(), "account {address:?} should not exist");
+            continue;
+        }
+        assert_eq!(
+            account.balance,
+            from_revm_u256(revm_account.info.balance),
+            "balance of {address:?} diverges"
+        );
+        assert_eq!(
+            account.nonce,
+            Word::from(revm_account.info.nonce),
+            "nonce of {address:?} diverges"
+        );
+        for (slot, value) in revm_account.storage.iter() {
+            let slot = from_revm_u256(*slot);
+            assert_eq!(
+                *builder.sdb.get_storage(&address, &slot).1,
+                from_revm_u256(*value),
+                "storage slot {slot:?} of {address:?} diverges"
+            );
+        }
+    }
+}
+
+fn check_bytecode(code: Bytecode) {
+    let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+        .unwrap()
+        .into();
+    check_against_revm(&block);
+}
+
+#[test]
+fn differential_value_transfer() {
+    let block: GethData = TestContext::<2, 2>::new(
+        None,
+        account_0_code_account_1_no_code(bytecode! { STOP }),
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[1].address)
+                .to(accs[0].address)
+                .value(Word::from(1_000_000u64));
+            txs[1]
+                .from(accs[1].address)
+                .to(accs[0].address)
+                .value(Word::from(2_000_000u64));
+        },
+        |block, _tx| block,
+    )
+    .unwrap()
+    .into();
+    check_against_revm(&block);
+}
+
+#[test]
+fn differential_storage_and_refund() {
+    check_bytecode(bytecode! {
+        PUSH1(0x01)
+        PUSH1(0x00)
+        SSTORE
+        PUSH1(0x02)
+        PUSH1(0x01)
+        SSTORE
+        // clearing the slot set above gives a refund
+        PUSH1(0x00)
+        PUSH1(0x00)
+        SSTORE
+        PUSH1(0x01)
+        SLOAD
+        POP
+        STOP
+    });
+}
+
+#[test]
+fn differential_logs() {
+    check_bytecode(bytecode! {
+        PUSH32(Word::MAX)
+        PUSH1(0x00)
+        MSTORE
+        PUSH1(0xaa)
+        PUSH1(0xbb)
+        PUSH1(0x20)
+        PUSH1(0x00)
+        LOG2
+        PUSH1(0x10)
+        PUSH1(0x08)
+        LOG0
+        STOP
+    });
+}
+
+#[test]
+fn differential_revert() {
+    check_bytecode(bytecode! {
+        PUSH1(0x01)
+        PUSH1(0x00)
+        SSTORE
+        PUSH1(0x00)
+        PUSH1(0x00)
+        LOG0
+        PUSH1(0x00)
+        PUSH1(0x00)
+        REVERT
+    });
+}
+
+#[test]
+fn differential_create() {
+    // init code returning a single STOP byte as the deployed code
+    let init_code = bytecode! {
+        PUSH1(0x00)
+        PUSH1(0x00)
+        MSTORE8
+        PUSH1(0x01)
+        PUSH1(0x00)
+        RETURN
+    };
+    let init_code = init_code.code();
+    let mut code = bytecode! {
+        PUSH32(Word::from_big_endian(&init_code))
+        PUSH1(0x00)
+        MSTORE
+    };
+    code.append(&bytecode! {
+        PUSH1(init_code.len())
+        PUSH1(32 - init_code.len())
+        PUSH1(0x00)
+        CREATE
+        POP
+        STOP
+    });
+    check_bytecode(code);
+}
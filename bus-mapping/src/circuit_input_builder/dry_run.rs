@@ -0,0 +1,974 @@
+//! Dry run of the circuit input builder, which only computes aggregate
+//! statistics of a block from its geth traces instead of generating the
+//! witness. It's meant for capacity planning and sequencer pre-checks, where
+//! the full witness isn't needed.
+//!
+//! The numbers of operations mirror the ones emitted by the handlers of
+//! [`crate::evm::opcodes`], and are checked against the builder in the tests.
+
+use super::{curie, CircuitInputBuilder, L1FeeSchema, TxL1Fee};
+use crate::{operation::Target, precompile::PrecompileCalls};
+use eth_types::{
+    evm_types::{
+        memory::MemoryWordRange, GasCost, OpcodeId, CREATE2_GAS_PER_CODE_WORD,
+        CREATE_GAS_PER_CODE_WORD, MAX_INIT_CODE_SIZE,
+    },
+    geth_types::{Account, GethData, TxType},
+    utils::is_precompiled,
+    Address, GethExecStep, GethExecTrace, ToAddress, Transaction, Word,
+};
+use ethers_core::utils::get_contract_address;
+use std::collections::{HashMap, HashSet};
+
+/// Call context writes of the `BeginTx` step of every tx: `TxId`, `L1Fee`,
+/// `RwCounterEndOfReversion`, `IsPersistent` and `IsSuccess`.
+const BEGIN_TX_CALL_CONTEXT_OPS: usize = 5;
+/// Call context writes initializing the root call when it executes code, from
+/// `Depth` to `CodeHash`.
+const ROOT_CALL_CONTEXT_OPS: usize = 13;
+/// Accounts warmed by the `BeginTx` step: the 9 precompiles, the caller, the
+/// callee and the coinbase.
+const BEGIN_TX_ACCESS_LIST_OPS: usize = 9 + 3;
+/// Call context reads of the `EndTx` step: `TxId`, `IsPersistent` and `L1Fee`.
+const END_TX_CALL_CONTEXT_OPS: usize = 3;
+/// Receipt writes of the `EndTx` step: `PostStateOrStatus`, `LogLength` and
+/// `CumulativeGasUsed`. The cumulative gas of the previous tx is also read
+/// from the second tx on.
+const END_TX_RECEIPT_OPS: usize = 3;
+/// Call context operations of returning to the caller: the `CallerId` read,
+/// the reads of the 8 saved fields of the caller and the writes of its 3
+/// `LastCallee*` fields.
+const RESTORE_CONTEXT_OPS: usize = 1 + 8 + 3;
+/// Call context reads of a call ending in an error: `IsSuccess` and
+/// `RwCounterEndOfReversion`.
+const CALL_FAILURE_OPS: usize = 2;
+/// Call context reads of `CALL*`: `TxId`, `RwCounterEndOfReversion`,
+/// `IsPersistent`, `IsStatic`, `Depth` and `CalleeAddress`, and the writes of
+/// `RwCounterEndOfReversion` and `IsPersistent` of the callee.
+const CALL_CONTEXT_OPS: usize = 6 + 2;
+/// Call context reads of `DELEGATECALL` on top of [`CALL_CONTEXT_OPS`]:
+/// `CallerAddress` and `Value`.
+const DELEGATECALL_CONTEXT_OPS: usize = 2;
+/// Call context writes of entering the code of a callee: the 5 fields of the
+/// caller saved until it's restored, and the 18 fields of the callee.
+const CALL_SWITCH_CONTEXT_OPS: usize = 5 + 18;
+/// Call context writes of the caller of a call which doesn't execute code:
+/// `LastCalleeId`, `LastCalleeReturnDataOffset` and
+/// `LastCalleeReturnDataLength`.
+const LAST_CALLEE_OPS: usize = 3;
+/// Call context writes of a call to a precompile: 8 fields of the callee and 8
+/// of the caller.
+const PRECOMPILE_CALL_OPS: usize = 8 + 8;
+/// Call context operations of `CREATE*` besides the ones of the callee: the
+/// reads of `TxId`, `Depth`, `RwCounterEndOfReversion`, `IsPersistent`,
+/// `IsStatic` and `CalleeAddress`, the 5 fields of the caller saved until it's
+/// restored, and the writes of `RwCounterEndOfReversion` and `IsPersistent` of
+/// the callee.
+const CREATE_CONTEXT_OPS: usize = 6 + 5 + 2;
+/// Call context writes of the callee of `CREATE*` running its init code.
+const CREATE_CALLEE_CONTEXT_OPS: usize = 13;
+/// Account operations of a successful `CREATE*` besides the account creation
+/// and the transfer: the caller balance and nonce reads and the nonce write,
+/// and the callee code hash read and nonce write.
+const CREATE_ACCOUNT_UPDATE_OPS: usize = 3 + 2;
+/// Call context reads of a successful `RETURN` deploying code: `CallerId`,
+/// `CalleeAddress`, `RwCounterEndOfReversion` and `IsPersistent`.
+const DEPLOY_CALL_CONTEXT_OPS: usize = 4;
+/// Code hash fields of an account, the keccak code hash being a separate
+/// field with scroll.
+const CODE_HASH_FIELDS: usize = if cfg!(feature = "scroll") { 2 } else { 1 };
+/// Account operations creating an account: a read and a write of each code
+/// hash field.
+const CREATE_ACCOUNT_OPS: usize = 2 * CODE_HASH_FIELDS;
+/// Account operations deploying code: a read and a write of each code hash
+/// field, and the code size write with scroll.
+const DEPLOY_ACCOUNT_OPS: usize = CREATE_ACCOUNT_OPS + cfg!(feature = "scroll") as usize;
+/// Account writes of the curie fork, at the end of its block.
+const CURIE_ACCOUNT_OPS: usize = 3;
+/// Storage writes of the curie fork, at the end of its block.
+const CURIE_STORAGE_OPS: usize = 4;
+
+/// Aggregate statistics of a block computed by
+/// [`CircuitInputBuilder::dry_run`].
+///
+/// With the `enable-stack` feature, the numbers are exact for the txs whose
+/// calls all succeed and which neither create contracts nor call
+/// precompiles. The operations reverting failed calls, and the ones of the
+/// precompiles, of `SELFDESTRUCT` and of the error steps, are estimates.
+///
+/// Without it, the copied words and bytes are bounded from the gas cost of
+/// the steps, so that the `Memory` and `TxLog` operations and the sizes of the
+/// copies are upper bounds, except for the init code of `CREATE*`. The calls
+/// are then assumed to send no value to existing accounts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockStats {
+    /// Number of transactions
+    pub txs: usize,
+    /// Number of execution steps, including the `BeginTx` and `EndTx` ones
+    pub steps: usize,
+    /// Number of rw operations per target
+    pub rw_ops: HashMap<Target, usize>,
+    /// Number of bytes copied by the copy events, as given by
+    /// [`super::CopyEvent::copy_length`]
+    pub copy_bytes: usize,
+    /// Number of bytes hashed by the `SHA3` opcode
+    pub keccak_bytes: usize,
+    /// Number of exponentiation events
+    pub exp_events: usize,
+}
+
+impl BlockStats {
+    /// Total number of rw operations.
+    pub fn total_rw_ops(&self) -> usize {
+        self.rw_ops.values().sum()
+    }
+
+    fn add_rw_ops(&mut self, target: Target, n: usize) {
+        if n > 0 {
+            *self.rw_ops.entry(target).or_default() += n;
+        }
+    }
+}
+
+/// Existence and code size of the accounts, which select the operations of
+/// the steps accessing them.
+#[derive(Debug, Default)]
+struct Accounts {
+    /// Accounts which are not empty as defined by EIP-161
+    existing: HashSet<Address>,
+    code_sizes: HashMap<Address, u64>,
+}
+
+impl Accounts {
+    fn new(accounts: &[Account]) -> Self {
+        let mut this = Self::default();
+        for account in accounts {
+            if !(account.nonce.is_zero() && account.balance.is_zero() && account.code.is_empty()) {
+                this.existing.insert(account.address);
+            }
+            if !account.code.is_empty() {
+                this.code_sizes
+                    .insert(account.address, account.code.len() as u64);
+            }
+        }
+        this
+    }
+
+    fn exists(&self, address: &Address) -> bool {
+        self.existing.contains(address)
+    }
+
+    fn code_size(&self, address: &Address) -> u64 {
+        self.code_sizes.get(address).copied().unwrap_or_default()
+    }
+}
+
+/// Outcome of the call of a step.
+#[derive(Debug, Clone, Copy)]
+struct CallStatus {
+    is_success: bool,
+    is_persistent: bool,
+}
+
+/// Outcome of the call of each step of a tx, known from the step ending it.
+fn call_statuses(steps: &[GethExecStep], is_success: bool) -> Vec<CallStatus> {
+    // the calls are numbered in the order they're entered, so that a caller
+    // always comes before its callees
+    let mut callers: Vec<Option<usize>> = vec![];
+    let mut successes: Vec<bool> = vec![];
+    let mut stack: Vec<usize> = vec![];
+    let mut step_calls = Vec::with_capacity(steps.len());
+    for (i, step) in steps.iter().enumerate() {
+        let depth = step.depth as usize;
+        stack.truncate(depth);
+        while stack.len() < depth {
+            callers.push(stack.last().copied());
+            successes.push(!stack.is_empty() || is_success);
+            stack.push(successes.len() - 1);
+        }
+        let call = stack[depth - 1];
+        step_calls.push(call);
+        let ends_call = steps
+            .get(i + 1)
+            .map_or(true, |next| (next.depth as usize) < depth);
+        if ends_call && (step.op == OpcodeId::REVERT || step.error.is_some()) {
+            successes[call] = false;
+        }
+    }
+    let mut persistent = Vec::with_capacity(successes.len());
+    for (call, caller) in callers.iter().enumerate() {
+        persistent.push(successes[call] && caller.map_or(true, |caller| persistent[caller]));
+    }
+    step_calls
+        .into_iter()
+        .map(|call| CallStatus {
+            is_success: successes[call],
+            is_persistent: persistent[call],
+        })
+        .collect()
+}
+
+/// Memory of a call read by its steps, when it's known.
+#[derive(Debug, Clone, Default)]
+struct Frame {
+    is_create: bool,
+    /// Offset and length of the call data, in the memory of the caller for an
+    /// internal call
+    call_data: Option<(u64, u64)>,
+    /// Offset and length of the return data in the memory of the caller
+    return_data: Option<(u64, u64)>,
+    code_size: Option<u64>,
+    /// Offset of the return data of the last callee in its memory
+    last_callee_return_offset: Option<u64>,
+}
+
+/// `nth` word of the traced stack of `step`.
+#[cfg(feature = "enable-stack")]
+fn stack_nth(step: &GethExecStep, nth: usize) -> Option<Word> {
+    step.stack.nth_last(nth).ok()
+}
+
+/// `nth` word of the traced stack of `step`, which isn't traced without the
+/// `enable-stack` feature.
+#[cfg(not(feature = "enable-stack"))]
+fn stack_nth(_step: &GethExecStep, _nth: usize) -> Option<Word> {
+    None
+}
+
+/// `nth` word of the traced stack of `step` as an offset or a length,
+/// saturated to `u64::MAX`.
+fn stack_u64(step: &GethExecStep, nth: usize) -> Option<u64> {
+    stack_nth(step, nth).map(|word| u64::try_from(word).unwrap_or(u64::MAX))
+}
+
+/// Number of memory words copied from `src` to `dst`, both aligned to words
+/// and extended to the same length, or an upper bound of it when one of them
+/// isn't known.
+fn copy_words(src: Option<u64>, dst: Option<u64>, length: u64) -> usize {
+    if length == 0 {
+        return 0;
+    }
+    match (src, dst) {
+        (Some(src), Some(dst)) => {
+            let mut src = MemoryWordRange::align_range(src, length);
+            let mut dst = MemoryWordRange::align_range(dst, length);
+            src.ensure_equal_length(&mut dst);
+            dst.word_count()
+        }
+        _ => length.div_ceil(32) as usize + 1,
+    }
+}
+
+/// Upper bound of the number of bytes copied by a successful `step`, derived
+/// from its gas cost assuming that it doesn't expand the memory.
+fn copy_size_bound(step: &GethExecStep) -> u64 {
+    let gas_cost = step.gas_cost.as_u64();
+    let words = |base: u64, per_word: u64| gas_cost.saturating_sub(base) / per_word * 32;
+    match step.op {
+        OpcodeId::SHA3 => words(GasCost::SHA3.as_u64(), GasCost::COPY_SHA3.as_u64()),
+        OpcodeId::CALLDATACOPY
+        | OpcodeId::CODECOPY
+        | OpcodeId::RETURNDATACOPY
+        | OpcodeId::MCOPY => words(GasCost::FASTEST.as_u64(), GasCost::COPY.as_u64()),
+        OpcodeId::EXTCODECOPY => words(GasCost::WARM_ACCESS.as_u64(), GasCost::COPY.as_u64()),
+        OpcodeId::CREATE => {
+            words(GasCost::CREATE.as_u64(), CREATE_GAS_PER_CODE_WORD).min(MAX_INIT_CODE_SIZE)
+        }
+        OpcodeId::CREATE2 => {
+            words(GasCost::CREATE.as_u64(), CREATE2_GAS_PER_CODE_WORD).min(MAX_INIT_CODE_SIZE)
+        }
+        op if op.is_log() => {
+            let topics = op.postfix().unwrap_or_default() as u64;
+            gas_cost.saturating_sub(GasCost::LOG.as_u64() * (topics + 1)) / 8
+        }
+        _ => 0,
+    }
+}
+
+/// Number of bytes copied by a successful `step`, read from the traced stack
+/// at `nth`, or bounded from its gas cost.
+fn copy_size(step: &GethExecStep, nth: usize) -> u64 {
+    stack_u64(step, nth).unwrap_or_else(|| copy_size_bound(step))
+}
+
+/// Number of stack reads and writes of `op`. `DUPn` and `SWAPn` only access
+/// the words they duplicate and swap, and `SELFDESTRUCT` pops its word without
+/// an operation.
+fn stack_rw_ops(op: OpcodeId) -> usize {
+    if op.is_dup() {
+        return 2;
+    }
+    if op.is_swap() {
+        return 4;
+    }
+    if op == OpcodeId::SELFDESTRUCT {
+        return 0;
+    }
+    let (pops, pushes) = op.stack_pops_pushes();
+    pops + pushes
+}
+
+/// State of a dry run over the txs of a block.
+struct DryRun {
+    stats: BlockStats,
+    accounts: Accounts,
+}
+
+impl DryRun {
+    fn add(&mut self, target: Target, n: usize) {
+        self.stats.add_rw_ops(target, n);
+    }
+
+    /// Operations of the `BeginTx` and `EndTx` steps of `tx` and of its
+    /// execution steps.
+    fn handle_tx(
+        &mut self,
+        geth_data: &GethData,
+        l1_fee_schema: L1FeeSchema,
+        tx: &Transaction,
+        trace: &GethExecTrace,
+    ) {
+        self.stats.txs += 1;
+        let tx_id = self.stats.txs;
+        let tx_type = TxType::get_tx_type(tx);
+        let is_create = tx.to.is_none();
+        let caller = tx.from;
+        let callee = tx
+            .to
+            .unwrap_or_else(|| get_contract_address(tx.from, tx.nonce));
+        let callee_exists = self.accounts.exists(&callee);
+        let is_precompile = !is_create && is_precompiled(&callee);
+        let code_size = if is_create {
+            tx.input.len() as u64
+        } else {
+            self.accounts.code_size(&callee)
+        };
+
+        // BeginTx
+        self.stats.steps += 1;
+        self.add(Target::CallContext, BEGIN_TX_CALL_CONTEXT_OPS);
+        if tx_type.is_l1_msg() {
+            // caller code hash read, and its creation
+            self.add(Target::Account, 1);
+            if !self.accounts.exists(&caller) {
+                self.add(Target::Account, CODE_HASH_FIELDS);
+            }
+        } else {
+            let block_num = tx.block_number.unwrap_or_default().as_u64();
+            let l1_fee =
+                TxL1Fee::from_storage(l1_fee_schema, geth_data.chain_id, block_num, |_, _| {
+                    Word::zero()
+                });
+            self.add(Target::Storage, l1_fee.slots().len());
+        }
+        if tx_type.is_eip1559() || tx_type.is_eip2930() {
+            if let Some(access_list) = &tx.access_list {
+                let addresses = access_list.0.len();
+                let storage_keys = access_list
+                    .0
+                    .iter()
+                    .map(|item| item.storage_keys.len())
+                    .sum();
+                self.add(Target::TxAccessListAccount, addresses);
+                self.add(Target::TxAccessListAccountStorage, storage_keys);
+                self.stats.copy_bytes += addresses + storage_keys;
+            }
+        }
+        self.add(Target::TxAccessListAccount, BEGIN_TX_ACCESS_LIST_OPS);
+        // caller nonce write, callee code hash read and fee write
+        self.add(Target::Account, 3);
+        if !tx.value.is_zero() {
+            self.add(Target::Account, 2);
+        }
+        if (!callee_exists && !tx.value.is_zero()) || is_create {
+            self.add(Target::Account, CREATE_ACCOUNT_OPS);
+        }
+        if is_create {
+            // callee nonce write
+            self.add(Target::Account, 1);
+            self.add(Target::CallContext, ROOT_CALL_CONTEXT_OPS);
+            self.stats.copy_bytes += tx.input.len();
+        } else if is_precompile {
+            // ReturnDataOffset and ReturnDataLength
+            self.add(Target::CallContext, 2 + ROOT_CALL_CONTEXT_OPS);
+            let precompile_call = PrecompileCalls::from(callee.0[19]);
+            self.stats.copy_bytes += precompile_call
+                .input_len()
+                .map_or(tx.input.len(), |input_len| input_len.min(tx.input.len()));
+            // the step of the precompile
+            self.stats.steps += 1;
+        } else if code_size > 0 {
+            self.add(Target::CallContext, ROOT_CALL_CONTEXT_OPS);
+        }
+
+        let root = Frame {
+            is_create,
+            call_data: Some((0, if is_create { 0 } else { tx.input.len() as u64 })),
+            code_size: Some(code_size),
+            ..Default::default()
+        };
+        let mut frames = vec![root];
+        let mut next_frame = None;
+        let statuses = call_statuses(&trace.struct_logs, !trace.failed);
+        for (i, step) in trace.struct_logs.iter().enumerate() {
+            let depth = step.depth as usize;
+            frames.truncate(depth);
+            while frames.len() < depth {
+                frames.push(next_frame.take().unwrap_or_default());
+            }
+            next_frame =
+                self.handle_step(step, trace.struct_logs.get(i + 1), statuses[i], &mut frames);
+        }
+
+        // EndTx
+        let coinbase = geth_data.eth_block.author.unwrap_or_default();
+        self.stats.steps += 1;
+        self.add(Target::CallContext, END_TX_CALL_CONTEXT_OPS);
+        self.add(Target::TxRefund, 1);
+        // coinbase code hash read
+        self.add(Target::Account, 1);
+        let mut is_rewarded = false;
+        if !tx_type.is_l1_msg() {
+            // caller refund
+            self.add(Target::Account, 1);
+            let gas_price = tx.gas_price.unwrap_or_default();
+            let base_fee = geth_data.eth_block.base_fee_per_gas.unwrap_or_default();
+            let tip = if cfg!(feature = "scroll") {
+                gas_price
+            } else {
+                gas_price.saturating_sub(base_fee)
+            };
+            is_rewarded = !(tip * trace.gas.0 + trace.l1_fee).is_zero();
+            if is_rewarded {
+                if !self.accounts.exists(&coinbase) {
+                    self.add(Target::Account, CREATE_ACCOUNT_OPS);
+                }
+                self.add(Target::Account, 1);
+            }
+        }
+        self.add(Target::TxReceipt, END_TX_RECEIPT_OPS + (tx_id > 1) as usize);
+
+        self.accounts.existing.insert(caller);
+        if is_create || is_precompile || !tx.value.is_zero() {
+            self.accounts.existing.insert(callee);
+        }
+        if is_rewarded {
+            self.accounts.existing.insert(coinbase);
+        }
+    }
+
+    /// Operations of a geth step, in the call of the last of `frames`. Return
+    /// the frame of the callee when the step enters its code.
+    fn handle_step(
+        &mut self,
+        step: &GethExecStep,
+        next_step: Option<&GethExecStep>,
+        status: CallStatus,
+        frames: &mut [Frame],
+    ) -> Option<Frame> {
+        self.stats.steps += 1;
+        let op = step.op;
+        let depth = frames.len();
+        let is_root = depth == 1;
+        let (frame, callers) = frames.split_last_mut().expect("a step runs in a call");
+        let caller = callers.last_mut();
+
+        // the operations specific to each error are not counted
+        if step.error.is_some() {
+            self.add(Target::CallContext, CALL_FAILURE_OPS);
+            if let Some(caller) = caller {
+                self.add(Target::CallContext, RESTORE_CONTEXT_OPS);
+                caller.last_callee_return_offset = Some(0);
+            }
+            return None;
+        }
+
+        self.add(Target::Stack, stack_rw_ops(op));
+        let mut callee = None;
+        match op {
+            OpcodeId::MLOAD | OpcodeId::MSTORE => self.add(Target::Memory, 2),
+            OpcodeId::MSTORE8 => self.add(Target::Memory, 1),
+            OpcodeId::SHA3 => {
+                let offset = stack_u64(step, 0);
+                let size = copy_size(step, 1);
+                self.add(Target::Memory, copy_words(offset, offset, size));
+                self.stats.copy_bytes += size as usize;
+                self.stats.keccak_bytes += size as usize;
+            }
+            OpcodeId::EXP => self.stats.exp_events += 1,
+            OpcodeId::CALLDATACOPY => {
+                let size = copy_size(step, 2);
+                let src = frame
+                    .call_data
+                    .zip(stack_u64(step, 1))
+                    .map(|((offset, length), data_offset)| offset + data_offset.min(length));
+                let words = copy_words(src, stack_u64(step, 0), size);
+                if is_root {
+                    // TxId and CallDataLength
+                    self.add(Target::CallContext, 2);
+                    self.add(Target::Memory, words);
+                } else {
+                    // CallerId, CallDataLength and CallDataOffset
+                    self.add(Target::CallContext, 3);
+                    self.add(Target::Memory, 2 * words);
+                }
+                self.stats.copy_bytes += size as usize;
+            }
+            OpcodeId::CODECOPY => {
+                let size = copy_size(step, 2);
+                let src = frame
+                    .code_size
+                    .zip(stack_u64(step, 1))
+                    .map(|(code_size, code_offset)| code_offset.min(code_size));
+                self.add(Target::Memory, copy_words(src, stack_u64(step, 0), size));
+                self.stats.copy_bytes += size as usize;
+            }
+            OpcodeId::RETURNDATACOPY => {
+                let size = copy_size(step, 2);
+                let src = frame
+                    .last_callee_return_offset
+                    .zip(stack_u64(step, 1))
+                    .map(|(offset, data_offset)| offset + data_offset);
+                // LastCalleeId, LastCalleeReturnDataOffset and
+                // LastCalleeReturnDataLength
+                self.add(Target::CallContext, 3);
+                self.add(
+                    Target::Memory,
+                    2 * copy_words(src, stack_u64(step, 0), size),
+                );
+                self.stats.copy_bytes += size as usize;
+            }
+            OpcodeId::MCOPY => {
+                let size = copy_size(step, 2);
+                let words = copy_words(stack_u64(step, 1), stack_u64(step, 0), size);
+                self.add(Target::Memory, 2 * words);
+                self.stats.copy_bytes += size as usize;
+            }
+            OpcodeId::EXTCODECOPY => {
+                let size = copy_size(step, 3);
+                let code_size = stack_nth(step, 0).map(|address| {
+                    let address = address.to_address();
+                    if self.accounts.exists(&address) {
+                        self.accounts.code_size(&address)
+                    } else {
+                        0
+                    }
+                });
+                let src = code_size
+                    .zip(stack_u64(step, 2))
+                    .map(|(code_size, code_offset)| code_offset.min(code_size));
+                // TxId, RwCounterEndOfReversion and IsPersistent
+                self.add(Target::CallContext, 3);
+                self.add(Target::TxAccessListAccount, 1);
+                self.add(Target::Account, 1);
+                self.add(Target::Memory, copy_words(src, stack_u64(step, 1), size));
+                self.stats.copy_bytes += size as usize;
+            }
+            OpcodeId::SLOAD => {
+                // TxId, RwCounterEndOfReversion, IsPersistent and CalleeAddress
+                self.add(Target::CallContext, 4);
+                self.add(Target::Storage, 1);
+                self.add(Target::TxAccessListAccountStorage, 2);
+            }
+            OpcodeId::SSTORE => {
+                // TxId, IsStatic, RwCounterEndOfReversion, IsPersistent and
+                // CalleeAddress
+                self.add(Target::CallContext, 5);
+                self.add(Target::Storage, 1);
+                self.add(Target::TxAccessListAccountStorage, 2);
+                self.add(Target::TxRefund, 1);
+            }
+            OpcodeId::TLOAD => {
+                // TxId and CalleeAddress
+                self.add(Target::CallContext, 2);
+                self.add(Target::TransientStorage, 1);
+            }
+            OpcodeId::TSTORE => {
+                // TxId, IsStatic, RwCounterEndOfReversion, IsPersistent and
+                // CalleeAddress
+                self.add(Target::CallContext, 5);
+                self.add(Target::TransientStorage, 1);
+            }
+            OpcodeId::BALANCE | OpcodeId::EXTCODESIZE | OpcodeId::EXTCODEHASH => {
+                let exists = stack_nth(step, 0)
+                    .map_or(true, |address| self.accounts.exists(&address.to_address()));
+                // TxId, RwCounterEndOfReversion and IsPersistent
+                self.add(Target::CallContext, 3);
+                self.add(Target::TxAccessListAccount, 1);
+                // the code hash, and the balance, code size or keccak code
+                // hash of an existing account
+                let extra_field = match op {
+                    OpcodeId::BALANCE => true,
+                    _ => cfg!(feature = "scroll"),
+                };
+                self.add(Target::Account, 1 + (exists && extra_field) as usize);
+            }
+            OpcodeId::SELFBALANCE => {
+                self.add(Target::CallContext, 1);
+                self.add(Target::Account, 1);
+            }
+            OpcodeId::ADDRESS
+            | OpcodeId::ORIGIN
+            | OpcodeId::CALLER
+            | OpcodeId::CALLVALUE
+            | OpcodeId::CALLDATASIZE
+            | OpcodeId::GASPRICE
+            | OpcodeId::RETURNDATASIZE => self.add(Target::CallContext, 1),
+            OpcodeId::CALLDATALOAD => {
+                // the offset overflowing u64 loads zero
+                if stack_u64(step, 0).map_or(true, |offset| offset < u64::MAX) {
+                    if is_root {
+                        // TxId and CallDataLength
+                        self.add(Target::CallContext, 2);
+                    } else {
+                        // CallerId, CallDataLength and CallDataOffset, and the
+                        // two words of the caller memory
+                        self.add(Target::CallContext, 3);
+                        self.add(Target::Memory, 2);
+                    }
+                }
+            }
+            OpcodeId::LOG0 | OpcodeId::LOG1 | OpcodeId::LOG2 | OpcodeId::LOG3 | OpcodeId::LOG4 => {
+                // TxId, IsStatic, CalleeAddress and IsPersistent
+                self.add(Target::CallContext, 4);
+                if status.is_persistent {
+                    let topics = op.postfix().unwrap_or_default() as usize;
+                    let offset = stack_u64(step, 0);
+                    let size = copy_size(step, 1);
+                    let words = copy_words(offset, offset, size);
+                    self.add(Target::Memory, words);
+                    self.add(Target::TxLog, 1 + topics + words);
+                    self.stats.copy_bytes += size as usize;
+                }
+            }
+            OpcodeId::STOP => {
+                // IsSuccess
+                self.add(Target::CallContext, 1);
+                if let Some(caller) = caller {
+                    self.add(Target::CallContext, RESTORE_CONTEXT_OPS);
+                    caller.last_callee_return_offset = Some(0);
+                }
+            }
+            OpcodeId::RETURN | OpcodeId::REVERT => {
+                let offset = stack_u64(step, 0);
+                // the traced return data of the caller is the returned one
+                let length = stack_u64(step, 1).unwrap_or_else(|| {
+                    next_step
+                        .filter(|next| next.depth as usize + 1 == depth)
+                        .and_then(|next| next.return_data.as_ref())
+                        .map_or(0, |return_data| return_data.len() as u64)
+                });
+                let deploys_code = op == OpcodeId::RETURN && frame.is_create;
+                // IsSuccess
+                self.add(Target::CallContext, 1);
+                if deploys_code && status.is_success && length > 0 {
+                    self.add(Target::CallContext, DEPLOY_CALL_CONTEXT_OPS);
+                    self.add(Target::Account, DEPLOY_ACCOUNT_OPS);
+                    self.add(Target::Memory, copy_words(offset, offset, length));
+                    self.stats.copy_bytes += length as usize;
+                }
+                if let Some(caller) = caller {
+                    self.add(Target::CallContext, RESTORE_CONTEXT_OPS);
+                    if !frame.is_create {
+                        // ReturnDataOffset and ReturnDataLength
+                        self.add(Target::CallContext, 2);
+                        let copy_length = frame
+                            .return_data
+                            .map_or(length, |(_, return_length)| length.min(return_length));
+                        let dst = frame.return_data.map(|(offset, _)| offset);
+                        self.add(Target::Memory, 2 * copy_words(offset, dst, copy_length));
+                        self.stats.copy_bytes += copy_length as usize;
+                    }
+                    caller.last_callee_return_offset = if deploys_code { Some(0) } else { offset };
+                } else {
+                    // IsPersistent
+                    self.add(Target::CallContext, 1);
+                }
+            }
+            OpcodeId::CALL | OpcodeId::CALLCODE | OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => {
+                let has_value = matches!(op, OpcodeId::CALL | OpcodeId::CALLCODE);
+                let (args, ret) = if has_value { (3, 5) } else { (2, 4) };
+                let address = stack_nth(step, 1).map(|address| address.to_address());
+                let value = if has_value {
+                    stack_nth(step, 2).unwrap_or_default()
+                } else {
+                    Word::zero()
+                };
+                let callee_exists = address.map_or(true, |address| self.accounts.exists(&address));
+                let code_size = address.map(|address| {
+                    if callee_exists {
+                        self.accounts.code_size(&address)
+                    } else {
+                        0
+                    }
+                });
+                let is_precompile = address.map_or(false, |address| is_precompiled(&address));
+                let enters_code = next_step.map_or(false, |next| next.depth as usize == depth + 1);
+                // a callee with code which isn't entered failed the depth or
+                // balance check
+                let is_precheck_ok =
+                    enters_code || is_precompile || code_size.map_or(true, |size| size == 0);
+
+                self.add(Target::CallContext, CALL_CONTEXT_OPS);
+                if op == OpcodeId::DELEGATECALL {
+                    self.add(Target::CallContext, DELEGATECALL_CONTEXT_OPS);
+                }
+                // callee code hash and caller balance reads
+                self.add(Target::Account, 2);
+                self.add(Target::TxAccessListAccount, 1);
+                if op == OpcodeId::CALL && is_precheck_ok && !value.is_zero() {
+                    if !callee_exists {
+                        self.add(Target::Account, CREATE_ACCOUNT_OPS);
+                    }
+                    self.add(Target::Account, 2);
+                    if let Some(address) = address {
+                        self.accounts.existing.insert(address);
+                    }
+                }
+
+                if is_precheck_ok && is_precompile {
+                    self.add(Target::CallContext, PRECOMPILE_CALL_OPS);
+                    let args_offset = stack_u64(step, args);
+                    let args_length = stack_u64(step, args + 1).unwrap_or_default();
+                    let precompile_call = PrecompileCalls::from(address.unwrap_or_default().0[19]);
+                    let input_length = precompile_call
+                        .input_len()
+                        .map_or(args_length, |input_len| args_length.min(input_len as u64));
+                    self.add(
+                        Target::Memory,
+                        copy_words(args_offset, args_offset, input_length),
+                    );
+                    self.stats.copy_bytes += input_length as usize;
+                    // the step of the precompile
+                    self.stats.steps += 1;
+                    frame.last_callee_return_offset = Some(0);
+                } else if enters_code {
+                    self.add(Target::CallContext, CALL_SWITCH_CONTEXT_OPS);
+                    callee = Some(Frame {
+                        call_data: stack_u64(step, args).zip(stack_u64(step, args + 1)),
+                        return_data: stack_u64(step, ret).zip(stack_u64(step, ret + 1)),
+                        code_size,
+                        ..Default::default()
+                    });
+                } else {
+                    self.add(Target::CallContext, LAST_CALLEE_OPS);
+                    frame.last_callee_return_offset = Some(0);
+                }
+            }
+            OpcodeId::CREATE | OpcodeId::CREATE2 => {
+                let value = stack_nth(step, 0).unwrap_or_default();
+                let offset = stack_u64(step, 1);
+                let size = copy_size(step, 2);
+                self.add(Target::CallContext, CREATE_CONTEXT_OPS);
+                self.add(Target::TxAccessListAccount, 1);
+                self.add(
+                    Target::Account,
+                    CREATE_ACCOUNT_UPDATE_OPS + CREATE_ACCOUNT_OPS,
+                );
+                if !value.is_zero() {
+                    self.add(Target::Account, 2);
+                }
+                self.add(Target::Memory, copy_words(offset, offset, size));
+                self.stats.copy_bytes += size as usize;
+                if next_step.map_or(false, |next| next.depth as usize == depth + 1) {
+                    self.add(Target::CallContext, CREATE_CALLEE_CONTEXT_OPS);
+                    callee = Some(Frame {
+                        is_create: true,
+                        call_data: Some((0, 0)),
+                        code_size: stack_u64(step, 2),
+                        ..Default::default()
+                    });
+                } else {
+                    self.add(Target::CallContext, LAST_CALLEE_OPS);
+                    frame.last_callee_return_offset = Some(0);
+                }
+            }
+            OpcodeId::SELFDESTRUCT => {
+                // the balance moved to the receiver isn't known, its transfer
+                // is counted as a balance write of each account
+                self.add(Target::TxAccessListAccount, 1);
+                self.add(Target::Account, 2 + 2);
+                if let Some(caller) = caller {
+                    self.add(Target::CallContext, RESTORE_CONTEXT_OPS);
+                    caller.last_callee_return_offset = Some(0);
+                }
+            }
+            _ => {}
+        }
+        callee
+    }
+}
+
+impl CircuitInputBuilder {
+    /// Compute the [`BlockStats`] of a block from its geth traces, without
+    /// generating the execution steps and operations of the witness, which
+    /// makes it much faster than [`Self::handle_block`]. The L1 fee of the txs
+    /// is read with `l1_fee_schema`.
+    pub fn dry_run(geth_data: &GethData, l1_fee_schema: L1FeeSchema) -> BlockStats {
+        let mut dry_run = DryRun {
+            stats: BlockStats::default(),
+            accounts: Accounts::new(&geth_data.accounts),
+        };
+        for (tx, trace) in geth_data
+            .eth_block
+            .transactions
+            .iter()
+            .zip(geth_data.geth_traces.iter())
+        {
+            dry_run.handle_tx(geth_data, l1_fee_schema, tx, trace);
+        }
+
+        // EndBlock: the TxId of the last tx, and the withdraw root
+        if dry_run.stats.txs > 0 {
+            dry_run.add(Target::CallContext, 1);
+        }
+        dry_run.add(Target::Storage, 1);
+        let block_num = geth_data.eth_block.number.unwrap_or_default().as_u64();
+        if curie::is_curie_fork_block(geth_data.chain_id, block_num) {
+            dry_run.add(Target::Account, CURIE_ACCOUNT_OPS);
+            dry_run.add(Target::Storage, CURIE_STORAGE_OPS);
+        }
+
+        log::debug!("dry run stats: {:?}", dry_run.stats);
+        dry_run.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_input_builder::CopyDataType, mock::BlockData};
+    use eth_types::{bytecode, Bytes};
+    use mock::{eth, TestContext, MOCK_ACCOUNTS};
+
+    #[test]
+    fn dry_run_block_stats() {
+        // called with the 0x40 bytes of call data from 0x00, and returns 0x20
+        // bytes to 0x80
+        let callee_code = bytecode! {
+            .op_calldatacopy(0x03, 0x01, 0x21)
+            .op_calldataload(0x02)
+            POP
+            .op_mstore(0x00, 0xabcd)
+            .op_return(0x00, 0x30)
+        };
+        let code = bytecode! {
+            .op_calldatacopy(0x00, 0x04, 0x40)
+            .op_sha3(0x00, 0x40)
+            POP
+            .op_mstore8(0x45, 0x2a)
+            .op_mload(0x07)
+            POP
+            .op_exp(0x02, 0x03)
+            POP
+            .op_sstore(0x00, 0x01)
+            .op_sload(0x00)
+            POP
+            .op_log2(0x10, 0x25, 0x01, 0x02)
+            CALLER
+            CALLVALUE
+            ADDRESS
+            SELFBALANCE
+            DUP2
+            SWAP3
+            POP
+            POP
+            POP
+            POP
+            .op_codecopy(0x60, 0x02, 0x23)
+            .op_call(0xffff, MOCK_ACCOUNTS[1], 0x00, 0x00, 0x40, 0x80, 0x20)
+            POP
+            RETURNDATASIZE
+            POP
+            .op_returndatacopy(0xa0, 0x08, 0x10)
+            .op_return(0x00, 0x20)
+        };
+        let block: GethData = TestContext::<3, 2>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(callee_code);
+                accs[2].address(MOCK_ACCOUNTS[2]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[2].address)
+                    .to(accs[0].address)
+                    .input(Bytes::from(vec![0x5a; 0x44]));
+                // a transfer creating its receiver
+                txs[1]
+                    .from(accs[2].address)
+                    .to(MOCK_ACCOUNTS[3])
+                    .value(eth(1));
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let stats = CircuitInputBuilder::dry_run(&block, L1FeeSchema::default());
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let container = &builder.block.container;
+        let rw_ops: HashMap<Target, usize> = [
+            (Target::Memory, container.memory.len()),
+            (Target::Stack, container.stack.len()),
+            (Target::Storage, container.storage.len()),
+            (Target::TransientStorage, container.transient_storage.len()),
+            (
+                Target::TxAccessListAccount,
+                container.tx_access_list_account.len(),
+            ),
+            (
+                Target::TxAccessListAccountStorage,
+                container.tx_access_list_account_storage.len(),
+            ),
+            (Target::TxRefund, container.tx_refund.len()),
+            (Target::Account, container.account.len()),
+            (Target::CallContext, container.call_context.len()),
+            (Target::TxReceipt, container.tx_receipt.len()),
+            (Target::TxLog, container.tx_log.len()),
+        ]
+        .into_iter()
+        .filter(|(_, n)| *n > 0)
+        .collect();
+        let copy_bytes: usize = builder
+            .block
+            .copy_events
+            .iter()
+            .map(|event| event.copy_length() as usize)
+            .sum();
+        let keccak_bytes: usize = builder
+            .block
+            .copy_events
+            .iter()
+            .filter(|event| event.dst_type == CopyDataType::RlcAcc)
+            .map(|event| event.copy_length() as usize)
+            .sum();
+
+        assert_eq!(stats.txs, 2);
+        assert_eq!(
+            stats.steps,
+            builder.block.txs.iter().map(|tx| tx.steps().len()).sum()
+        );
+        assert_eq!(stats.exp_events, builder.block.exp_events.len());
+        if cfg!(feature = "enable-stack") {
+            assert_eq!(stats.rw_ops, rw_ops);
+            assert_eq!(stats.copy_bytes, copy_bytes);
+            assert_eq!(stats.keccak_bytes, keccak_bytes);
+        } else {
+            // the copies are bounded from the gas costs
+            for (target, n) in rw_ops {
+                match target {
+                    Target::Memory | Target::TxLog => assert!(stats.rw_ops[&target] >= n),
+                    _ => assert_eq!(stats.rw_ops[&target], n, "{target:?}"),
+                }
+            }
+            assert!(stats.copy_bytes >= copy_bytes);
+            assert!(stats.keccak_bytes >= keccak_bytes);
+        }
+    }
+}
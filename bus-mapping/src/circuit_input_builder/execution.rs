@@ -1,13 +1,13 @@
 //! Execution step related module.
 
 use std::{
-    marker::PhantomData,
+    io,
     ops::{Add, Mul, Neg},
 };
 
 use crate::{
-    circuit_input_builder::CallContext,
-    error::{ExecError, OogError},
+    circuit_input_builder::{CallContext, WitnessArena},
+    error::{Error, ExecError, OogError},
     exec_trace::OperationRef,
     operation::RWCounter,
     precompile::{PrecompileAuxData, PrecompileCalls},
@@ -15,10 +15,10 @@ use crate::{
 use eth_types::{
     evm_types::{memory::MemoryWordRange, Gas, GasCost, MemoryAddress, OpcodeId, ProgramCounter},
     sign_types::SignData,
-    Address, GethExecStep, ToLittleEndian, Word, H256, U256,
+    Address, Bytes, GethExecStep, ToLittleEndian, Word, H256, U256,
 };
 use ethers_core::k256::elliptic_curve::subtle::CtOption;
-use gadgets::impl_expr;
+use gadgets::{binary_number_tag, impl_expr};
 use halo2_proofs::{
     arithmetic::{CurveAffine, Field as Halo2Field},
     halo2curves::{
@@ -27,6 +27,7 @@ use halo2_proofs::{
     },
     plonk::Expression,
 };
+use strum_macros::EnumIter;
 
 /// An execution step of the EVM.
 #[derive(Clone, Debug)]
@@ -47,8 +48,13 @@ pub struct ExecStep {
     pub gas_cost: GasCost,
     /// Accumulated gas refund
     pub gas_refund: Gas,
+    /// Return data of the last callee of the call, as captured by the
+    /// tracer, if any
+    pub return_data: Option<Bytes>,
     /// Call index within the Transaction.
     pub call_index: usize,
+    /// Call depth, 1 for the root call and 0 for the virtual steps.
+    pub depth: u16,
     /// The global counter when this step was executed.
     pub rwc: RWCounter,
     /// Reversible Write Counter.  Counter of write operations in the call that
@@ -67,6 +73,10 @@ pub struct ExecStep {
     pub error: Option<ExecError>,
     /// Optional auxiliary data that is attached to precompile call internal states.
     pub aux_data: Option<PrecompileAuxData>,
+    /// Whether the step has no circuit support, and is only handled by a
+    /// pass-through gadget under
+    /// [`UnsupportedOpcodePolicy::DummyGadget`](crate::circuit_input_builder::UnsupportedOpcodePolicy::DummyGadget).
+    pub unsupported: bool,
 }
 
 impl ExecStep {
@@ -86,7 +96,9 @@ impl ExecStep {
             gas_left: step.gas,
             gas_cost: step.gas_cost,
             gas_refund: step.refund,
+            return_data: step.return_data.clone(),
             call_index: call_ctx.index,
+            depth: step.depth,
             rwc,
             reversible_write_counter,
             reversible_write_counter_delta: 0,
@@ -95,6 +107,7 @@ impl ExecStep {
             copy_rw_counter_delta: 0,
             error: None,
             aux_data: None,
+            unsupported: false,
         }
     }
 
@@ -115,6 +128,46 @@ impl ExecStep {
     pub fn is_precompile_oog_err(&self) -> bool {
         matches!(self.error, Some(ExecError::OutOfGas(OogError::Precompile)))
     }
+
+    /// The error of the step with where it happened, if the step has an
+    /// error.
+    pub fn error_context(&self) -> Option<ExecErrorContext> {
+        self.error.clone().map(|error| ExecErrorContext {
+            code: error.code(),
+            error,
+            exec_state: self.exec_state.clone(),
+            pc: self.pc,
+            depth: self.depth,
+            call_index: self.call_index,
+        })
+    }
+}
+
+/// An [`ExecError`] with the step it happened at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecErrorContext {
+    /// The error
+    pub error: ExecError,
+    /// Numeric code of the error, see [`ExecError::code`]
+    pub code: u16,
+    /// Execution state of the step
+    pub exec_state: ExecState,
+    /// Program counter of the step
+    pub pc: ProgramCounter,
+    /// Call depth of the step
+    pub depth: u16,
+    /// Index of the call of the step within the transaction
+    pub call_index: usize,
+}
+
+impl std::fmt::Display for ExecErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at {:?} pc {} depth {} call {}",
+            self.error, self.exec_state, self.pc.0, self.depth, self.call_index
+        )
+    }
 }
 
 impl Default for ExecStep {
@@ -127,7 +180,9 @@ impl Default for ExecStep {
             gas_left: Gas(0),
             gas_cost: GasCost(0),
             gas_refund: Gas(0),
+            return_data: None,
             call_index: 0,
+            depth: 0,
             rwc: RWCounter(0),
             reversible_write_counter: 0,
             reversible_write_counter_delta: 0,
@@ -136,6 +191,7 @@ impl Default for ExecStep {
             copy_rw_counter_delta: 0,
             error: None,
             aux_data: None,
+            unsupported: false,
         }
     }
 }
@@ -205,137 +261,45 @@ impl ExecState {
     }
 }
 
-/// Defines the various source/destination types for a copy event.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum CopyDataType {
-    /// When we need to pad the Copy rows of the circuit up to a certain maximum
-    /// with rows that are not "useful".
-    Padding,
-    /// When the source for the copy event is the bytecode table.
-    Bytecode,
-    /// When the source/destination for the copy event is memory.
-    Memory,
-    /// When the source for the copy event is tx's calldata.
-    TxCalldata,
-    /// When the destination for the copy event is tx's log.
-    TxLog,
-    /// When the destination rows are not directly for copying but for a special
-    /// scenario where we wish to accumulate the value (RLC) over all rows.
-    /// This is used for Copy Lookup from SHA3 opcode verification.
-    RlcAcc,
-    /// When copy event is access-list addresses (EIP-2930), source is tx-table
-    /// and destination is rw-table.
-    AccessListAddresses,
-    /// When copy event is access-list storage keys (EIP-2930), source is
-    /// tx-table and destination is rw-table.
-    AccessListStorageKeys,
-}
-
-impl CopyDataType {
-    /// How many bits are necessary to represent a copy data type.
-    pub const N_BITS: usize = 3usize;
-}
-const NUM_COPY_DATA_TYPES: usize = 8usize;
-pub struct CopyDataTypeIter {
-    idx: usize,
-    back_idx: usize,
-    marker: PhantomData<()>,
-}
-impl CopyDataTypeIter {
-    fn get(&self, idx: usize) -> Option<CopyDataType> {
-        match idx {
-            0usize => Some(CopyDataType::Padding),
-            1usize => Some(CopyDataType::Bytecode),
-            2usize => Some(CopyDataType::Memory),
-            3usize => Some(CopyDataType::TxCalldata),
-            4usize => Some(CopyDataType::TxLog),
-            5usize => Some(CopyDataType::RlcAcc),
-            6usize => Some(CopyDataType::AccessListAddresses),
-            7usize => Some(CopyDataType::AccessListStorageKeys),
-            _ => None,
-        }
-    }
-}
-impl strum::IntoEnumIterator for CopyDataType {
-    type Iterator = CopyDataTypeIter;
-    fn iter() -> CopyDataTypeIter {
-        CopyDataTypeIter {
-            idx: 0,
-            back_idx: 0,
-            marker: PhantomData,
-        }
-    }
-}
-impl Iterator for CopyDataTypeIter {
-    type Item = CopyDataType;
-    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
-        #[allow(clippy::iter_nth_zero)]
-        self.nth(0)
-    }
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        let t = if self.idx + self.back_idx >= NUM_COPY_DATA_TYPES {
-            0
-        } else {
-            NUM_COPY_DATA_TYPES - self.idx - self.back_idx
-        };
-        (t, Some(t))
-    }
-    fn nth(&mut self, n: usize) -> Option<<Self as Iterator>::Item> {
-        let idx = self.idx + n + 1;
-        if idx + self.back_idx > NUM_COPY_DATA_TYPES {
-            self.idx = NUM_COPY_DATA_TYPES;
-            None
-        } else {
-            self.idx = idx;
-            self.get(idx - 1)
-        }
-    }
-}
-impl ExactSizeIterator for CopyDataTypeIter {
-    fn len(&self) -> usize {
-        self.size_hint().0
-    }
-}
-impl DoubleEndedIterator for CopyDataTypeIter {
-    fn next_back(&mut self) -> Option<<Self as Iterator>::Item> {
-        let back_idx = self.back_idx + 1;
-        if self.idx + back_idx > NUM_COPY_DATA_TYPES {
-            self.back_idx = NUM_COPY_DATA_TYPES;
-            None
-        } else {
-            self.back_idx = back_idx;
-            self.get(NUM_COPY_DATA_TYPES - self.back_idx)
-        }
-    }
-}
-
-impl From<CopyDataType> for usize {
-    fn from(t: CopyDataType) -> Self {
-        match t {
-            CopyDataType::Padding => 0,
-            CopyDataType::Bytecode => 1,
-            CopyDataType::Memory => 2,
-            CopyDataType::TxCalldata => 3,
-            CopyDataType::TxLog => 4,
-            CopyDataType::RlcAcc => 5,
-            CopyDataType::AccessListAddresses => 6,
-            CopyDataType::AccessListStorageKeys => 7,
-        }
+binary_number_tag! {
+    /// Defines the various source/destination types for a copy event.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+    pub enum CopyDataType {
+        /// When we need to pad the Copy rows of the circuit up to a certain maximum
+        /// with rows that are not "useful".
+        Padding,
+        /// When the source for the copy event is the bytecode table.
+        Bytecode,
+        /// When the source/destination for the copy event is memory.
+        Memory,
+        /// When the source for the copy event is tx's calldata.
+        TxCalldata,
+        /// When the destination for the copy event is tx's log.
+        TxLog,
+        /// When the destination rows are not directly for copying but for a special
+        /// scenario where we wish to accumulate the value (RLC) over all rows.
+        /// This is used for Copy Lookup from SHA3 opcode verification.
+        RlcAcc,
+        /// When copy event is access-list addresses (EIP-2930), source is tx-table
+        /// and destination is rw-table.
+        AccessListAddresses,
+        /// When copy event is access-list storage keys (EIP-2930), source is
+        /// tx-table and destination is rw-table.
+        AccessListStorageKeys,
+        /// When the destination for the copy event is the input of a precompile
+        /// call. Like `RlcAcc`, the RLC of the copied bytes is accumulated over
+        /// all rows and checked against the one given to the precompile gadget.
+        PrecompileInput,
+        /// When the source for the copy event is the output of a precompile call,
+        /// which is written to the callee memory. The RLC of the copied bytes is
+        /// checked against the one given by the precompile gadget.
+        PrecompileOutput,
     }
 }
 
 impl From<&CopyDataType> for u64 {
     fn from(t: &CopyDataType) -> Self {
-        match t {
-            CopyDataType::Padding => 0,
-            CopyDataType::Bytecode => 1,
-            CopyDataType::Memory => 2,
-            CopyDataType::TxCalldata => 3,
-            CopyDataType::TxLog => 4,
-            CopyDataType::RlcAcc => 5,
-            CopyDataType::AccessListAddresses => 6,
-            CopyDataType::AccessListStorageKeys => 7,
-        }
+        *t as u64
     }
 }
 
@@ -379,30 +343,113 @@ pub enum NumberOrHash {
 ///
 /// Additionally, when the destination is memory, `bytes_write_prev` holds the memory content
 /// *before* the write.
-#[derive(Clone, Debug, Default)]
+///
+/// The bytes are stored in the copy arena of the [`Blocks`](super::Blocks), `copy_bytes`, which
+/// must be given to read them.
+#[derive(Clone, Copy, Debug)]
 pub struct CopyBytes {
-    /// Represents the list of (bytes, is_code, mask) copied during this copy event
-    pub bytes: Vec<(u8, bool, bool)>,
-    /// Represents the list of (bytes, is_code, mask) read to copy during this copy event, used for
-    /// memory to memory write case
-    pub aux_bytes: Option<Vec<(u8, bool, bool)>>,
-    /// Represents the list of bytes before this copy event, it is required for memory write copy
-    /// event
-    pub bytes_write_prev: Option<Vec<u8>>,
+    /// Index in the arena of the (bytes, is_code, mask) copied during this copy event
+    bytes: usize,
+    /// Index in the arena of the (bytes, is_code, mask) read to copy during this copy event, used
+    /// for memory to memory write case
+    aux_bytes: Option<usize>,
+    /// Index in the arena of the bytes before this copy event, it is required for memory write
+    /// copy event
+    bytes_write_prev: Option<usize>,
+    /// Number of copy steps, including the masked ones
+    full_length: u64,
+    /// Number of copy steps, excluding the masked ones
+    copy_length: u64,
 }
 
 impl CopyBytes {
-    /// construct CopyBytes instance
+    /// construct CopyBytes instance, moving the bytes to `arena`
     pub fn new(
+        arena: &mut WitnessArena,
         bytes: Vec<(u8, bool, bool)>,
         aux_bytes: Option<Vec<(u8, bool, bool)>>,
         bytes_write_prev: Option<Vec<u8>>,
-    ) -> Self {
-        Self {
-            bytes,
-            aux_bytes,
-            bytes_write_prev,
-        }
+    ) -> Result<Self, Error> {
+        let checkpoint = arena.len();
+        let push = || -> io::Result<Self> {
+            Ok(Self {
+                bytes: arena.push(CopySteps::encode(&bytes))?,
+                aux_bytes: aux_bytes
+                    .map(|steps| arena.push(CopySteps::encode(&steps)))
+                    .transpose()?,
+                bytes_write_prev: bytes_write_prev.map(|prev| arena.push(prev)).transpose()?,
+                full_length: bytes.len() as u64,
+                copy_length: bytes.iter().filter(|&step| !step.2).count() as u64,
+            })
+        };
+        push().map_err(|e| {
+            arena.truncate(checkpoint);
+            Error::IoError(e)
+        })
+    }
+
+    /// The (bytes, is_code, mask) copied during this copy event
+    pub fn bytes<'a>(&self, arena: &'a WitnessArena) -> CopySteps<'a> {
+        CopySteps(arena.get(self.bytes).expect("copy bytes in the arena"))
+    }
+
+    /// The (bytes, is_code, mask) read to copy during this copy event, for memory to memory copies
+    pub fn aux_bytes<'a>(&self, arena: &'a WitnessArena) -> Option<CopySteps<'a>> {
+        self.aux_bytes
+            .map(|index| CopySteps(arena.get(index).expect("copy bytes in the arena")))
+    }
+
+    /// The memory bytes before this copy event, for copies writing memory
+    pub fn bytes_write_prev<'a>(&self, arena: &'a WitnessArena) -> Option<&'a [u8]> {
+        self.bytes_write_prev
+            .map(|index| arena.get(index).expect("copy bytes in the arena"))
+    }
+}
+
+/// Copy steps of a copy event, each stored as its byte followed by a byte of flags.
+#[derive(Clone, Copy, Debug)]
+pub struct CopySteps<'a>(&'a [u8]);
+
+impl CopySteps<'_> {
+    const IS_CODE: u8 = 1;
+    const MASK: u8 = 2;
+
+    fn encode(steps: &[(u8, bool, bool)]) -> Vec<u8> {
+        steps
+            .iter()
+            .flat_map(|&(value, is_code, mask)| {
+                [
+                    value,
+                    (is_code as u8 * Self::IS_CODE) | (mask as u8 * Self::MASK),
+                ]
+            })
+            .collect()
+    }
+
+    /// Number of steps.
+    pub fn len(&self) -> usize {
+        self.0.len() / 2
+    }
+
+    /// Return true if there is no step.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over the (bytes, is_code, mask) of the steps.
+    pub fn iter(&self) -> impl Iterator<Item = (u8, bool, bool)> + Clone + '_ {
+        self.0.chunks_exact(2).map(|step| {
+            (
+                step[0],
+                step[1] & Self::IS_CODE != 0,
+                step[1] & Self::MASK != 0,
+            )
+        })
+    }
+
+    /// Collect the (bytes, is_code, mask) of the steps.
+    pub fn to_vec(&self) -> Vec<(u8, bool, bool)> {
+        self.iter().collect()
     }
 }
 
@@ -480,12 +527,12 @@ pub type CopyEventPrevBytes = Vec<u8>;
 impl CopyEvent {
     /// The full length of the event, including masked segments.
     pub fn full_length(&self) -> u64 {
-        self.copy_bytes.bytes.len() as u64
+        self.copy_bytes.full_length
     }
 
     /// The length of the copied data, excluding masked segments.
     pub fn copy_length(&self) -> u64 {
-        self.copy_bytes.bytes.iter().filter(|&step| !step.2).count() as u64
+        self.copy_bytes.copy_length
     }
 
     /// Whether the source performs RW lookups in the state circuit.
@@ -505,7 +552,11 @@ impl CopyEvent {
     pub fn has_rlc(&self) -> bool {
         matches!(
             (self.src_type, self.dst_type),
-            (CopyDataType::RlcAcc, _) | (_, CopyDataType::RlcAcc) | (_, CopyDataType::Bytecode)
+            (CopyDataType::RlcAcc, _)
+                | (CopyDataType::PrecompileOutput, _)
+                | (_, CopyDataType::RlcAcc)
+                | (_, CopyDataType::PrecompileInput)
+                | (_, CopyDataType::Bytecode)
         )
     }
 
@@ -1224,8 +1275,14 @@ impl EcMulOp {
     }
 }
 
-/// The number of pairing inputs per pairing operation. If the inputs provided to the precompile
-/// call are < 4, we append (G1::infinity, G2::generator) until we have the required no. of inputs.
+/// The default number of pairing inputs per pairing operation, which is also the maximum number
+/// of pairs accepted by the ecPairing precompile. If the inputs provided to the precompile call
+/// are fewer, we append padding pairs until we have the required no. of inputs. Calls with more
+/// pairs fail.
+///
+/// The EVM and ECC circuits are sized from
+/// [`PrecompileEcParams::ec_pairing_pairs`](crate::circuit_input_builder::PrecompileEcParams),
+/// which defaults to this constant.
 pub const N_PAIRING_PER_OP: usize = 4;
 
 /// The number of bytes taken to represent a pair (G1, G2).
@@ -1319,7 +1376,7 @@ impl EcPairingPair {
     }
 
     /// Padding pair for EcPairing operation. The pairing check is done with a constant number
-    /// `N_PAIRING_PER_OP` of (G1, G2) pairs. In case EVM inputs are less in number, we pad them
+    /// `PrecompileEcParams::ec_pairing_pairs` of (G1, G2) pairs. In case EVM inputs are less in number, we pad them
     /// with `(G1::Infinity, G2::Infinity)` for simplicity.
     pub fn padding_pair() -> Self {
         Self {
@@ -1336,8 +1393,9 @@ impl EcPairingPair {
 /// EcPairing operation
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct EcPairingOp {
-    /// tuples of G1 and G2 points supplied to the ECC circuit.
-    pub pairs: [EcPairingPair; N_PAIRING_PER_OP],
+    /// tuples of G1 and G2 points supplied to the ECC circuit, padded to the configured number
+    /// of pairs per op.
+    pub pairs: Vec<EcPairingPair>,
     /// Result from the pairing check.
     pub output: Word,
     /// Input bytes to the ecPairing call.
@@ -1348,8 +1406,9 @@ pub struct EcPairingOp {
     pub return_bytes: Vec<u8>,
 }
 
-impl Default for EcPairingOp {
-    fn default() -> Self {
+impl EcPairingOp {
+    /// Filler op with `n_pairs` generator pairs, used to pad the ECC circuit and table.
+    pub fn filler(n_pairs: usize) -> Self {
         let g1_point = G1Affine::generator();
         let g2_point = G2Affine::generator();
         let g1_x = U256::from_little_endian(&g1_point.x.to_bytes());
@@ -1359,23 +1418,12 @@ impl Default for EcPairingOp {
         let g2_y0 = U256::from_little_endian(&g2_point.y.c1.to_bytes());
         let g2_y1 = U256::from_little_endian(&g2_point.y.c0.to_bytes());
         Self {
-            pairs: [
-                EcPairingPair {
-                    g1_point: (g1_x, g1_y),
-                    g2_point: (g2_x0, g2_x1, g2_y0, g2_y1),
-                },
-                EcPairingPair {
-                    g1_point: (g1_x, g1_y),
-                    g2_point: (g2_x0, g2_x1, g2_y0, g2_y1),
-                },
-                EcPairingPair {
-                    g1_point: (g1_x, g1_y),
-                    g2_point: (g2_x0, g2_x1, g2_y0, g2_y1),
-                },
+            pairs: vec![
                 EcPairingPair {
                     g1_point: (g1_x, g1_y),
                     g2_point: (g2_x0, g2_x1, g2_y0, g2_y1),
-                },
+                };
+                n_pairs
             ],
             output: Word::zero(),
             // It does not matter what the input bytes and return bytes are in this case, as this
@@ -1387,9 +1435,7 @@ impl Default for EcPairingOp {
             return_bytes: vec![],
         }
     }
-}
 
-impl EcPairingOp {
     /// Returns the uncompressed big-endian byte representation of inputs to the EcPairingOp.
     pub fn to_bytes_be(&self) -> Vec<u8> {
         self.pairs
@@ -1408,22 +1454,25 @@ impl EcPairingOp {
         self.pairs.iter().all(|pair| pair.is_valid())
     }
 
-    /// Dummy pairing op that satisfies the pairing check.
-    pub fn dummy_pairing_check_ok() -> Self {
+    /// Dummy pairing op with `n_pairs` pairs that satisfies the pairing check. It needs at least
+    /// 2 pairs.
+    pub fn dummy_pairing_check_ok(n_pairs: usize) -> Self {
+        assert!(n_pairs >= 2, "dummy pairing check needs at least 2 pairs");
         let g1 = G1Affine::from(G1Affine::generator() * Fr::from(2));
         let g1_neg = g1.neg();
         let g2 = G2Affine::from(G2Affine::generator() * Fr::from(3));
         let other_g1 = G1Affine::from(G1Affine::generator() * Fr::from(6));
         let other_g2 = G2Affine::generator();
         Self {
-            pairs: [
-                EcPairingPair::new(g1_neg, g2),
-                EcPairingPair::new(other_g1, other_g2),
-                EcPairingPair::new(G1Affine::identity(), G2Affine::generator()),
-                EcPairingPair::new(G1Affine::identity(), G2Affine::generator()),
-            ],
+            pairs: (0..n_pairs)
+                .map(|i| match i {
+                    0 => EcPairingPair::new(g1_neg, g2),
+                    1 => EcPairingPair::new(other_g1, other_g2),
+                    _ => EcPairingPair::new(G1Affine::identity(), G2Affine::generator()),
+                })
+                .collect(),
             output: 1.into(),
-            ..Default::default()
+            ..Self::filler(n_pairs)
         }
     }
 }
@@ -1459,4 +1508,151 @@ pub struct SHA256 {
     pub input: Vec<u8>,
     /// digest
     pub digest: [u8; 32],
+    /// intermediate state after each 512-bit block of the padded input, the
+    /// last one being the digest
+    pub block_digests: Vec<[u8; 32]>,
+}
+
+/// Round constants of SHA256.
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Initial state of SHA256.
+const SHA256_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Compress a 512-bit block into the SHA256 state.
+fn sha256_compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0u32; 64];
+    for (w, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *w = u32::from_be_bytes(bytes.try_into().unwrap());
+    }
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for (k, w) in SHA256_K.iter().zip(w) {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(*k)
+            .wrapping_add(w);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *s = s.wrapping_add(v);
+    }
+}
+
+impl SHA256 {
+    /// Create the event of hashing `input` into `digest`, computing the
+    /// intermediate state after each block of the padded input.
+    pub fn new(input: Vec<u8>, digest: [u8; 32]) -> Self {
+        let mut padded = input.clone();
+        padded.push(0x80);
+        padded.resize((padded.len() + 8).div_ceil(64) * 64 - 8, 0);
+        padded.extend(((input.len() * 8) as u64).to_be_bytes());
+
+        let mut state = SHA256_IV;
+        let block_digests: Vec<[u8; 32]> = padded
+            .chunks_exact(64)
+            .map(|block| {
+                sha256_compress(&mut state, block);
+                let mut block_digest = [0u8; 32];
+                for (bytes, word) in block_digest.chunks_exact_mut(4).zip(state) {
+                    bytes.copy_from_slice(&word.to_be_bytes());
+                }
+                block_digest
+            })
+            .collect();
+        debug_assert_eq!(
+            block_digests.last(),
+            Some(&digest),
+            "unexpected sha256 digest"
+        );
+
+        Self {
+            input,
+            digest,
+            block_digests,
+        }
+    }
+
+    /// Number of 512-bit blocks of the padded input.
+    pub fn blocks(&self) -> usize {
+        self.block_digests.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha256_block_digests() {
+        // (input length, number of blocks, digest) of inputs of repeated 'a'
+        for (len, blocks, digest) in [
+            (
+                0,
+                1,
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                55,
+                1,
+                "9f4390f8d30c2dd92ec9f095b65e2b9ae9b0a925a5258e241c9f1e910f734318",
+            ),
+            (
+                56,
+                2,
+                "b35439a4ac6f0948b6d6f9e3c6af0f5f590ce20f1bde7090ef7970686ec6738a",
+            ),
+            (
+                64,
+                2,
+                "ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb",
+            ),
+            (
+                1000,
+                16,
+                "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3",
+            ),
+        ] {
+            let digest: [u8; 32] = hex::decode(digest).unwrap().try_into().unwrap();
+            let event = SHA256::new(vec![b'a'; len], digest);
+            assert_eq!(event.blocks(), blocks, "input length {len}");
+            assert_eq!(
+                event.block_digests.last(),
+                Some(&digest),
+                "input length {len}"
+            );
+        }
+    }
 }
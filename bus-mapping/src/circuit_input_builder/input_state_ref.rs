@@ -2,8 +2,9 @@
 
 use super::{
     get_call_memory_offset_length, get_create_init_code, BlockContext, Blocks, Call, CallContext,
-    CallKind, CodeSource, CopyEvent, ExecState, ExecStep, ExpEvent, PrecompileEvent, Transaction,
-    TransactionContext,
+    CallKind, CodeSource, CopyEvent, DowngradedTx, ExecState, ExecStep, ExpEvent, PrecompileEvent,
+    TraceCheckKind, TraceCheckLevel, TraceMismatch, Transaction, TransactionContext,
+    UnsupportedOpcodePolicy, UnsupportedReason,
 };
 #[cfg(feature = "scroll")]
 use crate::util::KECCAK_CODE_HASH_EMPTY;
@@ -14,6 +15,7 @@ use crate::{
         InsufficientBalanceError, NonceUintOverflowError,
     },
     exec_trace::OperationRef,
+    gas::{step_gas_cost, GasStep},
     operation::{
         AccountField, AccountOp, CallContextField, CallContextOp, MemoryOp, Op, OpEnum, Operation,
         StackOp, Target, TxAccessListAccountOp, TxAccessListAccountStorageOp, TxLogField, TxLogOp,
@@ -55,6 +57,82 @@ pub struct CircuitInputStateRef<'a> {
 }
 
 impl<'a> CircuitInputStateRef<'a> {
+    /// Apply the [`UnsupportedOpcodePolicy`] of the block to a step which has
+    /// no circuit support: either fail, or record the current transaction as
+    /// downgraded so that the step can be handled with a dummy gadget.
+    pub(crate) fn handle_unsupported(&mut self, reason: UnsupportedReason) -> Result<(), Error> {
+        if self.block.unsupported_opcode_policy == UnsupportedOpcodePolicy::Error {
+            return Err(Error::Unsupported(reason));
+        }
+        let downgraded = DowngradedTx {
+            block_num: self.tx.block_num,
+            tx_hash: self.tx.hash,
+            reason,
+            skipped: false,
+        };
+        if !self.block.downgraded_txs.contains(&downgraded) {
+            self.block.downgraded_txs.push(downgraded);
+        }
+        Ok(())
+    }
+
+    /// Apply the [`TraceCheckConfig`] of the block to a mismatch of `kind`
+    /// between the reconstructed state and the trace before `geth_step`:
+    /// either fail, or record it in the report of the block.
+    pub(crate) fn report_trace_mismatch(
+        &mut self,
+        geth_step: &GethExecStep,
+        kind: TraceCheckKind,
+        detail: String,
+    ) -> Result<(), Error> {
+        let mismatch = TraceMismatch {
+            block_num: self.tx.block_num,
+            tx_hash: self.tx.hash,
+            pc: geth_step.pc,
+            op: geth_step.op,
+            depth: geth_step.depth,
+            kind,
+            detail,
+        };
+        match self.block.trace_check.level(kind) {
+            TraceCheckLevel::Off => {}
+            TraceCheckLevel::Warn => {
+                log::warn!("trace mismatch: {mismatch:?}");
+                self.block.trace_mismatches.push(mismatch);
+            }
+            TraceCheckLevel::Error => return Err(Error::TraceMismatch(Box::new(mismatch))),
+        }
+        Ok(())
+    }
+
+    /// Check the gas cost of `geth_step` computed from `step` by
+    /// [`step_gas_cost`] against the one in the trace, reporting a mismatch
+    /// as a [`TraceCheckKind::Gas`] one.
+    pub(crate) fn check_step_gas_cost(
+        &mut self,
+        geth_step: &GethExecStep,
+        step: &GasStep,
+    ) -> Result<(), Error> {
+        if !self.block.trace_check.is_enabled(TraceCheckKind::Gas) {
+            return Ok(());
+        }
+        let gas_cost = step_gas_cost(step);
+        if gas_cost != Some(geth_step.gas_cost.0) {
+            let detail = format!(
+                "computed gas cost {gas_cost:?} != traced gas cost {}",
+                geth_step.gas_cost.0
+            );
+            self.report_trace_mismatch(geth_step, TraceCheckKind::Gas, detail)?;
+        }
+        Ok(())
+    }
+
+    /// Id of the tx whose call data section in the tx table holds the call
+    /// data of the current tx, which is the source of the tx call data copies.
+    pub fn call_data_tx_id(&self) -> usize {
+        self.tx.shared_call_data_tx_id.unwrap_or(self.tx_ctx.id())
+    }
+
     /// Create a new step from a `GethExecStep`
     pub fn new_step(&self, geth_step: &GethExecStep) -> Result<ExecStep, Error> {
         let call_ctx = self.tx_ctx.call_ctx()?;
@@ -104,6 +182,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
             memory_size: call_ctx.memory.len(),
             call_index: call_ctx.index,
+            depth: ref_step.depth,
             reversible_write_counter: call_ctx.reversible_write_counter,
             rwc: self.block_ctx.rwc,
             log_id: self.tx_ctx.log_id,
@@ -1649,7 +1728,9 @@ impl<'a> CircuitInputStateRef<'a> {
         step: &GethExecStep,
         next_step: Option<&GethExecStep>,
     ) -> Result<Option<ExecError>, Error> {
-        if matches!(step.op, OpcodeId::INVALID(_)) {
+        if matches!(step.op, OpcodeId::INVALID(_))
+            && self.block.opcode_registry.get(&step.op).is_none()
+        {
             return Ok(Some(ExecError::InvalidOpcode));
         }
 
@@ -1691,6 +1772,15 @@ impl<'a> CircuitInputStateRef<'a> {
         let call_ctx = self.call_ctx()?;
         #[cfg(feature = "enable-stack")]
         assert_eq!(call_ctx.stack, step.stack);
+        // The trace may not report the overflows of a stack limit below the
+        // one of the EVM that produced it.
+        if self
+            .block
+            .chain_spec
+            .is_stack_overflow(step.op, call_ctx.stack.len())
+        {
+            return Ok(Some(ExecError::StackOverflow));
+        }
         // get value first if call/create
         let value = match step.op {
             OpcodeId::CALL | OpcodeId::CALLCODE => call_ctx.stack.nth_last(2)?,
@@ -1741,7 +1831,7 @@ impl<'a> CircuitInputStateRef<'a> {
                         return Ok(Some(ExecError::MaxCodeSizeExceeded));
                     } else if length > Word::zero()
                         && !call_ctx.memory.is_empty()
-                        && call_ctx.memory.0.get(offset.low_u64() as usize) == Some(&0xef)
+                        && call_ctx.memory.get(offset.low_u64() as usize) == Some(0xef)
                     {
                         return Ok(Some(ExecError::InvalidCreationCode));
                     } else if Word::from(200u64) * length > Word::from(step.gas.0) {
@@ -1793,7 +1883,7 @@ impl<'a> CircuitInputStateRef<'a> {
         ) && !next_success
             && next_pc != 0
         {
-            if step.depth == 1025 {
+            if self.block.chain_spec.is_depth_exceeded(step.depth as usize) {
                 return Ok(Some(ExecError::Depth(match step.op {
                     OpcodeId::CALL
                     | OpcodeId::CALLCODE
@@ -2136,16 +2226,16 @@ impl<'a> CircuitInputStateRef<'a> {
         let call_data_offset = self.call()?.call_data_offset;
         let call_data = if call_data_length != 0 {
             let ends = call_data_offset + call_data_length;
-            &caller_memory.0[..ends as usize]
+            caller_memory.read_chunk(MemoryRange::new_with_length(0, ends))
         } else {
-            &caller_memory.0[..call_data_offset as usize]
+            caller_memory.read_chunk(MemoryRange::new_with_length(0, call_data_offset))
         };
         let call_ctx = self.call_ctx_mut()?;
         let (src_range, dst_range, write_slot_bytes) = combine_copy_slot_bytes(
             src_addr.into().0,
             dst_addr.into().0,
             copy_length,
-            call_data,
+            &call_data,
             &mut call_ctx.memory,
             false,
         );
@@ -2196,18 +2286,18 @@ impl<'a> CircuitInputStateRef<'a> {
         let last_callee_memory = self.call()?.last_callee_memory.clone();
         let return_data_length = self.call()?.last_callee_return_data_length;
         let return_data_offset = self.call()?.last_callee_return_data_offset;
-        let return_data: &[u8] = if return_data_length != 0 {
+        let return_data = if return_data_length != 0 {
             let ends = return_data_offset + return_data_length;
-            &last_callee_memory.0[..ends as usize]
+            last_callee_memory.read_chunk(MemoryRange::new_with_length(0, ends))
         } else {
-            &last_callee_memory.0[..return_data_offset as usize]
+            last_callee_memory.read_chunk(MemoryRange::new_with_length(0, return_data_offset))
         };
         let call_ctx = self.call_ctx_mut()?;
         let (src_range, dst_range, write_slot_bytes) = combine_copy_slot_bytes(
             src_addr.into().0,
             dst_addr.into().0,
             copy_length,
-            return_data,
+            &return_data,
             &mut call_ctx.memory,
             false,
         );
@@ -2272,7 +2362,7 @@ impl<'a> CircuitInputStateRef<'a> {
             src_addr.into().0,
             dst_addr.into().0,
             copy_length,
-            &memory.0,
+            &memory.to_vec(),
             &mut call_ctx.memory,
             true,
         );
@@ -2457,12 +2547,18 @@ fn combine_copy_slot_bytes(
         .collect();
     let copy_padding_bytes = repeat(0).take(src_copy_end - src_addr_end);
     let end_padding_bytes = repeat(0).take(dst_end_slot - dst_addr_end);
-    let slot_bytes: Vec<u8> = dst_memory[dst_begin_slot..dst_addr]
-        .iter()
-        .cloned()
+    let slot_bytes: Vec<u8> = dst_memory
+        .read_chunk(MemoryRange::new_with_length(
+            dst_begin_slot,
+            dst_addr - dst_begin_slot,
+        ))
+        .into_iter()
         .chain(bytes_to_copy)
         .chain(copy_padding_bytes)
-        .chain(dst_memory[dst_copy_end..dst_addr_end].iter().cloned())
+        .chain(dst_memory.read_chunk(MemoryRange::new_with_length(
+            dst_copy_end,
+            dst_addr_end - dst_copy_end,
+        )))
         .chain(end_padding_bytes)
         .collect();
 
@@ -0,0 +1,268 @@
+//! Property-based tests running random bytecode through the
+//! [`CircuitInputBuilder`] and checking witness-level invariants, without
+//! proving.
+
+use super::{CircuitInputBuilder, ExecState};
+use crate::{
+    mock::BlockData,
+    operation::{Target, RW},
+};
+use eth_types::{evm_types::OpcodeId, geth_types::GethData, Bytecode, Word};
+use mock::test_ctx::{helpers::account_0_code_account_1_no_code, TestContext};
+use proptest::prelude::*;
+use std::collections::HashMap;
+
+/// How the inputs of a generated opcode are provided.
+#[derive(Clone, Copy, Debug)]
+enum Inputs {
+    /// From the stack, fresh random inputs are only pushed when the stack is
+    /// too shallow.
+    Stack,
+    /// Fresh small random inputs are always pushed, as the inputs are memory
+    /// offsets and sizes, to avoid out of gas on memory expansion.
+    Memory,
+    /// Fresh inputs calling the identity precompile with random call data.
+    Call,
+}
+
+/// Opcodes generated by the harness, with how their inputs are provided. Their
+/// stack effect is [`OpcodeId::stack_pops_pushes`].
+const OPCODES: &[(OpcodeId, Inputs)] = &[
+    (OpcodeId::ADD, Inputs::Stack),
+    (OpcodeId::MUL, Inputs::Stack),
+    (OpcodeId::SUB, Inputs::Stack),
+    (OpcodeId::DIV, Inputs::Stack),
+    (OpcodeId::SDIV, Inputs::Stack),
+    (OpcodeId::MOD, Inputs::Stack),
+    (OpcodeId::SMOD, Inputs::Stack),
+    (OpcodeId::ADDMOD, Inputs::Stack),
+    (OpcodeId::MULMOD, Inputs::Stack),
+    (OpcodeId::EXP, Inputs::Stack),
+    (OpcodeId::SIGNEXTEND, Inputs::Stack),
+    (OpcodeId::LT, Inputs::Stack),
+    (OpcodeId::GT, Inputs::Stack),
+    (OpcodeId::SLT, Inputs::Stack),
+    (OpcodeId::SGT, Inputs::Stack),
+    (OpcodeId::EQ, Inputs::Stack),
+    (OpcodeId::ISZERO, Inputs::Stack),
+    (OpcodeId::AND, Inputs::Stack),
+    (OpcodeId::OR, Inputs::Stack),
+    (OpcodeId::XOR, Inputs::Stack),
+    (OpcodeId::NOT, Inputs::Stack),
+    (OpcodeId::BYTE, Inputs::Stack),
+    (OpcodeId::SHL, Inputs::Stack),
+    (OpcodeId::SHR, Inputs::Stack),
+    (OpcodeId::SAR, Inputs::Stack),
+    (OpcodeId::ADDRESS, Inputs::Stack),
+    (OpcodeId::CALLER, Inputs::Stack),
+    (OpcodeId::CALLVALUE, Inputs::Stack),
+    (OpcodeId::CALLDATASIZE, Inputs::Stack),
+    (OpcodeId::CALLDATALOAD, Inputs::Stack),
+    (OpcodeId::CODESIZE, Inputs::Stack),
+    (OpcodeId::GAS, Inputs::Stack),
+    (OpcodeId::PC, Inputs::Stack),
+    (OpcodeId::MSIZE, Inputs::Stack),
+    (OpcodeId::POP, Inputs::Stack),
+    (OpcodeId::DUP1, Inputs::Stack),
+    (OpcodeId::SWAP1, Inputs::Stack),
+    (OpcodeId::SLOAD, Inputs::Stack),
+    (OpcodeId::SSTORE, Inputs::Stack),
+    (OpcodeId::MLOAD, Inputs::Memory),
+    (OpcodeId::MSTORE, Inputs::Memory),
+    (OpcodeId::MSTORE8, Inputs::Memory),
+    (OpcodeId::SHA3, Inputs::Memory),
+    (OpcodeId::CALLDATACOPY, Inputs::Memory),
+    (OpcodeId::CODECOPY, Inputs::Memory),
+    (OpcodeId::LOG0, Inputs::Memory),
+    (OpcodeId::CALL, Inputs::Call),
+    (OpcodeId::STATICCALL, Inputs::Call),
+];
+
+/// Maximum stack depth kept by the generated bytecode.
+const MAX_STACK_DEPTH: usize = 16;
+
+/// Strategy generating bytecode which never underflows nor overflows the
+/// stack: the inputs missing on the stack are pushed before each opcode.
+fn bytecode_strategy() -> impl Strategy<Value = Bytecode> {
+    prop::collection::vec(
+        (0..OPCODES.len(), prop::array::uniform3(any::<u8>())),
+        1..48,
+    )
+    .prop_map(|ops| {
+        let mut code = Bytecode::default();
+        let mut depth = 0;
+        for (index, inputs) in ops {
+            let (opcode, kind) = OPCODES[index];
+            let (n_pops, n_pushes) = opcode.stack_pops_pushes();
+            // The top of the stack is the first input.
+            let fresh_inputs = match kind {
+                Inputs::Stack => inputs[..n_pops.saturating_sub(depth)].to_vec(),
+                Inputs::Memory => inputs[..n_pops].to_vec(),
+                Inputs::Call => {
+                    // gas, address, [value,] args offset and size, return
+                    // offset and size
+                    let mut call_inputs = vec![0xff, 0x04];
+                    if opcode == OpcodeId::CALL {
+                        call_inputs.push(0);
+                    }
+                    call_inputs.extend([0, inputs[0], 0, 0x20]);
+                    call_inputs
+                }
+            };
+            for input in fresh_inputs.iter().rev() {
+                code.push(1, Word::from(*input));
+            }
+            depth += fresh_inputs.len();
+            code.write_op(opcode);
+            depth = depth - n_pops + n_pushes;
+            while depth > MAX_STACK_DEPTH {
+                code.write_op(OpcodeId::POP);
+                depth -= 1;
+            }
+        }
+        code.write_op(OpcodeId::STOP);
+        code
+    })
+}
+
+/// Check the invariants of the witness built by `builder`, for each tx:
+/// - the steps account for every rw operation between the rw counters of the first and the last
+///   step, and the rw counter never decreases,
+/// - the stack operations of each successful opcode step match its stack effect, and the stack
+///   size of the next step of the same call follows from it,
+/// - the gas left of the next step of the same call is the gas left minus the gas cost of a
+///   successful opcode step, which doesn't call,
+/// - the steps enter and leave the calls as a stack: a step either stays in the call of the
+///   previous step, enters one of its callees, one level deeper, or returns to its caller, and
+///   the last step is in the root call.
+fn check_invariants(builder: &CircuitInputBuilder) {
+    let container = &builder.block.container;
+    for (tx_index, tx) in builder.block.txs.iter().enumerate() {
+        let calls = tx.calls();
+        let steps = tx.steps();
+        assert!(calls[0].is_root, "first call of tx {tx_index} is not root");
+
+        // Every rw operation of the tx is referenced by exactly one of its
+        // steps, including the reversions which are referenced by the reverted
+        // steps.
+        let (first, last) = (&steps[0], steps.last().unwrap());
+        assert_eq!(
+            steps
+                .iter()
+                .map(|step| step.bus_mapping_instance.len())
+                .sum::<usize>(),
+            last.rwc.0 + last.bus_mapping_instance.len() - first.rwc.0,
+            "rw operations of tx {tx_index} not accounted for"
+        );
+        for (step, next) in steps.iter().zip(steps.iter().skip(1)) {
+            assert!(
+                step.rwc.0 <= next.rwc.0,
+                "rw counter decreases after tx {} step {:?}",
+                tx_index,
+                step.exec_state
+            );
+        }
+
+        // Last successful opcode step of each call
+        let mut last_steps = HashMap::new();
+        let mut call_stack = vec![0];
+        for step in steps {
+            let current = *call_stack.last().unwrap();
+            if step.call_index != current {
+                if call_stack.len() > 1 && step.call_index == call_stack[call_stack.len() - 2] {
+                    call_stack.pop();
+                } else {
+                    let (caller, callee) = (&calls[current], &calls[step.call_index]);
+                    assert_eq!(
+                        callee.caller_id, caller.call_id,
+                        "tx {} enters call {} from call {} which is not its caller",
+                        tx_index, callee.call_id, caller.call_id
+                    );
+                    assert_eq!(callee.depth, caller.depth + 1);
+                    call_stack.push(step.call_index);
+                }
+            }
+
+            let opcode = match step.exec_state {
+                ExecState::Op(opcode) => opcode,
+                _ => continue,
+            };
+            if let Some((prev_opcode, prev)) = last_steps.insert(step.call_index, (opcode, step)) {
+                let (pops, pushes) = prev_opcode.stack_pops_pushes();
+                assert_eq!(
+                    prev.stack_size - pops + pushes,
+                    step.stack_size,
+                    "stack size after {prev_opcode:?} in tx {tx_index}"
+                );
+                if !prev_opcode.is_call() {
+                    assert_eq!(
+                        prev.gas_left.0 - prev.gas_cost.0,
+                        step.gas_left.0,
+                        "gas left after {prev_opcode:?} in tx {tx_index}"
+                    );
+                }
+            }
+            if step.error.is_some() {
+                last_steps.remove(&step.call_index);
+                continue;
+            }
+
+            let stack_ops = step
+                .bus_mapping_instance
+                .iter()
+                .filter(|op_ref| op_ref.0 == Target::Stack)
+                .map(|op_ref| &container.stack[op_ref.1])
+                .collect::<Vec<_>>();
+            let reads = stack_ops.iter().filter(|op| op.rw() == RW::READ).count();
+            let writes = stack_ops.len() - reads;
+            // DUPn and SWAPn only read and write the words they move.
+            let expected = if opcode.is_dup() {
+                (1, 1)
+            } else if opcode.is_swap() {
+                (2, 2)
+            } else {
+                opcode.stack_pops_pushes()
+            };
+            assert_eq!(
+                (reads, writes),
+                expected,
+                "stack operations of {opcode:?} in tx {tx_index}"
+            );
+            assert!(
+                stack_ops
+                    .windows(2)
+                    .all(|ops| ops[0].rwc().0 < ops[1].rwc().0),
+                "stack operations of {opcode:?} in tx {tx_index} out of order"
+            );
+        }
+        assert_eq!(call_stack, vec![0], "tx {tx_index} ends in a callee");
+    }
+}
+
+proptest! {
+    // Each case runs the external tracer, so keep the number of cases small.
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn random_bytecode_invariants(code in bytecode_strategy()) {
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(10_000_000u64));
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        check_invariants(&builder);
+    }
+}
@@ -0,0 +1,146 @@
+//! Stack operations of an opcode step, checked against the stack effect of the
+//! opcode.
+
+use super::{CircuitInputStateRef, ExecStep};
+use crate::Error;
+use eth_types::{evm_types::OpcodeId, Word};
+
+/// Builder of the stack operations of a successful opcode step. The words
+/// popped and pushed through it are counted, and checked against the exact
+/// stack effect of the [`OpcodeId`] given by
+/// [`OpcodeId::stack_pops_pushes`].
+#[derive(Debug, Clone)]
+pub struct StackOpsBuilder {
+    opcode: OpcodeId,
+    n_pops: usize,
+    n_pushes: usize,
+}
+
+impl StackOpsBuilder {
+    /// Create a builder for the stack operations of `opcode`.
+    pub fn new(opcode: OpcodeId) -> Self {
+        Self {
+            opcode,
+            n_pops: 0,
+            n_pushes: 0,
+        }
+    }
+
+    /// Check that popping `n_pops` words and pushing `n_pushes` words is the
+    /// stack effect of `opcode`.
+    pub fn check(opcode: OpcodeId, n_pops: usize, n_pushes: usize) -> Result<(), Error> {
+        if opcode.stack_pops_pushes() == (n_pops, n_pushes) {
+            Ok(())
+        } else {
+            Err(Error::StackEffectMismatch(opcode, n_pops, n_pushes))
+        }
+    }
+
+    /// Pop a word, emitting its stack read. Fails if the opcode pops fewer
+    /// words or if a word was already pushed.
+    pub fn pop(
+        &mut self,
+        state: &mut CircuitInputStateRef,
+        step: &mut ExecStep,
+    ) -> Result<Word, Error> {
+        let (pops, _) = self.opcode.stack_pops_pushes();
+        if self.n_pops == pops || self.n_pushes > 0 {
+            return Err(Error::StackEffectMismatch(
+                self.opcode,
+                self.n_pops + 1,
+                self.n_pushes,
+            ));
+        }
+        self.n_pops += 1;
+        state.stack_pop(step)
+    }
+
+    /// Pop `n` words, the top of the stack first.
+    pub fn pops(
+        &mut self,
+        state: &mut CircuitInputStateRef,
+        step: &mut ExecStep,
+        n: usize,
+    ) -> Result<Vec<Word>, Error> {
+        (0..n).map(|_| self.pop(state, step)).collect()
+    }
+
+    /// Push a word, emitting its stack write. Fails if the opcode pushes fewer
+    /// words.
+    pub fn push(
+        &mut self,
+        state: &mut CircuitInputStateRef,
+        step: &mut ExecStep,
+        value: Word,
+    ) -> Result<(), Error> {
+        let (_, pushes) = self.opcode.stack_pops_pushes();
+        if self.n_pushes == pushes {
+            return Err(Error::StackEffectMismatch(
+                self.opcode,
+                self.n_pops,
+                self.n_pushes + 1,
+            ));
+        }
+        self.n_pushes += 1;
+        state.stack_push(step, value)
+    }
+
+    /// Check that all the words of the stack effect of the opcode were popped
+    /// and pushed.
+    pub fn finish(self) -> Result<(), Error> {
+        Self::check(self.opcode, self.n_pops, self.n_pushes)
+    }
+
+    /// Pop the words of the stack effect of the opcode, and push the words
+    /// returned by `outputs`, which is checked before any operation is
+    /// emitted. Return the popped words, the top of the stack first.
+    pub fn emit(
+        mut self,
+        state: &mut CircuitInputStateRef,
+        step: &mut ExecStep,
+        outputs: impl FnOnce(&[Word]) -> Vec<Word>,
+    ) -> Result<Vec<Word>, Error> {
+        let (pops, _) = self.opcode.stack_pops_pushes();
+        let inputs = {
+            let stack = &state.call_ctx()?.stack;
+            (0..pops)
+                .map(|i| stack.nth_last(i))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        let outputs = outputs(&inputs);
+        Self::check(self.opcode, pops, outputs.len())?;
+
+        self.pops(state, step, pops)?;
+        for value in outputs {
+            self.push(state, step, value)?;
+        }
+        self.finish()?;
+        Ok(inputs)
+    }
+}
+
+#[cfg(test)]
+mod stack_ops_tests {
+    use super::*;
+
+    #[test]
+    fn stack_effect_check() {
+        assert!(StackOpsBuilder::check(OpcodeId::ADD, 2, 1).is_ok());
+        assert!(StackOpsBuilder::check(OpcodeId::ADDMOD, 3, 1).is_ok());
+        assert!(StackOpsBuilder::check(OpcodeId::POP, 1, 0).is_ok());
+        assert!(StackOpsBuilder::check(OpcodeId::PUSH1, 0, 1).is_ok());
+        assert!(StackOpsBuilder::check(OpcodeId::CALL, 7, 1).is_ok());
+        assert!(StackOpsBuilder::check(OpcodeId::LOG2, 4, 0).is_ok());
+
+        // Pops too few words
+        assert!(StackOpsBuilder::check(OpcodeId::ADD, 1, 1).is_err());
+        // Does not push its result
+        assert!(StackOpsBuilder::check(OpcodeId::ADD, 2, 0).is_err());
+        // Pushes more words than it pops
+        assert!(StackOpsBuilder::check(OpcodeId::POP, 1, 2).is_err());
+        // Does not push the word it should
+        assert!(StackOpsBuilder::check(OpcodeId::PUSH1, 0, 0).is_err());
+        // DELEGATECALL has no value argument
+        assert!(StackOpsBuilder::check(OpcodeId::DELEGATECALL, 7, 1).is_err());
+    }
+}
@@ -0,0 +1,287 @@
+//! Sanity checks of the state reconstructed by the builder against the state
+//! captured in the geth traces.
+
+use crate::util::{GethTraceSanityCheckLevel, GETH_TRACE_CHECK_LEVEL};
+use eth_types::{
+    evm_types::{OpcodeId, ProgramCounter},
+    H256,
+};
+
+/// How a mismatch between the reconstructed and the traced state is handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TraceCheckLevel {
+    /// Don't check
+    #[default]
+    Off,
+    /// Report the mismatch and continue, with the traced stack, memory or
+    /// return data in case of a stack, memory or return data mismatch
+    Warn,
+    /// Fail the build with [`Error::TraceMismatch`](crate::Error::TraceMismatch)
+    Error,
+}
+
+impl From<GethTraceSanityCheckLevel> for TraceCheckLevel {
+    fn from(level: GethTraceSanityCheckLevel) -> Self {
+        match level {
+            GethTraceSanityCheckLevel::None => TraceCheckLevel::Off,
+            GethTraceSanityCheckLevel::Check => TraceCheckLevel::Warn,
+            GethTraceSanityCheckLevel::Strict => TraceCheckLevel::Error,
+        }
+    }
+}
+
+/// What part of the state is checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceCheckKind {
+    /// Stack before the step, requires the `enable-stack` feature
+    Stack,
+    /// Memory before the step, requires the `enable-memory` feature
+    Memory,
+    /// Storage value read by SLOAD, requires the `enable-stack` and
+    /// `enable-storage` features
+    Storage,
+    /// Gas left after the step, and gas cost of the steps whose cost the
+    /// builder computes
+    Gas,
+    /// Gas refund counter before the step
+    Refund,
+    /// Return data of the last callee before the step, when it is traced
+    ReturnData,
+}
+
+/// Strictness of the trace sanity checks, for each part of the state.
+///
+/// The default is taken from the `GETH_TRACE_CHECK_LEVEL` env var for all the
+/// parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceCheckConfig {
+    /// Stack check level
+    pub stack: TraceCheckLevel,
+    /// Memory check level
+    pub memory: TraceCheckLevel,
+    /// Storage check level
+    pub storage: TraceCheckLevel,
+    /// Gas check level
+    pub gas: TraceCheckLevel,
+    /// Gas refund check level
+    pub refund: TraceCheckLevel,
+    /// Return data check level
+    pub return_data: TraceCheckLevel,
+}
+
+impl TraceCheckConfig {
+    /// Check all the parts of the state with the same `level`.
+    pub fn uniform(level: TraceCheckLevel) -> Self {
+        Self {
+            stack: level,
+            memory: level,
+            storage: level,
+            gas: level,
+            refund: level,
+            return_data: level,
+        }
+    }
+
+    /// Level of the check of `kind`.
+    pub fn level(&self, kind: TraceCheckKind) -> TraceCheckLevel {
+        match kind {
+            TraceCheckKind::Stack => self.stack,
+            TraceCheckKind::Memory => self.memory,
+            TraceCheckKind::Storage => self.storage,
+            TraceCheckKind::Gas => self.gas,
+            TraceCheckKind::Refund => self.refund,
+            TraceCheckKind::ReturnData => self.return_data,
+        }
+    }
+
+    /// Whether the check of `kind` is done.
+    pub fn is_enabled(&self, kind: TraceCheckKind) -> bool {
+        self.level(kind) != TraceCheckLevel::Off
+    }
+}
+
+impl Default for TraceCheckConfig {
+    fn default() -> Self {
+        Self::uniform((*GETH_TRACE_CHECK_LEVEL).into())
+    }
+}
+
+/// A mismatch between the state reconstructed by the builder and the state
+/// in the trace, before the execution of a step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceMismatch {
+    /// Block of the transaction
+    pub block_num: u64,
+    /// Hash of the transaction
+    pub tx_hash: H256,
+    /// Program counter of the step
+    pub pc: ProgramCounter,
+    /// Opcode of the step
+    pub op: OpcodeId,
+    /// Call depth of the step
+    pub depth: u16,
+    /// What part of the state mismatches
+    pub kind: TraceCheckKind,
+    /// Description of the mismatch
+    pub detail: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock::BlockData, Error};
+    use eth_types::{bytecode, evm_types::Gas, geth_types::GethData, Bytes};
+    use mock::TestContext;
+
+    fn block_with_wrong_gas() -> GethData {
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x02)
+            ADD
+            STOP
+        };
+        let mut block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        // the ADD step claims to cost more gas than it does
+        let add = &mut block.geth_traces[0].struct_logs[2];
+        assert_eq!(add.op, OpcodeId::ADD);
+        add.gas_cost.0 += 1;
+        block
+    }
+
+    #[test]
+    fn trace_check_reports_gas_mismatch() {
+        let block = block_with_wrong_gas();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.trace_check = TraceCheckConfig {
+            gas: TraceCheckLevel::Warn,
+            ..TraceCheckConfig::uniform(TraceCheckLevel::Off)
+        };
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        assert_eq!(builder.block.trace_mismatches.len(), 1);
+        let mismatch = &builder.block.trace_mismatches[0];
+        assert_eq!(mismatch.op, OpcodeId::ADD);
+        assert_eq!(mismatch.kind, TraceCheckKind::Gas);
+        assert_eq!(mismatch.tx_hash, block.eth_block.transactions[0].hash);
+    }
+
+    #[test]
+    fn trace_check_fails_on_error_level() {
+        let block = block_with_wrong_gas();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.trace_check = TraceCheckConfig {
+            gas: TraceCheckLevel::Error,
+            ..TraceCheckConfig::uniform(TraceCheckLevel::Off)
+        };
+        let err = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap_err();
+        assert!(
+            matches!(err.inner(), Error::TraceMismatch(mismatch) if mismatch.kind == TraceCheckKind::Gas)
+        );
+
+        // the checks are off
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.trace_check = TraceCheckConfig::uniform(TraceCheckLevel::Off);
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        assert!(builder.block.trace_mismatches.is_empty());
+    }
+
+    #[test]
+    fn trace_check_reports_step_gas_cost_mismatch() {
+        let code = bytecode! {
+            PUSH2(0x100)
+            PUSH1(0x02)
+            EXP
+            STOP
+        };
+        let mut block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        // the EXP step claims to cost more gas than its exponent does, and the
+        // steps after it have the gas left accordingly
+        let struct_logs = &mut block.geth_traces[0].struct_logs;
+        assert_eq!(struct_logs[2].op, OpcodeId::EXP);
+        struct_logs[2].gas_cost.0 += 1;
+        for step in struct_logs[3..].iter_mut() {
+            step.gas.0 -= 1;
+        }
+
+        let mismatches = mismatches(
+            &block,
+            TraceCheckConfig {
+                gas: TraceCheckLevel::Warn,
+                ..TraceCheckConfig::uniform(TraceCheckLevel::Off)
+            },
+        );
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].op, OpcodeId::EXP);
+        assert_eq!(mismatches[0].kind, TraceCheckKind::Gas);
+        assert!(mismatches[0].detail.starts_with("computed gas cost"));
+    }
+
+    fn block_with_wrong_add_step(tamper: impl FnOnce(&mut eth_types::GethExecStep)) -> GethData {
+        let mut block = block_with_wrong_gas();
+        let add = &mut block.geth_traces[0].struct_logs[2];
+        add.gas_cost.0 -= 1;
+        tamper(add);
+        block
+    }
+
+    fn mismatches(block: &GethData, trace_check: TraceCheckConfig) -> Vec<TraceMismatch> {
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.trace_check = trace_check;
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        builder.block.trace_mismatches
+    }
+
+    #[cfg(not(feature = "fix-refund"))]
+    #[test]
+    fn trace_check_reports_refund_mismatch() {
+        let block = block_with_wrong_add_step(|add| add.refund = Gas(4800));
+        let mismatches = mismatches(
+            &block,
+            TraceCheckConfig {
+                refund: TraceCheckLevel::Warn,
+                ..TraceCheckConfig::uniform(TraceCheckLevel::Off)
+            },
+        );
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].op, OpcodeId::ADD);
+        assert_eq!(mismatches[0].kind, TraceCheckKind::Refund);
+    }
+
+    #[test]
+    fn trace_check_reports_return_data_mismatch() {
+        let block = block_with_wrong_add_step(|add| add.return_data = Some(Bytes::from([0x12])));
+        let mismatches = mismatches(
+            &block,
+            TraceCheckConfig {
+                return_data: TraceCheckLevel::Warn,
+                ..TraceCheckConfig::uniform(TraceCheckLevel::Off)
+            },
+        );
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].op, OpcodeId::ADD);
+        assert_eq!(mismatches[0].kind, TraceCheckKind::ReturnData);
+
+        // the return data is not traced
+        let block = block_with_wrong_add_step(|_| ());
+        assert!(mismatches(
+            &block,
+            TraceCheckConfig {
+                return_data: TraceCheckLevel::Warn,
+                ..TraceCheckConfig::uniform(TraceCheckLevel::Off)
+            },
+        )
+        .is_empty());
+    }
+}
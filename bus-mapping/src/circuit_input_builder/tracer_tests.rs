@@ -7,7 +7,7 @@ use crate::{
 };
 use eth_types::{
     address, bytecode,
-    evm_types::{stack::Stack, Gas, Memory, OpcodeId},
+    evm_types::{stack::Stack, ChainSpec, Gas, Memory, OpcodeId},
     geth_types::GethData,
     state_db::Account,
     word, Address, Bytecode, GethExecError, GethExecStep, Hash, ToAddress, ToWord, Word,
@@ -768,7 +768,7 @@ fn check_err_invalid_code(step: &GethExecStep, next_step: Option<&GethExecStep>)
         && result(next_step).is_zero()
         && length > Word::zero()
         && !step.memory.is_empty()
-        && step.memory.0.get(offset.low_u64() as usize) == Some(&0xef)
+        && step.memory.get(offset.low_u64() as usize) == Some(0xef)
 }
 
 #[test]
@@ -1703,6 +1703,50 @@ fn tracer_err_stack_overflow() {
     );
 }
 
+#[test]
+fn tracer_err_stack_overflow_chain_spec() {
+    // PUSH1 on a stack of 4 words, which overflows a stack limit of 4
+    let code = bytecode! {
+        PUSH1(1)
+        PUSH1(2)
+        PUSH1(3)
+        PUSH1(4)
+        PUSH1(5)
+        STOP
+    };
+    let block: GethData = TestContext::<2, 1>::new_with_logger_config(
+        None,
+        account_0_code_account_1_no_code(code),
+        tx_from_1_to_0,
+        |block, _tx| block.number(0xcafeu64),
+        LoggerConfig::enable_memory(),
+    )
+    .unwrap()
+    .into();
+
+    let index = 4; // PUSH1(5)
+    let step = &block.geth_traces[0].struct_logs[index];
+    let next_step = block.geth_traces[0].struct_logs.get(index + 1);
+    assert_eq!(step.op, OpcodeId::PUSH1);
+    assert_eq!(step.error, None);
+
+    let mut builder = CircuitInputBuilderTx::new(&block, step);
+    builder.tx_ctx.call_ctx_mut().unwrap().stack = step.stack.clone();
+    assert_eq!(
+        builder.state_ref().get_step_err(step, next_step).unwrap(),
+        None
+    );
+
+    builder.builder.block.chain_spec = ChainSpec {
+        stack_limit: 4,
+        ..ChainSpec::mainnet()
+    };
+    assert_eq!(
+        builder.state_ref().get_step_err(step, next_step).unwrap(),
+        Some(ExecError::StackOverflow)
+    );
+}
+
 #[test]
 fn tracer_err_stack_underflow() {
     // SWAP5 with an empty stack, which causes a stack underflow
@@ -2,9 +2,13 @@
 
 use super::{
     call::ReversionGroup, curie::is_curie_enabled, Call, CallContext, CallKind, CodeSource,
-    ExecStep,
+    ExecState, ExecStep,
+};
+use crate::{
+    error::ExecError,
+    l2_predeployed::{l1_gas_price_oracle, op_l1_block},
+    Error,
 };
-use crate::{l2_predeployed::l1_gas_price_oracle, Error};
 use eth_types::evm_types::gas_utils::tx_data_gas_cost;
 use eth_types::{
     evm_types::OpcodeId,
@@ -19,6 +23,75 @@ use ethers_core::utils::get_contract_address;
 pub const TX_L1_FEE_PRECISION: u64 = 1_000_000_000;
 /// Extra cost as the bytes of rlped tx commited to L1 (assume to non-zero, overestimated a bit)
 pub const TX_L1_COMMIT_EXTRA_COST: u64 = 64;
+/// Precision of the L1 fee scalar of the OP stack
+pub const OP_L1_FEE_PRECISION: u64 = 1_000_000;
+
+/// How the L1 fee of a transaction is computed, and the rollup config contract
+/// and storage slots its parameters are read from.
+///
+/// The EVM Circuit is configured for one schema, which must be the one of the
+/// witness.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum L1FeeSchema {
+    /// Scroll L1GasPriceOracle:
+    /// `l1BaseFee * scalar * (dataGas + overhead + 64) / 1e9` before the curie
+    /// fork, `(commitScalar * l1BaseFee + blobScalar * len * l1BlobBaseFee) /
+    /// 1e9` after it
+    #[default]
+    Scroll,
+    /// OP stack Bedrock L1Block: `l1BaseFee * scalar * (dataGas + overhead) /
+    /// 1e6`
+    OpBedrock,
+}
+
+impl L1FeeSchema {
+    /// Address of the contract holding the L1 fee parameters
+    pub fn address(&self) -> Address {
+        match self {
+            Self::Scroll => *l1_gas_price_oracle::ADDRESS,
+            Self::OpBedrock => *op_l1_block::ADDRESS,
+        }
+    }
+
+    /// Storage slots of the L1 base fee, the L1 fee overhead and the L1 fee
+    /// scalar in the contract at [`Self::address`]
+    pub fn fee_slots(&self) -> [Word; 3] {
+        match self {
+            Self::Scroll => [
+                *l1_gas_price_oracle::BASE_FEE_SLOT,
+                *l1_gas_price_oracle::OVERHEAD_SLOT,
+                *l1_gas_price_oracle::SCALAR_SLOT,
+            ],
+            Self::OpBedrock => [
+                *op_l1_block::BASE_FEE_SLOT,
+                *op_l1_block::OVERHEAD_SLOT,
+                *op_l1_block::SCALAR_SLOT,
+            ],
+        }
+    }
+
+    /// Divisor of the L1 fee, the precision of the L1 fee scalar
+    pub fn precision(&self) -> u64 {
+        match self {
+            Self::Scroll => TX_L1_FEE_PRECISION,
+            Self::OpBedrock => OP_L1_FEE_PRECISION,
+        }
+    }
+
+    /// L1 gas added to the data gas of each transaction, on top of the L1 fee
+    /// overhead
+    pub fn extra_l1_gas(&self) -> u64 {
+        match self {
+            Self::Scroll => TX_L1_COMMIT_EXTRA_COST,
+            Self::OpBedrock => 0,
+        }
+    }
+
+    /// Whether the schema has the curie fee formula, from the curie fork on
+    pub fn has_curie(&self) -> bool {
+        matches!(self, Self::Scroll)
+    }
+}
 
 #[derive(Debug, Default)]
 /// Context of a [`Transaction`] which can mutate in an [`ExecStep`].
@@ -188,6 +261,9 @@ pub struct Transaction {
     pub value: Word,
     /// Input / Call Data
     pub input: Vec<u8>,
+    /// Id of an earlier tx of the chunk with the same call data, whose call
+    /// data section in the tx table is shared with this tx
+    pub shared_call_data_tx_id: Option<usize>,
     /// Chain_id
     pub chain_id: u64,
     /// Signature
@@ -248,6 +324,7 @@ impl Transaction {
             to: Some(Address::zero()), // or use None?
             value: Word::zero(),
             input: Vec::new(),
+            shared_call_data_tx_id: None,
             chain_id: 0,
             signature: Signature {
                 r: Word::zero(),
@@ -272,6 +349,7 @@ impl Transaction {
     pub fn new(
         call_id: usize,
         chain_id: u64,
+        l1_fee_schema: L1FeeSchema,
         sdb: &StateDB,
         code_db: &mut CodeDB,
         eth_tx: &eth_types::Transaction,
@@ -347,8 +425,12 @@ impl Transaction {
             // So we should not use that.
             // We need to use "global" chain id.
             (
-                TxL1Fee::get_current_values_from_state_db(sdb, chain_id, block_num),
-                TxL1Fee::get_committed_values_from_state_db(sdb, chain_id, block_num),
+                TxL1Fee::from_storage(l1_fee_schema, chain_id, block_num, |address, slot| {
+                    *sdb.get_storage(address, slot).1
+                }),
+                TxL1Fee::from_storage(l1_fee_schema, chain_id, block_num, |address, slot| {
+                    *sdb.get_committed_storage(address, slot).1
+                }),
             )
         };
 
@@ -377,6 +459,7 @@ impl Transaction {
             to: eth_tx.to,
             value: eth_tx.value,
             input: eth_tx.input.to_vec(),
+            shared_call_data_tx_id: None,
             calls: vec![call],
             steps: Vec::new(),
             signature: Signature {
@@ -444,16 +527,85 @@ impl Transaction {
             )
             .0
     }
+
+    /// Summarize the gas accounting of each call frame, in the order of
+    /// [`Transaction::calls`], from the execution steps.
+    pub fn call_gas_report(&self) -> Vec<CallGasReport> {
+        self.calls
+            .iter()
+            .enumerate()
+            .map(|(call_index, call)| {
+                let mut steps = self.steps.iter().filter(|step| {
+                    step.call_index == call_index
+                        && matches!(step.exec_state, ExecState::Op(_) | ExecState::Precompile(_))
+                });
+                let first = steps.next();
+                let last = steps.last().or(first);
+
+                let mut report = CallGasReport {
+                    call_index,
+                    kind: call.kind,
+                    address: call.address,
+                    depth: call.depth,
+                    gas_forwarded: 0,
+                    gas_used: 0,
+                    gas_refund: 0,
+                    is_success: call.is_success,
+                    error: None,
+                };
+                if let (Some(first), Some(last)) = (first, last) {
+                    // an exceptional halt consumes all the gas left
+                    let gas_left = if !call.is_success && last.error.is_some() {
+                        0
+                    } else {
+                        last.gas_left.0.saturating_sub(last.gas_cost.0)
+                    };
+                    report.gas_forwarded = first.gas_left.0;
+                    report.gas_used = first.gas_left.0.saturating_sub(gas_left);
+                    if call.is_success {
+                        report.gas_refund = last.gas_refund.0 as i64 - first.gas_refund.0 as i64;
+                    } else {
+                        report.error = last.error.clone();
+                    }
+                }
+                report
+            })
+            .collect()
+    }
+}
+
+/// Gas accounting of a call frame of a [`Transaction`], see
+/// [`Transaction::call_gas_report`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallGasReport {
+    /// Index of the call in the transaction
+    pub call_index: usize,
+    /// Type of call
+    pub kind: CallKind,
+    /// Address where the call is executed
+    pub address: Address,
+    /// Depth of the call
+    pub depth: usize,
+    /// Gas left at the first step of the call, 0 for a call without steps
+    /// (e.g. to an account without code)
+    pub gas_forwarded: u64,
+    /// Gas used by the call and its subcalls. All the forwarded gas when the
+    /// call halts exceptionally.
+    pub gas_used: u64,
+    /// Change of the tx refund counter during the call, 0 when the call fails
+    /// and its refunds are reverted
+    pub gas_refund: i64,
+    /// Whether the call succeeds
+    pub is_success: bool,
+    /// Error that halts the call, `None` on success or REVERT
+    pub error: Option<ExecError>,
 }
 
 #[cfg(feature = "test")]
 impl Transaction {
     /// test if the transaction has different evm behaviour opcodes or precompiles
     pub fn has_l2_different_evm_behaviour_step(&self) -> bool {
-        use crate::{
-            circuit_input_builder::execution::ExecState, error::ExecError,
-            precompile::PrecompileCalls,
-        };
+        use crate::precompile::PrecompileCalls;
         let different_opcodes = self.steps.iter().any(|step| {
             matches!(
                 step.exec_state,
@@ -475,9 +627,12 @@ impl Transaction {
     }
 }
 
-/// Transaction L1 fee for L1GasPriceOracle contract
+/// Transaction L1 fee parameters, read from the rollup config contract of the
+/// [`L1FeeSchema`]
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub struct TxL1Fee {
+    /// L1 fee schema
+    pub schema: L1FeeSchema,
     /// chain id
     pub chain_id: u64,
     /// block number
@@ -499,21 +654,26 @@ pub struct TxL1Fee {
 impl TxL1Fee {
     /// Calculate L1 fee and remainder of transaction.
     pub fn tx_l1_fee(&self, tx_data_gas_cost: u64, tx_rlp_signed_len: u64) -> (u64, u64) {
-        let is_curie = is_curie_enabled(self.chain_id, self.block_number);
-        if is_curie {
+        if self.is_curie() {
             self.tx_l1_fee_after_curie(tx_rlp_signed_len)
         } else {
             self.tx_l1_fee_before_curie(tx_data_gas_cost)
         }
     }
 
+    fn is_curie(&self) -> bool {
+        self.schema.has_curie() && is_curie_enabled(self.chain_id, self.block_number)
+    }
+
     fn tx_l1_fee_before_curie(&self, tx_data_gas_cost: u64) -> (u64, u64) {
         // <https://github.com/scroll-tech/go-ethereum/blob/49192260a177f1b63fc5ea3b872fb904f396260c/rollup/fees/rollup_fee.go#L118>
-        let tx_l1_gas = tx_data_gas_cost + self.fee_overhead + TX_L1_COMMIT_EXTRA_COST;
+        // <https://github.com/ethereum-optimism/op-geth/blob/v1.101200.0/core/types/rollup_cost.go>
+        let tx_l1_gas = tx_data_gas_cost + self.fee_overhead + self.schema.extra_l1_gas();
         let tx_l1_fee = self.fee_scalar as u128 * self.base_fee as u128 * tx_l1_gas as u128;
+        let precision = self.schema.precision() as u128;
         (
-            (tx_l1_fee / TX_L1_FEE_PRECISION as u128) as u64,
-            (tx_l1_fee % TX_L1_FEE_PRECISION as u128) as u64,
+            (tx_l1_fee / precision) as u64,
+            (tx_l1_fee % precision) as u64,
         )
     }
 
@@ -541,72 +701,126 @@ impl TxL1Fee {
         )
     }
 
-    fn get_current_values_from_state_db(sdb: &StateDB, chain_id: u64, block_number: u64) -> Self {
-        let [base_fee, fee_overhead, fee_scalar] = [
-            &l1_gas_price_oracle::BASE_FEE_SLOT,
-            &l1_gas_price_oracle::OVERHEAD_SLOT,
-            &l1_gas_price_oracle::SCALAR_SLOT,
-        ]
-        .map(|slot| {
-            sdb.get_storage(&l1_gas_price_oracle::ADDRESS, slot)
-                .1
-                .as_u64()
-        });
-        let [l1_blob_basefee, commit_scalar, blob_scalar] = [
-            &l1_gas_price_oracle::L1_BLOB_BASEFEE_SLOT,
-            &l1_gas_price_oracle::COMMIT_SCALAR_SLOT,
-            &l1_gas_price_oracle::BLOB_SCALAR_SLOT,
-        ]
-        .map(|slot| {
-            sdb.get_storage(&l1_gas_price_oracle::ADDRESS, slot)
-                .1
-                .as_u64()
-        });
+    /// Storage slots of the rollup config contract read by the L1 fee, with
+    /// their values, in the order they are read by the begin tx step.
+    pub fn slots(&self) -> Vec<(Word, u64)> {
+        let [base_fee_slot, overhead_slot, scalar_slot] = self.schema.fee_slots();
+        let mut slots = vec![
+            (base_fee_slot, self.base_fee),
+            (overhead_slot, self.fee_overhead),
+            (scalar_slot, self.fee_scalar),
+        ];
+        if self.is_curie() {
+            slots.extend([
+                (
+                    *l1_gas_price_oracle::L1_BLOB_BASEFEE_SLOT,
+                    self.l1_blob_basefee,
+                ),
+                (*l1_gas_price_oracle::COMMIT_SCALAR_SLOT, self.commit_scalar),
+                (*l1_gas_price_oracle::BLOB_SCALAR_SLOT, self.blob_scalar),
+            ]);
+        }
+        slots
+    }
 
-        Self {
+    /// Read the L1 fee parameters of `schema` with `storage`, which returns
+    /// the value of a storage slot of an account.
+    pub fn from_storage(
+        schema: L1FeeSchema,
+        chain_id: u64,
+        block_number: u64,
+        storage: impl Fn(&Address, &Word) -> Word,
+    ) -> Self {
+        let read = |slot: &Word| storage(&schema.address(), slot).as_u64();
+        let [base_fee_slot, overhead_slot, scalar_slot] = schema.fee_slots();
+        let l1_fee = Self {
+            schema,
             chain_id,
             block_number,
-            base_fee,
-            fee_overhead,
-            fee_scalar,
-            l1_blob_basefee,
-            commit_scalar,
-            blob_scalar,
+            base_fee: read(&base_fee_slot),
+            fee_overhead: read(&overhead_slot),
+            fee_scalar: read(&scalar_slot),
+            ..Default::default()
+        };
+        if schema.has_curie() {
+            Self {
+                l1_blob_basefee: read(&l1_gas_price_oracle::L1_BLOB_BASEFEE_SLOT),
+                commit_scalar: read(&l1_gas_price_oracle::COMMIT_SCALAR_SLOT),
+                blob_scalar: read(&l1_gas_price_oracle::BLOB_SCALAR_SLOT),
+                ..l1_fee
+            }
+        } else {
+            l1_fee
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData, Bytecode};
+    use mock::TestContext;
+
+    fn call_gas_report(code: Bytecode) -> Vec<CallGasReport> {
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        builder.block.txs()[0].call_gas_report()
+    }
 
-    fn get_committed_values_from_state_db(sdb: &StateDB, chain_id: u64, block_number: u64) -> Self {
-        let [base_fee, fee_overhead, fee_scalar] = [
-            &l1_gas_price_oracle::BASE_FEE_SLOT,
-            &l1_gas_price_oracle::OVERHEAD_SLOT,
-            &l1_gas_price_oracle::SCALAR_SLOT,
-        ]
-        .map(|slot| {
-            sdb.get_committed_storage(&l1_gas_price_oracle::ADDRESS, slot)
-                .1
-                .as_u64()
+    #[test]
+    fn call_gas_report_success() {
+        let reports = call_gas_report(bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x02)
+            ADD
+            POP
+            STOP
         });
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert!(report.is_success);
+        assert_eq!(report.error, None);
+        assert_eq!(report.depth, 1);
+        // PUSH1 + PUSH1 + ADD + POP + STOP
+        assert_eq!(report.gas_used, 3 + 3 + 3 + 2);
+        assert!(report.gas_forwarded > report.gas_used);
+    }
 
-        let [l1_blob_basefee, commit_scalar, blob_scalar] = [
-            &l1_gas_price_oracle::L1_BLOB_BASEFEE_SLOT,
-            &l1_gas_price_oracle::COMMIT_SCALAR_SLOT,
-            &l1_gas_price_oracle::BLOB_SCALAR_SLOT,
-        ]
-        .map(|slot| {
-            sdb.get_committed_storage(&l1_gas_price_oracle::ADDRESS, slot)
-                .1
-                .as_u64()
+    #[test]
+    fn call_gas_report_exceptional_halt() {
+        let reports = call_gas_report(bytecode! {
+            PUSH1(0x00)
+            JUMP
         });
+        let report = &reports[0];
+        assert!(!report.is_success);
+        assert_eq!(report.error, Some(ExecError::InvalidJump));
+        assert_eq!(report.gas_used, report.gas_forwarded);
+    }
 
-        Self {
-            chain_id,
-            block_number,
-            base_fee,
-            fee_overhead,
-            fee_scalar,
-            l1_blob_basefee,
-            commit_scalar,
-            blob_scalar,
-        }
+    #[test]
+    fn op_bedrock_l1_fee() {
+        let storage = [
+            (*op_l1_block::BASE_FEE_SLOT, 10u64),
+            (*op_l1_block::OVERHEAD_SLOT, 188),
+            (*op_l1_block::SCALAR_SLOT, 684_000),
+        ];
+        let l1_fee = TxL1Fee::from_storage(L1FeeSchema::OpBedrock, 10, 1, |address, slot| {
+            assert_eq!(*address, *op_l1_block::ADDRESS);
+            storage
+                .iter()
+                .find(|(key, _)| key == slot)
+                .map_or(Word::zero(), |(_, value)| Word::from(*value))
+        });
+
+        assert_eq!(l1_fee.slots(), storage.to_vec());
+        // 10 * 684000 * (1000 + 188) / 1e6
+        assert_eq!(l1_fee.tx_l1_fee(1000, 0), (8125, 920_000));
     }
 }
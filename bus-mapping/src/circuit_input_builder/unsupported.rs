@@ -0,0 +1,213 @@
+//! Handling of the opcodes and error states which have no circuit support.
+
+use crate::{error::ExecError, evm::OpcodeId};
+use eth_types::H256;
+
+/// How the builder handles a transaction using an opcode or running into an
+/// error state which has no circuit support.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnsupportedOpcodePolicy {
+    /// Fail the build with [`Error::Unsupported`](crate::Error::Unsupported).
+    Error,
+    /// Handle the step with a dummy gadget, which only advances the execution
+    /// state without constraining the effects of the opcode. The EVM circuit
+    /// then sets its public flag marking the block as containing unproven
+    /// steps.
+    #[default]
+    DummyGadget,
+    /// Leave transactions using an unsupported opcode out of the witness.
+    /// Their state changes are still applied, so the transactions which follow
+    /// see them. Unsupported error states can only be found while handling
+    /// the transaction, so they fall back to dummy gadgets.
+    SkipTx,
+}
+
+/// What is not supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnsupportedReason {
+    /// An opcode without circuit support
+    Opcode(OpcodeId),
+    /// An error state without circuit support, with the opcode it occurred in
+    ErrorState(OpcodeId, ExecError),
+}
+
+/// A transaction handled in a degraded way because of an unsupported opcode
+/// or error state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DowngradedTx {
+    /// Block of the transaction
+    pub block_num: u64,
+    /// Hash of the transaction
+    pub tx_hash: H256,
+    /// What is not supported
+    pub reason: UnsupportedReason,
+    /// Whether the transaction was left out of the witness, rather than
+    /// handled with dummy gadgets
+    pub skipped: bool,
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "scroll"))]
+mod tests {
+    use super::*;
+    use crate::{error::OogError, mock::BlockData, Error};
+    use eth_types::{bytecode, geth_types::GethData, ToWord, Word};
+    use mock::{
+        test_ctx::{helpers::*, TestContext},
+        MOCK_ACCOUNTS,
+    };
+
+    #[test]
+    fn unsupported_error_state_fails_under_error_policy() {
+        // SELFDESTRUCT costs at least 5000 gas, and running out of gas in it
+        // has no circuit support
+        let code = bytecode! {
+            PUSH20(MOCK_ACCOUNTS[2].to_word())
+            SELFDESTRUCT
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(21_000 + 3 + 4_999));
+            },
+            |block, _tx| block.number(0xcafe_u64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.unsupported_opcode_policy = UnsupportedOpcodePolicy::Error;
+        let err = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap_err();
+
+        // the error is located in the code
+        match &err {
+            Error::StepError { op, context, .. } => {
+                assert_eq!(*op, OpcodeId::SELFDESTRUCT);
+                assert!(context.contains("> "), "{context}");
+            }
+            err => panic!("unexpected {err:?}"),
+        }
+        assert!(matches!(
+            err.inner(),
+            Error::Unsupported(UnsupportedReason::ErrorState(
+                OpcodeId::SELFDESTRUCT,
+                ExecError::OutOfGas(OogError::SelfDestruct)
+            ))
+        ));
+    }
+
+    #[test]
+    fn dummy_opcode_fails_under_error_policy() {
+        // SELFDESTRUCT only has a dummy handler
+        let code = bytecode! {
+            PUSH20(MOCK_ACCOUNTS[2].to_word())
+            SELFDESTRUCT
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafe_u64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.unsupported_opcode_policy = UnsupportedOpcodePolicy::Error;
+        let err = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap_err();
+        assert!(matches!(
+            err.inner(),
+            Error::Unsupported(UnsupportedReason::Opcode(OpcodeId::SELFDESTRUCT))
+        ));
+
+        // the default policy records the transaction as downgraded
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        assert_eq!(builder.block.downgraded_txs.len(), 1);
+        assert_eq!(
+            builder.block.downgraded_txs[0].reason,
+            UnsupportedReason::Opcode(OpcodeId::SELFDESTRUCT)
+        );
+        assert!(!builder.block.downgraded_txs[0].skipped);
+    }
+
+    #[test]
+    fn skipped_tx_state_is_seen_by_the_next_tx() {
+        // increments the counter in slot 0
+        let code = bytecode! {
+            JUMPDEST
+            PUSH1(0)
+            SLOAD
+            PUSH1(1)
+            ADD
+            PUSH1(0)
+            SSTORE
+            STOP
+        };
+        let mut block: GethData = TestContext::<2, 2>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+                txs[1].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        // the first transaction runs an opcode of the chain which the EVM of
+        // the circuits does not know, in place of the JUMPDEST
+        let unknown = OpcodeId::INVALID(0x49);
+        block.geth_traces[0].struct_logs[0].op = unknown;
+        let contract = block.eth_block.transactions[0].to.unwrap();
+        let skipped = block.eth_block.transactions[0].hash;
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.unsupported_opcode_policy = UnsupportedOpcodePolicy::SkipTx;
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        assert_eq!(builder.block.txs().len(), 1);
+        assert_eq!(
+            builder.block.unsupported_opcode_policy,
+            UnsupportedOpcodePolicy::SkipTx
+        );
+        assert_eq!(
+            builder.block.downgraded_txs,
+            vec![DowngradedTx {
+                block_num: block.eth_block.transactions[0]
+                    .block_number
+                    .unwrap_or_default()
+                    .as_u64(),
+                tx_hash: skipped,
+                reason: UnsupportedReason::Opcode(unknown),
+                skipped: true,
+            }]
+        );
+        // the proven transaction read the counter written by the skipped one
+        let storage_op = builder
+            .block
+            .container
+            .storage
+            .iter()
+            .map(|op| op.op())
+            .find(|op| op.value == Word::from(2))
+            .unwrap();
+        assert_eq!(storage_op.value_prev, Word::one());
+        assert_eq!(
+            *builder.sdb.get_storage(&contract, &Word::zero()).1,
+            Word::from(2)
+        );
+    }
+}
@@ -1,5 +1,6 @@
 //! Error module for the bus-mapping crate
 
+use crate::circuit_input_builder::{TraceMismatch, UnsupportedReason};
 use core::fmt::{Display, Formatter, Result as FmtResult};
 use eth_types::{evm_types::OpcodeId, Address, GethExecError, GethExecStep, Word, H256};
 use ethers_providers::ProviderError;
@@ -39,6 +40,36 @@ pub enum Error {
     ExecutionError(ExecError),
     /// Internal Code error
     InternalError(&'static str),
+    /// The number of words popped and pushed by an opcode step doesn't match
+    /// the stack metadata of its [`OpcodeId`]
+    StackEffectMismatch(OpcodeId, usize, usize),
+    /// Opcode or error state without circuit support, under
+    /// [`UnsupportedOpcodePolicy::Error`](crate::circuit_input_builder::UnsupportedOpcodePolicy::Error)
+    Unsupported(UnsupportedReason),
+    /// Mismatch between the reconstructed and the traced state, under
+    /// [`TraceCheckLevel::Error`](crate::circuit_input_builder::TraceCheckLevel::Error)
+    TraceMismatch(Box<TraceMismatch>),
+    /// Error raised while handling an opcode step, with the code around it
+    StepError {
+        /// Program counter of the step
+        pc: usize,
+        /// Opcode of the step
+        op: OpcodeId,
+        /// Disassembly of the code around `pc`
+        context: String,
+        /// Error raised by the step
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// The error without the context of the step which raised it.
+    pub fn inner(&self) -> &Error {
+        match self {
+            Error::StepError { source, .. } => source.inner(),
+            err => err,
+        }
+    }
 }
 
 impl From<eth_types::Error> for Error {
@@ -47,6 +78,12 @@ impl From<eth_types::Error> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::IoError(err)
+    }
+}
+
 impl From<ProviderError> for Error {
     fn from(err: ProviderError) -> Self {
         Error::JSONRpcError(err)
@@ -55,11 +92,26 @@ impl From<ProviderError> for Error {
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        write!(f, "{self:?}")
+        match self {
+            Error::StepError {
+                pc,
+                op,
+                context,
+                source,
+            } => write!(f, "failure at pc={pc:#x}: {op:?}, {source}\n{context}"),
+            err => write!(f, "{err:?}"),
+        }
     }
 }
 
-impl StdError for Error {}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::StepError { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 /// Out of Gas errors by opcode
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -179,6 +231,76 @@ pub enum ExecError {
     NonceUintOverflow(NonceUintOverflowError),
 }
 
+impl ExecError {
+    /// Stable numeric code of the error, to classify errors without matching
+    /// on their text. Codes are never reused: errors without a sub kind take
+    /// `1..100`, and each error with a sub kind takes a range of 100.
+    pub fn code(&self) -> u16 {
+        match self {
+            ExecError::InvalidOpcode => 1,
+            ExecError::StackOverflow => 2,
+            ExecError::StackUnderflow => 3,
+            ExecError::WriteProtection => 4,
+            ExecError::InvalidCreationCode => 5,
+            ExecError::InvalidJump => 6,
+            ExecError::ReturnDataOutOfBounds => 7,
+            ExecError::CodeStoreOutOfGas => 8,
+            ExecError::MaxCodeSizeExceeded => 9,
+            ExecError::PrecompileFailed => 10,
+            ExecError::OutOfGas(err) => {
+                100 + match err {
+                    OogError::Constant => 0,
+                    OogError::StaticMemoryExpansion => 1,
+                    OogError::DynamicMemoryExpansion => 2,
+                    OogError::MemoryCopy => 3,
+                    OogError::AccountAccess => 4,
+                    OogError::CodeStore => 5,
+                    OogError::Log => 6,
+                    OogError::Exp => 7,
+                    OogError::Sha3 => 8,
+                    OogError::SloadSstore => 9,
+                    OogError::Call => 10,
+                    OogError::Precompile => 11,
+                    OogError::Create => 12,
+                    OogError::SelfDestruct => 13,
+                }
+            }
+            ExecError::Depth(err) => {
+                200 + match err {
+                    DepthError::Call => 0,
+                    DepthError::Create => 1,
+                    DepthError::Create2 => 2,
+                }
+            }
+            ExecError::InsufficientBalance(err) => {
+                300 + match err {
+                    InsufficientBalanceError::Call => 0,
+                    InsufficientBalanceError::Create => 1,
+                    InsufficientBalanceError::Create2 => 2,
+                }
+            }
+            ExecError::ContractAddressCollision(err) => {
+                400 + match err {
+                    ContractAddressCollisionError::Create => 0,
+                    ContractAddressCollisionError::Create2 => 1,
+                }
+            }
+            ExecError::NonceUintOverflow(err) => {
+                500 + match err {
+                    NonceUintOverflowError::Create => 0,
+                    NonceUintOverflowError::Create2 => 1,
+                }
+            }
+        }
+    }
+}
+
+impl Display for ExecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{self:?} (code {})", self.code())
+    }
+}
+
 // TODO: Move to impl block.
 pub(crate) fn get_step_reported_error(
     op: &OpcodeId,
@@ -230,3 +352,95 @@ pub(crate) fn get_step_reported_error(
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_input_builder::{ExecState, ExecStep};
+    use eth_types::evm_types::ProgramCounter;
+    use std::collections::HashSet;
+
+    #[test]
+    fn exec_error_codes_are_unique() {
+        let errors = [
+            ExecError::InvalidOpcode,
+            ExecError::StackOverflow,
+            ExecError::StackUnderflow,
+            ExecError::WriteProtection,
+            ExecError::InvalidCreationCode,
+            ExecError::InvalidJump,
+            ExecError::ReturnDataOutOfBounds,
+            ExecError::CodeStoreOutOfGas,
+            ExecError::MaxCodeSizeExceeded,
+            ExecError::PrecompileFailed,
+        ]
+        .into_iter()
+        .chain(
+            [
+                OogError::Constant,
+                OogError::StaticMemoryExpansion,
+                OogError::DynamicMemoryExpansion,
+                OogError::MemoryCopy,
+                OogError::AccountAccess,
+                OogError::CodeStore,
+                OogError::Log,
+                OogError::Exp,
+                OogError::Sha3,
+                OogError::SloadSstore,
+                OogError::Call,
+                OogError::Precompile,
+                OogError::Create,
+                OogError::SelfDestruct,
+            ]
+            .map(ExecError::OutOfGas),
+        )
+        .chain([DepthError::Call, DepthError::Create, DepthError::Create2].map(ExecError::Depth))
+        .chain(
+            [
+                InsufficientBalanceError::Call,
+                InsufficientBalanceError::Create,
+                InsufficientBalanceError::Create2,
+            ]
+            .map(ExecError::InsufficientBalance),
+        )
+        .chain(
+            [
+                ContractAddressCollisionError::Create,
+                ContractAddressCollisionError::Create2,
+            ]
+            .map(ExecError::ContractAddressCollision),
+        )
+        .chain(
+            [
+                NonceUintOverflowError::Create,
+                NonceUintOverflowError::Create2,
+            ]
+            .map(ExecError::NonceUintOverflow),
+        )
+        .collect::<Vec<_>>();
+
+        let codes = errors.iter().map(ExecError::code).collect::<HashSet<_>>();
+        assert_eq!(codes.len(), errors.len());
+        assert_eq!(ExecError::OutOfGas(OogError::Call).code(), 110);
+    }
+
+    #[test]
+    fn exec_step_error_context() {
+        let mut step = ExecStep {
+            exec_state: ExecState::Op(OpcodeId::SSTORE),
+            pc: ProgramCounter(0x1a),
+            depth: 2,
+            call_index: 3,
+            ..Default::default()
+        };
+        assert_eq!(step.error_context(), None);
+
+        step.error = Some(ExecError::OutOfGas(OogError::SloadSstore));
+        let context = step.error_context().unwrap();
+        assert_eq!(context.code, 109);
+        assert_eq!(
+            context.to_string(),
+            "OutOfGas(SloadSstore) (code 109) at Op(SSTORE) pc 26 depth 2 call 3"
+        );
+    }
+}
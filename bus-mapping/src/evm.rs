@@ -3,7 +3,7 @@
 pub(crate) mod opcodes;
 
 pub use eth_types::evm_types::opcode_ids::OpcodeId;
-pub use opcodes::{calc_expected_tx_refund, Opcode};
+pub use opcodes::{calc_expected_tx_refund, FnGenAssociatedOps, Opcode, OpcodeRegistry};
 
 #[cfg(any(feature = "test", test))]
 pub use opcodes::{gen_sha3_code, MemoryKind};
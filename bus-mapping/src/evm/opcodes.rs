@@ -1,6 +1,8 @@
 //! Definition of each opcode of the EVM.
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecState, ExecStep},
+    circuit_input_builder::{
+        CircuitInputStateRef, ExecState, ExecStep, TraceCheckKind, UnsupportedReason,
+    },
     error::{
         ContractAddressCollisionError, DepthError, ExecError, InsufficientBalanceError,
         NonceUintOverflowError, OogError,
@@ -12,9 +14,6 @@ use crate::{
 use core::fmt::Debug;
 use eth_types::{evm_unimplemented, GethExecStep, ToAddress, ToWord, Word};
 
-#[cfg(any(feature = "enable-memory", feature = "enable-stack"))]
-use crate::util::GETH_TRACE_CHECK_LEVEL;
-
 #[cfg(any(feature = "test", test))]
 pub use self::sha3::sha3_tests::{gen_sha3_code, MemoryKind};
 
@@ -50,6 +49,7 @@ mod origin;
 mod precompiles;
 mod push0;
 mod pushn;
+mod registry;
 mod return_revert;
 mod returndatacopy;
 mod returndatasize;
@@ -134,6 +134,7 @@ use swap::Swap;
 use tload::Tload;
 use tstore::Tstore;
 
+pub use registry::OpcodeRegistry;
 pub use sstore::calc_expected_tx_refund;
 
 /// Generic opcode trait which defines the logic of the
@@ -172,17 +173,25 @@ impl Opcode for Dummy {
     }
 }
 
-type FnGenAssociatedOps = fn(
+/// Function generating the steps and operations of an opcode, see
+/// [`Opcode::gen_associated_ops`].
+pub type FnGenAssociatedOps = fn(
     state: &mut CircuitInputStateRef,
     geth_steps: &[GethExecStep],
 ) -> Result<Vec<ExecStep>, Error>;
 
-fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
+fn fn_gen_associated_ops(
+    registry: &OpcodeRegistry,
+    opcode_id: &OpcodeId,
+) -> Option<FnGenAssociatedOps> {
+    if let Some(handler) = registry.get(opcode_id) {
+        return Some(handler);
+    }
     if opcode_id.is_push_with_data() {
-        return PushN::gen_associated_ops;
+        return Some(PushN::gen_associated_ops);
     }
 
-    match opcode_id {
+    Some(match opcode_id {
         OpcodeId::PUSH0 => Push0::gen_associated_ops,
         OpcodeId::STOP => Stop::gen_associated_ops,
         OpcodeId::ADD => ArithmeticOpcode::<{ OpcodeId::ADD }, 2>::gen_associated_ops,
@@ -293,16 +302,30 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::CREATE => Create::<false>::gen_associated_ops,
         OpcodeId::CREATE2 => Create::<true>::gen_associated_ops,
         OpcodeId::RETURN | OpcodeId::REVERT => ReturnRevert::gen_associated_ops,
-        OpcodeId::INVALID(_) => Stop::gen_associated_ops,
         OpcodeId::SELFDESTRUCT => {
             log::debug!("Using dummy gen_selfdestruct_ops for opcode SELFDESTRUCT");
             DummySelfDestruct::gen_associated_ops
         }
-        _ => {
-            log::debug!("Using dummy gen_associated_ops for opcode {:?}", opcode_id);
-            Dummy::gen_associated_ops
-        }
-    }
+        // `INVALID(_)` isn't handled as STOP anymore: the opcodes unknown to
+        // the EVM fail with the invalid opcode error, which is handled as an
+        // error state, so an `INVALID` step without error is an opcode of the
+        // chain which has no circuit support. Proving it as STOP would prove a
+        // halt the chain did not do.
+        _ => return None,
+    })
+}
+
+/// Opcodes whose built-in handler is a dummy one: the builder keeps its state
+/// right, but the circuits don't prove their steps.
+const DUMMY_OPCODES: [OpcodeId; 1] = [OpcodeId::SELFDESTRUCT];
+
+/// Whether the opcode has circuit support, given the custom opcodes of
+/// `registry`. The opcodes with a dummy built-in handler are unsupported
+/// unless `registry` has a handler for them.
+pub(crate) fn is_supported_opcode(registry: &OpcodeRegistry, opcode_id: &OpcodeId) -> bool {
+    registry.get(opcode_id).is_some()
+        || (!DUMMY_OPCODES.contains(opcode_id)
+            && fn_gen_associated_ops(registry, opcode_id).is_some())
 }
 
 fn fn_gen_error_state_associated_ops(
@@ -385,10 +408,7 @@ fn fn_gen_error_state_associated_ops(
         }
         ExecError::InvalidCreationCode => Some(ErrorCreationCode::gen_associated_ops),
         // more future errors place here
-        _ => {
-            evm_unimplemented!("TODO: error state {:?} not implemented", error);
-            None
-        }
+        _ => None,
     }
 }
 
@@ -401,74 +421,112 @@ pub fn gen_associated_ops(
     geth_steps: &[GethExecStep],
 ) -> Result<Vec<ExecStep>, Error> {
     #[cfg(feature = "enable-memory")]
-    if GETH_TRACE_CHECK_LEVEL.should_check() {
+    if state.block.trace_check.is_enabled(TraceCheckKind::Memory) {
         let memory_enabled = !geth_steps.iter().all(|s| s.memory.is_empty());
-        assert!(memory_enabled);
-        if memory_enabled {
-            #[allow(clippy::collapsible_else_if)]
-            if state.call_ctx()?.memory != geth_steps[0].memory {
-                log::error!(
-                    "wrong mem before {:?}. len in state {}, len in step {}",
-                    opcode_id,
-                    &state.call_ctx()?.memory.len(),
-                    &geth_steps[0].memory.len(),
-                );
-                log::error!("state mem {:?}", &state.call_ctx()?.memory);
-                log::error!("step  mem {:?}", &geth_steps[0].memory);
-
-                for i in 0..std::cmp::min(
-                    state.call_ctx()?.memory.0.len(),
-                    geth_steps[0].memory.0.len(),
-                ) {
-                    let state_mem = state.call_ctx()?.memory.0[i];
-                    let step_mem = geth_steps[0].memory.0[i];
-                    if state_mem != step_mem {
-                        log::error!(
-                            "diff at {}: state {:?} != step {:?}",
-                            i,
-                            state_mem,
-                            step_mem
-                        );
-                    }
-                }
-                if GETH_TRACE_CHECK_LEVEL.should_panic() {
-                    panic!("mem wrong");
-                }
-                state.call_ctx_mut()?.memory = geth_steps[0].memory.clone();
-            }
+        if memory_enabled && state.call_ctx()?.memory != geth_steps[0].memory {
+            let state_mem = &state.call_ctx()?.memory;
+            let step_mem = &geth_steps[0].memory;
+            let first_diff = (0..std::cmp::min(state_mem.len(), step_mem.len()))
+                .find(|&i| state_mem.get(i) != step_mem.get(i));
+            log::debug!("state mem {:?}", state_mem);
+            log::debug!("step  mem {:?}", step_mem);
+            let detail = format!(
+                "len in state {}, len in step {}, first diff at {:?}",
+                state_mem.len(),
+                step_mem.len(),
+                first_diff
+            );
+            state.report_trace_mismatch(&geth_steps[0], TraceCheckKind::Memory, detail)?;
+            state.call_ctx_mut()?.memory = geth_steps[0].memory.clone();
         }
     }
     #[cfg(feature = "enable-stack")]
-    if GETH_TRACE_CHECK_LEVEL.should_check() {
+    if state.block.trace_check.is_enabled(TraceCheckKind::Stack) {
         if state.call_ctx()?.stack != geth_steps[0].stack {
-            log::error!(
-                "wrong stack before {:?}. len in state {}, len in step {}",
-                opcode_id,
-                &state.call_ctx()?.stack.len(),
-                &geth_steps[0].stack.len(),
+            let state_stack = &state.call_ctx()?.stack.0;
+            let step_stack = &geth_steps[0].stack.0;
+            let first_diff = (0..std::cmp::min(state_stack.len(), step_stack.len()))
+                .find(|&i| state_stack[i] != step_stack[i]);
+            log::debug!("state stack {:?}", state_stack);
+            log::debug!("step  stack {:?}", step_stack);
+            let detail = format!(
+                "len in state {}, len in step {}, first diff at {:?}",
+                state_stack.len(),
+                step_stack.len(),
+                first_diff
             );
-            log::error!("state stack {:?}", &state.call_ctx()?.stack);
-            log::error!("step  stack {:?}", &geth_steps[0].stack);
-
-            for i in 0..std::cmp::min(state.call_ctx()?.stack.0.len(), geth_steps[0].stack.0.len())
+            state.report_trace_mismatch(&geth_steps[0], TraceCheckKind::Stack, detail)?;
+            state.call_ctx_mut()?.stack = geth_steps[0].stack.clone();
+        } else {
+            log::debug!("stack sanity check passed");
+        }
+    }
+    #[cfg(all(feature = "enable-stack", feature = "enable-storage"))]
+    if state.block.trace_check.is_enabled(TraceCheckKind::Storage) && *opcode_id == OpcodeId::SLOAD
+    {
+        if let Ok(key) = geth_steps[0].stack.last() {
+            let address = state.call()?.address;
+            let (_, value) = state.sdb.get_storage(&address, &key);
+            let value = *value;
+            if let Some(step_value) = geth_steps[0].storage.0.get(&key) {
+                if *step_value != value {
+                    let detail = format!(
+                        "slot {key:?} of {address:?}: {value:?} in state, {step_value:?} in step"
+                    );
+                    state.report_trace_mismatch(&geth_steps[0], TraceCheckKind::Storage, detail)?;
+                }
+            }
+        }
+    }
+    if state.block.trace_check.is_enabled(TraceCheckKind::Gas) {
+        let step = &geth_steps[0];
+        if let Some(next_step) = geth_steps.get(1) {
+            // the gas cost of calls and creates includes the gas passed to
+            // the callee, and the steps which end the call are followed by a
+            // step of the caller
+            if step.error.is_none()
+                && !opcode_id.is_call_or_create()
+                && next_step.depth == step.depth
             {
-                let state_stack = state.call_ctx()?.stack.0[i];
-                let step_stack = geth_steps[0].stack.0[i];
-                if state_stack != step_stack {
-                    log::error!(
-                        "diff at {}: state {:?} != step {:?}",
-                        i,
-                        state_stack,
-                        step_stack
+                let expected = step.gas.0.checked_sub(step.gas_cost.0);
+                if expected != Some(next_step.gas.0) {
+                    let detail = format!(
+                        "gas {} - cost {} != gas of the next step {}",
+                        step.gas.0, step.gas_cost.0, next_step.gas.0
                     );
+                    state.report_trace_mismatch(step, TraceCheckKind::Gas, detail)?;
                 }
             }
-            if GETH_TRACE_CHECK_LEVEL.should_panic() {
-                panic!("stack wrong");
+        }
+    }
+    // with `fix-refund` the traced refund is known to be wrong, and the steps
+    // get the refund of the state instead
+    #[cfg(not(feature = "fix-refund"))]
+    if state.block.trace_check.is_enabled(TraceCheckKind::Refund)
+        && geth_steps[0].refund.0 != state.sdb.refund()
+    {
+        let detail = format!(
+            "refund {} in state, {} in step",
+            state.sdb.refund(),
+            geth_steps[0].refund.0
+        );
+        state.report_trace_mismatch(&geth_steps[0], TraceCheckKind::Refund, detail)?;
+    }
+    if state
+        .block
+        .trace_check
+        .is_enabled(TraceCheckKind::ReturnData)
+    {
+        if let Some(return_data) = &geth_steps[0].return_data {
+            if state.call_ctx()?.return_data != return_data.as_ref() {
+                let detail = format!(
+                    "len in state {}, len in step {}",
+                    state.call_ctx()?.return_data.len(),
+                    return_data.len()
+                );
+                state.report_trace_mismatch(&geth_steps[0], TraceCheckKind::ReturnData, detail)?;
+                state.call_ctx_mut()?.return_data = return_data.to_vec();
             }
-            state.call_ctx_mut()?.stack = geth_steps[0].stack.clone();
-        } else {
-            log::debug!("stack sanity check passed");
         }
     }
 
@@ -501,6 +559,13 @@ pub fn gen_associated_ops(
             steps[0].error = Some(exec_error.clone());
             return Ok(steps);
         } else {
+            state.handle_unsupported(UnsupportedReason::ErrorState(
+                geth_step.op,
+                exec_error.clone(),
+            ))?;
+            evm_unimplemented!("TODO: error state {:?} not implemented", exec_error);
+            exec_step.unsupported = true;
+
             // For exceptions that fail to enter next call context, we need
             // to restore call context of current caller
             let mut need_restore = true;
@@ -525,8 +590,25 @@ pub fn gen_associated_ops(
         }
     }
     // if no errors, continue as normal
-    let fn_gen_associated_ops = fn_gen_associated_ops(opcode_id);
-    fn_gen_associated_ops(state, geth_steps)
+    match fn_gen_associated_ops(&state.block.opcode_registry, opcode_id) {
+        Some(fn_gen_associated_ops) => {
+            // the steps of a dummy handler aren't proven, so the policy
+            // applies to them as to the opcodes without handler
+            if !is_supported_opcode(&state.block.opcode_registry, opcode_id) {
+                state.handle_unsupported(UnsupportedReason::Opcode(*opcode_id))?;
+            }
+            fn_gen_associated_ops(state, geth_steps)
+        }
+        None => {
+            state.handle_unsupported(UnsupportedReason::Opcode(*opcode_id))?;
+            log::debug!("Using dummy gen_associated_ops for opcode {:?}", opcode_id);
+            let mut steps = Dummy::gen_associated_ops(state, geth_steps)?;
+            for step in steps.iter_mut() {
+                step.unsupported = true;
+            }
+            Ok(steps)
+        }
+    }
 }
 
 pub fn gen_associated_steps(
@@ -1,6 +1,6 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     operation::CallContextField,
     Error,
 };
@@ -16,6 +16,7 @@ impl Opcode for Address {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
         let address = state.call()?.address.to_word();
         #[cfg(feature = "enable-stack")]
@@ -30,7 +31,8 @@ impl Opcode for Address {
         )?;
 
         // Write the address to stack.
-        state.stack_push(&mut exec_step, address)?;
+        stack_ops.push(state, &mut exec_step, address)?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
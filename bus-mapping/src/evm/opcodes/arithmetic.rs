@@ -1,5 +1,5 @@
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     evm::Opcode,
     Error,
 };
@@ -336,25 +336,24 @@ where
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
 
-        let stack_inputs: [Word; N_POPS] = state
-            .stack_pops(&mut exec_step, N_POPS)?
-            .try_into()
-            .unwrap();
+        let _stack_inputs = StackOpsBuilder::new(OP).emit(state, &mut exec_step, |inputs| {
+            vec![Self::handle(inputs.try_into().unwrap())]
+        })?;
 
         #[cfg(feature = "enable-stack")]
-        for (i, input) in stack_inputs.iter().enumerate() {
+        for (i, input) in _stack_inputs.iter().enumerate() {
             assert_eq!(*input, geth_step.stack.nth_last(i)?);
         }
-        let output = Self::handle(stack_inputs);
-        state.stack_push(&mut exec_step, output)?;
 
+        #[cfg(feature = "enable-stack")]
+        let output = state.call_ctx()?.stack.last()?;
         #[cfg(feature = "enable-stack")]
         assert_eq!(
             output,
             geth_steps[1].stack.nth_last(0)?,
             "stack mismatch, opcode: {}, inputs: {}, actual: {:x}, expected: {:x}",
             OP,
-            stack_inputs.iter().map(|w| format!("{w:x}")).join(", "),
+            _stack_inputs.iter().map(|w| format!("{w:x}")).join(", "),
             output,
             geth_steps[1].stack.nth_last(0)?
         );
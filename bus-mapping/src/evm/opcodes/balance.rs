@@ -1,6 +1,7 @@
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     evm::Opcode,
+    gas::GasStep,
     operation::{AccountField, CallContextField, TxAccessListAccountOp},
     Error,
 };
@@ -16,9 +17,10 @@ impl Opcode for Balance {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
         // Read account address from stack.
-        let address_word = state.stack_pop(&mut exec_step)?;
+        let address_word = stack_ops.pop(state, &mut exec_step)?;
         let address = address_word.to_address();
         #[cfg(feature = "enable-stack")]
         assert_eq!(address_word, geth_step.stack.last()?);
@@ -46,6 +48,13 @@ impl Opcode for Balance {
 
         // Update transaction access list for account address.
         let is_warm = state.sdb.check_account_in_access_list(&address);
+        state.check_step_gas_cost(
+            geth_step,
+            &GasStep {
+                is_warm,
+                ..GasStep::new(geth_step.op)
+            },
+        )?;
         state.push_op_reversible(
             &mut exec_step,
             TxAccessListAccountOp {
@@ -78,7 +87,8 @@ impl Opcode for Balance {
         // Write the BALANCE result to stack.
         #[cfg(feature = "enable-stack")]
         assert_eq!(geth_steps[1].stack.last()?, balance);
-        state.stack_push(&mut exec_step, balance)?;
+        stack_ops.push(state, &mut exec_step, balance)?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -4,10 +4,9 @@ use super::{
 };
 use crate::{
     circuit_input_builder::{
-        curie::is_curie_enabled, Call, CircuitInputStateRef, CopyAccessList, CopyBytes,
-        CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+        Call, CircuitInputStateRef, CopyAccessList, CopyBytes, CopyDataType, CopyEvent, ExecStep,
+        NumberOrHash,
     },
-    l2_predeployed::l1_gas_price_oracle,
     operation::{
         AccountField, AccountOp, CallContextField, StorageOp, TxReceiptField, TxRefundOp, RW,
     },
@@ -160,17 +159,23 @@ pub fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<Vec<ExecSt
         )?;
     }
 
-    // Add caller, callee and coinbase (only for Shanghai) to access list.
-    let accessed_addresses = [
-        call.caller_address,
-        call.address,
-        state
-            .block
-            .blocks
-            .get(&state.tx.block_num)
-            .unwrap()
-            .coinbase,
-    ];
+    // Add caller, callee and, from Shanghai, coinbase (EIP-3651) to access
+    // list.
+    let coinbase = state
+        .block
+        .chain_spec
+        .is_shanghai(state.tx.block_num)
+        .then(|| {
+            state
+                .block
+                .blocks
+                .get(&state.tx.block_num)
+                .unwrap()
+                .coinbase
+        });
+    let accessed_addresses = [call.caller_address, call.address]
+        .into_iter()
+        .chain(coinbase);
     for address in accessed_addresses {
         let is_warm_prev = !state.sdb.add_account_to_access_list(address);
         state.tx_access_list_account_write(
@@ -289,13 +294,13 @@ pub fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<Vec<ExecSt
             stream.append(&caller_address);
             stream.append(&nonce_prev);
             stream.out().to_vec()
-        });
+        })?;
         // 2. add init code to keccak circuit.
         let init_code = state.tx.input.as_slice();
         let length = init_code.len();
-        state.block.sha3_inputs.push(init_code.to_vec());
+        state.block.sha3_inputs.push(init_code)?;
         // 3. add init code to copy circuit.
-        let code_hash = CodeDB::hash(init_code);
+        let code_hash = state.code_db.code_hash(init_code);
         let bytes = Bytecode::from(init_code.to_vec())
             .code
             .iter()
@@ -303,19 +308,20 @@ pub fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<Vec<ExecSt
             .collect::<Vec<(u8, bool, bool)>>();
 
         let rw_counter_start = state.block_ctx.rwc;
+        let copy_bytes = CopyBytes::new(&mut state.block.copy_bytes, bytes, None, None)?;
         state.push_copy(
             &mut exec_step,
             CopyEvent {
                 src_addr: 0,
                 src_addr_end: length as u64,
                 src_type: CopyDataType::TxCalldata,
-                src_id: NumberOrHash::Number(state.tx_ctx.id()),
+                src_id: NumberOrHash::Number(state.call_data_tx_id()),
                 dst_addr: 0,
                 dst_type: CopyDataType::Bytecode,
                 dst_id: NumberOrHash::Hash(code_hash),
                 log_id: None,
                 rw_counter_start,
-                copy_bytes: CopyBytes::new(bytes, None, None),
+                copy_bytes,
                 access_list: vec![],
             },
         );
@@ -450,10 +456,11 @@ pub fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<Vec<ExecSt
                 .map(|t| t.0)
                 .collect::<Vec<u8>>();
             let rw_counter_start = state.block_ctx.rwc;
+            let copy_bytes = CopyBytes::new(&mut state.block.copy_bytes, copy_steps, None, None)?;
             state.push_copy(
                 &mut exec_step,
                 CopyEvent {
-                    src_id: NumberOrHash::Number(state.tx_ctx.id()),
+                    src_id: NumberOrHash::Number(state.call_data_tx_id()),
                     src_type: CopyDataType::TxCalldata,
                     src_addr,
                     src_addr_end,
@@ -462,7 +469,7 @@ pub fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<Vec<ExecSt
                     dst_addr: 0,
                     log_id: None,
                     rw_counter_start,
-                    copy_bytes: CopyBytes::new(copy_steps, None, None),
+                    copy_bytes,
                     access_list: vec![],
                 },
             );
@@ -726,103 +733,29 @@ fn write_tx_receipt(
     Ok(())
 }
 
-// Add 3(or 6 after curie) RW read operations for transaction L1 fee.
+// Add RW read operations for transaction L1 fee, 3 (or 6 after curie) for the
+// scroll schema.
 fn gen_tx_l1_fee_ops(
     state: &mut CircuitInputStateRef,
     exec_step: &mut ExecStep,
 ) -> Result<(), Error> {
     let tx_id = state.tx_ctx.id();
+    let address = state.tx.l1_fee.schema.address();
+    let slots = state.tx.l1_fee.slots();
+    let committed_slots = state.tx.l1_fee_committed.slots();
+    debug_assert_eq!(slots.len(), committed_slots.len());
 
-    let base_fee = Word::from(state.tx.l1_fee.base_fee);
-    let fee_overhead = Word::from(state.tx.l1_fee.fee_overhead);
-    let fee_scalar = Word::from(state.tx.l1_fee.fee_scalar);
-    let l1_blob_basefee = Word::from(state.tx.l1_fee.l1_blob_basefee);
-    let commit_scalar = Word::from(state.tx.l1_fee.commit_scalar);
-    let blob_scalar = Word::from(state.tx.l1_fee.blob_scalar);
-
-    let base_fee_committed = Word::from(state.tx.l1_fee_committed.base_fee);
-    let fee_overhead_committed = Word::from(state.tx.l1_fee_committed.fee_overhead);
-    let fee_scalar_committed = Word::from(state.tx.l1_fee_committed.fee_scalar);
-
-    let l1_blob_basefee_committed = Word::from(state.tx.l1_fee_committed.l1_blob_basefee);
-    let commit_scalar_committed = Word::from(state.tx.l1_fee_committed.commit_scalar);
-    let blob_scalar_committed = Word::from(state.tx.l1_fee_committed.blob_scalar);
-
-    state.push_op(
-        exec_step,
-        RW::READ,
-        StorageOp::new(
-            *l1_gas_price_oracle::ADDRESS,
-            *l1_gas_price_oracle::BASE_FEE_SLOT,
-            base_fee,
-            base_fee,
-            tx_id,
-            base_fee_committed,
-        ),
-    )?;
-    state.push_op(
-        exec_step,
-        RW::READ,
-        StorageOp::new(
-            *l1_gas_price_oracle::ADDRESS,
-            *l1_gas_price_oracle::OVERHEAD_SLOT,
-            fee_overhead,
-            fee_overhead,
-            tx_id,
-            fee_overhead_committed,
-        ),
-    )?;
-    state.push_op(
-        exec_step,
-        RW::READ,
-        StorageOp::new(
-            *l1_gas_price_oracle::ADDRESS,
-            *l1_gas_price_oracle::SCALAR_SLOT,
-            fee_scalar,
-            fee_scalar,
-            tx_id,
-            fee_scalar_committed,
-        ),
-    )?;
-
-    // curie operations
-    let chain_id = state.block.chain_id;
-    let block_number = state.tx.block_num;
-    if is_curie_enabled(chain_id, block_number) {
-        state.push_op(
-            exec_step,
-            RW::READ,
-            StorageOp::new(
-                *l1_gas_price_oracle::ADDRESS,
-                *l1_gas_price_oracle::L1_BLOB_BASEFEE_SLOT,
-                l1_blob_basefee,
-                l1_blob_basefee,
-                tx_id,
-                l1_blob_basefee_committed,
-            ),
-        )?;
-        state.push_op(
-            exec_step,
-            RW::READ,
-            StorageOp::new(
-                *l1_gas_price_oracle::ADDRESS,
-                *l1_gas_price_oracle::COMMIT_SCALAR_SLOT,
-                commit_scalar,
-                commit_scalar,
-                tx_id,
-                commit_scalar_committed,
-            ),
-        )?;
+    for ((slot, value), (_, value_committed)) in slots.into_iter().zip(committed_slots) {
         state.push_op(
             exec_step,
             RW::READ,
             StorageOp::new(
-                *l1_gas_price_oracle::ADDRESS,
-                *l1_gas_price_oracle::BLOB_SCALAR_SLOT,
-                blob_scalar,
-                blob_scalar,
+                address,
+                slot,
+                Word::from(value),
+                Word::from(value),
                 tx_id,
-                blob_scalar_committed,
+                Word::from(value_committed),
             ),
         )?;
     }
@@ -889,7 +822,12 @@ fn add_access_list_address_copy_event(
     let tx_id = NumberOrHash::Number(tx_id);
 
     // Use placeholder bytes for copy steps.
-    let copy_bytes = CopyBytes::new(vec![(0, false, false); access_list.len()], None, None);
+    let copy_bytes = CopyBytes::new(
+        &mut state.block.copy_bytes,
+        vec![(0, false, false); access_list.len()],
+        None,
+        None,
+    )?;
 
     // Add copy event to copy table.
     let copy_event = CopyEvent {
@@ -974,7 +912,12 @@ fn add_access_list_storage_key_copy_event(
     let tx_id = NumberOrHash::Number(state.tx_ctx.id());
 
     // Use placeholder bytes for copy steps.
-    let copy_bytes = CopyBytes::new(vec![(0, false, false); access_list.len()], None, None);
+    let copy_bytes = CopyBytes::new(
+        &mut state.block.copy_bytes,
+        vec![(0, false, false); access_list.len()],
+        None,
+        None,
+    )?;
 
     // Add copy event to copy table.
     let copy_event = CopyEvent {
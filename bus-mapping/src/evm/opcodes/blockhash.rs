@@ -1,5 +1,5 @@
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     Error,
 };
 use eth_types::{
@@ -19,8 +19,9 @@ impl Opcode for Blockhash {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
-        let block_number = state.stack_pop(&mut exec_step)?;
+        let block_number = stack_ops.pop(state, &mut exec_step)?;
         #[cfg(feature = "enable-stack")]
         assert_eq!(block_number, geth_step.stack.last()?);
 
@@ -29,7 +30,7 @@ impl Opcode for Blockhash {
             if cfg!(feature = "scroll") {
                 let (sha3_input, sha3_output) =
                     calculate_block_hash(state.block.chain_id, block_number);
-                state.block.sha3_inputs.push(sha3_input);
+                state.block.sha3_inputs.push(sha3_input)?;
                 sha3_output
             } else {
                 let block_head = state.block.blocks.get(&current_block_number).unwrap();
@@ -42,7 +43,8 @@ impl Opcode for Blockhash {
         };
         #[cfg(feature = "enable-stack")]
         assert_eq!(block_hash, geth_steps[1].stack.last()?);
-        state.stack_push(&mut exec_step, block_hash)?;
+        stack_ops.push(state, &mut exec_step, block_hash)?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
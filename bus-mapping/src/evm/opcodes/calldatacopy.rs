@@ -2,6 +2,7 @@ use super::Opcode;
 use crate::{
     circuit_input_builder::{
         CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+        StackOpsBuilder,
     },
     operation::CallContextField,
     Error,
@@ -19,9 +20,11 @@ impl Opcode for Calldatacopy {
         let geth_step = &geth_steps[0];
 
         let mut exec_step = state.new_step(geth_step)?;
-        let memory_offset = state.stack_pop(&mut exec_step)?;
-        let data_offset = state.stack_pop(&mut exec_step)?;
-        let length = state.stack_pop(&mut exec_step)?;
+
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
+        let memory_offset = stack_ops.pop(state, &mut exec_step)?;
+        let data_offset = stack_ops.pop(state, &mut exec_step)?;
+        let length = stack_ops.pop(state, &mut exec_step)?;
         #[cfg(feature = "enable-stack")]
         {
             assert_eq!(memory_offset, geth_step.stack.nth_last(0)?);
@@ -64,6 +67,7 @@ impl Opcode for Calldatacopy {
 
         let copy_event = gen_copy_event(state, memory_offset, data_offset, length, &mut exec_step)?;
         state.push_copy(&mut exec_step, copy_event);
+        stack_ops.finish()?;
         Ok(vec![exec_step])
     }
 }
@@ -95,11 +99,16 @@ fn gen_copy_event(
         let (copy_steps, prev_bytes) =
             state.gen_copy_steps_for_call_data_root(exec_step, src_addr, dst_addr, length)?;
 
-        let copy_bytes = CopyBytes::new(copy_steps, None, Some(prev_bytes));
+        let copy_bytes = CopyBytes::new(
+            &mut state.block.copy_bytes,
+            copy_steps,
+            None,
+            Some(prev_bytes),
+        )?;
 
         Ok(CopyEvent {
             src_type: CopyDataType::TxCalldata,
-            src_id: NumberOrHash::Number(state.tx_ctx.id()),
+            src_id: NumberOrHash::Number(state.call_data_tx_id()),
             src_addr,
             src_addr_end,
             dst_type: CopyDataType::Memory,
@@ -125,7 +134,12 @@ fn gen_copy_event(
             log_id: None,
             rw_counter_start,
             //fetch pre read and write bytes of CopyBytes
-            copy_bytes: CopyBytes::new(read_steps, Some(write_steps), Some(prev_bytes)),
+            copy_bytes: CopyBytes::new(
+                &mut state.block.copy_bytes,
+                read_steps,
+                Some(write_steps),
+                Some(prev_bytes),
+            )?,
             access_list: vec![],
         })
     }
@@ -295,11 +309,12 @@ mod calldatacopy_tests {
         );
         assert_eq!(copy_events[0].dst_addr as usize, dst_offset);
 
-        for (idx, (value, is_code, _)) in copy_events[0].copy_bytes.bytes.iter().enumerate() {
+        let copy_steps = copy_events[0].copy_bytes.bytes(&builder.block.copy_bytes);
+        for (idx, (value, is_code, _)) in copy_steps.iter().enumerate() {
             if idx < memory_a.len() {
-                assert_eq!(*value, memory_a[idx]);
+                assert_eq!(value, memory_a[idx]);
             } else {
-                assert_eq!(*value, 0);
+                assert_eq!(value, 0);
             }
             assert!(!is_code);
         }
@@ -452,7 +467,10 @@ mod calldatacopy_tests {
         let begin_slot = dst_offset - dst_offset % 32;
         let end_slot = (dst_offset + size - 1) - (dst_offset + size - 1) % 32;
         assert_eq!(
-            copy_events[0].copy_bytes.bytes.len(),
+            copy_events[0]
+                .copy_bytes
+                .bytes(&builder.block.copy_bytes)
+                .len(),
             end_slot - begin_slot + 32
         );
         assert_eq!(
@@ -460,9 +478,55 @@ mod calldatacopy_tests {
             (end_slot - begin_slot) / 32 + 1
         );
 
-        for (idx, (value, is_code, _)) in copy_events[0].copy_bytes.bytes.iter().enumerate() {
-            assert_eq!(value, calldata.get(offset as usize + idx).unwrap_or(&0));
+        let copy_steps = copy_events[0].copy_bytes.bytes(&builder.block.copy_bytes);
+        for (idx, (value, is_code, _)) in copy_steps.iter().enumerate() {
+            assert_eq!(value, *calldata.get(offset as usize + idx).unwrap_or(&0));
             assert!(!is_code);
         }
     }
+
+    #[test]
+    fn calldatacopy_opcode_root_shared_calldata() {
+        let calldata = vec![1, 3, 5, 7, 9, 2, 4, 6, 8];
+        let code = bytecode! {
+            .op_calldatacopy(0x00, 0x00, 0x20)
+            STOP
+        };
+
+        // Two txs with identical call data, only the first one owns a call data
+        // section in the tx table.
+        let block: GethData = TestContext::<2, 2>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .input(calldata.clone().into());
+                txs[1]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .input(calldata.clone().into());
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let txs = builder.block.txs();
+        assert_eq!(txs[0].shared_call_data_tx_id, None);
+        assert_eq!(txs[1].shared_call_data_tx_id, Some(1));
+
+        // Both copy events read from the section of the first tx.
+        let copy_events = builder.block.copy_events.clone();
+        assert_eq!(copy_events.len(), 2);
+        for copy_event in copy_events {
+            assert_eq!(copy_event.src_id, NumberOrHash::Number(1));
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     operation::CallContextField,
     Error,
 };
@@ -17,10 +17,11 @@ impl Opcode for Calldataload {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
         // fetch the top of the stack, i.e. offset in calldata to start reading 32-bytes
         // from.
-        let offset = state.stack_pop(&mut exec_step)?;
+        let offset = stack_ops.pop(state, &mut exec_step)?;
         #[cfg(feature = "enable-stack")]
         assert_eq!(offset, geth_step.stack.last()?);
 
@@ -97,7 +98,8 @@ impl Opcode for Calldataload {
             U256::zero()
         };
 
-        state.stack_push(&mut exec_step, calldata_word)?;
+        stack_ops.push(state, &mut exec_step, calldata_word)?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -1,5 +1,5 @@
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     operation::CallContextField,
     Error,
 };
@@ -18,6 +18,7 @@ impl Opcode for Calldatasize {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
         let call_data_length = Word::from(state.call()?.call_data_length);
         state.call_context_read(
             &mut exec_step,
@@ -27,7 +28,8 @@ impl Opcode for Calldatasize {
         )?;
         #[cfg(feature = "enable-stack")]
         assert_eq!(call_data_length, geth_steps[1].stack.last()?);
-        state.stack_push(&mut exec_step, call_data_length)?;
+        stack_ops.push(state, &mut exec_step, call_data_length)?;
+        stack_ops.finish()?;
         Ok(vec![exec_step])
     }
 }
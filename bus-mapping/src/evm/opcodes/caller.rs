@@ -1,6 +1,6 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     operation::CallContextField,
     Error,
 };
@@ -18,6 +18,7 @@ impl Opcode for Caller {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
         // Get caller_address result from next step
         let caller_address = state.call()?.caller_address.to_word();
         // CallContext read of the caller_address
@@ -31,7 +32,8 @@ impl Opcode for Caller {
         // Stack write of the caller_address
         #[cfg(feature = "enable-stack")]
         assert_eq!(caller_address, geth_steps[1].stack.last()?);
-        state.stack_push(&mut exec_step, caller_address)?;
+        stack_ops.push(state, &mut exec_step, caller_address)?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
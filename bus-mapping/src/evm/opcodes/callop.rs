@@ -2,12 +2,13 @@ use super::Opcode;
 use crate::{
     circuit_input_builder::{
         CallKind, CircuitInputStateRef, CodeSource, CopyBytes, CopyDataType, CopyEvent, ExecStep,
-        NumberOrHash,
+        NumberOrHash, StackOpsBuilder,
     },
     evm::opcodes::{
         error_oog_precompile::ErrorOOGPrecompile,
         precompiles::gen_associated_ops as precompile_associated_ops,
     },
+    gas,
     operation::{AccountField, CallContextField, TxAccessListAccountOp},
     precompile::{execute_precompiled, PrecompileCalls},
     Error,
@@ -15,6 +16,7 @@ use crate::{
 use eth_types::{
     evm_types::{
         gas_utils::{eip150_gas, memory_expansion_gas_cost},
+        memory::MemoryRange,
         Gas, GasCost, OpcodeId, GAS_STIPEND_CALL_WITH_VALUE,
     },
     state_db::CodeDB,
@@ -75,8 +77,11 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
         };
         // Precheck is OK when depth is in range and caller balance is sufficient.
         let is_call_or_callcode = matches!(callee_kind, CallKind::Call | CallKind::CallCode);
-        let is_precheck_ok =
-            geth_step.depth < 1025 && (!is_call_or_callcode || caller_balance >= call_value);
+        let is_precheck_ok = !state
+            .block
+            .chain_spec
+            .is_depth_exceeded(geth_step.depth as usize)
+            && (!is_call_or_callcode || caller_balance >= call_value);
 
         let callee_call = if is_precheck_ok {
             state.parse_call(geth_step)?
@@ -130,12 +135,18 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
             state.call_context_read(&mut exec_step, caller_call.call_id, field, value)?;
         }
 
-        let stack_inputs: Vec<Word> = state.stack_pops(&mut exec_step, N_ARGS)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
+        let stack_inputs: Vec<Word> = stack_ops.pops(state, &mut exec_step, N_ARGS)?;
         #[cfg(feature = "enable-stack")]
         for (i, input) in stack_inputs.iter().enumerate() {
             assert_eq!(*input, geth_step.stack.nth_last(i)?);
         }
-        state.stack_push(&mut exec_step, (callee_call.is_success as u64).into())?;
+        stack_ops.push(
+            state,
+            &mut exec_step,
+            (callee_call.is_success as u64).into(),
+        )?;
+        stack_ops.finish()?;
 
         let callee_code_hash = callee_call.code_hash;
         let callee_acc = state.sdb.get_account(&callee_address).1;
@@ -179,8 +190,11 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
             state.call_context_write(&mut exec_step, callee_call.call_id, field, value)?;
         }
 
-        let is_precheck_ok =
-            geth_step.depth < 1025 && (!is_call_or_callcode || caller_balance >= callee_call.value);
+        let is_precheck_ok = !state
+            .block
+            .chain_spec
+            .is_depth_exceeded(geth_step.depth as usize)
+            && (!is_call_or_callcode || caller_balance >= callee_call.value);
 
         // read balance of caller to compare to value for insufficient_balance checking
         // in circuit, also use for callcode successful case check balance is
@@ -226,20 +240,11 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
         let has_value = !callee_call.value.is_zero() && !callee_call.is_delegatecall();
         let memory_expansion_gas_cost =
             memory_expansion_gas_cost(curr_memory_word_size, next_memory_word_size);
-        let gas_cost = if is_warm {
-            GasCost::WARM_ACCESS.as_u64()
-        } else {
-            GasCost::COLD_ACCOUNT_ACCESS.as_u64()
-        } + if has_value {
-            GasCost::CALL_WITH_VALUE.as_u64()
-                + if callee_call.kind == CallKind::Call && !callee_exists {
-                    GasCost::NEW_ACCOUNT.as_u64()
-                } else {
-                    0
-                }
-        } else {
-            0
-        } + memory_expansion_gas_cost;
+        let gas_cost = gas::call(
+            is_warm,
+            has_value,
+            callee_call.kind == CallKind::Call && !callee_exists,
+        ) + memory_expansion_gas_cost;
         let gas_specified = stack_inputs[0];
         debug_assert!(
             geth_step.gas.0 >= gas_cost,
@@ -293,11 +298,13 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                 // For failed call, it will cost all gas provided.
                 let (result, precompile_call_gas_cost, has_oog_err) = execute_precompiled(
                     &code_address,
-                    if args_length != 0 {
-                        let caller_memory = &state.caller_ctx()?.memory;
-                        &caller_memory.0[args_offset..args_offset + args_length]
+                    &if args_length != 0 {
+                        state
+                            .caller_ctx()?
+                            .memory
+                            .read_chunk(MemoryRange::new_with_length(args_offset, args_length))
                     } else {
-                        &[]
+                        vec![]
                     },
                     callee_gas_left_with_stipend,
                 );
@@ -400,6 +407,8 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                         .filter(|(_, _, is_mask)| !*is_mask)
                         .map(|t| t.0)
                         .collect::<Vec<u8>>();
+                    let copy_bytes =
+                        CopyBytes::new(&mut state.block.copy_bytes, copy_steps, None, None)?;
                     state.push_copy(
                         &mut exec_step,
                         CopyEvent {
@@ -408,11 +417,11 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                             src_addr: callee_call.call_data_offset,
                             src_addr_end: callee_call.call_data_offset + n_input_bytes as u64,
                             dst_id: NumberOrHash::Number(callee_call.call_id),
-                            dst_type: CopyDataType::RlcAcc,
+                            dst_type: CopyDataType::PrecompileInput,
                             dst_addr: 0,
                             log_id: None,
                             rw_counter_start,
-                            copy_bytes: CopyBytes::new(copy_steps, None, None),
+                            copy_bytes,
                             access_list: vec![],
                         },
                     );
@@ -431,11 +440,17 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                         .filter(|(_, _, is_mask)| !*is_mask)
                         .map(|t| t.0)
                         .collect::<Vec<u8>>();
+                    let copy_bytes = CopyBytes::new(
+                        &mut state.block.copy_bytes,
+                        copy_steps,
+                        None,
+                        Some(prev_bytes),
+                    )?;
                     state.push_copy(
                         &mut exec_step,
                         CopyEvent {
                             src_id: NumberOrHash::Number(callee_call.call_id),
-                            src_type: CopyDataType::RlcAcc,
+                            src_type: CopyDataType::PrecompileOutput,
                             src_addr: 0,
                             src_addr_end: result.len() as u64,
                             dst_id: NumberOrHash::Number(callee_call.call_id),
@@ -443,7 +458,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                             dst_addr: 0,
                             log_id: None,
                             rw_counter_start,
-                            copy_bytes: CopyBytes::new(copy_steps, None, Some(prev_bytes)),
+                            copy_bytes,
                             access_list: vec![],
                         },
                     );
@@ -467,6 +482,12 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                         .filter(|(_, _, is_mask)| !*is_mask)
                         .map(|t| t.0)
                         .collect::<Vec<u8>>();
+                    let copy_bytes = CopyBytes::new(
+                        &mut state.block.copy_bytes,
+                        read_steps,
+                        Some(write_steps),
+                        Some(prev_bytes),
+                    )?;
                     state.push_copy(
                         &mut exec_step,
                         CopyEvent {
@@ -479,11 +500,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                             dst_addr: callee_call.return_data_offset,
                             log_id: None,
                             rw_counter_start,
-                            copy_bytes: CopyBytes::new(
-                                read_steps,
-                                Some(write_steps),
-                                Some(prev_bytes),
-                            ),
+                            copy_bytes,
                             access_list: vec![],
                         },
                     );
@@ -745,13 +762,13 @@ pub mod tests {
     // move this to circuit after circuit part is complete
     #[test]
     fn test_precompiled_call() {
-        use crate::{circuit_input_builder::CircuitsParams, mock::BlockData};
+        use crate::{
+            circuit_input_builder::{CircuitsParams, CopyDataType},
+            mock::BlockData,
+        };
         use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, word, Word};
         use mock::{
-            test_ctx::{
-                helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
-                LoggerConfig,
-            },
+            test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
             TestContext,
         };
 
@@ -1033,15 +1050,11 @@ pub mod tests {
 
         for (test_call, call_op) in itertools::iproduct!(test_vector.iter(), call_ops.iter()) {
             let code = test_call.with_call_op(*call_op);
-            let block: GethData = TestContext::<2, 1>::new_with_logger_config(
+            let block: GethData = TestContext::<2, 1>::new(
                 None,
                 account_0_code_account_1_no_code(code),
                 tx_from_1_to_0,
                 |block, _tx| block.number(0xcafeu64),
-                LoggerConfig {
-                    enable_memory: true,
-                    ..Default::default()
-                },
             )
             .unwrap()
             .into();
@@ -1058,6 +1071,26 @@ pub mod tests {
                 .handle_block(&block.eth_block, &block.geth_traces)
                 .unwrap();
 
+            // the precompile input is copied from the caller memory, and the
+            // output is written to the callee memory.
+            let copy_events = &builder.block.copy_events;
+            assert!(
+                copy_events.iter().any(|event| {
+                    event.src_type == CopyDataType::Memory
+                        && event.dst_type == CopyDataType::PrecompileInput
+                }),
+                "no precompile input copy event {}",
+                test_call.name
+            );
+            assert!(
+                copy_events
+                    .iter()
+                    .filter(|event| event.src_type == CopyDataType::PrecompileOutput)
+                    .all(|event| event.dst_type == CopyDataType::Memory),
+                "precompile output not copied to memory {}",
+                test_call.name
+            );
+
             #[cfg(feature = "enable-stack")]
             {
                 let step = block.geth_traces[0]
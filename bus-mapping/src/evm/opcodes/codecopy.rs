@@ -1,6 +1,7 @@
 use crate::{
     circuit_input_builder::{
         CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+        StackOpsBuilder,
     },
     Error,
 };
@@ -18,10 +19,11 @@ impl Opcode for Codecopy {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
-        let dest_offset = state.stack_pop(&mut exec_step)?;
-        let code_offset = state.stack_pop(&mut exec_step)?;
-        let length = state.stack_pop(&mut exec_step)?;
+        let dest_offset = stack_ops.pop(state, &mut exec_step)?;
+        let code_offset = stack_ops.pop(state, &mut exec_step)?;
+        let length = stack_ops.pop(state, &mut exec_step)?;
 
         #[cfg(feature = "enable-stack")]
         {
@@ -38,6 +40,7 @@ impl Opcode for Codecopy {
             &mut exec_step,
         )?;
         state.push_copy(&mut exec_step, copy_event);
+        stack_ops.finish()?;
         Ok(vec![exec_step])
     }
 }
@@ -79,7 +82,12 @@ fn gen_copy_event(
         log_id: None,
         rw_counter_start,
         //fetch pre write bytes of CopyBytes
-        copy_bytes: CopyBytes::new(copy_steps, None, Some(prev_bytes)),
+        copy_bytes: CopyBytes::new(
+            &mut state.block.copy_bytes,
+            copy_steps,
+            None,
+            Some(prev_bytes),
+        )?,
         access_list: vec![],
     })
 }
@@ -172,15 +180,15 @@ mod codecopy_tests {
         let word_ops = (copy_end + 32 - copy_start) / 32 - 1;
         let copied_bytes = builder.block.copy_events[0]
             .copy_bytes
-            .bytes
+            .bytes(&builder.block.copy_bytes)
             .iter()
-            .map(|(b, _, _)| *b)
+            .map(|(b, _, _)| b)
             .collect::<Vec<_>>();
         let prev_bytes = builder.block.copy_events[0]
             .copy_bytes
-            .bytes_write_prev
-            .clone()
-            .unwrap();
+            .bytes_write_prev(&builder.block.copy_bytes)
+            .unwrap()
+            .to_vec();
 
         assert_eq!(builder.block.container.memory.len(), word_ops);
         assert_eq!(
@@ -221,11 +229,12 @@ mod codecopy_tests {
         assert_eq!(copy_events[0].dst_type, CopyDataType::Memory);
         assert!(copy_events[0].log_id.is_none());
 
-        for (idx, (value, is_code, is_mask)) in copy_events[0].copy_bytes.bytes.iter().enumerate() {
+        let copy_steps = copy_events[0].copy_bytes.bytes(&builder.block.copy_bytes);
+        for (idx, (value, is_code, is_mask)) in copy_steps.iter().enumerate() {
             let bytecode_element = code.get(code_offset + idx).unwrap_or_default();
             if !is_mask {
-                assert_eq!(*value, bytecode_element.value);
-                assert_eq!(*is_code, bytecode_element.is_code);
+                assert_eq!(value, bytecode_element.value);
+                assert_eq!(is_code, bytecode_element.is_code);
             }
         }
     }
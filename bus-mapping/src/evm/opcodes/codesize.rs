@@ -1,5 +1,5 @@
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     Error,
 };
 
@@ -17,6 +17,7 @@ impl Opcode for Codesize {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
         let code_hash = state.call()?.code_hash;
         let code = state.code(code_hash)?;
@@ -24,7 +25,8 @@ impl Opcode for Codesize {
 
         #[cfg(feature = "enable-stack")]
         assert_eq!(codesize, geth_steps[1].stack.last()?.as_usize());
-        state.stack_push(&mut exec_step, codesize.into())?;
+        stack_ops.push(state, &mut exec_step, codesize.into())?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
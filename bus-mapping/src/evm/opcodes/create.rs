@@ -1,7 +1,7 @@
 use crate::{
     circuit_input_builder::{
         CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, CopyEventStepsBuilder, ExecStep,
-        NumberOrHash,
+        NumberOrHash, StackOpsBuilder,
     },
     error::{ContractAddressCollisionError, ExecError},
     evm::{Opcode, OpcodeId},
@@ -10,7 +10,10 @@ use crate::{
 };
 use eth_types::{
     bytecode::BytecodeElement,
-    evm_types::{memory::MemoryWordRange, Memory},
+    evm_types::{
+        memory::{MemoryRange, MemoryWordRange},
+        Memory,
+    },
     state_db::CodeDB,
     Bytecode, GethExecStep, ToBigEndian, ToWord, Word, H160, H256,
 };
@@ -49,8 +52,9 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
         // Check if an error of ErrDepth, ErrInsufficientBalance or
         // ErrNonceUintOverflow occurred.
         let depth = caller.depth;
-        let is_precheck_ok =
-            depth < 1025 && caller_balance >= callee_value && caller_nonce < u64::MAX;
+        let is_precheck_ok = !state.block.chain_spec.is_depth_exceeded(depth)
+            && caller_balance >= callee_value
+            && caller_nonce < u64::MAX;
         let callee = if is_precheck_ok && !is_address_collision {
             state.parse_call(geth_step)?
         } else {
@@ -105,13 +109,15 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
         )?;
 
         let n_pop = if IS_CREATE2 { 4 } else { 3 };
-        let stack_inputs = state.stack_pops(&mut exec_step, n_pop)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
+        let stack_inputs = stack_ops.pops(state, &mut exec_step, n_pop)?;
         #[cfg(feature = "enable-stack")]
         for (i, value) in stack_inputs.iter().enumerate() {
             assert_eq!(*value, geth_step.stack.nth_last(i)?);
         }
 
-        state.stack_push(
+        stack_ops.push(
+            state,
             &mut exec_step,
             if callee.is_success {
                 address.to_word()
@@ -119,6 +125,7 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
                 Word::zero()
             },
         )?;
+        stack_ops.finish()?;
         // stack end
 
         state.call_context_read(
@@ -266,8 +273,8 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
                 H160(keccak256(&keccak_input)[12..].try_into().unwrap())
             );
 
-            state.block.sha3_inputs.push(keccak_input);
-            state.block.sha3_inputs.push(initialization_code);
+            state.block.sha3_inputs.push(keccak_input)?;
+            state.block.sha3_inputs.push(initialization_code)?;
         }
         if is_precheck_ok && !is_address_collision {
             // Transfer function will skip transfer if the value is zero
@@ -353,12 +360,13 @@ fn handle_copy(
     length: usize,
 ) -> Result<(Vec<u8>, H256, H256), Error> {
     let rw_counter_start = state.block_ctx.rwc;
+    let code_hasher = state.code_db.hasher().clone();
     let call_ctx = state.call_ctx_mut()?;
     let memory: &Memory = &mut call_ctx.memory;
 
-    let initialization_bytes = memory.0[offset..offset + length].to_vec();
+    let initialization_bytes = memory.read_chunk(MemoryRange::new_with_length(offset, length));
     let keccak_code_hash = H256(keccak256(&initialization_bytes));
-    let code_hash = CodeDB::hash(&initialization_bytes);
+    let code_hash = code_hasher.hash_code(&initialization_bytes);
     let bytes = Bytecode::from(initialization_bytes.clone()).code;
 
     let dst_range = MemoryWordRange::align_range(offset, length);
@@ -385,6 +393,7 @@ fn handle_copy(
         .mapper(|v: &BytecodeElement| (v.value, v.is_code))
         .build();
 
+    let copy_bytes = CopyBytes::new(&mut state.block.copy_bytes, copy_steps, None, None)?;
     state.push_copy(
         step,
         CopyEvent {
@@ -397,7 +406,7 @@ fn handle_copy(
             dst_id: NumberOrHash::Hash(code_hash),
             dst_addr: 0,
             log_id: None,
-            copy_bytes: CopyBytes::new(copy_steps, None, None),
+            copy_bytes,
             access_list: vec![],
         },
     );
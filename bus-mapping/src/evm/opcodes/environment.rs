@@ -1,55 +1,58 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{Block, CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{Block, CircuitInputStateRef, ExecStep, StackOpsBuilder},
     Error,
 };
-use eth_types::{evm_types::OpcodeId, GethExecStep, ToWord, Word, U256};
+use eth_types::{
+    evm_types::{ChainSpec, OpcodeId},
+    GethExecStep, ToWord, Word, U256,
+};
 
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct GetBlockHeaderField<const OP: OpcodeId>;
 
 trait BlockHeaderToField {
-    fn handle(block_head: &Block) -> Word;
+    fn handle(block_head: &Block, chain_spec: &ChainSpec) -> Word;
 }
 
 impl BlockHeaderToField for GetBlockHeaderField<{ OpcodeId::COINBASE }> {
-    fn handle(block_head: &Block) -> Word {
+    fn handle(block_head: &Block, _chain_spec: &ChainSpec) -> Word {
         block_head.coinbase.to_word()
     }
 }
 
 impl BlockHeaderToField for GetBlockHeaderField<{ OpcodeId::TIMESTAMP }> {
-    fn handle(block_head: &Block) -> Word {
+    fn handle(block_head: &Block, _chain_spec: &ChainSpec) -> Word {
         block_head.timestamp
     }
 }
 
 impl BlockHeaderToField for GetBlockHeaderField<{ OpcodeId::NUMBER }> {
-    fn handle(block_head: &Block) -> Word {
+    fn handle(block_head: &Block, _chain_spec: &ChainSpec) -> Word {
         block_head.number
     }
 }
 
 impl BlockHeaderToField for GetBlockHeaderField<{ OpcodeId::DIFFICULTY }> {
-    fn handle(block_head: &Block) -> Word {
-        block_head.difficulty
+    fn handle(block_head: &Block, chain_spec: &ChainSpec) -> Word {
+        block_head.difficulty_or_prev_randao(chain_spec)
     }
 }
 
 impl BlockHeaderToField for GetBlockHeaderField<{ OpcodeId::GASLIMIT }> {
-    fn handle(block_head: &Block) -> Word {
+    fn handle(block_head: &Block, _chain_spec: &ChainSpec) -> Word {
         block_head.gas_limit.into()
     }
 }
 
 impl BlockHeaderToField for GetBlockHeaderField<{ OpcodeId::CHAINID }> {
-    fn handle(block_head: &Block) -> Word {
+    fn handle(block_head: &Block, _chain_spec: &ChainSpec) -> Word {
         block_head.chain_id.into()
     }
 }
 
 impl BlockHeaderToField for GetBlockHeaderField<{ OpcodeId::BASEFEE }> {
-    fn handle(block_head: &Block) -> Word {
+    fn handle(block_head: &Block, _chain_spec: &ChainSpec) -> Word {
         block_head.base_fee
     }
 }
@@ -65,11 +68,13 @@ where
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
         let block_head = state.block.blocks.get(&state.tx.block_num).unwrap();
-        let output = Self::handle(block_head);
+        let output = Self::handle(block_head, &state.block.chain_spec);
 
         #[cfg(feature = "enable-stack")]
         assert_eq!(output, geth_steps[1].stack.last()?);
-        state.stack_push(&mut exec_step, output)?;
+        let mut stack_ops = StackOpsBuilder::new(OP);
+        stack_ops.push(state, &mut exec_step, output)?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -88,7 +93,9 @@ impl Opcode for Pc {
 
         #[cfg(feature = "enable-stack")]
         assert_eq!(output, geth_steps[1].stack.last()?);
-        state.stack_push(&mut exec_step, output)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_steps[0].op);
+        stack_ops.push(state, &mut exec_step, output)?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -107,7 +114,9 @@ impl Opcode for Msize {
 
         #[cfg(feature = "enable-stack")]
         assert_eq!(output, geth_steps[1].stack.last()?);
-        state.stack_push(&mut exec_step, output)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_steps[0].op);
+        stack_ops.push(state, &mut exec_step, output)?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -126,7 +135,9 @@ impl Opcode for Gas {
 
         #[cfg(feature = "enable-stack")]
         assert_eq!(output, geth_steps[1].stack.last()?);
-        state.stack_push(&mut exec_step, output)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_steps[0].op);
+        stack_ops.push(state, &mut exec_step, output)?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -36,7 +36,11 @@ impl Opcode for ErrorCreationCode {
         assert!(length > U256::zero());
 
         // read first byte and assert it is 0xef
-        let byte = state.call_ctx()?.memory.0[offset.as_usize()];
+        let byte = state
+            .call_ctx()?
+            .memory
+            .get(offset.as_usize())
+            .unwrap_or_default();
         assert!(byte == 0xef);
 
         let shift = offset.as_u64() % 32;
@@ -1,5 +1,6 @@
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep, ExpEvent, ExpStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, ExpEvent, ExpStep, StackOpsBuilder},
+    gas::GasStep,
     Error,
 };
 use eth_types::{GethExecStep, U256};
@@ -40,9 +41,17 @@ impl Opcode for Exponentiation {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
-        let base = state.stack_pop(&mut exec_step)?;
-        let exponent = state.stack_pop(&mut exec_step)?;
+        let base = stack_ops.pop(state, &mut exec_step)?;
+        let exponent = stack_ops.pop(state, &mut exec_step)?;
+        state.check_step_gas_cost(
+            geth_step,
+            &GasStep {
+                exponent,
+                ..GasStep::new(geth_step.op)
+            },
+        )?;
         #[cfg(feature = "enable-stack")]
         {
             assert_eq!(base, geth_step.stack.nth_last(0)?);
@@ -50,7 +59,7 @@ impl Opcode for Exponentiation {
         }
 
         let (exponentiation, _) = base.overflowing_pow(exponent);
-        state.stack_push(&mut exec_step, exponentiation)?;
+        stack_ops.push(state, &mut exec_step, exponentiation)?;
 
         let mut steps = Vec::new();
         let exponentiation_calc = exp_by_squaring(base, exponent, &mut steps);
@@ -61,6 +70,7 @@ impl Opcode for Exponentiation {
             exponentiation,
             steps,
         });
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
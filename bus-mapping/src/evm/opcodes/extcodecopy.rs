@@ -2,11 +2,12 @@ use super::Opcode;
 use crate::{
     circuit_input_builder::{
         CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+        StackOpsBuilder,
     },
     operation::{AccountField, CallContextField, TxAccessListAccountOp},
     Error,
 };
-use eth_types::{Address, Bytecode, GethExecStep, ToAddress, ToWord, Word, H256};
+use eth_types::{Bytecode, GethExecStep, ToAddress, ToWord, Word, H256};
 
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct Extcodecopy;
@@ -18,12 +19,13 @@ impl Opcode for Extcodecopy {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
-        let external_address_word = state.stack_pop(&mut exec_step)?;
+        let external_address_word = stack_ops.pop(state, &mut exec_step)?;
         let external_address = external_address_word.to_address();
-        let dest_offset = state.stack_pop(&mut exec_step)?;
-        let offset = state.stack_pop(&mut exec_step)?;
-        let length = state.stack_pop(&mut exec_step)?;
+        let dest_offset = stack_ops.pop(state, &mut exec_step)?;
+        let offset = stack_ops.pop(state, &mut exec_step)?;
+        let length = stack_ops.pop(state, &mut exec_step)?;
         #[cfg(feature = "enable-stack")]
         {
             assert_eq!(external_address_word, geth_step.stack.nth_last(0)?);
@@ -74,20 +76,21 @@ impl Opcode for Extcodecopy {
 
         let copy_event = gen_copy_event(
             state,
-            external_address,
+            exists.then_some(code_hash),
             dest_offset,
             offset,
             length,
             &mut exec_step,
         )?;
         state.push_copy(&mut exec_step, copy_event);
+        stack_ops.finish()?;
         Ok(vec![exec_step])
     }
 }
 
 fn gen_copy_event(
     state: &mut CircuitInputStateRef,
-    external_address: Address,
+    code_hash: Option<H256>,
     dest_offset: Word,
     offset: Word,
     length: Word,
@@ -97,18 +100,10 @@ fn gen_copy_event(
 
     let length = length.as_u64();
 
-    let account = state.sdb.get_account(&external_address).1;
-    let exists = !account.is_empty();
-    let code_hash = if exists {
-        account.code_hash
-    } else {
-        H256::zero()
-    };
-
-    let bytecode: Bytecode = if exists {
-        state.code(code_hash)?.into()
-    } else {
-        Bytecode::default()
+    // Copy from the code of the account if it exists, otherwise from empty code.
+    let bytecode: Bytecode = match code_hash {
+        Some(code_hash) => state.code(code_hash)?.into(),
+        None => Bytecode::default(),
     };
     let code_size = bytecode.code.len() as u64;
 
@@ -127,13 +122,18 @@ fn gen_copy_event(
         src_addr,
         src_addr_end,
         src_type: CopyDataType::Bytecode,
-        src_id: NumberOrHash::Hash(code_hash),
+        src_id: NumberOrHash::Hash(code_hash.unwrap_or_default()),
         dst_addr,
         dst_type: CopyDataType::Memory,
         dst_id: NumberOrHash::Number(state.call()?.call_id),
         log_id: None,
         rw_counter_start,
-        copy_bytes: CopyBytes::new(copy_steps, None, Some(prev_bytes)),
+        copy_bytes: CopyBytes::new(
+            &mut state.block.copy_bytes,
+            copy_steps,
+            None,
+            Some(prev_bytes),
+        )?,
         access_list: vec![],
     })
 }
@@ -377,15 +377,15 @@ mod extcodecopy_tests {
         let word_ops = (copy_end + 32 - copy_start) / 32;
         let copied_bytes = builder.block.copy_events[0]
             .copy_bytes
-            .bytes
+            .bytes(&builder.block.copy_bytes)
             .iter()
-            .map(|(b, _, _)| *b)
+            .map(|(b, _, _)| b)
             .collect::<Vec<_>>();
         let prev_bytes = builder.block.copy_events[0]
             .copy_bytes
-            .bytes_write_prev
-            .clone()
-            .unwrap();
+            .bytes_write_prev(&builder.block.copy_bytes)
+            .unwrap()
+            .to_vec();
 
         assert_eq!(builder.block.container.memory.len(), word_ops);
         assert_eq!(
@@ -423,11 +423,12 @@ mod extcodecopy_tests {
         assert_eq!(copy_events[0].dst_type, CopyDataType::Memory);
         assert!(copy_events[0].log_id.is_none());
 
-        for (idx, (value, is_code, is_mask)) in copy_events[0].copy_bytes.bytes.iter().enumerate() {
-            if !*is_mask {
+        let copy_steps = copy_events[0].copy_bytes.bytes(&builder.block.copy_bytes);
+        for (idx, (value, is_code, is_mask)) in copy_steps.iter().enumerate() {
+            if !is_mask {
                 let bytecode_element = bytecode_ext.get(idx).unwrap_or_default();
-                assert_eq!(*value, bytecode_element.value);
-                assert_eq!(*is_code, bytecode_element.is_code);
+                assert_eq!(value, bytecode_element.value);
+                assert_eq!(is_code, bytecode_element.is_code);
             }
         }
     }
@@ -1,7 +1,8 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::CircuitInputStateRef,
+    circuit_input_builder::{CircuitInputStateRef, StackOpsBuilder},
     evm::opcodes::ExecStep,
+    gas::GasStep,
     operation::{AccountField, CallContextField, TxAccessListAccountOp},
     Error,
 };
@@ -17,9 +18,10 @@ impl Opcode for Extcodehash {
     ) -> Result<Vec<ExecStep>, Error> {
         let step = &steps[0];
         let mut exec_step = state.new_step(step)?;
+        let mut stack_ops = StackOpsBuilder::new(step.op);
 
         // Pop external address off stack
-        let external_address_word = state.stack_pop(&mut exec_step)?;
+        let external_address_word = stack_ops.pop(state, &mut exec_step)?;
         let external_address = external_address_word.to_address();
         #[cfg(feature = "enable-stack")]
         assert_eq!(external_address_word, step.stack.last()?);
@@ -43,6 +45,13 @@ impl Opcode for Extcodehash {
 
         // Update transaction access list for external_address
         let is_warm = state.sdb.check_account_in_access_list(&external_address);
+        state.check_step_gas_cost(
+            step,
+            &GasStep {
+                is_warm,
+                ..GasStep::new(step.op)
+            },
+        )?;
         state.push_op_reversible(
             &mut exec_step,
             TxAccessListAccountOp {
@@ -53,33 +62,35 @@ impl Opcode for Extcodehash {
             },
         )?;
 
-        let account = state.sdb.get_account(&external_address).1;
+        // Empty accounts (EIP-161) are encoded as non-existing ones with code_hash = 0,
+        // and EXTCODEHASH returns 0 for them.
+        let account = state.sdb.get_account(&external_address).1.clone();
         let exists = !account.is_empty();
-        let code_hash = if exists {
-            if cfg!(feature = "scroll") {
-                account.keccak_code_hash
-            } else {
-                account.code_hash
-            }
-        } else {
-            H256::zero()
-        };
-        // log::trace!("extcodehash addr {:?} acc {:?} exists {:?} codehash {:?}",
-        // external_address, account, exists, code_hash);
         state.account_read(
             &mut exec_step,
             external_address,
-            if cfg!(feature = "scroll") {
-                AccountField::KeccakCodeHash
-            } else {
-                AccountField::CodeHash
-            },
-            code_hash.to_word(),
+            AccountField::CodeHash,
+            account.code_hash_read().to_word(),
         )?;
+        let code_hash = if !exists {
+            H256::zero()
+        } else if cfg!(feature = "scroll") {
+            // The code hash field is the poseidon hash, read the keccak one.
+            state.account_read(
+                &mut exec_step,
+                external_address,
+                AccountField::KeccakCodeHash,
+                account.keccak_code_hash.to_word(),
+            )?;
+            account.keccak_code_hash
+        } else {
+            account.code_hash
+        };
         #[cfg(feature = "enable-stack")]
         assert_eq!(steps[1].stack.last()?, code_hash.to_word());
         // Stack write of the result of EXTCODEHASH.
-        state.stack_push(&mut exec_step, code_hash.to_word())?;
+        stack_ops.push(state, &mut exec_step, code_hash.to_word())?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -184,6 +195,12 @@ mod extcodehash_tests {
         builder
             .handle_block(&block.eth_block, &block.geth_traces)
             .unwrap();
+        // poseidon code hash under the scroll feature
+        let account_code_hash = builder
+            .sdb
+            .get_account(&external_address)
+            .1
+            .code_hash_read();
 
         // Check that `external_address` is in access list as a result of bus mapping.
         assert!(builder.sdb.add_account_to_access_list(external_address));
@@ -281,19 +298,35 @@ mod extcodehash_tests {
                 RW::READ,
                 &AccountOp {
                     address: external_address,
-                    field: if cfg!(feature = "scroll") {
-                        AccountField::KeccakCodeHash
-                    } else {
-                        AccountField::CodeHash
-                    },
-                    value: if exists { code_hash } else { U256::zero() },
-                    value_prev: if exists { code_hash } else { U256::zero() },
+                    field: AccountField::CodeHash,
+                    value: account_code_hash.to_word(),
+                    value_prev: account_code_hash.to_word(),
                 }
             )
         );
+        let stack_push_index = if cfg!(feature = "scroll") && exists {
+            assert_eq!(
+                {
+                    let operation = &container.account[indices[6].as_usize()];
+                    (operation.rw(), operation.op())
+                },
+                (
+                    RW::READ,
+                    &AccountOp {
+                        address: external_address,
+                        field: AccountField::KeccakCodeHash,
+                        value: code_hash,
+                        value_prev: code_hash,
+                    }
+                )
+            );
+            7
+        } else {
+            6
+        };
         assert_eq!(
             {
-                let operation = &container.stack[indices[6].as_usize()];
+                let operation = &container.stack[indices[stack_push_index].as_usize()];
                 (operation.rw(), operation.op())
             },
             (
@@ -1,6 +1,6 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     operation::CallContextField,
     Error,
 };
@@ -18,6 +18,7 @@ impl Opcode for GasPrice {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
         // Get gasprice result from next step
         let gasprice = state.tx.gas_price;
         let tx_id = state.tx_ctx.id();
@@ -33,7 +34,8 @@ impl Opcode for GasPrice {
         // Stack write of the gasprice value
         #[cfg(feature = "enable-stack")]
         assert_eq!(gasprice, geth_steps[1].stack.last()?);
-        state.stack_push(&mut exec_step, gasprice)?;
+        stack_ops.push(state, &mut exec_step, gasprice)?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -1,6 +1,6 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     Error,
 };
 use eth_types::GethExecStep;
@@ -15,9 +15,10 @@ impl Opcode for Jumpi {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
-        let pc = state.stack_pop(&mut exec_step)?;
-        let condition = state.stack_pop(&mut exec_step)?;
+        let pc = stack_ops.pop(state, &mut exec_step)?;
+        let condition = stack_ops.pop(state, &mut exec_step)?;
 
         if let Some(next_step) = geth_steps.get(1) {
             if condition == 0.into() {
@@ -43,6 +44,7 @@ impl Opcode for Jumpi {
         for (i, v) in [pc, condition].into_iter().enumerate() {
             assert_eq!(v, geth_step.stack.nth_last(i)?);
         }
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
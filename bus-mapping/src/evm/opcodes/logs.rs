@@ -1,7 +1,8 @@
 use super::Opcode;
 use crate::{
     circuit_input_builder::{
-        CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, ExecState, ExecStep, NumberOrHash,
+        CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, ExecState, ExecStep,
+        NumberOrHash, StackOpsBuilder,
     },
     operation::{CallContextField, TxLogField},
     Error,
@@ -19,9 +20,10 @@ impl Opcode for Log {
         let geth_step = &geth_steps[0];
 
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
-        let mstart = state.stack_pop(&mut exec_step)?;
-        let msize = state.stack_pop(&mut exec_step)?;
+        let mstart = stack_ops.pop(state, &mut exec_step)?;
+        let msize = stack_ops.pop(state, &mut exec_step)?;
         #[cfg(feature = "enable-stack")]
         {
             assert_eq!(mstart, geth_step.stack.nth_last(0)?);
@@ -73,7 +75,7 @@ impl Opcode for Log {
         };
 
         for i in 0..topic_count {
-            let topic = state.stack_pop(&mut exec_step)?;
+            let topic = stack_ops.pop(state, &mut exec_step)?;
             #[cfg(feature = "enable-stack")]
             assert_eq!(topic, geth_step.stack.nth_last(2 + i)?);
 
@@ -88,6 +90,7 @@ impl Opcode for Log {
                 )?;
             }
         }
+        stack_ops.finish()?;
 
         if state.call()?.is_persistent {
             // Get low Uint64 for memory start as below reference. Memory size must be
@@ -138,7 +141,12 @@ fn gen_copy_event(
         dst_addr: 0,
         log_id: Some(state.tx_ctx.log_id as u64 + 1),
         rw_counter_start,
-        copy_bytes: CopyBytes::new(read_steps, Some(write_steps), None),
+        copy_bytes: CopyBytes::new(
+            &mut state.block.copy_bytes,
+            read_steps,
+            Some(write_steps),
+            None,
+        )?,
         access_list: vec![],
     })
 }
@@ -157,7 +165,11 @@ mod log_tests {
         Bytecode, ToWord, Word,
     };
 
-    use mock::test_ctx::{helpers::*, TestContext};
+    use mock::{
+        generate_mock_call_bytecode,
+        test_ctx::{helpers::*, TestContext},
+        MockCallBytecodeParams, MOCK_ACCOUNTS,
+    };
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -350,9 +362,9 @@ mod log_tests {
         let word_ops = (copy_end + 32 - copy_start) / 32;
         let copied_bytes = builder.block.copy_events[0]
             .copy_bytes
-            .bytes
+            .bytes(&builder.block.copy_bytes)
             .iter()
-            .map(|(b, _, _)| *b)
+            .map(|(b, _, _)| b)
             .collect::<Vec<_>>();
         assert_eq!(
             builder.block.container.tx_log.len(),
@@ -394,11 +406,95 @@ mod log_tests {
         assert_eq!(copy_events[0].dst_addr as usize, 0);
         assert_eq!(copy_events[0].log_id, Some(step.log_id as u64 + 1));
 
-        for (idx, (byte, is_code, is_mask)) in copy_events[0].copy_bytes.bytes.iter().enumerate() {
-            if !*is_mask {
-                assert_eq!(Some(byte), memory_data.get(mstart + idx));
-                assert!(!*is_code);
+        let copy_steps = copy_events[0].copy_bytes.bytes(&builder.block.copy_bytes);
+        for (idx, (byte, is_code, is_mask)) in copy_steps.iter().enumerate() {
+            if !is_mask {
+                assert_eq!(Some(&byte), memory_data.get(mstart + idx));
+                assert!(!is_code);
             }
         }
     }
+
+    #[test]
+    fn logs_opcode_reverted_sub_call() {
+        // the callee logs and then reverts
+        let code_b = bytecode! {
+            PUSH32(Word::from(0xef))
+            PUSH1(0x20)
+            PUSH1(0x00)
+            LOG1
+            .op_revert(0x00, 0x00)
+        };
+        let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+            address: MOCK_ACCOUNTS[1],
+            pushdata: vec![0x01; 32],
+            instructions_after_call: bytecode! {
+                PUSH32(Word::from(0xb0))
+                PUSH1(0x20)
+                PUSH1(0x00)
+                LOG1
+            },
+            ..MockCallBytecodeParams::default()
+        });
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code_a);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(code_b);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let log_steps = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .filter(|step| step.exec_state == ExecState::Op(OpcodeId::LOG1))
+            .collect::<Vec<_>>();
+        assert_eq!(log_steps.len(), 2);
+        // the reverted log doesn't bump log_id
+        assert_eq!(log_steps[0].log_id, 0);
+        assert_eq!(log_steps[1].log_id, 0);
+
+        // only the caller's log is written: address + topic + one data word
+        let tx_log_ops = builder
+            .block
+            .container
+            .tx_log
+            .iter()
+            .map(|op| op.op().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(tx_log_ops.len(), 3);
+        assert_eq!(
+            tx_log_ops[0],
+            TxLogOp::new(1, 1, TxLogField::Address, 0, MOCK_ACCOUNTS[0].to_word())
+        );
+        assert_eq!(
+            tx_log_ops[1],
+            TxLogOp::new(1, 1, TxLogField::Topic, 0, Word::from(0xb0))
+        );
+        assert!(tx_log_ops.iter().all(|op| op.log_id == 1));
+
+        let log_copy_events = builder
+            .block
+            .copy_events
+            .iter()
+            .filter(|event| event.dst_type == CopyDataType::TxLog)
+            .collect::<Vec<_>>();
+        assert_eq!(log_copy_events.len(), 1);
+        assert_eq!(log_copy_events[0].log_id, Some(1));
+    }
 }
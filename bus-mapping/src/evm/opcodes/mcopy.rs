@@ -1,6 +1,7 @@
 use crate::{
     circuit_input_builder::{
         CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+        StackOpsBuilder,
     },
     Error,
 };
@@ -18,10 +19,11 @@ impl Opcode for MCopy {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
-        let dest_offset = state.stack_pop(&mut exec_step)?;
-        let src_offset = state.stack_pop(&mut exec_step)?;
-        let length = state.stack_pop(&mut exec_step)?;
+        let dest_offset = stack_ops.pop(state, &mut exec_step)?;
+        let src_offset = stack_ops.pop(state, &mut exec_step)?;
+        let length = stack_ops.pop(state, &mut exec_step)?;
 
         #[cfg(feature = "enable-stack")]
         {
@@ -38,6 +40,7 @@ impl Opcode for MCopy {
             &mut exec_step,
         )?;
         state.push_copy(&mut exec_step, copy_event);
+        stack_ops.finish()?;
         Ok(vec![exec_step])
     }
 }
@@ -79,7 +82,12 @@ fn gen_copy_event(
         log_id: None,
         rw_counter_start,
         // needs both read/write and prev bytes in CopyBytes
-        copy_bytes: CopyBytes::new(read_steps, Some(write_steps), Some(prev_bytes)),
+        copy_bytes: CopyBytes::new(
+            &mut state.block.copy_bytes,
+            read_steps,
+            Some(write_steps),
+            Some(prev_bytes),
+        )?,
         access_list: vec![],
     })
 }
@@ -206,23 +214,22 @@ mod mcopy_tests {
 
         let read_bytes = builder.block.copy_events[0]
             .copy_bytes
-            .bytes
+            .bytes(&builder.block.copy_bytes)
             .iter()
-            .map(|(b, _, _)| *b)
+            .map(|(b, _, _)| b)
             .collect::<Vec<_>>();
         let write_bytes = builder.block.copy_events[0]
             .copy_bytes
-            .aux_bytes
-            .as_ref()
+            .aux_bytes(&builder.block.copy_bytes)
             .unwrap()
             .iter()
-            .map(|(b, _, _)| *b)
+            .map(|(b, _, _)| b)
             .collect::<Vec<_>>();
         let prev_bytes = builder.block.copy_events[0]
             .copy_bytes
-            .bytes_write_prev
-            .clone()
-            .unwrap();
+            .bytes_write_prev(&builder.block.copy_bytes)
+            .unwrap()
+            .to_vec();
 
         // read and write ops.
         assert_eq!(
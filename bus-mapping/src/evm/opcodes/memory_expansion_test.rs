@@ -19,8 +19,8 @@ fn assert_expanded(traces: &[GethExecStep], before: isize, after: isize) {
     let traces_len = traces.len();
     let before = might_neg_index(before, traces_len);
     let after = might_neg_index(after, traces_len);
-    let size_before = traces[before].memory.0.len();
-    let size_after = traces[after].memory.0.len();
+    let size_before = traces[before].memory.len();
+    let size_after = traces[after].memory.len();
     assert_ne!(size_before, size_after);
 }
 
@@ -1,6 +1,6 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     Error,
 };
 use eth_types::GethExecStep;
@@ -20,7 +20,8 @@ impl Opcode for Mload {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
-        let stack_value_read = state.stack_pop(&mut exec_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
+        let stack_value_read = stack_ops.pop(state, &mut exec_step)?;
         #[cfg(feature = "enable-stack")]
         assert_eq!(stack_value_read, geth_step.stack.last()?);
 
@@ -37,7 +38,7 @@ impl Opcode for Mload {
         // First stack write
         #[cfg(feature = "enable-stack")]
         assert_eq!(mem_read_value, geth_steps[1].stack.last()?);
-        state.stack_push(&mut exec_step, mem_read_value)?;
+        stack_ops.push(state, &mut exec_step, mem_read_value)?;
 
         state.memory_read_word(&mut exec_step, slot.into())?;
         state.memory_read_word(&mut exec_step, (slot + 32).into())?;
@@ -47,6 +48,7 @@ impl Opcode for Mload {
             .call_ctx_mut()?
             .memory
             .extend_at_least((offset + 32) as usize);
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -1,6 +1,6 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     Error,
 };
 use eth_types::{evm_types::memory::MemoryRange, GethExecStep, ToBigEndian, ToLittleEndian, Word};
@@ -18,8 +18,9 @@ impl<const IS_MSTORE8: bool> Opcode for Mstore<IS_MSTORE8> {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
-        let offset = state.stack_pop(&mut exec_step)?;
-        let value = state.stack_pop(&mut exec_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
+        let offset = stack_ops.pop(state, &mut exec_step)?;
+        let value = stack_ops.pop(state, &mut exec_step)?;
         #[cfg(feature = "enable-stack")]
         {
             assert_eq!(offset, geth_step.stack.nth_last(0)?);
@@ -68,6 +69,7 @@ impl<const IS_MSTORE8: bool> Opcode for Mstore<IS_MSTORE8> {
 
             // TODO: edge case: if shift = 0, we could skip the right word?
         }
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -1,6 +1,6 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     operation::CallContextField,
     Error,
 };
@@ -16,6 +16,7 @@ impl Opcode for Origin {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
         // TODO: Get origin result
         let value = state.tx.from.to_word();
         let tx_id = state.tx_ctx.id();
@@ -31,7 +32,8 @@ impl Opcode for Origin {
         // Stack write of the origin address value
         #[cfg(feature = "enable-stack")]
         assert_eq!(value, geth_steps[1].stack.last()?);
-        state.stack_push(&mut exec_step, value)?;
+        stack_ops.push(state, &mut exec_step, value)?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
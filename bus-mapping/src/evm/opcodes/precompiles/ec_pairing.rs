@@ -3,12 +3,13 @@ use itertools::Itertools;
 
 use crate::{
     circuit_input_builder::{
-        EcPairingOp, EcPairingPair, PrecompileEvent, N_BYTES_PER_PAIR, N_PAIRING_PER_OP,
+        EcPairingOp, EcPairingPair, PrecompileEcParams, PrecompileEvent, N_BYTES_PER_PAIR,
     },
     precompile::{EcPairingAuxData, EcPairingError, PrecompileAuxData},
 };
 
 pub(crate) fn opt_data(
+    ec_params: &PrecompileEcParams,
     input_bytes: &[u8],
     output_bytes: &[u8],
     return_bytes: &[u8],
@@ -37,8 +38,9 @@ pub(crate) fn opt_data(
         );
     }
 
+    let n_pairs = ec_params.ec_pairing_pairs;
     let op = if !input_bytes.is_empty() {
-        if (input_bytes.len() > N_PAIRING_PER_OP * N_BYTES_PER_PAIR)
+        if (input_bytes.len() > ec_params.ec_pairing_max_input_len())
             || (input_bytes.len() % N_BYTES_PER_PAIR != 0)
         {
             return (
@@ -50,7 +52,7 @@ pub(crate) fn opt_data(
         }
         debug_assert!(
             input_bytes.len() % N_BYTES_PER_PAIR == 0
-                && input_bytes.len() <= N_PAIRING_PER_OP * N_BYTES_PER_PAIR
+                && input_bytes.len() <= ec_params.ec_pairing_max_input_len()
         );
         // process input bytes.
         let mut pairs = input_bytes
@@ -71,17 +73,17 @@ pub(crate) fn opt_data(
                 }
             })
             .collect_vec();
-        // pad the pairs to make them of fixed size: N_PAIRING_PER_OP.
-        pairs.resize(N_PAIRING_PER_OP, EcPairingPair::padding_pair());
+        // pad the pairs to make them of fixed size: ec_pairing_pairs.
+        pairs.resize(n_pairs, EcPairingPair::padding_pair());
         EcPairingOp {
-            pairs: <[_; N_PAIRING_PER_OP]>::try_from(pairs).unwrap(),
+            pairs,
             output: pairing_check,
             input_bytes: input_bytes.to_vec(),
             output_bytes: output_bytes.to_vec(),
             return_bytes: return_bytes.to_vec(),
         }
     } else {
-        let pairs = [EcPairingPair::padding_pair(); N_PAIRING_PER_OP];
+        let pairs = vec![EcPairingPair::padding_pair(); n_pairs];
         EcPairingOp {
             pairs,
             output: pairing_check,
@@ -63,7 +63,8 @@ pub fn gen_ops(
         PrecompileCalls::Bn128Add => opt_data_ec_add(input_bytes, output_bytes, return_bytes),
         PrecompileCalls::Bn128Mul => opt_data_ec_mul(input_bytes, output_bytes, return_bytes),
         PrecompileCalls::Bn128Pairing => {
-            opt_data_ec_pairing(input_bytes, output_bytes, return_bytes)
+            let ec_params = state.block.circuits_params.max_ec_ops;
+            opt_data_ec_pairing(&ec_params, input_bytes, output_bytes, return_bytes)
         }
         PrecompileCalls::Modexp => opt_data_modexp(input_bytes, output_bytes, return_bytes),
         PrecompileCalls::Identity => (
@@ -78,12 +79,12 @@ pub fn gen_ops(
             if output_bytes.is_empty() {
                 None
             } else {
-                Some(PrecompileEvent::SHA256(SHA256 {
-                    input: input_bytes.to_vec(),
-                    digest: output_bytes
+                Some(PrecompileEvent::SHA256(SHA256::new(
+                    input_bytes.to_vec(),
+                    output_bytes
                         .try_into()
                         .expect("output bytes must be 32 bytes"),
-                }))
+                )))
             },
             Some(PrecompileAuxData::SHA256 {
                 input_bytes: input_bytes.to_vec(),
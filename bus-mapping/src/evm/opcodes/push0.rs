@@ -1,6 +1,6 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     Error,
 };
 use eth_types::{GethExecStep, U256};
@@ -15,8 +15,10 @@ impl Opcode for Push0 {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
-        state.stack_push(&mut exec_step, U256::zero())?;
+        stack_ops.push(state, &mut exec_step, U256::zero())?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -0,0 +1,98 @@
+//! Registry of the handlers of custom opcodes, such as the system opcodes of an
+//! L2, which lets downstream crates support them without patching the dispatch
+//! of [`gen_associated_ops`](super::gen_associated_ops).
+//!
+//! The opcodes unknown to the EVM are traced as [`OpcodeId::INVALID`] with
+//! their byte. Once registered, such an opcode isn't handled as the invalid
+//! opcode error anymore, but by its registered handler.
+use super::FnGenAssociatedOps;
+use crate::evm::OpcodeId;
+use std::{collections::HashMap, fmt};
+
+/// Handlers of the custom opcodes of a chain, taking precedence over the
+/// built-in handlers. Each [`Blocks`](crate::circuit_input_builder::Blocks)
+/// has its own registry, so that builders of different chains can run in the
+/// same process.
+#[derive(Clone, Default)]
+pub struct OpcodeRegistry {
+    handlers: HashMap<OpcodeId, FnGenAssociatedOps>,
+}
+
+impl fmt::Debug for OpcodeRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.handlers.keys()).finish()
+    }
+}
+
+impl OpcodeRegistry {
+    /// Register `handler` to generate the steps and operations of
+    /// `opcode_id`, taking precedence over its built-in handler if any.
+    /// Return the handler previously registered for the opcode.
+    pub fn register(
+        &mut self,
+        opcode_id: OpcodeId,
+        handler: FnGenAssociatedOps,
+    ) -> Option<FnGenAssociatedOps> {
+        log::debug!("register handler for opcode {:?}", opcode_id);
+        self.handlers.insert(opcode_id, handler)
+    }
+
+    /// Remove the handler registered for `opcode_id`, returning it.
+    pub fn unregister(&mut self, opcode_id: &OpcodeId) -> Option<FnGenAssociatedOps> {
+        self.handlers.remove(opcode_id)
+    }
+
+    /// Return the handler registered for `opcode_id`.
+    pub fn get(&self, opcode_id: &OpcodeId) -> Option<FnGenAssociatedOps> {
+        self.handlers.get(opcode_id).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit_input_builder::{CircuitInputStateRef, ExecStep},
+        evm::opcodes::is_supported_opcode,
+        Error,
+    };
+    use eth_types::GethExecStep;
+
+    fn gen_system_opcode_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        Ok(vec![state.new_step(&geth_steps[0])?])
+    }
+
+    #[test]
+    fn opcode_registry_round_trip() {
+        let opcode_id = OpcodeId::INVALID(0x0c);
+        let mut registry = OpcodeRegistry::default();
+        assert!(registry.get(&opcode_id).is_none());
+        assert!(!is_supported_opcode(&registry, &opcode_id));
+
+        assert!(registry
+            .register(opcode_id, gen_system_opcode_ops)
+            .is_none());
+        assert!(registry.get(&opcode_id).is_some());
+        assert!(is_supported_opcode(&registry, &opcode_id));
+        // other unknown opcodes are still invalid
+        assert!(registry.get(&OpcodeId::INVALID(0x0d)).is_none());
+        // other registries are unaffected
+        assert!(!is_supported_opcode(&OpcodeRegistry::default(), &opcode_id));
+
+        assert!(registry.unregister(&opcode_id).is_some());
+        assert!(registry.get(&opcode_id).is_none());
+    }
+
+    #[test]
+    fn dummy_opcode_is_unsupported_until_registered() {
+        let mut registry = OpcodeRegistry::default();
+        assert!(!is_supported_opcode(&registry, &OpcodeId::SELFDESTRUCT));
+        assert!(is_supported_opcode(&registry, &OpcodeId::STOP));
+
+        registry.register(OpcodeId::SELFDESTRUCT, gen_system_opcode_ops);
+        assert!(is_supported_opcode(&registry, &OpcodeId::SELFDESTRUCT));
+    }
+}
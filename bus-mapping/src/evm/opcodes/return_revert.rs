@@ -2,7 +2,7 @@ use super::Opcode;
 use crate::{
     circuit_input_builder::{
         CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, CopyEventStepsBuilder,
-        NumberOrHash,
+        NumberOrHash, StackOpsBuilder,
     },
     evm::opcodes::ExecStep,
     operation::{AccountField, AccountOp, CallContextField},
@@ -10,7 +10,10 @@ use crate::{
 };
 use eth_types::{
     bytecode::BytecodeElement,
-    evm_types::{memory::MemoryWordRange, OpcodeId},
+    evm_types::{
+        memory::{MemoryRange, MemoryWordRange},
+        OpcodeId,
+    },
     state_db::CodeDB,
     Bytecode, GethExecStep, ToWord, Word, H256,
 };
@@ -27,8 +30,10 @@ impl Opcode for ReturnRevert {
         let step = &steps[0];
         let mut exec_step = state.new_step(step)?;
 
-        let offset = state.stack_pop(&mut exec_step)?;
-        let length = state.stack_pop(&mut exec_step)?;
+        let mut stack_ops = StackOpsBuilder::new(step.op);
+        let offset = stack_ops.pop(state, &mut exec_step)?;
+        let length = stack_ops.pop(state, &mut exec_step)?;
+        stack_ops.finish()?;
         #[cfg(feature = "enable-stack")]
         {
             assert_eq!(offset, step.stack.nth_last(0)?);
@@ -165,13 +170,22 @@ impl Opcode for ReturnRevert {
         // we still need to store return data.
         // Failed RETURN will not handled by here, so we don't need to check call.is_success.
         if !call.is_root {
-            let return_data = state
-                .call_ctx()?
-                .memory
-                .0
-                .get(offset..offset + length)
-                .unwrap_or_default()
-                .to_vec();
+            // the return data is the one of the next step in the caller when
+            // it is traced, and is read from the reconstructed memory otherwise
+            let traced_return_data = steps
+                .get(1)
+                .filter(|next_step| next_step.depth + 1 == step.depth)
+                .and_then(|next_step| next_step.return_data.as_ref());
+            let return_data = if let Some(return_data) = traced_return_data {
+                return_data.to_vec()
+            } else {
+                let memory = &state.call_ctx()?.memory;
+                if offset + length <= memory.len() {
+                    memory.read_chunk(MemoryRange::new_with_length(offset, length))
+                } else {
+                    vec![]
+                }
+            };
             if call.is_create() && step.op == OpcodeId::RETURN {
                 state.caller_ctx_mut()?.return_data.clear();
             } else {
@@ -281,6 +295,12 @@ fn handle_copy(
         .source(dst_data.as_slice())
         .build();
 
+    let copy_bytes = CopyBytes::new(
+        &mut state.block.copy_bytes,
+        read_steps,
+        Some(write_steps),
+        Some(dst_data_prev),
+    )?;
     state.push_copy(
         step,
         CopyEvent {
@@ -293,7 +313,7 @@ fn handle_copy(
             dst_id: NumberOrHash::Number(destination.id),
             dst_addr: destination.offset.try_into().unwrap(),
             log_id: None,
-            copy_bytes: CopyBytes::new(read_steps, Some(write_steps), Some(dst_data_prev)),
+            copy_bytes,
             access_list: vec![],
         },
     );
@@ -313,9 +333,12 @@ fn handle_create(
     step: &mut ExecStep,
     source: Source,
 ) -> Result<AccountCodeInfo, Error> {
-    let values = state.call_ctx()?.memory.0[source.offset..source.offset + source.length].to_vec();
+    let values = state
+        .call_ctx()?
+        .memory
+        .read_chunk(MemoryRange::new_with_length(source.offset, source.length));
     let keccak_hash = H256(keccak256(&values));
-    let code_hash = CodeDB::hash(&values);
+    let code_hash = state.code_db.code_hash(&values);
     let size = values.len();
     let dst_id = NumberOrHash::Hash(code_hash);
     let bytes = Bytecode::from(values).code;
@@ -348,6 +371,7 @@ fn handle_create(
         .mapper(|v: &BytecodeElement| (v.value, v.is_code))
         .build();
 
+    let copy_bytes = CopyBytes::new(&mut state.block.copy_bytes, copy_steps, None, None)?;
     state.push_copy(
         step,
         CopyEvent {
@@ -360,7 +384,7 @@ fn handle_create(
             dst_id,
             dst_addr: 0,
             log_id: None,
-            copy_bytes: CopyBytes::new(copy_steps, None, None),
+            copy_bytes,
             access_list: vec![],
         },
     );
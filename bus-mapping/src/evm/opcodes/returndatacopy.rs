@@ -1,6 +1,7 @@
 use crate::{
     circuit_input_builder::{
         CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+        StackOpsBuilder,
     },
     evm::Opcode,
     operation::CallContextField,
@@ -18,9 +19,10 @@ impl Opcode for Returndatacopy {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
-        let memory_offset = state.stack_pop(&mut exec_step)?;
-        let data_offset = state.stack_pop(&mut exec_step)?;
-        let length = state.stack_pop(&mut exec_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
+        let memory_offset = stack_ops.pop(state, &mut exec_step)?;
+        let data_offset = stack_ops.pop(state, &mut exec_step)?;
+        let length = stack_ops.pop(state, &mut exec_step)?;
 
         #[cfg(feature = "enable-stack")]
         {
@@ -62,6 +64,7 @@ impl Opcode for Returndatacopy {
 
         let copy_event = gen_copy_event(state, memory_offset, data_offset, length, &mut exec_step)?;
         state.push_copy(&mut exec_step, copy_event);
+        stack_ops.finish()?;
         Ok(vec![exec_step])
     }
 }
@@ -102,7 +105,12 @@ fn gen_copy_event(
         dst_addr,
         log_id: None,
         rw_counter_start,
-        copy_bytes: CopyBytes::new(read_steps, Some(write_steps), Some(prev_bytes)),
+        copy_bytes: CopyBytes::new(
+            &mut state.block.copy_bytes,
+            read_steps,
+            Some(write_steps),
+            Some(prev_bytes),
+        )?,
         access_list: vec![],
     })
 }
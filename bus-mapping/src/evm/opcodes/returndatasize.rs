@@ -1,5 +1,5 @@
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     operation::CallContextField,
     Error,
 };
@@ -18,6 +18,7 @@ impl Opcode for Returndatasize {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
         let size = state.call_ctx()?.return_data.len();
         state.call_context_read(
             &mut exec_step,
@@ -40,7 +41,8 @@ impl Opcode for Returndatasize {
                 assert_eq!(real_size, size);
             }
         }
-        state.stack_push(&mut exec_step, size.into())?;
+        stack_ops.push(state, &mut exec_step, size.into())?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -1,6 +1,6 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     operation::{AccountField, CallContextField},
     Error,
 };
@@ -16,6 +16,7 @@ impl Opcode for Selfbalance {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
         let callee_address = state.call()?.address;
         let self_balance = state.sdb.get_balance(&callee_address);
         #[cfg(feature = "enable-stack")]
@@ -38,7 +39,8 @@ impl Opcode for Selfbalance {
         )?;
 
         // Stack write of self_balance
-        state.stack_push(&mut exec_step, self_balance)?;
+        stack_ops.push(state, &mut exec_step, self_balance)?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
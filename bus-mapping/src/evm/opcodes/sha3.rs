@@ -1,8 +1,9 @@
 use crate::{
     circuit_input_builder::{
         CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, CopyEventStepsBuilder, ExecStep,
-        NumberOrHash,
+        NumberOrHash, StackOpsBuilder,
     },
+    gas::GasStep,
     Error,
 };
 use eth_types::{
@@ -23,21 +24,30 @@ impl Opcode for Sha3 {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
-        let offset = state.stack_pop(&mut exec_step)?;
-        let size = state.stack_pop(&mut exec_step)?;
+        let offset = stack_ops.pop(state, &mut exec_step)?;
+        let size = stack_ops.pop(state, &mut exec_step)?;
         #[cfg(feature = "enable-stack")]
         {
             assert_eq!(offset, geth_step.stack.nth_last(0)?);
             assert_eq!(size, geth_step.stack.nth_last(1)?);
         }
 
+        let curr_memory_word_size = state.call_ctx()?.memory_word_size();
         if size.gt(&U256::zero()) {
             state
                 .call_ctx_mut()?
                 .memory
                 .extend_at_least(offset.as_usize() + size.as_usize());
         }
+        let gas_step = GasStep {
+            curr_memory_word_size,
+            next_memory_word_size: state.call_ctx()?.memory_word_size(),
+            num_bytes: size.as_u64(),
+            ..GasStep::new(geth_step.op)
+        };
+        state.check_step_gas_cost(geth_step, &gas_step)?;
 
         let sha3_input = state
             .call_ctx()?
@@ -52,7 +62,7 @@ impl Opcode for Sha3 {
         let _output = Word::from_big_endian(&sha3);
         #[cfg(feature = "enable-stack")]
         assert_eq!(_output, geth_steps[1].stack.last()?);
-        state.stack_push(&mut exec_step, sha3.into())?;
+        stack_ops.push(state, &mut exec_step, sha3.into())?;
 
         // Memory read operations
         let rw_counter_start = state.block_ctx.rwc;
@@ -74,8 +84,9 @@ impl Opcode for Sha3 {
             vec![]
         };
 
-        state.block.sha3_inputs.push(sha3_input);
+        state.block.sha3_inputs.push(sha3_input)?;
         let call_id = state.call()?.call_id;
+        let copy_bytes = CopyBytes::new(&mut state.block.copy_bytes, copy_steps, None, None)?;
         state.push_copy(
             &mut exec_step,
             CopyEvent {
@@ -91,10 +102,11 @@ impl Opcode for Sha3 {
                 dst_id: NumberOrHash::Number(call_id),
                 log_id: None,
                 rw_counter_start,
-                copy_bytes: CopyBytes::new(copy_steps, None, None),
+                copy_bytes,
                 access_list: vec![],
             },
         );
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -255,7 +267,7 @@ pub mod sha3_tests {
 
         // Initial memory_len bytes are the memory writes from MSTORE instruction, so we
         // skip them.
-        let memory = Memory(memory);
+        let memory = Memory::from(memory);
         let dst_range = MemoryWordRange::align_range(offset, size);
         assert_eq!(
             builder
@@ -287,9 +299,10 @@ pub mod sha3_tests {
         //assert_eq!(copy_events[0].bytes.len(), size);
 
         let mut mask_count = 0;
-        for (idx, (value, is_code, is_mask)) in copy_events[0].copy_bytes.bytes.iter().enumerate() {
+        let copy_steps = copy_events[0].copy_bytes.bytes(&builder.block.copy_bytes);
+        for (idx, (value, is_code, is_mask)) in copy_steps.iter().enumerate() {
             if !is_mask {
-                assert_eq!(Some(value), memory_view.get(idx - mask_count));
+                assert_eq!(Some(&value), memory_view.get(idx - mask_count));
                 assert!(!is_code);
             } else {
                 mask_count += 1;
@@ -304,4 +317,11 @@ pub mod sha3_tests {
         test_ok(0x222, 0x111, MemoryKind::EqualToSize);
         test_ok(0x20, 0x30, MemoryKind::MoreThanSize);
     }
+
+    #[test]
+    fn sha3_opcode_large_input_reads_words() {
+        // the input is read by word through a single copy event, so hashing
+        // 10KB takes 321 memory reads rather than one per byte
+        test_ok(0x10, 10 * 1024, MemoryKind::EqualToSize);
+    }
 }
@@ -1,6 +1,7 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
+    gas::GasStep,
     operation::{CallContextField, StorageOp, TxAccessListAccountStorageOp, RW},
     Error,
 };
@@ -19,6 +20,7 @@ impl Opcode for Sload {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
         let call_id = state.call()?.call_id;
         let contract_addr = state.call()?.address;
@@ -52,7 +54,7 @@ impl Opcode for Sload {
         )?;
 
         // First stack read
-        let key = state.stack_pop(&mut exec_step)?;
+        let key = stack_ops.pop(state, &mut exec_step)?;
         #[cfg(feature = "enable-stack")]
         assert_eq!(key, geth_step.stack.last()?);
 
@@ -71,6 +73,13 @@ impl Opcode for Sload {
         let is_warm = state
             .sdb
             .check_account_storage_in_access_list(&(contract_addr, key));
+        state.check_step_gas_cost(
+            geth_step,
+            &GasStep {
+                is_warm,
+                ..GasStep::new(geth_step.op)
+            },
+        )?;
 
         let (_, committed_value) = state.sdb.get_committed_storage(&contract_addr, &key);
         let committed_value = *committed_value;
@@ -88,7 +97,7 @@ impl Opcode for Sload {
         )?;
 
         // First stack write
-        state.stack_push(&mut exec_step, value)?;
+        stack_ops.push(state, &mut exec_step, value)?;
         state.push_op(
             &mut exec_step,
             RW::READ,
@@ -110,6 +119,7 @@ impl Opcode for Sload {
                 is_warm_prev: is_warm,
             },
         )?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -1,67 +1,23 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     operation::{CallContextField, StorageOp, TxAccessListAccountStorageOp, TxRefundOp},
     Error,
 };
 
 use crate::operation::RW;
-use eth_types::{evm_types::GasCost, GethExecStep, ToWord, Word};
+use eth_types::{evm_types::gas_utils::SstoreCase, GethExecStep, ToWord, Word};
 
 /// Calculate the refund of a sstore op, base on EIP-3529 (the SSTORE_CLEARS_SCHEDULE
-/// has been updated to 4800)
+/// has been updated to 4800). See [`SstoreCase`] for the cases.
 pub fn calc_expected_tx_refund(
     tx_refund_old: u64,
     value: eth_types::Word,
     value_prev: eth_types::Word,
     original_value: eth_types::Word,
 ) -> u64 {
-    // Same clause tags(like "delete slot (2.1.2b)") used as [`makeGasSStoreFunc` in go-ethereum](https://github.com/ethereum/go-ethereum/blob/9fd8825d5a196edde6d8ef81382979875145b346/core/vm/operations_acl.go#L27)
-    // Control flow of this function try to follow `makeGasSStoreFunc` for better
-    // understanding and comparison.
-
-    let mut tx_refund_new = tx_refund_old;
-
-    // The "clearing slot refund" and "resetting value refund" are ADDED together,
-    // they are NOT MUTUALLY EXCLUSIVE.
-    // Search "Apply both of the following clauses" in EIP-2200 for more details.
-    // There can be five total kinds of refund:
-    // 1. -SSTORE_CLEARS_SCHEDULE
-    // 2. SSTORE_CLEARS_SCHEDULE
-    // 3. SSTORE_SET - WARM_ACCESS
-    // 4. SSTORE_RESET - WARM_ACCESS
-    // 5. -SSTORE_CLEARS_SCHEDULE + SSTORE_RESET - WARM_ACCESS
-    // The last case can happen if (original_value, prev_value, value) be (v,0,v)
-    // where v != 0,
-    // then both "clearing slot refund" and "resetting value refund" are non zero.
-
-    if value_prev != value {
-        // refund related to clearing slot
-        // "delete slot (2.1.2b)" can be safely merged in "delete slot (2.2.1.2)"
-        if !original_value.is_zero() {
-            if value_prev.is_zero() {
-                // recreate slot (2.2.1.1)
-                tx_refund_new -= GasCost::SSTORE_CLEARS_SCHEDULE.as_u64()
-            }
-            if value.is_zero() {
-                // delete slot (2.2.1.2)
-                tx_refund_new += GasCost::SSTORE_CLEARS_SCHEDULE.as_u64()
-            }
-        }
-
-        // refund related to resetting value
-        if original_value == value {
-            if original_value.is_zero() {
-                // reset to original inexistent slot (2.2.2.1)
-                tx_refund_new += GasCost::SSTORE_SET.as_u64() - GasCost::WARM_ACCESS.as_u64();
-            } else {
-                // reset to original existing slot (2.2.2.2)
-                tx_refund_new += GasCost::SSTORE_RESET.as_u64() - GasCost::WARM_ACCESS.as_u64();
-            }
-        }
-    }
-
-    tx_refund_new
+    tx_refund_old
+        .wrapping_add_signed(SstoreCase::new(original_value, value_prev, value).refund_delta())
 }
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
@@ -77,6 +33,7 @@ impl Opcode for Sstore {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
         let contract_addr = state.call()?.address;
 
@@ -114,8 +71,8 @@ impl Opcode for Sstore {
             state.call()?.address.to_word(),
         )?;
 
-        let key = state.stack_pop(&mut exec_step)?;
-        let value = state.stack_pop(&mut exec_step)?;
+        let key = stack_ops.pop(state, &mut exec_step)?;
+        let value = stack_ops.pop(state, &mut exec_step)?;
         #[cfg(feature = "enable-stack")]
         {
             assert_eq!(key, geth_step.stack.nth_last(0)?);
@@ -196,6 +153,7 @@ impl Opcode for Sstore {
                 value: refund,
             },
         )?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -211,11 +169,14 @@ mod sstore_tests {
     };
     use eth_types::{
         bytecode,
-        evm_types::{OpcodeId, StackAddress},
+        evm_types::{GasCost, OpcodeId, StackAddress},
         geth_types::GethData,
         Word,
     };
-    use mock::{test_ctx::helpers::tx_from_1_to_0, TestContext, MOCK_ACCOUNTS};
+    use mock::{
+        generate_mock_call_bytecode, test_ctx::helpers::tx_from_1_to_0, MockCallBytecodeParams,
+        TestContext, MOCK_ACCOUNTS,
+    };
     use pretty_assertions::assert_eq;
 
     fn test_ok(is_warm: bool) {
@@ -365,4 +326,77 @@ mod sstore_tests {
     fn sstore_opcode_impl_cold() {
         test_ok(false)
     }
+
+    #[test]
+    fn sstore_opcode_refund_reverted_in_sub_call() {
+        // The callee clears its slot and reverts, the caller clears its slot
+        // and stops. Only the caller's refund is left at the end of the tx.
+        let code_b = bytecode! {
+            PUSH1(0x00u64)
+            PUSH1(0x00u64)
+            SSTORE
+            .op_revert(0x00, 0x00)
+        };
+        let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+            address: MOCK_ACCOUNTS[1],
+            pushdata: vec![0x01; 32],
+            gas: 0x4_0000,
+            instructions_after_call: bytecode! {
+                PUSH1(0x00u64)
+                PUSH1(0x00u64)
+                SSTORE
+            },
+            ..MockCallBytecodeParams::default()
+        });
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .code(code_a)
+                    .storage(vec![(Word::zero(), Word::one())].into_iter());
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .code(code_b)
+                    .storage(vec![(Word::zero(), Word::one())].into_iter());
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let clear_refund = GasCost::SSTORE_CLEARS_SCHEDULE.as_u64();
+        let refund_ops = builder
+            .block
+            .container
+            .tx_refund
+            .iter()
+            .map(|op| (op.rw(), op.op().value_prev, op.op().value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            refund_ops,
+            vec![
+                // callee clears its slot
+                (RW::WRITE, 0, clear_refund),
+                // reverted when the callee reverts
+                (RW::WRITE, clear_refund, 0),
+                // caller clears its slot
+                (RW::WRITE, 0, clear_refund),
+                // read by EndTx
+                (RW::READ, clear_refund, clear_refund),
+            ]
+        );
+    }
 }
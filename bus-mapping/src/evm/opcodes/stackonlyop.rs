@@ -1,6 +1,6 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     Error,
 };
 use eth_types::GethExecStep;
@@ -22,7 +22,11 @@ impl<const N_POP: usize, const IS_ERR: bool> Opcode for StackPopOnlyOpcode<N_POP
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
         // N_POP stack reads
-        let _stack_inputs = state.stack_pops(&mut exec_step, N_POP)?;
+        let _stack_inputs = if IS_ERR {
+            state.stack_pops(&mut exec_step, N_POP)?
+        } else {
+            StackOpsBuilder::new(geth_step.op).emit(state, &mut exec_step, |_| vec![])?
+        };
         #[cfg(feature = "enable-stack")]
         for (i, v) in _stack_inputs.into_iter().enumerate() {
             assert_eq!(v, geth_step.stack.nth_last(i)?);
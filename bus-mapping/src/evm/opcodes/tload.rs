@@ -1,6 +1,6 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     operation::{CallContextField, TransientStorageOp, RW},
     Error,
 };
@@ -19,6 +19,7 @@ impl Opcode for Tload {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
         let call_id = state.call()?.call_id;
         let contract_addr = state.call()?.address;
@@ -38,7 +39,7 @@ impl Opcode for Tload {
         )?;
 
         // First stack read
-        let key = state.stack_pop(&mut exec_step)?;
+        let key = stack_ops.pop(state, &mut exec_step)?;
         #[cfg(feature = "enable-stack")]
         assert_eq!(key, geth_step.stack.last()?);
 
@@ -58,7 +59,8 @@ impl Opcode for Tload {
         )?;
 
         // First stack write
-        state.stack_push(&mut exec_step, value)?;
+        stack_ops.push(state, &mut exec_step, value)?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
@@ -1,6 +1,6 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{CircuitInputStateRef, ExecStep, StackOpsBuilder},
     operation::{CallContextField, TransientStorageOp},
     Error,
 };
@@ -19,6 +19,7 @@ impl Opcode for Tstore {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
+        let mut stack_ops = StackOpsBuilder::new(geth_step.op);
 
         let contract_addr = state.call()?.address;
 
@@ -56,8 +57,8 @@ impl Opcode for Tstore {
             state.call()?.address.to_word(),
         )?;
 
-        let key = state.stack_pop(&mut exec_step)?;
-        let value = state.stack_pop(&mut exec_step)?;
+        let key = stack_ops.pop(state, &mut exec_step)?;
+        let value = stack_ops.pop(state, &mut exec_step)?;
         #[cfg(feature = "enable-stack")]
         {
             assert_eq!(key, geth_step.stack.nth_last(0)?);
@@ -77,6 +78,7 @@ impl Opcode for Tstore {
                 state.tx_ctx.id(),
             ),
         )?;
+        stack_ops.finish()?;
 
         Ok(vec![exec_step])
     }
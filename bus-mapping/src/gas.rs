@@ -0,0 +1,334 @@
+//! Gas oracle: pure functions computing the gas cost of an opcode step from a
+//! minimal view of the step.
+
+use eth_types::{
+    evm_types::{
+        gas_utils::{memory_copier_gas_cost, memory_expansion_gas_cost},
+        GasCost, OpcodeId,
+    },
+    Word,
+};
+
+/// Gas cost per byte of data of LOGn
+const LOG_DATA_GAS: u64 = 8;
+
+/// Minimal view of an execution step required to compute its gas cost.
+#[derive(Clone, Copy, Debug)]
+pub struct GasStep {
+    /// Opcode of the step
+    pub opcode: OpcodeId,
+    /// Memory size in words before the step
+    pub curr_memory_word_size: u64,
+    /// Memory size in words after the step
+    pub next_memory_word_size: u64,
+    /// Whether the account or the storage slot accessed by the step is warm
+    pub is_warm: bool,
+    /// Number of bytes copied, hashed or logged by the step
+    pub num_bytes: u64,
+    /// Exponent of EXP
+    pub exponent: Word,
+}
+
+impl GasStep {
+    /// View of a step of `opcode` which doesn't expand the memory, has no data
+    /// and accesses cold state.
+    pub fn new(opcode: OpcodeId) -> Self {
+        Self {
+            opcode,
+            curr_memory_word_size: 0,
+            next_memory_word_size: 0,
+            is_warm: false,
+            num_bytes: 0,
+            exponent: Word::zero(),
+        }
+    }
+}
+
+/// Gas cost of the memory expansion of the step.
+pub fn memory_expansion(step: &GasStep) -> u64 {
+    memory_expansion_gas_cost(step.curr_memory_word_size, step.next_memory_word_size)
+}
+
+/// Gas cost of an account access (EIP 2929).
+pub fn account_access(is_warm: bool) -> u64 {
+    if is_warm {
+        GasCost::WARM_ACCESS.as_u64()
+    } else {
+        GasCost::COLD_ACCOUNT_ACCESS.as_u64()
+    }
+}
+
+/// Gas cost of a storage slot access (EIP 2929).
+pub fn storage_access(is_warm: bool) -> u64 {
+    if is_warm {
+        GasCost::WARM_ACCESS.as_u64()
+    } else {
+        GasCost::COLD_SLOAD.as_u64()
+    }
+}
+
+/// Gas cost of EXP, which depends on the byte size of the exponent.
+pub fn exp(exponent: Word) -> u64 {
+    let exponent_byte_size = (exponent.bits() as u64 + 7) / 8;
+    GasCost::SLOW.as_u64() + GasCost::EXP_BYTE_TIMES.as_u64() * exponent_byte_size
+}
+
+/// Gas cost of LOGn without the memory expansion.
+pub fn log(num_topics: u64, num_bytes: u64) -> u64 {
+    GasCost::LOG.as_u64() * (1 + num_topics) + LOG_DATA_GAS * num_bytes
+}
+
+/// Gas cost of the CALL family without the memory expansion and the gas
+/// passed to the callee.
+pub fn call(is_warm: bool, has_value: bool, is_new_account: bool) -> u64 {
+    account_access(is_warm)
+        + if has_value {
+            GasCost::CALL_WITH_VALUE.as_u64()
+                + if is_new_account {
+                    GasCost::NEW_ACCOUNT.as_u64()
+                } else {
+                    0
+                }
+        } else {
+            0
+        }
+}
+
+/// Gas cost of the step, as reported by geth. Returns `None` for the opcodes
+/// whose gas cost can't be computed from a [`GasStep`]: SSTORE depends on the
+/// original value of the slot, and the CALL and CREATE families include the
+/// gas passed to the callee.
+pub fn step_gas_cost(step: &GasStep) -> Option<u64> {
+    let constant = step.opcode.constant_gas_cost().as_u64();
+    let gas_cost = match step.opcode {
+        OpcodeId::EXP => exp(step.exponent),
+        OpcodeId::SHA3 => {
+            constant
+                + memory_copier_gas_cost(
+                    step.curr_memory_word_size,
+                    step.next_memory_word_size,
+                    step.num_bytes,
+                    GasCost::COPY_SHA3.as_u64(),
+                )
+        }
+        OpcodeId::CALLDATACOPY
+        | OpcodeId::CODECOPY
+        | OpcodeId::RETURNDATACOPY
+        | OpcodeId::MCOPY => {
+            constant
+                + memory_copier_gas_cost(
+                    step.curr_memory_word_size,
+                    step.next_memory_word_size,
+                    step.num_bytes,
+                    GasCost::COPY.as_u64(),
+                )
+        }
+        OpcodeId::EXTCODECOPY => {
+            account_access(step.is_warm)
+                + memory_copier_gas_cost(
+                    step.curr_memory_word_size,
+                    step.next_memory_word_size,
+                    step.num_bytes,
+                    GasCost::COPY.as_u64(),
+                )
+        }
+        OpcodeId::BALANCE | OpcodeId::EXTCODESIZE | OpcodeId::EXTCODEHASH => {
+            account_access(step.is_warm)
+        }
+        OpcodeId::SLOAD => storage_access(step.is_warm),
+        OpcodeId::LOG0 | OpcodeId::LOG1 | OpcodeId::LOG2 | OpcodeId::LOG3 | OpcodeId::LOG4 => {
+            let num_topics = (step.opcode.as_u8() - OpcodeId::LOG0.as_u8()) as u64;
+            log(num_topics, step.num_bytes) + memory_expansion(step)
+        }
+        OpcodeId::MLOAD
+        | OpcodeId::MSTORE
+        | OpcodeId::MSTORE8
+        | OpcodeId::RETURN
+        | OpcodeId::REVERT => constant + memory_expansion(step),
+        OpcodeId::SSTORE
+        | OpcodeId::CALL
+        | OpcodeId::CALLCODE
+        | OpcodeId::DELEGATECALL
+        | OpcodeId::STATICCALL
+        | OpcodeId::CREATE
+        | OpcodeId::CREATE2
+        | OpcodeId::SELFDESTRUCT
+        | OpcodeId::INVALID(_) => return None,
+        _ => constant,
+    };
+    Some(gas_cost)
+}
+
+#[cfg(test)]
+mod gas_tests {
+    use super::*;
+
+    #[test]
+    fn step_gas_cost_without_geth() {
+        assert_eq!(step_gas_cost(&GasStep::new(OpcodeId::ADD)), Some(3));
+        assert_eq!(step_gas_cost(&GasStep::new(OpcodeId::SSTORE)), None);
+        assert_eq!(
+            step_gas_cost(&GasStep {
+                exponent: Word::from(0x100),
+                ..GasStep::new(OpcodeId::EXP)
+            }),
+            Some(10 + 2 * 50)
+        );
+        assert_eq!(
+            step_gas_cost(&GasStep {
+                is_warm: true,
+                ..GasStep::new(OpcodeId::SLOAD)
+            }),
+            Some(100)
+        );
+        // Copying no byte doesn't expand the memory.
+        assert_eq!(
+            step_gas_cost(&GasStep {
+                next_memory_word_size: 1,
+                ..GasStep::new(OpcodeId::CALLDATACOPY)
+            }),
+            Some(3)
+        );
+    }
+
+    /// Replays the steps built from the geth trace, keeping track of the
+    /// memory size and of the accessed storage slots, and checks the gas cost
+    /// of every step against the one reported by geth. The stack operands are
+    /// read from the stack operations of the steps, so that the trace doesn't
+    /// need to include the stack.
+    #[test]
+    fn step_gas_cost_matches_geth() {
+        use crate::{circuit_input_builder::ExecState, mock::BlockData, operation::Target};
+        use eth_types::{bytecode, geth_types::GethData};
+        use mock::test_ctx::TestContext;
+        use std::collections::HashSet;
+
+        let code = bytecode! {
+            PUSH1(0x40)
+            PUSH1(0x80)
+            MSTORE
+            PUSH1(0x1f)
+            PUSH1(0xff)
+            MSTORE8
+            PUSH1(0x20)
+            PUSH1(0x00)
+            SHA3
+            POP
+            PUSH1(0x20)
+            PUSH1(0x00)
+            PUSH2(0x100)
+            CALLDATACOPY
+            PUSH1(0x40)
+            PUSH1(0x00)
+            PUSH2(0x200)
+            CODECOPY
+            PUSH2(0x1234)
+            PUSH1(0x03)
+            EXP
+            POP
+            PUSH1(0x00)
+            SLOAD
+            POP
+            PUSH1(0x00)
+            SLOAD
+            POP
+            ADDRESS
+            BALANCE
+            POP
+            PUSH1(0x10)
+            PUSH1(0x20)
+            LOG0
+            PUSH1(0x01)
+            PUSH1(0x30)
+            PUSH2(0x400)
+            LOG1
+            PUSH1(0x20)
+            PUSH1(0x00)
+            RETURN
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        fn memory_end(offset: Word, size: Word) -> (u64, u64) {
+            if size.is_zero() {
+                (0, 0)
+            } else {
+                (offset.as_u64() + size.as_u64(), size.as_u64())
+            }
+        }
+
+        let container = &builder.block.container;
+        let steps = builder.block.txs()[0].steps();
+        let mut curr_memory_word_size = 0;
+        let mut accessed_slots = HashSet::new();
+        let mut checked = 0;
+        for step in steps {
+            let opcode = match step.exec_state {
+                ExecState::Op(opcode) => opcode,
+                _ => continue,
+            };
+            // The stack words popped by the step, the top of the stack first.
+            let pops = step
+                .bus_mapping_instance
+                .iter()
+                .filter(|op_ref| op_ref.0 == Target::Stack)
+                .map(|op_ref| &container.stack[op_ref.1])
+                .take(opcode.stack_pops_pushes().0)
+                .map(|op| op.op().value)
+                .collect::<Vec<_>>();
+            let word = |n: usize| pops[n];
+            let (end, num_bytes) = match opcode {
+                OpcodeId::MLOAD | OpcodeId::MSTORE => (word(0).as_u64() + 32, 0),
+                OpcodeId::MSTORE8 => (word(0).as_u64() + 1, 0),
+                OpcodeId::SHA3
+                | OpcodeId::RETURN
+                | OpcodeId::REVERT
+                | OpcodeId::LOG0
+                | OpcodeId::LOG1
+                | OpcodeId::LOG2
+                | OpcodeId::LOG3
+                | OpcodeId::LOG4 => memory_end(word(0), word(1)),
+                OpcodeId::CALLDATACOPY | OpcodeId::CODECOPY | OpcodeId::RETURNDATACOPY => {
+                    memory_end(word(0), word(2))
+                }
+                _ => (0, 0),
+            };
+            let next_memory_word_size = curr_memory_word_size.max((end + 31) / 32);
+            let is_warm = match opcode {
+                OpcodeId::SLOAD => !accessed_slots.insert(word(0)),
+                // Only the balance of the callee is queried, which is warm.
+                OpcodeId::BALANCE => true,
+                _ => false,
+            };
+            let exponent = if opcode == OpcodeId::EXP {
+                word(1)
+            } else {
+                Word::zero()
+            };
+
+            let gas_step = GasStep {
+                opcode,
+                curr_memory_word_size,
+                next_memory_word_size,
+                is_warm,
+                num_bytes,
+                exponent,
+            };
+            if let Some(gas_cost) = step_gas_cost(&gas_step) {
+                assert_eq!(
+                    gas_cost, step.gas_cost.0,
+                    "gas cost mismatch at pc {:?} for {:?}",
+                    step.pc, opcode
+                );
+                checked += 1;
+            }
+            curr_memory_word_size = next_memory_word_size;
+        }
+        assert_eq!(checked, block.geth_traces[0].struct_logs.len());
+    }
+}
@@ -228,7 +228,10 @@ pub mod circuit_input_builder;
 pub mod error;
 pub mod evm;
 pub mod exec_trace;
+pub mod gas;
 pub use eth_types::l2_predeployed;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod mock;
 pub mod operation;
 pub mod precompile;
@@ -0,0 +1,255 @@
+//! Metrics of the witness generation, exported in the Prometheus text
+//! exposition format so that a prover fleet can be monitored.
+//!
+//! The metrics are process wide. They can be rendered with [`render`], or
+//! served over HTTP with [`serve`].
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::{self, Read, Write},
+    net::{TcpListener, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+/// Number of transaction traces fetched from the node.
+pub static TRACES_FETCHED: Counter = Counter::new(
+    "zkevm_traces_fetched_total",
+    "Number of transaction traces fetched from the node",
+);
+/// Number of transactions handled by the circuit input builder.
+pub static TXS_PROCESSED: Counter = Counter::new(
+    "zkevm_txs_processed_total",
+    "Number of transactions handled by the circuit input builder",
+);
+/// Number of execution steps handled by the circuit input builder.
+pub static STEPS_PROCESSED: Counter = Counter::new(
+    "zkevm_steps_processed_total",
+    "Number of execution steps handled by the circuit input builder",
+);
+/// Time spent handling a transaction, in seconds.
+pub static TX_HANDLE_SECONDS: Histogram = Histogram::new(
+    "zkevm_tx_handle_seconds",
+    "Time spent handling a transaction, in seconds",
+    &[0.001, 0.01, 0.1, 0.5, 1.0, 5.0, 10.0, 60.0],
+);
+/// Number of rw operations generated per block.
+pub static BLOCK_RW_OPS: Histogram = Histogram::new(
+    "zkevm_block_rw_ops",
+    "Number of rw operations generated per block",
+    &[1e3, 1e4, 1e5, 5e5, 1e6, 5e6, 1e7, 1e8],
+);
+/// Number of rows used by each sub circuit for the last witness.
+pub static CIRCUIT_ROWS: GaugeVec = GaugeVec::new(
+    "zkevm_circuit_rows",
+    "Number of rows used by each sub circuit for the last witness",
+    "circuit",
+);
+
+/// A monotonically increasing counter.
+#[derive(Debug)]
+pub struct Counter {
+    name: &'static str,
+    help: &'static str,
+    value: AtomicU64,
+}
+
+impl Counter {
+    const fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            value: AtomicU64::new(0),
+        }
+    }
+
+    /// Increment the counter by one.
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    /// Increment the counter by `n`.
+    pub fn inc_by(&self, n: u64) {
+        self.value.fetch_add(n, Ordering::Relaxed);
+    }
+
+    fn render(&self, out: &mut String) {
+        writeln!(out, "# HELP {} {}", self.name, self.help).unwrap();
+        writeln!(out, "# TYPE {} counter", self.name).unwrap();
+        writeln!(out, "{} {}", self.name, self.value.load(Ordering::Relaxed)).unwrap();
+    }
+}
+
+/// A gauge with one value per label value.
+#[derive(Debug)]
+pub struct GaugeVec {
+    name: &'static str,
+    help: &'static str,
+    label: &'static str,
+    values: Mutex<BTreeMap<String, u64>>,
+}
+
+impl GaugeVec {
+    const fn new(name: &'static str, help: &'static str, label: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            label,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Set the value of the gauge for `label_value`.
+    pub fn set(&self, label_value: &str, value: u64) {
+        self.values
+            .lock()
+            .unwrap()
+            .insert(label_value.to_string(), value);
+    }
+
+    fn render(&self, out: &mut String) {
+        writeln!(out, "# HELP {} {}", self.name, self.help).unwrap();
+        writeln!(out, "# TYPE {} gauge", self.name).unwrap();
+        for (label_value, value) in self.values.lock().unwrap().iter() {
+            writeln!(
+                out,
+                "{}{{{}=\"{}\"}} {}",
+                self.name, self.label, label_value, value
+            )
+            .unwrap();
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct HistogramState {
+    bucket_counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+/// A histogram with fixed buckets.
+#[derive(Debug)]
+pub struct Histogram {
+    name: &'static str,
+    help: &'static str,
+    buckets: &'static [f64],
+    state: Mutex<HistogramState>,
+}
+
+impl Histogram {
+    const fn new(name: &'static str, help: &'static str, buckets: &'static [f64]) -> Self {
+        Self {
+            name,
+            help,
+            buckets,
+            state: Mutex::new(HistogramState {
+                bucket_counts: Vec::new(),
+                sum: 0.0,
+                count: 0,
+            }),
+        }
+    }
+
+    /// Record an observation of `value`.
+    pub fn observe(&self, value: f64) {
+        let mut state = self.state.lock().unwrap();
+        state.bucket_counts.resize(self.buckets.len(), 0);
+        for (bound, count) in self.buckets.iter().zip(state.bucket_counts.iter_mut()) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        state.sum += value;
+        state.count += 1;
+    }
+
+    fn render(&self, out: &mut String) {
+        let state = self.state.lock().unwrap();
+        writeln!(out, "# HELP {} {}", self.name, self.help).unwrap();
+        writeln!(out, "# TYPE {} histogram", self.name).unwrap();
+        for (i, bound) in self.buckets.iter().enumerate() {
+            let count = state.bucket_counts.get(i).copied().unwrap_or_default();
+            writeln!(out, "{}_bucket{{le=\"{}\"}} {}", self.name, bound, count).unwrap();
+        }
+        writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", self.name, state.count).unwrap();
+        writeln!(out, "{}_sum {}", self.name, state.sum).unwrap();
+        writeln!(out, "{}_count {}", self.name, state.count).unwrap();
+    }
+}
+
+/// Peak resident set size of the process, in bytes. Only available on
+/// Linux, where it is read from `/proc/self/status`.
+fn peak_memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Render all the metrics in the Prometheus text exposition format.
+pub fn render() -> String {
+    let mut out = String::new();
+    TRACES_FETCHED.render(&mut out);
+    TXS_PROCESSED.render(&mut out);
+    STEPS_PROCESSED.render(&mut out);
+    TX_HANDLE_SECONDS.render(&mut out);
+    BLOCK_RW_OPS.render(&mut out);
+    CIRCUIT_ROWS.render(&mut out);
+    if let Some(peak_memory) = peak_memory_bytes() {
+        out.push_str("# HELP zkevm_peak_memory_bytes Peak resident set size of the process\n");
+        out.push_str("# TYPE zkevm_peak_memory_bytes gauge\n");
+        writeln!(out, "zkevm_peak_memory_bytes {peak_memory}").unwrap();
+    }
+    out
+}
+
+/// Serve the metrics over HTTP on `addr` from a background thread. Any
+/// request is answered with the rendered metrics.
+pub fn serve(addr: impl ToSocketAddrs) -> io::Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(addr)?;
+    Ok(thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            // the request itself is irrelevant, only drain what was sent
+            let mut buf = [0u8; 1024];
+            if stream.read(&mut buf).is_err() {
+                continue;
+            }
+            let body = render();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            if let Err(e) = stream.write_all(response.as_bytes()) {
+                log::warn!("failed to serve metrics: {e}");
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_histogram() {
+        static HISTOGRAM: Histogram = Histogram::new("test_histogram", "help", &[1.0, 10.0]);
+        HISTOGRAM.observe(0.5);
+        HISTOGRAM.observe(5.0);
+        HISTOGRAM.observe(50.0);
+
+        let mut out = String::new();
+        HISTOGRAM.render(&mut out);
+        assert!(out.contains("test_histogram_bucket{le=\"1\"} 1\n"));
+        assert!(out.contains("test_histogram_bucket{le=\"10\"} 2\n"));
+        assert!(out.contains("test_histogram_bucket{le=\"+Inf\"} 3\n"));
+        assert!(out.contains("test_histogram_sum 55.5\n"));
+        assert!(out.contains("test_histogram_count 3\n"));
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::circuit_input_builder::{AccessSet, Block, Blocks, CircuitInputBuilder, CircuitsParams};
 use eth_types::{
-    geth_types::GethData,
+    geth_types::{GethData, StateOverrides},
     state_db::{self, CodeDB, StateDB},
     ToWord, Word, H256,
 };
@@ -44,6 +44,13 @@ impl BlockData {
         block.circuits_params = self.circuits_params;
         CircuitInputBuilder::new(self.sdb.clone(), self.code_db.clone(), &block)
     }
+    /// Apply `overrides` on top of the state of the block. The traces are
+    /// expected to be produced against the overridden state, e.g. by tracing
+    /// with the same overrides.
+    pub fn apply_state_overrides(&mut self, overrides: &StateOverrides) {
+        self.sdb.apply_overrides(&mut self.code_db, overrides);
+    }
+
     /// Create a new block from the given Geth data.
     pub fn new_from_geth_data_with_params(
         geth_data: GethData,
@@ -1,7 +1,7 @@
 use super::{
-    AccountOp, CallContextOp, MemoryOp, Op, OpEnum, Operation, RWCounter, StackOp, StartOp,
-    StorageOp, Target, TransientStorageOp, TxAccessListAccountOp, TxAccessListAccountStorageOp,
-    TxLogOp, TxReceiptOp, TxRefundOp, RW,
+    AccountOp, CallContextOp, MemoryOp, Op, OpEnum, Operation, PaddingOp, RWCounter, StackOp,
+    StartOp, StorageOp, Target, TransientStorageOp, TxAccessListAccountOp,
+    TxAccessListAccountStorageOp, TxLogOp, TxReceiptOp, TxRefundOp, RW,
 };
 use crate::exec_trace::OperationRef;
 use itertools::Itertools;
@@ -46,6 +46,8 @@ pub struct OperationContainer {
     pub tx_log: Vec<Operation<TxLogOp>>,
     /// Operations of Start
     pub start: Vec<Operation<StartOp>>,
+    /// Operations of Padding
+    pub padding: Vec<Operation<PaddingOp>>,
 }
 
 impl Default for OperationContainer {
@@ -71,6 +73,7 @@ impl OperationContainer {
             tx_receipt: Vec::new(),
             tx_log: Vec::new(),
             start: Vec::new(),
+            padding: Vec::new(),
         }
     }
 
@@ -176,6 +179,10 @@ impl OperationContainer {
                 self.start.push(Operation::new(rwc, rw, op));
                 OperationRef::from((Target::Start, self.start.len() - 1))
             }
+            OpEnum::Padding(op) => {
+                self.padding.push(Operation::new(rwc, rw, op));
+                OperationRef::from((Target::Padding, self.padding.len() - 1))
+            }
         }
     }
 
@@ -196,6 +203,47 @@ impl OperationContainer {
     pub fn sorted_storage(&self) -> Vec<Operation<StorageOp>> {
         self.storage.iter().sorted().cloned().collect()
     }
+
+    /// Returns the number of operations of each target, to drop the
+    /// operations inserted afterwards with [`Self::truncate`].
+    pub fn lens(&self) -> [usize; 13] {
+        [
+            self.memory.len(),
+            self.stack.len(),
+            self.storage.len(),
+            self.transient_storage.len(),
+            self.tx_access_list_account.len(),
+            self.tx_access_list_account_storage.len(),
+            self.tx_refund.len(),
+            self.account.len(),
+            self.call_context.len(),
+            self.tx_receipt.len(),
+            self.tx_log.len(),
+            self.start.len(),
+            self.padding.len(),
+        ]
+    }
+
+    /// Drops the operations inserted after `lens` was returned by
+    /// [`Self::lens`].
+    pub fn truncate(&mut self, lens: [usize; 13]) {
+        let [memory, stack, storage, transient_storage, tx_access_list_account, tx_access_list_account_storage, tx_refund, account, call_context, tx_receipt, tx_log, start, padding] =
+            lens;
+        self.memory.truncate(memory);
+        self.stack.truncate(stack);
+        self.storage.truncate(storage);
+        self.transient_storage.truncate(transient_storage);
+        self.tx_access_list_account.truncate(tx_access_list_account);
+        self.tx_access_list_account_storage
+            .truncate(tx_access_list_account_storage);
+        self.tx_refund.truncate(tx_refund);
+        self.account.truncate(account);
+        self.call_context.truncate(call_context);
+        self.tx_receipt.truncate(tx_receipt);
+        self.tx_log.truncate(tx_log);
+        self.start.truncate(start);
+        self.padding.truncate(padding);
+    }
 }
 
 #[cfg(test)]
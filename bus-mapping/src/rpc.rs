@@ -1,16 +1,25 @@
 //! Module which contains all the RPC calls that are needed at any point to
 //! query a Geth node in order to get a Block, Tx or Trace info.
 
+#[cfg(feature = "rpc-streaming")]
+mod stream;
+
+#[cfg(feature = "rpc-streaming")]
+use eth_types::BlockTracesSeed;
+
 use crate::Error;
 use eth_types::{
-    Address, Block, Bytes, EIP1186ProofResponse, GethExecTrace, GethPrestateTrace, Hash,
-    ResultGethExecTraces, ResultGethPrestateTraces, Transaction, Word, H256, U64,
+    geth_types::StateOverrides, Address, Block, Bytes, EIP1186ProofResponse, GethExecTrace,
+    GethMuxDiffTrace, GethMuxTrace, GethPrestateDiff, GethPrestateTrace, Hash, ResultGethMuxTrace,
+    ResultGethPrestateTraces, Transaction, Word, H256, U64,
 };
 pub use ethers_core::types::BlockNumber;
+use ethers_core::types::TransactionRequest;
 use ethers_providers::JsonRpcClient;
-use serde::Serialize;
-use serde_json::json;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, value::RawValue};
 use std::collections::HashMap;
+use url::Url;
 
 use crate::util::GETH_TRACE_CHECK_LEVEL;
 
@@ -75,14 +84,42 @@ impl Default for GethLoggerConfig {
     }
 }
 
+/// Override of the header fields of the block a call is executed in. Follows
+/// the geth `BlockOverrides` format used by `debug_traceCall`.
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockOverrides {
+    /// Block number
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<U64>,
+    /// Block timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<U64>,
+    /// Block base fee
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_fee: Option<Word>,
+}
+
 /// Placeholder structure designed to contain the methods that the BusMapping
 /// needs in order to enable Geth queries.
-pub struct GethClient<P: JsonRpcClient>(pub P);
+pub struct GethClient<P: JsonRpcClient>(
+    pub P,
+    #[cfg_attr(not(feature = "rpc-streaming"), allow(dead_code))] Option<Url>,
+);
 
 impl<P: JsonRpcClient> GethClient<P> {
     /// Generates a new `GethClient` instance.
     pub fn new(provider: P) -> Self {
-        Self(provider)
+        Self(provider, None)
+    }
+
+    /// Receive the struct logs of whole blocks straight from the HTTP node at
+    /// `url`, which is the one behind the provider, decoding them as they
+    /// arrive. Going through the provider buffers the whole response first.
+    #[cfg(feature = "rpc-streaming")]
+    pub fn with_streaming(mut self, url: Url) -> Self {
+        self.1 = Some(url);
+        self
     }
 
     /// Calls `eth_coinbase` via JSON-RPC returning the coinbase of the network.
@@ -144,17 +181,8 @@ impl<P: JsonRpcClient> GethClient<P> {
     /// [`Vec<GethExecTrace>`] with each GethTrace corresponding to 1
     /// transaction of the block.
     pub async fn trace_block_by_hash(&self, hash: Hash) -> Result<Vec<GethExecTrace>, Error> {
-        let hash = serialize(&hash);
-        let cfg = serialize(&GethLoggerConfig {
-            timeout: Some("300s".to_string()),
-            ..Default::default()
-        });
-        let resp: ResultGethExecTraces = self
-            .0
-            .request("debug_traceBlockByHash", [hash, cfg])
+        self.trace_block("debug_traceBlockByHash", serialize(&hash), true)
             .await
-            .map_err(|e| Error::JSONRpcError(e.into()))?;
-        Ok(resp.0.into_iter().map(|step| step.result).collect())
     }
 
     /// Calls `debug_traceBlockByNumber` via JSON-RPC returning a
@@ -164,51 +192,92 @@ impl<P: JsonRpcClient> GethClient<P> {
         &self,
         block_num: BlockNumber,
     ) -> Result<Vec<GethExecTrace>, Error> {
-        let num = serialize(&block_num);
+        self.trace_block("debug_traceBlockByNumber", serialize(&block_num), true)
+            .await
+    }
+
+    /// Same as [`Self::trace_block_by_number`], for nodes which don't ship
+    /// the prestateTracer: the prestate of the returned traces is left
+    /// empty.
+    pub async fn trace_block_by_number_without_prestate(
+        &self,
+        block_num: BlockNumber,
+    ) -> Result<Vec<GethExecTrace>, Error> {
+        self.trace_block("debug_traceBlockByNumber", serialize(&block_num), false)
+            .await
+    }
+
+    /// Traces a block with both the struct logger and the muxTracer through
+    /// `method`, which is one of the `debug_traceBlockBy*` calls. The
+    /// muxTracer only runs the prestateTracer if `with_prestate` is set.
+    ///
+    /// The struct logger response is decoded one transaction at a time, so
+    /// that no intermediate [`serde_json::Value`] of the whole (possibly
+    /// multi-GB) response is built. It is decoded while it is received when
+    /// streaming is enabled with `Self::with_streaming` (`rpc-streaming`
+    /// feature), and kept as raw JSON until it is complete otherwise.
+    async fn trace_block(
+        &self,
+        method: &str,
+        block: serde_json::Value,
+        with_prestate: bool,
+    ) -> Result<Vec<GethExecTrace>, Error> {
         let cfg = serialize(&GethLoggerConfig {
             timeout: Some("300s".to_string()),
             ..Default::default()
         });
-        let mut struct_logs: Vec<serde_json::Value> = self
-            .0
-            .request("debug_traceBlockByNumber", [num.clone(), cfg])
-            .await
-            .map_err(|e| Error::JSONRpcError(e.into()))?;
-        let mux_trace: Vec<serde_json::Value> = self
+        let tracer_config = if with_prestate {
+            json!({
+                "callTracer": {},
+                "prestateTracer": {}
+            })
+        } else {
+            json!({
+                "callTracer": {}
+            })
+        };
+        let mux_trace: Vec<ResultGethMuxTrace> = self
             .0
             .request(
-                "debug_traceBlockByNumber",
+                method,
                 [
-                    num,
+                    block.clone(),
                     json!({
                         "tracer": "muxTracer",
-                        "tracerConfig": {
-                            "callTracer": {},
-                            "prestateTracer": {}
-                        }
+                        "tracerConfig": tracer_config
                     }),
                 ],
             )
             .await
             .map_err(|e| Error::JSONRpcError(e.into()))?;
+        let mux_trace = mux_trace.into_iter().map(|mux| mux.result).collect();
+        let traces = self
+            .block_struct_logs(method, json!([block, cfg]), mux_trace)
+            .await?;
+        #[cfg(feature = "metrics")]
+        crate::metrics::TRACES_FETCHED.inc_by(traces.len() as u64);
+        Ok(traces)
+    }
 
-        for (struct_log, mux) in struct_logs.iter_mut().zip(mux_trace.into_iter()) {
-            merge_json_object(
-                struct_log,
-                json!({
-                    "result": {
-                        "prestate": mux["result"]["prestateTracer"],
-                        "callTrace": mux["result"]["callTracer"],
-                    }
-                }),
-            );
+    /// Calls `method` with the struct logger and decodes its response along
+    /// with the `mux_trace` of each transaction.
+    async fn block_struct_logs(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        mux_trace: Vec<GethMuxTrace>,
+    ) -> Result<Vec<GethExecTrace>, Error> {
+        #[cfg(feature = "rpc-streaming")]
+        if let Some(url) = &self.1 {
+            return stream::request(url, method, params, BlockTracesSeed::new(mux_trace)).await;
         }
-
-        let resp: ResultGethExecTraces =
-            serde_json::from_value(serde_json::Value::Array(struct_logs))
-                .map_err(|e| Error::JSONRpcError(e.into()))?;
-
-        Ok(resp.0.into_iter().map(|step| step.result).collect())
+        let struct_logs: Box<RawValue> = self
+            .0
+            .request(method, params)
+            .await
+            .map_err(|e| Error::JSONRpcError(e.into()))?;
+        GethExecTrace::from_block_struct_logger_json(struct_logs.get(), mux_trace)
+            .map_err(|e| Error::JSONRpcError(e.into()))
     }
 
     /// ...
@@ -252,6 +321,8 @@ impl<P: JsonRpcClient> GethClient<P> {
         );
         let resp =
             serde_json::from_value(struct_logs).map_err(|e| Error::JSONRpcError(e.into()))?;
+        #[cfg(feature = "metrics")]
+        crate::metrics::TRACES_FETCHED.inc();
         Ok(resp)
     }
 
@@ -263,17 +334,12 @@ impl<P: JsonRpcClient> GethClient<P> {
             ..Default::default()
         };
         let cfg = serialize(&cfg);
-        let mut struct_logs: serde_json::Value = self
-            .0
-            .request("debug_traceTransaction", [hash.clone(), cfg])
-            .await
-            .map_err(|e| Error::JSONRpcError(e.into()))?;
-        let mux_trace: serde_json::Value = self
+        let mux_trace: GethMuxTrace = self
             .0
             .request(
                 "debug_traceTransaction",
                 [
-                    hash,
+                    hash.clone(),
                     json!({
                         "tracer": "muxTracer",
                         "tracerConfig": {
@@ -285,16 +351,143 @@ impl<P: JsonRpcClient> GethClient<P> {
             )
             .await
             .map_err(|e| Error::JSONRpcError(e.into()))?;
-        merge_json_object(
-            &mut struct_logs,
-            json!({
-                "prestate": mux_trace["prestateTracer"],
-                "callTrace": mux_trace["callTracer"],
-            }),
-        );
-        let resp =
-            serde_json::from_value(struct_logs).map_err(|e| Error::JSONRpcError(e.into()))?;
-        Ok(resp)
+        let struct_logs: Box<RawValue> = self
+            .0
+            .request("debug_traceTransaction", [hash, cfg])
+            .await
+            .map_err(|e| Error::JSONRpcError(e.into()))?;
+        let trace = GethExecTrace::from_struct_logger_json(struct_logs.get(), mux_trace)
+            .map_err(|e| Error::JSONRpcError(e.into()))?;
+        #[cfg(feature = "metrics")]
+        crate::metrics::TRACES_FETCHED.inc();
+        Ok(trace)
+    }
+
+    /// Calls `debug_traceCall` via JSON-RPC, tracing the (unsigned) call
+    /// described by `tx` on top of the state after `block`, with
+    /// `state_overrides` applied beforehand.
+    ///
+    /// The overrides are passed to both the struct logger and the muxTracer,
+    /// so the returned prestate already reflects them. `block_overrides`
+    /// changes the header fields seen by the call, e.g. to run it in the
+    /// block following `block`.
+    pub async fn trace_call(
+        &self,
+        tx: &TransactionRequest,
+        block: BlockNumber,
+        state_overrides: &StateOverrides,
+        block_overrides: Option<&BlockOverrides>,
+    ) -> Result<GethExecTrace, Error> {
+        let tracer_config = json!({
+            "callTracer": {},
+            "prestateTracer": {}
+        });
+        let (mux_trace, struct_logs): (GethMuxTrace, _) = self
+            .trace_call_with(tx, block, state_overrides, block_overrides, tracer_config)
+            .await?;
+        let trace = GethExecTrace::from_struct_logger_json(struct_logs.get(), mux_trace)
+            .map_err(|e| Error::JSONRpcError(e.into()))?;
+        #[cfg(feature = "metrics")]
+        crate::metrics::TRACES_FETCHED.inc();
+        Ok(trace)
+    }
+
+    /// Calls `debug_traceCall` like [`trace_call`](Self::trace_call), with the
+    /// prestateTracer of the muxTracer in diff mode, so that the accounts
+    /// modified by the call are also returned, before and after it.
+    ///
+    /// The prestate of the returned trace only has the accounts modified by
+    /// the call, without their unchanged slots: the state only read by the
+    /// call is left out of it.
+    pub async fn trace_call_with_state_diff(
+        &self,
+        tx: &TransactionRequest,
+        block: BlockNumber,
+        state_overrides: &StateOverrides,
+        block_overrides: Option<&BlockOverrides>,
+    ) -> Result<(GethExecTrace, GethPrestateDiff), Error> {
+        let tracer_config = json!({
+            "callTracer": {},
+            "prestateTracer": {
+                "diffMode": true
+            }
+        });
+        let (mux_trace, struct_logs): (GethMuxDiffTrace, _) = self
+            .trace_call_with(tx, block, state_overrides, block_overrides, tracer_config)
+            .await?;
+        let GethMuxDiffTrace { diff, call_trace } = mux_trace;
+        let mux_trace = GethMuxTrace {
+            prestate: diff.pre.clone(),
+            call_trace,
+        };
+        let trace = GethExecTrace::from_struct_logger_json(struct_logs.get(), mux_trace)
+            .map_err(|e| Error::JSONRpcError(e.into()))?;
+        #[cfg(feature = "metrics")]
+        crate::metrics::TRACES_FETCHED.inc();
+        Ok((trace, diff))
+    }
+
+    /// Calls `debug_traceCall` via JSON-RPC with the prestateTracer, returning
+    /// the state accessed by the call described by `tx`, with the same
+    /// overrides as [`trace_call`](Self::trace_call).
+    pub async fn trace_call_prestate(
+        &self,
+        tx: &TransactionRequest,
+        block: BlockNumber,
+        state_overrides: &StateOverrides,
+        block_overrides: Option<&BlockOverrides>,
+    ) -> Result<HashMap<Address, GethPrestateTrace>, Error> {
+        let tx = serialize(tx);
+        let block = serialize(&block);
+        let cfg = json!({
+            "tracer": "prestateTracer",
+            "stateOverrides": state_overrides,
+            "blockOverrides": block_overrides.cloned().unwrap_or_default(),
+        });
+        self.0
+            .request("debug_traceCall", [tx, block, cfg])
+            .await
+            .map_err(|e| Error::JSONRpcError(e.into()))
+    }
+
+    /// Trace the call described by `tx` with the muxTracer configured with
+    /// `tracer_config`, and with the struct logger, both with the overrides
+    /// applied.
+    async fn trace_call_with<T: DeserializeOwned>(
+        &self,
+        tx: &TransactionRequest,
+        block: BlockNumber,
+        state_overrides: &StateOverrides,
+        block_overrides: Option<&BlockOverrides>,
+        tracer_config: serde_json::Value,
+    ) -> Result<(T, Box<RawValue>), Error> {
+        let tx = serialize(tx);
+        let block = serialize(&block);
+        let overrides = json!({
+            "stateOverrides": state_overrides,
+            "blockOverrides": block_overrides.cloned().unwrap_or_default(),
+        });
+        let mut cfg = serialize(&GethLoggerConfig {
+            timeout: Some("60s".to_string()),
+            ..Default::default()
+        });
+        merge_json_object(&mut cfg, overrides.clone());
+        let mut mux_cfg = json!({
+            "tracer": "muxTracer",
+            "tracerConfig": tracer_config,
+        });
+        merge_json_object(&mut mux_cfg, overrides);
+        let mux_trace: T = self
+            .0
+            .request("debug_traceCall", [tx.clone(), block.clone(), mux_cfg])
+            .await
+            .map_err(|e| Error::JSONRpcError(e.into()))?;
+        let struct_logs: Box<RawValue> = self
+            .0
+            .request("debug_traceCall", [tx, block, cfg])
+            .await
+            .map_err(|e| Error::JSONRpcError(e.into()))?;
+        Ok((mux_trace, struct_logs))
     }
 
     /// Call `debug_traceBlockByHash` use prestateTracer to get prestate
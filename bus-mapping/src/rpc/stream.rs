@@ -0,0 +1,158 @@
+//! JSON-RPC requests whose response is decoded while it is received, for the
+//! responses too large to be buffered whole, like the struct logs of a block.
+
+use crate::Error;
+use ethers_providers::ProviderError;
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde_json::json;
+use std::{
+    fmt,
+    io::{self, Read},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+use url::Url;
+
+/// Number of received chunks of the body buffered ahead of the decoder.
+const CHUNKS_IN_FLIGHT: usize = 16;
+
+/// Blocking reader over the chunks of a body received by another thread.
+struct ChunkReader<B> {
+    chunks: Receiver<B>,
+    chunk: Option<B>,
+    pos: usize,
+}
+
+impl<B: AsRef<[u8]>> Read for ChunkReader<B> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if let Some(chunk) = &self.chunk {
+                let rest = &chunk.as_ref()[self.pos..];
+                if !rest.is_empty() {
+                    let n = rest.len().min(buf.len());
+                    buf[..n].copy_from_slice(&rest[..n]);
+                    self.pos += n;
+                    return Ok(n);
+                }
+            }
+            match self.chunks.recv() {
+                Ok(chunk) => {
+                    self.chunk = Some(chunk);
+                    self.pos = 0;
+                }
+                // the sender is dropped at the end of the body
+                Err(_) => return Ok(0),
+            }
+        }
+    }
+}
+
+/// Error object of a JSON-RPC response.
+#[derive(serde::Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// Decodes a JSON-RPC response, with `S` for its `result`.
+struct ResponseSeed<S>(S);
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for ResponseSeed<S> {
+    type Value = Result<S::Value, RpcError>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, S: DeserializeSeed<'de>> Visitor<'de> for ResponseSeed<S> {
+    type Value = Result<S::Value, RpcError>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a JSON-RPC response")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut seed = Some(self.0);
+        let mut result = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "result" => {
+                    let seed = seed
+                        .take()
+                        .ok_or_else(|| de::Error::duplicate_field("result"))?;
+                    result = Some(Ok(map.next_value_seed(seed)?));
+                }
+                "error" => result = Some(Err(map.next_value::<RpcError>()?)),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        result.ok_or_else(|| de::Error::missing_field("result"))
+    }
+}
+
+/// Calls `method` at `url` and decodes the `result` of the response with
+/// `seed` as the body is received, so that the raw response is never held in
+/// memory. The decoding runs on its own thread.
+pub(super) async fn request<S, T>(
+    url: &Url,
+    method: &str,
+    params: serde_json::Value,
+    seed: S,
+) -> Result<T, Error>
+where
+    S: for<'de> DeserializeSeed<'de, Value = T> + Send + 'static,
+    T: Send + 'static,
+{
+    let mut response = reqwest::Client::new()
+        .post(url.clone())
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        }))
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| Error::JSONRpcError(e.into()))?;
+
+    let (sender, chunks) = mpsc::sync_channel(CHUNKS_IN_FLIGHT);
+    let decoder = thread::spawn(move || {
+        let reader = ChunkReader {
+            chunks,
+            chunk: None,
+            pos: 0,
+        };
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let response = ResponseSeed(seed).deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok::<_, serde_json::Error>(response)
+    });
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| Error::JSONRpcError(e.into()))?
+    {
+        // Blocks while the decoder is CHUNKS_IN_FLIGHT chunks behind, which
+        // bounds the memory held by the body. The decoder stops early on
+        // malformed responses.
+        if sender.send(chunk).is_err() {
+            break;
+        }
+    }
+    drop(sender);
+
+    let response = decoder
+        .join()
+        .map_err(|_| Error::InternalError("the response decoder panicked"))?
+        .map_err(|e| Error::JSONRpcError(e.into()))?;
+    response.map_err(|err| {
+        Error::JSONRpcError(ProviderError::CustomError(format!(
+            "{method} failed with code {}: {}",
+            err.code, err.message
+        )))
+    })
+}
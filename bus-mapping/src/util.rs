@@ -1,5 +1,9 @@
 //! ..
-pub use eth_types::{constants::read_env_var, KECCAK_CODE_HASH_EMPTY, POSEIDON_CODE_HASH_EMPTY};
+pub use eth_types::{
+    constants::read_env_var,
+    utils::{default_code_hasher, CodeHasher, KeccakCodeHasher, PoseidonCodeHasher},
+    KECCAK_CODE_HASH_EMPTY, POSEIDON_CODE_HASH_EMPTY,
+};
 use std::{convert::Infallible, str::FromStr, sync::LazyLock};
 
 /// env var for Geth trace sanity check level
@@ -24,7 +24,10 @@ mod evm_circ_benches {
     use rand::SeedableRng;
     use rand_xorshift::XorShiftRng;
     use std::env::var;
-    use zkevm_circuits::evm_circuit::{witness::block_convert, TestEvmCircuit};
+    use zkevm_circuits::{
+        evm_circuit::{witness::block_convert, TestEvmCircuit},
+        util::SubCircuit,
+    };
 
     #[cfg_attr(not(feature = "benches"), ignore)]
     #[cfg_attr(not(feature = "print-trace"), allow(unused_variables))] // FIXME: remove this after ark-std upgrade
@@ -58,6 +61,8 @@ mod evm_circ_benches {
         let block = block_convert(&builder.block, &builder.code_db).unwrap();
 
         let circuit = TestEvmCircuit::<Fr>::new(block);
+        let instance = circuit.instance();
+        let instance_refs: Vec<&[Fr]> = instance.iter().map(Vec::as_slice).collect();
         let mut rng = XorShiftRng::from_seed([
             0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
             0xbc, 0xe5,
@@ -90,7 +95,7 @@ mod evm_circ_benches {
             &general_params,
             &pk,
             &[circuit],
-            &[&[]],
+            &[&instance_refs],
             rng,
             &mut transcript,
         )
@@ -113,7 +118,7 @@ mod evm_circ_benches {
             &verifier_params,
             pk.get_vk(),
             strategy,
-            &[&[]],
+            &[&instance_refs],
             &mut verifier_transcript,
         )
         .expect("failed to verify bench circuit");
@@ -32,6 +32,10 @@ pub mod copy_circuit;
 #[cfg(feature = "benches")]
 pub mod exp_circuit;
 
+#[cfg(test)]
+#[cfg(feature = "benches")]
+pub mod memory_expansion;
+
 #[cfg(test)]
 #[cfg(feature = "benches")]
 pub mod constants;
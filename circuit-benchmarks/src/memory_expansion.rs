@@ -0,0 +1,87 @@
+//! Witness generation benchmark over a trace with large memory expansion
+
+#[cfg(test)]
+mod tests {
+    use ark_std::{end_timer, start_timer};
+    use bus_mapping::{circuit_input_builder::CircuitsParams, mock::BlockData};
+    use eth_types::{bytecode, geth_types::GethData, Bytecode, ToWord, Word};
+    use mock::{TestContext, MOCK_ACCOUNTS};
+    use std::env::var;
+
+    /// Memory size the callee expands to, 1MB.
+    const MEMORY_SIZE: usize = 1 << 20;
+
+    #[cfg_attr(not(feature = "benches"), ignore)]
+    #[cfg_attr(not(feature = "print-trace"), allow(unused_variables))] // FIXME: remove this after ark-std upgrade
+    #[test]
+    fn bench_memory_expansion_witness() {
+        // Unique string used by bench results module for parsing the result
+        const BENCHMARK_ID: &str = "Memory Expansion Witness";
+
+        let calls: usize = var("CALLS")
+            .unwrap_or_else(|_| "8".to_string())
+            .parse()
+            .expect("Cannot parse CALLS env var as usize");
+
+        // The callee touches the last word of a 1MB memory and returns it, so
+        // that its whole memory becomes the caller's `last_callee_memory`.
+        let callee_code = bytecode! {
+            PUSH1(0xff)
+            PUSH32(MEMORY_SIZE - 32)
+            MSTORE
+            PUSH1(0x20)
+            PUSH32(MEMORY_SIZE - 32)
+            RETURN
+        };
+        let mut caller_code = Bytecode::default();
+        for _ in 0..calls {
+            caller_code.append(&bytecode! {
+                PUSH1(0x20) // retLength
+                PUSH1(0x00) // retOffset
+                PUSH1(0x00) // argsLength
+                PUSH1(0x00) // argsOffset
+                PUSH1(0x00) // value
+                PUSH32(MOCK_ACCOUNTS[0].to_word()) // addr
+                GAS // gas
+                CALL
+                POP
+                PUSH1(0x20) // size
+                PUSH1(0x00) // offset
+                PUSH1(0x20) // destOffset
+                RETURNDATACOPY
+            });
+        }
+        caller_code.op_stop();
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(callee_code);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(caller_code);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 60));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[1].address)
+                    .from(accs[2].address)
+                    .gas(Word::from(30_000_000u64));
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder =
+            BlockData::new_from_geth_data_with_params(block.clone(), CircuitsParams::default())
+                .new_circuit_input_builder();
+
+        let witness_message = format!("{BENCHMARK_ID} with {calls} calls");
+        let start = start_timer!(|| witness_message);
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        end_timer!(start);
+    }
+}
@@ -107,6 +107,8 @@ mod tests {
         } else {
             build_circuit_from_mainnet_block().await
         };
+        let instance = circuit.instance();
+        let instance_refs: Vec<&[Fr]> = instance.iter().map(Vec::as_slice).collect();
 
         // Bench setup generation
         let setup_message = format!("{BENCHMARK_ID} {setup_prfx} with degree = {degree}");
@@ -135,7 +137,7 @@ mod tests {
             &general_params,
             &pk,
             &[circuit],
-            &[&[&[]]],
+            &[&instance_refs],
             rng,
             &mut transcript,
         )
@@ -158,7 +160,7 @@ mod tests {
             &verifier_params,
             pk.get_vk(),
             strategy,
-            &[&[&[]]],
+            &[&instance_refs],
             &mut verifier_transcript,
         )
         .expect("failed to verify bench circuit");
@@ -1,7 +1,12 @@
 //! EVM byte code generator
 
 use crate::{evm_types::OpcodeId, Bytes, ToWord, Word};
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 /// Error type for Bytecode related failures
 #[derive(Debug)]
@@ -32,10 +37,16 @@ pub struct Bytecode {
     pub code: Vec<BytecodeElement>,
     num_opcodes: usize,
     markers: HashMap<String, usize>,
+    /// Position of the JUMPDEST of each label
+    labels: HashMap<String, usize>,
+    /// Position of the PUSH2 data of each jump to a label, patched when the
+    /// label is defined and when the code is appended to another one.
+    label_refs: Vec<(usize, String)>,
 }
 
 impl From<Bytecode> for Bytes {
     fn from(code: Bytecode) -> Self {
+        code.check_labels();
         code.code
             .iter()
             .map(|e| e.value)
@@ -57,11 +68,14 @@ impl Bytecode {
                 .collect(),
             markers: HashMap::new(),
             num_opcodes: 0,
+            labels: HashMap::new(),
+            label_refs: Vec::new(),
         }
     }
 
     /// Get the code
     pub fn code(&self) -> Vec<u8> {
+        self.check_labels();
         self.code.iter().map(|b| b.value).collect()
     }
 
@@ -72,16 +86,28 @@ impl Bytecode {
 
     /// Get the generated code
     pub fn to_vec(&self) -> Vec<u8> {
+        self.check_labels();
         self.code.iter().map(|e| e.value).collect()
     }
 
     /// Append
     pub fn append(&mut self, other: &Bytecode) {
+        let offset = self.code.len();
         self.code.extend_from_slice(&other.code);
         for (key, val) in other.markers.iter() {
             self.insert_marker(key, self.num_opcodes + val);
         }
         self.num_opcodes += other.num_opcodes;
+        for (label, pos) in other.labels.iter() {
+            self.insert_label(label, offset + pos);
+        }
+        self.label_refs.extend(
+            other
+                .label_refs
+                .iter()
+                .map(|(pos, label)| (offset + pos, label.clone())),
+        );
+        self.patch_labels();
     }
 
     /// Write op
@@ -154,6 +180,11 @@ impl Bytecode {
         self
     }
 
+    /// Disassemble the code into its instructions.
+    pub fn disassemble(&self) -> Disassembly {
+        Disassembly::new(&self.to_vec())
+    }
+
     /// Generate the diassembly
     pub fn disasm(&self) -> String {
         let mut asm = String::new();
@@ -198,8 +229,157 @@ impl Bytecode {
     }
 }
 
+/// Counter of the labels generated by the control flow helpers, unique in the
+/// process so that the codes using them can be appended to each other.
+static NEXT_LABEL: AtomicUsize = AtomicUsize::new(0);
+
+/// Control flow, with jumps to labels instead of code offsets.
+///
+/// A label is the position of a JUMPDEST, and a jump to a label pushes its
+/// position with a PUSH2 before the JUMP or JUMPI. The labels can be used
+/// before being defined, and are kept when the code is appended to another
+/// one, so that the jumps are relocated. The codes appended to each other
+/// must not define the same labels, and all the labels must be defined when
+/// the code is generated.
+///
+/// ## Example
+/// ```rust
+/// use eth_types::bytecode;
+///
+/// // sum the numbers from 1 to 10, in a loop
+/// let code = bytecode! {
+///     PUSH1(0) // sum
+///     PUSH1(10) // counter
+///     .while_loop(&bytecode! { DUP1 }, &bytecode! {
+///         DUP1
+///         SWAP2
+///         ADD
+///         SWAP1
+///         PUSH1(1)
+///         SWAP1
+///         SUB
+///     })
+///     POP
+///     STOP
+/// };
+/// assert_eq!(code.to_vec()[..4], [0x60, 0, 0x60, 10]);
+/// ```
+impl Bytecode {
+    /// Label unique in the process, starting with `prefix`.
+    pub fn new_label(prefix: &str) -> String {
+        format!("{prefix}#{}", NEXT_LABEL.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Write a JUMPDEST at the position of `label`.
+    pub fn label(&mut self, label: &str) -> &mut Self {
+        self.insert_label(label, self.code.len());
+        self.write_op(OpcodeId::JUMPDEST);
+        self.patch_labels();
+        self
+    }
+
+    /// Push the position of `label`, e.g. as the return address of a
+    /// function.
+    pub fn push_label(&mut self, label: &str) -> &mut Self {
+        self.label_refs
+            .push((self.code.len() + 1, label.to_string()));
+        self.push(2, Word::zero());
+        self.patch_labels();
+        self
+    }
+
+    /// Jump to `label`.
+    pub fn jump_to(&mut self, label: &str) -> &mut Self {
+        self.push_label(label).write_op(OpcodeId::JUMP)
+    }
+
+    /// Jump to `label` if the value on top of the stack is not zero, popping
+    /// it.
+    pub fn jumpi_to(&mut self, label: &str) -> &mut Self {
+        self.push_label(label).write_op(OpcodeId::JUMPI)
+    }
+
+    /// Run `then` if the value on top of the stack is not zero, `otherwise`
+    /// if it's zero, popping it.
+    pub fn if_else(&mut self, then: &Bytecode, otherwise: &Bytecode) -> &mut Self {
+        let then_label = Self::new_label("then");
+        let end_label = Self::new_label("end_if");
+        self.jumpi_to(&then_label);
+        self.append(otherwise);
+        self.jump_to(&end_label).label(&then_label);
+        self.append(then);
+        self.label(&end_label)
+    }
+
+    /// Run `body` while `condition` pushes a value which is not zero, the
+    /// value being popped. `body` must leave the stack as it found it, for the
+    /// next evaluation of `condition`.
+    pub fn while_loop(&mut self, condition: &Bytecode, body: &Bytecode) -> &mut Self {
+        let start_label = Self::new_label("while");
+        let end_label = Self::new_label("end_while");
+        self.label(&start_label);
+        self.append(condition);
+        self.write_op(OpcodeId::ISZERO).jumpi_to(&end_label);
+        self.append(body);
+        self.jump_to(&start_label).label(&end_label)
+    }
+
+    /// Define the function `label` running `body`, which is skipped when the
+    /// code runs through it.
+    ///
+    /// The function is called with [`Bytecode::call_function`], the return
+    /// address being pushed on top of the arguments. `body` must leave the
+    /// return address on top of its results, where it's popped by the jump
+    /// back to the caller.
+    pub fn function(&mut self, label: &str, body: &Bytecode) -> &mut Self {
+        let end_label = Self::new_label("end_function");
+        self.jump_to(&end_label).label(label);
+        self.append(body);
+        self.write_op(OpcodeId::JUMP).label(&end_label)
+    }
+
+    /// Call the function `label` defined with [`Bytecode::function`], on the
+    /// arguments on top of the stack.
+    pub fn call_function(&mut self, label: &str) -> &mut Self {
+        let return_label = Self::new_label("return");
+        self.push_label(&return_label)
+            .jump_to(label)
+            .label(&return_label)
+    }
+
+    fn insert_label(&mut self, label: &str, pos: usize) {
+        assert!(
+            !self.labels.contains_key(label),
+            "label already defined: {label}"
+        );
+        self.labels.insert(label.to_string(), pos);
+    }
+
+    /// Write the position of the defined labels in the jumps to them.
+    fn patch_labels(&mut self) {
+        for (pos, label) in self.label_refs.iter() {
+            if let Some(target) = self.labels.get(label) {
+                let target = u16::try_from(*target)
+                    .unwrap_or_else(|_| panic!("label {label} out of PUSH2 range"));
+                let [high, low] = target.to_be_bytes();
+                self.code[*pos].value = high;
+                self.code[*pos + 1].value = low;
+            }
+        }
+    }
+
+    fn check_labels(&self) {
+        for (_, label) in self.label_refs.iter() {
+            assert!(
+                self.labels.contains_key(label),
+                "label not defined: {label}"
+            );
+        }
+    }
+}
+
 /// An ASM entry
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum OpcodeWithData {
     /// A `PUSH0` or non-push opcode
     Opcode(OpcodeId),
@@ -283,6 +463,166 @@ impl<'a> Iterator for BytecodeIterator<'a> {
     }
 }
 
+/// An instruction of a [`Disassembly`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    /// Position of the opcode in the code
+    pub pc: usize,
+    /// Opcode, with the pushed value
+    pub op: OpcodeWithData,
+}
+
+impl Instruction {
+    /// Position of the next instruction.
+    pub fn next_pc(&self) -> usize {
+        self.pc + 1 + self.op.opcode().data_len()
+    }
+}
+
+/// Instructions of a code, with the targets of its static jumps, i.e. the
+/// JUMP and JUMPI right after a PUSH.
+///
+/// It's displayed one instruction per line, with its pc and its constant gas
+/// cost, the static jumps being annotated with their target and the
+/// JUMPDESTs with the static jumps to them:
+/// ```text
+/// 0x0000  PUSH1(4)                    3
+/// 0x0002  JUMP                        8  -> 0x0004
+/// 0x0003  STOP                        0
+/// 0x0004  JUMPDEST                    1  <- 0x0002
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Disassembly {
+    /// Instructions, in the order of the code
+    pub instructions: Vec<Instruction>,
+    /// Target of each static jump
+    jump_targets: BTreeMap<usize, usize>,
+    /// Static jumps to each pc
+    jumps_to: BTreeMap<usize, Vec<usize>>,
+}
+
+impl Disassembly {
+    /// Disassemble `code`. The data of a PUSH truncated by the end of the
+    /// code is zero padded, as in the EVM.
+    pub fn new(code: &[u8]) -> Self {
+        let mut instructions: Vec<Instruction> = Vec::new();
+        let mut jump_targets = BTreeMap::new();
+        let mut jumps_to = BTreeMap::<usize, Vec<usize>>::new();
+        let mut pc = 0;
+        while pc < code.len() {
+            let opcode = OpcodeId::from(code[pc]);
+            let op = if opcode.is_push_with_data() {
+                let n = opcode.data_len();
+                let mut data = [0u8; 32];
+                let available = code.len().min(pc + 1 + n) - pc - 1;
+                data[..available].copy_from_slice(&code[pc + 1..pc + 1 + available]);
+                OpcodeWithData::PushWithData(n as u8, Word::from_big_endian(&data[..n]))
+            } else {
+                OpcodeWithData::Opcode(opcode)
+            };
+            if matches!(opcode, OpcodeId::JUMP | OpcodeId::JUMPI) {
+                if let Some(OpcodeWithData::PushWithData(_, target)) =
+                    instructions.last().map(|instruction| &instruction.op)
+                {
+                    if *target <= Word::from(usize::MAX) {
+                        jump_targets.insert(pc, target.as_usize());
+                        jumps_to.entry(target.as_usize()).or_default().push(pc);
+                    }
+                }
+            }
+            let instruction = Instruction { pc, op };
+            pc = instruction.next_pc();
+            instructions.push(instruction);
+        }
+        Self {
+            instructions,
+            jump_targets,
+            jumps_to,
+        }
+    }
+
+    /// Index of the instruction at `pc`, if an instruction starts there.
+    fn index(&self, pc: usize) -> Option<usize> {
+        self.instructions
+            .binary_search_by_key(&pc, |instruction| instruction.pc)
+            .ok()
+    }
+
+    /// Instruction at `pc`, if an instruction starts there.
+    pub fn at(&self, pc: usize) -> Option<&Instruction> {
+        self.index(pc).map(|index| &self.instructions[index])
+    }
+
+    /// Return true if `pc` is a valid jump destination, i.e. a JUMPDEST which
+    /// isn't push data.
+    pub fn is_jumpdest(&self, pc: usize) -> bool {
+        self.at(pc)
+            .is_some_and(|instruction| instruction.op.opcode() == OpcodeId::JUMPDEST)
+    }
+
+    /// Target of the static jump at `pc`.
+    pub fn jump_target(&self, pc: usize) -> Option<usize> {
+        self.jump_targets.get(&pc).copied()
+    }
+
+    /// Static jumps to `pc`.
+    pub fn jumps_to(&self, pc: usize) -> &[usize] {
+        self.jumps_to
+            .get(&pc)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Instructions around `pc`, at most `lines` before and after it, the one
+    /// at `pc` being marked with a `>`.
+    pub fn context(&self, pc: usize, lines: usize) -> String {
+        let index = self
+            .instructions
+            .partition_point(|instruction| instruction.pc < pc);
+        let start = index.saturating_sub(lines);
+        let end = (index + lines + 1).min(self.instructions.len());
+        let mut context = String::new();
+        for instruction in &self.instructions[start..end] {
+            let marker = if instruction.pc == pc { ">" } else { " " };
+            context.push_str(&format!("{marker} {}\n", self.line(instruction)));
+        }
+        context
+    }
+
+    fn line(&self, instruction: &Instruction) -> String {
+        let opcode = instruction.op.opcode();
+        let mut line = format!(
+            "{:#06x}  {:<24}{:>5}",
+            instruction.pc,
+            instruction.op.to_string(),
+            opcode.constant_gas_cost().0
+        );
+        if let Some(target) = self.jump_target(instruction.pc) {
+            let valid = if self.is_jumpdest(target) {
+                ""
+            } else {
+                " (not a JUMPDEST)"
+            };
+            line.push_str(&format!("  -> {target:#06x}{valid}"));
+        }
+        let jumps = self.jumps_to(instruction.pc);
+        if !jumps.is_empty() && opcode == OpcodeId::JUMPDEST {
+            let jumps: Vec<_> = jumps.iter().map(|jump| format!("{jump:#06x}")).collect();
+            line.push_str(&format!("  <- {}", jumps.join(", ")));
+        }
+        line
+    }
+}
+
+impl fmt::Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for instruction in &self.instructions {
+            writeln!(f, "{}", self.line(instruction))?;
+        }
+        Ok(())
+    }
+}
+
 impl From<Vec<u8>> for Bytecode {
     fn from(input: Vec<u8>) -> Self {
         let mut code = Bytecode::default();
@@ -576,6 +916,133 @@ mod tests {
         assert_eq!(Bytecode::from(code.to_vec()), code);
     }
 
+    #[test]
+    fn test_labels() {
+        let mut code = bytecode! {
+            .jump_to("end")
+            .label("start")
+            STOP
+        };
+        let mut tail = bytecode! {
+            .label("end")
+            .jump_to("start")
+        };
+        code.append(&tail);
+        assert_eq!(
+            code.to_vec(),
+            [
+                vec![0x61, 0x00, 0x06, 0x56],       // PUSH2(6) JUMP
+                vec![0x5b, 0x00],                   // JUMPDEST STOP
+                vec![0x5b, 0x61, 0x00, 0x04, 0x56], // JUMPDEST PUSH2(4) JUMP
+            ]
+            .concat()
+        );
+
+        // the jumps of the appended code are relocated
+        tail = bytecode! { .jump_to("here") .label("here") };
+        code.append(&tail);
+        assert_eq!(code.to_vec()[11..], [0x61, 0x00, 0x0f, 0x56, 0x5b]);
+    }
+
+    #[test]
+    fn test_if_else() {
+        let code = bytecode! {
+            PUSH1(1)
+            .if_else(&bytecode! { PUSH1(2) }, &bytecode! { PUSH1(3) })
+            STOP
+        };
+        assert_eq!(
+            code.to_vec(),
+            [
+                vec![0x60, 0x01, 0x61, 0x00, 0x0c, 0x57], // PUSH1(1) PUSH2(12) JUMPI
+                vec![0x60, 0x03, 0x61, 0x00, 0x0f, 0x56], // PUSH1(3) PUSH2(15) JUMP
+                vec![0x5b, 0x60, 0x02],                   // JUMPDEST PUSH1(2)
+                vec![0x5b, 0x00],                         // JUMPDEST STOP
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_function() {
+        let code = bytecode! {
+            .function("double", &bytecode! { SWAP1 DUP1 ADD SWAP1 })
+            PUSH1(21)
+            .call_function("double")
+            STOP
+        };
+        let bytes = code.to_vec();
+        // the function is skipped, to the call
+        assert_eq!(bytes[..4], [0x61, 0x00, 0x0a, 0x56]);
+        assert_eq!(bytes[10], OpcodeId::JUMPDEST.as_u8());
+        // which jumps to the function, with the return address
+        assert_eq!(
+            bytes[11..],
+            [0x60, 21, 0x61, 0x00, 0x14, 0x61, 0x00, 0x04, 0x56, 0x5b, 0x00]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "label not defined: undefined")]
+    fn test_undefined_label() {
+        bytecode! { .jump_to("undefined") }.to_vec();
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let code = bytecode! {
+            PUSH1(1)
+            PUSH1(8)
+            JUMPI
+            PUSH1(3)
+            JUMP
+            JUMPDEST
+            PUSH2(0x0102)
+        };
+        let disassembly = code.disassemble();
+        assert_eq!(disassembly.instructions.len(), 7);
+        assert_eq!(disassembly.jump_target(4), Some(8));
+        assert_eq!(disassembly.jump_target(7), Some(3));
+        assert_eq!(disassembly.jumps_to(8), [4]);
+        assert!(disassembly.is_jumpdest(8));
+        // the target of the JUMP is the data of a PUSH
+        assert!(!disassembly.is_jumpdest(3));
+        assert_eq!(
+            disassembly.to_string(),
+            [
+                "0x0000  PUSH1(1)                    3",
+                "0x0002  PUSH1(8)                    3",
+                "0x0004  JUMPI                      10  -> 0x0008",
+                "0x0005  PUSH1(3)                    3",
+                "0x0007  JUMP                        8  -> 0x0003 (not a JUMPDEST)",
+                "0x0008  JUMPDEST                    1  <- 0x0004",
+                "0x0009  PUSH2(258)                  3",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            disassembly.context(8, 1),
+            [
+                "  0x0007  JUMP                        8  -> 0x0003 (not a JUMPDEST)",
+                "> 0x0008  JUMPDEST                    1  <- 0x0004",
+                "  0x0009  PUSH2(258)                  3",
+                "",
+            ]
+            .join("\n")
+        );
+
+        // the data of a truncated PUSH is zero padded
+        let disassembly = Disassembly::new(&[OpcodeId::PUSH2.as_u8(), 0x01]);
+        assert_eq!(
+            disassembly.instructions,
+            [Instruction {
+                pc: 0,
+                op: OpcodeWithData::PushWithData(2, 0x0100.into()),
+            }]
+        );
+    }
+
     #[test]
     fn test_asm_disasm() {
         let code = bytecode! {
@@ -0,0 +1,725 @@
+//! Compact binary encoding of the [`GethExecTrace`]s and [`GethData`], for the
+//! replay files and the offline trace dumps.
+//!
+//! The struct logs, which make most of a trace, are encoded field by field as
+//! varints, and the stack, memory and storage of each step as their difference
+//! with the previous step. The other parts of a trace or a block are small,
+//! and are kept as JSON.
+//!
+//! The stack, memory and storage are encoded when the `enable-*` features
+//! capturing them are enabled, and dropped when decoded without the feature.
+
+use crate::{
+    evm_types::{Gas, GasCost, Memory, OpcodeId, ProgramCounter, Storage},
+    geth_types::{Account, GethData},
+    l2_types::AccountProofWrapper,
+    Address, Bytes, Error, GethCallTrace, GethExecStep, GethExecTrace, GethPrestateTrace,
+    ToBigEndian, Word,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Start of the compact encodings, which can't be the start of a JSON text.
+const MAGIC: &[u8; 4] = b"\0GTC";
+/// Version of the encoding, bumped on incompatible changes.
+const VERSION: u8 = 1;
+
+/// What a compact encoding holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum Kind {
+    Trace = 0,
+    Traces = 1,
+    GethData = 2,
+}
+
+// Parts of the steps captured by the tracer.
+const STACK: u8 = 1;
+const MEMORY: u8 = 2;
+const STORAGE: u8 = 4;
+
+// Flags of a step.
+const HAS_ERROR: u8 = 1;
+const HAS_RETURN_DATA: u8 = 2;
+const SAME_STORAGE: u8 = 4;
+
+/// Size of the memory chunks, the memory being encoded as the chunks which
+/// changed since the previous step.
+const MEMORY_CHUNK_SIZE: usize = 32;
+
+fn decoding_error(msg: impl Into<String>) -> Error {
+    Error::CompactDecoding(msg.into())
+}
+
+/// Returns true if `data` is in the compact encoding, and not JSON.
+pub fn is_compact(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+#[derive(Default)]
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn new(kind: Kind) -> Self {
+        let mut encoder = Self(MAGIC.to_vec());
+        encoder.u8(VERSION);
+        encoder.u8(kind as u8);
+        encoder
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    /// LEB128 encoding
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.varint(value as u64);
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.usize(bytes.len());
+        self.0.extend_from_slice(bytes);
+    }
+
+    /// Big endian bytes without the leading zeros, prefixed by their number
+    fn word(&mut self, word: &Word) {
+        let bytes = word.to_be_bytes();
+        let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+        self.u8((32 - zeros) as u8);
+        self.0.extend_from_slice(&bytes[zeros..]);
+    }
+
+    fn json<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let json = serde_json::to_vec(value).map_err(Error::SerdeError)?;
+        self.bytes(&json);
+        Ok(())
+    }
+}
+
+struct Decoder<'a>(&'a [u8]);
+
+impl<'a> Decoder<'a> {
+    fn new(data: &'a [u8], kind: Kind) -> Result<Self, Error> {
+        let mut decoder = Self(
+            data.strip_prefix(MAGIC)
+                .ok_or_else(|| decoding_error("not a compact encoding"))?,
+        );
+        let version = decoder.u8()?;
+        if version != VERSION {
+            return Err(decoding_error(format!(
+                "unsupported version {version}, expected {VERSION}"
+            )));
+        }
+        let found = decoder.u8()?;
+        if found != kind as u8 {
+            return Err(decoding_error(format!(
+                "encoding of kind {found}, expected {kind:?}"
+            )));
+        }
+        Ok(decoder)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if len > self.0.len() {
+            return Err(decoding_error("unexpected end of data"));
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, Error> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(decoding_error("varint overflow"))
+    }
+
+    fn usize(&mut self) -> Result<usize, Error> {
+        usize::try_from(self.varint()?).map_err(|_| decoding_error("length overflow"))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], Error> {
+        let len = self.usize()?;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| decoding_error("invalid utf-8"))
+    }
+
+    fn word(&mut self) -> Result<Word, Error> {
+        let len = self.u8()?;
+        if len > 32 {
+            return Err(decoding_error(format!("word of {len} bytes")));
+        }
+        Ok(Word::from_big_endian(self.take(len.into())?))
+    }
+
+    fn json<T: DeserializeOwned>(&mut self) -> Result<T, Error> {
+        serde_json::from_slice(self.bytes()?).map_err(Error::SerdeError)
+    }
+
+    fn end(&self) -> Result<(), Error> {
+        if !self.0.is_empty() {
+            return Err(decoding_error(format!("{} trailing bytes", self.0.len())));
+        }
+        Ok(())
+    }
+}
+
+/// Stack, memory and storage of the previous step, against which the ones of
+/// the next step are encoded.
+#[derive(Default)]
+struct PrevStep {
+    stack: Vec<Word>,
+    memory: Vec<u8>,
+    memory_snapshot: Memory,
+    storage: Storage,
+}
+
+impl PrevStep {
+    /// Encode the words above the ones shared with the previous stack.
+    #[cfg_attr(not(feature = "enable-stack"), allow(dead_code))]
+    fn encode_stack(&mut self, encoder: &mut Encoder, stack: &[Word]) {
+        let shared = stack
+            .iter()
+            .zip(self.stack.iter())
+            .take_while(|(word, prev)| word == prev)
+            .count();
+        encoder.usize(shared);
+        encoder.usize(stack.len() - shared);
+        for word in &stack[shared..] {
+            encoder.word(word);
+        }
+        self.stack = stack.to_vec();
+    }
+
+    fn decode_stack(&mut self, decoder: &mut Decoder) -> Result<Vec<Word>, Error> {
+        let shared = decoder.usize()?;
+        if shared > self.stack.len() {
+            return Err(decoding_error("stack longer than the previous one"));
+        }
+        self.stack.truncate(shared);
+        for _ in 0..decoder.usize()? {
+            self.stack.push(decoder.word()?);
+        }
+        Ok(self.stack.clone())
+    }
+
+    /// Encode the memory chunks which differ from the previous memory, zero
+    /// extended.
+    #[cfg_attr(not(feature = "enable-memory"), allow(dead_code))]
+    fn encode_memory(&mut self, encoder: &mut Encoder, memory: Vec<u8>) {
+        let changed: Vec<_> = memory
+            .chunks(MEMORY_CHUNK_SIZE)
+            .enumerate()
+            .filter(|(index, chunk)| {
+                let prev = self
+                    .memory
+                    .get(index * MEMORY_CHUNK_SIZE..)
+                    .unwrap_or_default();
+                chunk
+                    .iter()
+                    .enumerate()
+                    .any(|(i, byte)| *byte != prev.get(i).copied().unwrap_or_default())
+            })
+            .collect();
+        encoder.usize(memory.len());
+        encoder.usize(changed.len());
+        let mut prev_index = 0;
+        for (index, chunk) in changed {
+            encoder.usize(index - prev_index);
+            encoder.0.extend_from_slice(chunk);
+            prev_index = index;
+        }
+        self.memory = memory;
+    }
+
+    fn decode_memory(&mut self, decoder: &mut Decoder) -> Result<Memory, Error> {
+        let len = decoder.usize()?;
+        self.memory.resize(len, 0);
+        let mut index = 0;
+        for _ in 0..decoder.usize()? {
+            index += decoder.usize()?;
+            let start = index
+                .checked_mul(MEMORY_CHUNK_SIZE)
+                .filter(|start| *start < len)
+                .ok_or_else(|| decoding_error("memory chunk out of bounds"))?;
+            let end = (start + MEMORY_CHUNK_SIZE).min(len);
+            self.memory[start..end].copy_from_slice(decoder.take(end - start)?);
+        }
+        let mut memory = Memory::from(self.memory.clone());
+        memory.share_pages_with(&self.memory_snapshot);
+        self.memory_snapshot = memory.clone();
+        Ok(memory)
+    }
+
+    #[cfg_attr(not(feature = "enable-storage"), allow(dead_code))]
+    fn encode_storage(&mut self, encoder: &mut Encoder, storage: &Storage) {
+        let mut slots: Vec<_> = storage.0.iter().collect();
+        slots.sort();
+        encoder.usize(slots.len());
+        for (key, value) in slots {
+            encoder.word(key);
+            encoder.word(value);
+        }
+        self.storage = storage.clone();
+    }
+
+    fn decode_storage(&mut self, decoder: &mut Decoder, same: bool) -> Result<Storage, Error> {
+        if !same {
+            let mut slots = HashMap::new();
+            for _ in 0..decoder.usize()? {
+                slots.insert(decoder.word()?, decoder.word()?);
+            }
+            self.storage = Storage::from(slots);
+        }
+        Ok(self.storage.clone())
+    }
+}
+
+fn encode_steps(encoder: &mut Encoder, steps: &[GethExecStep]) {
+    let captured = if cfg!(feature = "enable-stack") {
+        STACK
+    } else {
+        0
+    } | if cfg!(feature = "enable-memory") {
+        MEMORY
+    } else {
+        0
+    } | if cfg!(feature = "enable-storage") {
+        STORAGE
+    } else {
+        0
+    };
+    encoder.u8(captured);
+    encoder.usize(steps.len());
+    #[allow(unused_mut, unused_variables)]
+    let mut prev = PrevStep::default();
+    for step in steps {
+        encoder.usize(step.pc.0);
+        encoder.u8(step.op.as_u8());
+        encoder.varint(step.gas.0);
+        encoder.varint(step.gas_cost.0);
+        encoder.varint(step.refund.0);
+        encoder.varint(step.depth.into());
+
+        #[allow(unused_mut)]
+        let mut flags = 0;
+        if step.error.is_some() {
+            flags |= HAS_ERROR;
+        }
+        if step.return_data.is_some() {
+            flags |= HAS_RETURN_DATA;
+        }
+        #[cfg(feature = "enable-storage")]
+        if step.storage == prev.storage {
+            flags |= SAME_STORAGE;
+        }
+        encoder.u8(flags);
+
+        if let Some(error) = &step.error {
+            encoder.bytes(error.to_string().as_bytes());
+        }
+        if let Some(return_data) = &step.return_data {
+            encoder.bytes(return_data);
+        }
+        #[cfg(feature = "enable-stack")]
+        prev.encode_stack(encoder, &step.stack.0);
+        #[cfg(feature = "enable-memory")]
+        prev.encode_memory(encoder, step.memory.to_vec());
+        #[cfg(feature = "enable-storage")]
+        if flags & SAME_STORAGE == 0 {
+            prev.encode_storage(encoder, &step.storage);
+        }
+    }
+}
+
+fn decode_steps(decoder: &mut Decoder) -> Result<Vec<GethExecStep>, Error> {
+    let captured = decoder.u8()?;
+    let len = decoder.usize()?;
+    let mut prev = PrevStep::default();
+    // the length isn't trusted before the steps are decoded
+    let mut steps = Vec::with_capacity(len.min(decoder.0.len()));
+    for _ in 0..len {
+        let pc = ProgramCounter(decoder.usize()?);
+        let op = OpcodeId::from(decoder.u8()?);
+        let gas = Gas(decoder.varint()?);
+        let gas_cost = GasCost(decoder.varint()?);
+        let refund = Gas(decoder.varint()?);
+        let depth =
+            u16::try_from(decoder.varint()?).map_err(|_| decoding_error("depth overflow"))?;
+        let flags = decoder.u8()?;
+        let error = if flags & HAS_ERROR != 0 {
+            let error = decoder.string()?;
+            Some(
+                error
+                    .parse()
+                    .map_err(|_| decoding_error(format!("unknown error {error:?}")))?,
+            )
+        } else {
+            None
+        };
+        let return_data = if flags & HAS_RETURN_DATA != 0 {
+            Some(Bytes::from(decoder.bytes()?.to_vec()))
+        } else {
+            None
+        };
+
+        // decoded even when dropped, to move to the next step
+        #[cfg_attr(not(feature = "enable-stack"), allow(unused_variables))]
+        let stack = if captured & STACK != 0 {
+            prev.decode_stack(decoder)?
+        } else {
+            Vec::new()
+        };
+        #[cfg_attr(not(feature = "enable-memory"), allow(unused_variables))]
+        let memory = if captured & MEMORY != 0 {
+            prev.decode_memory(decoder)?
+        } else {
+            Memory::default()
+        };
+        #[cfg_attr(not(feature = "enable-storage"), allow(unused_variables))]
+        let storage = if captured & STORAGE != 0 {
+            prev.decode_storage(decoder, flags & SAME_STORAGE != 0)?
+        } else {
+            Storage::default()
+        };
+
+        steps.push(GethExecStep {
+            pc,
+            op,
+            gas,
+            gas_cost,
+            refund,
+            depth,
+            error,
+            return_data,
+            #[cfg(feature = "enable-stack")]
+            stack: crate::evm_types::Stack(stack),
+            #[cfg(feature = "enable-memory")]
+            memory,
+            #[cfg(feature = "enable-storage")]
+            storage,
+        });
+    }
+    Ok(steps)
+}
+
+/// Parts of a trace kept as JSON.
+#[derive(Serialize)]
+struct TraceParts<'a> {
+    account_after: &'a [AccountProofWrapper],
+    prestate: &'a HashMap<Address, GethPrestateTrace>,
+    call_trace: &'a GethCallTrace,
+}
+
+#[derive(Deserialize)]
+struct OwnedTraceParts {
+    account_after: Vec<AccountProofWrapper>,
+    prestate: HashMap<Address, GethPrestateTrace>,
+    call_trace: GethCallTrace,
+}
+
+fn encode_trace(encoder: &mut Encoder, trace: &GethExecTrace) -> Result<(), Error> {
+    encoder.varint(trace.l1_fee);
+    encoder.varint(trace.gas.0);
+    encoder.u8(trace.failed.into());
+    encoder.bytes(trace.return_value.as_bytes());
+    encoder.json(&TraceParts {
+        account_after: &trace.account_after,
+        prestate: &trace.prestate,
+        call_trace: &trace.call_trace,
+    })?;
+    encode_steps(encoder, &trace.struct_logs);
+    Ok(())
+}
+
+fn decode_trace(decoder: &mut Decoder) -> Result<GethExecTrace, Error> {
+    let l1_fee = decoder.varint()?;
+    let gas = Gas(decoder.varint()?);
+    let failed = decoder.u8()? != 0;
+    let return_value = decoder.string()?;
+    let parts: OwnedTraceParts = decoder.json()?;
+    let struct_logs = decode_steps(decoder)?;
+    Ok(GethExecTrace {
+        l1_fee,
+        gas,
+        failed,
+        return_value,
+        struct_logs,
+        account_after: parts.account_after,
+        prestate: parts.prestate,
+        call_trace: parts.call_trace,
+    })
+}
+
+fn encode_account(encoder: &mut Encoder, account: &Account) {
+    encoder.0.extend_from_slice(account.address.as_bytes());
+    encoder.word(&account.nonce);
+    encoder.word(&account.balance);
+    encoder.bytes(&account.code);
+    let mut slots: Vec<_> = account.storage.iter().collect();
+    slots.sort();
+    encoder.usize(slots.len());
+    for (key, value) in slots {
+        encoder.word(key);
+        encoder.word(value);
+    }
+}
+
+fn decode_account(decoder: &mut Decoder) -> Result<Account, Error> {
+    let address = Address::from_slice(decoder.take(Address::len_bytes())?);
+    let nonce = decoder.word()?;
+    let balance = decoder.word()?;
+    let code = Bytes::from(decoder.bytes()?.to_vec());
+    let mut storage = HashMap::new();
+    for _ in 0..decoder.usize()? {
+        storage.insert(decoder.word()?, decoder.word()?);
+    }
+    Ok(Account {
+        address,
+        nonce,
+        balance,
+        code,
+        storage,
+    })
+}
+
+/// Encode a list of traces, e.g. the ones of a block.
+pub fn encode_traces(traces: &[GethExecTrace]) -> Result<Vec<u8>, Error> {
+    let mut encoder = Encoder::new(Kind::Traces);
+    encoder.usize(traces.len());
+    for trace in traces {
+        encode_trace(&mut encoder, trace)?;
+    }
+    Ok(encoder.0)
+}
+
+/// Decode a list of traces encoded with [`encode_traces`].
+pub fn decode_traces(data: &[u8]) -> Result<Vec<GethExecTrace>, Error> {
+    let mut decoder = Decoder::new(data, Kind::Traces)?;
+    let traces = (0..decoder.usize()?)
+        .map(|_| decode_trace(&mut decoder))
+        .collect::<Result<_, _>>()?;
+    decoder.end()?;
+    Ok(traces)
+}
+
+impl GethExecTrace {
+    /// Encode the trace in the [compact](crate::compact) binary encoding.
+    pub fn to_compact(&self) -> Result<Vec<u8>, Error> {
+        let mut encoder = Encoder::new(Kind::Trace);
+        encode_trace(&mut encoder, self)?;
+        Ok(encoder.0)
+    }
+
+    /// Decode a trace encoded with [`GethExecTrace::to_compact`].
+    pub fn from_compact(data: &[u8]) -> Result<Self, Error> {
+        let mut decoder = Decoder::new(data, Kind::Trace)?;
+        let trace = decode_trace(&mut decoder)?;
+        decoder.end()?;
+        Ok(trace)
+    }
+}
+
+impl GethData {
+    /// Encode the block, its traces and accounts in the
+    /// [compact](crate::compact) binary encoding.
+    pub fn to_compact(&self) -> Result<Vec<u8>, Error> {
+        let mut encoder = Encoder::new(Kind::GethData);
+        encoder.varint(self.chain_id);
+        encoder.usize(self.history_hashes.len());
+        for hash in &self.history_hashes {
+            encoder.word(hash);
+        }
+        encoder.json(&self.eth_block)?;
+        encoder.usize(self.geth_traces.len());
+        for trace in &self.geth_traces {
+            encode_trace(&mut encoder, trace)?;
+        }
+        encoder.usize(self.accounts.len());
+        for account in &self.accounts {
+            encode_account(&mut encoder, account);
+        }
+        #[cfg(feature = "scroll")]
+        {
+            encoder.u8(1);
+            encoder.json(&self.block_trace)?;
+        }
+        #[cfg(not(feature = "scroll"))]
+        encoder.u8(0);
+        Ok(encoder.0)
+    }
+
+    /// Decode the data encoded with [`GethData::to_compact`].
+    pub fn from_compact(data: &[u8]) -> Result<Self, Error> {
+        let mut decoder = Decoder::new(data, Kind::GethData)?;
+        let chain_id = decoder.varint()?;
+        let history_hashes = (0..decoder.usize()?)
+            .map(|_| decoder.word())
+            .collect::<Result<_, _>>()?;
+        let eth_block = decoder.json()?;
+        let geth_traces = (0..decoder.usize()?)
+            .map(|_| decode_trace(&mut decoder))
+            .collect::<Result<_, _>>()?;
+        let accounts = (0..decoder.usize()?)
+            .map(|_| decode_account(&mut decoder))
+            .collect::<Result<_, _>>()?;
+        // the block trace of the scroll feature, dropped without it
+        let has_block_trace = decoder.u8()? != 0;
+        #[cfg_attr(not(feature = "scroll"), allow(unused_variables))]
+        let block_trace = if has_block_trace {
+            Some(decoder.bytes()?)
+        } else {
+            None
+        };
+        decoder.end()?;
+        Ok(Self {
+            chain_id,
+            history_hashes,
+            eth_block,
+            geth_traces,
+            accounts,
+            #[cfg(feature = "scroll")]
+            block_trace: block_trace
+                .map(serde_json::from_slice)
+                .transpose()
+                .map_err(Error::SerdeError)?
+                .unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trace() -> GethExecTrace {
+        let step = |pc: u64, op: &str, stack: &str, memory: &str| {
+            format!(
+                r#"{{
+                    "pc": {pc},
+                    "op": "{op}",
+                    "gas": {},
+                    "gasCost": 3,
+                    "refund": 0,
+                    "depth": 1,
+                    "stack": [{stack}],
+                    "storage": {{
+                        "0000000000000000000000000000000000000000000000000000000000000000": "000000000000000000000000000000000000000000000000000000000000006f"
+                    }},
+                    "memory": [{memory}]
+                }}"#,
+                1000 - pc
+            )
+        };
+        let word = |byte: &str| format!(r#""{}{byte}""#, "0".repeat(62));
+        let trace_json = format!(
+            r#"{{
+                "gas": 26809,
+                "failed": true,
+                "returnValue": "",
+                "structLogs": [{}, {}, {}, {}],
+                "prestate": {{
+                    "0x000000000000000000000000000000000cafe001": {{
+                        "balance": "0x10",
+                        "nonce": 1,
+                        "code": "0x6001",
+                        "storage": null
+                    }}
+                }},
+                "callTrace": {{
+                    "calls": [],
+                    "error": "execution reverted",
+                    "from": "0x000000000000000000000000000000000cafe001",
+                    "to": null,
+                    "gasUsed": "0x0",
+                    "type": "CALL",
+                    "output": "0x00"
+                }}
+            }}"#,
+            step(0, "PUSH1", "", ""),
+            step(2, "MSTORE", r#""0x2a", "0x0""#, ""),
+            step(3, "PUSH1", "", &[word("2a"), word("00")].join(",")),
+            step(5, "REVERT", r#""0x1""#, &[word("2a"), word("ff")].join(",")),
+        );
+        let mut trace: GethExecTrace = serde_json::from_str(&trace_json).unwrap();
+        trace.struct_logs[3].error = Some(crate::GethExecError::ExecutionReverted);
+        trace.struct_logs[3].return_data = Some(Bytes::from([0x12, 0x34]));
+        trace
+    }
+
+    #[test]
+    fn trace_roundtrip() {
+        let trace = trace();
+        let encoded = trace.to_compact().unwrap();
+        assert!(is_compact(&encoded));
+        assert_eq!(GethExecTrace::from_compact(&encoded).unwrap(), trace);
+
+        let traces = vec![trace.clone(), trace];
+        assert_eq!(
+            decode_traces(&encode_traces(&traces).unwrap()).unwrap(),
+            traces
+        );
+
+        // truncated or another kind
+        assert!(GethExecTrace::from_compact(&encoded[..encoded.len() - 1]).is_err());
+        assert!(decode_traces(&encoded).is_err());
+        assert!(!is_compact(b"{}"));
+    }
+
+    #[test]
+    fn geth_data_roundtrip() {
+        let geth_data = GethData {
+            chain_id: 1338,
+            history_hashes: vec![Word::from(1), Word::MAX],
+            geth_traces: vec![trace()],
+            accounts: vec![Account {
+                address: Address::from_low_u64_be(0xcafe),
+                nonce: Word::one(),
+                balance: Word::from(10).pow(18.into()),
+                code: Bytes::from([0x60, 0x01]),
+                storage: HashMap::from([(Word::zero(), Word::from(0x6f))]),
+            }],
+            ..Default::default()
+        };
+        let decoded = GethData::from_compact(&geth_data.to_compact().unwrap()).unwrap();
+        assert_eq!(decoded.chain_id, geth_data.chain_id);
+        assert_eq!(decoded.history_hashes, geth_data.history_hashes);
+        assert_eq!(decoded.eth_block, geth_data.eth_block);
+        assert_eq!(decoded.geth_traces, geth_data.geth_traces);
+        assert_eq!(decoded.accounts, geth_data.accounts);
+    }
+
+    #[test]
+    fn smaller_than_json() {
+        let trace = trace();
+        let json = serde_json::to_vec(&trace).unwrap();
+        let compact = trace.to_compact().unwrap();
+        assert!(
+            compact.len() * 2 < json.len(),
+            "{} >= {}",
+            compact.len(),
+            json.len()
+        );
+    }
+}
@@ -37,6 +37,10 @@ pub enum Error {
     WordToMemAddr,
     /// Signature parsing error.
     Signature,
+    /// Error while parsing a number or an address from a string.
+    ValueParsing(String),
+    /// Error while decoding a compact binary encoding.
+    CompactDecoding(String),
 }
 
 impl Display for Error {
@@ -73,6 +73,73 @@ pub const MAX_EXPANDED_MEMORY_ADDRESS: u64 = 0x1FFFFFFFE0;
 pub const MAX_REFUND_QUOTIENT_OF_GAS_USED: usize = 5;
 /// Gas stipend when CALL or CALLCODE is attached with value.
 pub const GAS_STIPEND_CALL_WITH_VALUE: u64 = 2300;
+/// Maximum depth of the call stack in Ethereum.
+pub const MAX_CALL_DEPTH: usize = 1024;
+/// Maximum number of words in the stack of a call in Ethereum.
+pub const STACK_LIMIT: usize = 1024;
+
+/// Limits of the EVM that some chains, or test harnesses, set to other values
+/// than Ethereum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainSpec {
+    /// Maximum depth of the call stack. A CALL or CREATE made by a call at
+    /// this depth fails with the depth error.
+    pub max_call_depth: usize,
+    /// Maximum number of words in the stack of a call.
+    pub stack_limit: usize,
+    /// Number of the first block past the Merge (EIP-3675), from which
+    /// DIFFICULTY returns prevRandao instead of the difficulty (EIP-4399).
+    #[serde(default)]
+    pub merge_block: u64,
+    /// Number of the first block of Shanghai, from which the coinbase is warm
+    /// at the start of the transactions (EIP-3651).
+    #[serde(default)]
+    pub shanghai_block: u64,
+}
+
+impl Default for ChainSpec {
+    fn default() -> Self {
+        Self::mainnet()
+    }
+}
+
+impl ChainSpec {
+    /// Return the limits of Ethereum, whose rules of Shanghai apply from the
+    /// genesis block.
+    pub const fn mainnet() -> Self {
+        Self {
+            max_call_depth: MAX_CALL_DEPTH,
+            stack_limit: STACK_LIMIT,
+            merge_block: 0,
+            shanghai_block: 0,
+        }
+    }
+
+    /// Return whether a CALL or CREATE made by a call at `depth` fails with
+    /// the depth error. The depth of the root call is 1.
+    pub fn is_depth_exceeded(&self, depth: usize) -> bool {
+        depth > self.max_call_depth
+    }
+
+    /// Return whether executing `op` on a stack of `stack_len` words
+    /// overflows the stack.
+    pub fn is_stack_overflow(&self, op: OpcodeId, stack_len: usize) -> bool {
+        // The minimum stack pointer of an opcode is the number of words it
+        // pushes in excess of the ones it pops.
+        let (min_stack_ptr, _) = op.valid_stack_ptr_range();
+        stack_len + min_stack_ptr as usize > self.stack_limit
+    }
+
+    /// Return whether the block `number` is past the Merge.
+    pub fn is_merge(&self, number: u64) -> bool {
+        number >= self.merge_block
+    }
+
+    /// Return whether the block `number` is past Shanghai.
+    pub fn is_shanghai(&self, number: u64) -> bool {
+        number >= self.shanghai_block
+    }
+}
 
 mod gas_create {
     // For EIP-3860, there are 2 special gas cost constraints in geth
@@ -227,3 +294,35 @@ impl From<u64> for GasCost {
         GasCost(cost)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain_spec_limits() {
+        let spec = ChainSpec::mainnet();
+        assert!(!spec.is_depth_exceeded(1024));
+        assert!(spec.is_depth_exceeded(1025));
+        assert!(!spec.is_stack_overflow(OpcodeId::PUSH1, 1023));
+        assert!(spec.is_stack_overflow(OpcodeId::PUSH1, 1024));
+        assert!(!spec.is_stack_overflow(OpcodeId::ADD, 1024));
+
+        assert!(spec.is_merge(0));
+        assert!(spec.is_shanghai(0));
+
+        let spec = ChainSpec {
+            max_call_depth: 16,
+            stack_limit: 32,
+            merge_block: 100,
+            shanghai_block: 200,
+        };
+        assert!(spec.is_depth_exceeded(17));
+        assert!(!spec.is_stack_overflow(OpcodeId::DUP1, 31));
+        assert!(spec.is_stack_overflow(OpcodeId::DUP1, 32));
+        assert!(!spec.is_merge(99));
+        assert!(spec.is_merge(100));
+        assert!(!spec.is_shanghai(199));
+        assert!(spec.is_shanghai(200));
+    }
+}
@@ -66,3 +66,248 @@ pub fn tx_data_gas_cost(data: &[u8]) -> u64 {
     data.iter()
         .fold(0, |acc, byte| acc + if *byte == 0 { 4 } else { 16 })
 }
+
+/// Relations between the original value (at the start of the transaction),
+/// the current value and the new value of a storage slot written by SSTORE.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SstoreRelations {
+    /// `current == new`
+    pub current_eq_new: bool,
+    /// `original == current`, the slot is not dirty
+    pub original_eq_current: bool,
+    /// `original == new`
+    pub original_eq_new: bool,
+    /// `original == 0`
+    pub original_is_zero: bool,
+    /// `current == 0`
+    pub current_is_zero: bool,
+    /// `new == 0`
+    pub new_is_zero: bool,
+}
+
+impl SstoreRelations {
+    /// Number of relations
+    pub const LEN: usize = 6;
+
+    /// Relations between `original`, `current` and `new`.
+    pub fn new(original: Word, current: Word, new: Word) -> Self {
+        Self {
+            current_eq_new: current == new,
+            original_eq_current: original == current,
+            original_eq_new: original == new,
+            original_is_zero: original.is_zero(),
+            current_is_zero: current.is_zero(),
+            new_is_zero: new.is_zero(),
+        }
+    }
+
+    /// The relations, in the order of the fields.
+    pub fn to_array(self) -> [bool; Self::LEN] {
+        [
+            self.current_eq_new,
+            self.original_eq_current,
+            self.original_eq_new,
+            self.original_is_zero,
+            self.current_is_zero,
+            self.new_is_zero,
+        ]
+    }
+}
+
+/// The cases of the gas cost and refund of SSTORE, from EIP-2200 with the
+/// EIP-2929 access costs and the EIP-3529 refunds.
+///
+/// A case is selected by the [`SstoreRelations`] of the slot, exactly one case
+/// matches any original, current and new value. The clause tags follow
+/// [`makeGasSStoreFunc` in go-ethereum](https://github.com/ethereum/go-ethereum/blob/9fd8825d5a196edde6d8ef81382979875145b346/core/vm/operations_acl.go#L27).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SstoreCase {
+    /// `current == new` (2.1 / 2.2 no-op)
+    Noop,
+    /// Clean slot, `original == 0`: create slot (2.1.1)
+    Set,
+    /// Clean slot, `original != 0`, `new != 0`: write existing slot (2.1.2)
+    Update,
+    /// Clean slot, `original != 0`, `new == 0`: delete slot (2.1.2b)
+    Delete,
+    /// Dirty slot, `original != 0`, `current == 0`, `new != original`:
+    /// recreate slot (2.2.1.1)
+    DirtyRecreate,
+    /// Dirty slot, `original != 0`, `current == 0`, `new == original`:
+    /// recreate slot (2.2.1.1) and reset to original existing slot (2.2.2.2)
+    DirtyRecreateAndReset,
+    /// Dirty slot, `original != 0`, `current != 0`, `new == 0`: delete slot
+    /// (2.2.1.2)
+    DirtyDelete,
+    /// Dirty slot, `original != 0`, `current != 0`, `new == original`: reset
+    /// to original existing slot (2.2.2.2)
+    DirtyResetExisting,
+    /// Dirty slot, `original == 0`, `new == 0`: reset to original inexistent
+    /// slot (2.2.2.1)
+    DirtyResetInexistent,
+    /// Dirty slot, `current != 0`, `new != 0`, `new != original`: no refund
+    DirtyUpdate,
+}
+
+impl SstoreCase {
+    /// All the cases
+    pub const ALL: [Self; 10] = [
+        Self::Noop,
+        Self::Set,
+        Self::Update,
+        Self::Delete,
+        Self::DirtyRecreate,
+        Self::DirtyRecreateAndReset,
+        Self::DirtyDelete,
+        Self::DirtyResetExisting,
+        Self::DirtyResetInexistent,
+        Self::DirtyUpdate,
+    ];
+
+    /// The relations required by the case, in the order of
+    /// [`SstoreRelations::to_array`]. `None` means the relation is not
+    /// constrained, either because it doesn't matter or because it follows
+    /// from the others.
+    pub fn pattern(&self) -> [Option<bool>; SstoreRelations::LEN] {
+        const T: Option<bool> = Some(true);
+        const F: Option<bool> = Some(false);
+        const X: Option<bool> = None;
+        // [current_eq_new, original_eq_current, original_eq_new,
+        //  original_is_zero, current_is_zero, new_is_zero]
+        match self {
+            Self::Noop => [T, X, X, X, X, X],
+            Self::Set => [F, T, X, T, X, X],
+            Self::Update => [F, T, X, F, X, F],
+            Self::Delete => [X, T, X, F, X, T],
+            Self::DirtyRecreate => [F, X, F, F, T, X],
+            Self::DirtyRecreateAndReset => [X, X, T, F, T, X],
+            Self::DirtyDelete => [X, F, X, F, F, T],
+            Self::DirtyResetExisting => [X, F, T, F, F, X],
+            Self::DirtyResetInexistent => [X, F, X, T, X, T],
+            Self::DirtyUpdate => [F, F, F, X, F, F],
+        }
+    }
+
+    /// Whether the case matches `relations`.
+    pub fn matches(&self, relations: &SstoreRelations) -> bool {
+        self.pattern()
+            .iter()
+            .zip(relations.to_array())
+            .all(|(expected, relation)| expected.map_or(true, |expected| expected == relation))
+    }
+
+    /// The case of a SSTORE writing `new` to a slot with the `original` and
+    /// `current` values.
+    pub fn new(original: Word, current: Word, new: Word) -> Self {
+        let relations = SstoreRelations::new(original, current, new);
+        Self::ALL
+            .into_iter()
+            .find(|case| case.matches(&relations))
+            .expect("sstore cases are exhaustive")
+    }
+
+    /// Gas cost when the slot is warm.
+    pub fn warm_gas_cost(&self) -> GasCost {
+        match self {
+            Self::Set => GasCost::SSTORE_SET,
+            Self::Update | Self::Delete => GasCost::SSTORE_RESET,
+            _ => GasCost::WARM_ACCESS,
+        }
+    }
+
+    /// Gas cost, including the cold access cost when the slot is not warm.
+    pub fn gas_cost(&self, is_warm: bool) -> u64 {
+        if is_warm {
+            self.warm_gas_cost().0
+        } else {
+            self.warm_gas_cost().0 + GasCost::COLD_SLOAD.0
+        }
+    }
+
+    /// Change of the transaction refund counter.
+    pub fn refund_delta(&self) -> i64 {
+        let clears = GasCost::SSTORE_CLEARS_SCHEDULE.0 as i64;
+        let reset_existing = (GasCost::SSTORE_RESET.0 - GasCost::WARM_ACCESS.0) as i64;
+        let reset_inexistent = (GasCost::SSTORE_SET.0 - GasCost::WARM_ACCESS.0) as i64;
+        match self {
+            Self::Noop | Self::Set | Self::Update | Self::DirtyUpdate => 0,
+            Self::Delete | Self::DirtyDelete => clears,
+            Self::DirtyRecreate => -clears,
+            Self::DirtyRecreateAndReset => reset_existing - clears,
+            Self::DirtyResetExisting => reset_existing,
+            Self::DirtyResetInexistent => reset_inexistent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// EIP-2200 gas and EIP-3529 refund, written after `makeGasSStoreFunc`
+    fn reference(original: Word, current: Word, new: Word, is_warm: bool) -> (u64, i64) {
+        let cold = if is_warm { 0 } else { GasCost::COLD_SLOAD.0 };
+        let clears = GasCost::SSTORE_CLEARS_SCHEDULE.0 as i64;
+        if current == new {
+            return (cold + GasCost::WARM_ACCESS.0, 0);
+        }
+        if original == current {
+            if original.is_zero() {
+                return (cold + GasCost::SSTORE_SET.0, 0);
+            }
+            let refund = if new.is_zero() { clears } else { 0 };
+            return (cold + GasCost::SSTORE_RESET.0, refund);
+        }
+        let mut refund = 0;
+        if !original.is_zero() {
+            if current.is_zero() {
+                refund -= clears;
+            } else if new.is_zero() {
+                refund += clears;
+            }
+        }
+        if original == new {
+            refund += if original.is_zero() {
+                (GasCost::SSTORE_SET.0 - GasCost::WARM_ACCESS.0) as i64
+            } else {
+                (GasCost::SSTORE_RESET.0 - GasCost::WARM_ACCESS.0) as i64
+            };
+        }
+        (cold + GasCost::WARM_ACCESS.0, refund)
+    }
+
+    #[test]
+    fn sstore_cases_exhaustive() {
+        // three values realize every combination of the relations
+        let values = [Word::zero(), Word::from(1), Word::from(2)];
+        let mut seen = Vec::new();
+        for original in values {
+            for current in values {
+                for new in values {
+                    let relations = SstoreRelations::new(original, current, new);
+                    let cases: Vec<_> = SstoreCase::ALL
+                        .into_iter()
+                        .filter(|case| case.matches(&relations))
+                        .collect();
+                    assert_eq!(
+                        cases.len(),
+                        1,
+                        "{original} {current} {new} matches {cases:?}"
+                    );
+                    let case = cases[0];
+                    for is_warm in [true, false] {
+                        assert_eq!(
+                            (case.gas_cost(is_warm), case.refund_delta()),
+                            reference(original, current, new, is_warm),
+                            "{case:?} {original} {current} {new} warm {is_warm}"
+                        );
+                    }
+                    if !seen.contains(&case) {
+                        seen.push(case);
+                    }
+                }
+            }
+        }
+        assert_eq!(seen.len(), SstoreCase::ALL.len());
+    }
+}
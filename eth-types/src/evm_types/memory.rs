@@ -6,7 +6,12 @@ use core::{
 };
 use itertools::Itertools;
 use serde::{Serialize, Serializer};
-use std::{cmp, cmp::max, fmt};
+use std::{
+    cmp,
+    cmp::max,
+    fmt,
+    sync::{Arc, OnceLock},
+};
 
 /// Represents a `MemoryAddress` of the EVM.
 #[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
@@ -164,17 +169,58 @@ impl<'b> MulAssign<&'b MemoryAddress> for MemoryAddress {
 
 define_mul_assign_variants!(LHS = MemoryAddress, RHS = MemoryAddress);
 
+/// Size in bytes of a single [`Memory`] page.
+pub const MEMORY_PAGE_SIZE: usize = 4096;
+
+type MemoryPage = [u8; MEMORY_PAGE_SIZE];
+
+/// Shared all-zero page, returned for pages that have never been written.
+static ZERO_PAGE: MemoryPage = [0u8; MEMORY_PAGE_SIZE];
+
 /// Represents a snapshot of the EVM memory state at a certain
 /// execution step height.
-#[derive(Clone, Eq, PartialEq)]
-pub struct Memory(pub Vec<u8>);
+///
+/// Memory is stored in copy-on-write pages of [`MEMORY_PAGE_SIZE`] bytes.
+/// Cloning a snapshot only clones the page handles, and pages that have never
+/// been written are not allocated at all.
+///
+/// Indexing by a range borrows a contiguous copy of the memory, built on first
+/// use and dropped by the next write.
+pub struct Memory {
+    /// Memory pages, where `None` stands for an all-zero page.
+    pages: Vec<Option<Arc<MemoryPage>>>,
+    /// Length of the memory in bytes.
+    len: usize,
+    /// Contiguous copy of the pages, padded to a whole number of pages.
+    flat: OnceLock<Vec<u8>>,
+    /// Whether `flat` has been written through [`IndexMut`] and holds the
+    /// content of the memory instead of `pages`.
+    flat_dirty: bool,
+}
+
+impl Clone for Memory {
+    fn clone(&self) -> Self {
+        Memory {
+            pages: self.pages.clone(),
+            len: self.len,
+            // only clone the contiguous copy when the pages are stale
+            flat: if self.flat_dirty {
+                self.flat.clone()
+            } else {
+                OnceLock::new()
+            },
+            flat_dirty: self.flat_dirty,
+        }
+    }
+}
 
 impl fmt::Debug for Memory {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = self.to_vec();
         if f.alternate() {
             // When formatting with "{:#?}" provide a pretty hex dump
             f.write_str("[\n")?;
-            for (index, b) in self.0.chunks(16).enumerate() {
+            for (index, b) in bytes.chunks(16).enumerate() {
                 f.write_fmt(format_args!("\t{:08x}  ", index * 16))?;
                 f.write_fmt(format_args!(
                     "{:02x} {:02x} {:02x} {:02x} ",
@@ -197,12 +243,28 @@ impl fmt::Debug for Memory {
             f.write_str("]")
         } else {
             f.debug_list()
-                .entries(self.0.iter().map(|b| DebugByte(*b)))
+                .entries(bytes.iter().map(|b| DebugByte(*b)))
                 .finish()
         }
     }
 }
 
+impl PartialEq for Memory {
+    fn eq(&self, other: &Self) -> bool {
+        if self.flat_dirty || other.flat_dirty {
+            return self[..] == other[..];
+        }
+        self.len == other.len
+            && (0..self.pages.len()).all(|index| match (&self.pages[index], &other.pages[index]) {
+                (None, None) => true,
+                (Some(a), Some(b)) if Arc::ptr_eq(a, b) => true,
+                _ => self.page(index) == other.page(index),
+            })
+    }
+}
+
+impl Eq for Memory {}
+
 impl Default for Memory {
     fn default() -> Self {
         Self::new()
@@ -211,13 +273,19 @@ impl Default for Memory {
 
 impl From<Vec<u8>> for Memory {
     fn from(vec: Vec<u8>) -> Self {
-        Memory(vec)
+        let mut memory = Memory::new();
+        memory.push(vec);
+        memory
     }
 }
 
 impl From<Vec<Word>> for Memory {
     fn from(vec: Vec<Word>) -> Self {
-        Memory(vec.iter().flat_map(|word| word.to_be_bytes()).collect())
+        Memory::from(
+            vec.iter()
+                .flat_map(|word| word.to_be_bytes())
+                .collect::<Vec<u8>>(),
+        )
     }
 }
 
@@ -225,13 +293,13 @@ impl Index<Range<usize>> for Memory {
     type Output = [u8];
 
     fn index(&self, index: Range<usize>) -> &Self::Output {
-        self.0.index(index)
+        &self.as_slice()[index]
     }
 }
 
 impl IndexMut<Range<usize>> for Memory {
     fn index_mut(&mut self, index: Range<usize>) -> &mut Self::Output {
-        self.0.index_mut(index)
+        &mut self.as_mut_slice()[index]
     }
 }
 
@@ -241,13 +309,17 @@ impl<A: Into<MemoryAddress>> Index<A> for Memory {
     fn index(&self, index: A) -> &Self::Output {
         // MemoryAddress is in base 16. Therefore since the vec is not, we need
         // to shift the addr.
-        &self.0[index.into().0 >> 5]
+        let index = index.into().0 >> 5;
+        assert!(index < self.len, "memory index out of bounds");
+        &self.page(index / MEMORY_PAGE_SIZE)[index % MEMORY_PAGE_SIZE]
     }
 }
 
 impl<A: Into<MemoryAddress>> IndexMut<A> for Memory {
     fn index_mut(&mut self, index: A) -> &mut Self::Output {
-        &mut self.0[index.into().0 >> 5]
+        let index = index.into().0 >> 5;
+        assert!(index < self.len, "memory index out of bounds");
+        &mut self.page_mut(index / MEMORY_PAGE_SIZE)[index % MEMORY_PAGE_SIZE]
     }
 }
 
@@ -256,7 +328,7 @@ impl Serialize for Memory {
     where
         S: Serializer,
     {
-        let encoded = hex::encode(&self.0);
+        let encoded = hex::encode(self.to_vec());
         serializer.serialize_str(encoded.as_str())
     }
 }
@@ -265,33 +337,99 @@ define_range_index_variants!(
     IN_RANGE = usize,
     OUT_RANGE = MemoryAddress,
     STRUCT_CONTAINER = Memory,
-    INDEX_OUTPUT = [u8]
+    INDEX_OUTPUT = [u8],
+    AS_SLICE = as_slice
 );
 
 impl Memory {
     /// Generate an new empty instance of EVM memory.
     pub const fn new() -> Memory {
-        Memory(Vec::new())
+        Memory {
+            pages: Vec::new(),
+            len: 0,
+            flat: OnceLock::new(),
+            flat_dirty: false,
+        }
     }
 
     /// Returns true if memory contains no elements.
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.len == 0
     }
 
     /// Returns the number of elements in the memory.
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.len
+    }
+
+    /// Returns the byte at `index`, or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<u8> {
+        (index < self.len).then(|| self.page(index / MEMORY_PAGE_SIZE)[index % MEMORY_PAGE_SIZE])
+    }
+
+    /// Returns an iterator over all the bytes of the memory.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        self.pages
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, _)| self.page(index).iter().copied())
+            .take(self.len)
+    }
+
+    /// Copies the whole memory into a contiguous byte vector.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.read_chunk(MemoryRange::new_with_length(0, self.len))
+    }
+
+    /// Returns the memory as a contiguous slice, copying the pages on the first
+    /// call after a write.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.flat()[..self.len]
+    }
+
+    /// Returns the memory as a contiguous mutable slice. The pages are only
+    /// updated from it on the next write through any other method.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        self.flat();
+        self.flat_dirty = true;
+        let len = self.len;
+        &mut self.flat.get_mut().expect("built above")[..len]
+    }
+
+    /// Returns the number of pages that are actually allocated.
+    pub fn allocated_pages(&self) -> usize {
+        self.pages.iter().filter(|page| page.is_some()).count()
+    }
+
+    /// Replace the pages of `self` with the ones of `other` wherever they hold
+    /// the same content, so that both snapshots share them.
+    pub fn share_pages_with(&mut self, other: &Memory) {
+        self.sync_pages();
+        if other.flat_dirty {
+            return;
+        }
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            match (page.as_ref(), other.pages.get(index)) {
+                (Some(a), Some(Some(b))) if !Arc::ptr_eq(a, b) && a == b => {
+                    *page = Some(b.clone());
+                }
+                (Some(a), _) if a.iter().all(|byte| *byte == 0) => *page = None,
+                _ => {}
+            }
+        }
     }
 
     /// Pushes a set of bytes or an [`Word`] in the last `Memory` position.
     pub fn push<T: AsRef<[u8]>>(&mut self, input: T) {
-        self.0.extend(input.as_ref())
+        let input = input.as_ref();
+        let offset = self.len;
+        self.resize(offset + input.len());
+        self.write(offset, input);
     }
 
     /// Returns the last memory address written at this execution step height.
     pub fn last_filled_addr(&self) -> MemoryAddress {
-        self.0.len().into()
+        self.len.into()
     }
 
     /// Reads an entire [`Word`] which starts at the provided [`MemoryAddress`]
@@ -303,16 +441,29 @@ impl Memory {
     /// Reads an chunk of memory[offset..offset+length]. Zeros will be padded if
     /// index out of range.
     pub fn read_chunk(&self, range: impl Into<MemoryRange>) -> Vec<u8> {
-        MemoryRef::from(self).read_chunk(range)
+        let range = range.into();
+        let mut chunk = vec![0u8; range.length().0];
+        let mut offset = range.start.0;
+        let end = cmp::min(range.end.0, self.len);
+        while offset < end {
+            let page_offset = offset % MEMORY_PAGE_SIZE;
+            let len = cmp::min(MEMORY_PAGE_SIZE - page_offset, end - offset);
+            let dst = offset - range.start.0;
+            chunk[dst..dst + len].copy_from_slice(
+                &self.page(offset / MEMORY_PAGE_SIZE)[page_offset..page_offset + len],
+            );
+            offset += len;
+        }
+        chunk
     }
 
     /// Write a chunk of memory[offset..offset+length]. If any data is written out-of-bound, it must
     /// be zeros. This does not resize the memory.
     pub fn write_chunk(&mut self, offset: MemoryAddress, data: &[u8]) {
-        let len = if self.0.len() > offset.0 {
-            let len = cmp::min(data.len(), self.0.len() - offset.0);
+        let len = if self.len > offset.0 {
+            let len = cmp::min(data.len(), self.len - offset.0);
             // Copy the data to the in-bound memory.
-            self.0[offset.0..offset.0 + len].copy_from_slice(&data[..len]);
+            self.write(offset.0, &data[..len]);
             len
         } else {
             0
@@ -325,15 +476,15 @@ impl Memory {
 
     /// Returns the size of memory in word.
     pub fn word_size(&self) -> usize {
-        self.0.len() / 32
+        self.len / 32
     }
 
     /// Resize the memory for at least length and align to 32 bytes.
     /// Note: must not be called when the length accessed is zero. Prefer extend_for_range(..).
     pub fn extend_at_least(&mut self, minimal_size: usize) {
         let memory_size = Self::align_length(minimal_size);
-        if memory_size > self.0.len() {
-            self.0.resize(memory_size, 0);
+        if memory_size > self.len {
+            self.resize(memory_size);
         }
     }
 
@@ -368,10 +519,7 @@ impl Memory {
             let minimal_length = dst_offset as usize + length;
             self.extend_at_least(minimal_length);
 
-            let mem_starts = dst_offset as usize;
-            let mem_ends = mem_starts + length;
-            let dst_slice = &mut self.0[mem_starts..mem_ends];
-            dst_slice.fill(0);
+            let mut chunk = vec![0u8; length];
             let data_starts = src_offset as usize;
             let actual_length = std::cmp::min(
                 length,
@@ -379,8 +527,9 @@ impl Memory {
             );
             if actual_length != 0 {
                 let src_slice = &data[data_starts..data_starts + actual_length];
-                dst_slice[..actual_length].copy_from_slice(src_slice);
+                chunk[..actual_length].copy_from_slice(src_slice);
             }
+            self.write(dst_offset as usize, &chunk);
         }
     }
 
@@ -389,6 +538,82 @@ impl Memory {
     fn align_length(len: usize) -> usize {
         (len + 31) / 32 * 32
     }
+
+    /// Returns the content of the page at `index`.
+    #[inline(always)]
+    fn page(&self, index: usize) -> &MemoryPage {
+        if self.flat_dirty {
+            let start = index * MEMORY_PAGE_SIZE;
+            let flat = self.flat.get().expect("dirty contiguous copy");
+            return flat[start..start + MEMORY_PAGE_SIZE].try_into().unwrap();
+        }
+        self.pages[index].as_deref().unwrap_or(&ZERO_PAGE)
+    }
+
+    /// Returns a mutable reference to the page at `index`, allocating it or
+    /// detaching it from other snapshots if needed.
+    fn page_mut(&mut self, index: usize) -> &mut MemoryPage {
+        self.sync_pages();
+        Arc::make_mut(self.pages[index].get_or_insert_with(|| Arc::new([0u8; MEMORY_PAGE_SIZE])))
+    }
+
+    /// Grow the memory to `len` bytes. Pages added by the expansion are left
+    /// unallocated until they are written.
+    fn resize(&mut self, len: usize) {
+        debug_assert!(len >= self.len, "memory can not shrink");
+        self.sync_pages();
+        self.pages
+            .resize((len + MEMORY_PAGE_SIZE - 1) / MEMORY_PAGE_SIZE, None);
+        self.len = len;
+    }
+
+    /// Returns the contiguous copy of the pages, building it if needed.
+    fn flat(&self) -> &Vec<u8> {
+        self.flat.get_or_init(|| {
+            (0..self.pages.len())
+                .flat_map(|index| self.page(index).iter().copied())
+                .collect()
+        })
+    }
+
+    /// Drop the contiguous copy of the memory before the pages are changed,
+    /// writing it back to the pages first if it has been changed itself.
+    fn sync_pages(&mut self) {
+        let Some(flat) = self.flat.take() else {
+            return;
+        };
+        if !std::mem::take(&mut self.flat_dirty) {
+            return;
+        }
+        for (page, chunk) in self.pages.iter_mut().zip(flat.chunks(MEMORY_PAGE_SIZE)) {
+            if page.is_some() || chunk.iter().any(|b| *b != 0) {
+                Arc::make_mut(page.get_or_insert_with(|| Arc::new([0u8; MEMORY_PAGE_SIZE])))
+                    .copy_from_slice(chunk);
+            }
+        }
+    }
+
+    /// Write `data` at `offset`, which must be in-bound.
+    fn write(&mut self, offset: usize, data: &[u8]) {
+        self.sync_pages();
+        assert!(
+            offset + data.len() <= self.len,
+            "memory write out of bounds"
+        );
+        let mut written = 0;
+        while written < data.len() {
+            let addr = offset + written;
+            let page_offset = addr % MEMORY_PAGE_SIZE;
+            let len = cmp::min(MEMORY_PAGE_SIZE - page_offset, data.len() - written);
+            let src = &data[written..written + len];
+            // Avoid allocating pages for zero writes into untouched pages.
+            if self.pages[addr / MEMORY_PAGE_SIZE].is_some() || src.iter().any(|b| *b != 0) {
+                self.page_mut(addr / MEMORY_PAGE_SIZE)[page_offset..page_offset + len]
+                    .copy_from_slice(src);
+            }
+            written += len;
+        }
+    }
 }
 
 /// Reference of the EVM memory
@@ -396,7 +621,7 @@ pub struct MemoryRef<'a>(pub &'a [u8]);
 
 impl<'a> From<&'a Memory> for MemoryRef<'a> {
     fn from(memory: &'a Memory) -> Self {
-        MemoryRef(&memory.0)
+        MemoryRef(memory.as_slice())
     }
 }
 
@@ -616,12 +841,7 @@ mod memory_tests {
 
     #[test]
     fn push_and_read_works() -> Result<(), Error> {
-        let mem_map = Memory(
-            [Word::from(0), Word::from(0), Word::from(0x80)]
-                .iter()
-                .flat_map(|w| w.to_be_bytes())
-                .collect(),
-        );
+        let mem_map = Memory::from(vec![Word::from(0), Word::from(0), Word::from(0x80)]);
 
         // At this point at position [0x40, 0x80) we've allocated the `0x80`
         // value.
@@ -635,6 +855,101 @@ mod memory_tests {
         Ok(())
     }
 
+    #[test]
+    fn paged_memory_cross_page_access() {
+        let mut memory = Memory::new();
+        memory.extend_at_least(3 * MEMORY_PAGE_SIZE);
+        // Nothing is allocated until written.
+        assert_eq!(memory.allocated_pages(), 0);
+        assert_eq!(memory.len(), 3 * MEMORY_PAGE_SIZE);
+
+        let offset = MEMORY_PAGE_SIZE - 16;
+        let data: Vec<u8> = (1..=32).collect();
+        memory.write_chunk(MemoryAddress(offset), &data);
+        assert_eq!(memory.allocated_pages(), 2);
+        assert_eq!(
+            memory.read_chunk(MemoryRange::new_with_length(offset, 32)),
+            data
+        );
+        assert_eq!(memory.get(offset + 31), Some(32));
+        assert_eq!(memory.get(3 * MEMORY_PAGE_SIZE), None);
+
+        // Reading out of bound is zero padded.
+        let tail = memory.read_chunk(MemoryRange::new_with_length(3 * MEMORY_PAGE_SIZE - 1, 4));
+        assert_eq!(tail, vec![0; 4]);
+    }
+
+    #[test]
+    fn paged_memory_copy_on_write() {
+        let mut memory = Memory::from(vec![Word::from(1), Word::from(2)]);
+        let snapshot = memory.clone();
+        assert_eq!(memory, snapshot);
+
+        memory.write_chunk(MemoryAddress(0), &[0xff]);
+        assert_ne!(memory, snapshot);
+        assert_eq!(snapshot.read_word(MemoryAddress(0)), Word::from(1));
+        assert_eq!(memory.get(0), Some(0xff));
+
+        // An explicitly zeroed page equals an untouched one.
+        let mut zeroed = Memory::new();
+        zeroed.extend_at_least(64);
+        zeroed.write_chunk(MemoryAddress(32), &[1]);
+        zeroed.write_chunk(MemoryAddress(32), &[0]);
+        let mut untouched = Memory::new();
+        untouched.extend_at_least(64);
+        assert_eq!(zeroed, untouched);
+        assert_eq!(zeroed.to_vec(), vec![0; 64]);
+    }
+
+    #[test]
+    fn paged_memory_copy_from() {
+        let mut memory = Memory::new();
+        let data: Vec<u8> = (0..100).collect();
+        memory.copy_from(
+            Word::from(MEMORY_PAGE_SIZE - 10),
+            Word::from(90),
+            Word::from(20),
+            &data,
+        );
+        assert_eq!(memory.len(), MEMORY_PAGE_SIZE + 32);
+        let mut expected = data[90..].to_vec();
+        expected.resize(20, 0);
+        assert_eq!(
+            memory.read_chunk(MemoryRange::new_with_length(MEMORY_PAGE_SIZE - 10, 20)),
+            expected
+        );
+    }
+
+    #[test]
+    fn paged_memory_range_index() {
+        let mut memory = Memory::new();
+        memory.extend_at_least(2 * MEMORY_PAGE_SIZE);
+        let offset = MEMORY_PAGE_SIZE - 2;
+        memory.write_chunk(MemoryAddress(offset), &[1, 2, 3, 4]);
+        assert_eq!(&memory[offset..offset + 4], &[1, 2, 3, 4]);
+        assert_eq!(
+            &memory[MemoryAddress(offset)..MemoryAddress(offset + 2)],
+            &[1, 2]
+        );
+        assert_eq!(memory[..].len(), memory.len());
+
+        // Writes through the slice are seen by snapshots taken after them
+        // only, and by the other accessors.
+        let snapshot = memory.clone();
+        memory[offset..offset + 4].copy_from_slice(&[5, 6, 7, 8]);
+        let written = memory.clone();
+        assert_eq!(memory.get(offset + 3), Some(8));
+        assert_eq!(&snapshot[offset..offset + 4], &[1, 2, 3, 4]);
+        memory.write_chunk(MemoryAddress(0), &[9]);
+        assert_eq!(
+            memory.read_chunk(MemoryRange::new_with_length(offset, 4)),
+            vec![5, 6, 7, 8]
+        );
+        assert_eq!(&memory[0..1], &[9]);
+        assert_eq!(written.read_word(MemoryAddress(0)), Word::zero());
+        assert_eq!(&written[offset..offset + 4], &[5, 6, 7, 8]);
+    }
+
     #[test]
     fn align_range() {
         const WORD: usize = 32;
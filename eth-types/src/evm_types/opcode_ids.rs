@@ -865,6 +865,97 @@ impl OpcodeId {
         (min_stack_ptr, max_stack_ptr)
     }
 
+    /// Returns the number of words popped from and pushed to the stack by a
+    /// successful execution of `OpcodeId`, the δ and α of the yellow paper.
+    /// DUPn and SWAPn count the words they read as popped and pushed back.
+    pub fn stack_pops_pushes(&self) -> (usize, usize) {
+        if self.is_push() {
+            return (0, 1);
+        }
+        if let Some(n) = self.postfix().map(usize::from) {
+            return if self.is_dup() {
+                (n, n + 1)
+            } else if self.is_swap() {
+                (n + 1, n + 1)
+            } else {
+                // LOGn
+                (n + 2, 0)
+            };
+        }
+        match self {
+            OpcodeId::STOP | OpcodeId::JUMPDEST | OpcodeId::INVALID(_) => (0, 0),
+            OpcodeId::ADDRESS
+            | OpcodeId::ORIGIN
+            | OpcodeId::CALLER
+            | OpcodeId::CALLVALUE
+            | OpcodeId::CALLDATASIZE
+            | OpcodeId::CODESIZE
+            | OpcodeId::GASPRICE
+            | OpcodeId::RETURNDATASIZE
+            | OpcodeId::COINBASE
+            | OpcodeId::TIMESTAMP
+            | OpcodeId::NUMBER
+            | OpcodeId::DIFFICULTY
+            | OpcodeId::GASLIMIT
+            | OpcodeId::CHAINID
+            | OpcodeId::SELFBALANCE
+            | OpcodeId::BASEFEE
+            | OpcodeId::PC
+            | OpcodeId::MSIZE
+            | OpcodeId::GAS => (0, 1),
+            OpcodeId::POP | OpcodeId::JUMP | OpcodeId::SELFDESTRUCT => (1, 0),
+            OpcodeId::ISZERO
+            | OpcodeId::NOT
+            | OpcodeId::CALLDATALOAD
+            | OpcodeId::BALANCE
+            | OpcodeId::EXTCODESIZE
+            | OpcodeId::EXTCODEHASH
+            | OpcodeId::BLOCKHASH
+            | OpcodeId::MLOAD
+            | OpcodeId::SLOAD
+            | OpcodeId::TLOAD => (1, 1),
+            OpcodeId::MSTORE
+            | OpcodeId::MSTORE8
+            | OpcodeId::SSTORE
+            | OpcodeId::TSTORE
+            | OpcodeId::JUMPI
+            | OpcodeId::RETURN
+            | OpcodeId::REVERT => (2, 0),
+            OpcodeId::ADD
+            | OpcodeId::MUL
+            | OpcodeId::SUB
+            | OpcodeId::DIV
+            | OpcodeId::SDIV
+            | OpcodeId::MOD
+            | OpcodeId::SMOD
+            | OpcodeId::EXP
+            | OpcodeId::SIGNEXTEND
+            | OpcodeId::LT
+            | OpcodeId::GT
+            | OpcodeId::SLT
+            | OpcodeId::SGT
+            | OpcodeId::EQ
+            | OpcodeId::AND
+            | OpcodeId::OR
+            | OpcodeId::XOR
+            | OpcodeId::BYTE
+            | OpcodeId::SHL
+            | OpcodeId::SHR
+            | OpcodeId::SAR
+            | OpcodeId::SHA3 => (2, 1),
+            OpcodeId::CALLDATACOPY
+            | OpcodeId::CODECOPY
+            | OpcodeId::RETURNDATACOPY
+            | OpcodeId::MCOPY => (3, 0),
+            OpcodeId::ADDMOD | OpcodeId::MULMOD | OpcodeId::CREATE => (3, 1),
+            OpcodeId::EXTCODECOPY => (4, 0),
+            OpcodeId::CREATE2 => (4, 1),
+            OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => (6, 1),
+            OpcodeId::CALL | OpcodeId::CALLCODE => (7, 1),
+            _ => unreachable!("{:?} is handled above", self),
+        }
+    }
+
     /// Returns `true` if the `OpcodeId` has memory access
     pub const fn has_memory_access(&self) -> bool {
         matches!(
@@ -1313,6 +1404,26 @@ mod opcode_ids_tests {
         assert_eq!(OpcodeId::CALLCODE.postfix(), None);
     }
 
+    #[test]
+    fn stack_pops_pushes() {
+        assert_eq!(OpcodeId::ADD.stack_pops_pushes(), (2, 1));
+        assert_eq!(OpcodeId::PUSH32.stack_pops_pushes(), (0, 1));
+        assert_eq!(OpcodeId::DUP3.stack_pops_pushes(), (3, 4));
+        assert_eq!(OpcodeId::SWAP3.stack_pops_pushes(), (4, 4));
+        assert_eq!(OpcodeId::LOG2.stack_pops_pushes(), (4, 0));
+        assert_eq!(OpcodeId::CALL.stack_pops_pushes(), (7, 1));
+
+        // the stack pointer range follows from the stack effect
+        for opcode in OpcodeId::valid_opcodes() {
+            let (pops, pushes) = opcode.stack_pops_pushes();
+            assert_eq!(
+                opcode.valid_stack_ptr_range(),
+                (pushes.saturating_sub(pops) as u32, 1024 - pops as u32),
+                "{opcode:?}"
+            );
+        }
+    }
+
     #[test]
     fn data_len() {
         assert_eq!(OpcodeId::PUSH0.data_len(), 0);
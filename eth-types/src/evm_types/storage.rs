@@ -1,16 +1,19 @@
 //! Doc this
 use crate::{DebugWord, Error, ToBigEndian, Word};
 use serde::{ser::SerializeMap, Serialize, Serializer};
-use std::{collections::HashMap, fmt};
+use std::{collections::HashMap, fmt, sync::Arc};
 
 /// Represents a snapshot of the EVM stack state at a certain
 /// execution step height.
+///
+/// The map is reference counted so that consecutive snapshots holding the same
+/// slots can share it.
 #[derive(Clone, Eq, PartialEq)]
-pub struct Storage(pub HashMap<Word, Word>);
+pub struct Storage(pub Arc<HashMap<Word, Word>>);
 
 impl<T: Into<HashMap<Word, Word>>> From<T> for Storage {
     fn from(map: T) -> Self {
-        Self(map.into())
+        Self(Arc::new(map.into()))
     }
 }
 
@@ -44,7 +47,7 @@ impl Default for Storage {
 impl Storage {
     /// Generate an empty instance of EVM Storage.
     pub fn empty() -> Self {
-        Storage(HashMap::new())
+        Storage(Arc::new(HashMap::new()))
     }
 
     /// Generate an new instance of EVM storage given a `HashMap<Word, Word>`.
@@ -62,4 +65,11 @@ impl Storage {
     pub fn get_or_err(&self, key: &Word) -> Result<Word, Error> {
         self.get(key).cloned().ok_or(Error::InvalidStorageKey)
     }
+
+    /// Share the map of `other` if it holds the same slots as `self`.
+    pub fn share_with(&mut self, other: &Storage) {
+        if !Arc::ptr_eq(&self.0, &other.0) && self.0 == other.0 {
+            self.0 = other.0.clone();
+        }
+    }
 }
@@ -14,10 +14,10 @@ use ethers_core::types::{
 use halo2curves::{group::ff::PrimeField, secp256k1::Fq};
 use num::Integer;
 use num_bigint::BigUint;
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Serialize, Serializer};
 use serde_with::serde_as;
 use sha3::{Digest, Keccak256};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use strum_macros::EnumIter;
 
 /// Tx type
@@ -196,6 +196,66 @@ fn serde_account_storage<S: Serializer>(
         .serialize(serializer)
 }
 
+/// Override of the state of a single account, applied on top of the state
+/// a call is executed against. Follows the geth `OverrideAccount` format used
+/// by `eth_call` and `debug_traceCall`.
+#[derive(PartialEq, Eq, Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+    /// Balance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<Word>,
+    /// nonce
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U64>,
+    /// EVM Code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Storage slots to patch, the slots not listed here keep their value
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serde_account_storage"
+    )]
+    pub state_diff: HashMap<Word, Word>,
+}
+
+impl AccountOverride {
+    /// Apply the override on top of `account`.
+    pub fn apply(&self, account: &mut Account) {
+        if let Some(balance) = self.balance {
+            account.balance = balance;
+        }
+        if let Some(nonce) = self.nonce {
+            account.nonce = nonce.as_u64().into();
+        }
+        if let Some(code) = &self.code {
+            account.code = code.clone();
+        }
+        account
+            .storage
+            .extend(self.state_diff.iter().map(|(k, v)| (*k, *v)));
+    }
+}
+
+/// Account overrides keyed by address.
+pub type StateOverrides = HashMap<Address, AccountOverride>;
+
+/// Apply `overrides` on top of `accounts`. Overridden accounts which are
+/// missing in `accounts` are inserted, starting from an empty account.
+pub fn apply_state_overrides(
+    accounts: &mut BTreeMap<Address, Account>,
+    overrides: &StateOverrides,
+) {
+    for (address, account_override) in overrides {
+        let account = accounts.entry(*address).or_insert_with(|| Account {
+            address: *address,
+            ..Default::default()
+        });
+        account_override.apply(account);
+    }
+}
+
 /// Definition of all of the constants related to an Ethereum block and
 /// chain to be used as setup for the external tracer.
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
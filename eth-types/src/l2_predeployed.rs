@@ -79,3 +79,19 @@ pub mod l1_gas_price_oracle {
         }
     }
 }
+
+/// Helper for the L1Block contract of the OP stack (Bedrock)
+pub mod op_l1_block {
+    use crate::{Address, U256};
+    use std::{str::FromStr, sync::LazyLock};
+
+    /// L1Block predeployed address
+    pub static ADDRESS: LazyLock<Address> =
+        LazyLock::new(|| Address::from_str("0x4200000000000000000000000000000000000015").unwrap());
+    /// L1 base fee slot in L1Block
+    pub static BASE_FEE_SLOT: LazyLock<U256> = LazyLock::new(|| U256::from(1));
+    /// L1 fee overhead slot in L1Block
+    pub static OVERHEAD_SLOT: LazyLock<U256> = LazyLock::new(|| U256::from(5));
+    /// L1 fee scalar slot in L1Block
+    pub static SCALAR_SLOT: LazyLock<U256> = LazyLock::new(|| U256::from(6));
+}
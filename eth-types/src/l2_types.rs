@@ -2,6 +2,7 @@
 
 use crate::{
     evm_types::{Gas, GasCost, OpcodeId, ProgramCounter},
+    utils::default_code_hasher,
     EthBlock, GethCallTrace, GethExecError, GethExecStep, GethExecTrace, GethPrestateTrace, Hash,
     ToBigEndian, Transaction, H256,
 };
@@ -48,7 +49,7 @@ pub struct BlockTraceV2 {
 
 impl From<BlockTrace> for BlockTraceV2 {
     fn from(b: BlockTrace) -> Self {
-        let codes = collect_codes(&b, None)
+        let codes = collect_codes(&b, None, default_code_hasher().as_ref())
             .expect("collect codes should not fail")
             .into_iter()
             .map(|(hash, code)| BytecodeTrace {
@@ -396,7 +397,12 @@ pub struct ExecutionResult {
 
 impl From<ExecutionResult> for GethExecTrace {
     fn from(e: ExecutionResult) -> Self {
-        let struct_logs = e.exec_steps.into_iter().map(GethExecStep::from).collect();
+        let mut struct_logs: Vec<GethExecStep> =
+            e.exec_steps.into_iter().map(GethExecStep::from).collect();
+        for index in 1..struct_logs.len() {
+            let (prev, rest) = struct_logs.split_at_mut(index);
+            crate::struct_logs::share_snapshots(&mut rest[0], &prev[index - 1]);
+        }
         GethExecTrace {
             l1_fee: e.l1_fee.as_u64(),
             gas: Gas(e.gas),
@@ -423,6 +429,8 @@ pub struct ExecStep {
     pub refund: u64,
     pub depth: isize,
     pub error: Option<GethExecError>,
+    #[serde(rename = "returnData", default)]
+    pub return_data: Option<Bytes>,
     #[cfg(feature = "enable-stack")]
     pub stack: Option<Vec<crate::Word>>,
     #[cfg(feature = "enable-memory")]
@@ -444,6 +452,7 @@ impl From<ExecStep> for GethExecStep {
             refund: Gas(e.refund),
             depth: e.depth as u16,
             error: e.error,
+            return_data: e.return_data,
             #[cfg(feature = "enable-stack")]
             stack: e.stack.map_or_else(Stack::new, Stack::from),
             #[cfg(feature = "enable-memory")]
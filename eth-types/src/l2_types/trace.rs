@@ -1,17 +1,19 @@
 use crate::{
     evm_types::OpcodeId,
     l2_types::BlockTrace,
-    state_db::{CodeDB, StateDB},
-    utils::is_precompiled,
+    state_db::StateDB,
+    utils::{is_precompiled, CodeHasher},
     Address, Error, H256,
 };
 use ethers_core::types::Bytes;
 use itertools::Itertools;
 
-/// Update codedb from statedb and trace
+/// Update codedb from statedb and trace, hashing the codes whose hash is not
+/// in the trace with `code_hasher`
 pub fn collect_codes(
     block: &BlockTrace,
     sdb: Option<&StateDB>,
+    code_hasher: &dyn CodeHasher,
 ) -> Result<Vec<(H256, Vec<u8>)>, Error> {
     if !block.codes.is_empty() {
         log::debug!("codes available in trace, skip collecting");
@@ -35,9 +37,9 @@ pub fn collect_codes(
                 .to
                 .as_ref()
                 .and_then(|t| t.poseidon_code_hash)
-                .unwrap_or_else(|| CodeDB::hash(&bytecode));
+                .unwrap_or_else(|| code_hasher.hash_code(&bytecode));
             let code_hash = if code_hash.is_zero() {
-                CodeDB::hash(&bytecode)
+                code_hasher.hash_code(&bytecode)
             } else {
                 code_hash
             };
@@ -113,6 +115,7 @@ pub fn collect_codes(
                             callee_code.unwrap_or_default(),
                             Some(addr),
                             sdb,
+                            code_hasher,
                         );
                     }
                     OpcodeId::EXTCODECOPY => {
@@ -121,7 +124,7 @@ pub fn collect_codes(
                             log::warn!("unable to fetch code from step. {step:?}");
                             continue;
                         }
-                        trace_code(&mut codes, None, code.unwrap(), None, sdb);
+                        trace_code(&mut codes, None, code.unwrap(), None, sdb, code_hasher);
                     }
 
                     _ => {}
@@ -141,12 +144,13 @@ fn trace_code(
     addr: Option<Address>,
     // sdb is used to read codehash if available without recomputing
     sdb: Option<&StateDB>,
+    code_hasher: &dyn CodeHasher,
 ) {
     let code_hash = code_hash.or_else(|| {
         let addr = addr?;
         let sdb = sdb.as_ref()?;
         let (_existed, acc_data) = sdb.get_account(&addr);
-        if acc_data.code_hash != CodeDB::empty_code_hash() && !code.is_empty() {
+        if acc_data.code_hash != code_hasher.hash_code(&[]) && !code.is_empty() {
             Some(acc_data.code_hash)
         } else {
             None
@@ -155,7 +159,7 @@ fn trace_code(
     let code_hash = match code_hash {
         Some(code_hash) if !code_hash.is_zero() => code_hash,
         _ => {
-            let hash = CodeDB::hash(&code);
+            let hash = code_hasher.hash_code(&code);
             log::debug!(
                 "hash_code done: addr {addr:?}, size {}, hash {hash:?}",
                 &code.len()
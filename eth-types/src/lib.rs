@@ -24,6 +24,7 @@ pub mod macros;
 pub mod error;
 #[macro_use]
 pub mod bytecode;
+pub mod compact;
 pub mod constants;
 pub mod evm_types;
 pub mod forks;
@@ -31,8 +32,10 @@ pub mod geth_types;
 /// L2 system contracts
 pub mod l2_predeployed;
 pub mod l2_types;
+pub mod parse;
 pub mod sign_types;
 pub mod state_db;
+mod struct_logs;
 pub mod utils;
 
 use crate::evm_types::{Gas, GasCost, OpcodeId, ProgramCounter};
@@ -51,7 +54,7 @@ pub use ethers_core::{
 };
 use serde::{de, Deserialize, Deserializer, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     fmt,
     fmt::{Display, Formatter},
     str::FromStr,
@@ -328,18 +331,20 @@ struct GethExecStepInternal {
     gas_cost: GasCost,
     depth: u16,
     error: Option<GethExecError>,
+    #[serde(rename = "returnData", default)]
+    return_data: Option<Bytes>,
     // stack is in hex 0x prefixed
     #[cfg(feature = "enable-stack")]
-    #[serde(default)]
-    stack: Vec<DebugU256>,
+    #[serde(default, deserialize_with = "struct_logs::deserialize_words")]
+    stack: Vec<Word>,
     // memory is in chunks of 32 bytes, in hex
     #[cfg(feature = "enable-memory")]
-    #[serde(default)]
-    memory: Vec<DebugU256>,
+    #[serde(default, deserialize_with = "struct_logs::deserialize_memory")]
+    memory: Memory,
     // storage is hex -> hex
     #[cfg(feature = "enable-storage")]
-    #[serde(default)]
-    storage: HashMap<DebugU256, DebugU256>,
+    #[serde(default, deserialize_with = "struct_logs::deserialize_storage")]
+    storage: Storage,
 }
 
 /// The execution step type returned by geth RPC debug_trace* methods.
@@ -350,10 +355,16 @@ pub struct GethExecStep {
     pub pc: ProgramCounter,
     pub op: OpcodeId,
     pub gas: Gas,
+    // renamed like in the geth output, so that serialized traces can be read back
+    #[serde(rename = "gasCost")]
     pub gas_cost: GasCost,
     pub refund: Gas,
     pub depth: u16,
     pub error: Option<GethExecError>,
+    // return data of the last sub call of the current call, only present if
+    // the tracer is configured to capture it
+    #[serde(rename = "returnData", skip_serializing_if = "Option::is_none")]
+    pub return_data: Option<Bytes>,
     // stack is in hex 0x prefixed
     #[cfg(feature = "enable-stack")]
     pub stack: Stack,
@@ -528,8 +539,9 @@ impl Serialize for GethExecError {
     where
         S: serde::Serializer,
     {
-        // We serialize the error as a string constant.
-        serializer.serialize_str(self.error())
+        // We serialize the error as the geth error string, including the
+        // parameters of the stack and opcode errors so that it can be parsed back.
+        serializer.collect_str(self)
     }
 }
 
@@ -588,7 +600,8 @@ impl fmt::Debug for GethExecStep {
             .field("gas_cost", &format_args!("{}", self.gas_cost.0))
             .field("refund", &format_args!("{}", self.refund.0))
             .field("depth", &self.depth)
-            .field("error", &self.error);
+            .field("error", &self.error)
+            .field("return_data", &self.return_data);
         #[cfg(feature = "enable-stack")]
         f.field("stack", &self.stack);
         #[cfg(feature = "enable-memory")]
@@ -613,22 +626,13 @@ impl<'de> Deserialize<'de> for GethExecStep {
             gas_cost: s.gas_cost,
             depth: s.depth,
             error: s.error,
+            return_data: s.return_data,
             #[cfg(feature = "enable-stack")]
-            stack: Stack(s.stack.iter().map(|dw| dw.to_word()).collect::<Vec<Word>>()),
+            stack: Stack::from_vec(s.stack),
             #[cfg(feature = "enable-memory")]
-            memory: Memory::from(
-                s.memory
-                    .iter()
-                    .map(|dw| dw.to_word())
-                    .collect::<Vec<Word>>(),
-            ),
+            memory: s.memory,
             #[cfg(feature = "enable-storage")]
-            storage: Storage(
-                s.storage
-                    .iter()
-                    .map(|(k, v)| (k.to_word(), v.to_word()))
-                    .collect(),
-            ),
+            storage: s.storage,
         })
     }
 }
@@ -649,6 +653,174 @@ pub struct ResultGethExecTrace {
     pub result: GethExecTrace,
 }
 
+/// Output of the geth `muxTracer` configured with `callTracer` and
+/// `prestateTracer`, which completes the struct logger output of a
+/// [`GethExecTrace`].
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct GethMuxTrace {
+    /// prestate trace, empty when the prestateTracer is not configured
+    #[serde(rename = "prestateTracer", default)]
+    pub prestate: HashMap<Address, GethPrestateTrace>,
+    /// call trace
+    #[serde(rename = "callTracer")]
+    pub call_trace: GethCallTrace,
+}
+
+/// Helper type built to deal with the `result` field wrapping each
+/// [`GethMuxTrace`] in `debug_traceBlockBy*` Geth JSON-RPC calls.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[doc(hidden)]
+pub struct ResultGethMuxTrace {
+    pub result: GethMuxTrace,
+}
+
+/// Output of the geth `muxTracer` configured with `callTracer` and
+/// `prestateTracer` in diff mode.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct GethMuxDiffTrace {
+    /// prestate trace in diff mode
+    #[serde(rename = "prestateTracer")]
+    pub diff: GethPrestateDiff,
+    /// call trace
+    #[serde(rename = "callTracer")]
+    pub call_trace: GethCallTrace,
+}
+
+/// Struct logger part of a [`GethExecTrace`], as returned by geth when no
+/// custom tracer is configured.
+#[derive(Deserialize)]
+struct GethStructLoggerTrace {
+    #[serde(default)]
+    l1_fee: u64,
+    gas: Gas,
+    failed: bool,
+    #[serde(rename = "returnValue")]
+    return_value: String,
+    #[serde(
+        rename = "structLogs",
+        deserialize_with = "struct_logs::deserialize_struct_logs"
+    )]
+    struct_logs: Vec<GethExecStep>,
+    #[serde(
+        rename = "accountAfter",
+        default,
+        deserialize_with = "parse_account_after"
+    )]
+    account_after: Vec<crate::l2_types::AccountProofWrapper>,
+}
+
+impl GethStructLoggerTrace {
+    fn with_mux_trace(self, mux: GethMuxTrace) -> GethExecTrace {
+        GethExecTrace {
+            l1_fee: self.l1_fee,
+            gas: self.gas,
+            failed: self.failed,
+            return_value: self.return_value,
+            struct_logs: self.struct_logs,
+            account_after: self.account_after,
+            prestate: mux.prestate,
+            call_trace: mux.call_trace,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ResultGethStructLoggerTrace {
+    result: GethStructLoggerTrace,
+}
+
+/// Deserializes the array of struct logger results of a block, pairing each
+/// one with its muxTracer output as soon as it is decoded.
+///
+/// Used as a [`de::DeserializeSeed`] on the `result` of a
+/// `debug_traceBlockBy*` response, from a reader or from the raw text.
+pub struct BlockTracesSeed(std::vec::IntoIter<GethMuxTrace>);
+
+impl BlockTracesSeed {
+    /// Seed pairing the struct logger traces with `mux`, in order.
+    pub fn new(mux: Vec<GethMuxTrace>) -> Self {
+        Self(mux.into_iter())
+    }
+}
+
+impl<'de> de::DeserializeSeed<'de> for BlockTracesSeed {
+    type Value = Vec<GethExecTrace>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> de::Visitor<'de> for BlockTracesSeed {
+    type Value = Vec<GethExecTrace>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "a list of struct logger traces")
+    }
+
+    fn visit_seq<A>(mut self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut traces = Vec::with_capacity(self.0.len());
+        while let Some(trace) = seq.next_element::<ResultGethStructLoggerTrace>()? {
+            let mux = self.0.next().ok_or_else(|| {
+                de::Error::custom("more struct logger traces than muxTracer traces")
+            })?;
+            traces.push(trace.result.with_mux_trace(mux));
+        }
+        if self.0.next().is_some() {
+            return Err(de::Error::custom(
+                "more muxTracer traces than struct logger traces",
+            ));
+        }
+        Ok(traces)
+    }
+}
+
+impl GethExecTrace {
+    /// Decode the struct logger output of a `debug_traceTransaction` call from
+    /// its raw JSON, completing it with the `mux` tracer output.
+    ///
+    /// The struct logs are decoded straight from the text, without building an
+    /// intermediate [`serde_json::Value`] tree.
+    pub fn from_struct_logger_json(
+        json: &str,
+        mux: GethMuxTrace,
+    ) -> Result<Self, serde_json::Error> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        deserializer.disable_recursion_limit();
+        let trace = GethStructLoggerTrace::deserialize(serde_stacker::Deserializer::new(
+            &mut deserializer,
+        ))?;
+        deserializer.end()?;
+        Ok(trace.with_mux_trace(mux))
+    }
+
+    /// Decode the struct logger output of a `debug_traceBlockBy*` call from its
+    /// raw JSON, completing the trace of each transaction with the
+    /// corresponding `mux` tracer output.
+    ///
+    /// Transactions are decoded one at a time, so that apart from the raw text
+    /// only the typed traces are kept in memory.
+    pub fn from_block_struct_logger_json(
+        json: &str,
+        mux: Vec<GethMuxTrace>,
+    ) -> Result<Vec<Self>, serde_json::Error> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        deserializer.disable_recursion_limit();
+        let traces = de::DeserializeSeed::deserialize(
+            BlockTracesSeed::new(mux),
+            serde_stacker::Deserializer::new(&mut deserializer),
+        )?;
+        deserializer.end()?;
+        Ok(traces)
+    }
+}
+
 /// The execution trace type returned by geth RPC debug_trace* methods.
 /// Corresponds to `ExecutionResult` in `go-ethereum/internal/ethapi/api.go`.
 /// The deserialization truncates the memory of each step in `struct_logs` to
@@ -668,7 +840,10 @@ pub struct GethExecTrace {
     #[serde(rename = "returnValue")]
     pub return_value: String,
     /// Vector of geth execution steps of the trace.
-    #[serde(rename = "structLogs")]
+    #[serde(
+        rename = "structLogs",
+        deserialize_with = "struct_logs::deserialize_struct_logs"
+    )]
     pub struct_logs: Vec<GethExecStep>,
     #[serde(
         rename = "accountAfter",
@@ -718,6 +893,58 @@ pub struct GethPrestateTrace {
     pub storage: Option<HashMap<U256, U256>>,
 }
 
+/// The prestate trace in diff mode returned by geth RPC debug_trace* methods,
+/// with the state of the modified accounts before and after the transaction.
+///
+/// Only the modified fields of an account are in its post state, the
+/// accounts deleted by the transaction aren't, and the storage slots set to
+/// zero are only in the pre state.
+#[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct GethPrestateDiff {
+    /// State before the transaction
+    pub pre: HashMap<Address, GethPrestateTrace>,
+    /// State after the transaction
+    pub post: HashMap<Address, GethPrestateTrace>,
+}
+
+impl GethPrestateDiff {
+    /// Record the state after the transaction in `overrides`, so that the
+    /// next transaction traced with them runs on top of it. The storage of a
+    /// deleted account is only cleared for the slots in its pre state.
+    pub fn carry_over(&self, overrides: &mut geth_types::StateOverrides) {
+        let addresses: BTreeSet<_> = self.pre.keys().chain(self.post.keys()).collect();
+        for address in addresses {
+            let account = overrides.entry(*address).or_default();
+            let pre_storage = self.pre.get(address).and_then(|pre| pre.storage.as_ref());
+            let Some(post) = self.post.get(address) else {
+                // deleted by the transaction
+                account.balance = Some(U256::zero());
+                account.nonce = Some(U64::zero());
+                account.code = Some(Bytes::default());
+                for key in pre_storage.into_iter().flat_map(HashMap::keys) {
+                    account.state_diff.insert(*key, U256::zero());
+                }
+                continue;
+            };
+            if let Some(balance) = post.balance {
+                account.balance = Some(balance);
+            }
+            if let Some(nonce) = post.nonce {
+                account.nonce = Some(nonce.into());
+            }
+            if let Some(code) = &post.code {
+                account.code = Some(code.clone());
+            }
+            for key in pre_storage.into_iter().flat_map(HashMap::keys) {
+                account.state_diff.insert(*key, U256::zero());
+            }
+            account
+                .state_diff
+                .extend(post.storage.iter().flatten().map(|(k, v)| (*k, *v)));
+        }
+    }
+}
+
 /// The call trace returned by geth RPC debug_trace* methods.
 /// using callTracer
 #[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
@@ -737,6 +964,24 @@ pub struct GethCallTrace {
     // value: U256,
 }
 
+/// Depth-first iterator over the frames of a [`GethCallTrace`], yielding each
+/// frame with its depth, which is 1 for the root call.
+#[derive(Clone, Debug)]
+pub struct GethCallFrames<'a> {
+    stack: Vec<(u16, &'a GethCallTrace)>,
+}
+
+impl<'a> Iterator for GethCallFrames<'a> {
+    type Item = (u16, &'a GethCallTrace);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, frame) = self.stack.pop()?;
+        self.stack
+            .extend(frame.calls.iter().rev().map(|call| (depth + 1, call)));
+        Some((depth, frame))
+    }
+}
+
 /// Flattened Call Trace
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct FlatGethCallTrace {
@@ -788,6 +1033,124 @@ impl GethCallTrace {
         call_is_success
     }
 
+    /// type of the call, the CALL*, CREATE* or SELFDESTRUCT opcode which made
+    /// it. Fails if the tracer reports another type.
+    pub fn call_type(&self) -> Result<OpcodeId, Error> {
+        match OpcodeId::from_str(&self.call_type) {
+            Ok(op) if op.is_call_or_create() || op == OpcodeId::SELFDESTRUCT => Ok(op),
+            _ => Err(Error::TracingError(format!(
+                "unknown call frame type {}",
+                self.call_type
+            ))),
+        }
+    }
+
+    /// caller address
+    pub fn from(&self) -> Address {
+        self.from
+    }
+
+    /// callee address
+    pub fn to(&self) -> Option<Address> {
+        self.to
+    }
+
+    /// error of the call, if it failed
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// gas used by the call
+    pub fn gas_used(&self) -> U256 {
+        self.gas_used
+    }
+
+    /// output of the call
+    pub fn output(&self) -> Option<&Bytes> {
+        self.output.as_ref()
+    }
+
+    /// sub calls, in execution order
+    pub fn calls(&self) -> &[GethCallTrace] {
+        &self.calls
+    }
+
+    /// Depth-first iterator over the call frames, starting with this call.
+    pub fn iter(&self) -> GethCallFrames<'_> {
+        GethCallFrames {
+            stack: vec![(1, self)],
+        }
+    }
+
+    /// Attach each step of the struct logs of the call to its call frame.
+    ///
+    /// Returns, for each step, the index of the frame which executes it in
+    /// the depth-first order of [`Self::iter`]. Each CALL*/CREATE* step (and
+    /// SELFDESTRUCT if the tracer reports it) is matched with the next frame
+    /// of the trace, so that the frames don't need to be inferred from the
+    /// depth changes of the steps.
+    pub fn reconcile(&self, steps: &[GethExecStep]) -> Result<Vec<usize>, Error> {
+        let frames = self.iter().collect::<Vec<_>>();
+        // index after the sub frames of each frame
+        let mut ends = vec![frames.len(); frames.len()];
+        let mut open = Vec::<usize>::new();
+        for (index, (depth, _)) in frames.iter().enumerate() {
+            while let Some(&parent) = open.last() {
+                if frames[parent].0 < *depth {
+                    break;
+                }
+                ends[parent] = index;
+                open.pop();
+            }
+            open.push(index);
+        }
+
+        let mut next = 1;
+        let mut stack = vec![0];
+        let mut step_frames = Vec::with_capacity(steps.len());
+        for (index, step) in steps.iter().enumerate() {
+            while stack.len() > step.depth as usize {
+                let frame = stack.pop().unwrap();
+                next = next.max(ends[frame]);
+            }
+            if stack.len() != step.depth as usize {
+                return Err(Error::TracingError(format!(
+                    "step {index} at depth {} is not in a call frame",
+                    step.depth
+                )));
+            }
+            step_frames.push(*stack.last().unwrap());
+
+            if step.error.is_some() {
+                continue;
+            }
+            let is_entered = steps
+                .get(index + 1)
+                .map(|next_step| next_step.depth == step.depth + 1)
+                .unwrap_or(false);
+            let next_frame = frames
+                .get(next)
+                .map(|(depth, frame)| frame.call_type().map(|call_type| (*depth, call_type)))
+                .transpose()?;
+            match next_frame {
+                Some((depth, call_type)) if depth == step.depth + 1 && call_type == step.op => {
+                    if is_entered {
+                        stack.push(next);
+                    }
+                    next += 1;
+                }
+                _ if step.op.is_call_or_create() && is_entered => {
+                    return Err(Error::TracingError(format!(
+                        "no call frame for the {:?} step {index}",
+                        step.op
+                    )));
+                }
+                _ => {}
+            }
+        }
+        Ok(step_frames)
+    }
+
     /// flatten the call trace as it is.
     pub fn flatten_trace(
         &self,
@@ -993,10 +1356,11 @@ mod tests {
                         gas_cost: GasCost(3),
                         depth: 1,
                         error: None,
+                        return_data: None,
                         #[cfg(feature = "enable-stack")]
                         stack: Stack::new(),
                         #[cfg(feature = "enable-storage")]
-                        storage: Storage(word_map!()),
+                        storage: Storage::from(word_map!()),
                         #[cfg(feature = "enable-memory")]
                         memory: Memory::new(),
                     },
@@ -1008,10 +1372,11 @@ mod tests {
                         gas_cost: GasCost(2100),
                         depth: 1,
                         error: None,
+                        return_data: None,
                         #[cfg(feature = "enable-stack")]
                         stack: Stack(vec![word!("0x1003e2d2"), word!("0x2a"), word!("0x0")]),
                         #[cfg(feature = "enable-storage")]
-                        storage: Storage(word_map!("0x0" => "0x6f")),
+                        storage: Storage::from(word_map!("0x0" => "0x6f")),
                         #[cfg(feature = "enable-memory")]
                         memory: Memory::from(vec![word!("0x0"), word!("0x0"), word!("0x080")]),
                     },
@@ -1023,6 +1388,7 @@ mod tests {
                         gas_cost: GasCost(42),
                         depth: 1,
                         error: None,
+                        return_data: None,
                         #[cfg(feature = "enable-stack")]
                         stack: Stack(vec![
                             word!("0x3635c9adc5dea00000"),
@@ -1030,7 +1396,7 @@ mod tests {
                             word!("0x0")
                         ]),
                         #[cfg(feature = "enable-storage")]
-                        storage: Storage(word_map!()),
+                        storage: Storage::from(word_map!()),
                         #[cfg(feature = "enable-memory")]
                         memory: Memory::from(vec![
                             word!(
@@ -1067,6 +1433,297 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn deserialize_block_struct_logger_traces() {
+        let struct_logs_json = r#"[
+            {
+                "result": {
+                    "gas": 21000,
+                    "failed": false,
+                    "returnValue": "",
+                    "structLogs": []
+                }
+            },
+            {
+                "txHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                "result": {
+                    "gas": 21040,
+                    "failed": true,
+                    "returnValue": "",
+                    "refund": 0,
+                    "structLogs": [
+                        {
+                            "pc": 0,
+                            "op": "STOP",
+                            "gas": 0,
+                            "gasCost": 0,
+                            "depth": 1
+                        }
+                    ]
+                }
+            }
+        ]"#;
+        let mux_json = |from: &str| {
+            format!(
+                r#"{{
+                    "prestateTracer": {{}},
+                    "callTracer": {{
+                        "from": "{from}",
+                        "gasUsed": "0x0",
+                        "type": "CALL"
+                    }}
+                }}"#
+            )
+        };
+        let mux: Vec<GethMuxTrace> = [
+            "0x000000000000000000000000000000000cafe001",
+            "0x000000000000000000000000000000000cafe002",
+        ]
+        .iter()
+        .map(|from| serde_json::from_str(&mux_json(from)).unwrap())
+        .collect();
+
+        let traces =
+            GethExecTrace::from_block_struct_logger_json(struct_logs_json, mux.clone()).unwrap();
+        assert_eq!(traces.len(), 2);
+        assert_eq!(traces[0].gas, Gas(21000));
+        assert_eq!(traces[0].call_trace, mux[0].call_trace);
+        assert!(traces[1].failed);
+        assert_eq!(traces[1].struct_logs.len(), 1);
+        assert_eq!(traces[1].call_trace, mux[1].call_trace);
+
+        // The number of mux traces must match the number of transactions.
+        assert!(
+            GethExecTrace::from_block_struct_logger_json(struct_logs_json, mux[..1].to_vec())
+                .is_err()
+        );
+
+        // The seed also decodes the traces from a reader.
+        let mut deserializer = serde_json::Deserializer::from_reader(struct_logs_json.as_bytes());
+        let streamed =
+            de::DeserializeSeed::deserialize(BlockTracesSeed::new(mux), &mut deserializer).unwrap();
+        assert_eq!(streamed, traces);
+    }
+
+    #[test]
+    fn geth_exec_trace_serde_roundtrip() {
+        let trace_json = r#"{
+            "gas": 21040,
+            "failed": true,
+            "returnValue": "",
+            "structLogs": [
+                {
+                    "pc": 0,
+                    "op": "PUSH1",
+                    "gas": 100,
+                    "gasCost": 3,
+                    "depth": 1
+                },
+                {
+                    "pc": 2,
+                    "op": "STOP",
+                    "gas": 97,
+                    "gasCost": 0,
+                    "refund": 0,
+                    "depth": 1,
+                    "error": "stack underflow (0 <=> 1)"
+                }
+            ],
+            "prestate": {},
+            "callTrace": {
+                "from": "0x000000000000000000000000000000000cafe001",
+                "gasUsed": "0x0",
+                "type": "CALL"
+            }
+        }"#;
+        let trace: GethExecTrace = serde_json::from_str(trace_json).unwrap();
+        let serialized = serde_json::to_string(&trace).unwrap();
+        let deserialized: GethExecTrace = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(trace, deserialized);
+    }
+
+    #[test]
+    fn geth_exec_step_return_data() {
+        let step_json = |return_data: &str| {
+            format!(
+                r#"{{"pc": 0, "op": "RETURNDATASIZE", "gas": 100, "gasCost": 2, "refund": 4800, "depth": 1{return_data}}}"#
+            )
+        };
+        let step: GethExecStep = serde_json::from_str(&step_json("")).unwrap();
+        assert_eq!(step.refund, Gas(4800));
+        assert_eq!(step.return_data, None);
+
+        let step: GethExecStep =
+            serde_json::from_str(&step_json(r#", "returnData": "0x1234""#)).unwrap();
+        assert_eq!(step.return_data, Some(Bytes::from([0x12, 0x34])));
+        let serialized = serde_json::to_string(&step).unwrap();
+        let deserialized: GethExecStep = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(step, deserialized);
+    }
+
+    #[test]
+    fn call_trace_reconcile() {
+        let step = |op: &str, depth: u16| {
+            format!(r#"{{"pc": 0, "op": "{op}", "gas": 0, "gasCost": 0, "depth": {depth}}}"#)
+        };
+        let trace_json = format!(
+            r#"{{
+                "gas": 21040,
+                "failed": false,
+                "returnValue": "",
+                "structLogs": [{}],
+                "prestate": {{}},
+                "callTrace": {{
+                    "from": "0x000000000000000000000000000000000cafe001",
+                    "to": "0x000000000000000000000000000000000cafe002",
+                    "gasUsed": "0x0",
+                    "type": "CALL",
+                    "calls": [
+                        {{
+                            "from": "0x000000000000000000000000000000000cafe002",
+                            "to": "0x000000000000000000000000000000000cafe003",
+                            "gasUsed": "0x0",
+                            "type": "DELEGATECALL",
+                            "calls": [
+                                {{
+                                    "from": "0x000000000000000000000000000000000cafe002",
+                                    "to": "0x0000000000000000000000000000000000000004",
+                                    "gasUsed": "0x0",
+                                    "type": "STATICCALL"
+                                }}
+                            ]
+                        }},
+                        {{
+                            "from": "0x000000000000000000000000000000000cafe002",
+                            "to": "0x000000000000000000000000000000000cafe004",
+                            "gasUsed": "0x0",
+                            "type": "CALL"
+                        }}
+                    ]
+                }}
+            }}"#,
+            [
+                step("PUSH1", 1),
+                step("DELEGATECALL", 1),
+                step("STATICCALL", 2),
+                step("STOP", 2),
+                step("CALL", 1),
+                step("STOP", 1),
+            ]
+            .join(",")
+        );
+        let trace: GethExecTrace = serde_json::from_str(&trace_json).unwrap();
+
+        let frames = trace
+            .call_trace
+            .iter()
+            .map(|(depth, frame)| (depth, frame.call_type().unwrap(), frame.to()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            frames,
+            vec![
+                (
+                    1,
+                    OpcodeId::CALL,
+                    Some(address!("0x000000000000000000000000000000000cafe002"))
+                ),
+                (
+                    2,
+                    OpcodeId::DELEGATECALL,
+                    Some(address!("0x000000000000000000000000000000000cafe003"))
+                ),
+                (
+                    3,
+                    OpcodeId::STATICCALL,
+                    Some(address!("0x0000000000000000000000000000000000000004"))
+                ),
+                (
+                    2,
+                    OpcodeId::CALL,
+                    Some(address!("0x000000000000000000000000000000000cafe004"))
+                ),
+            ]
+        );
+        assert_eq!(
+            trace.call_trace.reconcile(&trace.struct_logs).unwrap(),
+            vec![0, 0, 1, 1, 0, 0]
+        );
+
+        // a step entering a call frame which is not in the call trace
+        let mut struct_logs = trace.struct_logs.clone();
+        struct_logs[4].op = OpcodeId::CREATE;
+        struct_logs.insert(5, struct_logs[3].clone());
+        assert!(trace.call_trace.reconcile(&struct_logs).is_err());
+
+        // a call frame of an unknown type
+        let trace: GethExecTrace =
+            serde_json::from_str(&trace_json.replace(r#""STATICCALL""#, r#""FOO""#)).unwrap();
+        let (_, frame) = trace.call_trace.iter().nth(2).unwrap();
+        assert!(frame.call_type().is_err());
+        assert!(trace.call_trace.reconcile(&trace.struct_logs).is_err());
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "enable-memory", feature = "enable-storage"))]
+mod struct_logs_tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn unchanged_snapshots_are_shared() {
+        let step_json = |pc: u64, memory: &str| {
+            format!(
+                r#"{{
+                    "pc": {pc},
+                    "op": "SLOAD",
+                    "gas": 5217,
+                    "gasCost": 2100,
+                    "depth": 1,
+                    "stack": ["0x0"],
+                    "storage": {{
+                        "0000000000000000000000000000000000000000000000000000000000000000": "000000000000000000000000000000000000000000000000000000000000006f"
+                    }},
+                    "memory": ["{memory}"]
+                }}"#
+            )
+        };
+        let zero = "0".repeat(64);
+        let one = format!("{}1", "0".repeat(63));
+        let trace_json = format!(
+            r#"{{
+                "gas": 26809,
+                "failed": false,
+                "returnValue": "",
+                "structLogs": [{}, {}, {}],
+                "prestate": {{}},
+                "callTrace": {{
+                    "calls": [],
+                    "error": null,
+                    "from": "0x000000000000000000000000000000000cafe001",
+                    "to": null,
+                    "gasUsed": "0x0",
+                    "type": "CALL",
+                    "output": "0x00"
+                }}
+            }}"#,
+            step_json(0, &one),
+            step_json(1, &one),
+            step_json(2, &zero),
+        );
+        let trace: GethExecTrace =
+            serde_json::from_str(&trace_json).expect("json-deserialize GethExecTrace");
+        let steps = &trace.struct_logs;
+
+        assert!(Arc::ptr_eq(&steps[0].storage.0, &steps[1].storage.0));
+        assert!(Arc::ptr_eq(&steps[1].storage.0, &steps[2].storage.0));
+        assert_eq!(steps[0].memory, steps[1].memory);
+        assert_eq!(steps[1].memory.allocated_pages(), 1);
+        // Pages that only hold zeros are never allocated.
+        assert_eq!(steps[2].memory.allocated_pages(), 0);
+        assert_eq!(steps[2].memory.read_word(0.into()), Word::zero());
+    }
 }
 
 #[cfg(test)]
@@ -1168,4 +1825,64 @@ mod eth_types_test {
         assert_eq!(req.to, None);
         Ok(())
     }
+
+    #[test]
+    fn prestate_diff_carry_over() {
+        let diff: GethPrestateDiff = serde_json::from_str(
+            r#"{
+              "pre": {
+                "0x00000000000000000000000000000000000000aa": {
+                  "balance": "0x10",
+                  "nonce": 1,
+                  "storage": {
+                    "0x01": "0x05",
+                    "0x02": "0x06"
+                  }
+                },
+                "0x00000000000000000000000000000000000000bb": {
+                  "balance": "0x20",
+                  "code": "0x00",
+                  "storage": { "0x01": "0x07" }
+                }
+              },
+              "post": {
+                "0x00000000000000000000000000000000000000aa": {
+                  "balance": "0x08",
+                  "nonce": 2,
+                  "storage": { "0x01": "0x09" }
+                },
+                "0x00000000000000000000000000000000000000cc": {
+                  "balance": "0x08",
+                  "code": "0x6000"
+                }
+              }
+            }"#,
+        )
+        .unwrap();
+        let mut overrides = geth_types::StateOverrides::new();
+        diff.carry_over(&mut overrides);
+
+        let modified = &overrides[&Address::from_low_u64_be(0xaa)];
+        assert_eq!(modified.balance, Some(0x08.into()));
+        assert_eq!(modified.nonce, Some(2.into()));
+        assert_eq!(modified.code, None);
+        assert_eq!(
+            modified.state_diff,
+            HashMap::from([(1.into(), 9.into()), (2.into(), U256::zero())])
+        );
+
+        let deleted = &overrides[&Address::from_low_u64_be(0xbb)];
+        assert_eq!(deleted.balance, Some(U256::zero()));
+        assert_eq!(deleted.nonce, Some(U64::zero()));
+        assert_eq!(deleted.code, Some(Bytes::default()));
+        assert_eq!(
+            deleted.state_diff,
+            HashMap::from([(1.into(), U256::zero())])
+        );
+
+        let created = &overrides[&Address::from_low_u64_be(0xcc)];
+        assert_eq!(created.balance, Some(0x08.into()));
+        assert_eq!(created.code, Some(Bytes::from(vec![0x60, 0x00])));
+        assert!(created.state_diff.is_empty());
+    }
 }
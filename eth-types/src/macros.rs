@@ -139,15 +139,15 @@ macro_rules! define_mul_assign_variants {
 }
 
 /// Define Range indexing ops for the given type converting the ranges
-/// internally.
+/// internally, indexing the slice returned by the `AS_SLICE` method.
 macro_rules! define_range_index_variants {
-    (IN_RANGE = $inner_range:ty, OUT_RANGE = $out_range:ty, STRUCT_CONTAINER = $struc:ty, INDEX_OUTPUT = $output:ty) => {
+    (IN_RANGE = $inner_range:ty, OUT_RANGE = $out_range:ty, STRUCT_CONTAINER = $struc:ty, INDEX_OUTPUT = $output:ty, AS_SLICE = $as_slice:ident) => {
         impl core::ops::Index<core::ops::Range<$out_range>> for $struc {
             type Output = $output;
 
             #[inline]
             fn index(&self, index: core::ops::Range<$out_range>) -> &Self::Output {
-                &self.0[..][convert_range(index)]
+                &self.$as_slice()[convert_range(index)]
             }
         }
 
@@ -156,7 +156,7 @@ macro_rules! define_range_index_variants {
 
             #[inline]
             fn index(&self, _index: core::ops::RangeFull) -> &Self::Output {
-                &self.0[..]
+                self.$as_slice()
             }
         }
 
@@ -165,7 +165,7 @@ macro_rules! define_range_index_variants {
 
             #[inline]
             fn index(&self, index: core::ops::RangeTo<$out_range>) -> &Self::Output {
-                &self.0[..][convert_range_to(index)]
+                &self.$as_slice()[convert_range_to(index)]
             }
         }
 
@@ -174,7 +174,7 @@ macro_rules! define_range_index_variants {
 
             #[inline]
             fn index(&self, index: core::ops::RangeFrom<$out_range>) -> &Self::Output {
-                &self.0[..][convert_range_from(index)]
+                &self.$as_slice()[convert_range_from(index)]
             }
         }
 
@@ -183,7 +183,7 @@ macro_rules! define_range_index_variants {
 
             #[inline]
             fn index(&self, index: core::ops::RangeToInclusive<$out_range>) -> &Self::Output {
-                &self.0[..][convert_range_to_inclusive(index)]
+                &self.$as_slice()[convert_range_to_inclusive(index)]
             }
         }
 
@@ -0,0 +1,270 @@
+//! Parsing of the numbers and addresses written as strings by the test
+//! formats (state tests, fillers, test specs) and the rpc responses, as
+//! `0x` prefixed hex, decimal or `0x:bigint 0x...` values.
+//!
+//! The [`serde_u256`], [`serde_u64`] and [`serde_address`] modules wrap these
+//! parsers for `#[serde(with = "...")]`, accepting both strings and json
+//! numbers.
+
+use crate::{Address, Error, H256, U256};
+use serde::{de, Deserializer, Serializer};
+use std::fmt;
+
+/// Prefix of the big integers of the fillers, e.g. `0x:bigint 0x0100`.
+const BIGINT_PREFIX: &str = "0x:bigint ";
+
+fn parsing_error(value: &str, err: impl fmt::Display) -> Error {
+    Error::ValueParsing(format!("{value:?}: {err}"))
+}
+
+/// Parse a `0x` prefixed hex, a decimal, or a `0x:bigint` number. An
+/// unprefixed number containing hex digits is parsed as hex.
+pub fn parse_u256(value: &str) -> Result<U256, Error> {
+    let trimmed = value.trim();
+    let trimmed = trimmed.strip_prefix(BIGINT_PREFIX).unwrap_or(trimmed);
+    let parsed = if let Some(hex) = trimmed.strip_prefix("0x") {
+        U256::from_str_radix(hex, 16)
+    } else if trimmed
+        .to_lowercase()
+        .contains(['a', 'b', 'c', 'd', 'e', 'f'])
+    {
+        U256::from_str_radix(trimmed, 16)
+    } else {
+        U256::from_str_radix(trimmed, 10)
+    };
+    parsed.map_err(|err| parsing_error(value, err))
+}
+
+/// Parse a number like [`parse_u256`], failing if it doesn't fit in a u64.
+pub fn parse_u64(value: &str) -> Result<u64, Error> {
+    let parsed = parse_u256(value)?;
+    if parsed > U256::from(u64::MAX) {
+        return Err(parsing_error(value, "overflows u64"));
+    }
+    Ok(parsed.as_u64())
+}
+
+/// Parse a 20 bytes hex address, `0x` prefixed or not.
+pub fn parse_address(value: &str) -> Result<Address, Error> {
+    let trimmed = value.trim();
+    let bytes =
+        hex::decode(trimmed.strip_prefix("0x").unwrap_or(trimmed)).map_err(Error::HexError)?;
+    if bytes.len() != Address::len_bytes() {
+        return Err(parsing_error(value, "not a 20 bytes address"));
+    }
+    Ok(Address::from_slice(&bytes))
+}
+
+/// Parse a 32 bytes hex hash, `0x` prefixed or not. An empty value is the
+/// zero hash.
+pub fn parse_hash(value: &str) -> Result<H256, Error> {
+    let trimmed = value.trim();
+    let hex = trimmed.strip_prefix("0x").unwrap_or(trimmed);
+    if hex.is_empty() {
+        return Ok(H256::zero());
+    }
+    let bytes = hex::decode(hex).map_err(Error::HexError)?;
+    if bytes.len() != H256::len_bytes() {
+        return Err(parsing_error(value, "not a 32 bytes hash"));
+    }
+    Ok(H256::from_slice(&bytes))
+}
+
+/// Visitor of a string parsed with `parse`, or of a json number.
+struct ValueVisitor<T> {
+    expecting: &'static str,
+    parse: fn(&str) -> Result<T, Error>,
+}
+
+impl<'de, T> de::Visitor<'de> for ValueVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.expecting)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        (self.parse)(value).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<T, E> {
+        // decimal, as written in the json
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<T, E> {
+        let value = u64::try_from(value).map_err(E::custom)?;
+        self.visit_u64(value)
+    }
+}
+
+/// Serde helpers of [`U256`] values, see [`parse_u256`]. They are serialized
+/// as `0x` prefixed hex.
+pub mod serde_u256 {
+    use super::*;
+
+    /// Serialize as `0x` prefixed hex.
+    pub fn serialize<S: Serializer>(value: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{value:#x}"))
+    }
+
+    /// Deserialize a string or a json number.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+        deserializer.deserialize_any(ValueVisitor {
+            expecting: "a hex or decimal number",
+            parse: parse_u256,
+        })
+    }
+}
+
+/// Serde helpers of u64 values, see [`parse_u64`]. They are serialized as
+/// `0x` prefixed hex.
+pub mod serde_u64 {
+    use super::*;
+
+    /// Serialize as `0x` prefixed hex.
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{value:#x}"))
+    }
+
+    /// Deserialize a string or a json number.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        deserializer.deserialize_any(ValueVisitor {
+            expecting: "a hex or decimal number",
+            parse: parse_u64,
+        })
+    }
+}
+
+/// Serde helpers of [`Address`] values, see [`parse_address`]. They are
+/// serialized as `0x` prefixed hex.
+pub mod serde_address {
+    use super::*;
+
+    /// Serialize as `0x` prefixed hex.
+    pub fn serialize<S: Serializer>(value: &Address, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{value:?}"))
+    }
+
+    /// Deserialize a hex string.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Address, D::Error> {
+        deserializer.deserialize_str(ValueVisitor {
+            expecting: "a hex address",
+            parse: parse_address,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address;
+    use serde::{Deserialize, Serialize};
+
+    #[test]
+    fn parse_numbers() {
+        for (value, expected) in [
+            ("0x10", 16),
+            ("0x0000000000000000000000000000000000000010", 16),
+            ("10", 10),
+            (" 10 ", 10),
+            ("0a", 10),
+            ("0xFF", 255),
+            ("0x:bigint 0x0100", 256),
+            ("0x:bigint 256", 256),
+        ] {
+            assert_eq!(parse_u256(value).unwrap(), U256::from(expected), "{value}");
+            assert_eq!(parse_u64(value).unwrap(), expected, "{value}");
+        }
+        assert_eq!(
+            parse_u256("0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff")
+                .unwrap(),
+            U256::MAX
+        );
+        assert_eq!(
+            parse_u256(
+                "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+            )
+            .unwrap(),
+            U256::MAX
+        );
+
+        // too large or not a number
+        assert!(
+            parse_u256("0x10000000000000000000000000000000000000000000000000000000000000000")
+                .is_err()
+        );
+        assert!(parse_u256("0xg").is_err());
+        assert!(parse_u256("-1").is_err());
+        assert!(parse_u64("0x10000000000000000").is_err());
+        assert_eq!(parse_u64("0xffffffffffffffff").unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn parse_addresses() {
+        let expected = address!("0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba");
+        for value in [
+            "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+            "2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+            "0x2ADC25665018AA1FE0E6BC666DAC8FC2697FF9BA",
+        ] {
+            assert_eq!(parse_address(value).unwrap(), expected, "{value}");
+        }
+        assert!(parse_address("0x2adc").is_err());
+        assert!(parse_address("0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba00").is_err());
+        assert!(parse_address("0xzz").is_err());
+
+        assert_eq!(parse_hash("").unwrap(), H256::zero());
+        assert_eq!(parse_hash("0x").unwrap(), H256::zero());
+        assert_eq!(
+            parse_hash(&format!("0x{}01", "00".repeat(31))).unwrap(),
+            H256::from_low_u64_be(1)
+        );
+        assert!(parse_hash("0x01").is_err());
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Values {
+        #[serde(with = "serde_u256")]
+        balance: U256,
+        #[serde(with = "serde_u64")]
+        nonce: u64,
+        #[serde(with = "serde_address")]
+        address: Address,
+    }
+
+    #[test]
+    fn serde_values() {
+        let values = Values {
+            balance: U256::from(1_000_000),
+            nonce: 16,
+            address: address!("0x00000000000000000000000000000000000000ff"),
+        };
+        for json in [
+            r#"{"balance":"1000000","nonce":"0x10","address":"0x00000000000000000000000000000000000000ff"}"#,
+            r#"{"balance":1000000,"nonce":16,"address":"00000000000000000000000000000000000000ff"}"#,
+            r#"{"balance":"0x:bigint 0x0f4240","nonce":"16","address":"0x00000000000000000000000000000000000000FF"}"#,
+        ] {
+            assert_eq!(
+                serde_json::from_str::<Values>(json).unwrap(),
+                values,
+                "{json}"
+            );
+        }
+
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(
+            json,
+            r#"{"balance":"0xf4240","nonce":"0x10","address":"0x00000000000000000000000000000000000000ff"}"#
+        );
+        assert_eq!(serde_json::from_str::<Values>(&json).unwrap(), values);
+
+        assert!(
+            serde_json::from_str::<Values>(r#"{"balance":-1,"nonce":0,"address":"0x00"}"#).is_err()
+        );
+        assert!(serde_json::from_str::<Values>(
+            r#"{"balance":0,"nonce":"0x10000000000000000","address":"0x00000000000000000000000000000000000000ff"}"#
+        )
+        .is_err());
+    }
+}
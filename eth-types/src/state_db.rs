@@ -2,12 +2,14 @@
 //! Ethereum State Trie.
 
 use crate::{
-    utils::{hash_code, is_precompiled},
+    geth_types::StateOverrides,
+    utils::{default_code_hasher, hash_code, hash_code_keccak, is_precompiled, CodeHasher},
     Address, Hash, Word, H256, KECCAK_CODE_HASH_EMPTY, U256,
 };
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeSet, HashMap, HashSet},
-    sync::LazyLock,
+    sync::{Arc, LazyLock},
 };
 
 static ACCOUNT_ZERO: LazyLock<Account> = LazyLock::new(Account::zero);
@@ -22,15 +24,10 @@ pub static EMPTY_CODE_HASH_LE: LazyLock<[u8; 32]> = LazyLock::new(|| {
 
 const VALUE_ZERO: Word = Word::zero();
 
-/// Memory storage for contract code by code hash.
-#[derive(Debug)]
-pub struct CodeDB(pub HashMap<Hash, Vec<u8>>);
-
-impl Clone for CodeDB {
-    fn clone(&self) -> Self {
-        CodeDB(self.0.clone())
-    }
-}
+/// Memory storage for contract code by code hash, hashed with its
+/// [`CodeHasher`].
+#[derive(Debug, Clone)]
+pub struct CodeDB(pub HashMap<Hash, Vec<u8>>, Arc<dyn CodeHasher>);
 
 impl Default for CodeDB {
     fn default() -> Self {
@@ -39,28 +36,42 @@ impl Default for CodeDB {
 }
 
 impl CodeDB {
-    /// Create a new empty Self.
+    /// Create a new empty Self, with the
+    /// [`default_code_hasher`](crate::utils::default_code_hasher).
     pub fn new() -> Self {
-        let mut codedb = Self(HashMap::new());
+        Self::with_hasher(default_code_hasher())
+    }
+    /// Create a new empty Self, hashing the codes with `hasher`.
+    pub fn with_hasher(hasher: Arc<dyn CodeHasher>) -> Self {
+        let mut codedb = Self(HashMap::new(), hasher);
         codedb.insert(Vec::new());
         codedb
     }
+    /// Hasher of the codes
+    pub fn hasher(&self) -> &Arc<dyn CodeHasher> {
+        &self.1
+    }
     /// Insert code along with code hash
     pub fn insert_with_hash(&mut self, hash: H256, code: Vec<u8>) {
         self.0.insert(hash, code);
     }
     /// Insert code to CodeDB, and return the code hash.
     pub fn insert(&mut self, code: Vec<u8>) -> Hash {
-        let hash = Self::hash(&code);
+        let hash = self.code_hash(&code);
         self.insert_with_hash(hash, code);
         hash
     }
+    /// Compute hash of given code with the hasher of Self.
+    pub fn code_hash(&self, code: &[u8]) -> Hash {
+        self.1.hash_code(code)
+    }
     /// Specify code hash for empty code (nil)
     pub fn empty_code_hash() -> Hash {
         *EMPTY_CODE_HASH
     }
 
-    /// Compute hash of given code.
+    /// Compute hash of given code, with the
+    /// [`default_code_hasher`](crate::utils::default_code_hasher).
     pub fn hash(code: &[u8]) -> Hash {
         H256(hash_code(code).into())
     }
@@ -68,7 +79,7 @@ impl CodeDB {
 
 /// Account of the Ethereum State Trie, which contains an in-memory key-value
 /// database that represents the Account Storage Trie.
-#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 pub struct Account {
     /// Nonce
     pub nonce: Word,
@@ -155,6 +166,31 @@ impl StateDB {
         Self::default()
     }
 
+    /// Create a StateDB from the accounts and the touched accounts found at a
+    /// transaction boundary, as returned by [`StateDB::committed_accounts`]
+    /// and [`StateDB::touched_accounts`].
+    pub fn from_committed(
+        state: HashMap<Address, Account>,
+        touched_account: HashSet<Address>,
+    ) -> Self {
+        Self {
+            state,
+            touched_account,
+            ..Default::default()
+        }
+    }
+
+    /// Accounts of the committed state, not including the writes of the
+    /// current transaction.
+    pub fn committed_accounts(&self) -> &HashMap<Address, Account> {
+        &self.state
+    }
+
+    /// Accounts which are still empty but already touched.
+    pub fn touched_accounts(&self) -> &HashSet<Address> {
+        &self.touched_account
+    }
+
     /// Set an [`Account`] at `addr` in the StateDB.
     pub fn set_account(&mut self, addr: &Address, acc: Account) {
         self.state.insert(*addr, acc);
@@ -169,6 +205,14 @@ impl StateDB {
         }
     }
 
+    /// Keep only the accounts for which `f` returns true, returning the number
+    /// of accounts removed.
+    pub fn retain_accounts(&mut self, mut f: impl FnMut(&Address, &Account) -> bool) -> usize {
+        let len = self.state.len();
+        self.state.retain(|addr, acc| f(addr, acc));
+        len - self.state.len()
+    }
+
     /// List all account addresses in current state db
     pub fn list_accounts(&self) {
         let addrs: BTreeSet<_> = self.state.keys().collect();
@@ -206,6 +250,28 @@ impl StateDB {
         (found, self.state.get_mut(addr).expect("addr not inserted"))
     }
 
+    /// Apply `overrides` on top of the state, inserting the overridden codes
+    /// into `code_db`. Overridden accounts which are missing in the state are
+    /// inserted, starting from a zero [`Account`].
+    pub fn apply_overrides(&mut self, code_db: &mut CodeDB, overrides: &StateOverrides) {
+        for (addr, account_override) in overrides {
+            let (_, acc) = self.get_account_mut(addr);
+            if let Some(balance) = account_override.balance {
+                acc.balance = balance;
+            }
+            if let Some(nonce) = account_override.nonce {
+                acc.nonce = nonce.as_u64().into();
+            }
+            if let Some(code) = &account_override.code {
+                acc.code_hash = code_db.insert(code.to_vec());
+                acc.keccak_code_hash = hash_code_keccak(code);
+                acc.code_size = code.len().into();
+            }
+            acc.storage
+                .extend(account_override.state_diff.iter().map(|(k, v)| (*k, *v)));
+        }
+    }
+
     /// Get a reference to the storage value from [`Account`] at `addr`, at
     /// `key`.  Returns false and a zero [`Word`] when the [`Account`] or `key`
     /// wasn't found in the state.
@@ -428,4 +494,83 @@ mod statedb_tests {
         assert!(found);
         assert_eq!(value, &Word::from(102));
     }
+
+    #[test]
+    fn codedb_with_hasher() {
+        use crate::utils::{
+            hash_code_keccak, hash_code_poseidon, KeccakCodeHasher, PoseidonCodeHasher,
+        };
+
+        let code = vec![0x60u8, 0x00, 0x60, 0x00, 0xf3];
+        let mut code_db = CodeDB::with_hasher(Arc::new(KeccakCodeHasher));
+        assert_eq!(code_db.insert(code.clone()), hash_code_keccak(&code));
+        assert!(code_db.0.contains_key(&hash_code_keccak(&[])));
+
+        let mut code_db = CodeDB::with_hasher(Arc::new(PoseidonCodeHasher));
+        let hash = code_db.insert(code.clone());
+        assert_eq!(hash, hash_code_poseidon(&code));
+        assert_eq!(hash, code_db.code_hash(&code));
+        assert_eq!(code_db.clone().code_hash(&code), hash);
+        assert!(code_db.0.contains_key(&hash_code_poseidon(&[])));
+    }
+
+    #[test]
+    fn statedb_apply_overrides() {
+        use crate::geth_types::AccountOverride;
+
+        let addr_a = address!("0x0000000000000000000000000000000000000001");
+        let addr_b = address!("0x0000000000000000000000000000000000000002");
+        let mut statedb = StateDB::new();
+        let mut code_db = CodeDB::new();
+        statedb.set_account(
+            &addr_a,
+            Account {
+                nonce: Word::from(1),
+                balance: Word::from(10),
+                storage: HashMap::from([(Word::from(1), Word::from(2))]),
+                ..Account::zero()
+            },
+        );
+
+        let code = vec![0x60u8, 0x00, 0x60, 0x00, 0xf3];
+        let overrides = HashMap::from([
+            (
+                addr_a,
+                AccountOverride {
+                    balance: Some(Word::from(20)),
+                    state_diff: HashMap::from([(Word::from(3), Word::from(4))]),
+                    ..Default::default()
+                },
+            ),
+            (
+                addr_b,
+                AccountOverride {
+                    nonce: Some(7.into()),
+                    code: Some(code.clone().into()),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        statedb.apply_overrides(&mut code_db, &overrides);
+
+        // Overridden fields are replaced, the others are kept
+        let (_, acc) = statedb.get_account(&addr_a);
+        assert_eq!(acc.nonce, Word::from(1));
+        assert_eq!(acc.balance, Word::from(20));
+        assert_eq!(
+            statedb.get_storage(&addr_a, &Word::from(1)).1,
+            &Word::from(2)
+        );
+        assert_eq!(
+            statedb.get_storage(&addr_a, &Word::from(3)).1,
+            &Word::from(4)
+        );
+
+        // Missing accounts are created
+        let (found, acc) = statedb.get_account(&addr_b);
+        assert!(found);
+        assert_eq!(acc.nonce, Word::from(7));
+        assert_eq!(acc.code_size, Word::from(code.len()));
+        assert_eq!(code_db.0.get(&acc.code_hash), Some(&code));
+    }
 }
@@ -0,0 +1,192 @@
+//! Allocation-light deserialization of geth struct logs.
+//!
+//! Stack, memory and storage snapshots are decoded straight into [`Word`]s from
+//! the borrowed hex strings of the response, and snapshots that are unchanged
+//! from the previous step share its allocation instead of holding a copy.
+
+use crate::{DebugU256, GethExecStep, Word};
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "enable-storage")]
+use crate::evm_types::Storage;
+#[cfg(feature = "enable-memory")]
+use crate::{evm_types::Memory, ToBigEndian};
+#[cfg(feature = "enable-storage")]
+use serde::de::MapAccess;
+#[cfg(feature = "enable-storage")]
+use std::collections::HashMap;
+
+/// A [`Word`] encoded as a hex string, with or without the `0x` prefix.
+struct HexWord(Word);
+
+impl<'de> Deserialize<'de> for HexWord {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct HexWordVisitor;
+
+        impl<'de> Visitor<'de> for HexWordVisitor {
+            type Value = HexWord;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a hex encoded 256-bit word")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                DebugU256::from_str(v)
+                    .map(|w| HexWord(Word(w.0)))
+                    .map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(HexWordVisitor)
+    }
+}
+
+/// Deserialize a list of hex encoded words, as found in the `stack` field of
+/// a struct log.
+pub(crate) fn deserialize_words<'de, D>(deserializer: D) -> Result<Vec<Word>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct WordsVisitor;
+
+    impl<'de> Visitor<'de> for WordsVisitor {
+        type Value = Vec<Word>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "a list of hex encoded words")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut words = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+            while let Some(HexWord(word)) = seq.next_element()? {
+                words.push(word);
+            }
+            Ok(words)
+        }
+    }
+
+    deserializer.deserialize_seq(WordsVisitor)
+}
+
+/// Deserialize the `memory` field of a struct log, which is a list of 32 bytes
+/// chunks in hex.
+#[cfg(feature = "enable-memory")]
+pub(crate) fn deserialize_memory<'de, D>(deserializer: D) -> Result<Memory, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct MemoryVisitor;
+
+    impl<'de> Visitor<'de> for MemoryVisitor {
+        type Value = Memory;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "a list of hex encoded memory words")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default() * 32);
+            while let Some(HexWord(word)) = seq.next_element()? {
+                bytes.extend_from_slice(&word.to_be_bytes());
+            }
+            Ok(Memory::from(bytes))
+        }
+    }
+
+    deserializer.deserialize_seq(MemoryVisitor)
+}
+
+/// Deserialize the `storage` field of a struct log, which is a map from hex
+/// encoded keys to hex encoded values.
+#[cfg(feature = "enable-storage")]
+pub(crate) fn deserialize_storage<'de, D>(deserializer: D) -> Result<Storage, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct StorageVisitor;
+
+    impl<'de> Visitor<'de> for StorageVisitor {
+        type Value = Storage;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "a map of hex encoded storage slots")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut storage = HashMap::with_capacity(map.size_hint().unwrap_or_default());
+            while let Some((HexWord(key), HexWord(value))) = map.next_entry()? {
+                storage.insert(key, value);
+            }
+            Ok(Storage::from(storage))
+        }
+    }
+
+    deserializer.deserialize_map(StorageVisitor)
+}
+
+/// Deserialize the `structLogs` field of a trace, sharing the snapshots of
+/// each step with the previous one whenever they are unchanged.
+pub(crate) fn deserialize_struct_logs<'de, D>(
+    deserializer: D,
+) -> Result<Vec<GethExecStep>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct StructLogsVisitor;
+
+    impl<'de> Visitor<'de> for StructLogsVisitor {
+        type Value = Vec<GethExecStep>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "a list of struct logs")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut steps: Vec<GethExecStep> =
+                Vec::with_capacity(seq.size_hint().unwrap_or_default());
+            while let Some(mut step) = seq.next_element::<GethExecStep>()? {
+                if let Some(prev) = steps.last() {
+                    share_snapshots(&mut step, prev);
+                }
+                steps.push(step);
+            }
+            Ok(steps)
+        }
+    }
+
+    deserializer.deserialize_seq(StructLogsVisitor)
+}
+
+/// Make `step` reuse the return data, memory pages and storage of `prev`
+/// wherever they hold the same content.
+pub(crate) fn share_snapshots(step: &mut GethExecStep, prev: &GethExecStep) {
+    if step.return_data.is_some() && step.return_data == prev.return_data {
+        step.return_data = prev.return_data.clone();
+    }
+    #[cfg(feature = "enable-memory")]
+    step.memory.share_pages_with(&prev.memory);
+    #[cfg(feature = "enable-storage")]
+    step.storage.share_with(&prev.storage);
+}
@@ -2,6 +2,7 @@
 
 use crate::{Address, Hash};
 use revm_precompile::Precompiles;
+use std::{fmt::Debug, sync::Arc};
 
 /// Check if address is a precompiled or not.
 pub fn is_precompiled(address: &Address) -> bool {
@@ -15,7 +16,47 @@ pub fn is_precompiled(address: &Address) -> bool {
 /// Default number of bytes to pack into a field element.
 pub const POSEIDON_HASH_BYTES_IN_FIELD: usize = 31;
 
-/// Default code hash
+/// Hashing of the contract codes, which differs between the zk-rollup
+/// flavors.
+pub trait CodeHasher: Debug + Send + Sync {
+    /// Hash of `code`
+    fn hash_code(&self, code: &[u8]) -> Hash;
+}
+
+/// Keccak code hash, as in Ethereum.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeccakCodeHasher;
+
+impl CodeHasher for KeccakCodeHasher {
+    fn hash_code(&self, code: &[u8]) -> Hash {
+        hash_code_keccak(code)
+    }
+}
+
+/// Poseidon code hash of the code packed into field elements, as in Scroll.
+///
+/// The packing of [`POSEIDON_HASH_BYTES_IN_FIELD`] bytes per field element
+/// and the hash domain are fixed: the step of the poseidon hash chip is a
+/// constant, so the circuits can't prove a code hash with other parameters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoseidonCodeHasher;
+
+impl CodeHasher for PoseidonCodeHasher {
+    fn hash_code(&self, code: &[u8]) -> Hash {
+        hash_code_poseidon(code)
+    }
+}
+
+/// The code hasher selected at build time: the poseidon code hash with the
+/// `scroll` feature, and the keccak code hash otherwise.
+pub fn default_code_hasher() -> Arc<dyn CodeHasher> {
+    #[cfg(feature = "scroll")]
+    return Arc::new(PoseidonCodeHasher);
+    #[cfg(not(feature = "scroll"))]
+    return Arc::new(KeccakCodeHasher);
+}
+
+/// Code hash of the [`default_code_hasher`]
 pub fn hash_code(code: &[u8]) -> Hash {
     #[cfg(feature = "scroll")]
     return hash_code_poseidon(code);
@@ -75,6 +116,17 @@ fn test_empty_code_hash() {
     assert_eq!(*crate::KECCAK_CODE_HASH_EMPTY, hash_code_keccak(&[]));
 }
 
+#[test]
+fn code_hashers() {
+    let code = [1u8; 40];
+    assert_eq!(KeccakCodeHasher.hash_code(&code), hash_code_keccak(&code));
+    assert_eq!(
+        PoseidonCodeHasher.hash_code(&code),
+        hash_code_poseidon(&code)
+    );
+    assert_eq!(default_code_hasher().hash_code(&code), hash_code(&code));
+}
+
 #[cfg(feature = "scroll")]
 #[test]
 fn code_hashing() {
@@ -58,7 +58,35 @@ impl Default for LoggerConfig {
 }
 
 impl LoggerConfig {
-    #[cfg(feature = "enable-memory")]
+    /// Capture only what the circuit input builder needs: no memory, stack
+    /// nor storage.
+    pub fn minimal() -> Self {
+        Self {
+            enable_memory: false,
+            disable_stack: true,
+            disable_storage: true,
+            enable_return_data: true,
+        }
+    }
+
+    /// Capture the parts of the steps kept by the enabled `enable-memory`,
+    /// `enable-stack` and `enable-storage` features, like [`Self::default`].
+    pub fn standard() -> Self {
+        Self::default()
+    }
+
+    /// Capture everything, which requires the `enable-memory`, `enable-stack`
+    /// and `enable-storage` features.
+    pub fn full() -> Self {
+        Self {
+            enable_memory: true,
+            disable_stack: false,
+            disable_storage: false,
+            enable_return_data: true,
+        }
+    }
+
+    /// Capture the memory, which requires the `enable-memory` feature.
     pub fn enable_memory() -> Self {
         Self {
             enable_memory: true,
@@ -72,6 +100,37 @@ impl LoggerConfig {
             ..Self::default()
         }
     }
+
+    /// Check that the parts of the steps to capture are kept once the trace is
+    /// deserialized, i.e. that the matching `enable-*` features are enabled,
+    /// so that a test relying on them fails clearly instead of seeing empty
+    /// values.
+    pub fn check_supported(&self) -> Result<(), Error> {
+        for (captured, supported, feature) in [
+            (
+                self.enable_memory,
+                cfg!(feature = "enable-memory"),
+                "enable-memory",
+            ),
+            (
+                !self.disable_stack,
+                cfg!(feature = "enable-stack"),
+                "enable-stack",
+            ),
+            (
+                !self.disable_storage,
+                cfg!(feature = "enable-storage"),
+                "enable-storage",
+            ),
+        ] {
+            if captured && !supported {
+                return Err(Error::TracingError(format!(
+                    "the logger config {self:?} requires the `{feature}` feature"
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Configuration structure for `params.ChainConfig`
@@ -125,3 +184,67 @@ pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
         .map(From::from)
         .collect::<Vec<_>>())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn captured(config: &LoggerConfig) -> (bool, bool, bool, bool) {
+        (
+            config.enable_memory,
+            !config.disable_stack,
+            !config.disable_storage,
+            config.enable_return_data,
+        )
+    }
+
+    #[test]
+    fn logger_config_presets() {
+        assert_eq!(
+            captured(&LoggerConfig::minimal()),
+            (false, false, false, true)
+        );
+        assert_eq!(
+            captured(&LoggerConfig::standard()),
+            captured(&LoggerConfig::default())
+        );
+        assert_eq!(
+            captured(&LoggerConfig::default()),
+            (
+                cfg!(feature = "enable-memory"),
+                cfg!(feature = "enable-stack"),
+                cfg!(feature = "enable-storage"),
+                true
+            )
+        );
+        assert_eq!(captured(&LoggerConfig::full()), (true, true, true, true));
+        assert!(LoggerConfig::enable_memory().enable_memory);
+        assert!(!LoggerConfig::enable_stack().disable_stack);
+    }
+
+    #[test]
+    fn logger_config_check_supported() {
+        assert!(LoggerConfig::minimal().check_supported().is_ok());
+        assert!(LoggerConfig::standard().check_supported().is_ok());
+
+        let full = LoggerConfig::full().check_supported();
+        let all_features = cfg!(all(
+            feature = "enable-memory",
+            feature = "enable-stack",
+            feature = "enable-storage"
+        ));
+        assert_eq!(full.is_ok(), all_features);
+
+        let memory = LoggerConfig::enable_memory().check_supported();
+        if cfg!(feature = "enable-memory") {
+            assert!(memory.is_ok());
+        } else {
+            match memory {
+                Err(Error::TracingError(err)) => {
+                    assert!(err.contains("`enable-memory`"), "{err}")
+                }
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+    }
+}
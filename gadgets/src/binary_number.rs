@@ -34,6 +34,110 @@ where
     }
 }
 
+/// Enum tags whose bit encoding is derived at compile time by [`binary_number_tag`].
+///
+/// [`binary_number_tag`]: crate::binary_number_tag
+pub trait BinaryNumberTag: Copy + Into<usize> + IntoEnumIterator + 'static {
+    /// Minimum number of bits needed to represent every variant.
+    const N_BITS: usize;
+    /// All variants, in declaration order.
+    const VARIANTS: &'static [Self];
+}
+
+/// Returns the minimum number of bits needed to represent every one of `tags`.
+pub const fn n_bits_for_tags(tags: &[usize]) -> usize {
+    let mut max = 0;
+    let mut i = 0;
+    while i < tags.len() {
+        if tags[i] > max {
+            max = tags[i];
+        }
+        i += 1;
+    }
+    if max == 0 {
+        1
+    } else {
+        (usize::BITS - max.leading_zeros()) as usize
+    }
+}
+
+/// Declares a fieldless enum and derives its binary number encoding from the
+/// variant discriminants, so that the bit width used by a [`BinaryNumberConfig`]
+/// can't drift from the enum definition. Besides the enum itself this implements
+/// [`BinaryNumberTag`], an inherent `N_BITS` constant and `From<T> for usize`.
+///
+/// ```
+/// gadgets::binary_number_tag! {
+///     #[derive(Clone, Copy, Debug, PartialEq, Eq, strum_macros::EnumIter)]
+///     pub enum Tag {
+///         Start = 1,
+///         Middle,
+///         End,
+///     }
+/// }
+/// assert_eq!(Tag::N_BITS, 2);
+/// ```
+#[macro_export]
+macro_rules! binary_number_tag {
+    (
+        $(#[$attr:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$variant_attr:meta])*
+                $variant:ident $(= $discriminant:expr)?
+            ),+ $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis enum $name {
+            $(
+                $(#[$variant_attr])*
+                $variant $(= $discriminant)?
+            ),+
+        }
+
+        impl $name {
+            /// How many bits are necessary to represent this tag.
+            pub const N_BITS: usize =
+                $crate::binary_number::n_bits_for_tags(&[$($name::$variant as usize),+]);
+        }
+
+        impl $crate::binary_number::BinaryNumberTag for $name {
+            const N_BITS: usize = $name::N_BITS;
+            const VARIANTS: &'static [Self] = &[$($name::$variant),+];
+        }
+
+        impl From<$name> for usize {
+            fn from(t: $name) -> Self {
+                t as usize
+            }
+        }
+    };
+}
+
+/// Checks the encoding of a [`BinaryNumberTag`] exhaustively: every variant is
+/// listed by its iterator, fits in `N` bits, round-trips through [`AsBits`] and
+/// has a distinct value. Meant to be called from the tests of each tag enum.
+pub fn assert_tag_encoding<T, const N: usize>()
+where
+    T: BinaryNumberTag + std::fmt::Debug + PartialEq,
+{
+    assert_eq!(T::N_BITS, N, "bit width of {}", std::any::type_name::<T>());
+    assert_eq!(T::iter().collect::<Vec<_>>(), T::VARIANTS);
+    let mut seen = BTreeSet::new();
+    for tag in T::iter() {
+        let value: usize = tag.into();
+        assert!(value < 1 << N, "{tag:?} = {value} doesn't fit in {N} bits");
+        assert_eq!(from_bits(&AsBits::<N>::as_bits(&tag)), value, "{tag:?}");
+        assert!(seen.insert(value), "{tag:?} = {value} is used twice");
+    }
+    let max = seen.last().copied().unwrap_or_default();
+    assert!(
+        N == 1 || max >= 1 << (N - 1),
+        "{N} bits is more than needed"
+    );
+}
+
 /// Config for the binary number chip.
 #[derive(Clone, Copy, Debug)]
 pub struct BinaryNumberConfig<T, const N: usize> {
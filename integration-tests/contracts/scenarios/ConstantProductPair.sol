@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: GPL-3.0
+
+pragma solidity >=0.7.0 <0.9.0;
+
+interface IERC20 {
+    function balanceOf(address owner) external view returns (uint256);
+    function transfer(address to, uint256 value) external returns (bool);
+}
+
+/**
+ * @title ConstantProductPair
+ * @dev Uniswap V2 style pair: tokens are sent to the pair before calling
+ * swap, which checks the constant product invariant with a 0.3% fee.
+ */
+contract ConstantProductPair {
+    event Sync(uint112 reserve0, uint112 reserve1);
+    event Swap(
+        address indexed sender,
+        uint256 amount0In,
+        uint256 amount1In,
+        uint256 amount0Out,
+        uint256 amount1Out,
+        address indexed to
+    );
+
+    address public token0;
+    address public token1;
+    uint112 public reserve0;
+    uint112 public reserve1;
+
+    constructor(address _token0, address _token1) {
+        token0 = _token0;
+        token1 = _token1;
+    }
+
+    function sync() public {
+        _update(
+            IERC20(token0).balanceOf(address(this)),
+            IERC20(token1).balanceOf(address(this))
+        );
+    }
+
+    function swap(uint256 amount0Out, uint256 amount1Out, address to) public {
+        require(amount0Out > 0 || amount1Out > 0, "insufficient output amount");
+        require(amount0Out < reserve0 && amount1Out < reserve1, "insufficient liquidity");
+
+        if (amount0Out > 0) require(IERC20(token0).transfer(to, amount0Out), "transfer failed");
+        if (amount1Out > 0) require(IERC20(token1).transfer(to, amount1Out), "transfer failed");
+        uint256 balance0 = IERC20(token0).balanceOf(address(this));
+        uint256 balance1 = IERC20(token1).balanceOf(address(this));
+
+        uint256 amount0In = balance0 > reserve0 - amount0Out ? balance0 - (reserve0 - amount0Out) : 0;
+        uint256 amount1In = balance1 > reserve1 - amount1Out ? balance1 - (reserve1 - amount1Out) : 0;
+        require(amount0In > 0 || amount1In > 0, "insufficient input amount");
+        uint256 balance0Adjusted = balance0 * 1000 - amount0In * 3;
+        uint256 balance1Adjusted = balance1 * 1000 - amount1In * 3;
+        require(
+            balance0Adjusted * balance1Adjusted >= uint256(reserve0) * uint256(reserve1) * 1000**2,
+            "K"
+        );
+
+        _update(balance0, balance1);
+        emit Swap(msg.sender, amount0In, amount1In, amount0Out, amount1Out, to);
+    }
+
+    function _update(uint256 balance0, uint256 balance1) private {
+        require(balance0 <= type(uint112).max && balance1 <= type(uint112).max, "overflow");
+        reserve0 = uint112(balance0);
+        reserve1 = uint112(balance1);
+        emit Sync(reserve0, reserve1);
+    }
+}
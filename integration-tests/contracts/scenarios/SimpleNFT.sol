@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: GPL-3.0
+
+pragma solidity >=0.7.0 <0.9.0;
+
+/**
+ * @title SimpleNFT
+ * @dev Minimal ERC721 token with owner-only minting
+ */
+contract SimpleNFT {
+    event Transfer(address indexed from, address indexed to, uint256 indexed tokenId);
+    event Approval(address indexed owner, address indexed approved, uint256 indexed tokenId);
+
+    address public minter;
+    mapping(uint256 => address) public ownerOf;
+    mapping(address => uint256) public balanceOf;
+    mapping(uint256 => address) public getApproved;
+    mapping(uint256 => string) public tokenURI;
+
+    constructor() {
+        minter = msg.sender;
+    }
+
+    function mint(address to, uint256 tokenId, string calldata uri) public {
+        require(msg.sender == minter, "not minter");
+        require(to != address(0), "mint to zero address");
+        require(ownerOf[tokenId] == address(0), "already minted");
+        ownerOf[tokenId] = to;
+        balanceOf[to] += 1;
+        tokenURI[tokenId] = uri;
+        emit Transfer(address(0), to, tokenId);
+    }
+
+    function approve(address approved, uint256 tokenId) public {
+        require(msg.sender == ownerOf[tokenId], "not owner");
+        getApproved[tokenId] = approved;
+        emit Approval(msg.sender, approved, tokenId);
+    }
+
+    function transferFrom(address from, address to, uint256 tokenId) public {
+        require(ownerOf[tokenId] == from, "wrong owner");
+        require(
+            msg.sender == from || msg.sender == getApproved[tokenId],
+            "not approved"
+        );
+        delete getApproved[tokenId];
+        ownerOf[tokenId] = to;
+        balanceOf[from] -= 1;
+        balanceOf[to] += 1;
+        emit Transfer(from, to, tokenId);
+    }
+}
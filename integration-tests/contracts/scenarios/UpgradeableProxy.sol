@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: GPL-3.0
+
+pragma solidity >=0.7.0 <0.9.0;
+
+/**
+ * @title UpgradeableProxy
+ * @dev ERC1967 style proxy delegating every call to an implementation which
+ * the admin can upgrade.
+ */
+contract UpgradeableProxy {
+    // bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)
+    bytes32 internal constant IMPLEMENTATION_SLOT =
+        0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc;
+    // bytes32(uint256(keccak256("eip1967.proxy.admin")) - 1)
+    bytes32 internal constant ADMIN_SLOT =
+        0xb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103;
+
+    event Upgraded(address indexed implementation);
+
+    constructor(address implementation) {
+        _setImplementation(implementation);
+        bytes32 slot = ADMIN_SLOT;
+        address admin = msg.sender;
+        assembly {
+            sstore(slot, admin)
+        }
+    }
+
+    function upgradeTo(address implementation) external {
+        bytes32 slot = ADMIN_SLOT;
+        address admin;
+        assembly {
+            admin := sload(slot)
+        }
+        require(msg.sender == admin, "not admin");
+        _setImplementation(implementation);
+    }
+
+    function _setImplementation(address implementation) private {
+        bytes32 slot = IMPLEMENTATION_SLOT;
+        assembly {
+            sstore(slot, implementation)
+        }
+        emit Upgraded(implementation);
+    }
+
+    fallback() external payable {
+        bytes32 slot = IMPLEMENTATION_SLOT;
+        assembly {
+            let implementation := sload(slot)
+            calldatacopy(0, 0, calldatasize())
+            let result := delegatecall(gas(), implementation, 0, calldatasize(), 0, 0)
+            returndatacopy(0, 0, returndatasize())
+            switch result
+            case 0 {
+                revert(0, returndatasize())
+            }
+            default {
+                return(0, returndatasize())
+            }
+        }
+    }
+}
+
+/**
+ * @title CounterV1
+ * @dev First implementation behind the proxy
+ */
+contract CounterV1 {
+    uint256 public count;
+
+    function increment() public {
+        count += 1;
+    }
+}
+
+/**
+ * @title CounterV2
+ * @dev Upgraded implementation behind the proxy, sharing the storage layout
+ * of CounterV1
+ */
+contract CounterV2 {
+    uint256 public count;
+
+    function increment() public {
+        count += 2;
+    }
+
+    function reset() public {
+        count = 0;
+    }
+}
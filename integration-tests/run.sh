@@ -2,6 +2,7 @@
 set -e
 
 ARG_DEFAULT_SUDO=
+ARG_DEFAULT_EMBEDDED=
 ARG_DEFAULT_STEPS="setup gendata tests cleanup"
 ARG_DEFAULT_TESTS="rpc circuit_input_builder circuits::mock_prover"
 
@@ -10,6 +11,9 @@ usage() {
         Usage: $0 [OPTIONS]
         Options:
           --sudo         Use sudo for docker compose commands.
+          --embedded     Run a dev node spawned by the dev_node binary instead
+                         of the docker setup. It is stopped when the script
+                         exits, so the setup step must be part of the run.
           --steps ARG    Space separated list of steps to do.
                          Default: "${ARG_DEFAULT_STEPS}".
           --tests ARG    Space separated list of tests to run.
@@ -20,6 +24,7 @@ EOF
 }
 
 ARG_SUDO="${ARG_DEFAULT_SUDO}"
+ARG_EMBEDDED="${ARG_DEFAULT_EMBEDDED}"
 ARG_STEPS="${ARG_DEFAULT_STEPS}"
 ARG_TESTS="${ARG_DEFAULT_TESTS}"
 
@@ -28,6 +33,9 @@ while [ "$1" != "" ]; do
         --sudo )
             ARG_SUDO=1
         ;;
+        --embedded )
+            ARG_EMBEDDED=1
+        ;;
         --steps )
             shift
             ARG_STEPS="$1"
@@ -74,6 +82,11 @@ for step in $ARG_STEPS; do
     esac
 done
 
+if [ -n "$ARG_EMBEDDED" ] && [ -z "$STEP_SETUP" ]; then
+    echo "--embedded needs the setup step, the node only lives during this run"
+    exit 1
+fi
+
 docker_compose_cmd() {
     if [ -n "$ARG_SUDO" ]; then
         sudo docker compose $@
@@ -82,10 +95,41 @@ docker_compose_cmd() {
     fi
 }
 
+# The dev node reads its stdin from a fifo kept open on fd 3, and stops when
+# it is closed.
+start_embedded_node() {
+    NODE_DIR=$(mktemp -d)
+    mkfifo "$NODE_DIR/stdin"
+    cargo run --bin dev_node < "$NODE_DIR/stdin" > "$NODE_DIR/url" &
+    NODE_PID=$!
+    exec 3> "$NODE_DIR/stdin"
+    trap stop_embedded_node EXIT
+    while [ ! -s "$NODE_DIR/url" ]; do
+        if ! kill -0 "$NODE_PID" 2> /dev/null; then
+            echo "the dev node failed to start"
+            exit 1
+        fi
+        sleep 1
+    done
+    GETH0_URL=$(cat "$NODE_DIR/url")
+    export GETH0_URL
+    echo "+ Dev node listening on $GETH0_URL"
+}
+
+stop_embedded_node() {
+    exec 3>&-
+    wait "$NODE_PID" || true
+    rm -rf "$NODE_DIR"
+}
+
 if [ -n "$STEP_SETUP" ]; then
     echo "+ Setup..."
-    docker_compose_cmd down -v --remove-orphans
-    docker_compose_cmd up -d geth0
+    if [ -n "$ARG_EMBEDDED" ]; then
+        start_embedded_node
+    else
+        docker_compose_cmd down -v --remove-orphans
+        docker_compose_cmd up -d geth0
+    fi
 fi
 
 if [ -n "$STEP_GENDATA" ]; then
@@ -102,7 +146,7 @@ if [ -n "$STEP_TESTS" ]; then
     done
 fi
 
-if [ -n "$STEP_CLEANUP" ]; then
+if [ -n "$STEP_CLEANUP" ] && [ -z "$ARG_EMBEDDED" ]; then
     echo "+ Cleanup..."
     docker_compose_cmd down -v --remove-orphans
 fi
@@ -0,0 +1,93 @@
+//! Convert the JSON trace dumps and replays to the compact encoding of the
+//! traces, or back to JSON.
+//!
+//! ```sh
+//! cargo run --release -p integration-tests --bin compact_traces -- <input> <output> [--json]
+//! ```
+//!
+//! The input is a `.replay` file, a `GethExecTrace` or a list of them, as JSON
+//! or in the [compact](eth_types::compact) encoding. It's written to the output
+//! in the compact encoding, or as JSON when `--json` is passed.
+
+use eth_types::{compact, GethExecTrace};
+use integration_tests::{
+    log_init,
+    replay::{Replay, REPLAY_EXTENSION},
+};
+use log::info;
+use std::{env, fs, path::Path, process};
+
+const USAGE: &str = "usage: compact_traces <input> <output> [--json]";
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(2)
+}
+
+/// The traces of a trace dump, and whether it's a single trace.
+fn load_traces(data: &[u8]) -> Result<(Vec<GethExecTrace>, bool), String> {
+    if compact::is_compact(data) {
+        if let Ok(trace) = GethExecTrace::from_compact(data) {
+            return Ok((vec![trace], true));
+        }
+        return compact::decode_traces(data)
+            .map(|traces| (traces, false))
+            .map_err(|err| err.to_string());
+    }
+    let json: serde_json::Value = serde_json::from_slice(data).map_err(|err| err.to_string())?;
+    if json.is_array() {
+        serde_json::from_value(json).map(|traces| (traces, false))
+    } else {
+        serde_json::from_value(json).map(|trace| (vec![trace], true))
+    }
+    .map_err(|err| err.to_string())
+}
+
+fn main() {
+    log_init();
+
+    let mut paths = Vec::new();
+    let mut json = false;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            _ if paths.len() < 2 && !arg.starts_with('-') => paths.push(arg),
+            _ => usage(),
+        }
+    }
+    let [input, output] = paths.as_slice() else {
+        usage()
+    };
+
+    if Path::new(input)
+        .extension()
+        .is_some_and(|ext| ext == REPLAY_EXTENSION)
+    {
+        let replay = Replay::load(input).expect("cannot load replay");
+        if json {
+            replay.save_json(output)
+        } else {
+            replay.save(output)
+        }
+        .expect("cannot write replay");
+    } else {
+        let data = fs::read(input).expect("cannot read input");
+        let (traces, single) = load_traces(&data).unwrap_or_else(|err| {
+            eprintln!("{input} is not a trace or a list of traces: {err}");
+            process::exit(1)
+        });
+        let converted = match (json, single) {
+            (true, true) => serde_json::to_vec(&traces[0]).expect("cannot serialize trace"),
+            (true, false) => serde_json::to_vec(&traces).expect("cannot serialize traces"),
+            (false, true) => traces[0].to_compact().expect("cannot encode trace"),
+            (false, false) => compact::encode_traces(&traces).expect("cannot encode traces"),
+        };
+        fs::write(output, converted).expect("cannot write output");
+    }
+
+    let (before, after) = (
+        fs::metadata(input).map(|m| m.len()).unwrap_or_default(),
+        fs::metadata(output).map(|m| m.len()).unwrap_or_default(),
+    );
+    info!("converted {input} ({before} bytes) to {output} ({after} bytes)");
+}
@@ -0,0 +1,47 @@
+//! Run a dev node in place of the docker setup, until stdin is closed.
+//!
+//! ```sh
+//! cargo run -p integration-tests --bin dev_node [-- anvil]
+//! ```
+//!
+//! The url of the node is printed on stdout once it accepts connections, to
+//! be used as `GETH0_URL`. The node is killed and its data removed when stdin
+//! is closed, which is how `run.sh --embedded` stops it.
+
+use integration_tests::{
+    geth_instance::{GethInstance, NodeKind},
+    log_init,
+};
+use log::info;
+use std::{
+    env,
+    io::{self, Write},
+    process,
+};
+
+const USAGE: &str = "usage: dev_node [geth|anvil]";
+
+fn main() {
+    log_init();
+
+    let kind = match env::args().nth(1).as_deref() {
+        None | Some("geth") => NodeKind::Geth,
+        Some("anvil") => NodeKind::Anvil,
+        Some(_) => {
+            eprintln!("{USAGE}");
+            process::exit(2)
+        }
+    };
+    let node = GethInstance::spawn_kind(kind).unwrap_or_else(|e| {
+        eprintln!("cannot spawn the {kind:?} dev node: {e}");
+        process::exit(1)
+    });
+
+    let mut stdout = io::stdout();
+    writeln!(stdout, "{}", node.url()).expect("cannot write the node url");
+    stdout.flush().expect("cannot write the node url");
+
+    // the node lives as long as stdin is open
+    io::copy(&mut io::stdin(), &mut io::sink()).ok();
+    info!("stdin closed, stopping the {kind:?} dev node");
+}
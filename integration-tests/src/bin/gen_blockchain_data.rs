@@ -11,7 +11,6 @@ use ethers::{
     middleware::SignerMiddleware,
     providers::{Middleware, PendingTransaction},
     signers::Signer,
-    solc::Solc,
 };
 use integration_tests::{
     get_client, get_provider, get_wallet, log_init, CompiledContract, GenDataOutput, CONTRACTS,
@@ -78,26 +77,7 @@ async fn main() {
     let mut contracts = HashMap::new();
     for (name, contract_path) in CONTRACTS {
         let path_sol = Path::new(CONTRACTS_PATH).join(contract_path);
-        let compiled = Solc::default()
-            .compile_source(&path_sol)
-            .unwrap_or_else(|_| panic!("solc compile error {path_sol:?}",));
-        if !compiled.errors.is_empty() {
-            panic!("Errors compiling {:?}:\n{:#?}", &path_sol, compiled.errors)
-        }
-
-        let contract = compiled
-            .get(path_sol.to_str().expect("path is not str"), name)
-            .expect("contract not found");
-        let abi = contract.abi.expect("no abi found").clone();
-        let bin = contract.bin.expect("no bin found").clone();
-        let bin_runtime = contract.bin_runtime.expect("no bin_runtime found").clone();
-        let compiled_contract = CompiledContract {
-            path: path_sol.to_str().expect("path is not str").to_string(),
-            name: name.to_string(),
-            abi,
-            bin: bin.into_bytes().expect("bin"),
-            bin_runtime: bin_runtime.into_bytes().expect("bin_runtime"),
-        };
+        let compiled_contract = CompiledContract::compile(&path_sol, name);
 
         let mut path_json = path_sol.clone();
         path_json.set_extension("json");
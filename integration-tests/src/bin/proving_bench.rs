@@ -0,0 +1,119 @@
+//! Prove the canned blocks with every circuit and write a machine-readable
+//! report of the proving time and peak memory, to track them across
+//! releases.
+//!
+//! Environment variables:
+//! - `BENCH_CIRCUITS`: comma separated list of circuits to prove, e.g. `EVM,State`. Defaults to all
+//!   of them.
+//! - `BENCH_LABEL`: label stored in the report, e.g. a git revision.
+//! - `BENCH_REPORT`: path of the report. Defaults to `proving_bench_report.json`.
+
+use halo2_proofs::{halo2curves::bn256::Fr, plonk::Circuit};
+use integration_tests::{
+    canned_blocks::canned_blocks,
+    integration_test_circuits::{
+        block_from_geth_data, IntegrationTest, ProvingStats, BYTECODE_CIRCUIT_TEST,
+        COPY_CIRCUIT_TEST, EVM_CIRCUIT_TEST, EXP_CIRCUIT_TEST, KECCAK_CIRCUIT_TEST,
+        STATE_CIRCUIT_TEST, SUPER_CIRCUIT_TEST, TX_CIRCUIT_TEST,
+    },
+    log_init,
+};
+use log::info;
+use serde::Serialize;
+use std::{env, fs};
+use tokio::sync::Mutex as TokioMutex;
+use zkevm_circuits::{util::SubCircuit, witness::Block};
+
+const BENCH_REPORT_DEFAULT: &str = "proving_bench_report.json";
+
+/// Measurements of all the circuits on a canned block.
+#[derive(Serialize)]
+struct BlockReport {
+    block: &'static str,
+    circuits: Vec<ProvingStats>,
+}
+
+#[derive(Serialize)]
+struct Report {
+    version: &'static str,
+    label: Option<String>,
+    blocks: Vec<BlockReport>,
+}
+
+async fn bench_circuit<C: SubCircuit<Fr> + Circuit<Fr>>(
+    name: &str,
+    test: &TokioMutex<IntegrationTest<C>>,
+    block: &Block,
+    selected: &Option<Vec<String>>,
+    stats: &mut Vec<ProvingStats>,
+) {
+    if let Some(selected) = selected {
+        if !selected
+            .iter()
+            .any(|circuit| circuit.eq_ignore_ascii_case(name))
+        {
+            return;
+        }
+    }
+    stats.push(test.lock().await.bench_block(block));
+}
+
+#[tokio::main]
+async fn main() {
+    log_init();
+
+    let selected: Option<Vec<String>> = env::var("BENCH_CIRCUITS")
+        .ok()
+        .map(|circuits| circuits.split(',').map(|c| c.trim().to_string()).collect());
+    let report_path = env::var("BENCH_REPORT").unwrap_or_else(|_| BENCH_REPORT_DEFAULT.into());
+
+    let mut report = Report {
+        version: env!("CARGO_PKG_VERSION"),
+        label: env::var("BENCH_LABEL").ok(),
+        blocks: Vec::new(),
+    };
+    for (name, geth_data) in canned_blocks() {
+        info!("proving canned block {name}");
+        let block = block_from_geth_data(&geth_data);
+        let mut stats = Vec::new();
+        bench_circuit("EVM", &EVM_CIRCUIT_TEST, &block, &selected, &mut stats).await;
+        bench_circuit("State", &STATE_CIRCUIT_TEST, &block, &selected, &mut stats).await;
+        bench_circuit("Tx", &TX_CIRCUIT_TEST, &block, &selected, &mut stats).await;
+        bench_circuit(
+            "Bytecode",
+            &BYTECODE_CIRCUIT_TEST,
+            &block,
+            &selected,
+            &mut stats,
+        )
+        .await;
+        bench_circuit("Copy", &COPY_CIRCUIT_TEST, &block, &selected, &mut stats).await;
+        bench_circuit(
+            "Keccak",
+            &KECCAK_CIRCUIT_TEST,
+            &block,
+            &selected,
+            &mut stats,
+        )
+        .await;
+        bench_circuit("Exp", &EXP_CIRCUIT_TEST, &block, &selected, &mut stats).await;
+        bench_circuit("Super", &SUPER_CIRCUIT_TEST, &block, &selected, &mut stats).await;
+        for stat in &stats {
+            info!(
+                "{name}: {} circuit proved in {} ms, peak memory {:?} kB",
+                stat.circuit, stat.proving_ms, stat.peak_memory_kb
+            );
+        }
+        report.blocks.push(BlockReport {
+            block: name,
+            circuits: stats,
+        });
+    }
+
+    fs::write(
+        &report_path,
+        serde_json::to_vec_pretty(&report).expect("cannot serialize report"),
+    )
+    .expect("cannot write report");
+    info!("report written to {report_path}");
+}
@@ -0,0 +1,92 @@
+//! Reproduce an integration test failure from its `.replay` file, without a
+//! geth node.
+//!
+//! ```sh
+//! cargo run --release -p integration-tests --bin replay -- <file.replay> [--actual] [--circuit <name>]
+//! ```
+//!
+//! The witness of the recorded block is built with the recorded circuit
+//! parameters, then the failed circuit is run with the mock prover, or with
+//! the real prover when `--actual` is passed. `--circuit` overrides the
+//! recorded circuit. Without a circuit, only the witness is built.
+
+use halo2_proofs::{halo2curves::bn256::Fr, plonk::Circuit};
+use integration_tests::{
+    integration_test_circuits::{
+        IntegrationTest, BYTECODE_CIRCUIT_TEST, COPY_CIRCUIT_TEST, EVM_CIRCUIT_TEST,
+        EXP_CIRCUIT_TEST, KECCAK_CIRCUIT_TEST, STATE_CIRCUIT_TEST, SUPER_CIRCUIT_TEST,
+        TX_CIRCUIT_TEST,
+    },
+    log_init,
+    replay::Replay,
+};
+use log::info;
+use std::{env, process};
+use tokio::sync::Mutex as TokioMutex;
+use zkevm_circuits::{util::SubCircuit, witness::Block};
+
+const USAGE: &str = "usage: replay <file.replay> [--actual] [--circuit <name>]";
+
+async fn run<C: SubCircuit<Fr> + Circuit<Fr>>(
+    test: &TokioMutex<IntegrationTest<C>>,
+    block: &Block,
+    actual: bool,
+) {
+    test.lock().await.test_block(block, actual);
+}
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(2)
+}
+
+#[tokio::main]
+async fn main() {
+    log_init();
+
+    let mut path = None;
+    let mut actual = false;
+    let mut circuit = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--actual" => actual = true,
+            "--circuit" => circuit = Some(args.next().unwrap_or_else(|| usage())),
+            _ if path.is_none() && !arg.starts_with('-') => path = Some(arg),
+            _ => usage(),
+        }
+    }
+    let path = path.unwrap_or_else(|| usage());
+
+    let replay = Replay::load(&path).expect("cannot load replay");
+    info!(
+        "replaying block #{} from {path}, recorded failure: {:?}",
+        replay.block_number(),
+        replay.error
+    );
+    let block = replay.block().expect("cannot build the witness");
+
+    let circuit = match circuit.or(replay.circuit) {
+        Some(circuit) => circuit,
+        None => {
+            info!("no circuit recorded, witness built");
+            return;
+        }
+    };
+    info!("testing {circuit} circuit, actual prover: {actual}");
+    match circuit.to_ascii_lowercase().as_str() {
+        "evm" => run(&EVM_CIRCUIT_TEST, &block, actual).await,
+        "state" => run(&STATE_CIRCUIT_TEST, &block, actual).await,
+        "tx" => run(&TX_CIRCUIT_TEST, &block, actual).await,
+        "bytecode" => run(&BYTECODE_CIRCUIT_TEST, &block, actual).await,
+        "copy" => run(&COPY_CIRCUIT_TEST, &block, actual).await,
+        "keccak" => run(&KECCAK_CIRCUIT_TEST, &block, actual).await,
+        "exp" => run(&EXP_CIRCUIT_TEST, &block, actual).await,
+        "super" => run(&SUPER_CIRCUIT_TEST, &block, actual).await,
+        _ => {
+            eprintln!("unknown circuit {circuit}");
+            process::exit(2)
+        }
+    }
+    info!("{circuit} circuit passed");
+}
@@ -0,0 +1,243 @@
+//! Fixed blocks used to benchmark proving.
+//!
+//! The blocks are built with the mock tracer so they don't depend on a geth
+//! node, and their contracts are hand-written to reproduce the execution
+//! pattern of the real ones (storage updates, nested calls, logs) without
+//! requiring solc.
+
+use eth_types::{bytecode, geth_types::GethData, Address, Bytecode, ToBigEndian, ToWord, Word};
+use ethers::utils::keccak256;
+use mock::{eth, TestContext, MOCK_ACCOUNTS};
+
+/// Number of transfers in the ERC20 block.
+const ERC20_TRANSFERS: usize = 3;
+/// Size of the calldata of the calldata-heavy transaction.
+const CALLDATA_SIZE: usize = 1024;
+/// Initial token balance of every holder.
+const INITIAL_TOKEN_BALANCE: u64 = 1_000_000_000;
+/// Initial reserves of the swap pair.
+const INITIAL_RESERVES: (u64, u64) = (1_000_000, 2_000_000);
+
+fn event_topic(signature: &str) -> Word {
+    Word::from_big_endian(&keccak256(signature.as_bytes()))
+}
+
+/// Token contract with a single method `transfer(to, amount)`, taking its
+/// arguments as two calldata words without selector. Balances are stored at
+/// the slot of the holder address.
+fn token_code() -> Bytecode {
+    bytecode! {
+        // balances[caller] -= amount
+        PUSH1(0x20)
+        CALLDATALOAD
+        CALLER
+        SLOAD
+        SUB
+        CALLER
+        SSTORE
+        // balances[to] += amount
+        PUSH1(0x20)
+        CALLDATALOAD
+        PUSH1(0)
+        CALLDATALOAD
+        SLOAD
+        ADD
+        PUSH1(0)
+        CALLDATALOAD
+        SSTORE
+        // emit Transfer(caller, to, amount)
+        PUSH1(0x20)
+        CALLDATALOAD
+        PUSH1(0)
+        MSTORE
+        PUSH1(0)
+        CALLDATALOAD
+        CALLER
+        PUSH32(event_topic("Transfer(address,address,uint256)"))
+        PUSH1(0x20)
+        PUSH1(0)
+        LOG3
+        STOP
+    }
+}
+
+/// Constant product pair contract with a single method `swap(amount_in)`,
+/// which updates the reserves stored at slots 0 and 1 and pays out with a
+/// token transfer.
+fn pair_code(token: Address) -> Bytecode {
+    bytecode! {
+        // amount_out = amount_in * 997 * reserve1 / (reserve0 * 1000 + amount_in * 997)
+        PUSH1(0)
+        CALLDATALOAD
+        PUSH2(997)
+        MUL
+        DUP1
+        PUSH1(0)
+        SLOAD
+        PUSH2(1000)
+        MUL
+        ADD
+        SWAP1
+        PUSH1(1)
+        SLOAD
+        MUL
+        DIV
+        // reserve0 += amount_in
+        PUSH1(0)
+        CALLDATALOAD
+        PUSH1(0)
+        SLOAD
+        ADD
+        PUSH1(0)
+        SSTORE
+        // reserve1 -= amount_out
+        DUP1
+        PUSH1(1)
+        SLOAD
+        SUB
+        PUSH1(1)
+        SSTORE
+        // token.transfer(caller, amount_out)
+        DUP1
+        PUSH1(0x20)
+        MSTORE
+        CALLER
+        PUSH1(0)
+        MSTORE
+        PUSH1(0)
+        PUSH1(0)
+        PUSH1(0x40)
+        PUSH1(0)
+        PUSH1(0)
+        PUSH20(token)
+        GAS
+        CALL
+        POP
+        // emit Swap(caller, amount_in, amount_out)
+        PUSH1(0)
+        CALLDATALOAD
+        PUSH1(0)
+        MSTORE
+        CALLER
+        PUSH32(event_topic("Swap(address,uint256,uint256)"))
+        PUSH1(0x40)
+        PUSH1(0)
+        LOG2
+        STOP
+    }
+}
+
+/// Contract copying its calldata to memory and storing its hash.
+fn calldata_hash_code() -> Bytecode {
+    bytecode! {
+        CALLDATASIZE
+        PUSH1(0)
+        PUSH1(0)
+        CALLDATACOPY
+        CALLDATASIZE
+        PUSH1(0)
+        SHA3
+        PUSH1(0)
+        SSTORE
+        STOP
+    }
+}
+
+fn words_calldata(words: &[Word]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_be_bytes()).collect()
+}
+
+/// Block with several ERC20 transfers between different holders.
+pub fn erc20_transfer_block() -> GethData {
+    let token = MOCK_ACCOUNTS[0];
+    let holders = &MOCK_ACCOUNTS[1..=ERC20_TRANSFERS + 1];
+    TestContext::<{ ERC20_TRANSFERS + 2 }, ERC20_TRANSFERS>::new(
+        None,
+        |accs| {
+            accs[0].address(token).code(token_code()).storage(
+                holders
+                    .iter()
+                    .map(|holder| (holder.to_word(), Word::from(INITIAL_TOKEN_BALANCE))),
+            );
+            for (acc, holder) in accs[1..].iter_mut().zip(holders) {
+                acc.address(*holder).balance(eth(10));
+            }
+        },
+        |txs, accs| {
+            for (i, tx) in txs.into_iter().enumerate() {
+                let to = accs[i + 2].address;
+                tx.from(accs[i + 1].address)
+                    .to(token)
+                    .input(words_calldata(&[to.to_word(), Word::from(1000 * (i + 1))]).into());
+            }
+        },
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .into()
+}
+
+/// Block with a swap on a constant product pair, which pays out by calling
+/// the token contract.
+pub fn swap_block() -> GethData {
+    let token = MOCK_ACCOUNTS[0];
+    let pair = MOCK_ACCOUNTS[1];
+    let trader = MOCK_ACCOUNTS[2];
+    TestContext::<3, 1>::new(
+        None,
+        |accs| {
+            accs[0]
+                .address(token)
+                .code(token_code())
+                .storage([(pair.to_word(), Word::from(INITIAL_RESERVES.1))].into_iter());
+            accs[1].address(pair).code(pair_code(token)).storage(
+                [
+                    (Word::zero(), Word::from(INITIAL_RESERVES.0)),
+                    (Word::one(), Word::from(INITIAL_RESERVES.1)),
+                ]
+                .into_iter(),
+            );
+            accs[2].address(trader).balance(eth(10));
+        },
+        |mut txs, _accs| {
+            txs[0]
+                .from(trader)
+                .to(pair)
+                .input(words_calldata(&[Word::from(10_000u64)]).into());
+        },
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .into()
+}
+
+/// Block with a transaction carrying a large calldata, which is copied to
+/// memory and hashed.
+pub fn calldata_heavy_block() -> GethData {
+    let calldata: Vec<u8> = (0..CALLDATA_SIZE).map(|i| (i % 255 + 1) as u8).collect();
+    TestContext::<2, 1>::new(
+        None,
+        |accs| {
+            accs[0].address(MOCK_ACCOUNTS[0]).code(calldata_hash_code());
+            accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+        },
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[1].address)
+                .to(accs[0].address)
+                .input(calldata.into());
+        },
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .into()
+}
+
+/// All the canned blocks, with their names.
+pub fn canned_blocks() -> Vec<(&'static str, GethData)> {
+    vec![
+        ("erc20_transfer", erc20_transfer_block()),
+        ("swap", swap_block()),
+        ("calldata_heavy", calldata_heavy_block()),
+    ]
+}
@@ -0,0 +1,289 @@
+//! Dev-mode node managed as a child process, so that tests can talk to a node
+//! without the docker setup.
+//!
+//! The node binary is looked up in this order:
+//! - `GETH_BIN` (or `ANVIL_BIN` for anvil) env var,
+//! - the `PATH`,
+//! - for geth only, a release downloaded into `GETH_CACHE_DIR` (defaults to a directory in the
+//!   system temp dir), using the same version as the docker setup. The archive must match the
+//!   sha256 digest given by the `GETH_SHA256` env var, else it is rejected. [`GETH_SHA256`] lists
+//!   no digest yet, so the env var is required to download geth.
+
+use crate::CHAIN_ID;
+use bus_mapping::rpc::GethClient;
+use ethers::providers::{Http, Provider};
+use std::{
+    env, fs, io,
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+use url::Url;
+
+/// Geth release downloaded when no geth binary is found, matching the
+/// docker-compose setup.
+const GETH_VERSION: &str = "1.11.5-a38f4108";
+const GETH_DOWNLOAD_BASE_URL: &str = "https://gethstore.blob.core.windows.net/builds";
+/// sha256 digests of the [`GETH_VERSION`] release archives, by platform, as
+/// listed on <https://geth.ethereum.org/downloads>. It is empty until the
+/// digests are checked against that page, so every platform needs its digest
+/// given by the `GETH_SHA256` env var to download geth.
+pub const GETH_SHA256: &[(&str, &str)] = &[];
+/// Time to wait for the node to accept connections.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Kind of dev node to spawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// geth in `--dev` mode
+    Geth,
+    /// foundry's anvil
+    Anvil,
+}
+
+/// A dev-mode node running as a child process on a free local port. The node
+/// is killed and its data removed when the instance is dropped.
+#[derive(Debug)]
+pub struct GethInstance {
+    kind: NodeKind,
+    child: Child,
+    port: u16,
+    data_dir: Option<PathBuf>,
+}
+
+impl GethInstance {
+    /// Spawn a geth dev node.
+    pub fn spawn() -> io::Result<Self> {
+        Self::spawn_kind(NodeKind::Geth)
+    }
+
+    /// Spawn a dev node of the given kind.
+    pub fn spawn_kind(kind: NodeKind) -> io::Result<Self> {
+        let port = free_port()?;
+        let (mut command, data_dir) = match kind {
+            NodeKind::Geth => {
+                let data_dir =
+                    env::temp_dir().join(format!("zkevm-geth-{}-{port}", std::process::id()));
+                let mut command = Command::new(geth_bin()?);
+                command
+                    .args(["--dev", "--vmdebug", "--gcmode=archive", "--nodiscover"])
+                    .args(["--port", "0", "--authrpc.port"])
+                    .arg(free_port()?.to_string())
+                    .arg("--datadir")
+                    .arg(&data_dir)
+                    .args(["--http", "--http.addr", "127.0.0.1", "--http.port"])
+                    .arg(port.to_string())
+                    .args(["--http.api", "eth,net,web3,personal,txpool,miner,debug"]);
+                (command, Some(data_dir))
+            }
+            NodeKind::Anvil => {
+                let mut command = Command::new(env::var("ANVIL_BIN").unwrap_or("anvil".into()));
+                command
+                    .args(["--host", "127.0.0.1", "--steps-tracing", "--port"])
+                    .arg(port.to_string())
+                    .args(["--chain-id", &CHAIN_ID.to_string()]);
+                (command, None)
+            }
+        };
+        let child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        log::info!("spawned {kind:?} dev node on port {port}");
+
+        let mut instance = Self {
+            kind,
+            child,
+            port,
+            data_dir,
+        };
+        instance.wait_ready()?;
+        Ok(instance)
+    }
+
+    /// Kind of the node.
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    /// HTTP RPC port of the node.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// HTTP RPC url of the node.
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://127.0.0.1:{}", self.port)).expect("valid url")
+    }
+
+    /// [`GethClient`] connected to the node.
+    pub fn client(&self) -> GethClient<Http> {
+        GethClient::new(Http::new(self.url())).with_streaming(self.url())
+    }
+
+    /// [`Provider`] connected to the node.
+    pub fn provider(&self) -> Provider<Http> {
+        Provider::new(Http::new(self.url())).interval(Duration::from_millis(100))
+    }
+
+    fn wait_ready(&mut self) -> io::Result<()> {
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, self.port));
+        let start = Instant::now();
+        loop {
+            if let Some(status) = self.child.try_wait()? {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("{:?} dev node exited with {status}", self.kind),
+                ));
+            }
+            if TcpStream::connect_timeout(&addr, Duration::from_millis(100)).is_ok() {
+                return Ok(());
+            }
+            if start.elapsed() > STARTUP_TIMEOUT {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!(
+                        "{:?} dev node did not start on port {}",
+                        self.kind, self.port
+                    ),
+                ));
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+impl Drop for GethInstance {
+    fn drop(&mut self) {
+        if let Err(e) = self.child.kill() {
+            log::warn!("cannot kill {:?} dev node: {e}", self.kind);
+        }
+        let _ = self.child.wait();
+        if let Some(data_dir) = &self.data_dir {
+            let _ = fs::remove_dir_all(data_dir);
+        }
+    }
+}
+
+/// Let the OS pick a free port. The port may in theory be taken again before
+/// the node binds it, which is not a concern for tests.
+fn free_port() -> io::Result<u16> {
+    Ok(TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?
+        .local_addr()?
+        .port())
+}
+
+fn is_runnable(bin: &str) -> bool {
+    Command::new(bin)
+        .arg("version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+fn geth_bin() -> io::Result<PathBuf> {
+    if let Ok(bin) = env::var("GETH_BIN") {
+        return Ok(bin.into());
+    }
+    if is_runnable("geth") {
+        return Ok("geth".into());
+    }
+    download_geth()
+}
+
+/// Download the geth release into the cache dir, unless already there.
+fn download_geth() -> io::Result<PathBuf> {
+    let platform = match (env::consts::OS, env::consts::ARCH) {
+        ("linux", "x86_64") => "linux-amd64",
+        ("linux", "aarch64") => "linux-arm64",
+        ("macos", "x86_64") => "darwin-amd64",
+        (os, arch) => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("no geth release for {os}-{arch}, set GETH_BIN"),
+            ))
+        }
+    };
+    let cache_dir = env::var("GETH_CACHE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| env::temp_dir().join("zkevm-geth"));
+    let release = format!("geth-{platform}-{GETH_VERSION}");
+    let bin = cache_dir.join(&release).join("geth");
+    if bin.exists() {
+        return Ok(bin);
+    }
+
+    let digest = match GETH_SHA256.iter().find(|(p, _)| *p == platform) {
+        Some((_, digest)) => digest.to_string(),
+        None => env::var("GETH_SHA256").map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no known digest for {release}, set GETH_SHA256 or GETH_BIN"),
+            )
+        })?,
+    };
+
+    fs::create_dir_all(&cache_dir)?;
+    let url = format!("{GETH_DOWNLOAD_BASE_URL}/{release}.tar.gz");
+    log::info!("downloading geth from {url}");
+    let archive = cache_dir.join(format!("{release}.tar.gz"));
+    run(Command::new("curl")
+        .args(["-sSfL", "-o"])
+        .arg(&archive)
+        .arg(&url))?;
+    let actual = sha256_file(&archive)?;
+    if !actual.eq_ignore_ascii_case(digest.trim()) {
+        fs::remove_file(&archive)?;
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{release}.tar.gz has sha256 {actual}, expected {digest}"),
+        ));
+    }
+    run(Command::new("tar")
+        .arg("-xzf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(&cache_dir))?;
+    fs::remove_file(&archive)?;
+    Ok(bin)
+}
+
+/// sha256 digest of the file in hex, computed by the `sha256sum` tool, or by
+/// `shasum` where it is missing like on macOS.
+fn sha256_file(path: &Path) -> io::Result<String> {
+    let output = match Command::new("sha256sum").arg(path).output() {
+        Ok(output) => output,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Command::new("shasum")
+            .args(["-a", "256"])
+            .arg(path)
+            .output()?,
+        Err(e) => return Err(e),
+    };
+    if !output.status.success() {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("cannot hash {}", path.display()),
+        ));
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .next()
+        .map(str::to_string)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "empty sha256 output"))
+}
+
+fn run(command: &mut Command) -> io::Result<()> {
+    let status = command.status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("{command:?} failed with {status}"),
+        ))
+    }
+}
@@ -1,7 +1,11 @@
-use crate::{get_client, GenDataOutput};
+use crate::{
+    get_client,
+    replay::{panic_message, Replay},
+    GenDataOutput,
+};
 use bus_mapping::{
     circuit_input_builder::{
-        BuilderClient, CircuitInputBuilder, CircuitsParams, PrecompileEcParams,
+        BuilderClient, CircuitInputBuilder, CircuitsParams, PrecompileEcParams, N_PAIRING_PER_OP,
     },
     mock::BlockData,
 };
@@ -26,10 +30,15 @@ use mock::TestContext;
 use rand_chacha::rand_core::SeedableRng;
 use rand_core::RngCore;
 use rand_xorshift::XorShiftRng;
+use serde::Serialize;
 use std::{
+    any::Any,
     collections::HashMap,
+    fs,
     marker::PhantomData,
+    panic::{self, AssertUnwindSafe},
     sync::{LazyLock, Mutex},
+    time::Instant,
 };
 use tokio::sync::Mutex as TokioMutex;
 use zkevm_circuits::{
@@ -79,7 +88,8 @@ const MAX_EC_MUL: usize = 10;
 /// Max number of EcPairing ops.
 const MAX_EC_PAIRING: usize = 4;
 
-const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
+/// Circuit parameters of the integration tests.
+pub const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_rws: MAX_RWS,
     max_txs: MAX_TXS,
     max_calldata: MAX_CALLDATA,
@@ -97,6 +107,7 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
         ec_add: MAX_EC_ADD,
         ec_mul: MAX_EC_MUL,
         ec_pairing: MAX_EC_PAIRING,
+        ec_pairing_pairs: N_PAIRING_PER_OP,
     },
 };
 
@@ -197,60 +208,6 @@ impl<C: SubCircuit<Fr> + Circuit<Fr>> IntegrationTest<C> {
     }
 
     fn test_actual(&self, circuit: C, instance: Vec<Vec<Fr>>, proving_key: ProvingKey<G1Affine>) {
-        fn test_gen_proof<C: Circuit<Fr>, R: RngCore>(
-            rng: R,
-            circuit: C,
-            general_params: &ParamsKZG<Bn256>,
-            proving_key: &ProvingKey<G1Affine>,
-            mut transcript: Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
-            instances: &[&[Fr]],
-        ) -> Vec<u8> {
-            create_proof::<
-                KZGCommitmentScheme<Bn256>,
-                ProverSHPLONK<'_, Bn256>,
-                Challenge255<G1Affine>,
-                R,
-                Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
-                C,
-            >(
-                general_params,
-                proving_key,
-                &[circuit],
-                &[instances],
-                rng,
-                &mut transcript,
-            )
-            .expect("proof generation should not fail");
-
-            transcript.finalize()
-        }
-
-        fn test_verify(
-            general_params: &ParamsKZG<Bn256>,
-            verifier_params: &ParamsKZG<Bn256>,
-            verifying_key: &VerifyingKey<G1Affine>,
-            proof: &[u8],
-            instances: &[&[Fr]],
-        ) {
-            let mut verifier_transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
-            let strategy = SingleStrategy::new(general_params);
-
-            verify_proof::<
-                KZGCommitmentScheme<Bn256>,
-                VerifierSHPLONK<'_, Bn256>,
-                Challenge255<G1Affine>,
-                Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
-                SingleStrategy<'_, Bn256>,
-            >(
-                verifier_params,
-                verifying_key,
-                strategy,
-                &[instances],
-                &mut verifier_transcript,
-            )
-            .expect("failed to verify circuit");
-        }
-
         let general_params = get_general_params(self.degree);
         let verifier_params: ParamsVerifierKZG<Bn256> = general_params.verifier_params().clone();
 
@@ -307,19 +264,34 @@ impl<C: SubCircuit<Fr> + Circuit<Fr>> IntegrationTest<C> {
         // is private so cannot store.
     }
 
-    /// Run integration test at a block identified by a tag.
+    /// Run integration test at a block identified by a tag. On failure, a
+    /// [`Replay`] of the block is written in the replay directory.
     pub async fn test_at_block_tag(&mut self, block_tag: &str, actual: bool) {
         let block_num = *GEN_DATA.blocks.get(block_tag).unwrap();
-        let (builder, _) = gen_inputs(block_num).await;
-
         log::info!(
             "test {} circuit, block: #{} - {}",
             self.name,
             block_num,
             block_tag
         );
-        let block = block_convert(&builder.block, &builder.code_db).unwrap();
-        let circuit = C::new_from_block(&block);
+
+        let result = match gen_inputs(block_num).await {
+            Ok((builder, _)) => panic::catch_unwind(AssertUnwindSafe(|| {
+                let block = block_convert(&builder.block, &builder.code_db).unwrap();
+                self.test_block(&block, actual);
+            })),
+            Err(e) => Err(Box::new(format!("gen_inputs failed: {e:?}")) as Box<dyn Any + Send>),
+        };
+        if let Err(payload) = result {
+            self.write_replay(block_num, &panic_message(payload.as_ref()))
+                .await;
+            panic::resume_unwind(payload);
+        }
+    }
+
+    /// Run integration test on the witness of a block.
+    pub fn test_block(&mut self, block: &Block, actual: bool) {
+        let circuit = C::new_from_block(block);
         let instance = circuit.instance();
 
         if actual {
@@ -329,12 +301,170 @@ impl<C: SubCircuit<Fr> + Circuit<Fr>> IntegrationTest<C> {
             self.test_mock(&circuit, instance);
         }
     }
+
+    async fn write_replay(&self, block_num: u64, error: &str) {
+        let replay = match Replay::fetch(block_num, CIRCUITS_PARAMS).await {
+            Ok(replay) => replay.with_failure(self.name, error),
+            Err(e) => {
+                log::error!("cannot fetch block {block_num} for the replay: {e:?}");
+                return;
+            }
+        };
+        match replay.save_in_replay_dir() {
+            Ok(path) => log::error!(
+                "{} circuit failed at block {block_num}, replay written to {}",
+                self.name,
+                path.display()
+            ),
+            Err(e) => log::error!("cannot write the replay of block {block_num}: {e}"),
+        }
+    }
+
+    /// Prove and verify `block`, measuring the proving time and memory.
+    pub fn bench_block(&mut self, block: &Block) -> ProvingStats {
+        let circuit = C::new_from_block(block);
+        let instance = circuit.instance();
+        let instance: Vec<&[Fr]> = instance.iter().map(|v| v.as_slice()).collect();
+
+        let key = self.get_key();
+        let general_params = get_general_params(self.degree);
+        let verifier_params: ParamsVerifierKZG<Bn256> = general_params.verifier_params().clone();
+        let transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+
+        log::info!("bench {} circuit", self.name);
+        reset_peak_memory();
+        let start = Instant::now();
+        let proof = test_gen_proof(
+            RNG.clone(),
+            circuit,
+            &general_params,
+            &key,
+            transcript,
+            &instance,
+        );
+        let proving_time = start.elapsed();
+        let peak_memory_kb = peak_memory_kb();
+
+        let start = Instant::now();
+        test_verify(
+            &general_params,
+            &verifier_params,
+            key.get_vk(),
+            &proof,
+            &instance,
+        );
+        let verifying_time = start.elapsed();
+
+        ProvingStats {
+            circuit: self.name,
+            degree: self.degree,
+            proving_ms: proving_time.as_millis(),
+            verifying_ms: verifying_time.as_millis(),
+            proof_size: proof.len(),
+            peak_memory_kb,
+        }
+    }
+}
+
+/// Proving measurements of a circuit on a block.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProvingStats {
+    /// Name of the circuit
+    pub circuit: &'static str,
+    /// Degree of the circuit
+    pub degree: u32,
+    /// Proof generation time in milliseconds
+    pub proving_ms: u128,
+    /// Proof verification time in milliseconds
+    pub verifying_ms: u128,
+    /// Proof size in bytes
+    pub proof_size: usize,
+    /// Peak resident memory of the process during the proof generation, in
+    /// kB. Only available on Linux.
+    pub peak_memory_kb: Option<u64>,
+}
+
+/// Reset the peak resident memory reported by `peak_memory_kb`.
+fn reset_peak_memory() {
+    // Writing 5 to clear_refs resets VmHWM, see proc(5).
+    if let Err(e) = fs::write("/proc/self/clear_refs", "5") {
+        log::debug!("cannot reset peak memory: {e}");
+    }
+}
+
+/// Peak resident memory (VmHWM) of the process, in kB.
+fn peak_memory_kb() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+}
+
+fn test_gen_proof<C: Circuit<Fr>, R: RngCore>(
+    rng: R,
+    circuit: C,
+    general_params: &ParamsKZG<Bn256>,
+    proving_key: &ProvingKey<G1Affine>,
+    mut transcript: Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
+    instances: &[&[Fr]],
+) -> Vec<u8> {
+    create_proof::<
+        KZGCommitmentScheme<Bn256>,
+        ProverSHPLONK<'_, Bn256>,
+        Challenge255<G1Affine>,
+        R,
+        Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
+        C,
+    >(
+        general_params,
+        proving_key,
+        &[circuit],
+        &[instances],
+        rng,
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+
+    transcript.finalize()
+}
+
+fn test_verify(
+    general_params: &ParamsKZG<Bn256>,
+    verifier_params: &ParamsKZG<Bn256>,
+    verifying_key: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[&[Fr]],
+) {
+    let mut verifier_transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
+    let strategy = SingleStrategy::new(general_params);
+
+    verify_proof::<
+        KZGCommitmentScheme<Bn256>,
+        VerifierSHPLONK<'_, Bn256>,
+        Challenge255<G1Affine>,
+        Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
+        SingleStrategy<'_, Bn256>,
+    >(
+        verifier_params,
+        verifying_key,
+        strategy,
+        &[instances],
+        &mut verifier_transcript,
+    )
+    .expect("failed to verify circuit");
 }
 
 fn new_empty_block() -> Block {
     let block: GethData = TestContext::<0, 0>::new(None, |_| {}, |_, _| {}, |b, _| b)
         .unwrap()
         .into();
+    block_from_geth_data(&block)
+}
+
+/// Build the witness of a block traced offline, with the circuit parameters
+/// of the integration tests.
+pub fn block_from_geth_data(block: &GethData) -> Block {
     let mut builder = BlockData::new_from_geth_data_with_params(block.clone(), CIRCUITS_PARAMS)
         .new_circuit_input_builder();
     builder
@@ -358,12 +488,15 @@ fn get_general_params(degree: u32) -> ParamsKZG<Bn256> {
 /// returns gen_inputs for a block number
 async fn gen_inputs(
     block_num: u64,
-) -> (
-    CircuitInputBuilder,
-    eth_types::Block<eth_types::Transaction>,
-) {
+) -> Result<
+    (
+        CircuitInputBuilder,
+        eth_types::Block<eth_types::Transaction>,
+    ),
+    bus_mapping::Error,
+> {
     let cli = get_client();
-    let cli = BuilderClient::new(cli, CIRCUITS_PARAMS).await.unwrap();
+    let cli = BuilderClient::new(cli, CIRCUITS_PARAMS).await?;
 
-    cli.gen_inputs(block_num).await.unwrap()
+    cli.gen_inputs(block_num).await
 }
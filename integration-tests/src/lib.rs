@@ -12,12 +12,14 @@ use ethers::{
     core::{k256::ecdsa::SigningKey, types::Bytes},
     providers::{Http, Provider},
     signers::{coins_bip39::English, MnemonicBuilder, Signer, Wallet},
+    solc::Solc,
 };
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     env::{self, VarError},
     fs::File,
+    path::Path,
     sync::{LazyLock, Once},
     time::Duration,
 };
@@ -83,8 +85,8 @@ pub fn log_init() {
 
 /// Get the integration test [`GethClient`]
 pub fn get_client() -> GethClient<Http> {
-    let transport = Http::new(Url::parse(&GETH0_URL).expect("invalid url"));
-    GethClient::new(transport)
+    let url = Url::parse(&GETH0_URL).expect("invalid url");
+    GethClient::new(Http::new(url.clone())).with_streaming(url)
 }
 
 /// Get the integration test [`Provider`]
@@ -161,5 +163,44 @@ pub struct CompiledContract {
     pub bin_runtime: Bytes,
 }
 
+impl CompiledContract {
+    /// Compile the contract `name` of the solidity file at `path_sol` with
+    /// solc.
+    pub fn compile(path_sol: &Path, name: &str) -> Self {
+        let compiled = Solc::default()
+            .compile_source(path_sol)
+            .unwrap_or_else(|_| panic!("solc compile error {path_sol:?}",));
+        if !compiled.errors.is_empty() {
+            panic!("Errors compiling {:?}:\n{:#?}", &path_sol, compiled.errors)
+        }
+
+        let contract = compiled
+            .get(path_sol.to_str().expect("path is not str"), name)
+            .expect("contract not found");
+        let abi = contract.abi.expect("no abi found").clone();
+        let bin = contract.bin.expect("no bin found").clone();
+        let bin_runtime = contract.bin_runtime.expect("no bin_runtime found").clone();
+        Self {
+            path: path_sol.to_str().expect("path is not str").to_string(),
+            name: name.to_string(),
+            abi,
+            bin: bin.into_bytes().expect("bin"),
+            bin_runtime: bin_runtime.into_bytes().expect("bin_runtime"),
+        }
+    }
+}
+
 /// Common code for integration tests of circuits.
 pub mod integration_test_circuits;
+
+/// Fixed blocks for proving benchmarks.
+pub mod canned_blocks;
+
+/// Dev-mode node spawned as a child process.
+pub mod geth_instance;
+
+/// Real-world contract workloads.
+pub mod scenarios;
+
+/// Reproduction files of integration test failures.
+pub mod replay;
@@ -0,0 +1,311 @@
+//! Self-contained reproduction files of integration test failures.
+//!
+//! When a circuit integration test fails, the block, its traces, the prestate
+//! of the accounts it touches and the circuit parameters are written to a
+//! `.replay` file, which the `replay` bin loads to reproduce the failure
+//! offline:
+//!
+//! ```sh
+//! cargo run --release -p integration-tests --bin replay -- replays/EVM_12.replay
+//! ```
+//!
+//! The files are written in `REPLAY_DIR`, which defaults to `replays`. The
+//! traces, which make most of a replay, are written in the
+//! [compact](eth_types::compact) encoding after the JSON of the other fields,
+//! and the replays written as a single JSON by [`Replay::save_json`] or by the
+//! older versions are still loaded.
+
+use crate::get_client;
+use bus_mapping::{
+    circuit_input_builder::{BuilderClient, CircuitsParams},
+    mock::BlockData,
+    Error,
+};
+use eth_types::{
+    compact,
+    geth_types::{Account, GethData},
+    Address, EthBlock, GethExecTrace, Word,
+};
+use ethers::core::types::Bytes;
+use serde::{Deserialize, Serialize};
+use std::{
+    any::Any,
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+use zkevm_circuits::witness::{block_convert, Block};
+
+/// Version of the replay format, bumped on incompatible changes.
+pub const REPLAY_VERSION: u32 = 1;
+/// Extension of the replay files.
+pub const REPLAY_EXTENSION: &str = "replay";
+/// Start of the replay files with compact traces, which can't be the start of
+/// a JSON replay.
+const REPLAY_MAGIC: &[u8; 4] = b"\0RPL";
+
+/// Directory where the replay files of the failed tests are written.
+pub static REPLAY_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
+    env::var("REPLAY_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| "replays".into())
+});
+
+/// State of an account before the block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayAccount {
+    /// Address
+    pub address: Address,
+    /// Nonce
+    pub nonce: Word,
+    /// Balance
+    pub balance: Word,
+    /// EVM code
+    pub code: Bytes,
+    /// Storage slots read or written by the block
+    pub storage: BTreeMap<Word, Word>,
+}
+
+impl From<&Account> for ReplayAccount {
+    fn from(account: &Account) -> Self {
+        Self {
+            address: account.address,
+            nonce: account.nonce,
+            balance: account.balance,
+            code: account.code.clone(),
+            storage: account.storage.iter().map(|(k, v)| (*k, *v)).collect(),
+        }
+    }
+}
+
+impl From<&ReplayAccount> for Account {
+    fn from(account: &ReplayAccount) -> Self {
+        Self {
+            address: account.address,
+            nonce: account.nonce,
+            balance: account.balance,
+            code: account.code.clone(),
+            storage: account.storage.iter().map(|(k, v)| (*k, *v)).collect(),
+        }
+    }
+}
+
+/// Everything needed to build the witness of a block, without a geth node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    /// Version of the format
+    pub version: u32,
+    /// Name of the failed circuit, if any
+    pub circuit: Option<String>,
+    /// Failure message, if any
+    pub error: Option<String>,
+    /// Circuit parameters the test ran with
+    pub circuits_params: CircuitsParams,
+    /// Chain id
+    pub chain_id: u64,
+    /// Most recent block hashes, the latest one last
+    pub history_hashes: Vec<Word>,
+    /// Block
+    pub eth_block: EthBlock,
+    /// Execution traces of the transactions of the block
+    pub geth_traces: Vec<GethExecTrace>,
+    /// State of the accounts touched by the block, before the block, sorted
+    /// by address
+    pub prestate: Vec<ReplayAccount>,
+}
+
+impl Replay {
+    /// Replay of a block, with the prestate collected from the prestate
+    /// traces of its transactions.
+    pub fn new(
+        chain_id: u64,
+        history_hashes: Vec<Word>,
+        eth_block: EthBlock,
+        geth_traces: Vec<GethExecTrace>,
+        circuits_params: CircuitsParams,
+    ) -> Self {
+        let mut accounts = BTreeMap::<Address, ReplayAccount>::new();
+        for (address, prestate) in geth_traces.iter().flat_map(|trace| trace.prestate.iter()) {
+            // The state before the block is the one seen by the first
+            // transaction touching the account.
+            let account = accounts.entry(*address).or_insert_with(|| ReplayAccount {
+                address: *address,
+                nonce: prestate.nonce.unwrap_or_default().into(),
+                balance: prestate.balance.unwrap_or_default(),
+                code: prestate.code.clone().unwrap_or_default(),
+                storage: BTreeMap::new(),
+            });
+            for (key, value) in prestate.storage.iter().flatten() {
+                account.storage.entry(*key).or_insert(*value);
+            }
+        }
+
+        Self {
+            version: REPLAY_VERSION,
+            circuit: None,
+            error: None,
+            circuits_params,
+            chain_id,
+            history_hashes,
+            eth_block,
+            geth_traces,
+            prestate: accounts.into_values().collect(),
+        }
+    }
+
+    /// Replay of a block traced offline.
+    pub fn from_geth_data(geth_data: &GethData, circuits_params: CircuitsParams) -> Self {
+        let mut prestate: Vec<ReplayAccount> =
+            geth_data.accounts.iter().map(ReplayAccount::from).collect();
+        prestate.sort_by_key(|account| account.address);
+
+        Self {
+            prestate,
+            ..Self::new(
+                geth_data.chain_id,
+                geth_data.history_hashes.clone(),
+                geth_data.eth_block.clone(),
+                geth_data.geth_traces.clone(),
+                circuits_params,
+            )
+        }
+    }
+
+    /// Fetch the block `block_num` from the integration test geth node.
+    pub async fn fetch(block_num: u64, circuits_params: CircuitsParams) -> Result<Self, Error> {
+        let cli = get_client();
+        let chain_id = cli.get_chain_id().await?;
+        let cli = BuilderClient::new(cli, circuits_params).await?;
+        let (eth_block, geth_traces, history_hashes, _) = cli.get_block(block_num).await?;
+
+        Ok(Self::new(
+            chain_id,
+            history_hashes,
+            eth_block,
+            geth_traces,
+            circuits_params,
+        ))
+    }
+
+    /// Record the failure of `circuit`.
+    pub fn with_failure(mut self, circuit: &str, error: impl Into<String>) -> Self {
+        self.circuit = Some(circuit.to_string());
+        self.error = Some(error.into());
+        self
+    }
+
+    /// Number of the block.
+    pub fn block_number(&self) -> u64 {
+        self.eth_block.number.unwrap_or_default().as_u64()
+    }
+
+    /// Write the replay to `path`, as the magic, the length of the JSON of the
+    /// replay without its traces, that JSON, and the compact traces.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let header = Self {
+            geth_traces: Vec::new(),
+            ..self.clone()
+        };
+        let json = serde_json::to_vec(&header).map_err(io::Error::from)?;
+        let traces = compact::encode_traces(&self.geth_traces).map_err(invalid_data)?;
+
+        let mut data = Vec::with_capacity(REPLAY_MAGIC.len() + 8 + json.len() + traces.len());
+        data.extend_from_slice(REPLAY_MAGIC);
+        data.extend_from_slice(&(json.len() as u64).to_le_bytes());
+        data.extend(json);
+        data.extend(traces);
+        Self::write(path, &data)
+    }
+
+    /// Write the replay to `path` as a single JSON, readable without the
+    /// compact decoder.
+    pub fn save_json(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(self).map_err(io::Error::from)?;
+        Self::write(path, &json)
+    }
+
+    fn write(path: impl AsRef<Path>, data: &[u8]) -> io::Result<()> {
+        if let Some(dir) = path.as_ref().parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, data)
+    }
+
+    /// Write the replay in [`REPLAY_DIR`], in a file named after the failed
+    /// circuit and the block number, and return its path.
+    pub fn save_in_replay_dir(&self) -> io::Result<PathBuf> {
+        let path = REPLAY_DIR.join(format!(
+            "{}_{}.{REPLAY_EXTENSION}",
+            self.circuit.as_deref().unwrap_or("block"),
+            self.block_number()
+        ));
+        self.save(&path)?;
+        Ok(path)
+    }
+
+    /// Read a replay from `path`, written by [`Replay::save`] or
+    /// [`Replay::save_json`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let data = fs::read(path)?;
+        let replay = match data.strip_prefix(REPLAY_MAGIC) {
+            Some(data) => {
+                if data.len() < 8 {
+                    return Err(invalid_data("truncated replay"));
+                }
+                let (len, data) = data.split_at(8);
+                let len = usize::try_from(u64::from_le_bytes(len.try_into().unwrap()))
+                    .ok()
+                    .filter(|len| *len <= data.len())
+                    .ok_or_else(|| invalid_data("truncated replay"))?;
+                let (json, traces) = data.split_at(len);
+                let mut replay: Self = serde_json::from_slice(json).map_err(io::Error::from)?;
+                replay.geth_traces = compact::decode_traces(traces).map_err(invalid_data)?;
+                replay
+            }
+            None => serde_json::from_slice(&data).map_err(io::Error::from)?,
+        };
+        if replay.version != REPLAY_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported replay version {}, expected {REPLAY_VERSION}",
+                replay.version
+            )));
+        }
+        Ok(replay)
+    }
+
+    /// Block, traces and prestate of the replay.
+    pub fn geth_data(&self) -> GethData {
+        GethData {
+            chain_id: self.chain_id,
+            history_hashes: self.history_hashes.clone(),
+            eth_block: self.eth_block.clone(),
+            geth_traces: self.geth_traces.clone(),
+            accounts: self.prestate.iter().map(Account::from).collect(),
+            #[cfg(feature = "scroll")]
+            block_trace: Default::default(),
+        }
+    }
+
+    /// Build the witness of the block with the recorded circuit parameters.
+    pub fn block(&self) -> Result<Block, Error> {
+        let mut builder =
+            BlockData::new_from_geth_data_with_params(self.geth_data(), self.circuits_params)
+                .new_circuit_input_builder();
+        builder.handle_block(&self.eth_block, &self.geth_traces)?;
+        block_convert(&builder.block, &builder.code_db)
+    }
+}
+
+fn invalid_data(error: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+/// Message of a panic payload, as printed by the default panic hook.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string())
+}
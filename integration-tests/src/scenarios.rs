@@ -0,0 +1,294 @@
+//! Real-world contract workloads, deployed and invoked in a single mock
+//! block.
+//!
+//! Every scenario is a list of transactions sent by [`deployer`]: first the
+//! deployments of the contracts, found in `contracts/scenarios`, then the calls
+//! to them. The transactions are traced with the mock tracer, so the
+//! resulting [`TestContext`] can go through witness generation and the mock
+//! prover without a geth node.
+
+use crate::{CompiledContract, CONTRACTS_PATH};
+use bus_mapping::circuit_input_builder::{CircuitsParams, PrecompileEcParams, N_PAIRING_PER_OP};
+use eth_types::{Address, Word};
+use ethers::{
+    abi::Tokenize,
+    core::{types::Bytes, utils::get_contract_address},
+};
+use mock::{eth, TestContext, MOCK_ACCOUNTS};
+use std::{collections::HashMap, path::Path, sync::LazyLock};
+
+/// List of scenario contracts as (ContractName, ContractSolidityFile)
+pub const SCENARIO_CONTRACTS: &[(&str, &str)] = &[
+    ("BatchToken", "scenarios/BatchToken.sol"),
+    ("SimpleNFT", "scenarios/SimpleNFT.sol"),
+    ("ConstantProductPair", "scenarios/ConstantProductPair.sol"),
+    ("Multicall", "scenarios/Multicall.sol"),
+    ("UpgradeableProxy", "scenarios/UpgradeableProxy.sol"),
+    ("CounterV1", "scenarios/UpgradeableProxy.sol"),
+    ("CounterV2", "scenarios/UpgradeableProxy.sol"),
+];
+
+/// Circuit parameters large enough for all the scenarios.
+pub const SCENARIO_CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
+    max_rws: 50_000,
+    max_txs: 8,
+    max_calldata: 20_000,
+    max_inner_blocks: 64,
+    max_copy_rows: 50_000,
+    max_mpt_rows: 50_000,
+    max_exp_steps: 1000,
+    max_bytecode: 30_000,
+    max_evm_rows: 0,
+    max_keccak_rows: 0,
+    max_poseidon_rows: 0,
+    max_vertical_circuit_rows: 0,
+    max_rlp_rows: 30_000,
+    max_ec_ops: PrecompileEcParams {
+        ec_add: 0,
+        ec_mul: 0,
+        ec_pairing: 0,
+        ec_pairing_pairs: N_PAIRING_PER_OP,
+    },
+};
+
+/// Gas limit of every scenario transaction.
+const SCENARIO_TX_GAS: u64 = 5_000_000;
+
+static COMPILED: LazyLock<HashMap<&'static str, CompiledContract>> = LazyLock::new(|| {
+    SCENARIO_CONTRACTS
+        .iter()
+        .map(|(name, path)| {
+            let path_sol = Path::new(CONTRACTS_PATH).join(path);
+            (*name, CompiledContract::compile(&path_sol, name))
+        })
+        .collect()
+});
+
+/// Compiled scenario contract.
+pub fn compiled(name: &str) -> &'static CompiledContract {
+    COMPILED
+        .get(name)
+        .unwrap_or_else(|| panic!("unknown scenario contract {name}"))
+}
+
+/// Sender of all the scenario transactions.
+pub fn deployer() -> Address {
+    MOCK_ACCOUNTS[0]
+}
+
+/// Address of the contract deployed by the transaction at `index` of a
+/// scenario, whose nonce is the index.
+pub fn deployed_address(index: u64) -> Address {
+    get_contract_address(deployer(), index)
+}
+
+/// Calldata of a call to `method` of `contract`.
+pub fn encode_call(contract: &str, method: &str, args: impl Tokenize) -> Bytes {
+    compiled(contract)
+        .abi
+        .function(method)
+        .unwrap_or_else(|_| panic!("no method {method} in {contract}"))
+        .encode_input(&args.into_tokens())
+        .expect("cannot encode call")
+        .into()
+}
+
+/// A transaction of a scenario.
+#[derive(Debug, Clone)]
+pub struct ScenarioTx {
+    to: Option<Address>,
+    input: Bytes,
+}
+
+impl ScenarioTx {
+    /// Deploy `contract` with the constructor arguments `args`.
+    pub fn deploy(contract: &str, args: impl Tokenize) -> Self {
+        let compiled = compiled(contract);
+        let code = compiled.bin.to_vec();
+        let input = match compiled.abi.constructor() {
+            Some(constructor) => constructor
+                .encode_input(code, &args.into_tokens())
+                .expect("cannot encode constructor"),
+            None => code,
+        };
+        Self {
+            to: None,
+            input: input.into(),
+        }
+    }
+
+    /// Call `method` of `contract` deployed at `to`.
+    pub fn call(to: Address, contract: &str, method: &str, args: impl Tokenize) -> Self {
+        Self {
+            to: Some(to),
+            input: encode_call(contract, method, args),
+        }
+    }
+}
+
+/// Trace the transactions of a scenario in a single block.
+pub fn scenario_ctx<const NTX: usize>(txs: [ScenarioTx; NTX]) -> TestContext<2, NTX> {
+    TestContext::new(
+        None,
+        |accs| {
+            accs[0].address(deployer()).balance(eth(100));
+            accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(1));
+        },
+        |mock_txs, _accs| {
+            for (mock_tx, tx) in mock_txs.into_iter().zip(txs) {
+                mock_tx
+                    .from(deployer())
+                    .gas(Word::from(SCENARIO_TX_GAS))
+                    .input(tx.input);
+                if let Some(to) = tx.to {
+                    mock_tx.to(to);
+                }
+            }
+        },
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .expect("cannot trace scenario")
+}
+
+fn recipients() -> [Address; 3] {
+    [MOCK_ACCOUNTS[1], MOCK_ACCOUNTS[2], MOCK_ACCOUNTS[3]]
+}
+
+/// ERC20 token deployment, followed by a batch transfer and a single
+/// transfer.
+pub fn erc20_batch_transfers() -> TestContext<2, 3> {
+    let token = deployed_address(0);
+    scenario_ctx([
+        ScenarioTx::deploy("BatchToken", (Word::from(1_000_000u64),)),
+        ScenarioTx::call(
+            token,
+            "BatchToken",
+            "batchTransfer",
+            (
+                recipients().to_vec(),
+                vec![Word::from(100u64), Word::from(200u64), Word::from(300u64)],
+            ),
+        ),
+        ScenarioTx::call(
+            token,
+            "BatchToken",
+            "transfer",
+            (recipients()[0], Word::from(1000u64)),
+        ),
+    ])
+}
+
+/// ERC721 deployment, followed by two mints and a transfer.
+pub fn erc721_mint() -> TestContext<2, 4> {
+    let nft = deployed_address(0);
+    let [recipient, ..] = recipients();
+    scenario_ctx([
+        ScenarioTx::deploy("SimpleNFT", ()),
+        ScenarioTx::call(
+            nft,
+            "SimpleNFT",
+            "mint",
+            (deployer(), Word::from(1u64), "ipfs://token/1".to_string()),
+        ),
+        ScenarioTx::call(
+            nft,
+            "SimpleNFT",
+            "mint",
+            (recipient, Word::from(2u64), "ipfs://token/2".to_string()),
+        ),
+        ScenarioTx::call(
+            nft,
+            "SimpleNFT",
+            "transferFrom",
+            (deployer(), recipient, Word::from(1u64)),
+        ),
+    ])
+}
+
+/// Output amount of a swap on a Uniswap V2 pair, with the 0.3% fee.
+fn get_amount_out(amount_in: u64, reserve_in: u64, reserve_out: u64) -> Word {
+    let amount_in_with_fee = Word::from(amount_in) * 997;
+    amount_in_with_fee * reserve_out / (Word::from(reserve_in) * 1000 + amount_in_with_fee)
+}
+
+/// Uniswap V2 style pair deployment and funding, followed by a swap of
+/// token0 for token1.
+pub fn uniswap_v2_swap() -> TestContext<2, 8> {
+    const RESERVES: (u64, u64) = (1_000_000, 2_000_000);
+    const AMOUNT_IN: u64 = 10_000;
+
+    let token0 = deployed_address(0);
+    let token1 = deployed_address(1);
+    let pair = deployed_address(2);
+    let supply = Word::from(1_000_000_000u64);
+    scenario_ctx([
+        ScenarioTx::deploy("BatchToken", (supply,)),
+        ScenarioTx::deploy("BatchToken", (supply,)),
+        ScenarioTx::deploy("ConstantProductPair", (token0, token1)),
+        ScenarioTx::call(
+            token0,
+            "BatchToken",
+            "transfer",
+            (pair, Word::from(RESERVES.0)),
+        ),
+        ScenarioTx::call(
+            token1,
+            "BatchToken",
+            "transfer",
+            (pair, Word::from(RESERVES.1)),
+        ),
+        ScenarioTx::call(pair, "ConstantProductPair", "sync", ()),
+        ScenarioTx::call(
+            token0,
+            "BatchToken",
+            "transfer",
+            (pair, Word::from(AMOUNT_IN)),
+        ),
+        ScenarioTx::call(
+            pair,
+            "ConstantProductPair",
+            "swap",
+            (
+                Word::zero(),
+                get_amount_out(AMOUNT_IN, RESERVES.0, RESERVES.1),
+                deployer(),
+            ),
+        ),
+    ])
+}
+
+/// Multicall aggregating views of an ERC20 token.
+pub fn multicall() -> TestContext<2, 3> {
+    let token = deployed_address(0);
+    let multicall = deployed_address(1);
+    let calls = vec![
+        (token, encode_call("BatchToken", "balanceOf", (deployer(),))),
+        (token, encode_call("BatchToken", "totalSupply", ())),
+        (
+            token,
+            encode_call("BatchToken", "balanceOf", (recipients()[0],)),
+        ),
+    ];
+    scenario_ctx([
+        ScenarioTx::deploy("BatchToken", (Word::from(1_000_000u64),)),
+        ScenarioTx::deploy("Multicall", ()),
+        ScenarioTx::call(multicall, "Multicall", "aggregate", (calls,)),
+    ])
+}
+
+/// Proxy deployment, followed by calls through the proxy before and after
+/// upgrading its implementation.
+pub fn proxy_upgrade() -> TestContext<2, 7> {
+    let v1 = deployed_address(0);
+    let v2 = deployed_address(1);
+    let proxy = deployed_address(2);
+    scenario_ctx([
+        ScenarioTx::deploy("CounterV1", ()),
+        ScenarioTx::deploy("CounterV2", ()),
+        ScenarioTx::deploy("UpgradeableProxy", (v1,)),
+        ScenarioTx::call(proxy, "CounterV1", "increment", ()),
+        ScenarioTx::call(proxy, "UpgradeableProxy", "upgradeTo", (v2,)),
+        ScenarioTx::call(proxy, "CounterV2", "increment", ()),
+        ScenarioTx::call(proxy, "CounterV2", "reset", ()),
+    ])
+}
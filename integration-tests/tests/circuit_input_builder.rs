@@ -8,27 +8,26 @@ use std::sync::LazyLock;
 
 pub static GEN_DATA: LazyLock<GenDataOutput> = LazyLock::new(GenDataOutput::load);
 
+fn circuits_params() -> CircuitsParams {
+    CircuitsParams {
+        max_rws: 16384,
+        max_txs: 1,
+        max_calldata: 4000,
+        max_inner_blocks: 64,
+        max_bytecode: 4000,
+        max_copy_rows: 16384,
+        max_mpt_rows: 4000,
+        max_evm_rows: 0,
+        max_exp_steps: 1000,
+        max_keccak_rows: 0,
+        max_rlp_rows: 4200,
+        ..Default::default()
+    }
+}
+
 async fn test_circuit_input_builder_block(block_num: u64) {
     let cli = get_client();
-    let cli = BuilderClient::new(
-        cli,
-        CircuitsParams {
-            max_rws: 16384,
-            max_txs: 1,
-            max_calldata: 4000,
-            max_inner_blocks: 64,
-            max_bytecode: 4000,
-            max_copy_rows: 16384,
-            max_mpt_rows: 4000,
-            max_evm_rows: 0,
-            max_exp_steps: 1000,
-            max_keccak_rows: 0,
-            max_rlp_rows: 4200,
-            ..Default::default()
-        },
-    )
-    .await
-    .unwrap();
+    let cli = BuilderClient::new(cli, circuits_params()).await.unwrap();
 
     // 1. Query geth for Block, Txs and TxExecTraces
     let (eth_block, geth_trace, history_hashes, prev_state_root) =
@@ -102,3 +101,27 @@ declare_tests!(
     test_circuit_input_builder_multiple_erc20_openzeppelin_transfers,
     "Multiple ERC20 OpenZeppelin transfers"
 );
+
+// This test builds the circuit inputs of the last transfer of the block with
+// multiple transfer txs alone, on top of the state left by the other ones.
+#[tokio::test]
+async fn test_circuit_input_builder_tx_multiple_transfers_0() {
+    log_init();
+    let block_num = GEN_DATA.blocks.get("Multiple transfers 0").unwrap();
+    let block = get_client()
+        .get_block_by_number((*block_num).into())
+        .await
+        .unwrap();
+    assert!(block.transactions.len() > 1);
+    let tx_hash = block.transactions.last().unwrap().hash;
+
+    let cli = BuilderClient::new(get_client(), circuits_params())
+        .await
+        .unwrap();
+    let (builder, eth_block) = cli.build_tx_inputs(tx_hash).await.unwrap();
+
+    assert_eq!(eth_block.hash, block.hash);
+    assert_eq!(eth_block.transactions.len(), 1);
+    assert_eq!(builder.block.txs().len(), 1);
+    assert_eq!(builder.block.txs()[0].hash, tx_hash);
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "embedded_geth")]
+
+use bus_mapping::circuit_input_builder::{BuilderClient, CircuitsParams};
+use eth_types::{Address, Transaction, Word};
+use ethers::providers::Middleware;
+use integration_tests::{
+    geth_instance::{GethInstance, NodeKind},
+    log_init, CHAIN_ID,
+};
+
+#[tokio::test]
+async fn test_embedded_geth() {
+    log_init();
+    let geth = GethInstance::spawn().expect("cannot spawn geth");
+    let cli = geth.client();
+    assert_eq!(CHAIN_ID, cli.get_chain_id().await.unwrap());
+
+    let block = cli.get_block_by_number(0.into()).await.unwrap();
+    assert_eq!(block.number, Some(0u64.into()));
+}
+
+#[tokio::test]
+async fn test_embedded_geth_teardown() {
+    let port = {
+        let geth = GethInstance::spawn().expect("cannot spawn geth");
+        geth.port()
+    };
+    assert!(
+        std::net::TcpStream::connect(("127.0.0.1", port)).is_err(),
+        "geth still listening after drop"
+    );
+}
+
+#[tokio::test]
+async fn test_embedded_geth_pending_block() {
+    log_init();
+    let geth = GethInstance::spawn_kind(NodeKind::Geth).expect("cannot spawn geth");
+    let dev_account = geth.provider().get_accounts().await.unwrap()[0];
+    let cli = BuilderClient::new(
+        geth.client(),
+        CircuitsParams {
+            max_txs: 2,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    // the receiver of the first transfer is only funded by it, so the second
+    // transfer fails to be traced unless the state is carried over
+    let receiver = Address::repeat_byte(0x11);
+    let ether = Word::exp10(18);
+    let transfer = |from, to, value| {
+        let mut tx = Transaction {
+            from,
+            to: Some(to),
+            nonce: Word::zero(),
+            gas: 21000.into(),
+            gas_price: Some(Word::exp10(10)),
+            value,
+            // an unsigned transaction, as in `BuilderClient::gen_inputs_call`
+            v: (CHAIN_ID * 2 + 35).into(),
+            chain_id: Some(CHAIN_ID.into()),
+            ..Default::default()
+        };
+        tx.hash = tx.hash();
+        tx
+    };
+    let txs = vec![
+        transfer(dev_account, receiver, ether),
+        transfer(receiver, dev_account, ether / 2),
+    ];
+
+    let (builder, eth_block) = cli.build_pending_block(txs).await.unwrap();
+    assert_eq!(eth_block.number, Some(1u64.into()));
+    assert_eq!(eth_block.gas_used, (2 * 21000).into());
+    assert_eq!(builder.block.txs().len(), 2);
+    let (_, receiver_account) = builder.sdb.get_account(&receiver);
+    assert_eq!(receiver_account.nonce, Word::one());
+}
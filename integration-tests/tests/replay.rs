@@ -0,0 +1,32 @@
+use integration_tests::{
+    canned_blocks::swap_block, integration_test_circuits::CIRCUITS_PARAMS, replay::Replay,
+};
+use std::env;
+
+#[test]
+fn test_replay_roundtrip() {
+    let geth_data = swap_block();
+    let replay = Replay::from_geth_data(&geth_data, CIRCUITS_PARAMS).with_failure("EVM", "failed");
+
+    let path = env::temp_dir().join(format!("zkevm-replay-{}.replay", std::process::id()));
+    replay.save(&path).unwrap();
+    let loaded = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.circuit.as_deref(), Some("EVM"));
+    assert_eq!(loaded.error.as_deref(), Some("failed"));
+    assert_eq!(loaded.circuits_params.max_rws, CIRCUITS_PARAMS.max_rws);
+    assert_eq!(loaded.eth_block, replay.eth_block);
+    assert_eq!(loaded.geth_traces, replay.geth_traces);
+    assert_eq!(loaded.prestate, replay.prestate);
+
+    let block = loaded.block().unwrap();
+    assert_eq!(block.txs.len(), geth_data.eth_block.transactions.len());
+
+    // replays written as a single JSON are still loaded
+    replay.save_json(&path).unwrap();
+    let loaded = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.geth_traces, replay.geth_traces);
+    assert_eq!(loaded.prestate, replay.prestate);
+}
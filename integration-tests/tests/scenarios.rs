@@ -0,0 +1,29 @@
+#![cfg(feature = "scenarios")]
+
+use integration_tests::{log_init, scenarios::*};
+use paste::paste;
+use zkevm_circuits::test_util::CircuitTestBuilder;
+
+macro_rules! declare_tests {
+    ($($name:ident),* $(,)?) => {
+        paste! {
+            $(
+                #[test]
+                fn [<test_scenario_ $name>]() {
+                    log_init();
+                    CircuitTestBuilder::new_from_test_ctx($name())
+                        .params(SCENARIO_CIRCUITS_PARAMS)
+                        .run();
+                }
+            )*
+        }
+    };
+}
+
+declare_tests!(
+    erc20_batch_transfers,
+    erc721_mint,
+    uniswap_v2_swap,
+    multicall,
+    proxy_upgrade,
+);
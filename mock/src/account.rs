@@ -18,6 +18,9 @@ pub struct MockAccount {
     pub code: Bytes,
     /// Storage
     pub storage: HashMap<Word, Word>,
+    /// Keep the balance as is, even if it can't pay for the transactions sent
+    /// by the account
+    pub skip_auto_funding: bool,
 }
 
 impl From<MockAccount> for Account {
@@ -51,6 +54,14 @@ impl MockAccount {
         self
     }
 
+    /// Keep the balance of the MockAccount even if it can't pay for the
+    /// transactions it sends, which are otherwise funded by the
+    /// [`TestContext`](crate::TestContext).
+    pub fn skip_auto_funding(&mut self) -> &mut Self {
+        self.skip_auto_funding = true;
+        self
+    }
+
     /// Set code field for the MockAccount.
     pub fn code<T: Into<Bytes>>(&mut self, code: T) -> &mut Self {
         self.code = code.into();
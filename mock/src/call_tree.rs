@@ -0,0 +1,290 @@
+//! Declarative construction of the contracts of nested calls, to test deep
+//! call trees without writing the bytecode of each contract by hand.
+
+use crate::{test_ctx::TestContext, MockAccount, MOCK_WALLETS};
+use eth_types::{bytecode::Bytecode, evm_types::OpcodeId, Address, Error, ToWord, Word};
+use ethers_signers::Signer;
+use itertools::Itertools;
+
+/// How a contract of a [`CallTree`] is called by its parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallKind {
+    /// CALL
+    Call,
+    /// CALLCODE
+    CallCode,
+    /// DELEGATECALL
+    DelegateCall,
+    /// STATICCALL
+    StaticCall,
+}
+
+impl CallKind {
+    fn opcode(&self) -> OpcodeId {
+        match self {
+            Self::Call => OpcodeId::CALL,
+            Self::CallCode => OpcodeId::CALLCODE,
+            Self::DelegateCall => OpcodeId::DELEGATECALL,
+            Self::StaticCall => OpcodeId::STATICCALL,
+        }
+    }
+
+    fn has_value(&self) -> bool {
+        matches!(self, Self::Call | Self::CallCode)
+    }
+}
+
+/// A contract of a call tree, which runs its code, then calls its children in
+/// order and ends, with a REVERT if [`CallNode::reverts`] is set.
+///
+/// Note that a child called with some value in a static context fails, as in
+/// the EVM.
+#[derive(Debug, Clone)]
+pub struct CallNode {
+    kind: CallKind,
+    value: Word,
+    gas: Option<Word>,
+    code: Bytecode,
+    children: Vec<CallNode>,
+    reverts: bool,
+}
+
+impl CallNode {
+    /// Create a contract called with `kind` by its parent. The kind of the
+    /// root is ignored, since it's called by the transaction.
+    pub fn new(kind: CallKind) -> Self {
+        Self {
+            kind,
+            value: Word::zero(),
+            gas: None,
+            code: Bytecode::default(),
+            children: Vec::new(),
+            reverts: false,
+        }
+    }
+
+    /// Create a contract called with CALL.
+    pub fn call() -> Self {
+        Self::new(CallKind::Call)
+    }
+
+    /// Create a contract called with CALLCODE.
+    pub fn callcode() -> Self {
+        Self::new(CallKind::CallCode)
+    }
+
+    /// Create a contract called with DELEGATECALL.
+    pub fn delegatecall() -> Self {
+        Self::new(CallKind::DelegateCall)
+    }
+
+    /// Create a contract called with STATICCALL.
+    pub fn staticcall() -> Self {
+        Self::new(CallKind::StaticCall)
+    }
+
+    /// Set the value sent by the CALL or CALLCODE of the contract.
+    pub fn value(mut self, value: Word) -> Self {
+        assert!(
+            self.kind.has_value(),
+            "{:?} can't send value",
+            self.kind.opcode()
+        );
+        self.value = value;
+        self
+    }
+
+    /// Set the gas given to the contract, all the gas left by default.
+    pub fn gas(mut self, gas: Word) -> Self {
+        self.gas = Some(gas);
+        self
+    }
+
+    /// Set the code run by the contract before calling its children.
+    pub fn code(mut self, code: Bytecode) -> Self {
+        self.code = code;
+        self
+    }
+
+    /// Add a child called after the previous ones.
+    pub fn child(mut self, child: CallNode) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// End the contract with a REVERT instead of a STOP, reverting the calls
+    /// of its children too.
+    pub fn reverts(mut self) -> Self {
+        self.reverts = true;
+        self
+    }
+
+    fn total_value(&self) -> Word {
+        self.children
+            .iter()
+            .fold(self.value, |total, child| total + child.total_value())
+    }
+}
+
+/// Builder of the contracts of a [`CallTree`].
+///
+/// ## Example
+/// ```rust
+/// use mock::call_tree::{CallNode, CallTreeBuilder};
+///
+/// // the root calls a contract which reverts after a static call, then a
+/// // contract which calls itself 3 levels deep
+/// let tree = CallTreeBuilder::new(
+///     CallNode::call()
+///         .child(CallNode::call().child(CallNode::staticcall()).reverts())
+///         .child(CallTreeBuilder::chain(3, CallNode::delegatecall)),
+/// )
+/// .build();
+/// assert_eq!(tree.len(), 6);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CallTreeBuilder {
+    root: CallNode,
+    first_address: u64,
+}
+
+impl CallTreeBuilder {
+    /// Default address of the root, the other contracts follow it.
+    pub const FIRST_ADDRESS: u64 = 0xca11_0000;
+
+    /// Create a builder of the tree starting at `root`.
+    pub fn new(root: CallNode) -> Self {
+        Self {
+            root,
+            first_address: Self::FIRST_ADDRESS,
+        }
+    }
+
+    /// A chain of `depth` contracts created by `node`, each one calling the
+    /// next one.
+    pub fn chain(depth: usize, node: impl Fn() -> CallNode) -> CallNode {
+        assert!(depth > 0, "empty call chain");
+        (1..depth).fold(node(), |child, _| node().child(child))
+    }
+
+    /// Set the address of the root, the address of the other contracts being
+    /// the next ones in depth first order.
+    pub fn first_address(mut self, first_address: u64) -> Self {
+        self.first_address = first_address;
+        self
+    }
+
+    /// Generate the bytecode of the contracts.
+    pub fn build(self) -> CallTree {
+        let balance = self.root.total_value();
+        let mut contracts = Vec::new();
+        Self::build_node(&self.root, self.first_address, &mut contracts);
+        CallTree {
+            contracts: contracts
+                .into_iter()
+                .sorted_by_key(|(address, _)| *address)
+                .collect(),
+            balance,
+        }
+    }
+
+    /// Push the contracts of the subtree of `node` at `address` and the next
+    /// ones, returning the number of contracts.
+    fn build_node(node: &CallNode, address: u64, contracts: &mut Vec<(Address, Bytecode)>) -> u64 {
+        let mut code = node.code.clone();
+        let mut next_address = address + 1;
+        for child in node.children.iter() {
+            // ret_size, ret_offset, args_size, args_offset
+            for _ in 0..4 {
+                code.op_push1(0u64);
+            }
+            if child.kind.has_value() {
+                code.op_push32(child.value);
+            }
+            code.op_push32(Address::from_low_u64_be(next_address).to_word());
+            match child.gas {
+                Some(gas) => code.op_push32(gas),
+                None => code.write_op(OpcodeId::GAS),
+            };
+            code.write_op(child.kind.opcode());
+            code.write_op(OpcodeId::POP);
+            next_address += Self::build_node(child, next_address, contracts);
+        }
+        if node.reverts {
+            code.op_revert(0u64, 0u64);
+        } else {
+            code.op_stop();
+        }
+        contracts.push((Address::from_low_u64_be(address), code));
+        next_address - address
+    }
+}
+
+/// Contracts generated by a [`CallTreeBuilder`], the root being the first
+/// one.
+#[derive(Debug, Clone)]
+pub struct CallTree {
+    contracts: Vec<(Address, Bytecode)>,
+    /// Balance of each contract, enough for all the values sent in the tree
+    balance: Word,
+}
+
+impl CallTree {
+    /// Number of contracts.
+    pub fn len(&self) -> usize {
+        self.contracts.len()
+    }
+
+    /// Return true if the tree has no contract, which never happens.
+    pub fn is_empty(&self) -> bool {
+        self.contracts.is_empty()
+    }
+
+    /// Address of the root contract, called by the transaction.
+    pub fn root(&self) -> Address {
+        self.contracts[0].0
+    }
+
+    /// Addresses and codes of the contracts, in depth first order.
+    pub fn contracts(&self) -> &[(Address, Bytecode)] {
+        &self.contracts
+    }
+
+    /// Set the first accounts to the contracts, each one with enough balance
+    /// for all the values sent in the tree.
+    pub fn set_accounts(&self, accs: &mut [&mut MockAccount]) {
+        assert!(
+            accs.len() >= self.len(),
+            "{} accounts for {} contracts",
+            accs.len(),
+            self.len()
+        );
+        for (acc, (address, code)) in accs.iter_mut().zip(self.contracts.iter()) {
+            acc.address(*address)
+                .balance(self.balance)
+                .code(code.clone());
+        }
+    }
+
+    /// Create a [`TestContext`] with the contracts and a wallet sending a
+    /// transaction to the root, `NACC` being the number of contracts plus one.
+    pub fn test_ctx<const NACC: usize>(&self) -> Result<TestContext<NACC, 1>, Error> {
+        assert_eq!(
+            NACC,
+            self.len() + 1,
+            "NACC must be the number of contracts plus one"
+        );
+        let root = self.root();
+        TestContext::new(
+            None,
+            |mut accs| {
+                self.set_accounts(&mut accs);
+                accs[NACC - 1].address(MOCK_WALLETS[0].address());
+            },
+            |mut txs, _accs| {
+                txs[0].from(MOCK_WALLETS[0].clone()).to(root);
+            },
+            |block, _txs| block,
+        )
+    }
+}
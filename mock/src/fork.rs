@@ -0,0 +1,140 @@
+//! Remote state used to fork a [`TestContext`](crate::TestContext) from a live
+//! chain.
+
+use eth_types::{
+    geth_types::{self, Account, AccountOverride, StateOverrides},
+    Address, Block, Error, GethExecTrace, ToBigEndian, ToWord, Word, H256,
+};
+use ethers_core::types::{BlockId, BlockNumber};
+use ethers_providers::{Http, Middleware, Provider};
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+};
+use tokio::runtime::Runtime;
+
+fn rpc_error(err: impl std::fmt::Display) -> Error {
+    Error::TracingError(format!("fork rpc error: {err}"))
+}
+
+/// State of a remote node at a given block, fetched lazily, one account or
+/// storage slot at a time.
+pub(crate) struct ForkedState {
+    provider: Provider<Http>,
+    runtime: Runtime,
+    block: BlockId,
+    /// Accounts fetched so far, only holding the storage slots fetched so far
+    accounts: HashMap<Address, Account>,
+}
+
+impl ForkedState {
+    /// Fork the state after block `block_number` of the node at `rpc_url`.
+    pub(crate) fn new(rpc_url: &str, block_number: u64) -> Result<Self, Error> {
+        let provider = Provider::<Http>::try_from(rpc_url).map_err(rpc_error)?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(rpc_error)?;
+        Ok(Self {
+            provider,
+            runtime,
+            block: BlockNumber::Number(block_number.into()).into(),
+            accounts: HashMap::new(),
+        })
+    }
+
+    fn request<T, E: std::fmt::Display>(
+        &self,
+        request: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, Error> {
+        self.runtime.block_on(request).map_err(rpc_error)
+    }
+
+    /// Header of the forked block.
+    pub(crate) fn header(&self) -> Result<Block<H256>, Error> {
+        self.request(self.provider.get_block(self.block))?
+            .ok_or(Error::IncompleteBlock)
+    }
+
+    fn fetch_account(&mut self, address: Address) -> Result<&mut Account, Error> {
+        if !self.accounts.contains_key(&address) {
+            let block = Some(self.block);
+            let account = Account {
+                address,
+                nonce: self.request(self.provider.get_transaction_count(address, block))?,
+                balance: self.request(self.provider.get_balance(address, block))?,
+                code: self.request(self.provider.get_code(address, block))?,
+                storage: HashMap::new(),
+            };
+            self.accounts.insert(address, account);
+        }
+        Ok(self.accounts.get_mut(&address).expect("account fetched"))
+    }
+
+    fn fetch_storage(&mut self, address: Address, key: Word) -> Result<(), Error> {
+        if !self.fetch_account(address)?.storage.contains_key(&key) {
+            let value = self.request(self.provider.get_storage_at(
+                address,
+                H256::from(key.to_be_bytes()),
+                Some(self.block),
+            ))?;
+            self.fetch_account(address)?
+                .storage
+                .insert(key, value.to_word());
+        }
+        Ok(())
+    }
+
+    /// Fetch the accounts in `addresses` which are not fetched yet.
+    pub(crate) fn fetch_accounts(
+        &mut self,
+        addresses: impl IntoIterator<Item = Address>,
+    ) -> Result<(), Error> {
+        for address in addresses {
+            self.fetch_account(address)?;
+        }
+        Ok(())
+    }
+
+    /// Fetch the accounts and storage slots accessed by `traces` which are
+    /// not fetched yet. Returns whether anything was fetched, in which case
+    /// the block needs to be traced again against the completed state.
+    pub(crate) fn fetch_accessed(&mut self, traces: &[GethExecTrace]) -> Result<bool, Error> {
+        let mut fetched = false;
+        for (address, prestate) in traces.iter().flat_map(|trace| trace.prestate.iter()) {
+            if !self.accounts.contains_key(address) {
+                self.fetch_account(*address)?;
+                fetched = true;
+            }
+            for key in prestate.storage.iter().flat_map(|storage| storage.keys()) {
+                if !self.accounts[address].storage.contains_key(key) {
+                    self.fetch_storage(*address, *key)?;
+                    fetched = true;
+                }
+            }
+        }
+        Ok(fetched)
+    }
+
+    /// The fetched accounts, with `overrides` applied on top.
+    pub(crate) fn accounts(&self, overrides: &StateOverrides) -> BTreeMap<Address, Account> {
+        let mut accounts = self
+            .accounts
+            .iter()
+            .map(|(address, account)| (*address, account.clone()))
+            .collect();
+        geth_types::apply_state_overrides(&mut accounts, overrides);
+        accounts
+    }
+}
+
+/// Turn a mock account into an override of the remote account at its address.
+/// Only the fields set to non default values are overridden.
+pub(crate) fn mock_account_override(account: &Account) -> AccountOverride {
+    AccountOverride {
+        balance: (!account.balance.is_zero()).then_some(account.balance),
+        nonce: (!account.nonce.is_zero()).then(|| account.nonce.as_u64().into()),
+        code: (!account.code.is_empty()).then(|| account.code.clone()),
+        state_diff: account.storage.clone(),
+    }
+}
@@ -8,6 +8,10 @@ use rand_chacha::ChaCha20Rng;
 use std::sync::LazyLock;
 mod account;
 mod block;
+pub mod call_tree;
+#[cfg(feature = "fork")]
+mod fork;
+pub mod precompile;
 pub mod test_ctx;
 mod transaction;
 
@@ -0,0 +1,226 @@
+//! Calls to the precompiled contracts with representative valid and invalid
+//! inputs, so that the precompile gadgets are exercised the same way by the
+//! circuit tests and the testool scenarios.
+
+use eth_types::{bytecode::Bytecode, evm_types::OpcodeId, Address, ToWord, Word};
+
+/// A call to a precompiled contract.
+#[derive(Debug, Clone)]
+pub struct PrecompileCase {
+    /// Description of the case
+    pub name: &'static str,
+    /// Address of the precompile
+    pub address: Address,
+    /// Calldata of the call
+    pub input: Vec<u8>,
+    /// Number of bytes of the return data copied to memory
+    pub ret_size: usize,
+    /// Whether the call succeeds with [`PrecompileCase::GAS`]. Note that
+    /// ecrecover succeeds with an empty output on an invalid signature, and
+    /// that ripemd160 and blake2f always fail with the `scroll` feature.
+    pub valid: bool,
+}
+
+impl PrecompileCase {
+    /// Gas given to each call, enough for the most expensive valid case, so
+    /// that a failing call doesn't consume the gas of the next ones.
+    pub const GAS: u64 = 100_000;
+
+    fn new(name: &'static str, address: u64, input: Vec<u8>, ret_size: usize, valid: bool) -> Self {
+        Self {
+            name,
+            address: Address::from_low_u64_be(address),
+            input,
+            ret_size,
+            valid,
+        }
+    }
+
+    /// Bytecode storing the input in memory at 0, then calling the precompile
+    /// with `call_op` and [`PrecompileCase::GAS`], the return data being
+    /// copied right after the input. The success of the call is stored at
+    /// `result_slot`.
+    pub fn code(&self, call_op: OpcodeId, result_slot: usize) -> Bytecode {
+        assert!(
+            call_op.is_call_with_value() || call_op.is_call_without_value(),
+            "{call_op:?} is not a call op"
+        );
+        let mut code = Bytecode::default();
+        for (offset, chunk) in self.input.chunks(32).enumerate() {
+            // the last chunk is padded with zeros, past the calldata
+            let mut word = [0u8; 32];
+            word[..chunk.len()].copy_from_slice(chunk);
+            code.op_mstore(offset * 32, Word::from_big_endian(&word));
+        }
+        let ret_offset = self.input.len().next_multiple_of(32);
+        code.push(32, self.ret_size)
+            .push(32, ret_offset)
+            .push(32, self.input.len())
+            .push(32, Word::zero());
+        if call_op.is_call_with_value() {
+            code.push(32, Word::zero());
+        }
+        code.push(32, self.address.to_word())
+            .push(32, Self::GAS)
+            .write_op(call_op)
+            .push(32, result_slot)
+            .write_op(OpcodeId::SSTORE);
+        code
+    }
+}
+
+fn words(words: &[&str]) -> Vec<u8> {
+    words
+        .iter()
+        .flat_map(|word| hex::decode(word).expect("invalid hex"))
+        .collect()
+}
+
+const ECRECOVER_HASH: &str = "456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3";
+const ECRECOVER_R: &str = "9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac8038825608";
+const ECRECOVER_S: &str = "4f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada";
+
+const ONE: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const TWO: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+const THREE: &str = "0000000000000000000000000000000000000000000000000000000000000003";
+
+/// The blake2b state of the EIP-152 test vectors
+const BLAKE2F_H: &str = "48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+                         d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b";
+
+fn blake2f_input(final_block: u8) -> Vec<u8> {
+    let mut input = hex::decode("0000000c").unwrap();
+    input.extend(hex::decode(BLAKE2F_H).unwrap());
+    // "abc" in the 128 bytes message block
+    let mut message = [0u8; 128];
+    message[..3].copy_from_slice(b"abc");
+    input.extend(message);
+    // offset counters
+    input.extend([3, 0, 0, 0, 0, 0, 0, 0]);
+    input.extend([0u8; 8]);
+    input.push(final_block);
+    input
+}
+
+/// The calls of each precompile, 0x01 to 0x09, with valid and invalid
+/// inputs.
+pub fn precompile_cases() -> Vec<PrecompileCase> {
+    let ecrecover = |v: &str| words(&[ECRECOVER_HASH, v, ECRECOVER_R, ECRECOVER_S]);
+    vec![
+        PrecompileCase::new(
+            "ecrecover",
+            0x01,
+            ecrecover(&format!("{:064x}", 28)),
+            32,
+            true,
+        ),
+        // the signature isn't recovered, but the call succeeds
+        PrecompileCase::new(
+            "ecrecover (invalid v)",
+            0x01,
+            ecrecover(&format!("{:064x}", 29)),
+            32,
+            true,
+        ),
+        PrecompileCase::new("sha256", 0x02, b"abc".to_vec(), 32, true),
+        PrecompileCase::new("sha256 (empty input)", 0x02, vec![], 32, true),
+        PrecompileCase::new(
+            "ripemd160",
+            0x03,
+            b"abc".to_vec(),
+            32,
+            cfg!(not(feature = "scroll")),
+        ),
+        PrecompileCase::new("identity", 0x04, (0..40).collect(), 40, true),
+        PrecompileCase::new("identity (empty input)", 0x04, vec![], 0, true),
+        // 8 ** 9 % 10
+        PrecompileCase::new(
+            "modexp",
+            0x05,
+            [words(&[ONE, ONE, ONE]), vec![8, 9, 10]].concat(),
+            1,
+            true,
+        ),
+        PrecompileCase::new(
+            "modexp (zero modulus)",
+            0x05,
+            [words(&[ONE, ONE, ONE]), vec![8, 9, 0]].concat(),
+            1,
+            true,
+        ),
+        #[cfg(feature = "scroll")]
+        PrecompileCase::new(
+            "modexp (modulus longer than 32 bytes)",
+            0x05,
+            [words(&[ONE, ONE, &format!("{:064x}", 33)]), vec![8, 9, 10]].concat(),
+            33,
+            false,
+        ),
+        // (1, 2) + (1, 2)
+        PrecompileCase::new("ecAdd", 0x06, words(&[ONE, TWO, ONE, TWO]), 64, true),
+        PrecompileCase::new(
+            "ecAdd (point not on curve)",
+            0x06,
+            words(&[TWO, THREE, ONE, TWO]),
+            64,
+            false,
+        ),
+        // (1, 2) * 3
+        PrecompileCase::new("ecMul", 0x07, words(&[ONE, TWO, THREE]), 64, true),
+        PrecompileCase::new(
+            "ecMul (point not on curve)",
+            0x07,
+            words(&[TWO, THREE, THREE]),
+            64,
+            false,
+        ),
+        PrecompileCase::new("ecPairing (empty input)", 0x08, vec![], 32, true),
+        // (0, 0) is the point at infinity of G1, and of G2
+        PrecompileCase::new(
+            "ecPairing (points at infinity)",
+            0x08,
+            vec![0; 192],
+            32,
+            true,
+        ),
+        PrecompileCase::new(
+            "ecPairing (length not a multiple of 192)",
+            0x08,
+            vec![0; 100],
+            32,
+            false,
+        ),
+        PrecompileCase::new(
+            "blake2f",
+            0x09,
+            blake2f_input(1),
+            64,
+            cfg!(not(feature = "scroll")),
+        ),
+        PrecompileCase::new(
+            "blake2f (invalid final flag)",
+            0x09,
+            blake2f_input(2),
+            64,
+            false,
+        ),
+    ]
+}
+
+/// Bytecode calling the precompiles with each of the [`precompile_cases`] in
+/// turn, with `call_op`. The success of the call of the `i`-th case is stored
+/// at the slot `i`.
+pub fn precompile_calls_code(call_op: OpcodeId) -> Bytecode {
+    let mut code = Bytecode::default();
+    for (slot, case) in precompile_cases().iter().enumerate() {
+        code.append(&case.code(call_op, slot));
+    }
+    code.op_stop();
+    code
+}
+
+/// Gas limit of a transaction running [`precompile_calls_code`]: the gas of
+/// each call, plus the cost of storing its result and copying its input.
+pub fn precompile_calls_gas() -> u64 {
+    21_000 + precompile_cases().len() as u64 * (PrecompileCase::GAS + 30_000)
+}
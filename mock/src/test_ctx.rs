@@ -1,5 +1,7 @@
 //! Mock types and functions to generate Test environments for ZKEVM tests
 
+#[cfg(feature = "fork")]
+use crate::fork::{self, ForkedState};
 use crate::{eth, MockAccount, MockBlock, MockTransaction, MOCK_WALLETS};
 #[cfg(feature = "scroll")]
 use eth_types::l2_types::BlockTrace;
@@ -95,6 +97,9 @@ pub struct TestContext<const NACC: usize, const NTX: usize> {
     pub eth_block: eth_types::Block<eth_types::Transaction>,
     /// Execution Trace from geth
     pub geth_traces: Vec<eth_types::GethExecTrace>,
+    /// Accounts fetched from the remote node a forked context is built on,
+    /// other than the ones in `accounts`
+    pub forked_accounts: Vec<Account>,
     #[cfg(feature = "scroll")]
     block_trace: BlockTrace,
 }
@@ -107,6 +112,105 @@ fn deployed_system_contract_for_test_env() -> Vec<Account> {
     }
 }
 
+/// All the accounts of the test env: the system contracts are injected unless
+/// they are already part of `accounts`.
+fn test_env_accounts(accounts: impl Iterator<Item = Account>) -> Vec<Account> {
+    let accounts = accounts.collect_vec();
+    let system_contracts = deployed_system_contract_for_test_env()
+        .into_iter()
+        .filter(|contract| accounts.iter().all(|acc| acc.address != contract.address))
+        .collect_vec();
+    accounts.into_iter().chain(system_contracts).collect()
+}
+
+/// Maximum amount the transactions can take from their sender: the value
+/// plus the gas limit times the gas price, or the max fee per gas for the
+/// EIP-1559 transactions. `None` on overflow.
+fn max_tx_cost(tx: &Transaction) -> Option<Word> {
+    // L1 messages don't pay for their gas
+    if tx.transaction_type.map(|ty| ty.as_u64()) == Some(0x7e) {
+        return Some(tx.value);
+    }
+    let gas_price = if tx.transaction_type.map(|ty| ty.as_u64()) == Some(2) {
+        tx.max_fee_per_gas
+    } else {
+        tx.gas_price
+    }
+    .unwrap_or_default();
+    tx.gas
+        .checked_mul(gas_price)
+        .and_then(|fee| fee.checked_add(tx.value))
+}
+
+/// Raise the balance of the senders of `transactions` among `accounts`, so
+/// that the tests don't depend on magic balances which break when the gas
+/// costs change. The L1 fee of the scroll transactions isn't accounted for.
+fn fund_senders(accounts: &mut [MockAccount], transactions: &[Transaction]) {
+    for acc in accounts.iter_mut().filter(|acc| !acc.skip_auto_funding) {
+        let required = transactions
+            .iter()
+            .filter(|tx| tx.from == acc.address)
+            .try_fold(Word::zero(), |total, tx| {
+                max_tx_cost(tx).and_then(|cost| total.checked_add(cost))
+            });
+        match required {
+            Some(required) if required > acc.balance => {
+                log::debug!(
+                    "funding {:?} with {} to pay for its txs",
+                    acc.address,
+                    required - acc.balance
+                );
+                acc.balance = required;
+            }
+            Some(_) => {}
+            None => log::warn!("the txs of {:?} overflow, not funded", acc.address),
+        }
+    }
+}
+
+/// Execution traces of a test block.
+struct BlockTraces {
+    geth_traces: Vec<eth_types::GethExecTrace>,
+    #[cfg(feature = "scroll")]
+    block_trace: BlockTrace,
+}
+
+fn trace_block(
+    chain_id: u64,
+    block: &Block<Transaction>,
+    accounts: Vec<Account>,
+    history_hashes: Option<Vec<Word>>,
+    logger_config: LoggerConfig,
+) -> Result<BlockTraces, Error> {
+    let trace_config = gen_trace_config(
+        chain_id,
+        block.clone(),
+        accounts,
+        history_hashes,
+        logger_config,
+    )?;
+
+    #[cfg(feature = "scroll")]
+    let block_trace = l2trace(&trace_config)?;
+
+    #[cfg(feature = "scroll")]
+    let geth_traces = block_trace
+        .execution_results
+        .clone()
+        .into_iter()
+        .map(From::from)
+        .collect::<Vec<_>>();
+
+    #[cfg(not(feature = "scroll"))]
+    let geth_traces = trace(&trace_config)?;
+
+    Ok(BlockTraces {
+        geth_traces,
+        #[cfg(feature = "scroll")]
+        block_trace,
+    })
+}
+
 impl<const NACC: usize, const NTX: usize> From<TestContext<NACC, NTX>> for GethData {
     fn from(ctx: TestContext<NACC, NTX>) -> GethData {
         GethData {
@@ -116,12 +220,12 @@ impl<const NACC: usize, const NTX: usize> From<TestContext<NACC, NTX>> for GethD
             geth_traces: ctx.geth_traces.to_vec(),
             // TODO: any better method?
             // always inject system contract
-            accounts: ctx
-                .accounts
-                .iter()
-                .cloned()
-                .chain(deployed_system_contract_for_test_env())
-                .collect_vec(),
+            accounts: test_env_accounts(
+                ctx.accounts
+                    .iter()
+                    .cloned()
+                    .chain(ctx.forked_accounts.iter().cloned()),
+            ),
             #[cfg(feature = "scroll")]
             block_trace: ctx.block_trace,
         }
@@ -129,13 +233,17 @@ impl<const NACC: usize, const NTX: usize> From<TestContext<NACC, NTX>> for GethD
 }
 
 impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
-    pub fn new_with_logger_config<FAcc, FTx, Fb>(
-        history_hashes: Option<Vec<Word>>,
+    /// Apply the `acc_fns`, `func_tx` and `func_block` modifiers, returning
+    /// the chain id, the accounts and the block to trace. The accounts
+    /// sending transactions are funded to pay for them, unless
+    /// [`MockAccount::skip_auto_funding`] is set.
+    #[allow(clippy::type_complexity)]
+    fn build<FAcc, FTx, Fb>(
+        history_hashes: &Option<Vec<Word>>,
         acc_fns: FAcc,
         func_tx: FTx,
         func_block: Fb,
-        logger_config: LoggerConfig,
-    ) -> Result<Self, Error>
+    ) -> (u64, [Account; NACC], Block<Transaction>)
     where
         FTx: FnOnce(Vec<&mut MockTransaction>, [MockAccount; NACC]),
         Fb: FnOnce(&mut MockBlock, Vec<MockTransaction>) -> &mut MockBlock,
@@ -149,7 +257,7 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
             .try_into()
             .expect("Mismatched len err");
         acc_fns(account_refs);
-        let accounts: [MockAccount; NACC] = accounts
+        let mut accounts: [MockAccount; NACC] = accounts
             .iter_mut()
             .map(|acc| acc.build())
             .collect_vec()
@@ -190,6 +298,7 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
 
         let chain_id = block.chain_id;
         let block = Block::<Transaction>::from(block);
+        fund_senders(&mut accounts, &block.transactions);
         let accounts: [Account; NACC] = accounts
             .iter()
             .cloned()
@@ -198,40 +307,119 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
             .try_into()
             .expect("Mismatched acc len");
 
-        let trace_config = gen_trace_config(
+        (chain_id, accounts, block)
+    }
+
+    /// Create a new TestContext like [`TestContext::new`], capturing the
+    /// parts of the steps given by `logger_config`.
+    ///
+    /// Returns an error if `logger_config` captures a part of the steps which
+    /// isn't kept by the enabled features, see
+    /// [`LoggerConfig::check_supported`].
+    pub fn new_with_logger_config<FAcc, FTx, Fb>(
+        history_hashes: Option<Vec<Word>>,
+        acc_fns: FAcc,
+        func_tx: FTx,
+        func_block: Fb,
+        logger_config: LoggerConfig,
+    ) -> Result<Self, Error>
+    where
+        FTx: FnOnce(Vec<&mut MockTransaction>, [MockAccount; NACC]),
+        Fb: FnOnce(&mut MockBlock, Vec<MockTransaction>) -> &mut MockBlock,
+        FAcc: FnOnce([&mut MockAccount; NACC]),
+    {
+        logger_config.check_supported()?;
+        let (chain_id, accounts, block) =
+            Self::build(&history_hashes, acc_fns, func_tx, func_block);
+
+        let traces = trace_block(
             chain_id,
-            block.clone(),
-            accounts
-                .iter()
-                .cloned()
-                .chain(deployed_system_contract_for_test_env())
-                .collect_vec(),
+            &block,
+            test_env_accounts(accounts.iter().cloned()),
             history_hashes.clone(),
             logger_config,
         )?;
 
-        #[cfg(feature = "scroll")]
-        let block_trace = l2trace(&trace_config)?;
+        Ok(Self {
+            chain_id,
+            accounts,
+            history_hashes: history_hashes.unwrap_or_default(),
+            eth_block: block,
+            geth_traces: traces.geth_traces,
+            forked_accounts: vec![],
+            #[cfg(feature = "scroll")]
+            block_trace: traces.block_trace,
+        })
+    }
 
-        #[cfg(feature = "scroll")]
-        let geth_traces = block_trace
-            .execution_results
-            .clone()
-            .into_iter()
-            .map(From::from)
-            .collect::<Vec<_>>();
+    /// Create a new TestContext on top of the state after block
+    /// `block_number` of the node at `rpc_url`, in the context of the block
+    /// which would follow it.
+    ///
+    /// The `acc_fns` and `func_tx` modifiers work as in [`TestContext::new`],
+    /// except that the mock accounts are overlaid on the remote accounts at
+    /// the same address: only their non default fields are overridden, and
+    /// their storage is merged into the remote one. Any other account or
+    /// storage slot accessed by the transactions is fetched lazily from the
+    /// node, tracing the block again until no new state is accessed.
+    #[cfg(feature = "fork")]
+    pub fn new_forked<FAcc, FTx>(
+        rpc_url: &str,
+        block_number: u64,
+        acc_fns: FAcc,
+        func_tx: FTx,
+    ) -> Result<Self, Error>
+    where
+        FTx: FnOnce(Vec<&mut MockTransaction>, [MockAccount; NACC]),
+        FAcc: FnOnce([&mut MockAccount; NACC]),
+    {
+        let mut state = ForkedState::new(rpc_url, block_number)?;
+        let header = state.header()?;
+        let parent_hash = header.hash.ok_or(Error::IncompleteBlock)?;
+        let history_hashes = Some(vec![parent_hash.into_uint()]);
+        let (chain_id, accounts, block) =
+            Self::build(&history_hashes, acc_fns, func_tx, |block, _txs| {
+                block
+                    .number(block_number + 1)
+                    .author(header.author.unwrap_or_default())
+                    .timestamp(header.timestamp)
+                    .gas_limit(header.gas_limit)
+                    .base_fee_per_gas(header.base_fee_per_gas.unwrap_or_default())
+            });
+
+        let overrides = accounts
+            .iter()
+            .map(|acc| (acc.address, fork::mock_account_override(acc)))
+            .collect();
+        state.fetch_accounts(accounts.iter().map(|acc| acc.address))?;
+        let traces = loop {
+            let traces = trace_block(
+                chain_id,
+                &block,
+                test_env_accounts(state.accounts(&overrides).into_values()),
+                history_hashes.clone(),
+                LoggerConfig::default(),
+            )?;
+            if !state.fetch_accessed(&traces.geth_traces)? {
+                break traces;
+            }
+        };
 
-        #[cfg(not(feature = "scroll"))]
-        let geth_traces = trace(&trace_config)?;
+        let mut forked_accounts = state.accounts(&overrides);
+        let accounts = accounts.map(|acc| forked_accounts[&acc.address].clone());
+        for acc in accounts.iter() {
+            forked_accounts.remove(&acc.address);
+        }
 
         Ok(Self {
             chain_id,
             accounts,
             history_hashes: history_hashes.unwrap_or_default(),
             eth_block: block,
-            geth_traces,
+            geth_traces: traces.geth_traces,
+            forked_accounts: forked_accounts.into_values().collect(),
             #[cfg(feature = "scroll")]
-            block_trace,
+            block_trace: traces.block_trace,
         })
     }
 
@@ -273,13 +461,23 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
     /// account_0_code_wallet_0_no_code`]. Extra accounts, txs and/or block
     /// configs are set as [`Default`].
     pub fn simple_ctx_with_bytecode(bytecode: Bytecode) -> Result<TestContext<2, 1>, Error> {
-        TestContext::new(
+        Self::simple_ctx_with_bytecode_and_logger_config(bytecode, LoggerConfig::default())
+    }
+
+    /// Same as [`TestContext::simple_ctx_with_bytecode`], capturing the parts
+    /// of the steps given by `logger_config`.
+    pub fn simple_ctx_with_bytecode_and_logger_config(
+        bytecode: Bytecode,
+        logger_config: LoggerConfig,
+    ) -> Result<TestContext<2, 1>, Error> {
+        TestContext::new_with_logger_config(
             None,
             account_0_code_wallet_0_no_code(bytecode),
             |mut txs, accs| {
                 txs[0].from(MOCK_WALLETS[0].clone()).to(accs[0].address);
             },
             |block, _txs| block.number(0xcafeu64),
+            logger_config,
         )
     }
 }
@@ -358,3 +556,84 @@ pub mod helpers {
         txs[0].from(accs[1].address).to(accs[0].address);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gwei, MOCK_ACCOUNTS};
+
+    /// Balances of the accounts of a context where the first account sends
+    /// a transfer of 5 wei to the second one with `tx`.
+    fn funded_balances(
+        sender: impl FnOnce(&mut MockAccount),
+        tx: impl FnOnce(&mut MockTransaction),
+    ) -> [Word; 2] {
+        let (_, accounts, _) = TestContext::<2, 1>::build(
+            &None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]);
+                sender(accs[0]);
+                accs[1].address(MOCK_ACCOUNTS[1]);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .value(Word::from(5))
+                    .gas(Word::from(21000));
+                tx(txs[0]);
+            },
+            |block, _txs| block,
+        );
+        accounts.map(|acc| acc.balance)
+    }
+
+    #[test]
+    fn fund_senders_max_tx_cost() {
+        let balances = funded_balances(
+            |_| {},
+            |tx| {
+                tx.gas_price(gwei(2));
+            },
+        );
+        assert_eq!(balances, [gwei(2) * 21000u64 + 5u64, Word::zero()]);
+
+        // already funded senders are left as is
+        let balances = funded_balances(
+            |acc| {
+                acc.balance(eth(1));
+            },
+            |tx| {
+                tx.gas_price(gwei(2));
+            },
+        );
+        assert_eq!(balances[0], eth(1));
+    }
+
+    #[test]
+    fn fund_senders_skip_auto_funding() {
+        let balances = funded_balances(
+            |acc| {
+                acc.balance(Word::from(7)).skip_auto_funding();
+            },
+            |tx| {
+                tx.gas_price(gwei(2));
+            },
+        );
+        assert_eq!(balances[0], Word::from(7));
+    }
+
+    #[test]
+    fn fund_senders_eip1559_max_fee() {
+        let balances = funded_balances(
+            |_| {},
+            |tx| {
+                tx.transaction_type(2)
+                    .gas_price(gwei(1))
+                    .max_priority_fee_per_gas(gwei(1))
+                    .max_fee_per_gas(gwei(3));
+            },
+        );
+        assert_eq!(balances[0], gwei(3) * 21000u64 + 5u64);
+    }
+}
@@ -5,7 +5,7 @@ use crate::{
     io::{force_to_read, try_to_read},
     BatchProof, BatchProvingTask, ChunkProof,
 };
-use aggregator::{ChunkInfo, MAX_AGG_SNARKS};
+use aggregator::{BatchHash, ChunkInfo, MAX_AGG_SNARKS};
 use anyhow::{bail, Result};
 use sha2::{Digest, Sha256};
 use snark_verifier_sdk::Snark;
@@ -133,12 +133,15 @@ impl Prover {
             layer2_snarks.extend(repeat(padding_snark).take(MAX_AGG_SNARKS - real_chunk_count));
         }
 
+        let batch_hash = BatchHash::construct(&chunk_hashes)
+            .with_batch_header(batch.parent_batch_hash, batch.batch_header_encoding);
+
         // Load or generate aggregation snark (layer-3).
         let layer3_snark = self.prover_impl.load_or_gen_agg_snark(
             name,
             LayerId::Layer3.id(),
             LayerId::Layer3.degree(),
-            &chunk_hashes,
+            batch_hash,
             &layer2_snarks,
             output_dir,
         )?;
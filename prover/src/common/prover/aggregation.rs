@@ -4,7 +4,7 @@ use crate::{
     io::{load_snark, write_snark},
     utils::gen_rng,
 };
-use aggregator::{AggregationCircuit, BatchHash, ChunkInfo, MAX_AGG_SNARKS};
+use aggregator::{AggregationCircuit, BatchHash, MAX_AGG_SNARKS};
 use anyhow::{anyhow, Result};
 use rand::Rng;
 use snark_verifier_sdk::Snark;
@@ -16,13 +16,11 @@ impl Prover {
         id: &str,
         degree: u32,
         mut rng: impl Rng + Send,
-        chunk_hashes: &[ChunkInfo],
+        batch_hash: BatchHash<MAX_AGG_SNARKS>,
         previous_snarks: &[Snark],
     ) -> Result<Snark> {
         env::set_var("AGGREGATION_CONFIG", layer_config_path(id));
 
-        let batch_hash = BatchHash::construct(chunk_hashes);
-
         let circuit: AggregationCircuit<MAX_AGG_SNARKS> =
             AggregationCircuit::new(self.params(degree), previous_snarks, &mut rng, batch_hash)
                 .map_err(|err| anyhow!("Failed to construct aggregation circuit: {err:?}"))?;
@@ -35,7 +33,7 @@ impl Prover {
         name: &str,
         id: &str,
         degree: u32,
-        chunk_hashes: &[ChunkInfo],
+        batch_hash: BatchHash<MAX_AGG_SNARKS>,
         previous_snarks: &[Snark],
         output_dir: Option<&str>,
     ) -> Result<Snark> {
@@ -50,7 +48,7 @@ impl Prover {
             Some(snark) => Ok(snark),
             None => {
                 let rng = gen_rng();
-                let result = self.gen_agg_snark(id, degree, rng, chunk_hashes, previous_snarks);
+                let result = self.gen_agg_snark(id, degree, rng, batch_hash, previous_snarks);
                 if let (Some(_), Ok(snark)) = (output_dir, &result) {
                     write_snark(&file_path, snark);
                 }
@@ -34,6 +34,8 @@ pub fn gen_evm_verifier<C: CircuitExt<Fr>>(
         // Dump bytecode.
         let mut dir = PathBuf::from_str(dir).unwrap();
         write_file(&mut dir, "evm_verifier.bin", &deployment_code);
+        // Dump the proof in the transport encoding, whose tail is the verifier calldata.
+        write_file(&mut dir, "evm_proof.bin", &evm_proof.proof.encode());
     }
 
     let success = evm_proof.proof.evm_verify(deployment_code);
@@ -0,0 +1,292 @@
+//! Locating, downloading, verifying and caching the KZG params of the provers.
+//!
+//! The params of degree `k` are stored as `params{k}` in a params directory,
+//! as produced by `testool/download_setup.sh`. A missing file is downloaded
+//! from the scroll setup release, or from another source following the same
+//! layout. Params are checked against a known SHA-256 digest before use, and
+//! params without a known digest are rejected. The digests come from the
+//! `params.sha256` file of the directory, in the `sha256sum` format, or from
+//! [`ParamsManager::with_checksum`]. [`SCROLL_PARAMS_SHA256`] holds no digest
+//! of the scroll setup release yet, so the `params.sha256` file is mandatory
+//! for now: generate it with `sha256sum params* > params.sha256` from params
+//! obtained from a trusted source.
+
+use crate::utils::{load_params, param_path_for_degree};
+use anyhow::{bail, Context, Result};
+use halo2_proofs::{
+    halo2curves::bn256::Bn256,
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Arc, Mutex},
+};
+
+/// Release of the scroll setup params, with `{degree}` standing for the degree.
+pub const SCROLL_PARAMS_URL: &str =
+    "https://circuit-release.s3.us-west-2.amazonaws.com/setup/params{degree}";
+
+/// SHA-256 digests of the params of the [`SCROLL_PARAMS_URL`] release, by
+/// degree. It is empty until the digests of the release are published, so
+/// the params of every degree need their digest in the `params.sha256` file
+/// of the directory, or given by [`ParamsManager::with_checksum`].
+pub const SCROLL_PARAMS_SHA256: &[(u32, &str)] = &[];
+
+/// Environment variable overriding the default params directory.
+pub const PARAMS_DIR_ENV: &str = "SCROLL_PROVER_PARAMS_DIR";
+
+/// File of a params directory listing the SHA-256 digests of its params.
+pub const CHECKSUMS_FILE: &str = "params.sha256";
+
+/// Params of a directory, downloaded on demand and loaded at most once per
+/// degree.
+#[derive(Debug)]
+pub struct ParamsManager {
+    dir: PathBuf,
+    url: Option<String>,
+    checksums: HashMap<u32, String>,
+    loaded: Mutex<BTreeMap<u32, Arc<ParamsKZG<Bn256>>>>,
+    /// Lock of each degree being downloaded, so that a degree is downloaded
+    /// once without blocking the loading of the other degrees
+    downloads: Mutex<HashMap<u32, Arc<Mutex<()>>>>,
+}
+
+impl ParamsManager {
+    /// Manage the params of `dir`, downloading the missing ones from the
+    /// scroll setup release.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            url: Some(SCROLL_PARAMS_URL.to_string()),
+            checksums: SCROLL_PARAMS_SHA256
+                .iter()
+                .map(|(degree, digest)| (*degree, digest.to_lowercase()))
+                .collect(),
+            loaded: Mutex::new(BTreeMap::new()),
+            downloads: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Manage the params of the directory of [`PARAMS_DIR_ENV`], or of
+    /// `$HOME/.cache/scroll-prover/params` by default.
+    pub fn from_env() -> Self {
+        let dir = std::env::var_os(PARAMS_DIR_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                let home = std::env::var_os("HOME").unwrap_or_else(|| ".".into());
+                Path::new(&home).join(".cache/scroll-prover/params")
+            });
+        Self::new(dir)
+    }
+
+    /// Download the missing params from `url`, where `{degree}` stands for the
+    /// degree, or never download them with `None`.
+    pub fn with_url(mut self, url: Option<&str>) -> Self {
+        self.url = url.map(str::to_string);
+        self
+    }
+
+    /// Expect the params of `degree` to have the hex encoded SHA-256 `digest`.
+    pub fn with_checksum(mut self, degree: u32, digest: &str) -> Self {
+        self.checksums.insert(degree, digest.to_lowercase());
+        self
+    }
+
+    /// Directory of the params.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the params of `degree`, whether they exist or not.
+    pub fn path(&self, degree: u32) -> PathBuf {
+        PathBuf::from(param_path_for_degree(&self.dir.to_string_lossy(), degree))
+    }
+
+    /// Path of the params of `degree` if they are in the directory.
+    pub fn locate(&self, degree: u32) -> Option<PathBuf> {
+        Some(self.path(degree)).filter(|path| path.is_file())
+    }
+
+    /// Expected SHA-256 digest of the params of `degree`, if known. The
+    /// digests given by [`Self::with_checksum`] or listed in
+    /// [`SCROLL_PARAMS_SHA256`] take precedence over the `params.sha256` file
+    /// of the directory.
+    pub fn checksum(&self, degree: u32) -> Result<Option<String>> {
+        if let Some(digest) = self.checksums.get(&degree) {
+            return Ok(Some(digest.clone()));
+        }
+        let path = self.dir.join(CHECKSUMS_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let filename = format!("params{degree}");
+        Ok(parse_checksums(&fs::read_to_string(&path)?).remove(&filename))
+    }
+
+    /// Check the params file of `degree` against its expected digest, failing
+    /// when the digest is not known.
+    pub fn verify(&self, degree: u32, path: &Path) -> Result<()> {
+        let Some(expected) = self.checksum(degree)? else {
+            bail!(
+                "no checksum of params{degree} to verify {path:?}, add it to {:?}",
+                self.dir.join(CHECKSUMS_FILE)
+            );
+        };
+        let digest = sha256_file(path)?;
+        if digest != expected {
+            bail!("params{degree} at {path:?} has digest {digest}, expected {expected}");
+        }
+        Ok(())
+    }
+
+    /// Download the params of `degree` into the directory and verify them.
+    /// Params downloaded meanwhile by another thread are not downloaded again.
+    pub fn download(&self, degree: u32) -> Result<PathBuf> {
+        let Some(url) = &self.url else {
+            bail!("params{degree} not found in {:?}", self.dir);
+        };
+        let lock = self
+            .downloads
+            .lock()
+            .unwrap()
+            .entry(degree)
+            .or_default()
+            .clone();
+        let _guard = lock.lock().unwrap();
+        if let Some(path) = self.locate(degree) {
+            return Ok(path);
+        }
+        if self.checksum(degree)?.is_none() {
+            bail!(
+                "no checksum of params{degree} to verify its download, add it to {:?}",
+                self.dir.join(CHECKSUMS_FILE)
+            );
+        }
+        let url = url.replace("{degree}", &degree.to_string());
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(degree);
+        // a partial file per process, as other processes can share the
+        // directory
+        let partial = path.with_extension(format!("partial.{}", std::process::id()));
+        log::info!("downloading params{degree} from {url} to {path:?}");
+        let status = Command::new("curl")
+            .args(["-fsSL", "--retry", "3", "-o"])
+            .arg(&partial)
+            .arg(&url)
+            .status()
+            .context("run curl to download the params")?;
+        if !status.success() {
+            let _ = fs::remove_file(&partial);
+            bail!("failed to download params{degree} from {url}: {status}");
+        }
+        if let Err(e) = self.verify(degree, &partial) {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+        fs::rename(&partial, &path)?;
+        Ok(path)
+    }
+
+    /// Load the params of `degree`. They are taken from the ones already
+    /// loaded, read from the directory, downsized from loaded params of a
+    /// higher degree, or downloaded, in this order. The params are read and
+    /// downloaded without holding the lock of the loaded params.
+    pub fn load(&self, degree: u32) -> Result<Arc<ParamsKZG<Bn256>>> {
+        let higher = {
+            let loaded = self.loaded.lock().unwrap();
+            if let Some(params) = loaded.get(&degree) {
+                return Ok(params.clone());
+            }
+            loaded
+                .range(degree..)
+                .next()
+                .map(|(_, params)| params.clone())
+        };
+        let params = if let Some(path) = self.locate(degree) {
+            self.read(degree, &path)?
+        } else if let Some(higher) = higher {
+            let mut params = higher.as_ref().clone();
+            params.downsize(degree);
+            params
+        } else {
+            let path = self.download(degree)?;
+            self.read(degree, &path)?
+        };
+        // keep the params loaded first by another thread meanwhile
+        let mut loaded = self.loaded.lock().unwrap();
+        Ok(loaded.entry(degree).or_insert(Arc::new(params)).clone())
+    }
+
+    fn read(&self, degree: u32, path: &Path) -> Result<ParamsKZG<Bn256>> {
+        self.verify(degree, path)?;
+        load_params(&path.to_string_lossy(), degree, None)
+    }
+}
+
+/// Parse the `<digest>  <file>` lines of a `sha256sum` output.
+fn parse_checksums(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (digest, file) = line.trim().split_once(char::is_whitespace)?;
+            let file = file.trim_start().trim_start_matches('*');
+            let file = Path::new(file).file_name()?.to_string_lossy().to_string();
+            Some((file, digest.to_lowercase()))
+        })
+        .collect()
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 1 << 20];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_checksums() {
+        let dir = std::env::temp_dir().join(format!("params-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("params1");
+        fs::write(&path, b"abc").unwrap();
+        // sha256("abc")
+        let digest = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        fs::write(
+            dir.join(CHECKSUMS_FILE),
+            format!("{digest}  params1\n{}  *sub/params2\n", "00".repeat(32)),
+        )
+        .unwrap();
+
+        let manager = ParamsManager::new(&dir).with_url(None);
+        assert_eq!(manager.locate(1), Some(path.clone()));
+        assert_eq!(manager.locate(3), None);
+        assert_eq!(manager.checksum(2).unwrap(), Some("00".repeat(32)));
+        assert!(manager.verify(1, &path).is_ok());
+        assert!(manager.verify(2, &path).is_err());
+        assert!(manager.download(3).is_err());
+        // params without a known digest are rejected
+        assert_eq!(manager.checksum(3).unwrap(), None);
+        assert!(manager.verify(3, &path).is_err());
+
+        let manager = manager.with_checksum(1, &"11".repeat(32));
+        assert!(manager.verify(1, &path).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
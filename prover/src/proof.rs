@@ -14,10 +14,12 @@ use std::{fs::File, path::PathBuf};
 
 mod batch;
 mod chunk;
+mod encoding;
 mod evm;
 
 pub use batch::BatchProof;
 pub use chunk::{compare_chunk_info, ChunkProof};
+pub use encoding::{decode, encode, evm_calldata, ENCODING_VERSION};
 pub use evm::EvmProof;
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -68,6 +70,11 @@ impl Proof {
         dump_as_json(dir, filename, &self)
     }
 
+    /// Encode the proof and its instances with [`encode`].
+    pub fn encode(&self) -> Vec<u8> {
+        encode(&self.proof, &self.instances())
+    }
+
     pub fn evm_verify(&self, deployment_code: Vec<u8>) -> bool {
         verify_evm_proof(deployment_code, self.instances(), self.proof().to_vec())
     }
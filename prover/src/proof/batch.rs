@@ -6,9 +6,11 @@ use snark_verifier_sdk::encode_calldata;
 
 const ACC_LEN: usize = 12;
 const PI_LEN: usize = 32;
+const L1_QUEUE_INDEX_LEN: usize = 2;
+const BATCH_HEADER_HASH_LEN: usize = 32;
 
 const ACC_BYTES: usize = ACC_LEN * 32;
-const PI_BYTES: usize = PI_LEN * 32;
+const PI_BYTES: usize = (PI_LEN + L1_QUEUE_INDEX_LEN + BATCH_HEADER_HASH_LEN) * 32;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct BatchProof {
@@ -20,7 +22,10 @@ impl From<Proof> for BatchProof {
     fn from(proof: Proof) -> Self {
         let instances = proof.instances();
         assert_eq!(instances.len(), 1);
-        assert_eq!(instances[0].len(), ACC_LEN + PI_LEN);
+        assert_eq!(
+            instances[0].len(),
+            ACC_LEN + PI_LEN + L1_QUEUE_INDEX_LEN + BATCH_HEADER_HASH_LEN
+        );
 
         let vk = proof.vk;
         let git_version = proof.git_version;
@@ -51,7 +56,8 @@ impl BatchProof {
     }
 
     /// Returns the calldata given to YUL verifier.
-    /// Format: Accumulator(12x32bytes) || PIHASH(32x32bytes) || Proof
+    /// Format: Accumulator(12x32bytes) || PIHASH(32x32bytes) || L1QueueIndexes(2x32bytes) ||
+    /// BatchHeaderHash(32x32bytes) || Proof
     pub fn calldata(self) -> Vec<u8> {
         let proof = self.proof_to_verify();
 
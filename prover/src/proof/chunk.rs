@@ -39,6 +39,8 @@ pub fn compare_chunk_info(name: &str, lhs: &ChunkInfo, rhs: &ChunkInfo) -> Resul
     compare_field!(name, post_state_root, lhs, rhs);
     compare_field!(name, withdraw_root, lhs, rhs);
     compare_field!(name, data_hash, lhs, rhs);
+    compare_field!(name, start_l1_queue_index, lhs, rhs);
+    compare_field!(name, end_l1_queue_index, lhs, rhs);
     if lhs.tx_bytes != rhs.tx_bytes {
         bail!(
             "{} chunk different {}: {} != {}",
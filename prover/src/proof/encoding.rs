@@ -0,0 +1,139 @@
+//! Versioned byte encoding of a proof along with its public inputs, for
+//! services transporting proofs without depending on halo2 types.
+//!
+//! Layout of version 1, all integers big-endian:
+//!
+//! | Size               | Field                                           |
+//! |--------------------|-------------------------------------------------|
+//! | 1                  | version, `1`                                    |
+//! | 4                  | number of instance columns `n`                  |
+//! | 4 * n              | number of instances of each column              |
+//! | 32 * sum(lengths)  | instances, column after column, as 32-byte words |
+//! | rest               | proof transcript                                |
+//!
+//! Everything after the column lengths is the calldata of the verifier
+//! contract generated by [`gen_evm_verifier`](crate::evm::gen_evm_verifier):
+//! the instances as 32-byte words followed by the proof.
+
+use anyhow::{bail, ensure, Result};
+use halo2_proofs::halo2curves::{bn256::Fr, ff::PrimeField};
+
+/// Version of the layout written by [`encode`].
+pub const ENCODING_VERSION: u8 = 1;
+
+/// Encode `proof` and its public inputs `instances`.
+pub fn encode(proof: &[u8], instances: &[Vec<Fr>]) -> Vec<u8> {
+    let num_instances: usize = instances.iter().map(Vec::len).sum();
+    let mut bytes = Vec::with_capacity(5 + 4 * instances.len() + 32 * num_instances + proof.len());
+    bytes.push(ENCODING_VERSION);
+    bytes.extend_from_slice(&(instances.len() as u32).to_be_bytes());
+    for column in instances {
+        bytes.extend_from_slice(&(column.len() as u32).to_be_bytes());
+    }
+    for value in instances.iter().flatten() {
+        bytes.extend(value.to_repr().as_ref().iter().rev());
+    }
+    bytes.extend_from_slice(proof);
+    bytes
+}
+
+/// Decode the proof and public inputs encoded by [`encode`].
+pub fn decode(bytes: &[u8]) -> Result<(Vec<u8>, Vec<Vec<Fr>>)> {
+    let (lengths, calldata) = split_header(bytes)?;
+    let num_instances: usize = lengths.iter().sum();
+    ensure!(
+        calldata.len() >= 32 * num_instances,
+        "{num_instances} instances don't fit in {} bytes",
+        calldata.len()
+    );
+    let (words, proof) = calldata.split_at(32 * num_instances);
+
+    let mut words = words.chunks(32);
+    let instances = lengths
+        .iter()
+        .map(|&len| {
+            words
+                .by_ref()
+                .take(len)
+                .map(|word| {
+                    let mut repr = <Fr as PrimeField>::Repr::default();
+                    repr.as_mut().copy_from_slice(word);
+                    repr.as_mut().reverse();
+                    Option::<Fr>::from(Fr::from_repr(repr))
+                        .ok_or_else(|| anyhow::anyhow!("instance {word:02x?} is not in the field"))
+                })
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((proof.to_vec(), instances))
+}
+
+/// The calldata of the EVM verifier contained in the encoded `bytes`.
+pub fn evm_calldata(bytes: &[u8]) -> Result<&[u8]> {
+    Ok(split_header(bytes)?.1)
+}
+
+/// Split the encoded `bytes` into the instance column lengths and the rest.
+fn split_header(bytes: &[u8]) -> Result<(Vec<usize>, &[u8])> {
+    let Some((&version, rest)) = bytes.split_first() else {
+        bail!("empty encoded proof");
+    };
+    if version != ENCODING_VERSION {
+        bail!("unsupported encoded proof version {version}");
+    }
+    let (num_columns, rest) = read_u32(rest)?;
+    let mut lengths = Vec::with_capacity(num_columns.min(rest.len() / 4));
+    let mut rest = rest;
+    for _ in 0..num_columns {
+        let (len, tail) = read_u32(rest)?;
+        lengths.push(len);
+        rest = tail;
+    }
+    Ok((lengths, rest))
+}
+
+fn read_u32(bytes: &[u8]) -> Result<(usize, &[u8])> {
+    ensure!(bytes.len() >= 4, "truncated encoded proof");
+    let (value, rest) = bytes.split_at(4);
+    Ok((u32::from_be_bytes(value.try_into()?) as usize, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoding_roundtrip() {
+        let proof = vec![0xab; 100];
+        let instances = vec![
+            vec![Fr::from(1), -Fr::from(1)],
+            vec![],
+            vec![Fr::from(0x1234)],
+        ];
+        let bytes = encode(&proof, &instances);
+        assert_eq!(bytes.len(), 1 + 4 + 3 * 4 + 3 * 32 + 100);
+
+        let calldata = evm_calldata(&bytes).unwrap();
+        assert_eq!(&calldata[..32], &{
+            let mut word = [0; 32];
+            word[31] = 1;
+            word
+        });
+        assert_eq!(&calldata[3 * 32..], &proof[..]);
+
+        assert_eq!(decode(&bytes).unwrap(), (proof, instances));
+    }
+
+    #[test]
+    fn encoding_rejects_malformed() {
+        let bytes = encode(&[], &[vec![Fr::from(1)]]);
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode(&[2]).is_err());
+        assert!(decode(&[]).is_err());
+
+        let mut bytes = bytes;
+        bytes[9..].fill(0xff);
+        assert!(decode(&bytes).is_err());
+    }
+}
@@ -1,5 +1,5 @@
-use aggregator::ChunkInfo;
-use eth_types::l2_types::BlockTrace;
+use aggregator::{BatchHeaderEncoding, ChunkInfo};
+use eth_types::{l2_types::BlockTrace, H256};
 use serde::{Deserialize, Serialize};
 use zkevm_circuits::evm_circuit::witness::Block;
 
@@ -44,6 +44,12 @@ impl ChunkProvingTask {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BatchProvingTask {
     pub chunk_proofs: Vec<ChunkProof>,
+    /// The hash of the parent batch, hashed in the batch header
+    #[serde(default)]
+    pub parent_batch_hash: H256,
+    /// The encoding of the batch header
+    #[serde(default)]
+    pub batch_header_encoding: BatchHeaderEncoding,
 }
 
 impl BatchProvingTask {
@@ -9,12 +9,13 @@ mod utils;
 
 use crate::{config::TestSuite, statetest::ResultLevel};
 use anyhow::{bail, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use compiler::Compiler;
 use config::Config;
 use log::info;
 use statetest::{
-    load_statetests_suite, run_statetests_suite, run_test, CircuitsConfig, Results, StateTest,
+    load_statetests_suite, run_remote_worker, run_statetests_suite, run_test,
+    serve_statetests_suite, CircuitsConfig, History, Results, StateTest, COVERAGE,
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -40,10 +41,35 @@ enum Circuits {
     sc,
 }
 
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Serve the tests to remote workers instead of running them, and report
+    /// their results
+    Serve {
+        /// Address to listen on for workers
+        #[clap(long, default_value = "127.0.0.1:9200")]
+        listen: String,
+    },
+    /// Run the tests served by a remote `serve`, which must use the same suite
+    Worker {
+        /// Address of the `serve` coordinator
+        #[clap(long)]
+        connect: String,
+
+        /// Number of tests run in parallel, by default one per CPU, or only
+        /// one with the super circuit
+        #[clap(long)]
+        jobs: Option<usize>,
+    },
+}
+
 /// EVM test vectors utility
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// Suite (by default is "default")
     #[clap(long, default_value = "default")]
     suite: String,
@@ -52,6 +78,19 @@ struct Args {
     #[clap(long)]
     inspect: Option<String>,
 
+    /// Execute only one test, without dumping its trace
+    #[clap(long)]
+    run_one: Option<String>,
+
+    /// Mock prove the super circuit even if a real prover is enabled
+    #[clap(long)]
+    mock_prove: bool,
+
+    /// Print the failing constraints and lookups of the MockProver, with
+    /// their region, offset and cell values
+    #[clap(long)]
+    verbose_errors: bool,
+
     /// Do not execute any test, just list collected tests
     #[clap(long)]
     ls: bool,
@@ -88,6 +127,15 @@ struct Args {
     #[clap(long)]
     exclude_test_ids: Option<String>,
 
+    /// Run each test N times, reporting it as flaky if the runs don't have the
+    /// same result
+    #[clap(long, default_value_t = 1)]
+    repeat: usize,
+
+    /// Print the ExecutionStates and opcodes exercised by the tests
+    #[clap(long)]
+    coverage: bool,
+
     /// Verbose
     #[clap(short, long)]
     v: bool,
@@ -137,13 +185,13 @@ fn run_single_test(
     circuits_config: CircuitsConfig,
 ) -> Result<()> {
     log::info!("run single test {}", &test);
-    let circuits_config = CircuitsConfig {
-        verbose: true,
-        super_circuit: circuits_config.super_circuit,
-    };
     //let trace = geth_trace(test.clone())?;
     //crate::utils::print_trace(trace)?;
-    log::info!("result={:?}", run_test(test, suite, circuits_config));
+    let test_id = test.id.clone();
+    match run_test(test, suite, circuits_config) {
+        Ok(()) => info!("{test_id}: success"),
+        Err(err) => bail!("{test_id}: {err}"),
+    }
     Ok(())
 }
 
@@ -155,14 +203,22 @@ fn go() -> Result<()> {
 
     let args = Args::parse();
 
-    let mut circuits_config = CircuitsConfig::default();
+    let mut circuits_config = CircuitsConfig {
+        mock_prove: args.mock_prove,
+        verbose_errors: args.verbose_errors,
+        ..Default::default()
+    };
     if args.circuits == Some(Circuits::sc) {
         circuits_config.super_circuit = true;
     }
+    let inspect_config = CircuitsConfig {
+        verbose: true,
+        ..circuits_config.clone()
+    };
 
     if let Some(oneliner) = &args.oneliner {
         let test = StateTest::parse_oneline_spec(oneliner)?;
-        run_single_test(test, Default::default(), circuits_config)?;
+        run_single_test(test, Default::default(), inspect_config)?;
         return Ok(());
     }
 
@@ -188,7 +244,18 @@ fn go() -> Result<()> {
         }
         return Ok(());
     }
-    if let Some(test_id) = args.inspect {
+    let single_test = match (args.inspect, args.run_one) {
+        (Some(test_id), _) => Some((test_id, inspect_config)),
+        (None, Some(test_id)) => Some((
+            test_id,
+            CircuitsConfig {
+                verbose: args.v,
+                ..circuits_config.clone()
+            },
+        )),
+        (None, None) => None,
+    };
+    if let Some((test_id, circuits_config)) = single_test {
         // Test only one and return
         let mut state_tests_filtered: Vec<_> =
             state_tests.iter().filter(|t| t.id == test_id).collect();
@@ -210,6 +277,30 @@ fn go() -> Result<()> {
         return Ok(());
     };
 
+    if let Some(Command::Worker { connect, jobs }) = &args.command {
+        let jobs = jobs.unwrap_or(if circuits_config.super_circuit {
+            1
+        } else {
+            rayon::current_num_threads()
+        });
+        run_remote_worker(
+            connect,
+            state_tests,
+            &suite,
+            &circuits_config,
+            args.repeat,
+            jobs,
+        )?;
+        if args.coverage {
+            COVERAGE.lock().unwrap().print_tty()?;
+        }
+        return Ok(());
+    }
+    let run_tests = |state_tests: Vec<StateTest>, results: &mut Results| match &args.command {
+        Some(Command::Serve { listen }) => serve_statetests_suite(listen, state_tests, results),
+        _ => run_statetests_suite(state_tests, &circuits_config, &suite, args.repeat, results),
+    };
+
     // It is better to sue deterministic testing order.
     // If there is a list, follow list.
     // If not, order by test id.
@@ -256,6 +347,12 @@ fn go() -> Result<()> {
             "{}/{}.{}.{}.html",
             REPORT_FOLDER, args.suite, timestamp, git_hash
         );
+        // not prefixed by the suite, to not be taken for results
+        let coverage_filename = format!(
+            "{}/coverage-{}.{}.{}.csv",
+            REPORT_FOLDER, args.suite, timestamp, git_hash
+        );
+        let history_filename = format!("{}/history-{}.csv", REPORT_FOLDER, args.suite);
 
         let cache_file_name = if !args.use_cache {
             None
@@ -305,7 +402,17 @@ fn go() -> Result<()> {
 
         previous_results.set_cache(PathBuf::from(csv_filename));
         previous_results.write_cache()?;
-        run_statetests_suite(state_tests, &circuits_config, &suite, &mut previous_results)?;
+        let cached: HashSet<_> = previous_results.tests.keys().cloned().collect();
+        run_tests(state_tests, &mut previous_results)?;
+
+        // only the tests executed in this run are added to the history
+        let mut history = History::load(PathBuf::from(&history_filename))?;
+        for (id, result) in previous_results.tests.iter() {
+            if !cached.contains(id) {
+                history.record(result);
+            }
+        }
+        history.write()?;
 
         // filter non-csv files and files from the same commit
         let mut files: Vec<_> = std::fs::read_dir(REPORT_FOLDER)
@@ -332,7 +439,15 @@ fn go() -> Result<()> {
         std::fs::write(&html_filename, report.gen_html(git_submodule_tests_hash)?)?;
 
         report.print_tty()?;
+        history.print_tty()?;
         info!("{}", html_filename);
+
+        if args.coverage {
+            let coverage = COVERAGE.lock().unwrap();
+            coverage.print_tty()?;
+            coverage.write_csv(&coverage_filename)?;
+            info!("{}", coverage_filename);
+        }
     } else {
         let mut results = if let Some(cache_filename) = args.cache {
             Results::with_cache(cache_filename)?
@@ -341,11 +456,14 @@ fn go() -> Result<()> {
         };
 
         log::info!("Executing...");
-        run_statetests_suite(state_tests, &circuits_config, &suite, &mut results)?;
+        run_tests(state_tests, &mut results)?;
         let success = results.success();
 
         log::info!("Generating report...");
         results.report(None).print_tty()?;
+        if args.coverage {
+            COVERAGE.lock().unwrap().print_tty()?;
+        }
 
         if !success {
             std::process::exit(1);
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use eth_types::evm_types::OpcodeId;
+use prettytable::Table;
+use std::{
+    collections::HashMap,
+    io::Write,
+    path::Path,
+    sync::{LazyLock, Mutex},
+};
+use strum::IntoEnumIterator;
+use zkevm_circuits::{evm_circuit::ExecutionState, witness::Block};
+
+/// Coverage of the tests run by this process.
+pub static COVERAGE: LazyLock<Mutex<Coverage>> = LazyLock::new(Default::default);
+
+/// How many times each ExecutionState and opcode was hit by the tests.
+#[derive(Default, Debug, Clone)]
+pub struct Coverage {
+    pub execution_states: HashMap<ExecutionState, usize>,
+    pub opcodes: HashMap<OpcodeId, usize>,
+}
+
+impl Coverage {
+    /// Record the steps of the transactions of a witness block.
+    pub fn record(&mut self, block: &Block) {
+        for step in block.txs.iter().flat_map(|tx| tx.steps.iter()) {
+            *self
+                .execution_states
+                .entry(step.execution_state)
+                .or_default() += 1;
+            if let Some(opcode) = step.opcode {
+                *self.opcodes.entry(opcode).or_default() += 1;
+            }
+        }
+    }
+
+    /// ExecutionStates never hit, i.e. the gadgets not exercised by the tests.
+    pub fn uncovered_execution_states(&self) -> Vec<ExecutionState> {
+        ExecutionState::iter()
+            .filter(|state| !self.execution_states.contains_key(state))
+            .collect()
+    }
+
+    /// Valid opcodes never hit.
+    pub fn uncovered_opcodes(&self) -> Vec<OpcodeId> {
+        OpcodeId::valid_opcodes()
+            .into_iter()
+            .filter(|opcode| !self.opcodes.contains_key(opcode))
+            .collect()
+    }
+
+    fn sorted<K: Copy + std::fmt::Debug>(hits: &HashMap<K, usize>) -> Vec<(String, usize)> {
+        let mut hits: Vec<_> = hits
+            .iter()
+            .map(|(key, count)| (format!("{key:?}"), *count))
+            .collect();
+        hits.sort_by(|(a_key, a_count), (b_key, b_count)| {
+            b_count.cmp(a_count).then_with(|| a_key.cmp(b_key))
+        });
+        hits
+    }
+
+    pub fn print_tty(&self) -> Result<()> {
+        for (title, hits) in [
+            ("ExecutionState", Self::sorted(&self.execution_states)),
+            ("Opcode", Self::sorted(&self.opcodes)),
+        ] {
+            let mut table = Table::new();
+            table.add_row(row![title, "Hits"]);
+            for (key, count) in hits {
+                table.add_row(row![key, count]);
+            }
+            table.print_tty(false)?;
+        }
+
+        let uncovered = self.uncovered_execution_states();
+        println!("{} ExecutionStates never exercised:", uncovered.len());
+        for state in uncovered {
+            println!("- {state:?}");
+        }
+        let uncovered = self.uncovered_opcodes();
+        println!("{} opcodes never exercised:", uncovered.len());
+        for opcode in uncovered {
+            println!("- {opcode:?}");
+        }
+        Ok(())
+    }
+
+    /// Write the hits as a `kind;name;hits` csv, including the never hit
+    /// ExecutionStates and opcodes with 0 hits.
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut file = std::fs::File::create(path)?;
+        for (kind, hits) in [
+            ("ExecutionState", Self::sorted(&self.execution_states)),
+            ("Opcode", Self::sorted(&self.opcodes)),
+        ] {
+            for (key, count) in hits {
+                writeln!(file, "{kind};{key};{count}")?;
+            }
+        }
+        for state in self.uncovered_execution_states() {
+            writeln!(file, "ExecutionState;{state:?};0")?;
+        }
+        for opcode in self.uncovered_opcodes() {
+            writeln!(file, "Opcode;{opcode:?};0")?;
+        }
+        Ok(())
+    }
+}
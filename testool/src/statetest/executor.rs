@@ -1,6 +1,8 @@
-use super::{AccountMatch, StateTest, StateTestResult};
+use super::{AccountMatch, StateTest, StateTestResult, COVERAGE};
 use crate::{config::TestSuite, utils::ETH_CHAIN_ID};
-use bus_mapping::circuit_input_builder::{CircuitInputBuilder, CircuitsParams, PrecompileEcParams};
+use bus_mapping::circuit_input_builder::{
+    CircuitInputBuilder, CircuitsParams, PrecompileEcParams, N_PAIRING_PER_OP,
+};
 use eth_types::{
     geth_types, state_db::CodeDB, Address, Bytes, GethExecTrace, ToBigEndian, ToWord, H256, U256,
     U64,
@@ -8,7 +10,11 @@ use eth_types::{
 use ethers_core::utils::keccak256;
 use ethers_signers::LocalWallet;
 use external_tracer::{LoggerConfig, TraceConfig};
-use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit};
+use halo2_proofs::{
+    dev::{MockProver, VerifyFailure},
+    halo2curves::bn256::Fr,
+    plonk::Circuit,
+};
 use itertools::Itertools;
 use std::{collections::BTreeMap, env, str::FromStr, sync::LazyLock};
 use thiserror::Error;
@@ -91,6 +97,10 @@ impl StateTestError {
 pub struct CircuitsConfig {
     pub super_circuit: bool,
     pub verbose: bool,
+    /// Mock prove the super circuit even if a real prover is enabled
+    pub mock_prove: bool,
+    /// Print the details of the failures of the MockProver
+    pub verbose_errors: bool,
 }
 
 fn check_post(
@@ -458,6 +468,7 @@ fn get_params_for_super_circuit_test() -> CircuitsParams {
             ec_add: 50,
             ec_mul: 50,
             ec_pairing: 2,
+            ec_pairing_pairs: N_PAIRING_PER_OP,
         },
     }
 }
@@ -482,10 +493,31 @@ fn get_params_for_sub_circuit_test() -> CircuitsParams {
             ec_add: 50,
             ec_mul: 50,
             ec_pairing: 2,
+            ec_pairing_pairs: N_PAIRING_PER_OP,
         },
     }
 }
 
+/// Check the verification `result` of the MockProver of the `name` circuit,
+/// printing the failing constraints and lookups with their region, offset and
+/// cell values if `verbose_errors`.
+fn check_mock_prover(
+    name: &str,
+    prover: &MockProver<Fr>,
+    result: Result<(), Vec<VerifyFailure>>,
+    verbose_errors: bool,
+) {
+    if let Err(failures) = result {
+        if verbose_errors {
+            eprintln!("{name} circuit: {} failures", failures.len());
+            for failure in failures.iter() {
+                failure.emit(prover);
+            }
+        }
+        panic!("{name} circuit was not satisfied: {failures:?}");
+    }
+}
+
 fn test_with<C: SubCircuit<Fr> + Circuit<Fr>>(block: &Block) {
     let num_row = C::min_num_rows_block(block).1;
     let k = zkevm_circuits::util::log2_ceil(num_row + 256);
@@ -565,6 +597,7 @@ pub fn run_test(
     };
 
     log::debug!("witness_block created");
+    COVERAGE.lock().unwrap().record(&witness_block);
     //builder.sdb.list_accounts();
 
     let row_usage = ScrollSuperCircuit::min_num_rows_block_subcircuits(&witness_block);
@@ -609,7 +642,32 @@ pub fn run_test(
 
     if !circuits_config.super_circuit {
         if (*CIRCUIT).is_empty() {
-            CircuitTestBuilder::<1, 1>::new_from_block(witness_block).run();
+            let mut test_builder = CircuitTestBuilder::<1, 1>::new_from_block(witness_block);
+            if circuits_config.verbose_errors {
+                test_builder = test_builder
+                    .evm_checks(Some(Box::new(|prover, gate_rows, lookup_rows| {
+                        let result = prover.verify_at_rows_par(
+                            gate_rows.iter().cloned(),
+                            lookup_rows.iter().cloned(),
+                        );
+                        check_mock_prover("evm", &prover, result, true)
+                    })))
+                    .state_checks(Some(Box::new(|prover, gate_rows, lookup_rows| {
+                        let result = prover.verify_at_rows_par(
+                            gate_rows.iter().cloned(),
+                            lookup_rows.iter().cloned(),
+                        );
+                        check_mock_prover("state", &prover, result, true)
+                    })))
+                    .copy_checks(Some(Box::new(|prover, gate_rows, lookup_rows| {
+                        let result = prover.verify_at_rows_par(
+                            gate_rows.iter().cloned(),
+                            lookup_rows.iter().cloned(),
+                        );
+                        check_mock_prover("copy", &prover, result, true)
+                    })));
+            }
+            test_builder.run();
         } else {
             match (*CIRCUIT).as_str() {
                 "modexp" => test_with::<ModExpCircuit<Fr>>(&witness_block),
@@ -632,22 +690,28 @@ pub fn run_test(
     } else {
         log::debug!("test super circuit {}", *CIRCUIT);
 
-        // TODO: these codes are too difficult to maintain.
-        // The correct way is to dump trace files,
-        // and use seperate tools to test trace files.
-        #[cfg(feature = "inner-prove")]
+        if circuits_config.mock_prove
+            || cfg!(not(any(feature = "inner-prove", feature = "chunk-prove")))
         {
-            eth_types::constants::set_env_coinbase(&st.env.current_coinbase);
-            prover::test::inner_prove(&test_id, &witness_block);
-        }
-        #[cfg(feature = "chunk-prove")]
-        {
-            eth_types::constants::set_env_coinbase(&st.env.current_coinbase);
-            prover::test::chunk_prove(&test_id, prover::ChunkProvingTask::from(vec![scroll_trace]));
+            mock_prove(&test_id, &witness_block, circuits_config.verbose_errors);
+        } else {
+            // TODO: these codes are too difficult to maintain.
+            // The correct way is to dump trace files,
+            // and use seperate tools to test trace files.
+            #[cfg(feature = "inner-prove")]
+            {
+                eth_types::constants::set_env_coinbase(&st.env.current_coinbase);
+                prover::test::inner_prove(&test_id, &witness_block);
+            }
+            #[cfg(feature = "chunk-prove")]
+            {
+                eth_types::constants::set_env_coinbase(&st.env.current_coinbase);
+                prover::test::chunk_prove(
+                    &test_id,
+                    prover::ChunkProvingTask::from(vec![scroll_trace]),
+                );
+            }
         }
-
-        #[cfg(not(any(feature = "inner-prove", feature = "chunk-prove")))]
-        mock_prove(&test_id, &witness_block);
     };
     log::debug!("balance_overflow = {balance_overflow}");
     log::debug!(
@@ -698,8 +762,7 @@ pub fn run_test(
     Ok(())
 }
 
-#[cfg(not(any(feature = "inner-prove", feature = "chunk-prove")))]
-fn mock_prove(test_id: &str, witness_block: &Block) {
+fn mock_prove(test_id: &str, witness_block: &Block, verbose_errors: bool) {
     log::info!("{test_id}: mock-prove BEGIN");
     // TODO: do we need to automatically adjust this k?
     let k = 20;
@@ -707,7 +770,7 @@ fn mock_prove(test_id: &str, witness_block: &Block) {
     let circuit = ScrollSuperCircuit::new_from_block(witness_block);
     let instance = circuit.instance();
     let prover = MockProver::run(k, &circuit, instance).unwrap();
-    prover.assert_satisfied_par();
+    check_mock_prover("super", &prover, prover.verify_par(), verbose_errors);
 
     log::info!("{test_id}: mock-prove END");
 }
@@ -0,0 +1,128 @@
+use super::results::{ResultInfo, ResultLevel};
+use anyhow::{bail, Result};
+use prettytable::Table;
+use std::{collections::BTreeMap, io::Write, path::PathBuf, str::FromStr};
+
+/// Number of runs kept in the history of each test
+const HISTORY_LEN: usize = 10;
+
+/// Results of the last runs of each test, kept across the runs to find the
+/// tests whose result isn't stable.
+///
+/// It's stored as a `id#path;level,level,...` csv, oldest level first.
+#[derive(Default, Debug)]
+pub struct History {
+    path: Option<PathBuf>,
+    tests: BTreeMap<String, Vec<ResultLevel>>,
+}
+
+impl History {
+    /// Load the history from `path`, or start an empty one if it doesn't
+    /// exist yet.
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let mut tests = BTreeMap::new();
+        if path.exists() {
+            for line in std::fs::read_to_string(&path)?.lines() {
+                let Some((id, levels)) = line.rsplit_once(';') else {
+                    bail!("bad history line {line:?}");
+                };
+                let levels = levels
+                    .split(',')
+                    .map(ResultLevel::from_str)
+                    .collect::<Result<_, _>>()?;
+                tests.insert(id.to_string(), levels);
+            }
+        }
+        Ok(Self {
+            path: Some(path),
+            tests,
+        })
+    }
+
+    /// Record the result of a run of a test.
+    pub fn record(&mut self, result: &ResultInfo) {
+        let levels = self
+            .tests
+            .entry(format!("{}#{}", result.test_id, result.path))
+            .or_default();
+        levels.push(result.level);
+        if levels.len() > HISTORY_LEN {
+            levels.drain(..levels.len() - HISTORY_LEN);
+        }
+    }
+
+    /// The tests which both succeeded and failed in the last runs, or were
+    /// found flaky in a run.
+    pub fn flaky(&self) -> impl Iterator<Item = (&String, &[ResultLevel])> {
+        self.tests
+            .iter()
+            .filter(|(_, levels)| {
+                levels.contains(&ResultLevel::Flaky)
+                    || (levels.contains(&ResultLevel::Success)
+                        && levels.iter().any(ResultLevel::is_failure))
+            })
+            .map(|(id, levels)| (id, levels.as_slice()))
+    }
+
+    pub fn print_tty(&self) -> Result<()> {
+        let mut table = Table::new();
+        table.add_row(row!["Flaky test", format!("Last {HISTORY_LEN} runs")]);
+        for (id, levels) in self.flaky() {
+            table.add_row(row![id, format!("{levels:?}")]);
+        }
+        table.print_tty(false)?;
+        Ok(())
+    }
+
+    pub fn write(&self) -> Result<()> {
+        if let Some(path) = &self.path {
+            let mut file = std::fs::File::create(path)?;
+            for (id, levels) in &self.tests {
+                let levels: Vec<_> = levels.iter().map(|level| format!("{level:?}")).collect();
+                writeln!(file, "{id};{}", levels.join(","))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn result(level: ResultLevel) -> ResultInfo {
+        ResultInfo {
+            test_id: "add_d0_g0_v0".to_string(),
+            level,
+            details: String::new(),
+            path: "tests/add.json".to_string(),
+        }
+    }
+
+    #[test]
+    fn history() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("testool-history-{}.csv", std::process::id()));
+        let mut history = History::load(path.clone())?;
+        history.record(&result(ResultLevel::Success));
+        assert_eq!(history.flaky().count(), 0);
+        history.record(&result(ResultLevel::Fail));
+        history.write()?;
+
+        let mut history = History::load(path.clone())?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(
+            history.flaky().collect::<Vec<_>>(),
+            vec![(
+                &"add_d0_g0_v0#tests/add.json".to_string(),
+                &[ResultLevel::Success, ResultLevel::Fail][..]
+            )]
+        );
+
+        // the older runs are forgotten
+        for _ in 0..HISTORY_LEN {
+            history.record(&result(ResultLevel::Fail));
+        }
+        assert_eq!(history.flaky().count(), 0);
+        Ok(())
+    }
+}
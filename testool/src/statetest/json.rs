@@ -26,12 +26,16 @@ fn default_block_base_fee() -> String {
 struct TestEnv {
     #[serde(default = "default_block_base_fee")]
     current_base_fee: String,
-    current_coinbase: String,
+    #[serde(with = "eth_types::parse::serde_address")]
+    current_coinbase: Address,
     #[serde(default)]
     current_difficulty: String,
-    current_gas_limit: String,
-    current_number: String,
-    current_timestamp: String,
+    #[serde(with = "eth_types::parse::serde_u64")]
+    current_gas_limit: u64,
+    #[serde(with = "eth_types::parse::serde_u64")]
+    current_number: u64,
+    #[serde(with = "eth_types::parse::serde_u64")]
+    current_timestamp: u64,
     #[serde(default)]
     previous_hash: String,
 }
@@ -57,9 +61,11 @@ struct AccountPost {
 
 #[derive(Debug, Clone, Deserialize)]
 struct AccountPre {
-    balance: String,
+    #[serde(with = "eth_types::parse::serde_u256")]
+    balance: U256,
     code: String,
-    nonce: String,
+    #[serde(with = "eth_types::parse::serde_u256")]
+    nonce: U256,
     storage: HashMap<String, String>,
 }
 
@@ -252,11 +258,11 @@ impl<'a> JsonStateTestBuilder<'a> {
         Ok(Env {
             current_base_fee: parse::parse_u256(&env.current_base_fee)
                 .unwrap_or_else(|_| U256::from(DEFAULT_BASE_FEE)),
-            current_coinbase: parse::parse_address(&env.current_coinbase)?,
+            current_coinbase: env.current_coinbase,
             current_difficulty: parse::parse_u256(&env.current_difficulty).unwrap_or_default(),
-            current_gas_limit: parse::parse_u64(&env.current_gas_limit)?,
-            current_number: parse::parse_u64(&env.current_number)?,
-            current_timestamp: parse::parse_u64(&env.current_timestamp)?,
+            current_gas_limit: env.current_gas_limit,
+            current_number: env.current_number,
+            current_timestamp: env.current_timestamp,
             previous_hash: parse::parse_hash(&env.previous_hash).unwrap_or_default(),
         })
     }
@@ -275,8 +281,8 @@ impl<'a> JsonStateTestBuilder<'a> {
             }
             let account = Account {
                 address,
-                balance: parse::parse_u256(&acc.balance)?,
-                nonce: parse::parse_u256(&acc.nonce)?,
+                balance: acc.balance,
+                nonce: acc.nonce,
                 code: parse::parse_code(self.compiler, &acc.code)?,
                 storage,
             };
@@ -1,13 +1,19 @@
+mod coverage;
 mod executor;
+mod history;
 mod json;
 mod parse;
+mod remote;
 mod results;
 pub mod spec;
 mod suite;
 mod yaml;
 
+pub use coverage::{Coverage, COVERAGE};
 pub use executor::{run_test, CircuitsConfig};
+pub use history::History;
 pub use json::JsonStateTestBuilder;
+pub use remote::{run_remote_worker, serve_statetests_suite};
 pub use results::{ResultLevel, Results};
 pub use spec::{AccountMatch, StateTest, StateTestResult};
 pub use suite::{load_statetests_suite, run_statetests_suite};
@@ -52,10 +52,7 @@ static YUL_FRAGMENT_PARSER: LazyLock<Regex> = LazyLock::new(|| {
 
 /// returns the element as an address
 pub fn parse_address(as_str: &str) -> Result<Address> {
-    let hex = as_str.strip_prefix("0x").unwrap_or(as_str);
-    Ok(Address::from_slice(
-        &hex::decode(hex).context("parse_address")?,
-    ))
+    Ok(eth_types::parse::parse_address(as_str)?)
 }
 
 /// returns the element as a to address
@@ -172,35 +169,17 @@ pub fn parse_code(compiler: &Compiler, as_str: &str) -> Result<Bytes> {
 
 /// parse a hash entry
 pub fn parse_hash(value: &str) -> Result<H256> {
-    let hex = value.strip_prefix("0x").unwrap_or(value);
-    if hex.is_empty() {
-        return Ok(H256::zero());
-    }
-    Ok(H256::from_slice(&hex::decode(hex).context("parse_hash")?))
+    Ok(eth_types::parse::parse_hash(value)?)
 }
 
 /// parse an uint256 entry
 pub fn parse_u256(as_str: &str) -> Result<U256> {
-    if let Some(stripped) = as_str.strip_prefix("0x") {
-        Ok(U256::from_str_radix(stripped, 16)?)
-    } else if as_str
-        .to_lowercase()
-        .contains(['a', 'b', 'c', 'd', 'e', 'f'])
-    {
-        Ok(U256::from_str_radix(as_str, 16)?)
-    } else {
-        Ok(U256::from_str_radix(as_str, 10)?)
-    }
+    Ok(eth_types::parse::parse_u256(as_str)?)
 }
 
 /// parse u64 entry
-#[allow(clippy::cast_sign_loss)]
 pub fn parse_u64(as_str: &str) -> Result<u64> {
-    if let Some(stripped) = as_str.strip_prefix("0x") {
-        Ok(U256::from_str_radix(stripped, 16)?.as_u64())
-    } else {
-        Ok(U256::from_str_radix(as_str, 10)?.as_u64())
-    }
+    Ok(eth_types::parse::parse_u64(as_str)?)
 }
 
 // Parse calldata to bytes
@@ -0,0 +1,314 @@
+//! Distribution of the tests of a suite over several machines.
+//!
+//! A coordinator (`testool serve`) serves the tests to run over TCP, and the
+//! workers (`testool worker --connect`) run them and send their results back,
+//! so that the coordinator generates the report as if it had run them. The
+//! messages are json lines, and the tests are referred to by their
+//! `id#path`, so the workers have to load the same suite as the coordinator.
+
+use super::{
+    results::{ResultInfo, ResultLevel},
+    suite::run_state_test,
+    CircuitsConfig, Results, StateTest,
+};
+use crate::config::TestSuite;
+use anyhow::{bail, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{BufRead, BufReader, ErrorKind, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+/// Message sent by a worker to the coordinator.
+#[derive(Debug, Serialize, Deserialize)]
+enum WorkerMessage {
+    /// Ask for a test to run
+    Next,
+    /// Result of the test being run
+    Result(ResultInfo),
+}
+
+/// Message sent by the coordinator to a worker.
+#[derive(Debug, Serialize, Deserialize)]
+enum CoordinatorMessage {
+    /// Run the test with this `id#path`
+    Run(String),
+    /// No test left to run
+    Done,
+}
+
+fn test_key(tc: &StateTest) -> String {
+    format!("{}#{}", tc.id, tc.path)
+}
+
+fn send<T: Serialize>(stream: &mut TcpStream, message: &T) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Receive a message, or `None` if the connection was closed.
+fn recv<T: DeserializeOwned>(reader: &mut impl BufRead) -> Result<Option<T>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+/// Tests not run yet, shared by the connections of the coordinator.
+struct Queue<'a> {
+    tests: Mutex<VecDeque<String>>,
+    /// Number of tests whose result wasn't received yet
+    pending: AtomicUsize,
+    results: Mutex<&'a mut Results>,
+}
+
+impl Queue<'_> {
+    /// Serve the tests to a worker until the queue is empty. The test being
+    /// run is put back in the queue if the worker goes away, or if it sends
+    /// the result of another test.
+    fn serve(&self, stream: TcpStream) -> Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut stream = stream;
+        let mut running = None;
+        let served = (|| loop {
+            match recv(&mut reader)? {
+                None => bail!("connection closed"),
+                Some(WorkerMessage::Next) => {
+                    let next = self.tests.lock().unwrap().pop_front();
+                    match next {
+                        Some(key) => {
+                            send(&mut stream, &CoordinatorMessage::Run(key.clone()))?;
+                            running = Some(key);
+                        }
+                        None => return send(&mut stream, &CoordinatorMessage::Done),
+                    }
+                }
+                Some(WorkerMessage::Result(result)) => {
+                    let key = format!("{}#{}", result.test_id, result.path);
+                    if running.as_ref() != Some(&key) {
+                        bail!("unexpected result of {key}");
+                    }
+                    running = None;
+                    self.results.lock().unwrap().insert(result)?;
+                    self.pending.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+        })();
+        if let Some(key) = running {
+            log::warn!("{key} is put back in the queue");
+            self.tests.lock().unwrap().push_front(key);
+        }
+        served
+    }
+}
+
+/// Serve the tests to the workers connecting to `addr`, and gather their
+/// results in `results`. Returns once all the tests have a result.
+pub fn serve_statetests_suite(
+    addr: &str,
+    tcs: Vec<StateTest>,
+    results: &mut Results,
+) -> Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    log::info!("waiting for workers on {}", listener.local_addr()?);
+    serve(listener, tcs, results)
+}
+
+fn serve(listener: TcpListener, tcs: Vec<StateTest>, results: &mut Results) -> Result<()> {
+    // Filter already cached entries
+    let all_test_count = tcs.len();
+    let tests: VecDeque<_> = tcs
+        .iter()
+        .map(test_key)
+        .filter(|key| !results.contains(key))
+        .collect();
+    log::info!(
+        "{} test results cached, {} remaining",
+        all_test_count - tests.len(),
+        tests.len()
+    );
+
+    let queue = Queue {
+        pending: AtomicUsize::new(tests.len()),
+        tests: Mutex::new(tests),
+        results: Mutex::new(results),
+    };
+
+    // poll the listener, to stop accepting workers once all the results
+    // are received
+    listener.set_nonblocking(true)?;
+    std::thread::scope(|scope| {
+        while queue.pending.load(Ordering::SeqCst) > 0 {
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    log::info!("worker {peer} connected");
+                    stream.set_nonblocking(false)?;
+                    let queue = &queue;
+                    scope.spawn(move || {
+                        if let Err(err) = queue.serve(stream) {
+                            log::warn!("worker {peer} left: {err}");
+                        }
+                    });
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Run the tests served by the coordinator at `addr`, `jobs` at a time, each
+/// one `repeat` times.
+pub fn run_remote_worker(
+    addr: &str,
+    tcs: Vec<StateTest>,
+    suite: &TestSuite,
+    circuits_config: &CircuitsConfig,
+    repeat: usize,
+    jobs: usize,
+) -> Result<()> {
+    let tests: HashMap<_, _> = tcs.into_iter().map(|tc| (test_key(&tc), tc)).collect();
+    log::info!("running the tests of {addr} with {jobs} jobs");
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| scope.spawn(|| work(addr, &tests, suite, circuits_config, repeat)))
+            .collect();
+        workers
+            .into_iter()
+            .try_for_each(|worker| worker.join().unwrap())
+    })
+}
+
+fn work(
+    addr: &str,
+    tests: &HashMap<String, StateTest>,
+    suite: &TestSuite,
+    circuits_config: &CircuitsConfig,
+    repeat: usize,
+) -> Result<()> {
+    let mut stream = TcpStream::connect(addr)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    loop {
+        send(&mut stream, &WorkerMessage::Next)?;
+        let key = match recv(&mut reader)? {
+            Some(CoordinatorMessage::Run(key)) => key,
+            // the coordinator stops once it has all the results
+            Some(CoordinatorMessage::Done) | None => return Ok(()),
+        };
+        let result = match tests.get(&key) {
+            Some(tc) => run_state_test(tc, suite, circuits_config, repeat),
+            None => {
+                let (test_id, path) = key.split_once('#').unwrap_or((&key, ""));
+                ResultInfo {
+                    test_id: test_id.to_string(),
+                    level: ResultLevel::Fail,
+                    details: "test not found by the worker".to_string(),
+                    path: path.to_string(),
+                }
+            }
+        };
+        send(&mut stream, &WorkerMessage::Result(result))?;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn remote_workers() -> Result<()> {
+        let tcs: Vec<_> = (0..5)
+            .map(|i| StateTest {
+                id: format!("test_{i}"),
+                path: "remote.yml".to_string(),
+                ..StateTest::parse_oneline_spec("call").unwrap()
+            })
+            .collect();
+
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?.to_string();
+        let mut results = Results::default();
+        std::thread::scope(|scope| {
+            // the workers don't know the tests, so that they aren't run
+            scope.spawn(|| {
+                run_remote_worker(
+                    &addr,
+                    Vec::new(),
+                    &TestSuite::default(),
+                    &CircuitsConfig::default(),
+                    1,
+                    2,
+                )
+            });
+            serve(listener, tcs, &mut results)
+        })?;
+
+        assert_eq!(results.tests.len(), 5);
+        let result = &results.tests["test_3#remote.yml"];
+        assert_eq!(result.level, ResultLevel::Fail);
+        assert_eq!(result.details, "test not found by the worker");
+        Ok(())
+    }
+
+    #[test]
+    fn remote_unexpected_result() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let mut results = Results::default();
+        let queue = Queue {
+            tests: Mutex::new(VecDeque::from(["test_0#remote.yml".to_string()])),
+            pending: AtomicUsize::new(1),
+            results: Mutex::new(&mut results),
+        };
+        let result = |test_id: &str| {
+            WorkerMessage::Result(ResultInfo {
+                test_id: test_id.to_string(),
+                level: ResultLevel::Success,
+                details: String::new(),
+                path: "remote.yml".to_string(),
+            })
+        };
+        std::thread::scope(|scope| {
+            let worker = scope.spawn(|| -> Result<()> {
+                // a result before any test was served
+                let mut stream = TcpStream::connect(addr)?;
+                send(&mut stream, &result("test_0"))?;
+                // the result of another test than the one served
+                let mut stream = TcpStream::connect(addr)?;
+                let mut reader = BufReader::new(stream.try_clone()?);
+                send(&mut stream, &WorkerMessage::Next)?;
+                match recv(&mut reader)? {
+                    Some(CoordinatorMessage::Run(key)) => assert_eq!(key, "test_0#remote.yml"),
+                    message => panic!("unexpected message {message:?}"),
+                }
+                send(&mut stream, &result("test_1"))?;
+                Ok(())
+            });
+            for _ in 0..2 {
+                let (stream, _) = listener.accept()?;
+                assert!(queue.serve(stream).is_err());
+            }
+            worker.join().unwrap()
+        })?;
+
+        assert_eq!(queue.pending.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            queue.tests.lock().unwrap().front().map(String::as_str),
+            Some("test_0#remote.yml")
+        );
+        assert!(queue.results.lock().unwrap().tests.is_empty());
+        Ok(())
+    }
+}
@@ -14,7 +14,8 @@ use strum_macros::{EnumIter, EnumString}; // 0.17.1
 
 const MAX_DETAILS_LEN: usize = 128;
 
-const OUTPUT_ALL_RESULT_LEVELS: [ResultLevel; 2] = [ResultLevel::Fail, ResultLevel::Panic];
+const OUTPUT_ALL_RESULT_LEVELS: [ResultLevel; 3] =
+    [ResultLevel::Fail, ResultLevel::Panic, ResultLevel::Flaky];
 
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, EnumIter, EnumString, Serialize, Deserialize)]
 pub enum ResultLevel {
@@ -26,6 +27,9 @@ pub enum ResultLevel {
     Fail,
     #[strum(ascii_case_insensitive)]
     Panic,
+    /// The result changed when the test was run again
+    #[strum(ascii_case_insensitive)]
+    Flaky,
 }
 
 #[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
@@ -37,6 +41,11 @@ pub struct ResultInfo {
 }
 
 impl ResultLevel {
+    /// Return true for the levels making the run fail.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Self::Fail | Self::Panic | Self::Flaky)
+    }
+
     pub fn display_string(&self) -> String {
         use ResultLevel::*;
         match self {
@@ -44,6 +53,7 @@ impl ResultLevel {
             Fail => "🔴FAILD",
             Ignored => "🟠IGNOR",
             Success => "🟢SUCCS",
+            Flaky => "🟣FLAKY",
         }
         .to_string()
     }
@@ -144,7 +154,7 @@ impl Report {
             if info.level == ResultLevel::Success {
                 num_succ += 1.0;
             }
-            if info.level == ResultLevel::Fail || info.level == ResultLevel::Panic {
+            if info.level.is_failure() {
                 num_fail += 1.0;
                 println!("- {:?} {}", info.level, test_id);
             }
@@ -389,10 +399,7 @@ impl Results {
     }
 
     pub fn success(&self) -> bool {
-        !self
-            .tests
-            .values()
-            .any(|result| result.level == ResultLevel::Fail || result.level == ResultLevel::Panic)
+        !self.tests.values().any(|result| result.level.is_failure())
     }
 
     pub fn contains(&self, test: &str) -> bool {
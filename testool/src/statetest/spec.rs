@@ -202,10 +202,8 @@ impl StateTest {
         let parse_u256 = |s: &str| {
             if s.is_empty() {
                 Ok(Word::zero())
-            } else if let Some(hex) = s.strip_prefix("0x") {
-                Word::from_str_radix(hex, 16)
             } else {
-                Word::from_str_radix(s, 10)
+                eth_types::parse::parse_u256(s)
             }
         };
 
@@ -77,6 +77,7 @@ pub fn run_statetests_suite(
     tcs: Vec<StateTest>,
     circuits_config: &CircuitsConfig,
     suite: &TestSuite,
+    repeat: usize,
     results: &mut Results,
 ) -> Result<()> {
     // Filter already cached entries
@@ -97,97 +98,16 @@ pub fn run_statetests_suite(
     // for each test
     let test_count = tcs.len();
     let run_state_test = |tc: &StateTest| {
-        let (test_id, path) = (tc.id.clone(), tc.path.clone());
-        if !suite.allowed(&test_id) {
-            results
-                .write()
-                .unwrap()
-                .insert(ResultInfo {
-                    test_id,
-                    level: ResultLevel::Ignored,
-                    details: "Ignored in config file".to_string(),
-                    path,
-                })
-                .unwrap();
-            return;
-        }
-
-        std::panic::set_hook(Box::new(|_info| {}));
-
         log::debug!(
             target : "testool",
             "🐕 running test (done {}/{}) {}#{}...",
             results.read().unwrap().tests.len(),
             test_count,
-            test_id,
-            path,
+            tc.id,
+            tc.path,
         );
-        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
-            run_test(tc.clone(), suite.clone(), circuits_config.clone())
-        }));
-
-        // handle panic
-        let result = match result {
-            Ok(res) => res,
-            Err(err) => {
-                let panic_err = if let Some(s) = err.downcast_ref::<String>() {
-                    s.to_string()
-                } else if let Some(s) = err.downcast_ref::<&str>() {
-                    s.to_string()
-                } else {
-                    "unable to get panic info".into()
-                };
-
-                let level = if panic_err.contains("circuit was not satisfied") {
-                    ResultLevel::Fail
-                } else if panic_err.contains("evm_unimplemented") {
-                    ResultLevel::Ignored
-                } else {
-                    ResultLevel::Panic
-                };
-                results
-                    .write()
-                    .unwrap()
-                    .insert(ResultInfo {
-                        test_id,
-                        level,
-                        details: panic_err,
-                        path,
-                    })
-                    .unwrap();
-                return;
-            }
-        };
-
-        // handle known error
-        if let Err(err) = result {
-            results
-                .write()
-                .unwrap()
-                .insert(ResultInfo {
-                    test_id,
-                    level: if err.is_skip() {
-                        ResultLevel::Ignored
-                    } else {
-                        ResultLevel::Fail
-                    },
-                    details: err.to_string(),
-                    path,
-                })
-                .unwrap();
-            return;
-        }
-
-        results
-            .write()
-            .unwrap()
-            .insert(ResultInfo {
-                test_id,
-                level: ResultLevel::Success,
-                details: String::default(),
-                path,
-            })
-            .unwrap();
+        let result = run_state_test(tc, suite, circuits_config, repeat);
+        results.write().unwrap().insert(result).unwrap();
     };
 
     if circuits_config.super_circuit {
@@ -205,3 +125,116 @@ pub fn run_statetests_suite(
     }
     Ok(())
 }
+
+/// Run a test `repeat` times, and return its result, which is
+/// [`ResultLevel::Flaky`] if the runs don't have the same result.
+pub(crate) fn run_state_test(
+    tc: &StateTest,
+    suite: &TestSuite,
+    circuits_config: &CircuitsConfig,
+    repeat: usize,
+) -> ResultInfo {
+    let result = run_state_test_once(tc, suite, circuits_config);
+    if result.level == ResultLevel::Ignored {
+        return result;
+    }
+
+    let mut levels = vec![result.level];
+    let mut unstable = None;
+    for _ in 1..repeat {
+        let rerun = run_state_test_once(tc, suite, circuits_config);
+        levels.push(rerun.level);
+        if rerun.level != result.level && unstable.is_none() {
+            unstable = Some(rerun);
+        }
+    }
+
+    match unstable {
+        None => result,
+        Some(rerun) => {
+            // keep the details of a failing run
+            let failing = if result.level == ResultLevel::Success {
+                rerun
+            } else {
+                result
+            };
+            ResultInfo {
+                level: ResultLevel::Flaky,
+                details: format!("{levels:?} {}", failing.details),
+                ..failing
+            }
+        }
+    }
+}
+
+/// Run a test, catching its panics, and return its result.
+fn run_state_test_once(
+    tc: &StateTest,
+    suite: &TestSuite,
+    circuits_config: &CircuitsConfig,
+) -> ResultInfo {
+    let (test_id, path) = (tc.id.clone(), tc.path.clone());
+    if !suite.allowed(&test_id) {
+        return ResultInfo {
+            test_id,
+            level: ResultLevel::Ignored,
+            details: "Ignored in config file".to_string(),
+            path,
+        };
+    }
+
+    std::panic::set_hook(Box::new(|_info| {}));
+
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        run_test(tc.clone(), suite.clone(), circuits_config.clone())
+    }));
+
+    // handle panic
+    let result = match result {
+        Ok(res) => res,
+        Err(err) => {
+            let panic_err = if let Some(s) = err.downcast_ref::<String>() {
+                s.to_string()
+            } else if let Some(s) = err.downcast_ref::<&str>() {
+                s.to_string()
+            } else {
+                "unable to get panic info".into()
+            };
+
+            let level = if panic_err.contains("circuit was not satisfied") {
+                ResultLevel::Fail
+            } else if panic_err.contains("evm_unimplemented") {
+                ResultLevel::Ignored
+            } else {
+                ResultLevel::Panic
+            };
+            return ResultInfo {
+                test_id,
+                level,
+                details: panic_err,
+                path,
+            };
+        }
+    };
+
+    // handle known error
+    if let Err(err) = result {
+        return ResultInfo {
+            test_id,
+            level: if err.is_skip() {
+                ResultLevel::Ignored
+            } else {
+                ResultLevel::Fail
+            },
+            details: err.to_string(),
+            path,
+        };
+    }
+
+    ResultInfo {
+        test_id,
+        level: ResultLevel::Success,
+        details: String::default(),
+        path,
+    }
+}
@@ -24,16 +24,13 @@ struct Refs(Vec<Ref>);
 
 impl Refs {
     fn contains_index(&self, idx: usize) -> bool {
-        self.0.iter().any(|r| match r {
-            Ref::Index(i) => i == &idx,
-            Ref::Label(_) => false,
-            Ref::Any => true,
-        })
+        self.contains(idx, None)
     }
-    fn contains_label(&self, lbl: &str) -> bool {
+    /// a labeled item can be referred to by its label or its index
+    fn contains(&self, idx: usize, lbl: Option<&str>) -> bool {
         self.0.iter().any(|r| match r {
-            Ref::Index(_) => false,
-            Ref::Label(l) => l == lbl,
+            Ref::Index(i) => i == &idx,
+            Ref::Label(l) => Some(l.as_str()) == lbl,
             Ref::Any => true,
         })
     }
@@ -96,12 +93,14 @@ impl<'a> YamlStateTestBuilder<'a> {
 
             // parse transaction
             let yaml_transaction = &yaml_test["transaction"];
+            // the access list of the transaction applies to the data without their own
+            let access_list = parse_raw_access_list(Some(&yaml_transaction["accessList"])).unwrap();
             let data_s: Vec<_> = yaml_transaction["data"]
                 .as_vec()
                 .context("as_vec")
                 .unwrap()
                 .iter()
-                .map(|item| self.parse_calldata(item))
+                .map(|item| self.parse_calldata(item, &access_list))
                 .collect::<Result<_>>()
                 .unwrap();
 
@@ -124,17 +123,26 @@ impl<'a> YamlStateTestBuilder<'a> {
                 .unwrap();
 
             let max_priority_fee_per_gas =
-                Self::parse_u256(&yaml_transaction["maxPriorityFeePerGas"]).ok();
-            let max_fee_per_gas = Self::parse_u256(&yaml_transaction["maxFeePerGas"]).ok();
+                Self::parse_optional_u256(&yaml_transaction["maxPriorityFeePerGas"]).unwrap();
+            let max_fee_per_gas =
+                Self::parse_optional_u256(&yaml_transaction["maxFeePerGas"]).unwrap();
 
             // Set gas price to `min(max_priority_fee_per_gas + base_fee, max_fee_per_gas)` for
             // EIP-1559 transaction.
             // <https://github.com/ethereum/go-ethereum/blob/1485814f89d8206bb4a1c8e10a4a2893920f683a/core/state_transition.go#L167>
-            let gas_price = Self::parse_u256(&yaml_transaction["gasPrice"]).unwrap_or_else(|_| {
-                max_fee_per_gas
-                    .unwrap()
-                    .min(max_priority_fee_per_gas.unwrap() + env.current_base_fee)
-            });
+            let gas_price = match (
+                Self::parse_optional_u256(&yaml_transaction["gasPrice"]).unwrap(),
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            ) {
+                (Some(gas_price), _, _) => gas_price,
+                (None, Some(max_fee_per_gas), Some(max_priority_fee_per_gas)) => {
+                    max_fee_per_gas.min(max_priority_fee_per_gas + env.current_base_fee)
+                }
+                _ => {
+                    bail!("{test_name}: neither gasPrice nor maxFeePerGas and maxPriorityFeePerGas")
+                }
+            };
 
             let nonce = Self::parse_u256(&yaml_transaction["nonce"]).unwrap();
             let to =
@@ -150,16 +158,18 @@ impl<'a> YamlStateTestBuilder<'a> {
                 .unwrap()
                 .iter()
             {
-                let networks: Vec<_> = expect["network"]
-                    .as_vec()
-                    .expect("cannot convert network into vec<string>")
-                    .iter()
-                    .map(|n| {
-                        n.as_str()
-                            .expect("cannot convert network into string")
-                            .to_string()
-                    })
-                    .collect();
+                // a single network can be given without a list
+                let networks: Vec<_> = match &expect["network"] {
+                    Yaml::Array(networks) => networks.iter().collect(),
+                    network => vec![network],
+                }
+                .into_iter()
+                .map(|n| {
+                    n.as_str()
+                        .expect("cannot convert network into string")
+                        .to_string()
+                })
+                .collect();
 
                 let mut exception: bool = false;
 
@@ -193,15 +203,14 @@ impl<'a> YamlStateTestBuilder<'a> {
                         // find the first result that fulfills the pattern
                         for (exception, data_refs, gas_refs, value_refs, result) in &expects {
                             // check if this result can be applied to the current test
-                            let mut data_label = String::new();
-                            if let Some(label) = &calldata.label {
-                                if !data_refs.contains_label(label) {
-                                    continue;
-                                }
-                                data_label = format!("({label})");
-                            } else if !data_refs.contains_index(idx_data) {
+                            if !data_refs.contains(idx_data, calldata.label.as_deref()) {
                                 continue;
                             }
+                            let data_label = calldata
+                                .label
+                                .as_ref()
+                                .map(|label| format!("({label})"))
+                                .unwrap_or_default();
 
                             if !gas_refs.contains_index(idx_gas) {
                                 continue;
@@ -409,16 +418,28 @@ impl<'a> YamlStateTestBuilder<'a> {
     }
 
     /// returns the element as calldata bytes, supports 0x, :raw, :abi, :yul and
-    /// { LLL }
-    fn parse_calldata(&mut self, yaml: &Yaml) -> Result<parse::Calldata> {
+    /// { LLL }, with its own access list or `access_list`
+    fn parse_calldata(
+        &mut self,
+        yaml: &Yaml,
+        access_list: &Option<parse::RawAccessList>,
+    ) -> Result<parse::Calldata> {
         if let Some(as_str) = yaml.as_str() {
-            return parse::parse_calldata(self.compiler, as_str, &None);
+            return parse::parse_calldata(self.compiler, as_str, access_list);
         }
         if let Some(as_map) = yaml.as_hash() {
             if let Some(Yaml::String(data)) = as_map.get(&Yaml::String("data".to_string())) {
                 let raw_access_list =
                     parse_raw_access_list(as_map.get(&Yaml::String("accessList".to_string())))?;
-                return parse::parse_calldata(self.compiler, data, &raw_access_list);
+                return parse::parse_calldata(
+                    self.compiler,
+                    data,
+                    if raw_access_list.is_some() {
+                        &raw_access_list
+                    } else {
+                        access_list
+                    },
+                );
             } else {
                 bail!("do not know what to do with calldata(3): {:?}", yaml);
             }
@@ -452,7 +473,7 @@ impl<'a> YamlStateTestBuilder<'a> {
             parse::parse_u256(as_str)
         } else if yaml.as_f64().is_some() {
             if let Yaml::Real(value) = yaml {
-                Ok(U256::from_str_radix(value, 10)?)
+                parse::parse_u256(value)
             } else {
                 unreachable!()
             }
@@ -461,6 +482,15 @@ impl<'a> YamlStateTestBuilder<'a> {
         }
     }
 
+    /// parse an optional uint256 entry, which is `None` if missing
+    fn parse_optional_u256(yaml: &Yaml) -> Result<Option<U256>> {
+        if yaml.is_badvalue() {
+            Ok(None)
+        } else {
+            Self::parse_u256(yaml).map(Some)
+        }
+    }
+
     /// parse u64 entry
     #[allow(clippy::cast_sign_loss)]
     fn parse_u64(yaml: &Yaml) -> Result<u64> {
@@ -822,6 +852,88 @@ arith:
         Ok(())
     }
 
+    #[test]
+    fn eip1559_with_access_list() -> Result<()> {
+        let yaml = r#"
+eip1559:
+  env:
+    currentBaseFee: 10
+    currentCoinbase: 2adc25665018aa1fe0e6bc666dac8fc2697ff9ba
+    currentGasLimit: 100000000
+    currentNumber: 1
+    currentTimestamp: 1000
+  pre:
+    cccccccccccccccccccccccccccccccccccccccc:
+      balance: 1000000000000
+      code: :raw 0x600100
+      nonce: '0'
+      storage: {}
+  transaction:
+    data:
+    - :label first :raw 0x00
+    - data: :label second :raw 0x01
+      accessList: []
+    accessList:
+    - address: '0xcccccccccccccccccccccccccccccccccccccccc'
+      storageKeys:
+      - '0x01'
+    gasLimit:
+    - '80000000'
+    maxFeePerGas: 1000
+    maxPriorityFeePerGas: 2
+    nonce: '0'
+    to: cccccccccccccccccccccccccccccccccccccccc
+    value:
+    - '1'
+    secretKey: "45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8"
+  expect:
+    - indexes:
+        data: 1
+      network: '>=Shanghai<Prague'
+      result:
+        cccccccccccccccccccccccccccccccccccccccc:
+          balance: 20
+    - indexes:
+        data: :label first
+      network:
+        - 'Cancun'
+      result:
+        cccccccccccccccccccccccccccccccccccccccc:
+          balance: 10
+    - network:
+        - '<Cancun'
+      result:
+        cccccccccccccccccccccccccccccccccccccccc:
+          balance: 30
+"#;
+        let tcs = YamlStateTestBuilder::new(&Compiler::default())
+            .load_yaml("", yaml)?
+            .into_iter()
+            .map(|v| (v.id.clone(), v))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(tcs.len(), 2);
+
+        let ccccc = address!("cccccccccccccccccccccccccccccccccccccccc");
+        let first = &tcs["eip1559_d0(first)_g0_v0"];
+        assert_eq!(first.result[&ccccc].balance, Some(U256::from(10)));
+        assert_eq!(first.max_fee_per_gas, Some(U256::from(1000)));
+        assert_eq!(first.max_priority_fee_per_gas, Some(U256::from(2)));
+        assert_eq!(first.gas_price, U256::from(12));
+        assert_eq!(
+            first.access_list,
+            Some(AccessList(vec![AccessListItem {
+                address: ccccc,
+                storage_keys: vec![H256::from_low_u64_be(1)],
+            }]))
+        );
+
+        // labeled data referred by its index, with its own empty access list
+        let second = &tcs["eip1559_d1(second)_g0_v0"];
+        assert_eq!(second.result[&ccccc].balance, Some(U256::from(20)));
+        assert_eq!(second.access_list, Some(AccessList(vec![])));
+        Ok(())
+    }
+
     #[test]
     fn result_pass() -> Result<()> {
         let mut tc = YamlStateTestBuilder::new(&Compiler::default())
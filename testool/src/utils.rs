@@ -14,6 +14,7 @@ pub const ETH_CHAIN_ID: u64 = 1;
 
 #[derive(Debug, Eq, PartialEq, PartialOrd)]
 pub enum MainnetFork {
+    Prague = 17,
     Cancun = 16,
     Shanghai = 15,
     Paris = 14,
@@ -39,6 +40,7 @@ impl FromStr for MainnetFork {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
+            "Prague" => Self::Prague,
             "Cancun" => Self::Cancun,
             "Shanghai" => Self::Shanghai,
             "Paris" => Self::Paris,
@@ -71,7 +73,16 @@ impl MainnetFork {
             let mut in_network = false;
             for network in expect {
                 if let Some(network) = network.strip_prefix(">=") {
-                    if crate::utils::TEST_FORK >= crate::utils::MainnetFork::from_str(network)? {
+                    // a bounded range, e.g. `>=Shanghai<Cancun`
+                    let (lo, hi) = match network.split_once('<') {
+                        Some((lo, hi)) => (lo, Some(hi)),
+                        None => (network, None),
+                    };
+                    let below_hi = match hi {
+                        Some(hi) => crate::utils::TEST_FORK < MainnetFork::from_str(hi)?,
+                        None => true,
+                    };
+                    if crate::utils::TEST_FORK >= MainnetFork::from_str(lo)? && below_hi {
                         in_network = true;
                     }
                 } else if let Some(network) = network.strip_prefix('<') {
@@ -99,7 +110,7 @@ pub fn print_trace(trace: GethExecTrace) -> Result<()> {
         }
     }
     #[cfg(feature = "enable-storage")]
-    fn kv(storage: std::collections::HashMap<U256, U256>) -> Vec<String> {
+    fn kv(storage: &std::collections::HashMap<U256, U256>) -> Vec<String> {
         let mut keys: Vec<_> = storage.keys().collect();
         keys.sort();
         keys.iter()
@@ -149,12 +160,12 @@ pub fn print_trace(trace: GethExecTrace) -> Result<()> {
         let stack = vec![];
 
         #[cfg(feature = "enable-memory")]
-        let memory = step.memory.0.iter().map(ToString::to_string).collect();
+        let memory = step.memory.iter().map(|b| b.to_string()).collect();
         #[cfg(not(feature = "enable-memory"))]
         let memory = vec![];
 
         #[cfg(feature = "enable-storage")]
-        let storage = kv(step.storage.0);
+        let storage = kv(&step.storage.0);
         #[cfg(not(feature = "enable-storage"))]
         let storage = vec![];
 
@@ -231,6 +242,17 @@ mod test {
     fn networks() {
         assert!(MainnetFork::in_network_range(&[String::from(">=Istanbul")])
             .expect("can parse network"));
+        assert!(
+            MainnetFork::in_network_range(&[String::from(">=Shanghai<Prague")])
+                .expect("can parse network")
+        );
+        assert!(
+            !MainnetFork::in_network_range(&[String::from(">=Shanghai<Cancun")])
+                .expect("can parse network")
+        );
+        assert!(
+            MainnetFork::in_network_range(&[String::from("<Prague")]).expect("can parse network")
+        );
     }
 }
 
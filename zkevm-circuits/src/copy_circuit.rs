@@ -13,7 +13,7 @@ pub use dev::CopyCircuit as TestCopyCircuit;
 
 use crate::util::Field;
 use array_init::array_init;
-use bus_mapping::circuit_input_builder::{CopyDataType, CopyEvent};
+use bus_mapping::circuit_input_builder::{CopyDataType, CopyEvent, WitnessArena};
 use eth_types::Word;
 use gadgets::{
     binary_number::BinaryNumberChip,
@@ -624,9 +624,10 @@ impl<F: Field> CopyCircuitConfig<F> {
         lt_word_end_chip: &IsEqualChip<F>,
         challenges: Challenges<Value<F>>,
         copy_event: &CopyEvent,
+        copy_bytes: &WitnessArena,
     ) -> Result<(), Error> {
         for (step_idx, (tag, table_row, circuit_row)) in
-            CopyTable::assignments(copy_event, challenges)
+            CopyTable::assignments(copy_event, copy_bytes, challenges)
                 .iter()
                 .enumerate()
         {
@@ -780,11 +781,12 @@ impl<F: Field> CopyCircuitConfig<F> {
         Ok(())
     }
 
-    /// Assign vec of copy events
+    /// Assign vec of copy events, whose bytes are stored in `copy_bytes`
     pub fn assign_copy_events(
         &self,
         layouter: &mut impl Layouter<F>,
         copy_events: &[CopyEvent],
+        copy_bytes: &WitnessArena,
         max_copy_rows: usize,
         challenges: Challenges<Value<F>>,
     ) -> Result<(), Error> {
@@ -830,12 +832,7 @@ impl<F: Field> CopyCircuitConfig<F> {
                         offset,
                         ev_idx,
                         copy_event.full_length(),
-                        {
-                            CopyEvent {
-                                copy_bytes: Default::default(),
-                                ..copy_event.clone()
-                            }
-                        }
+                        copy_event
                     );
                     self.assign_copy_event(
                         &mut region,
@@ -846,6 +843,7 @@ impl<F: Field> CopyCircuitConfig<F> {
                         &lt_word_end_chip,
                         challenges,
                         copy_event,
+                        copy_bytes,
                     )?;
                     log::trace!("offset after {}th copy event: {}", ev_idx, offset);
                 }
@@ -1103,6 +1101,8 @@ pub struct ExternalData {
 pub struct CopyCircuit<F: Field> {
     /// Copy events
     pub copy_events: Vec<CopyEvent>,
+    /// Bytes of the copy events
+    pub copy_bytes: WitnessArena,
     /// Max number of rows in copy circuit
     pub max_copy_rows: usize,
     _marker: PhantomData<F>,
@@ -1112,9 +1112,14 @@ pub struct CopyCircuit<F: Field> {
 
 impl<F: Field> CopyCircuit<F> {
     /// Return a new CopyCircuit
-    pub fn new(copy_events: Vec<CopyEvent>, max_copy_rows: usize) -> Self {
+    pub fn new(
+        copy_events: Vec<CopyEvent>,
+        copy_bytes: WitnessArena,
+        max_copy_rows: usize,
+    ) -> Self {
         Self {
             copy_events,
+            copy_bytes,
             max_copy_rows,
             _marker: PhantomData,
             external_data: ExternalData::default(),
@@ -1124,11 +1129,13 @@ impl<F: Field> CopyCircuit<F> {
     /// Return a new CopyCircuit with external data
     pub fn new_with_external_data(
         copy_events: Vec<CopyEvent>,
+        copy_bytes: WitnessArena,
         max_copy_rows: usize,
         external_data: ExternalData,
     ) -> Self {
         Self {
             copy_events,
+            copy_bytes,
             max_copy_rows,
             _marker: PhantomData,
             external_data,
@@ -1142,6 +1149,7 @@ impl<F: Field> CopyCircuit<F> {
     pub fn new_from_block_no_external(block: &witness::Block) -> Self {
         Self::new(
             block.copy_events.clone(),
+            block.copy_bytes.clone(),
             block.circuits_params.max_copy_rows,
         )
     }
@@ -1159,6 +1167,7 @@ impl<F: Field> SubCircuit<F> for CopyCircuit<F> {
     fn new_from_block(block: &witness::Block) -> Self {
         Self::new_with_external_data(
             block.copy_events.clone(),
+            block.copy_bytes.clone(),
             block.circuits_params.max_copy_rows,
             ExternalData {
                 max_txs: block.circuits_params.max_txs,
@@ -1190,7 +1199,13 @@ impl<F: Field> SubCircuit<F> for CopyCircuit<F> {
         challenges: &Challenges<Value<F>>,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
-        config.assign_copy_events(layouter, &self.copy_events, self.max_copy_rows, *challenges)
+        config.assign_copy_events(
+            layouter,
+            &self.copy_events,
+            &self.copy_bytes,
+            self.max_copy_rows,
+            *challenges,
+        )
     }
 }
 
@@ -1235,7 +1250,7 @@ mod copy_circuit_stats {
                 block
                     .copy_events
                     .iter()
-                    .map(|c| c.copy_bytes.bytes.len() * 2)
+                    .map(|c| c.full_length() as usize * 2)
                     .sum::<usize>()
             },
         );
@@ -349,12 +349,16 @@ pub fn constrain_event_rlc_acc<F: Field>(
 
     // Check the rlc_acc given in the event if any of:
     // - RlcAcc => *
+    // - PrecompileOutput => *
     // - * => RlcAcc
+    // - * => PrecompileInput
     // - * => Bytecode
     // See also `CopyEvent::has_rlc()`
     let rlc_acc_cond = sum::expr([
         tag.value_equals(CopyDataType::RlcAcc, CURRENT)(meta),
+        tag.value_equals(CopyDataType::PrecompileOutput, CURRENT)(meta),
         tag.value_equals(CopyDataType::RlcAcc, NEXT_ROW)(meta),
+        tag.value_equals(CopyDataType::PrecompileInput, NEXT_ROW)(meta),
         meta.query_advice(is_bytecode, NEXT_ROW),
     ]);
 
@@ -7,7 +7,7 @@ use crate::{
     witness::Block,
 };
 use bus_mapping::{
-    circuit_input_builder::{CircuitInputBuilder, CircuitsParams},
+    circuit_input_builder::{CircuitInputBuilder, CircuitsParams, CopyBytes, WitnessArena},
     evm::{gen_sha3_code, MemoryKind, OpcodeId, PrecompileCallArgs},
     mock::BlockData,
     precompile::PrecompileCalls,
@@ -35,14 +35,24 @@ fn copy_circuit_unusable_rows() {
     )
 }
 
+#[test]
+fn copy_data_type_encoding() {
+    gadgets::binary_number::assert_tag_encoding::<CopyDataType, 4>();
+}
+
 /// Test copy circuit from copy events and test data
 pub fn test_copy_circuit(
     copy_events: Vec<CopyEvent>,
+    copy_bytes: WitnessArena,
     max_copy_rows: usize,
     external_data: ExternalData,
 ) -> Result<(), Vec<VerifyFailure>> {
-    let circuit =
-        CopyCircuit::<Fr>::new_with_external_data(copy_events, max_copy_rows, external_data);
+    let circuit = CopyCircuit::<Fr>::new_with_external_data(
+        copy_events,
+        copy_bytes,
+        max_copy_rows,
+        external_data,
+    );
 
     let prover = MockProver::<Fr>::run(K, &circuit, vec![]).unwrap();
     prover.verify_par()
@@ -52,6 +62,7 @@ pub fn test_copy_circuit(
 pub fn test_copy_circuit_from_block(block: Block) -> Result<(), Vec<VerifyFailure>> {
     test_copy_circuit(
         block.copy_events,
+        block.copy_bytes,
         block.circuits_params.max_copy_rows,
         ExternalData {
             max_txs: block.circuits_params.max_txs,
@@ -64,6 +75,22 @@ pub fn test_copy_circuit_from_block(block: Block) -> Result<(), Vec<VerifyFailur
     )
 }
 
+/// Add one to the first byte of the first copy event.
+fn modify_first_copy_byte(builder: &mut CircuitInputBuilder) {
+    let block = &mut builder.block;
+    let copy_bytes = block.copy_events[0].copy_bytes;
+    let mut bytes = copy_bytes.bytes(&block.copy_bytes).to_vec();
+    bytes[0].0 = bytes[0].0.wrapping_add(1);
+    let aux_bytes = copy_bytes
+        .aux_bytes(&block.copy_bytes)
+        .map(|steps| steps.to_vec());
+    let bytes_write_prev = copy_bytes
+        .bytes_write_prev(&block.copy_bytes)
+        .map(<[u8]>::to_vec);
+    block.copy_events[0].copy_bytes =
+        CopyBytes::new(&mut block.copy_bytes, bytes, aux_bytes, bytes_write_prev).unwrap();
+}
+
 fn gen_calldatacopy_data() -> CircuitInputBuilder {
     let length = 512 / 2 - 32;
     let code = bytecode! {
@@ -445,10 +472,7 @@ fn copy_circuit_invalid_calldatacopy() {
     let mut builder = gen_calldatacopy_data();
 
     // modify first byte of first copy event
-    builder.block.copy_events[0].copy_bytes.bytes[0].0 =
-        builder.block.copy_events[0].copy_bytes.bytes[0]
-            .0
-            .wrapping_add(1);
+    modify_first_copy_byte(&mut builder);
 
     let block = block_convert(&builder.block, &builder.code_db).unwrap();
 
@@ -463,10 +487,7 @@ fn copy_circuit_invalid_codecopy() {
     let mut builder = gen_codecopy_data();
 
     // modify first byte of first copy event
-    builder.block.copy_events[0].copy_bytes.bytes[0].0 =
-        builder.block.copy_events[0].copy_bytes.bytes[0]
-            .0
-            .wrapping_add(1);
+    modify_first_copy_byte(&mut builder);
 
     let block = block_convert(&builder.block, &builder.code_db).unwrap();
 
@@ -481,10 +502,7 @@ fn copy_circuit_invalid_extcodecopy() {
     let mut builder = gen_extcodecopy_data();
 
     // modify first byte of first copy event
-    builder.block.copy_events[0].copy_bytes.bytes[0].0 =
-        builder.block.copy_events[0].copy_bytes.bytes[0]
-            .0
-            .wrapping_add(1);
+    modify_first_copy_byte(&mut builder);
 
     let block = block_convert(&builder.block, &builder.code_db).unwrap();
 
@@ -499,10 +517,7 @@ fn copy_circuit_invalid_sha3() {
     let mut builder = gen_sha3_data();
 
     // modify first byte of first copy event
-    builder.block.copy_events[0].copy_bytes.bytes[0].0 =
-        builder.block.copy_events[0].copy_bytes.bytes[0]
-            .0
-            .wrapping_add(1);
+    modify_first_copy_byte(&mut builder);
 
     let block = block_convert(&builder.block, &builder.code_db).unwrap();
 
@@ -514,10 +529,7 @@ fn copy_circuit_invalid_tx_log() {
     let mut builder = gen_tx_log_data();
 
     // modify first byte of first copy event
-    builder.block.copy_events[0].copy_bytes.bytes[0].0 =
-        builder.block.copy_events[0].copy_bytes.bytes[0]
-            .0
-            .wrapping_add(1);
+    modify_first_copy_byte(&mut builder);
 
     let block = block_convert(&builder.block, &builder.code_db).unwrap();
     let result = test_copy_circuit_from_block(block);
@@ -579,11 +591,16 @@ fn variadic_size_check() {
         .unwrap();
     let block2 = block_convert(&builder.block, &builder.code_db).unwrap();
 
-    let circuit = CopyCircuit::<Fr>::new(block1.copy_events, block1.circuits_params.max_copy_rows);
+    let circuit = CopyCircuit::<Fr>::new(
+        block1.copy_events,
+        block1.copy_bytes,
+        block1.circuits_params.max_copy_rows,
+    );
     let prover1 = MockProver::<Fr>::run(14, &circuit, vec![]).unwrap();
 
     let circuit = CopyCircuit::<Fr>::new(
         block2.copy_events.clone(),
+        block2.copy_bytes.clone(),
         block2.circuits_params.max_copy_rows,
     );
     let prover2 = MockProver::<Fr>::run(14, &circuit, vec![]).unwrap();
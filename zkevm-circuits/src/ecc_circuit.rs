@@ -5,7 +5,7 @@ use std::{iter, marker::PhantomData};
 
 use crate::util::Field;
 use bus_mapping::{
-    circuit_input_builder::{EcAddOp, EcMulOp, EcPairingOp, N_BYTES_PER_PAIR, N_PAIRING_PER_OP},
+    circuit_input_builder::{EcAddOp, EcMulOp, EcPairingOp, N_BYTES_PER_PAIR},
     precompile::PrecompileCalls,
 };
 use eth_types::{ToLittleEndian, U256};
@@ -150,6 +150,8 @@ pub struct EccCircuit<F: Field, const XI_0: i64> {
     pub max_mul_ops: usize,
     /// Maximum number of pairing operations supported in one instance of the ECC Circuit.
     pub max_pairing_ops: usize,
+    /// Number of (G1, G2) pairs in every pairing operation.
+    pub max_pairing_pairs: usize,
 
     /// EcAdd operations provided as witness data to the ECC circuit.
     pub add_ops: Vec<EcAddOp>,
@@ -198,12 +200,24 @@ impl<F: Field, const XI_0: i64> EccCircuit<F, XI_0> {
             );
             return Err(Error::Synthesis);
         }
+        if self.max_pairing_pairs < 2
+            || self
+                .pairing_ops
+                .iter()
+                .any(|op| op.pairs.len() != self.max_pairing_pairs)
+        {
+            error!(
+                "pairing ops must have exactly max pairing pairs = {} (at least 2) pairs",
+                self.max_pairing_pairs,
+            );
+            return Err(Error::Synthesis);
+        }
 
         // keccak powers of randomness.
         let keccak_powers = std::iter::successors(Some(Value::known(F::one())), |coeff| {
             Some(challenges.keccak_input() * coeff)
         })
-        .take(N_PAIRING_PER_OP * N_BYTES_PER_PAIR)
+        .take(self.max_pairing_pairs * N_BYTES_PER_PAIR)
         .map(|x| QuantumCell::Witness(x))
         .collect_vec();
 
@@ -236,10 +250,10 @@ impl<F: Field, const XI_0: i64> EccCircuit<F, XI_0> {
                 let mut ctx = config.fp_config.new_context(region);
 
                 macro_rules! decompose_ec_op {
-                    ($op_type:ident, $ops:expr, $n_ops:expr, $decompose_fn:ident) => {
+                    ($padding_op:expr, $ops:expr, $n_ops:expr, $decompose_fn:ident) => {
                         $ops.iter()
                             .filter(|op| !op.skip_by_ecc_circuit())
-                            .chain(std::iter::repeat(&$op_type::default()))
+                            .chain(std::iter::repeat(&$padding_op))
                             .take($n_ops)
                             .map(|op| {
                                 self.$decompose_fn(
@@ -268,16 +282,24 @@ impl<F: Field, const XI_0: i64> EccCircuit<F, XI_0> {
                 }
 
                 // P + Q == R
-                let ec_adds_decomposed =
-                    decompose_ec_op!(EcAddOp, self.add_ops, self.max_add_ops, decompose_ec_add_op);
+                let ec_adds_decomposed = decompose_ec_op!(
+                    EcAddOp::default(),
+                    self.add_ops,
+                    self.max_add_ops,
+                    decompose_ec_add_op
+                );
 
                 // s.P = R
-                let ec_muls_decomposed =
-                    decompose_ec_op!(EcMulOp, self.mul_ops, self.max_mul_ops, decompose_ec_mul_op);
+                let ec_muls_decomposed = decompose_ec_op!(
+                    EcMulOp::default(),
+                    self.mul_ops,
+                    self.max_mul_ops,
+                    decompose_ec_mul_op
+                );
 
                 // e(G1 . G2) * ... * e(G1 . G2) -> Gt
                 let ec_pairings_decomposed = decompose_ec_op!(
-                    EcPairingOp,
+                    EcPairingOp::filler(self.max_pairing_pairs),
                     self.pairing_ops,
                     self.max_pairing_ops,
                     decompose_ec_pairing_op
@@ -824,7 +846,7 @@ impl<F: Field, const XI_0: i64> EccCircuit<F, XI_0> {
         log_context_cursor!(ctx);
 
         // EVM input for EcPairing in Big-Endian representation, padded by 0 bytes so that the
-        // total number of bytes are N_PAIRING_PER_OP * N_BYTES_PER_PAIR.
+        // total number of bytes are max_pairing_pairs * N_BYTES_PER_PAIR.
         let input_cells = decomposed_pairs
             .iter()
             .flat_map(|(_, _, g1, g2)| {
@@ -876,7 +898,7 @@ impl<F: Field, const XI_0: i64> EccCircuit<F, XI_0> {
             Vec<EcPoint<F, FieldExtPoint<CRTInteger<F>>>>,
         );
         let (dummy_pair_check_ok_g1s, dummy_pair_check_ok_g2s): TupleG1sG2s<F> =
-            EcPairingOp::dummy_pairing_check_ok()
+            EcPairingOp::dummy_pairing_check_ok(op.pairs.len())
                 .pairs
                 .iter()
                 .map(|pair| {
@@ -1322,6 +1344,7 @@ impl<F: Field, const XI_0: i64> SubCircuit<F> for EccCircuit<F, XI_0> {
             max_add_ops: block.circuits_params.max_ec_ops.ec_add,
             max_mul_ops: block.circuits_params.max_ec_ops.ec_mul,
             max_pairing_ops: block.circuits_params.max_ec_ops.ec_pairing,
+            max_pairing_pairs: block.circuits_params.max_ec_ops.ec_pairing_pairs,
             add_ops: block.get_ec_add_ops(),
             mul_ops: block.get_ec_mul_ops(),
             pairing_ops: block.get_ec_pairing_ops(),
@@ -5,7 +5,7 @@ use std::{
 
 use crate::util::Field;
 use bus_mapping::circuit_input_builder::{
-    EcAddOp, EcMulOp, EcPairingOp, EcPairingPair, PrecompileEcParams,
+    EcAddOp, EcMulOp, EcPairingOp, EcPairingPair, PrecompileEcParams, N_PAIRING_PER_OP,
 };
 use eth_types::U256;
 use halo2_proofs::{
@@ -28,6 +28,7 @@ fn run<F: Field, const MUST_FAIL: bool>(
         max_add_ops: max_ec_ops.ec_add,
         max_mul_ops: max_ec_ops.ec_mul,
         max_pairing_ops: max_ec_ops.ec_pairing,
+        max_pairing_pairs: max_ec_ops.ec_pairing_pairs,
         add_ops,
         mul_ops,
         pairing_ops,
@@ -116,7 +117,7 @@ impl GenRand for EcPairingOp {
         let point_c = G1Affine::from(G1Affine::generator() * alpha * beta);
         let point_d = G2Affine::generator();
 
-        let mut pairs = [
+        let mut pairs = vec![
             EcPairingPair::new(point_p_negated, point_q),
             EcPairingPair::new(point_s, point_t),
             EcPairingPair::new(point_a_negated, point_b),
@@ -130,7 +131,7 @@ impl GenRand for EcPairingOp {
                 true => Self {
                     pairs,
                     output: eth_types::U256::one() - output,
-                    ..Default::default()
+                    ..Self::filler(N_PAIRING_PER_OP)
                 },
                 // change a point in one of the pairs.
                 false => {
@@ -140,7 +141,7 @@ impl GenRand for EcPairingOp {
                     Self {
                         pairs,
                         output,
-                        ..Default::default()
+                        ..Self::filler(N_PAIRING_PER_OP)
                     }
                 }
             }
@@ -148,7 +149,7 @@ impl GenRand for EcPairingOp {
             Self {
                 pairs,
                 output,
-                ..Default::default()
+                ..Self::filler(N_PAIRING_PER_OP)
             }
         }
     }
@@ -251,7 +252,7 @@ mod valid_invalid_cases {
                 let point_q = G2Affine::from(G2Affine::generator() * beta);
                 let point_s = G1Affine::from(G1Affine::generator() * alpha * beta);
                 let point_t = G2Affine::generator();
-                let pairs = [
+                let pairs = vec![
                     EcPairingPair::new(point_p_negated, point_q),
                     EcPairingPair::new(point_s, point_t),
                     EcPairingPair::padding_pair(),
@@ -260,7 +261,7 @@ mod valid_invalid_cases {
                 EcPairingOp {
                     pairs,
                     output: U256::one(),
-                    ..Default::default()
+                    ..EcPairingOp::filler(N_PAIRING_PER_OP)
                 }
             },
             // 2. invalid: field element > Fq::MODULUS, mod p is OK
@@ -271,7 +272,7 @@ mod valid_invalid_cases {
                 let point_p_negated = point_p.neg();
                 let point_q = G2Affine::from(G2Affine::generator() * beta);
                 let point_t = G2Affine::from(G2Affine::generator() * alpha * beta);
-                let pairs = [
+                let pairs = vec![
                     EcPairingPair::new(point_p_negated, point_q),
                     EcPairingPair {
                         g1_point: (
@@ -291,7 +292,7 @@ mod valid_invalid_cases {
                 EcPairingOp {
                     pairs,
                     output: U256::zero(),
-                    ..Default::default()
+                    ..EcPairingOp::filler(N_PAIRING_PER_OP)
                 }
             },
         ]
@@ -308,7 +309,7 @@ mod valid_invalid_cases {
                 let point_q = G2Affine::from(G2Affine::generator() * beta);
                 let point_s = G1Affine::from(G1Affine::generator() * gamma);
                 let point_t = G2Affine::generator();
-                let pairs = [
+                let pairs = vec![
                     EcPairingPair::new(point_p_negated, point_q),
                     EcPairingPair::new(point_s, point_t),
                     EcPairingPair::padding_pair(),
@@ -317,12 +318,12 @@ mod valid_invalid_cases {
                 EcPairingOp {
                     pairs,
                     output: U256::zero(),
-                    ..Default::default()
+                    ..EcPairingOp::filler(N_PAIRING_PER_OP)
                 }
             },
             // 4. invalid: not on curve G1.
             EcPairingOp {
-                pairs: [
+                pairs: vec![
                     EcPairingPair {
                         g1_point: (U256::from(3), U256::from(4)),
                         g2_point: (U256::zero(), U256::zero(), U256::zero(), U256::zero()),
@@ -332,7 +333,7 @@ mod valid_invalid_cases {
                     EcPairingPair::padding_pair(),
                 ],
                 output: 0.into(),
-                ..Default::default()
+                ..EcPairingOp::filler(N_PAIRING_PER_OP)
             },
         ]
     });
@@ -340,7 +341,7 @@ mod valid_invalid_cases {
         vec![
             // 5. invalid: not on curve G2.
             EcPairingOp {
-                pairs: [
+                pairs: vec![
                     EcPairingPair {
                         g1_point: (U256::zero(), U256::zero()),
                         g2_point: (U256::from(3), U256::from(4), U256::from(5), U256::from(6)),
@@ -350,18 +351,18 @@ mod valid_invalid_cases {
                     EcPairingPair::padding_pair(),
                 ],
                 output: 0.into(),
-                ..Default::default()
+                ..EcPairingOp::filler(N_PAIRING_PER_OP)
             },
             // 6. valid: all zero.
             EcPairingOp {
-                pairs: [
+                pairs: vec![
                     EcPairingPair::padding_pair(),
                     EcPairingPair::padding_pair(),
                     EcPairingPair::padding_pair(),
                     EcPairingPair::padding_pair(),
                 ],
                 output: 1.into(),
-                ..Default::default()
+                ..EcPairingOp::filler(N_PAIRING_PER_OP)
             },
         ]
     });
@@ -369,25 +370,25 @@ mod valid_invalid_cases {
         vec![
             // 7. valid: [(G1::gen, G2::gen), (-G1::gen, G2::gen)]
             EcPairingOp {
-                pairs: [
+                pairs: vec![
                     EcPairingPair::new(G1Affine::generator(), G2Affine::generator()),
                     EcPairingPair::new(G1Affine::generator().neg(), G2Affine::generator()),
                     EcPairingPair::padding_pair(),
                     EcPairingPair::padding_pair(),
                 ],
                 output: 1.into(),
-                ..Default::default()
+                ..EcPairingOp::filler(N_PAIRING_PER_OP)
             },
             // 8. valid: [(G1::gen, G2::gen), (-G1::gen, G2::gen); 2]
             EcPairingOp {
-                pairs: [
+                pairs: vec![
                     EcPairingPair::new(G1Affine::generator(), G2Affine::generator()),
                     EcPairingPair::new(G1Affine::generator().neg(), G2Affine::generator()),
                     EcPairingPair::new(G1Affine::generator(), G2Affine::generator()),
                     EcPairingPair::new(G1Affine::generator().neg(), G2Affine::generator()),
                 ],
                 output: 1.into(),
-                ..Default::default()
+                ..EcPairingOp::filler(N_PAIRING_PER_OP)
             },
         ]
     });
@@ -415,6 +416,7 @@ fn test_ecc_circuit_valid_invalid() {
             ec_add: 0,
             ec_mul: 0,
             ec_pairing: 2,
+            ec_pairing_pairs: N_PAIRING_PER_OP,
         },
         vec![],
         vec![],
@@ -427,6 +429,7 @@ fn test_ecc_circuit_valid_invalid() {
             ec_add: 0,
             ec_mul: 0,
             ec_pairing: 2,
+            ec_pairing_pairs: N_PAIRING_PER_OP,
         },
         vec![],
         vec![],
@@ -439,6 +442,7 @@ fn test_ecc_circuit_valid_invalid() {
             ec_add: 0,
             ec_mul: 0,
             ec_pairing: 2,
+            ec_pairing_pairs: N_PAIRING_PER_OP,
         },
         vec![],
         vec![],
@@ -446,6 +450,48 @@ fn test_ecc_circuit_valid_invalid() {
     );
 }
 
+#[test]
+fn test_ecc_circuit_pairing_pairs_param() {
+    use crate::ecc_circuit::util::LOG_TOTAL_NUM_ROWS;
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    let params = PrecompileEcParams {
+        ec_add: 0,
+        ec_mul: 0,
+        ec_pairing: 2,
+        ec_pairing_pairs: 2,
+    };
+    // [(G1::gen, G2::gen), (-G1::gen, G2::gen)]
+    let pairing_ops = vec![EcPairingOp {
+        pairs: vec![
+            EcPairingPair::new(G1Affine::generator(), G2Affine::generator()),
+            EcPairingPair::new(G1Affine::generator().neg(), G2Affine::generator()),
+        ],
+        output: 1.into(),
+        ..EcPairingOp::filler(2)
+    }];
+    run::<Fr, false>(
+        LOG_TOTAL_NUM_ROWS,
+        params,
+        vec![],
+        vec![],
+        pairing_ops.clone(),
+    );
+
+    // ops padded to another number of pairs are rejected
+    let circuit = EccCircuit::<Fr, 9> {
+        max_add_ops: params.ec_add,
+        max_mul_ops: params.ec_mul,
+        max_pairing_ops: params.ec_pairing,
+        max_pairing_pairs: N_PAIRING_PER_OP,
+        add_ops: vec![],
+        mul_ops: vec![],
+        pairing_ops,
+        _marker: PhantomData,
+    };
+    assert!(MockProver::<Fr>::run(LOG_TOTAL_NUM_ROWS, &circuit, vec![]).is_err());
+}
+
 #[ignore = "generate a lot of random invalid inputs for bn254 add"]
 #[test]
 fn test_invalid_ec_add() {
@@ -494,6 +540,7 @@ fn test_invalid_ec_add() {
             ec_add: ec_adds.len(),
             ec_mul: 0,
             ec_pairing: 0,
+            ec_pairing_pairs: N_PAIRING_PER_OP,
         },
         ec_adds,
         vec![],
@@ -537,6 +584,7 @@ fn test_invalid_ec_add() {
             ec_add: ec_adds.len(),
             ec_mul: 0,
             ec_pairing: 0,
+            ec_pairing_pairs: N_PAIRING_PER_OP,
         },
         ec_adds,
         vec![],
@@ -590,6 +638,7 @@ fn variadic_size_check() {
         max_add_ops: default_params.ec_add,
         max_mul_ops: default_params.ec_mul,
         max_pairing_ops: default_params.ec_pairing,
+        max_pairing_pairs: default_params.ec_pairing_pairs,
         add_ops: gen(&mut rng, 25, false),
         mul_ops: gen(&mut rng, 20, false),
         pairing_ops: EC_PAIRING_OPS1.clone(),
@@ -601,6 +650,7 @@ fn variadic_size_check() {
         max_add_ops: default_params.ec_add,
         max_mul_ops: default_params.ec_mul,
         max_pairing_ops: default_params.ec_pairing,
+        max_pairing_pairs: default_params.ec_pairing_pairs,
         add_ops: {
             let mut ops = gen(&mut rng, 30, false);
             ops.extend_from_slice(&EC_ADD_OPS);
@@ -23,11 +23,15 @@ use crate::{
     evm_circuit::param::{MAX_STEP_HEIGHT, STEP_STATE_HEIGHT},
     table::{
         BlockTable, BytecodeTable, CopyTable, EccTable, ExpTable, KeccakTable, LookupTable,
-        ModExpTable, PowOfRandTable, RwTable, SHA256Table, SigTable, TxTable,
+        ModExpTable, PowOfRandTable, RwTable, SHA256Table, SigTable, TxTable, U8Table,
     },
     util::{Field, SubCircuit, SubCircuitConfig},
 };
-use bus_mapping::evm::OpcodeId;
+use bus_mapping::{
+    circuit_input_builder::{L1FeeSchema, PrecompileEcParams, N_PAIRING_PER_OP},
+    evm::OpcodeId,
+};
+use eth_types::evm_types::ChainSpec;
 use execution::ExecutionConfig;
 use itertools::Itertools;
 use strum::IntoEnumIterator;
@@ -39,9 +43,9 @@ use witness::Block;
 #[derive(Clone, Debug)]
 pub struct EvmCircuitConfig<F> {
     fixed_table: [Column<Fixed>; 4],
-    byte_table: [Column<Fixed>; 1],
     pub(crate) execution: Box<ExecutionConfig<F>>,
     // External tables
+    u8_table: U8Table,
     tx_table: TxTable,
     rw_table: RwTable,
     bytecode_table: BytecodeTable,
@@ -54,12 +58,17 @@ pub struct EvmCircuitConfig<F> {
     modexp_table: ModExpTable,
     ecc_table: EccTable,
     pub(crate) pow_of_rand_table: PowOfRandTable,
+    // Public flag set when the block contains steps handled by a pass-through
+    // gadget, see `ExecutionState::is_unproven`.
+    unproven_steps: Column<Instance>,
 }
 
 /// Circuit configuration arguments
 pub struct EvmCircuitConfigArgs<F: Field> {
     /// Challenge
     pub challenges: crate::util::Challenges<Expression<F>>,
+    /// Shared byte range table, loaded by the caller
+    pub u8_table: U8Table,
     /// TxTable
     pub tx_table: TxTable,
     /// RwTable
@@ -84,6 +93,32 @@ pub struct EvmCircuitConfigArgs<F: Field> {
     pub ecc_table: EccTable,
     // Power of Randomness Table.
     pub pow_of_rand_table: PowOfRandTable,
+    /// Parameters of the proven chain
+    pub params: EvmCircuitConfigParams,
+}
+
+/// Parameters of the chain proven by the EVM Circuit. They are constants of
+/// its constraints, so the witness must be generated with the same ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EvmCircuitConfigParams {
+    /// Schema of the L1 fee of the transactions
+    pub l1_fee_schema: L1FeeSchema,
+    /// Call depth and stack limits, the stack limit being at most the one of
+    /// Ethereum
+    pub chain_spec: ChainSpec,
+    /// Maximum number of pairs of an ecPairing call, which must match
+    /// [`PrecompileEcParams::ec_pairing_pairs`] of the witness
+    pub max_ec_pairing_pairs: usize,
+}
+
+impl Default for EvmCircuitConfigParams {
+    fn default() -> Self {
+        Self {
+            l1_fee_schema: L1FeeSchema::default(),
+            chain_spec: ChainSpec::default(),
+            max_ec_pairing_pairs: N_PAIRING_PER_OP,
+        }
+    }
 }
 
 /// Circuit exported cells after synthesis, used for subcircuit
@@ -102,6 +137,7 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
         meta: &mut ConstraintSystem<F>,
         Self::ConfigArgs {
             challenges,
+            u8_table,
             tx_table,
             rw_table,
             bytecode_table,
@@ -114,15 +150,16 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             modexp_table,
             ecc_table,
             pow_of_rand_table,
+            params,
         }: Self::ConfigArgs,
     ) -> Self {
         let fixed_table = [(); 4].map(|_| meta.fixed_column());
-        let byte_table = [(); 1].map(|_| meta.fixed_column());
+        let unproven_steps = meta.instance_column();
         let execution = Box::new(ExecutionConfig::configure(
             meta,
             challenges,
             &fixed_table,
-            &byte_table,
+            u8_table,
             &tx_table,
             &rw_table,
             &bytecode_table,
@@ -135,9 +172,10 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             &modexp_table,
             &ecc_table,
             &pow_of_rand_table,
+            unproven_steps,
+            params,
         ));
 
-        meta.annotate_lookup_any_column(byte_table[0], || "byte_range");
         fixed_table.iter().enumerate().for_each(|(idx, &col)| {
             meta.annotate_lookup_any_column(col, || format!("fix_table_{idx}"))
         });
@@ -155,8 +193,8 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
 
         Self {
             fixed_table,
-            byte_table,
             execution,
+            u8_table,
             tx_table,
             rw_table,
             bytecode_table,
@@ -169,6 +207,7 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             modexp_table,
             ecc_table,
             pow_of_rand_table,
+            unproven_steps,
         }
     }
 }
@@ -196,25 +235,6 @@ impl<F: Field> EvmCircuitConfig<F> {
             },
         )
     }
-
-    /// Load byte table
-    pub fn load_byte_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
-        layouter.assign_region(
-            || "byte table",
-            |mut region| {
-                for offset in 0..256 {
-                    region.assign_fixed(
-                        || "",
-                        self.byte_table[0],
-                        offset,
-                        || Value::known(F::from(offset as u64)),
-                    )?;
-                }
-
-                Ok(())
-            },
-        )
-    }
 }
 
 /// Tx Circuit for verifying transaction signatures
@@ -280,7 +300,7 @@ impl<F: Field> EvmCircuit<F> {
     }
 }
 
-const FIXED_TABLE_ROWS_NO_BITWISE: usize = 3659;
+const FIXED_TABLE_ROWS_NO_BITWISE: usize = 3915;
 const FIXED_TABLE_ROWS: usize = FIXED_TABLE_ROWS_NO_BITWISE + 3 * 65536;
 
 impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
@@ -296,6 +316,13 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
         Self::new(block.clone())
     }
 
+    /// The only public input is the flag marking the blocks which contain
+    /// steps handled by a pass-through gadget.
+    fn instance(&self) -> Vec<Vec<F>> {
+        let has_unproven_steps = self.block.as_ref().map_or(false, Block::has_unproven_steps);
+        vec![vec![F::from(has_unproven_steps as u64)]]
+    }
+
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block) -> (usize, usize) {
         let num_rows_required_for_execution_steps: usize =
@@ -324,7 +351,6 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
         let block = self.block.as_ref().unwrap();
 
         config.load_fixed_table(layouter, self.fixed_table_tags.clone())?;
-        config.load_byte_table(layouter)?;
         let export = config.execution.assign_block(layouter, block, challenges)?;
         self.exports.borrow_mut().replace(export);
         Ok(())
@@ -421,6 +447,10 @@ pub(crate) mod cached {
         pub fn get_test_cicuit_from_block(block: Block) -> Self {
             Self(EvmCircuit::<Fr>::get_test_cicuit_from_block(block))
         }
+
+        pub fn instance(&self) -> Vec<Vec<Fr>> {
+            self.0.instance()
+        }
     }
 }
 
@@ -431,19 +461,15 @@ use crate::util::Challenges;
 #[cfg(feature = "onephase")]
 use crate::util::MockChallenges as Challenges;
 
-impl<F: Field> Circuit<F> for EvmCircuit<F> {
-    type Config = (EvmCircuitConfig<F>, Challenges);
-    type FloorPlanner = SimpleFloorPlanner;
-    #[cfg(feature = "circuit-params")]
-    type Params = ();
-
-    fn without_witnesses(&self) -> Self {
-        Self::default()
-    }
-
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+impl<F: Field> EvmCircuit<F> {
+    /// Configure the test circuit for the chain of `params`.
+    pub(crate) fn configure_with_params(
+        meta: &mut ConstraintSystem<F>,
+        params: EvmCircuitConfigParams,
+    ) -> <Self as Circuit<F>>::Config {
         let challenges = Challenges::construct(meta);
         let challenges_expr = challenges.exprs(meta);
+        let u8_table = U8Table::construct(meta);
         let rw_table = RwTable::construct(meta);
         let tx_table = TxTable::construct(meta);
         let bytecode_table = BytecodeTable::construct(meta);
@@ -462,6 +488,7 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
                 meta,
                 EvmCircuitConfigArgs {
                     challenges: challenges_expr,
+                    u8_table,
                     tx_table,
                     rw_table,
                     bytecode_table,
@@ -474,11 +501,27 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
                     modexp_table,
                     ecc_table,
                     pow_of_rand_table,
+                    params,
                 },
             ),
             challenges,
         )
     }
+}
+
+impl<F: Field> Circuit<F> for EvmCircuit<F> {
+    type Config = (EvmCircuitConfig<F>, Challenges);
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        Self::configure_with_params(meta, EvmCircuitConfigParams::default())
+    }
 
     fn synthesize(
         &self,
@@ -490,6 +533,7 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
         let (config, challenges) = config;
         let challenges = challenges.values(&layouter);
 
+        config.u8_table.load(&mut layouter)?;
         config.tx_table.load(
             &mut layouter,
             &block.txs,
@@ -516,7 +560,7 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
             .dev_load(&mut layouter, block, &challenges)?;
         config
             .keccak_table
-            .dev_load(&mut layouter, &block.sha3_inputs, &challenges)?;
+            .dev_load(&mut layouter, block.sha3_inputs.iter(), &challenges)?;
         config.sha256_table.dev_load(
             &mut layouter,
             block
@@ -557,20 +601,31 @@ mod evm_circuit_stats {
             },
             step::ExecutionState,
             table::FixedTableTag,
-            EvmCircuit, FIXED_TABLE_ROWS, FIXED_TABLE_ROWS_NO_BITWISE,
+            util::constraint_builder::{IMPLICIT_DEGREE, MAX_DEGREE},
+            EvmCircuit, EvmCircuitConfigParams, FIXED_TABLE_ROWS, FIXED_TABLE_ROWS_NO_BITWISE,
         },
         stats::print_circuit_stats_by_states,
+        table::RwTableTag,
         test_util::CircuitTestBuilder,
         util::{unusable_rows, SubCircuit},
-        witness::block_convert,
+        witness::{block_convert, block_convert_with_opcodes, Block, OpcodeExecutionStates},
+    };
+    use bus_mapping::{
+        circuit_input_builder::{self, CircuitInputStateRef, CircuitsParams, L1FeeSchema},
+        mock::BlockData,
     };
-    use bus_mapping::{circuit_input_builder::CircuitsParams, mock::BlockData};
     use cli_table::{print_stdout, Cell, Style, Table};
-    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, ToWord};
+    use eth_types::{
+        bytecode,
+        evm_types::{ChainSpec, OpcodeId},
+        geth_types::GethData,
+        GethExecStep, ToWord, Word,
+    };
     use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
         dev::MockProver,
         halo2curves::bn256::Fr,
-        plonk::{Circuit, ConstraintSystem},
+        plonk::{Circuit, ConstraintSystem, Error},
     };
     use itertools::Itertools;
     use mock::{
@@ -630,6 +685,24 @@ mod evm_circuit_stats {
         .run();
     }
 
+    #[test]
+    fn evm_circuit_with_seeded_challenges() {
+        let code = bytecode! {
+            PUSH32(Word::MAX)
+            PUSH1(0)
+            MSTORE
+            PUSH1(0x40)
+            PUSH1(0)
+            SHA3
+            STOP
+        };
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .challenge_seed(0xcafe)
+        .run();
+    }
+
     #[test]
     pub fn empty_evm_circuit_with_padding() {
         CircuitTestBuilder::new_from_test_ctx(
@@ -767,6 +840,102 @@ mod evm_circuit_stats {
         );
     }
 
+    /// This function prints to stdout a table with the constraint coverage of
+    /// each ExecutionState gadget, flagging the queried cells never
+    /// referenced in a constraint or a lookup.
+    ///
+    /// Run with:
+    /// `cargo test -p zkevm-circuits --release get_exec_steps_coverage
+    /// --features test -- --nocapture --ignored`
+    #[ignore]
+    #[test]
+    fn get_exec_steps_coverage() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let circuit = EvmCircuit::configure(&mut meta);
+
+        let rows = circuit
+            .0
+            .execution
+            .instrument()
+            .coverage()
+            .iter()
+            .sorted_by_key(|(_, coverage)| coverage.unused_cells.len())
+            .rev()
+            .map(|(state, coverage)| {
+                vec![
+                    format!("{state:?}"),
+                    format!("{}", coverage.cells),
+                    format!("{}", coverage.constraints),
+                    format!("{}", coverage.lookups),
+                    format!("{}", coverage.unused_cells.len()),
+                    format!("{:?}", coverage.unused_cells),
+                ]
+            })
+            .collect::<Vec<Vec<String>>>();
+
+        let table = rows.table().title(vec![
+            "state".cell().bold(true),
+            "cells".cell().bold(true),
+            "constraints".cell().bold(true),
+            "lookups".cell().bold(true),
+            "unused_cells".cell().bold(true),
+            "unused (column, rotation)".cell().bold(true),
+        ]);
+        print_stdout(table).unwrap();
+    }
+
+    /// This function prints to stdout the maximum expression degree of each
+    /// ExecutionState gadget, followed by the constraints and lookups whose
+    /// degree exceeds a target. The target defaults to the degree above which
+    /// expressions are split into extra cells, and can be set with the
+    /// `DEGREE_TARGET` environment variable.
+    ///
+    /// Run with:
+    /// `cargo test -p zkevm-circuits --release get_exec_steps_degree
+    /// --features test -- --nocapture --ignored`
+    #[ignore]
+    #[test]
+    fn get_exec_steps_degree() {
+        let target = std::env::var("DEGREE_TARGET")
+            .map(|target| target.parse().expect("DEGREE_TARGET must be a number"))
+            .unwrap_or(MAX_DEGREE - IMPLICIT_DEGREE);
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let circuit = EvmCircuit::configure(&mut meta);
+        let instrument = circuit.0.execution.instrument();
+
+        let rows = instrument
+            .degrees()
+            .iter()
+            .filter_map(|(state, degrees)| degrees.max().map(|max| (state, max)))
+            .sorted_by_key(|(_, (_, degree))| *degree)
+            .rev()
+            .map(|(state, (name, degree))| {
+                vec![format!("{state:?}"), format!("{degree}"), name.to_string()]
+            })
+            .collect::<Vec<Vec<String>>>();
+        let table = rows.table().title(vec![
+            "state".cell().bold(true),
+            "max degree".cell().bold(true),
+            "expression".cell().bold(true),
+        ]);
+        print_stdout(table).unwrap();
+
+        let rows = instrument
+            .degrees_exceeding(target)
+            .into_iter()
+            .map(|(state, name, degree)| {
+                vec![format!("{state:?}"), format!("{degree}"), name.to_string()]
+            })
+            .collect::<Vec<Vec<String>>>();
+        println!("{} expressions exceed degree {target}", rows.len());
+        let table = rows.table().title(vec![
+            "state".cell().bold(true),
+            "degree".cell().bold(true),
+            "expression".cell().bold(true),
+        ]);
+        print_stdout(table).unwrap();
+    }
+
     #[ignore = "need to make table dev_load padding to fix this"]
     #[test]
     fn variadic_size_check() {
@@ -789,7 +958,7 @@ mod evm_circuit_stats {
         let k = block.get_evm_test_circuit_degree();
 
         let circuit = EvmCircuit::<Fr>::get_test_cicuit_from_block(block);
-        let prover1 = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+        let prover1 = MockProver::<Fr>::run(k, &circuit, circuit.instance()).unwrap();
 
         let code = bytecode! {
             STOP
@@ -810,9 +979,306 @@ mod evm_circuit_stats {
         let block = block_convert(&builder.block, &builder.code_db).unwrap();
         let k = block.get_evm_test_circuit_degree();
         let circuit = EvmCircuit::<Fr>::get_test_cicuit_from_block(block);
-        let prover2 = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+        let prover2 = MockProver::<Fr>::run(k, &circuit, circuit.instance()).unwrap();
 
         assert_eq!(prover1.fixed(), prover2.fixed());
         assert_eq!(prover1.permutation(), prover2.permutation());
     }
+
+    #[cfg(not(feature = "scroll"))]
+    #[test]
+    fn unproven_steps_flag() {
+        let unproven_steps = |code| {
+            let block: GethData = TestContext::<2, 1>::new(
+                None,
+                account_0_code_account_1_no_code(code),
+                tx_from_1_to_0,
+                |b, _| b,
+            )
+            .unwrap()
+            .into();
+            let mut builder =
+                BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+            builder
+                .handle_block(&block.eth_block, &block.geth_traces)
+                .unwrap();
+            let block = block_convert(&builder.block, &builder.code_db).unwrap();
+            let k = block.get_evm_test_circuit_degree();
+            let circuit = EvmCircuit::<Fr>::get_test_cicuit_from_block(block);
+            let instance = circuit.instance();
+            let prover = MockProver::<Fr>::run(k, &circuit, instance.clone()).unwrap();
+            prover.assert_satisfied_par();
+
+            // The flag can't be cleared
+            let forged = vec![vec![Fr::from(1) - instance[0][0]]];
+            let prover = MockProver::<Fr>::run(k, &circuit, forged).unwrap();
+            let unproven = instance[0][0] == Fr::from(1);
+            assert_eq!(prover.verify_par().is_err(), unproven);
+            unproven
+        };
+
+        assert!(!unproven_steps(bytecode! { STOP }));
+        assert!(unproven_steps(bytecode! {
+            PUSH20(MOCK_ACCOUNTS[0].to_word())
+            SELFDESTRUCT
+        }));
+    }
+
+    fn gen_custom_jumpdest_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<circuit_input_builder::ExecStep>, bus_mapping::Error> {
+        Ok(vec![state.new_step(&geth_steps[0])?])
+    }
+
+    #[test]
+    fn opcode_registry_of_builder() {
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! {
+            JUMPDEST
+            STOP
+        })
+        .unwrap()
+        .into();
+        let witness = |custom: bool| {
+            let mut builder =
+                BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+            let mut execution_states = OpcodeExecutionStates::default();
+            if custom {
+                builder
+                    .block
+                    .opcode_registry
+                    .register(OpcodeId::JUMPDEST, gen_custom_jumpdest_ops);
+                execution_states.register(OpcodeId::JUMPDEST, ExecutionState::UNSUPPORTED);
+            }
+            builder
+                .handle_block(&block.eth_block, &block.geth_traces)
+                .unwrap();
+            block_convert_with_opcodes(&builder.block, &builder.code_db, &execution_states).unwrap()
+        };
+
+        // both builders live in the same process, the custom opcode of one
+        // doesn't leak into the other
+        let custom = witness(true);
+        let builtin = witness(false);
+        for (block, state, other_state) in [
+            (
+                custom,
+                ExecutionState::UNSUPPORTED,
+                ExecutionState::JUMPDEST,
+            ),
+            (
+                builtin,
+                ExecutionState::JUMPDEST,
+                ExecutionState::UNSUPPORTED,
+            ),
+        ] {
+            let states = block.txs[0]
+                .steps
+                .iter()
+                .map(|step| step.execution_state)
+                .collect::<Vec<_>>();
+            assert!(states.contains(&state));
+            assert!(!states.contains(&other_state));
+
+            let k = block.get_evm_test_circuit_degree();
+            let circuit = EvmCircuit::<Fr>::get_test_cicuit_from_block(block);
+            MockProver::<Fr>::run(k, &circuit, circuit.instance())
+                .unwrap()
+                .assert_satisfied_par();
+        }
+    }
+
+    #[test]
+    fn l1_fee_schema_of_other_config() {
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! { STOP })
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let mut block = block_convert(&builder.block, &builder.code_db).unwrap();
+        // the test circuit is configured for the scroll schema
+        block.l1_fee_schema = L1FeeSchema::OpBedrock;
+        let k = block.get_evm_test_circuit_degree();
+        let circuit = EvmCircuit::<Fr>::get_test_cicuit_from_block(block);
+        assert!(MockProver::<Fr>::run(k, &circuit, circuit.instance()).is_err());
+    }
+
+    /// Limits of the chain of [`ChainSpecCircuit`], which is before its Merge
+    /// and Shanghai
+    const TEST_CHAIN_SPEC: ChainSpec = ChainSpec {
+        max_call_depth: 2,
+        stack_limit: 16,
+        merge_block: u64::MAX,
+        shanghai_block: u64::MAX,
+    };
+
+    /// EVM Circuit configured for the limits of [`TEST_CHAIN_SPEC`]
+    struct ChainSpecCircuit(EvmCircuit<Fr>);
+
+    impl Circuit<Fr> for ChainSpecCircuit {
+        type Config = <EvmCircuit<Fr> as Circuit<Fr>>::Config;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            EvmCircuit::configure_with_params(
+                meta,
+                EvmCircuitConfigParams {
+                    chain_spec: TEST_CHAIN_SPEC,
+                    ..Default::default()
+                },
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            self.0.synthesize(config, layouter)
+        }
+    }
+
+    #[test]
+    fn chain_spec_of_config() {
+        // A CALL, then pushes up to `stack_len` words
+        let witness = |stack_len: usize, chain_spec: ChainSpec| {
+            let mut code = bytecode! {
+                PUSH1(0) // retLength
+                PUSH1(0) // retOffset
+                PUSH1(0) // argsLength
+                PUSH1(0) // argsOffset
+                PUSH1(0) // value
+                PUSH20(MOCK_ACCOUNTS[2].to_word()) // addr
+                GAS
+                CALL
+            };
+            for i in 1..stack_len {
+                code.push(1, Word::from(i));
+            }
+            code.op_stop();
+            let block: GethData = TestContext::<3, 1>::new(
+                None,
+                |accs| {
+                    accs[0].address(MOCK_ACCOUNTS[0]).code(code);
+                    accs[1]
+                        .address(MOCK_ACCOUNTS[1])
+                        .balance(Word::from(10u64.pow(19)));
+                    accs[2].address(MOCK_ACCOUNTS[2]);
+                },
+                |mut txs, accs| {
+                    txs[0].from(accs[1].address).to(accs[0].address);
+                },
+                |block, _tx| block,
+            )
+            .unwrap()
+            .into();
+            let mut builder =
+                BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+            builder.block.chain_spec = chain_spec;
+            builder
+                .handle_block(&block.eth_block, &block.geth_traces)
+                .unwrap();
+            block_convert(&builder.block, &builder.code_db).unwrap()
+        };
+
+        assert_eq!(
+            verify_chain_spec_circuit(witness(16, TEST_CHAIN_SPEC)),
+            Ok(())
+        );
+        // the witness of another chain spec is rejected
+        assert!(verify_chain_spec_circuit(witness(16, ChainSpec::mainnet())).is_err());
+        // a witness overflowing the stack limit does not verify
+        let mut block = witness(17, ChainSpec::mainnet());
+        block.chain_spec = TEST_CHAIN_SPEC;
+        assert!(verify_chain_spec_circuit(block).is_err());
+    }
+
+    #[cfg(not(feature = "scroll"))]
+    #[test]
+    fn chain_spec_merge_of_config() {
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! {
+                DIFFICULTY
+                STOP
+            }),
+            tx_from_1_to_0,
+            |block, _tx| {
+                block
+                    .difficulty(Word::from(0x20000u64))
+                    .mix_hash(eth_types::H256::repeat_byte(0xab))
+            },
+        )
+        .unwrap()
+        .into();
+        let witness = |chain_spec: ChainSpec| {
+            let mut builder =
+                BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+            builder.block.chain_spec = chain_spec;
+            builder
+                .handle_block(&block.eth_block, &block.geth_traces)
+                .unwrap();
+            block_convert(&builder.block, &builder.code_db).unwrap()
+        };
+
+        // DIFFICULTY returns the difficulty before the Merge of the chain
+        assert_eq!(verify_chain_spec_circuit(witness(TEST_CHAIN_SPEC)), Ok(()));
+        // and not prevRandao
+        let mut block = witness(ChainSpec::mainnet());
+        block.chain_spec = TEST_CHAIN_SPEC;
+        assert!(verify_chain_spec_circuit(block).is_err());
+    }
+
+    #[test]
+    fn chain_spec_shanghai_of_config() {
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! { STOP }),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let witness = |chain_spec: ChainSpec| {
+            let mut builder =
+                BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+            builder.block.chain_spec = chain_spec;
+            builder
+                .handle_block(&block.eth_block, &block.geth_traces)
+                .unwrap();
+            block_convert(&builder.block, &builder.code_db).unwrap()
+        };
+        let access_list_writes =
+            |block: &Block| block.rws.0[&RwTableTag::TxAccessListAccount].len();
+
+        // the coinbase isn't warm at the start of the tx before Shanghai
+        let pre_shanghai = witness(TEST_CHAIN_SPEC);
+        let shanghai = witness(ChainSpec::mainnet());
+        assert_eq!(
+            access_list_writes(&pre_shanghai) + 1,
+            access_list_writes(&shanghai)
+        );
+        assert_eq!(verify_chain_spec_circuit(pre_shanghai), Ok(()));
+        // and warming it up is rejected
+        let mut block = shanghai;
+        block.chain_spec = TEST_CHAIN_SPEC;
+        assert!(verify_chain_spec_circuit(block).is_err());
+    }
+
+    fn verify_chain_spec_circuit(block: Block) -> Result<(), ()> {
+        let k = block.get_evm_test_circuit_degree();
+        let circuit = ChainSpecCircuit(EvmCircuit::<Fr>::get_test_cicuit_from_block(block));
+        MockProver::<Fr>::run(k, &circuit, circuit.0.instance())
+            .map_err(|_| ())?
+            .verify_par()
+            .map_err(|_| ())
+    }
 }
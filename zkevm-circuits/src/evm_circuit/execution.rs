@@ -6,11 +6,11 @@ use super::{
         RW_TABLE_LOOKUPS, SHA256_TABLE_LOOKUPS, SIG_TABLE_LOOKUPS, TX_TABLE_LOOKUPS,
     },
     util::{instrumentation::Instrument, CachedRegion, CellManager, Inverter, StoredExpression},
-    EvmCircuitExports,
+    EvmCircuitConfigParams, EvmCircuitExports,
 };
 use crate::{
     evm_circuit::{
-        param::{EVM_LOOKUP_COLS, MAX_STEP_HEIGHT, N_PHASE2_COLUMNS, STEP_WIDTH},
+        param::{EVM_LOOKUP_COLS, MAX_STEP_HEIGHT, N_PHASE2_COLUMNS, STACK_CAPACITY, STEP_WIDTH},
         step::{ExecutionState, Step},
         table::Table,
         util::{
@@ -21,7 +21,7 @@ use crate::{
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
-    table::{LookupTable, RwTableTag, TxReceiptFieldTag},
+    table::{LookupTable, RwTableTag, TxReceiptFieldTag, U8Table},
     util::{query_expression, Challenges, Expr, Field},
 };
 use bus_mapping::util::read_env_var;
@@ -30,8 +30,8 @@ use gadgets::util::not;
 use halo2_proofs::{
     circuit::{Layouter, Region, Value},
     plonk::{
-        Advice, Assigned, Column, ConstraintSystem, Error, Expression, FirstPhase, Fixed, Selector,
-        VirtualCells,
+        Advice, Assigned, Column, ConstraintSystem, Error, Expression, FirstPhase, Fixed, Instance,
+        Selector, VirtualCells,
     },
     poly::Rotation,
 };
@@ -148,9 +148,7 @@ use address::AddressGadget;
 use balance::BalanceGadget;
 use begin_tx::BeginTxGadget;
 use bitwise::BitwiseGadget;
-#[cfg(feature = "scroll")]
-use block_ctx::DifficultyGadget;
-use block_ctx::{BlockCtxU160Gadget, BlockCtxU256Gadget, BlockCtxU64Gadget};
+use block_ctx::{BlockCtxU160Gadget, BlockCtxU256Gadget, BlockCtxU64Gadget, DifficultyGadget};
 use blockhash::BlockHashGadget;
 use byte::ByteGadget;
 use calldatacopy::CallDataCopyGadget;
@@ -213,7 +211,7 @@ use pc::PcGadget;
 use pop::PopGadget;
 use precompiles::{
     BasePrecompileGadget, EcAddGadget, EcMulGadget, EcPairingGadget, EcrecoverGadget,
-    IdentityGadget, ModExpGadget, SHA256Gadget,
+    IdentityGadget, ModExpGadget, Ripemd160Gadget, SHA256Gadget,
 };
 use push::PushGadget;
 use return_revert::ReturnRevertGadget;
@@ -273,6 +271,7 @@ pub(crate) struct ExecutionConfig<F> {
     pub(crate) height_map: HashMap<ExecutionState, usize>,
     stored_expressions_map: HashMap<ExecutionState, Vec<StoredExpression<F>>>,
     instrument: Instrument,
+    params: EvmCircuitConfigParams,
     // internal state gadgets
     begin_tx_gadget: Box<BeginTxGadget<F>>,
     end_block_gadget: Box<EndBlockGadget<F>>,
@@ -330,6 +329,7 @@ pub(crate) struct ExecutionConfig<F> {
     create2_gadget: Box<CreateGadget<F, true, { ExecutionState::CREATE2 }>>,
     #[cfg(not(feature = "scroll"))]
     selfdestruct_gadget: Box<DummyGadget<F, 1, 0, { ExecutionState::SELFDESTRUCT }>>,
+    unsupported_gadget: Box<DummyGadget<F, 0, 0, { ExecutionState::UNSUPPORTED }>>,
     signed_comparator_gadget: Box<SignedComparatorGadget<F>>,
     signextend_gadget: Box<SignextendGadget<F>>,
     sload_gadget: Box<SloadGadget<F>>,
@@ -342,7 +342,6 @@ pub(crate) struct ExecutionConfig<F> {
     block_ctx_u64_gadget: Box<BlockCtxU64Gadget<F>>,
     block_ctx_u160_gadget: Box<BlockCtxU160Gadget<F>>,
     block_ctx_u256_gadget: Box<BlockCtxU256Gadget<F>>,
-    #[cfg(feature = "scroll")]
     difficulty_gadget: Box<DifficultyGadget<F>>,
     // error gadgets
     error_oog_call: Box<ErrorOOGCallGadget<F>>,
@@ -371,7 +370,7 @@ pub(crate) struct ExecutionConfig<F> {
     // precompile calls
     precompile_ecrecover_gadget: Box<EcrecoverGadget<F>>,
     precompile_sha2_gadget: Box<SHA256Gadget<F>>,
-    precompile_ripemd_gadget: Box<BasePrecompileGadget<F, { ExecutionState::PrecompileRipemd160 }>>,
+    precompile_ripemd_gadget: Box<Ripemd160Gadget<F>>,
     precompile_identity_gadget: Box<IdentityGadget<F>>,
     precompile_modexp_gadget: Box<ModExpGadget<F>>,
     precompile_bn128add_gadget: Box<EcAddGadget<F>>,
@@ -387,7 +386,7 @@ impl<F: Field> ExecutionConfig<F> {
         meta: &mut ConstraintSystem<F>,
         challenges: Challenges<Expression<F>>,
         fixed_table: &dyn LookupTable<F>,
-        byte_table: &dyn LookupTable<F>,
+        u8_table: U8Table,
         tx_table: &dyn LookupTable<F>,
         rw_table: &dyn LookupTable<F>,
         bytecode_table: &dyn LookupTable<F>,
@@ -400,7 +399,14 @@ impl<F: Field> ExecutionConfig<F> {
         modexp_table: &dyn LookupTable<F>,
         ecc_table: &dyn LookupTable<F>,
         pow_of_rand_table: &dyn LookupTable<F>,
+        unproven_steps: Column<Instance>,
+        params: EvmCircuitConfigParams,
     ) -> Self {
+        assert!(
+            params.chain_spec.stack_limit <= STACK_CAPACITY,
+            "the stack limit {} exceeds the stack capacity of the circuit",
+            params.chain_spec.stack_limit
+        );
         let mut instrument = Instrument::default();
         let q_usable = meta.fixed_column();
         let q_step = meta.advice_column();
@@ -529,6 +535,18 @@ impl<F: Field> ExecutionConfig<F> {
             // On each usable row
             cb.gate(q_usable)
         });
+        // The public flag is the only non-zero cell of the instance column, so
+        // looking up 1 at the steps handled by a pass-through gadget forces the
+        // flag to be set whenever the block contains one.
+        meta.lookup_any("unproven steps flag", |meta| {
+            let q_usable = meta.query_fixed(q_usable, Rotation::cur());
+            let q_step = meta.query_advice(q_step, Rotation::cur());
+            let is_unproven = step_curr.execution_state_selector(
+                ExecutionState::iter().filter(ExecutionState::is_unproven),
+            );
+            let flag = meta.query_instance(unproven_steps, Rotation::cur());
+            vec![(q_usable * q_step * is_unproven, flag)]
+        });
 
         let mut stored_expressions_map = HashMap::new();
 
@@ -549,6 +567,7 @@ impl<F: Field> ExecutionConfig<F> {
                         q_step_first,
                         q_step_last,
                         &challenges,
+                        params,
                         &step_curr,
                         &mut height_map,
                         &mut stored_expressions_map,
@@ -626,6 +645,7 @@ impl<F: Field> ExecutionConfig<F> {
             create2_gadget: configure_gadget!(),
             #[cfg(not(feature = "scroll"))]
             selfdestruct_gadget: configure_gadget!(),
+            unsupported_gadget: configure_gadget!(),
             shl_shr_gadget: configure_gadget!(),
             signed_comparator_gadget: configure_gadget!(),
             signextend_gadget: configure_gadget!(),
@@ -638,7 +658,6 @@ impl<F: Field> ExecutionConfig<F> {
             block_ctx_u64_gadget: configure_gadget!(),
             block_ctx_u160_gadget: configure_gadget!(),
             block_ctx_u256_gadget: configure_gadget!(),
-            #[cfg(feature = "scroll")]
             difficulty_gadget: configure_gadget!(),
             // error gadgets
             error_oog_constant: configure_gadget!(),
@@ -678,12 +697,13 @@ impl<F: Field> ExecutionConfig<F> {
             height_map,
             stored_expressions_map,
             instrument,
+            params,
         };
 
         Self::configure_lookup(
             meta,
             fixed_table,
-            byte_table,
+            u8_table,
             tx_table,
             rw_table,
             bytecode_table,
@@ -716,6 +736,7 @@ impl<F: Field> ExecutionConfig<F> {
         q_step_first: Selector,
         q_step_last: Selector,
         challenges: &Challenges<Expression<F>>,
+        params: EvmCircuitConfigParams,
         step_curr: &Step<F>,
         height_map: &mut HashMap<ExecutionState, usize>,
         stored_expressions_map: &mut HashMap<ExecutionState, Vec<StoredExpression<F>>>,
@@ -729,6 +750,7 @@ impl<F: Field> ExecutionConfig<F> {
                 step_curr.clone(),
                 dummy_step_next,
                 challenges,
+                params,
                 G::EXECUTION_STATE,
             );
             cb.annotation(G::NAME, |cb| G::configure(cb));
@@ -742,6 +764,7 @@ impl<F: Field> ExecutionConfig<F> {
             step_curr.clone(),
             step_next.clone(),
             challenges,
+            params,
             G::EXECUTION_STATE,
         );
 
@@ -952,7 +975,7 @@ impl<F: Field> ExecutionConfig<F> {
     fn configure_lookup(
         meta: &mut ConstraintSystem<F>,
         fixed_table: &dyn LookupTable<F>,
-        byte_table: &dyn LookupTable<F>,
+        u8_table: U8Table,
         tx_table: &dyn LookupTable<F>,
         rw_table: &dyn LookupTable<F>,
         bytecode_table: &dyn LookupTable<F>,
@@ -997,10 +1020,7 @@ impl<F: Field> ExecutionConfig<F> {
         }
         for column in cell_manager.columns().iter() {
             if let CellType::LookupByte = column.cell_type {
-                meta.lookup_any("Byte lookup", |meta| {
-                    let byte_table_expression = byte_table.table_exprs(meta)[0].clone();
-                    vec![(column.expr(), byte_table_expression)]
-                });
+                meta.lookup("Byte lookup", |_| vec![(column.expr(), u8_table.into())]);
             }
         }
     }
@@ -1083,6 +1103,31 @@ impl<F: Field> ExecutionConfig<F> {
         // If the height is not 1, padding to fixed height will be impossible
         debug_assert_eq!(ExecutionState::Padding.get_step_height(), 1);
 
+        if block.l1_fee_schema != self.params.l1_fee_schema {
+            log::error!(
+                "the witness has the l1 fee schema {:?}, the circuit is configured for {:?}",
+                block.l1_fee_schema,
+                self.params.l1_fee_schema
+            );
+            return Err(Error::Synthesis);
+        }
+        if block.chain_spec != self.params.chain_spec {
+            log::error!(
+                "the witness has the chain spec {:?}, the circuit is configured for {:?}",
+                block.chain_spec,
+                self.params.chain_spec
+            );
+            return Err(Error::Synthesis);
+        }
+        if block.circuits_params.max_ec_ops.ec_pairing_pairs != self.params.max_ec_pairing_pairs {
+            log::error!(
+                "the witness has {} pairs per ecPairing op, the circuit is configured for {}",
+                block.circuits_params.max_ec_ops.ec_pairing_pairs,
+                self.params.max_ec_pairing_pairs
+            );
+            return Err(Error::Synthesis);
+        }
+
         let inverter = Inverter::new(MAX_STEP_HEIGHT as u64);
         let evm_rows = block.circuits_params.max_evm_rows;
         // 0 means "dynamic height". If fixed height is used in unittests, CI will be quite slow.
@@ -1621,7 +1666,6 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::BLOCKCTXU64 => assign_exec_step!(self.block_ctx_u64_gadget),
             ExecutionState::BLOCKCTXU160 => assign_exec_step!(self.block_ctx_u160_gadget),
             ExecutionState::BLOCKCTXU256 => assign_exec_step!(self.block_ctx_u256_gadget),
-            #[cfg(feature = "scroll")]
             ExecutionState::DIFFICULTY => assign_exec_step!(self.difficulty_gadget),
             ExecutionState::BLOCKHASH => assign_exec_step!(self.blockhash_gadget),
             ExecutionState::SELFBALANCE => assign_exec_step!(self.selfbalance_gadget),
@@ -1633,6 +1677,7 @@ impl<F: Field> ExecutionConfig<F> {
                 #[cfg(not(feature = "scroll"))]
                 assign_exec_step!(self.selfdestruct_gadget)
             }
+            ExecutionState::UNSUPPORTED => assign_exec_step!(self.unsupported_gadget),
             // end of dummy gadgets
             ExecutionState::SHA3 => assign_exec_step!(self.sha3_gadget),
             ExecutionState::SHL_SHR => assign_exec_step!(self.shl_shr_gadget),
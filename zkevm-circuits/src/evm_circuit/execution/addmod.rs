@@ -226,8 +226,8 @@ impl<F: Field> ExecutionGadget<F> for AddModGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::CircuitTestBuilder;
-    use eth_types::{bytecode, Word};
+    use crate::{evm_circuit::test::rand_word, test_util::CircuitTestBuilder};
+    use eth_types::{bytecode, Word, U256, U512};
     use mock::TestContext;
 
     #[cfg(feature = "enable-stack")]
@@ -271,6 +271,14 @@ mod test {
         test(a.into(), b.into(), c.into(), r.map(Word::from), true)
     }
 
+    /// Reference `a + b mod n` computed without wrapping at 2^256.
+    fn addmod(a: Word, b: Word, n: Word) -> Word {
+        if n.is_zero() {
+            return Word::zero();
+        }
+        U256::try_from((U512::from(a) + U512::from(b)) % U512::from(n)).unwrap()
+    }
+
     // TODO: re-enable when we have a way to check for errors
     // fn test_ko_u32(a: u32, b: u32, c: u32, r: Option<u32>) {
     //     test(a.into(), b.into(), c.into(), r.map(Word::from), false)
@@ -299,6 +307,37 @@ mod test {
         test(0.into(), 0.into(), Word::MAX, None, true);
     }
 
+    #[test]
+    fn addmod_limits_reference() {
+        // sums overflowing 2^256 are reduced without wrapping
+        assert_eq!(addmod(Word::MAX, 1.into(), Word::MAX - 1), 2.into());
+        assert_eq!(addmod(Word::MAX, Word::MAX, Word::MAX - 1), 2.into());
+        assert_eq!(addmod(Word::MAX, Word::MAX, 0.into()), 0.into());
+
+        for n in [0.into(), 1.into(), Word::MAX - 1, Word::MAX] {
+            for (a, b) in [
+                (Word::MAX, Word::MAX),
+                (Word::MAX - 1, Word::MAX),
+                (Word::MAX, 1.into()),
+                (Word::MAX, 0.into()),
+                (0.into(), 0.into()),
+            ] {
+                test(a, b, n, Some(addmod(a, b, n)), true);
+            }
+        }
+    }
+
+    #[test]
+    fn addmod_random() {
+        for _ in 0..4 {
+            let (a, b, n) = (rand_word(), rand_word(), rand_word());
+            test(a, b, n, Some(addmod(a, b, n)), true);
+        }
+        // a small modulus exercises a large quotient
+        let (a, b, n) = (rand_word(), rand_word(), Word::from(rand_word().low_u32()));
+        test(a, b, n, Some(addmod(a, b, n)), true);
+    }
+
     #[test]
     fn addmod_bad_r_on_nonzero_n() {
         test_ok_u32(7, 18, 10, Some(5));
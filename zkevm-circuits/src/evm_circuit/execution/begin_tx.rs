@@ -57,6 +57,7 @@ pub(crate) struct BeginTxGadget<F> {
     tx_is_create: Cell<F>,
     tx_value: Word<F>,
     tx_call_data_length: Cell<F>,
+    tx_call_data_tx_id: Cell<F>,
     is_call_data_empty: IsZeroGadget<F>,
     tx_call_data_word_length: ConstantDivisionGadget<F, N_BYTES_U64>,
     tx_call_data_gas_cost: Cell<F>,
@@ -93,8 +94,9 @@ pub(crate) struct BeginTxGadget<F> {
     is_callee_warm: Cell<F>,
     is_coinbase_warm: Cell<F>,
     are_precompile_warm: [Cell<F>; PRECOMPILE_COUNT],
-    // EIP-3651 (Warm COINBASE) for Shanghai
+    // EIP-3651 (Warm COINBASE), applied from Shanghai
     coinbase: Cell<F>,
+    is_before_shanghai: LtGadget<F, N_BYTES_U64>,
     tx_l1_fee: TxL1FeeGadget<F>,
     tx_l1_msg: TxL1MsgGadget<F>,
     tx_access_list: TxAccessListGadget<F>,
@@ -136,6 +138,8 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
 
         let tx_signed_length = cb.tx_context(tx_id.expr(), TxContextFieldTag::TxHashLength, None);
+        // The tx whose call data section in the tx table holds our call data.
+        let tx_call_data_tx_id = cb.tx_context(tx_id.expr(), TxContextFieldTag::CallDataTxId, None);
         let tx_access_list = TxAccessListGadget::construct(cb, tx_id.expr(), tx_type.expr());
         let is_call_data_empty = IsZeroGadget::construct(cb, tx_call_data_length.expr());
 
@@ -313,7 +317,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             None,
         ); // rwc_delta += 1
 
-        // Query coinbase address for Shanghai.
+        // Query coinbase address to warm it up from Shanghai (EIP-3651).
         let coinbase = cb.query_cell();
         cb.block_lookup(
             BlockContextFieldTag::Coinbase.expr(),
@@ -321,13 +325,19 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             coinbase.expr(),
         );
 
-        cb.account_access_list_write(
-            tx_id.expr(),
-            coinbase.expr(),
-            1.expr(),
-            is_coinbase_warm.expr(),
-            None,
-        ); // rwc_delta += 1
+        let shanghai_block = cb.params().chain_spec.shanghai_block;
+        let is_before_shanghai =
+            LtGadget::construct(cb, cb.curr.state.block_number.expr(), shanghai_block.expr());
+        let is_shanghai = not::expr(is_before_shanghai.expr());
+        cb.condition(is_shanghai.expr(), |cb| {
+            cb.account_access_list_write(
+                tx_id.expr(),
+                coinbase.expr(),
+                1.expr(),
+                is_coinbase_warm.expr(),
+                None,
+            );
+        }); // rwc_delta += is_shanghai
 
         let account_code_hash = cb.query_cell_phase2();
         let account_code_hash_is_empty =
@@ -434,7 +444,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             });
             cb.condition(not::expr(is_call_data_empty.expr()), |cb| {
                 cb.copy_table_lookup(
-                    tx_id.expr(),                    // src_id
+                    tx_call_data_tx_id.expr(),       // src_id
                     CopyDataType::TxCalldata.expr(), // src_tag
                     cb.curr.state.code_hash.expr(),  // dst_id
                     CopyDataType::Bytecode.expr(),   // dst_tag
@@ -489,7 +499,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write TxAccessListAccount (Precompile) x PRECOMPILE_COUNT
                 //   - Write TxAccessListAccount (Caller)
                 //   - Write TxAccessListAccount (Callee)
-                //   - Write TxAccessListAccount (Coinbase) only for Shanghai
+                //   - Write TxAccessListAccount (Coinbase) from Shanghai (EIP-3651)
                 //   - Read Account CodeHash
                 //   - a TransferWithGasFeeGadget
                 //   - Write Account (Callee) Nonce (Reversible)
@@ -507,7 +517,8 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write CallContext IsCreate
                 //   - Write CallContext CodeHash
                 rw_counter: Delta(
-                    23.expr()
+                    22.expr()
+                        + is_shanghai.expr()
                         + l1_rw_delta.expr()
                         + transfer_with_gas_fee.rw_delta()
                         + tx_access_list.rw_delta_expr()
@@ -598,7 +609,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 let precompile_input_bytes_rlc = cb.query_cell_phase2();
                 cb.condition(not::expr(is_call_data_empty.expr()), |cb| {
                     cb.copy_table_lookup(
-                        tx_id.expr(), //cb.curr.state.call_id.expr(),
+                        tx_call_data_tx_id.expr(),
                         CopyDataType::TxCalldata.expr(),
                         call_id.expr(),
                         CopyDataType::RlcAcc.expr(),
@@ -621,7 +632,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write TxAccessListAccount (Precompile) x PRECOMPILE_COUNT
                     //   - Write TxAccessListAccount (Caller)
                     //   - Write TxAccessListAccount (Callee)
-                    //   - Write TxAccessListAccount (Coinbase) only for Shanghai
+                    //   - Write TxAccessListAccount (Coinbase) from Shanghai (EIP-3651)
                     //   - Read Account CodeHash
                     //   - a TxL1FeeGadget
                     //   - a TransferWithGasFeeGadget
@@ -642,7 +653,8 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write CallContext IsCreate
                     //   - Write CallContext CodeHash
                     rw_counter: Delta(
-                        24.expr()
+                        23.expr()
+                            + is_shanghai.expr()
                             + l1_rw_delta.expr()
                             + transfer_with_gas_fee.rw_delta()
                             + tx_access_list.rw_delta_expr()
@@ -698,18 +710,20 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write TxAccessListAccount (Precompile) x PRECOMPILE_COUNT
                     //   - Write TxAccessListAccount (Caller)
                     //   - Write TxAccessListAccount (Callee)
-                    //   - Write TxAccessListAccount (Coinbase) only for Shanghai
+                    //   - Write TxAccessListAccount (Coinbase) from Shanghai (EIP-3651)
                     //   - Read Account CodeHash
                     //   - a TxL1FeeGadget
                     //   - a TransferWithGasFeeGadget
                     rw_counter: Delta(
-                        9.expr()
+                        8.expr()
+                            + is_shanghai.expr()
                             + l1_rw_delta.expr()
                             + transfer_with_gas_fee.rw_delta()
                             + tx_access_list.rw_delta_expr()
                             + PRECOMPILE_COUNT.expr(),
                     ),
                     call_id: To(call_id.expr()),
+                    log_id: To(0.expr()),
                     end_tx: To(1.expr()),
                     ..StepStateTransition::any()
                 });
@@ -759,7 +773,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write TxAccessListAccount (Precompile) x PRECOMPILE_COUNT
                     //   - Write TxAccessListAccount (Caller)
                     //   - Write TxAccessListAccount (Callee)
-                    //   - Write TxAccessListAccount (Coinbase) only for Shanghai
+                    //   - Write TxAccessListAccount (Coinbase) from Shanghai (EIP-3651)
                     //   - Read Account CodeHash
                     //   - a TransferWithGasFeeGadget
                     //   - Write CallContext Depth
@@ -776,7 +790,8 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write CallContext IsCreate
                     //   - Write CallContext CodeHash
                     rw_counter: Delta(
-                        22.expr()
+                        21.expr()
+                            + is_shanghai.expr()
                             + l1_rw_delta.expr()
                             + transfer_with_gas_fee.rw_delta()
                             + tx_access_list.rw_delta_expr()
@@ -811,6 +826,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx_is_create,
             tx_value,
             tx_call_data_length,
+            tx_call_data_tx_id,
             is_call_data_empty,
             tx_call_data_word_length,
             tx_call_data_gas_cost,
@@ -841,6 +857,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             is_coinbase_warm,
             are_precompile_warm,
             coinbase,
+            is_before_shanghai,
             tx_l1_fee,
             tx_l1_msg,
             tx_access_list,
@@ -876,7 +893,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         // Precompiles
         // caller addr
         // callee addr
-        // coinbase
+        // coinbase, from Shanghai
 
         let mut rws = StepRws::new(block, step);
         let rw = rws.next();
@@ -949,7 +966,8 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         let is_caller_warm = rws.next().tx_access_list_value_pair().1;
         let is_callee_warm = rws.next().tx_access_list_value_pair().1;
 
-        let is_coinbase_warm = rws.next().tx_access_list_value_pair().1;
+        let is_shanghai = block.chain_spec.is_shanghai(tx.block_number);
+        let is_coinbase_warm = is_shanghai && rws.next().tx_access_list_value_pair().1;
 
         let account_code_hash = rws.next().account_codehash_pair().1;
         let transfer_assign_result = self.transfer_with_gas_fee.assign_from_rws(
@@ -1091,6 +1109,11 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             offset,
             Value::known(F::from(tx.call_data_length as u64)),
         )?;
+        self.tx_call_data_tx_id.assign(
+            region,
+            offset,
+            Value::known(F::from(tx.call_data_tx_id() as u64)),
+        )?;
         self.is_call_data_empty
             .assign(region, offset, F::from(tx.call_data_length as u64))?;
         self.tx_call_data_word_length
@@ -1217,6 +1240,12 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     .expect("unexpected Address -> Scalar conversion failure"),
             ),
         )?;
+        self.is_before_shanghai.assign(
+            region,
+            offset,
+            F::from(tx.block_number),
+            F::from(block.chain_spec.shanghai_block),
+        )?;
 
         let (tx_l1_fee, tx_l2_fee) = if tx.tx_type.is_l1_msg() {
             log::trace!("tx is l1msg and l1 fee is 0");
@@ -1277,10 +1306,11 @@ mod test {
     use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
     use bus_mapping::evm::OpcodeId;
     use eth_types::{
-        self, address, bytecode, evm_types::GasCost, word, Address, Bytecode, Hash, Word, U256,
+        self, address, bytecode, evm_types::GasCost, word, Address, Bytecode, Hash, ToWord, Word,
+        U256,
     };
     use ethers_core::{types::Bytes, utils::get_contract_address};
-    use mock::{eth, gwei, MockTransaction, TestContext, MOCK_ACCOUNTS};
+    use mock::{eth, gwei, MockTransaction, TestContext, MOCK_ACCOUNTS, MOCK_COINBASE};
 
     fn gas(call_data: &[u8]) -> Word {
         Word::from(
@@ -1370,6 +1400,28 @@ mod test {
         );
     }
 
+    #[test]
+    fn begin_tx_coinbase_warm() {
+        // The coinbase is added to the access list at the beginning of the tx
+        // (EIP-3651), so the BALANCE below costs a warm access.
+        let code = bytecode! {
+            PUSH20(MOCK_COINBASE.to_word())
+            BALANCE
+            POP
+            STOP
+        };
+        let ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap();
+
+        let balance_step = ctx.geth_traces[0]
+            .struct_logs
+            .iter()
+            .find(|step| step.op == OpcodeId::BALANCE)
+            .expect("BALANCE step");
+        assert_eq!(balance_step.gas_cost, GasCost::WARM_ACCESS);
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     #[test]
     fn begin_tx_large_nonce() {
         // This test checks that the rw table assignment and evm circuit are consistent
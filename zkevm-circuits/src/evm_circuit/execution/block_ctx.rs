@@ -17,6 +17,9 @@ use bus_mapping::evm::OpcodeId;
 use eth_types::ToLittleEndian;
 use halo2_proofs::plonk::Error;
 
+#[cfg(not(feature = "scroll"))]
+use crate::evm_circuit::util::{math_gadget::LtGadget, not, Word};
+
 #[derive(Clone, Debug)]
 pub(crate) struct BlockCtxGadget<F, const N_BYTES: usize> {
     same_context: SameContextGadget<F>,
@@ -230,6 +233,89 @@ impl<F: Field> ExecutionGadget<F> for DifficultyGadget<F> {
     }
 }
 
+/// DIFFICULTY returns the difficulty of the header before the Merge, and
+/// prevRandao after it (EIP-4399). The Merge block is the one of the chain
+/// spec the circuit is configured with.
+#[cfg(not(feature = "scroll"))]
+#[derive(Clone, Debug)]
+pub(crate) struct DifficultyGadget<F> {
+    same_context: SameContextGadget<F>,
+    value: Word<F>,
+    is_before_merge: LtGadget<F, N_BYTES_U64>,
+}
+
+#[cfg(not(feature = "scroll"))]
+impl<F: Field> ExecutionGadget<F> for DifficultyGadget<F> {
+    const NAME: &'static str = "DIFFICULTY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::DIFFICULTY;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let value = cb.query_word_rlc();
+        cb.stack_push(value.expr());
+
+        let merge_block = cb.params().chain_spec.merge_block;
+        let is_before_merge =
+            LtGadget::construct(cb, cb.curr.state.block_number.expr(), merge_block.expr());
+        // DIFFICULTY returns the difficulty before the Merge, prevRandao after it
+        cb.condition(is_before_merge.expr(), |cb| {
+            cb.block_lookup(
+                BlockContextFieldTag::Difficulty.expr(),
+                cb.curr.state.block_number.expr(),
+                value.expr(),
+            );
+        });
+        cb.condition(not::expr(is_before_merge.expr()), |cb| {
+            cb.block_lookup(
+                BlockContextFieldTag::PrevRandao.expr(),
+                cb.curr.state.block_number.expr(),
+                value.expr(),
+            );
+        });
+
+        let opcode = cb.query_cell();
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(1.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta((-1).expr()),
+            gas_left: Delta(-OpcodeId::DIFFICULTY.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            value,
+            is_before_merge,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let value = block.rws[step.rw_indices[0]].stack_value();
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+
+        self.is_before_merge.assign(
+            region,
+            offset,
+            F::from(step.block_num),
+            F::from(block.chain_spec.merge_block),
+        )?;
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::test_util::CircuitTestBuilder;
@@ -274,4 +360,28 @@ mod test {
         };
         test_ok(bytecode);
     }
+
+    #[cfg(not(feature = "scroll"))]
+    #[test]
+    fn difficulty_gadget_prev_randao_test() {
+        use eth_types::{Word, H256};
+        use mock::test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0};
+
+        let bytecode = bytecode! {
+            DIFFICULTY
+            STOP
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            tx_from_1_to_0,
+            |block, _tx| {
+                block
+                    .difficulty(Word::zero())
+                    .mix_hash(H256::repeat_byte(0xab))
+            },
+        )
+        .unwrap();
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
 }
@@ -17,7 +17,7 @@ use crate::{
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
-    table::CallContextFieldTag,
+    table::{CallContextFieldTag, TxContextFieldTag},
     util::{Expr, Field},
 };
 use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
@@ -30,6 +30,8 @@ pub(crate) struct CallDataCopyGadget<F> {
     same_context: SameContextGadget<F>,
     memory_address: MemoryAddressGadget<F>,
     data_offset: WordByteCapGadget<F, N_BYTES_U64>,
+    /// Transaction ID, only used in the root call.
+    tx_id: Cell<F>,
     src_id: Cell<F>,
     call_data_length: Cell<F>,
     call_data_offset: Cell<F>, // Only used in the internal call
@@ -46,6 +48,7 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
 
+        let tx_id = cb.query_cell();
         let src_id = cb.query_cell();
         let call_data_length = cb.query_cell();
         let call_data_offset = cb.query_cell();
@@ -62,7 +65,15 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
         // Lookup the calldata_length and caller_address in Tx context table or
         // Call context table
         cb.condition(cb.curr.state.is_root.expr(), |cb| {
-            cb.call_context_lookup(false.expr(), None, CallContextFieldTag::TxId, src_id.expr());
+            cb.call_context_lookup(false.expr(), None, CallContextFieldTag::TxId, tx_id.expr());
+            // Identical call data is stored once per chunk, under the first tx
+            // carrying it.
+            cb.tx_context_lookup(
+                tx_id.expr(),
+                TxContextFieldTag::CallDataTxId,
+                None,
+                src_id.expr(),
+            );
             cb.call_context_lookup(
                 false.expr(),
                 None,
@@ -160,6 +171,7 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
             same_context,
             memory_address,
             data_offset,
+            tx_id,
             src_id,
             call_data_length,
             call_data_offset,
@@ -186,7 +198,13 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
         let memory_address = self
             .memory_address
             .assign(region, offset, memory_offset, length)?;
-        let src_id = if call.is_root { tx.id } else { call.caller_id };
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        let src_id = if call.is_root {
+            tx.call_data_tx_id()
+        } else {
+            call.caller_id
+        };
         self.src_id.assign(
             region,
             offset,
@@ -372,6 +390,35 @@ mod test {
         test_internal_ok(0x40, 0x40, 0, 0x10.into(), Word::MAX);
     }
 
+    #[test]
+    fn calldatacopy_gadget_shared_calldata() {
+        let bytecode = bytecode! {
+            .op_calldatacopy(0x00, 0x10, 0x40)
+            STOP
+        };
+        let call_data = rand_bytes(0x50);
+
+        // the second tx reads the call data section of the first one
+        let ctx = TestContext::<2, 2>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .input(call_data.clone().into());
+                txs[1]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .input(call_data.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     #[test]
     fn calldatacopy_unaligned_data() {
         // calldatacopy_d0(cdc_0_1_2)_g0_v0
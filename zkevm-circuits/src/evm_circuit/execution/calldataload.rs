@@ -38,8 +38,11 @@ const OFFSET_RW_MEMORY_INDICES: usize = 4usize;
 pub(crate) struct CallDataLoadGadget<F> {
     /// Gadget to constrain the same context.
     same_context: SameContextGadget<F>,
-    /// Source of data, this is transaction ID for a root call and caller ID for
-    /// an internal call.
+    /// Transaction ID, only used for a root call.
+    tx_id: Cell<F>,
+    /// Source of data, this is the ID of the transaction holding the call data
+    /// section in the tx table for a root call and caller ID for an internal
+    /// call.
     src_id: Cell<F>,
     /// The size of the call's data (tx input for a root call or calldata length
     /// of an internal call).
@@ -71,6 +74,7 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
 
+        let tx_id = cb.query_cell();
         let src_id = cb.query_cell();
         let call_data_length = cb.query_cell();
         let call_data_offset = cb.query_cell();
@@ -85,10 +89,11 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
         cb.condition(
             and::expr([data_offset.not_overflow(), cb.curr.state.is_root.expr()]),
             |cb| {
-                cb.call_context_lookup(
-                    false.expr(),
+                cb.call_context_lookup(false.expr(), None, CallContextFieldTag::TxId, tx_id.expr());
+                cb.tx_context_lookup(
+                    tx_id.expr(),
+                    TxContextFieldTag::CallDataTxId,
                     None,
-                    CallContextFieldTag::TxId,
                     src_id.expr(),
                 );
                 cb.call_context_lookup(
@@ -246,6 +251,7 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
 
         Self {
             same_context,
+            tx_id,
             src_id,
             call_data_length,
             call_data_offset,
@@ -273,7 +279,7 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
         // Assign to the buffer reader gadget.
         let (src_id, call_data_offset, call_data_length) = if call.is_root {
             (
-                tx.id,
+                tx.call_data_tx_id(),
                 0,
                 if tx.is_create {
                     0u64
@@ -284,6 +290,8 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
         } else {
             (call.caller_id, call.call_data_offset, call.call_data_length)
         };
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
         self.src_id
             .assign(region, offset, Value::known(F::from(src_id as u64)))?;
         self.call_data_length
@@ -380,7 +388,8 @@ mod test {
     use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
     use eth_types::{bytecode, Bytecode, Word};
     use mock::{
-        eth, generate_mock_call_bytecode, MockCallBytecodeParams, TestContext, MOCK_ACCOUNTS,
+        eth, generate_mock_call_bytecode, test_ctx::helpers::account_0_code_account_1_no_code,
+        MockCallBytecodeParams, TestContext, MOCK_ACCOUNTS,
     };
 
     fn test_bytecode(offset: Word) -> eth_types::Bytecode {
@@ -454,6 +463,31 @@ mod test {
         test_internal_ok(0x1010, 0xff, Word::MAX);
     }
 
+    #[test]
+    fn calldataload_gadget_shared_calldata() {
+        let call_data = rand_bytes(0x40);
+
+        // both txs load from the call data section of the first tx
+        let ctx = TestContext::<2, 2>::new(
+            None,
+            account_0_code_account_1_no_code(test_bytecode(0x10.into())),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .input(call_data.clone().into());
+                txs[1]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .input(call_data.into());
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     fn initialization_bytecode() -> Bytecode {
         let memory_bytes = [0x60; 10];
         let memory_value = Word::from_big_endian(&memory_bytes);
@@ -5,7 +5,10 @@ use crate::{
         step::ExecutionState,
         util::{
             and,
-            common_gadget::{CommonCallGadget, TransferGadget, TransferGadgetInfo},
+            common_gadget::{
+                CallContextGadget, CallVariantGadget, CommonCallGadget, TransferGadget,
+                TransferGadgetInfo,
+            },
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
@@ -43,16 +46,11 @@ use std::cmp::min;
 
 pub(crate) struct CallOpGadget<F> {
     opcode: Cell<F>,
-    is_call: IsZeroGadget<F>,
-    is_callcode: IsZeroGadget<F>,
-    is_delegatecall: IsZeroGadget<F>,
-    is_staticcall: IsZeroGadget<F>,
+    variant: CallVariantGadget<F>,
     tx_id: Cell<F>,
     reversion_info: ReversionInfo<F>,
-    current_callee_address: Cell<F>,
+    caller_context: CallContextGadget<F, 3>,
     current_caller_address: Cell<F>,
-    is_static: Cell<F>,
-    depth: Cell<F>,
     call: CommonCallGadget<F, MemoryAddressGadget<F>, true>,
     current_value: Word<F>,
     is_warm: Cell<F>,
@@ -67,6 +65,8 @@ pub(crate) struct CallOpGadget<F> {
     // check if insufficient balance case
     is_insufficient_balance: LtWordGadget<F>,
     is_depth_ok: LtGadget<F, N_BYTES_U64>,
+    // maximum call depth of the chain spec of the circuit
+    max_call_depth: u64,
     one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
     capped_callee_gas_left: MinMaxGadget<F, N_BYTES_GAS>,
     // to handle precompile calls
@@ -93,12 +93,11 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
         cb.opcode_lookup(opcode.expr(), 1.expr());
-        let is_call = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALL.expr());
-        let is_callcode = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALLCODE.expr());
-        let is_delegatecall =
-            IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::DELEGATECALL.expr());
-        let is_staticcall =
-            IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::STATICCALL.expr());
+        let variant = CallVariantGadget::construct(cb, opcode.expr());
+        let is_call = variant.is_call();
+        let is_callcode = variant.is_callcode();
+        let is_delegatecall = variant.is_delegatecall();
+        let is_staticcall = variant.is_staticcall();
 
         // Use rw_counter of the step which triggers next call as its call_id.
         let callee_call_id = cb.curr.state.rw_counter.clone();
@@ -108,12 +107,16 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         // rwc_delta = 1
         let mut reversion_info = cb.reversion_info_read(None);
         // rwc_delta = 3
-        let [is_static, depth, current_callee_address] = [
-            CallContextFieldTag::IsStatic,
-            CallContextFieldTag::Depth,
-            CallContextFieldTag::CalleeAddress,
-        ]
-        .map(|field_tag| cb.call_context(None, field_tag));
+        let caller_context = CallContextGadget::construct(
+            cb,
+            None,
+            [
+                CallContextFieldTag::IsStatic,
+                CallContextFieldTag::Depth,
+                CallContextFieldTag::CalleeAddress,
+            ],
+        );
+        let [is_static, depth, current_callee_address] = caller_context.cells();
 
         // rwc_delta = 6
         let (current_caller_address, current_value) = cb.condition(is_delegatecall.expr(), |cb| {
@@ -124,13 +127,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         });
         // rwc_delta = 6 + is_delegatecall * 2
         let call_gadget: CommonCallGadget<F, MemoryAddressGadget<F>, true> =
-            CommonCallGadget::construct(
-                cb,
-                is_call.expr(),
-                is_callcode.expr(),
-                is_delegatecall.expr(),
-                is_staticcall.expr(),
-            );
+            CommonCallGadget::construct(cb, &variant);
         // rwc_delta = 6 + is_delegatecall * 2 + call_gadget.rw_delta()
         cb.condition(not::expr(is_call.expr() + is_callcode.expr()), |cb| {
             cb.require_zero(
@@ -180,12 +177,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             );
         });
 
-        cb.condition(is_call.expr() * call_gadget.has_value.clone(), |cb| {
-            cb.require_zero(
-                "CALL with value must not be in static call stack",
-                is_static.expr(),
-            );
-        });
+        call_gadget.require_no_value_in_static_call(cb, is_static.expr());
 
         let caller_balance_word = cb.query_word_rlc();
         cb.account_read(
@@ -197,8 +189,9 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         // callee_reversion_info.rw_delta()
         let is_insufficient_balance =
             LtWordGadget::construct(cb, &caller_balance_word, &call_gadget.value);
-        // depth < 1025
-        let is_depth_ok = LtGadget::construct(cb, depth.expr(), 1025.expr());
+        // depth <= max_call_depth
+        let max_call_depth = cb.params().chain_spec.max_call_depth as u64;
+        let is_depth_ok = LtGadget::construct(cb, depth.expr(), (max_call_depth + 1).expr());
 
         let is_precheck_ok = and::expr([
             is_depth_ok.expr(),
@@ -278,7 +271,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
 
         // Sum up and verify gas cost.
         // Only CALL opcode could invoke transfer to make empty account into non-empty.
-        let gas_cost = call_gadget.gas_cost_expr(is_warm_prev.expr(), is_call.expr());
+        let gas_cost = call_gadget.gas_cost_expr(is_warm_prev.expr());
         // Apply EIP 150
         let gas_available = cb.curr.state.gas_left.expr() - gas_cost.clone();
         let one_64th_gas = cb.annotation("one_64th_gas", |cb| {
@@ -408,7 +401,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                             cb.curr.state.call_id.expr(),
                             CopyDataType::Memory.expr(),
                             callee_call_id.expr(),
-                            CopyDataType::RlcAcc.expr(),
+                            CopyDataType::PrecompileInput.expr(),
                             call_gadget.cd_address.offset(),
                             call_gadget.cd_address.offset() + precompile_input_len.expr(),
                             0.expr(),
@@ -431,7 +424,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                         let precompile_output_bytes_rlc = cb.query_cell_phase2();
                         cb.copy_table_lookup(
                             callee_call_id.expr(),
-                            CopyDataType::RlcAcc.expr(),
+                            CopyDataType::PrecompileOutput.expr(),
                             callee_call_id.expr(),
                             CopyDataType::Memory.expr(),
                             0.expr(),
@@ -709,17 +702,12 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
 
         Self {
             opcode,
-            is_call,
-            is_callcode,
-            is_delegatecall,
-            is_staticcall,
+            variant,
             tx_id,
             reversion_info,
-            current_callee_address,
+            caller_context,
             current_caller_address,
             current_value,
-            is_static,
-            depth,
             call: call_gadget,
             is_warm,
             is_warm_prev,
@@ -731,6 +719,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             caller_balance_word,
             is_insufficient_balance,
             is_depth_ok,
+            max_call_depth,
             one_64th_gas,
             capped_callee_gas_left,
             // precompile related fields.
@@ -768,12 +757,17 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
 
         let tx_id = rws.next().call_context_value();
         rws.offset_add(2); // skip RwCounterEndOfReversion, IsPersistent
-        let is_static = rws.next().call_context_value();
-        let depth = rws.next().call_context_value();
-        let current_callee_address = rws.next().call_context_value();
+        let [_is_static, depth, _current_callee_address] = self
+            .caller_context
+            .assign_exec_step(region, offset, block, step)?;
+        rws.offset_add(3);
 
-        self.is_depth_ok
-            .assign(region, offset, F::from(depth.low_u64()), F::from(1025))?;
+        self.is_depth_ok.assign(
+            region,
+            offset,
+            F::from(depth.low_u64()),
+            F::from(self.max_call_depth + 1),
+        )?;
 
         // This offset is used to change the index offset of `step.rw_indices`.
         // Since both CALL and CALLCODE have an extra stack pop `value`, and
@@ -809,8 +803,8 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         self.is_insufficient_balance
             .assign(region, offset, caller_balance, value)?;
 
-        let is_precheck_ok =
-            depth.low_u64() < 1025 && (!(is_call || is_callcode) || caller_balance >= value);
+        let is_precheck_ok = depth.low_u64() <= self.max_call_depth
+            && (!(is_call || is_callcode) || caller_balance >= value);
 
         // only call opcode do transfer in sucessful case.
         if is_call && is_precheck_ok && !value.is_zero() {
@@ -843,26 +837,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
 
         self.opcode
             .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
-        self.is_call.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::CALL.as_u64()),
-        )?;
-        self.is_callcode.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::CALLCODE.as_u64()),
-        )?;
-        self.is_delegatecall.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::DELEGATECALL.as_u64()),
-        )?;
-        self.is_staticcall.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::STATICCALL.as_u64()),
-        )?;
+        self.variant.assign(region, offset, opcode)?;
         self.tx_id
             .assign(region, offset, Value::known(F::from(tx_id.low_u64())))?;
         self.reversion_info.assign(
@@ -871,15 +846,6 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             call.rw_counter_end_of_reversion,
             call.is_persistent,
         )?;
-        self.current_callee_address.assign(
-            region,
-            offset,
-            Value::known(
-                current_callee_address
-                    .to_scalar()
-                    .expect("unexpected Address -> Scalar conversion failure"),
-            ),
-        )?;
         self.current_caller_address.assign(
             region,
             offset,
@@ -891,10 +857,6 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         )?;
         self.current_value
             .assign(region, offset, Some(current_value.to_le_bytes()))?;
-        self.is_static
-            .assign(region, offset, Value::known(F::from(is_static.low_u64())))?;
-        self.depth
-            .assign(region, offset, Value::known(F::from(depth.low_u64())))?;
 
         let memory_expansion_gas_cost = self.call.assign(
             region,
@@ -921,12 +883,11 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             callee_is_persistent.low_u64() != 0,
         )?;
 
-        let has_value = !value.is_zero() && !is_delegatecall;
         let gas_cost = self.call.cal_gas_cost_for_assignment(
             memory_expansion_gas_cost,
             is_warm_prev,
-            is_call,
-            has_value,
+            opcode,
+            value,
             !callee_exists,
         )?;
         let gas_available = step.gas_left - gas_cost;
@@ -1157,6 +1118,7 @@ mod test {
     };
     use itertools::Itertools;
     use mock::{
+        call_tree::{CallNode, CallTreeBuilder},
         test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
         TestContext,
     };
@@ -1215,6 +1177,26 @@ mod test {
         }
     }
 
+    #[test]
+    fn callop_deep_call_tree() {
+        // a chain of calls 4 levels deep, next to a reverting subtree whose
+        // static call is reverted too
+        let tree = CallTreeBuilder::new(
+            CallNode::call()
+                .child(CallTreeBuilder::chain(4, || {
+                    CallNode::call().value(Word::from(1))
+                }))
+                .child(
+                    CallNode::callcode()
+                        .child(CallNode::delegatecall().child(CallNode::staticcall()))
+                        .reverts(),
+                ),
+        )
+        .build();
+        let ctx = tree.test_ctx::<9>().unwrap();
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     #[test]
     fn callop_simple() {
         let stacks = [
@@ -32,8 +32,10 @@ use eth_types::{
     ToBigEndian, ToLittleEndian, ToWord, H256, KECCAK_CODE_HASH_EMPTY, U256,
 };
 use ethers_core::utils::keccak256;
-use gadgets::util::{and, expr_from_bytes};
-use gadgets::ToScalar;
+use gadgets::{
+    util::{and, expr_from_bytes},
+    ToScalar,
+};
 use halo2_proofs::{circuit::Value, plonk::Error};
 use log::trace;
 use std::iter::once;
@@ -68,6 +70,8 @@ pub(crate) struct CreateGadget<F, const IS_CREATE2: bool, const S: ExecutionStat
     keccak_output: Word<F>,
 
     is_depth_in_range: LtGadget<F, N_BYTES_U64>,
+    // maximum call depth of the chain spec of the circuit
+    max_call_depth: u64,
     is_insufficient_balance: LtWordGadget<F>,
     is_nonce_in_range: LtGadget<F, N_BYTES_U64>,
     not_address_collision: IsZeroGadget<F>,
@@ -180,6 +184,15 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                 cb.empty_code_hash_rlc(),
             );
         });
+        if IS_CREATE2 {
+            // the init code hash hashed into the contract address is the one looked up in the
+            // keccak table.
+            cb.require_equal(
+                "keccak hash of init code is used for CREATE2 address",
+                keccak_code_hash.expr(),
+                create.keccak_code_hash_word_rlc(cb),
+            );
+        }
 
         cb.call_context_lookup(
             0.expr(),
@@ -203,7 +216,8 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
         );
 
         // Pre-check: call depth, user's nonce and user's balance
-        let is_depth_in_range = LtGadget::construct(cb, depth.expr(), 1025.expr());
+        let max_call_depth = cb.params().chain_spec.max_call_depth as u64;
+        let is_depth_in_range = LtGadget::construct(cb, depth.expr(), (max_call_depth + 1).expr());
         let is_insufficient_balance = LtWordGadget::construct(cb, &caller_balance, &value);
         let is_nonce_in_range = LtGadget::construct(cb, caller_nonce.expr(), u64::MAX.expr());
 
@@ -347,7 +361,20 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                     init_code_rlc.expr(),
                     copy_rw_increase.expr(),
                 );
+                cb.keccak_table_lookup(
+                    init_code_rlc.expr(),
+                    init_code.length(),
+                    keccak_code_hash.expr(),
+                );
             });
+
+            // keccak table lookup to verify contract address, which is also needed when the
+            // address collides with an existing account.
+            cb.keccak_table_lookup(
+                create.input_rlc(cb),
+                create.input_length(),
+                keccak_output.expr(),
+            );
         });
 
         let mut callee_reversion_info = cb.reversion_info_write(Some(callee_call_id.expr()));
@@ -383,21 +410,6 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
         let transfer = cb.condition(
             and::expr([is_precheck_ok.clone(), not_address_collision.expr()]),
             |cb| {
-                cb.condition(init_code.has_length(), |cb| {
-                    cb.keccak_table_lookup(
-                        init_code_rlc.expr(),
-                        init_code.length(),
-                        keccak_code_hash.expr(),
-                    );
-                });
-
-                // keccak table lookup to verify contract address.
-                cb.keccak_table_lookup(
-                    create.input_rlc(cb),
-                    create.input_length(),
-                    keccak_output.expr(),
-                );
-
                 // propagate is_persistent
                 cb.require_equal(
                     "callee_is_persistent == is_persistent ⋅ is_success",
@@ -555,6 +567,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             init_code_rlc,
             keccak_output,
             is_depth_in_range,
+            max_call_depth,
             is_insufficient_balance,
             is_nonce_in_range,
             not_address_collision,
@@ -614,8 +627,9 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                     // Pre-check: call depth, user's nonce and user's balance
         let caller_balance = rws.next().account_balance_pair().1;
         let caller_nonce = rws.next().account_nonce_pair().1.low_u64();
-        let is_precheck_ok =
-            call.depth < 1025 && caller_balance >= value && caller_nonce < u64::MAX;
+        let is_precheck_ok = call.depth as u64 <= self.max_call_depth
+            && caller_balance >= value
+            && caller_nonce < u64::MAX;
 
         self.caller_balance
             .assign(region, offset, Some(caller_balance.to_le_bytes()))?;
@@ -822,8 +836,12 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
 
         self.is_insufficient_balance
             .assign(region, offset, caller_balance, value)?;
-        self.is_depth_in_range
-            .assign(region, offset, F::from(call.depth as u64), F::from(1025))?;
+        self.is_depth_in_range.assign(
+            region,
+            offset,
+            F::from(call.depth as u64),
+            F::from(self.max_call_depth + 1),
+        )?;
         self.is_nonce_in_range
             .assign(region, offset, F::from(caller_nonce), F::from(u64::MAX))?;
 
@@ -838,6 +856,7 @@ mod test {
     use eth_types::{
         address, bytecode, evm_types::OpcodeId, geth_types::Account, word, Address, Bytecode, Word,
     };
+    use ethers_core::utils::get_contract_address;
     use itertools::Itertools;
     use mock::{eth, TestContext, MOCK_ACCOUNTS};
     use std::sync::LazyLock;
@@ -1065,6 +1084,43 @@ mod test {
         run_test_circuits(test_context(caller));
     }
 
+    #[test]
+    fn test_create_address_collision_with_existing_account() {
+        let caller = Account {
+            address: *CALLER_ADDRESS,
+            code: creator_bytecode(initialization_bytecode(true), 23414.into(), false, true).into(),
+            nonce: Word::one(),
+            balance: eth(10),
+            ..Default::default()
+        };
+        // the account at the address derived from RLP(caller, nonce) already has a nonce.
+        let existing = Account {
+            address: get_contract_address(*CALLER_ADDRESS, Word::one()),
+            nonce: Word::one(),
+            balance: eth(1),
+            ..Default::default()
+        };
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(eth(10));
+                accs[1].account(&caller);
+                accs[2].account(&existing);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(word!("0x2386F26FC10000"));
+            },
+            |block, _| block,
+        )
+        .unwrap();
+        run_test_circuits(ctx);
+    }
+
     #[ignore]
     #[test]
     fn test_create_2tx_at_same_address() {
@@ -37,6 +37,7 @@ pub(crate) struct EndBlockGadget<F> {
     total_txs_is_max_txs: IsEqualGadget<F>,
     is_empty_block: IsZeroGadget<F>,
     max_rws: Cell<F>,
+    is_full_rw_table: IsEqualGadget<F>,
     max_txs: Cell<F>,
     phase2_withdraw_root: Cell<F>,
     phase2_withdraw_root_prev: Cell<F>,
@@ -55,6 +56,7 @@ impl<F: Clone> Clone for EndBlockGadget<F> {
             total_txs_is_max_txs: self.total_txs_is_max_txs.clone(),
             is_empty_block: self.is_empty_block.clone(),
             max_rws: self.max_rws.clone(),
+            is_full_rw_table: self.is_full_rw_table.clone(),
             max_txs: self.max_txs.clone(),
             phase2_withdraw_root: self.phase2_withdraw_root.clone(),
             phase2_withdraw_root_prev: self.phase2_withdraw_root_prev.clone(),
@@ -67,10 +69,12 @@ impl<F: Clone> Clone for EndBlockGadget<F> {
 The goal of EndBlockGadget is to:
     0. expose withdraw root. Then it can be copied into pi circuit.
     1. constrain rws of evm circuit is same to rws of state circuit.
-        We use 2 StartOp rw lookup to do this.
+        We use a StartOp and 2 PaddingOp rw lookups to do this.
     2. constrain all txs inside tx circuit are processed inside evm circuit.
         (We don't need to constrain txs in evm circuit are not in tx circuit,
         since there are tx lookups)
+    3. constrain the number of processed txs equals the cumulative number of
+        txs of the last block in the block table, which is exposed in pi circuit.
 To achieve the above goal:
     We need to pass "rwc" and "call_id" all the way to EndBlock.
     Then "rwc" can be used for goal1.
@@ -192,6 +196,16 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
             cb.call_context_lookup(0.expr(), None, CallContextFieldTag::TxId, total_txs.expr());
         });
 
+        // 1c. total_txs matches the cumulative number of txs up until the last block, which is
+        // exposed in the pi circuit. This prevents dropping txs from the tail of the last block.
+        // `cb.curr.state.block_number` is the number of the last block since EndInnerBlock ->
+        // Padding -> EndBlock keeps it unchanged.
+        cb.block_lookup(
+            BlockContextFieldTag::CumNumTxs.expr(),
+            cb.curr.state.block_number.expr(),
+            total_txs.expr(),
+        );
+
         let mut withdraw_trie_root_slot_le = [0u8; 32];
         WITHDRAW_TRIE_ROOT_SLOT.to_little_endian(withdraw_trie_root_slot_le.as_mut_slice());
 
@@ -228,9 +242,16 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
 
         // 3. Verify rw_counter counts to the same number of meaningful rows in
         // rw_table to ensure there is no malicious insertion.
-        // Verify that there are at most total_rws meaningful entries in the rw_table
+        // Verify that there are at most total_rws meaningful entries in the rw_table:
+        // besides the Start row, the rw_table is filled with Padding rows whose
+        // rw_counter go without gaps from total_rws + 1 to max_rws - 1.
         cb.rw_table_start_lookup(1.expr());
-        cb.rw_table_start_lookup(max_rws.expr() - total_rws.expr());
+        let is_full_rw_table =
+            IsEqualGadget::construct(cb, total_rws.expr() + 1.expr(), max_rws.expr());
+        cb.condition(not::expr(is_full_rw_table.expr()), |cb| {
+            cb.rw_table_padding_lookup(total_rws.expr() + 1.expr());
+            cb.rw_table_padding_lookup(max_rws.expr() - 1.expr());
+        });
         // Since every lookup done in the EVM circuit must succeed and uses
         // a unique rw_counter, we know that at least there are
         // total_rws meaningful entries in the rw_table.
@@ -250,6 +271,7 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
             chain_id,
             max_txs,
             max_rws,
+            is_full_rw_table,
             phase2_withdraw_root,
             phase2_withdraw_root_prev,
             total_txs,
@@ -297,6 +319,15 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
         self.is_curie_fork_block
             .assign(region, offset, Value::known(F::from(is_curie as u64)))?;
 
+        let total_rws = if step.rw_counter == 1 {
+            0
+        } else {
+            step.rw_counter
+        } + 1
+            + if is_curie { 7 } else { 0 };
+        self.is_full_rw_table
+            .assign(region, offset, F::from(total_rws as u64 + 1), max_rws)?;
+
         let withdraw_root = self.phase2_withdraw_root.assign(
             region,
             offset,
@@ -332,7 +363,7 @@ mod test {
 
     use eth_types::bytecode;
 
-    use mock::TestContext;
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
 
     fn test_circuit(evm_circuit_pad_to: usize) {
         let bytecode = bytecode! {
@@ -363,4 +394,44 @@ mod test {
     fn end_block_padding() {
         test_circuit(100);
     }
+
+    // Test where the block contains several txs, so that the number of processed
+    // txs is checked against the cumulative number of txs in the block table.
+    #[test]
+    fn end_block_multiple_txs() {
+        let ctx = TestContext::<2, 3>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! { STOP }),
+            |mut txs, accs| {
+                for tx in txs.iter_mut() {
+                    tx.to(accs[0].address).from(accs[1].address);
+                }
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::<2, 3>::new_from_test_ctx(ctx).run();
+    }
+
+    // Test where the block has no txs, so that the EVM circuit only contains
+    // padding steps and the EndBlock, which doesn't read any call context.
+    #[test]
+    fn end_block_no_txs() {
+        for evm_circuit_pad_to in [0, 100] {
+            let ctx = TestContext::<2, 0>::new(
+                None,
+                account_0_code_account_1_no_code(bytecode! { STOP }),
+                |_txs, _accs| {},
+                |block, _tx| block,
+            )
+            .unwrap();
+
+            CircuitTestBuilder::<2, 0>::new_from_test_ctx(ctx)
+                .block_modifier(Box::new(move |block| {
+                    block.circuits_params.max_evm_rows = evm_circuit_pad_to
+                }))
+                .run();
+        }
+    }
 }
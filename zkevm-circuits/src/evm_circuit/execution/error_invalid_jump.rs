@@ -234,6 +234,33 @@ mod test {
         test_internal_jump_error(true);
     }
 
+    #[test]
+    fn invalid_jump_into_push_data() {
+        // The destination is the JUMPDEST byte pushed by PUSH1, which is not
+        // code.
+        for is_jumpi in [false, true] {
+            let mut bytecode = bytecode! {
+                PUSH1(0x01) // condition
+                PUSH1(0x07)
+            };
+            bytecode.write_op(if is_jumpi {
+                OpcodeId::JUMPI
+            } else {
+                OpcodeId::JUMP
+            });
+            bytecode.append(&bytecode! {
+                STOP
+                PUSH1(OpcodeId::JUMPDEST.as_u64())
+                STOP
+            });
+
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+            )
+            .run();
+        }
+    }
+
     #[test]
     fn invalid_jump_dest_overflow() {
         let bytecode = bytecode! {
@@ -4,9 +4,9 @@ use crate::{
         param::N_BYTES_GAS,
         step::ExecutionState,
         util::{
-            common_gadget::{CommonCallGadget, CommonErrorGadget},
+            common_gadget::{CallVariantGadget, CommonCallGadget, CommonErrorGadget},
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
-            math_gadget::{IsZeroGadget, LtGadget},
+            math_gadget::LtGadget,
             memory_gadget::MemoryExpandedAddressGadget,
             or, CachedRegion, Cell, StepRws,
         },
@@ -25,10 +25,7 @@ use halo2_proofs::{circuit::Value, plonk::Error};
 #[derive(Clone, Debug)]
 pub(crate) struct ErrorOOGCallGadget<F> {
     opcode: Cell<F>,
-    is_call: IsZeroGadget<F>,
-    is_callcode: IsZeroGadget<F>,
-    is_delegatecall: IsZeroGadget<F>,
-    is_staticcall: IsZeroGadget<F>,
+    variant: CallVariantGadget<F>,
     tx_id: Cell<F>,
     is_static: Cell<F>,
     is_warm: Cell<F>,
@@ -45,24 +42,13 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
 
-        let is_call = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALL.expr());
-        let is_callcode = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALLCODE.expr());
-        let is_delegatecall =
-            IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::DELEGATECALL.expr());
-        let is_staticcall =
-            IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::STATICCALL.expr());
+        let variant = CallVariantGadget::construct(cb, opcode.expr());
 
         let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
         let is_static = cb.call_context(None, CallContextFieldTag::IsStatic);
 
         let call_gadget: CommonCallGadget<F, MemoryExpandedAddressGadget<F>, false> =
-            CommonCallGadget::construct(
-                cb,
-                is_call.expr(),
-                is_callcode.expr(),
-                is_delegatecall.expr(),
-                is_staticcall.expr(),
-            );
+            CommonCallGadget::construct(cb, &variant);
 
         // Add callee to access list
         let is_warm = cb.query_bool();
@@ -72,15 +58,10 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
             is_warm.expr(),
         );
 
-        cb.condition(is_call.expr() * call_gadget.has_value.expr(), |cb| {
-            cb.require_zero(
-                "CALL with value must not be in static call stack",
-                is_static.expr(),
-            );
-        });
+        call_gadget.require_no_value_in_static_call(cb, is_static.expr());
 
         // Verify gas cost
-        let gas_cost = call_gadget.gas_cost_expr(is_warm.expr(), is_call.expr());
+        let gas_cost = call_gadget.gas_cost_expr(is_warm.expr());
 
         // Check if the amount of gas available is less than the amount of gas required
         let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
@@ -100,15 +81,12 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
         let common_error_gadget = CommonErrorGadget::construct(
             cb,
             opcode.expr(),
-            13.expr() + is_call.expr() + is_callcode.expr(),
+            13.expr() + variant.is_call() + variant.is_callcode(),
         );
 
         Self {
             opcode,
-            is_call,
-            is_callcode,
-            is_delegatecall,
-            is_staticcall,
+            variant,
             tx_id,
             is_static,
             is_warm,
@@ -170,26 +148,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
         self.opcode
             .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
 
-        self.is_call.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::CALL.as_u64()),
-        )?;
-        self.is_callcode.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::CALLCODE.as_u64()),
-        )?;
-        self.is_delegatecall.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::DELEGATECALL.as_u64()),
-        )?;
-        self.is_staticcall.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::STATICCALL.as_u64()),
-        )?;
+        self.variant.assign(region, offset, opcode)?;
 
         self.tx_id
             .assign(region, offset, Value::known(F::from(tx_id.low_u64())))?;
@@ -200,12 +159,11 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
         self.is_warm
             .assign(region, offset, Value::known(F::from(is_warm as u64)))?;
 
-        let has_value = !value.is_zero();
         let gas_cost = self.call.cal_gas_cost_for_assignment(
             memory_expansion_gas_cost,
             is_warm_prev,
-            is_call,
-            has_value,
+            opcode,
+            value,
             !callee_exists,
         )?;
 
@@ -232,10 +190,14 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::CircuitTestBuilder;
+    use crate::{evm_circuit::step::ExecutionState, test_util::CircuitTestBuilder, witness};
+    use bus_mapping::mock::BlockData;
     use eth_types::{
-        address, bytecode, bytecode::Bytecode, evm_types::OpcodeId, geth_types::Account, Address,
-        ToWord, Word,
+        address, bytecode,
+        bytecode::Bytecode,
+        evm_types::{gas_utils::memory_expansion_gas_cost, GasCost, OpcodeId},
+        geth_types::{Account, GethData},
+        Address, ToWord, Word,
     };
     use mock::TestContext;
     use std::default::Default;
@@ -303,8 +265,15 @@ mod test {
     }
 
     fn test_oog(caller: &Account, callee: &Account, is_root: bool) {
-        let tx_gas = if is_root { 21100 } else { 25000 };
-        let ctx = TestContext::<3, 1>::new(
+        test_with_tx_gas(caller, callee, if is_root { 21100 } else { 25000 });
+    }
+
+    fn test_with_tx_gas(caller: &Account, callee: &Account, tx_gas: u64) {
+        CircuitTestBuilder::new_from_test_ctx(test_ctx(caller, callee, tx_gas)).run();
+    }
+
+    fn test_ctx(caller: &Account, callee: &Account, tx_gas: u64) -> TestContext<3, 1> {
+        TestContext::<3, 1>::new(
             None,
             |accs| {
                 accs[0]
@@ -329,9 +298,28 @@ mod test {
             },
             |block, _tx| block.number(0xcafeu64),
         )
-        .unwrap();
+        .unwrap()
+    }
 
-        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    /// Execution state of the call of the root call run with `tx_gas`
+    fn call_execution_state(caller: &Account, callee: &Account, tx_gas: u64) -> ExecutionState {
+        let block: GethData = test_ctx(caller, callee, tx_gas).into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let block = witness::block_convert(&builder.block, &builder.code_db).unwrap();
+        block.txs[0]
+            .steps
+            .iter()
+            .find(|step| {
+                step.call_index == 0
+                    && step
+                        .opcode
+                        .map_or(false, |opcode| TEST_CALL_OPCODES.contains(&opcode))
+            })
+            .expect("the root call runs a call opcode")
+            .execution_state
     }
 
     #[test]
@@ -443,4 +431,149 @@ mod test {
             test_oog(&caller(*opcode, stack), &callee, true);
         }
     }
+
+    /// Memory layouts of the boundary tests: no expansion, and an expansion
+    /// by the call data and by the return data.
+    const BOUNDARY_STACKS: &[Stack] = &[
+        Stack {
+            gas: Word([100, 0, 0, 0]),
+            value: Word::zero(),
+            cd_offset: 0,
+            cd_length: 0,
+            rd_offset: 0,
+            rd_length: 0,
+        },
+        Stack {
+            gas: Word([100, 0, 0, 0]),
+            value: Word::zero(),
+            cd_offset: 64,
+            cd_length: 320,
+            rd_offset: 0,
+            rd_length: 32,
+        },
+        Stack {
+            gas: Word([100, 0, 0, 0]),
+            value: Word::zero(),
+            cd_offset: 0,
+            cd_length: 0,
+            rd_offset: 1000,
+            rd_length: 33,
+        },
+    ];
+
+    /// Gas charged by `opcode` before the callee runs: the account access,
+    /// the value transfer including the new account, and the memory
+    /// expansion. The stipend is given to the callee on top of this.
+    fn call_gas_cost(opcode: OpcodeId, stack: &Stack, is_warm: bool, callee_exists: bool) -> u64 {
+        let access_cost = if is_warm {
+            GasCost::WARM_ACCESS
+        } else {
+            GasCost::COLD_ACCOUNT_ACCESS
+        };
+        let has_value =
+            matches!(opcode, OpcodeId::CALL | OpcodeId::CALLCODE) && !stack.value.is_zero();
+        let transfer_cost = if has_value {
+            GasCost::CALL_WITH_VALUE.as_u64()
+                + if opcode == OpcodeId::CALL && !callee_exists {
+                    GasCost::NEW_ACCOUNT.as_u64()
+                } else {
+                    0
+                }
+        } else {
+            0
+        };
+        let memory_word_size = [
+            (stack.cd_offset, stack.cd_length),
+            (stack.rd_offset, stack.rd_length),
+        ]
+        .into_iter()
+        .filter(|(_, length)| *length != 0)
+        .map(|(offset, length)| (offset + length + 31) / 32)
+        .max()
+        .unwrap_or_default();
+
+        access_cost.as_u64() + transfer_cost + memory_expansion_gas_cost(0, memory_word_size)
+    }
+
+    /// Run `opcode` with exactly the gas it costs, and with one gas less, for
+    /// every combination of the gas cost components.
+    fn test_oog_call_boundaries(opcode: OpcodeId) {
+        let pops_value = matches!(opcode, OpcodeId::CALL | OpcodeId::CALLCODE);
+        let values: &[u64] = if pops_value { &[0, 1] } else { &[0] };
+        let callee = callee(bytecode! { STOP });
+        for stack in BOUNDARY_STACKS {
+            for value in values {
+                for is_warm in [false, true] {
+                    for callee_exists in [false, true] {
+                        let stack = Stack {
+                            value: (*value).into(),
+                            ..*stack
+                        };
+                        let callee_address = if callee_exists {
+                            callee.address
+                        } else {
+                            Address::repeat_byte(0xfd)
+                        };
+
+                        // The callee is warmed up by a cold EXTCODESIZE.
+                        let mut code = Bytecode::default();
+                        let mut gas_before_call = GasCost::TX.as_u64();
+                        if is_warm {
+                            code.append(&bytecode! {
+                                PUSH20(callee_address.to_word())
+                                EXTCODESIZE
+                                POP
+                            });
+                            gas_before_call += OpcodeId::PUSH20.constant_gas_cost().as_u64()
+                                + GasCost::COLD_ACCOUNT_ACCESS.as_u64()
+                                + OpcodeId::POP.constant_gas_cost().as_u64();
+                        }
+                        let call_code = call_bytecode(opcode, callee_address, stack);
+                        let num_pushes = if pops_value { 7 } else { 6 };
+                        gas_before_call +=
+                            num_pushes * OpcodeId::PUSH32.constant_gas_cost().as_u64();
+                        code.append(&call_code);
+
+                        let caller = Account {
+                            code: code.to_vec().into(),
+                            ..caller(opcode, stack)
+                        };
+                        let gas_cost = call_gas_cost(opcode, &stack, is_warm, callee_exists);
+                        let exact_gas = gas_before_call + gas_cost;
+                        for (tx_gas, execution_state) in [
+                            (exact_gas - 1, ExecutionState::ErrorOutOfGasCall),
+                            (exact_gas, ExecutionState::CALL_OP),
+                        ] {
+                            assert_eq!(
+                                call_execution_state(&caller, &callee, tx_gas),
+                                execution_state,
+                                "{opcode:?} {stack:?} warm={is_warm} exists={callee_exists}",
+                            );
+                            test_with_tx_gas(&caller, &callee, tx_gas);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_oog_call_boundaries_call() {
+        test_oog_call_boundaries(OpcodeId::CALL);
+    }
+
+    #[test]
+    fn test_oog_call_boundaries_callcode() {
+        test_oog_call_boundaries(OpcodeId::CALLCODE);
+    }
+
+    #[test]
+    fn test_oog_call_boundaries_delegatecall() {
+        test_oog_call_boundaries(OpcodeId::DELEGATECALL);
+    }
+
+    #[test]
+    fn test_oog_call_boundaries_staticcall() {
+        test_oog_call_boundaries(OpcodeId::STATICCALL);
+    }
 }
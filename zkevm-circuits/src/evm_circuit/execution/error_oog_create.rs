@@ -206,9 +206,8 @@ mod tests {
     use crate::test_util::CircuitTestBuilder;
     use eth_types::{bytecode, word, Bytecode, ToWord};
     use mock::{
-        eth,
-        test_ctx::{helpers::account_0_code_account_1_no_code, LoggerConfig},
-        TestContext, MOCK_ACCOUNTS, MOCK_BLOCK_GAS_LIMIT,
+        eth, test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS,
+        MOCK_BLOCK_GAS_LIMIT,
     };
 
     struct TestCase {
@@ -320,7 +319,7 @@ mod tests {
     }
 
     fn test_root(case: &TestCase) {
-        let ctx = TestContext::<2, 1>::new_with_logger_config(
+        let ctx = TestContext::<2, 1>::new(
             None,
             account_0_code_account_1_no_code(case.bytecode.clone()),
             |mut txs, accs| {
@@ -330,10 +329,6 @@ mod tests {
                     .gas(case.gas.into());
             },
             |block, _tx| block,
-            LoggerConfig {
-                enable_memory: true,
-                ..Default::default()
-            },
         )
         .unwrap();
 
@@ -353,7 +348,7 @@ mod tests {
             STOP
         };
 
-        let ctx = TestContext::<3, 1>::new_with_logger_config(
+        let ctx = TestContext::<3, 1>::new(
             None,
             |accs| {
                 accs[0].address(MOCK_ACCOUNTS[0]).code(code_a);
@@ -369,10 +364,6 @@ mod tests {
                     .gas(word!("0xFFFFF"));
             },
             |block, _tx| block,
-            LoggerConfig {
-                enable_memory: true,
-                ..Default::default()
-            },
         )
         .unwrap();
 
@@ -3,10 +3,10 @@ use crate::{
         execution::ExecutionGadget,
         step::ExecutionState,
         util::{
+            common_gadget::CallVariantGadget,
             constraint_builder::EVMConstraintBuilder,
-            math_gadget::IsZeroGadget,
             memory_gadget::{CommonMemoryAddressGadget, MemoryAddressGadget},
-            sum, CachedRegion, Cell, Word,
+            CachedRegion, Cell, Word,
         },
     },
     table::CallContextFieldTag,
@@ -20,10 +20,7 @@ use halo2_proofs::{circuit::Value, plonk::Error};
 #[derive(Clone, Debug)]
 pub(crate) struct ErrorPrecompileFailedGadget<F> {
     opcode: Cell<F>,
-    is_call: IsZeroGadget<F>,
-    is_callcode: IsZeroGadget<F>,
-    is_delegatecall: IsZeroGadget<F>,
-    is_staticcall: IsZeroGadget<F>,
+    variant: CallVariantGadget<F>,
     gas: Word<F>,
     callee_address: Word<F>,
     value: Word<F>,
@@ -40,26 +37,11 @@ impl<F: Field> ExecutionGadget<F> for ErrorPrecompileFailedGadget<F> {
         let opcode = cb.query_cell();
         cb.opcode_lookup(opcode.expr(), 1.expr());
 
-        let is_call = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALL.expr());
-        let is_callcode = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALLCODE.expr());
-        let is_delegatecall =
-            IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::DELEGATECALL.expr());
-        let is_staticcall =
-            IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::STATICCALL.expr());
-
         // constrain op code
         // NOTE: this precompile gadget is for dummy use at the moment, the real error handling for
         // precompile will be done in each precompile gadget in the future. won't add step
         // state transition constraint here as well.
-        cb.require_true(
-            "opcode is one of [call, callcode, staticcall, delegatecall]",
-            sum::expr(vec![
-                is_call.expr(),
-                is_callcode.expr(),
-                is_delegatecall.expr(),
-                is_staticcall.expr(),
-            ]),
-        );
+        let variant = CallVariantGadget::construct(cb, opcode.expr());
 
         // Use rw_counter of the step which triggers next call as its call_id.
         let callee_call_id = cb.curr.state.rw_counter.clone();
@@ -76,7 +58,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorPrecompileFailedGadget<F> {
         cb.stack_pop(callee_address.expr());
 
         // `CALL` and `CALLCODE` opcodes have an additional stack pop `value`.
-        cb.condition(is_call.expr() + is_callcode.expr(), |cb| {
+        cb.condition(variant.is_call() + variant.is_callcode(), |cb| {
             cb.stack_pop(value.expr())
         });
         cb.stack_pop(cd_offset.expr());
@@ -98,10 +80,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorPrecompileFailedGadget<F> {
 
         Self {
             opcode,
-            is_call,
-            is_callcode,
-            is_delegatecall,
-            is_staticcall,
+            variant,
             gas,
             callee_address,
             value,
@@ -140,26 +119,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorPrecompileFailedGadget<F> {
 
         self.opcode
             .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
-        self.is_call.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::CALL.as_u64()),
-        )?;
-        self.is_callcode.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::CALLCODE.as_u64()),
-        )?;
-        self.is_delegatecall.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::DELEGATECALL.as_u64()),
-        )?;
-        self.is_staticcall.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::STATICCALL.as_u64()),
-        )?;
+        self.variant.assign(region, offset, opcode)?;
         self.gas.assign(region, offset, Some(gas.to_le_bytes()))?;
         self.callee_address
             .assign(region, offset, Some(callee_address.to_le_bytes()))?;
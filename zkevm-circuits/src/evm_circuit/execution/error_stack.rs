@@ -1,6 +1,7 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
+        param::STACK_CAPACITY,
         step::ExecutionState,
         table::{FixedTableTag, Lookup},
         util::{
@@ -17,6 +18,10 @@ use halo2_proofs::{circuit::Value, plonk::Error};
 pub(crate) struct ErrorStackGadget<F> {
     opcode: Cell<F>,
     push_rlc: Cell<F>,
+    is_overflow: Cell<F>,
+    // how much the stack limit of the chain spec of the circuit is below
+    // STACK_CAPACITY
+    stack_limit_offset: u64,
     common_error_gadget: CommonErrorGadget<F>,
 }
 
@@ -28,7 +33,13 @@ impl<F: Field> ExecutionGadget<F> for ErrorStackGadget<F> {
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
         let push_rlc = cb.query_cell_phase2();
+        let is_overflow = cb.query_bool();
 
+        // The invalid stack pointers of the fixed table are the ones of the
+        // Ethereum stack limit. The stack pointer starts at STACK_CAPACITY, so
+        // the stack underflows at the same pointers under a lower limit, while
+        // it overflows at pointers shifted by the difference of the limits.
+        let stack_limit_offset = (STACK_CAPACITY - cb.params().chain_spec.stack_limit) as u64;
         cb.add_lookup(
             "Responsible opcode lookup for invalid stack pointer",
             Lookup::Fixed {
@@ -36,7 +47,8 @@ impl<F: Field> ExecutionGadget<F> for ErrorStackGadget<F> {
                 values: [
                     Self::EXECUTION_STATE.as_u64().expr(),
                     opcode.expr(),
-                    cb.curr.state.stack_pointer.expr(),
+                    cb.curr.state.stack_pointer.expr()
+                        - is_overflow.expr() * stack_limit_offset.expr(),
                 ],
             },
         );
@@ -47,6 +59,8 @@ impl<F: Field> ExecutionGadget<F> for ErrorStackGadget<F> {
         Self {
             opcode,
             push_rlc,
+            is_overflow,
+            stack_limit_offset,
             common_error_gadget,
         }
     }
@@ -63,6 +77,11 @@ impl<F: Field> ExecutionGadget<F> for ErrorStackGadget<F> {
         let opcode = step.opcode.unwrap();
         self.opcode
             .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        let (min_stack_ptr, _) = opcode.valid_stack_ptr_range();
+        let is_overflow =
+            (step.stack_pointer as u64) < u64::from(min_stack_ptr) + self.stack_limit_offset;
+        self.is_overflow
+            .assign(region, offset, Value::known(F::from(is_overflow)))?;
 
         let push_rlc = CommonErrorGadget::get_push_rlc(region, block, call, step);
         self.push_rlc.assign(region, offset, push_rlc)?;
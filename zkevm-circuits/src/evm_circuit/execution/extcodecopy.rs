@@ -23,8 +23,7 @@ use crate::{
 };
 use bus_mapping::circuit_input_builder::CopyDataType;
 use eth_types::{evm_types::GasCost, ToLittleEndian};
-use gadgets::util::Expr;
-use gadgets::ToScalar;
+use gadgets::{util::Expr, ToScalar};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 use super::ExecutionGadget;
@@ -463,4 +462,22 @@ mod test {
             true,
         );
     }
+
+    #[test]
+    fn extcodecopy_account_without_code() {
+        // the account exists but has no code, so the copied bytes are all zero padding.
+        for is_warm in [true, false] {
+            test_ok(
+                Some(Account {
+                    address: *EXTERNAL_ADDRESS,
+                    balance: Word::from(1u64 << 20),
+                    ..Default::default()
+                }),
+                Word::zero(),
+                Word::zero(),
+                0x36,
+                is_warm,
+            );
+        }
+    }
 }
@@ -6,9 +6,12 @@ use crate::{
         util::{
             common_gadget::SameContextGadget,
             constraint_builder::{
-                EVMConstraintBuilder, ReversionInfo, StepStateTransition, Transition::Delta,
+                ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::Delta,
             },
-            from_bytes, select, CachedRegion, Cell, Word,
+            from_bytes,
+            math_gadget::IsZeroGadget,
+            not, select, CachedRegion, Cell, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -26,6 +29,10 @@ pub(crate) struct ExtcodehashGadget<F> {
     reversion_info: ReversionInfo<F>,
     is_warm: Cell<F>,
     code_hash: Cell<F>,
+    not_exists: IsZeroGadget<F>,
+    /// Keccak code hash of an existing account, only used with the scroll
+    /// feature where the code hash field is the poseidon hash.
+    keccak_code_hash: Cell<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for ExtcodehashGadget<F> {
@@ -51,17 +58,34 @@ impl<F: Field> ExecutionGadget<F> for ExtcodehashGadget<F> {
         );
 
         let code_hash = cb.query_cell_phase2();
-        // For non-existing accounts the code_hash must be 0 in the rw_table.
-        cb.account_read(
-            address,
-            if cfg!(feature = "scroll") {
-                AccountFieldTag::KeccakCodeHash
-            } else {
-                AccountFieldTag::CodeHash
-            },
-            code_hash.expr(),
-        );
-        cb.stack_push(code_hash.expr());
+        // For non-existing and empty accounts the code_hash must be 0 in the rw_table,
+        // and EXTCODEHASH returns 0 for them.
+        cb.account_read(address.expr(), AccountFieldTag::CodeHash, code_hash.expr());
+        let not_exists = IsZeroGadget::construct(cb, code_hash.expr());
+
+        let keccak_code_hash = cb.query_cell_phase2();
+        if cfg!(feature = "scroll") {
+            cb.condition(not::expr(not_exists.expr()), |cb| {
+                cb.account_read(
+                    address.expr(),
+                    AccountFieldTag::KeccakCodeHash,
+                    keccak_code_hash.expr(),
+                );
+            });
+            cb.condition(not_exists.expr(), |cb| {
+                cb.require_zero(
+                    "keccak_code_hash is zero when non_exists",
+                    keccak_code_hash.expr(),
+                );
+            });
+        } else {
+            cb.require_equal(
+                "keccak_code_hash == code_hash",
+                keccak_code_hash.expr(),
+                code_hash.expr(),
+            );
+        }
+        cb.stack_push(keccak_code_hash.expr());
 
         let gas_cost = select::expr(
             is_warm.expr(),
@@ -87,6 +111,8 @@ impl<F: Field> ExecutionGadget<F> for ExtcodehashGadget<F> {
             reversion_info,
             is_warm,
             code_hash,
+            not_exists,
+            keccak_code_hash,
         }
     }
 
@@ -120,7 +146,17 @@ impl<F: Field> ExecutionGadget<F> for ExtcodehashGadget<F> {
 
         let code_hash = block.rws[step.rw_indices[5]].account_value_pair().0;
         self.code_hash
-            .assign(region, offset, region.word_rlc(code_hash))?;
+            .assign(region, offset, region.code_hash(code_hash))?;
+        self.not_exists
+            .assign_value(region, offset, region.code_hash(code_hash))?;
+
+        let keccak_code_hash = if cfg!(feature = "scroll") && !code_hash.is_zero() {
+            block.rws[step.rw_indices[6]].account_value_pair().0
+        } else {
+            code_hash
+        };
+        self.keccak_code_hash
+            .assign(region, offset, region.word_rlc(keccak_code_hash))?;
 
         Ok(())
     }
@@ -262,6 +298,39 @@ mod test {
         }
     }
 
+    #[test]
+    fn extcodehash_empty_account_after_call() {
+        // A value-less call to an empty account touches it without creating
+        // it, EXTCODEHASH must still return 0.
+        let code = bytecode! {
+            .op_call(0x1_0000, *EXTERNAL_ADDRESS, 0, 0, 0, 0, 0)
+            POP
+            PUSH20(EXTERNAL_ADDRESS.to_word())
+            EXTCODEHASH
+            STOP
+        };
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(address!("0x000000000000000000000000000000000000cafe"))
+                    .balance(Word::from(1u64 << 20))
+                    .code(code);
+                accs[1]
+                    .address(address!("0x0000000000000000000000000000000000000010"))
+                    .balance(Word::from(1u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     #[test]
     // Regression test to ensure that the code hash for an account that is is being initialized is
     // the empty code hash.
@@ -35,12 +35,8 @@ impl<F: Field> ExecutionGadget<F> for JumpGadget<F> {
         // Pop the value from the stack
         cb.stack_pop(destination.expr());
 
-        // Lookup opcode at destination
-        cb.opcode_lookup_at(
-            from_bytes::expr(&destination.cells),
-            OpcodeId::JUMPDEST.expr(),
-            1.expr(),
-        );
+        // Lookup JUMPDEST at destination
+        cb.jumpdest_lookup(from_bytes::expr(&destination.cells));
 
         // State transition
         let opcode = cb.query_cell();
@@ -52,7 +52,7 @@ impl<F: Field> ExecutionGadget<F> for JumpiGadget<F> {
                 1.expr(),
             );
 
-            cb.opcode_lookup_at(dest.valid_value(), OpcodeId::JUMPDEST.expr(), 1.expr());
+            cb.jumpdest_lookup(dest.valid_value());
         });
 
         // Transit program_counter to destination when should_jump, otherwise by
@@ -168,6 +168,32 @@ mod test {
         test_ok(rand_range(1 << 11..0x5fff), rand_word());
     }
 
+    #[test]
+    fn jumpi_gadget_while_loop() {
+        // store the counter at slots 3, 2 and 1, then branch on slot 2
+        let bytecode = bytecode! {
+            PUSH1(3)
+            .while_loop(&bytecode! { DUP1 }, &bytecode! {
+                DUP1
+                DUP1
+                SSTORE
+                PUSH1(1)
+                SWAP1
+                SUB
+            })
+            POP
+            PUSH1(2)
+            SLOAD
+            .if_else(&bytecode! { PUSH1(1) }, &bytecode! { PUSH1(0) })
+            STOP
+        };
+
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        )
+        .run();
+    }
+
     #[test]
     fn jumpi_gadget_with_zero_cond_and_overflow_dest() {
         let bytecode = bytecode! {
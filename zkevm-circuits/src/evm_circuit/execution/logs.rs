@@ -285,8 +285,11 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
 #[cfg(test)]
 mod test {
     use crate::test_util::CircuitTestBuilder;
-    use eth_types::{evm_types::OpcodeId, Bytecode, Word};
-    use mock::TestContext;
+    use eth_types::{bytecode, evm_types::OpcodeId, Bytecode, Word};
+    use mock::{
+        generate_mock_call_bytecode, test_ctx::helpers::account_0_code_account_1_no_code,
+        MockCallBytecodeParams, TestContext, MOCK_ACCOUNTS,
+    };
     use rand::Rng;
 
     #[test]
@@ -380,6 +383,76 @@ mod test {
         );
     }
 
+    #[test]
+    fn log_gadget_reverted_sub_call() {
+        // callee logs and then reverts, so its log must not be written
+        let code_b = bytecode! {
+            .op_mstore(0x00, Word::from(0xA0))
+            PUSH32(Word::from(0xef))
+            PUSH1(0x20)
+            PUSH1(0x00)
+            LOG1
+            .op_revert(0x00, 0x00)
+        };
+        // caller logs after the reverted call, with log_id 1
+        let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+            address: MOCK_ACCOUNTS[1],
+            pushdata: vec![0x01; 32],
+            instructions_after_call: bytecode! {
+                PUSH32(Word::from(0xb0))
+                PUSH1(0x20)
+                PUSH1(0x00)
+                LOG1
+            },
+            ..MockCallBytecodeParams::default()
+        });
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code_a);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(code_b);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn log_gadget_log_id_per_tx() {
+        // log_id restarts from 0 in every tx
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            LOG0
+            PUSH1(0x20)
+            PUSH1(0x00)
+            LOG0
+            STOP
+        };
+
+        let ctx = TestContext::<2, 2>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+                txs[1].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     #[derive(Clone, Copy)]
     struct Stack {
         mstart: Word,
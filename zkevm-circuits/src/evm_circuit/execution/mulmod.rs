@@ -166,8 +166,8 @@ impl<F: Field> ExecutionGadget<F> for MulModGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::CircuitTestBuilder;
-    use eth_types::{bytecode, Word, U256};
+    use crate::{evm_circuit::test::rand_word, test_util::CircuitTestBuilder};
+    use eth_types::{bytecode, Word, U256, U512};
     use mock::TestContext;
 
     #[cfg(feature = "enable-stack")]
@@ -212,6 +212,14 @@ mod test {
         test(a.into(), b.into(), n.into(), r.map(Word::from), true)
     }
 
+    /// Reference `a * b mod n` computed on the full 512-bit product.
+    fn mulmod(a: Word, b: Word, n: Word) -> Word {
+        if n.is_zero() {
+            return Word::zero();
+        }
+        U256::try_from(a.full_mul(b) % U512::from(n)).unwrap()
+    }
+
     // TODO: re-enable when we have a way to check for errors
     // fn test_ko_u32(a: u32, b: u32, n: u32, r: Option<u32>) {
     //     test(a.into(), b.into(), n.into(), r.map(Word::from), false)
@@ -255,6 +263,29 @@ mod test {
         );
     }
 
+    #[test]
+    fn mulmod_limits() {
+        for n in [0.into(), 1.into(), Word::MAX - 1, Word::MAX] {
+            for (a, b) in [
+                (Word::MAX, Word::MAX),
+                (Word::MAX - 1, Word::MAX),
+                (Word::MAX, 1.into()),
+                (Word::MAX, 0.into()),
+                (0.into(), 0.into()),
+            ] {
+                test(a, b, n, Some(mulmod(a, b, n)), true);
+            }
+        }
+    }
+
+    #[test]
+    fn mulmod_random() {
+        for _ in 0..4 {
+            let (a, b, n) = (rand_word(), rand_word(), rand_word());
+            test(a, b, n, Some(mulmod(a, b, n)), true);
+        }
+    }
+
     #[test]
     fn mulmod_division_by_zero() {
         test_ok_u32(7, 1, 0, None);
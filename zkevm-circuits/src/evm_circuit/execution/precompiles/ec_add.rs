@@ -2,8 +2,7 @@ use crate::util::Field;
 use bus_mapping::precompile::{PrecompileAuxData, PrecompileCalls};
 use eth_types::{evm_types::GasCost, ToLittleEndian};
 use gadgets::util::{not, select, Expr};
-use gadgets::ToScalar;
-use halo2_proofs::{circuit::Value, plonk::Error};
+use halo2_proofs::plonk::Error;
 
 use crate::{
     evm_circuit::{
@@ -11,7 +10,7 @@ use crate::{
         param::N_BYTES_MEMORY_ADDRESS,
         step::ExecutionState,
         util::{
-            common_gadget::RestoreContextGadget,
+            common_gadget::{CallContextGadget, RestoreContextGadget},
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
             math_gadget::LtGadget,
             padding_gadget::PaddingGadget,
@@ -43,13 +42,7 @@ pub struct EcAddGadget<F> {
     point_r_x_rlc: Cell<F>,
     point_r_y_rlc: Cell<F>,
 
-    is_success: Cell<F>,
-    callee_address: Cell<F>,
-    is_root: Cell<F>,
-    call_data_offset: Cell<F>,
-    call_data_length: Cell<F>,
-    return_data_offset: Cell<F>,
-    return_data_length: Cell<F>,
+    call_context: CallContextGadget<F, 7>,
     restore_context: RestoreContextGadget<F>,
 }
 
@@ -81,7 +74,9 @@ impl<F: Field> ExecutionGadget<F> for EcAddGadget<F> {
             cb.query_cell_phase2(),
         );
 
-        let [is_success, callee_address, is_root, call_data_offset, call_data_length, return_data_offset, return_data_length] =
+        let call_context = CallContextGadget::construct(
+            cb,
+            None,
             [
                 CallContextFieldTag::IsSuccess,
                 CallContextFieldTag::CalleeAddress,
@@ -90,8 +85,10 @@ impl<F: Field> ExecutionGadget<F> for EcAddGadget<F> {
                 CallContextFieldTag::CallDataLength,
                 CallContextFieldTag::ReturnDataOffset,
                 CallContextFieldTag::ReturnDataLength,
-            ]
-            .map(|tag| cb.call_context(None, tag));
+            ],
+        );
+        let [is_success, callee_address, is_root, _call_data_offset, call_data_length, _return_data_offset, _return_data_length] =
+            call_context.cells();
 
         // all gas sent to this call will be consumed if `is_success == false`.
         let gas_cost = select::expr(
@@ -185,13 +182,7 @@ impl<F: Field> ExecutionGadget<F> for EcAddGadget<F> {
             point_r_x_rlc,
             point_r_y_rlc,
 
-            is_success,
-            callee_address,
-            is_root,
-            call_data_offset,
-            call_data_length,
-            return_data_offset,
-            return_data_length,
+            call_context,
             restore_context,
         }
     }
@@ -250,38 +241,8 @@ impl<F: Field> ExecutionGadget<F> for EcAddGadget<F> {
             return Err(Error::Synthesis);
         }
 
-        self.is_success.assign(
-            region,
-            offset,
-            Value::known(F::from(u64::from(call.is_success))),
-        )?;
-        self.callee_address.assign(
-            region,
-            offset,
-            Value::known(call.code_address.unwrap().to_scalar().unwrap()),
-        )?;
-        self.is_root
-            .assign(region, offset, Value::known(F::from(call.is_root as u64)))?;
-        self.call_data_offset.assign(
-            region,
-            offset,
-            Value::known(F::from(call.call_data_offset)),
-        )?;
-        self.call_data_length.assign(
-            region,
-            offset,
-            Value::known(F::from(call.call_data_length)),
-        )?;
-        self.return_data_offset.assign(
-            region,
-            offset,
-            Value::known(F::from(call.return_data_offset)),
-        )?;
-        self.return_data_length.assign(
-            region,
-            offset,
-            Value::known(F::from(call.return_data_length)),
-        )?;
+        self.call_context
+            .assign_exec_step(region, offset, block, step)?;
 
         self.restore_context
             .assign(region, offset, block, call, step, 7)
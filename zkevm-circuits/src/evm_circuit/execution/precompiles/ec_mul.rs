@@ -4,7 +4,7 @@ use crate::{
         param::N_BYTES_MEMORY_ADDRESS,
         step::ExecutionState,
         util::{
-            common_gadget::RestoreContextGadget,
+            common_gadget::{CallContextGadget, RestoreContextGadget},
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
             math_gadget::{AddWordsGadget, IsEqualGadget, IsZeroGadget, LtGadget, ModGadget},
             padding_gadget::PaddingGadget,
@@ -19,10 +19,7 @@ use bus_mapping::precompile::{PrecompileAuxData, PrecompileCalls};
 use eth_types::{evm_types::GasCost, ToLittleEndian, U256};
 use gadgets::util::{and, not, or, select, split_u256, sum, Expr};
 use gadgets::ToScalar;
-use halo2_proofs::{
-    circuit::Value,
-    plonk::{Error, Expression},
-};
+use halo2_proofs::plonk::{Error, Expression};
 use std::{
     ops::{Add, Sub},
     sync::LazyLock,
@@ -75,13 +72,7 @@ pub struct EcMulGadget<F> {
     fr_modulus: Word<F>,   // Fr::MODULUS
     modword: ModGadget<F, false>,
 
-    is_success: Cell<F>,
-    callee_address: Cell<F>,
-    is_root: Cell<F>,
-    call_data_offset: Cell<F>,
-    call_data_length: Cell<F>,
-    return_data_offset: Cell<F>,
-    return_data_length: Cell<F>,
+    call_context: CallContextGadget<F, 7>,
     restore_context: RestoreContextGadget<F>,
 }
 
@@ -182,7 +173,9 @@ impl<F: Field> ExecutionGadget<F> for EcMulGadget<F> {
             sum::expr(fq_modulus_hi.to_le_bytes()),
         );
 
-        let [is_success, callee_address, is_root, call_data_offset, call_data_length, return_data_offset, return_data_length] =
+        let call_context = CallContextGadget::construct(
+            cb,
+            None,
             [
                 CallContextFieldTag::IsSuccess,
                 CallContextFieldTag::CalleeAddress,
@@ -191,8 +184,10 @@ impl<F: Field> ExecutionGadget<F> for EcMulGadget<F> {
                 CallContextFieldTag::CallDataLength,
                 CallContextFieldTag::ReturnDataOffset,
                 CallContextFieldTag::ReturnDataLength,
-            ]
-            .map(|tag| cb.call_context(None, tag));
+            ],
+        );
+        let [is_success, callee_address, is_root, _call_data_offset, call_data_length, _return_data_offset, _return_data_length] =
+            call_context.cells();
 
         // all gas sent to this call will be consumed if `is_success == false`.
         let gas_cost = select::expr(
@@ -343,13 +338,7 @@ impl<F: Field> ExecutionGadget<F> for EcMulGadget<F> {
             fr_modulus,
             modword,
 
-            is_success,
-            callee_address,
-            is_root,
-            call_data_offset,
-            call_data_length,
-            return_data_offset,
-            return_data_length,
+            call_context,
             restore_context,
         }
     }
@@ -455,38 +444,8 @@ impl<F: Field> ExecutionGadget<F> for EcMulGadget<F> {
             return Err(Error::Synthesis);
         }
 
-        self.is_success.assign(
-            region,
-            offset,
-            Value::known(F::from(u64::from(call.is_success))),
-        )?;
-        self.callee_address.assign(
-            region,
-            offset,
-            Value::known(call.code_address.unwrap().to_scalar().unwrap()),
-        )?;
-        self.is_root
-            .assign(region, offset, Value::known(F::from(call.is_root as u64)))?;
-        self.call_data_offset.assign(
-            region,
-            offset,
-            Value::known(F::from(call.call_data_offset)),
-        )?;
-        self.call_data_length.assign(
-            region,
-            offset,
-            Value::known(F::from(call.call_data_length)),
-        )?;
-        self.return_data_offset.assign(
-            region,
-            offset,
-            Value::known(F::from(call.return_data_offset)),
-        )?;
-        self.return_data_length.assign(
-            region,
-            offset,
-            Value::known(F::from(call.return_data_length)),
-        )?;
+        self.call_context
+            .assign_exec_step(region, offset, block, step)?;
 
         self.restore_context
             .assign(region, offset, block, call, step, 7)
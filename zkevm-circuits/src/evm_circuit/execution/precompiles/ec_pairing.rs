@@ -1,11 +1,13 @@
 use crate::util::Field;
 use bus_mapping::{
-    circuit_input_builder::{N_BYTES_PER_PAIR, N_PAIRING_PER_OP},
+    circuit_input_builder::N_BYTES_PER_PAIR,
     precompile::{EcPairingError, PrecompileAuxData, PrecompileCalls},
 };
 use eth_types::evm_types::GasCost;
-use gadgets::util::{and, not, or, select, Expr};
-use gadgets::ToScalar;
+use gadgets::{
+    util::{and, not, or, select, Expr},
+    ToScalar,
+};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 use crate::{
@@ -13,7 +15,7 @@ use crate::{
         execution::ExecutionGadget,
         step::ExecutionState,
         util::{
-            common_gadget::RestoreContextGadget,
+            common_gadget::{CallContextGadget, RestoreContextGadget},
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
             math_gadget::{BinaryNumberGadget, IsZeroGadget, LtGadget},
             rlc, CachedRegion, Cell,
@@ -23,10 +25,16 @@ use crate::{
     witness::{Block, Call, ExecStep, Transaction},
 };
 
-/// Note: input_len ∈ { 0, 192, 384, 576, 768 } if valid.
+/// Number of bits representing the number of pairs, from 0 to `max_pairs`, so at most 15 pairs
+/// are supported.
+const N_PAIRS_BITS: usize = 4;
+
+/// Note: input_len ∈ { 0, 192, ..., max_pairs * 192 } if valid, where `max_pairs` is
+/// [`EvmCircuitConfigParams::max_ec_pairing_pairs`](crate::evm_circuit::EvmCircuitConfigParams).
 ///
-/// Note: input bytes are padded to 768 bytes within our zkEVM implementation to standardise a
-/// pairing operation, such that each pairing op has 4 pairs: [(G1, G2); 4].
+/// Note: input bytes are padded to `max_pairs * 192` bytes within our zkEVM
+/// implementation to standardise a pairing operation, such that each pairing op has
+/// `max_pairs` pairs: [(G1, G2); max_pairs].
 #[derive(Clone, Debug)]
 pub struct EcPairingGadget<F> {
     // Random linear combination of input bytes to the precompile ecPairing call.
@@ -41,30 +49,26 @@ pub struct EcPairingGadget<F> {
     output: Cell<F>,
 
     // Verify invalidity of input bytes. We basically check `or(1, 2)` where:
-    // 1. input_len > 4 * 192
+    // 1. input_len > max_pairs * 192
     // 2. input_len % 192 != 0
     input_is_zero: IsZeroGadget<F>,
 
     // call_data_len must less than 2^32.
-    input_lt_769: LtGadget<F, 4>,
+    input_lt_max: LtGadget<F, 4>,
     input_mod_192: Cell<F>,
     input_div_192: Cell<F>,
     input_mod_192_lt: LtGadget<F, 1>,
     input_mod_192_is_zero: IsZeroGadget<F>,
 
-    /// Number of pairs provided through EVM input. Since a maximum of 4 pairs can be supplied from
-    /// EVM, we need 3 binary bits for a max value of [1, 0, 0].
+    /// Number of pairs provided through EVM input. Since a maximum of `max_pairs` pairs can
+    /// be supplied from EVM, we need `N_PAIRS_BITS` binary bits to represent it.
     n_pairs: Cell<F>,
-    n_pairs_cmp: BinaryNumberGadget<F, 3>,
+    n_pairs_cmp: BinaryNumberGadget<F, N_PAIRS_BITS>,
     rand_pow_64: Cell<F>,
 
-    is_success: Cell<F>,
-    callee_address: Cell<F>,
-    is_root: Cell<F>,
-    call_data_offset: Cell<F>,
-    call_data_length: Cell<F>,
-    return_data_offset: Cell<F>,
-    return_data_length: Cell<F>,
+    max_pairs: usize,
+
+    call_context: CallContextGadget<F, 7>,
     restore_context: RestoreContextGadget<F>,
 }
 
@@ -74,6 +78,14 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
     const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileBn256Pairing;
 
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let max_pairs = cb.params().max_ec_pairing_pairs;
+        assert!(
+            max_pairs < 1 << N_PAIRS_BITS,
+            "ecPairing supports at most {} pairs, {max_pairs} configured",
+            (1 << N_PAIRS_BITS) - 1
+        );
+        let max_input_len = max_pairs * N_BYTES_PER_PAIR;
+
         let (input_bytes_rlc, output_bytes_rlc, return_bytes_rlc, output) = (
             cb.query_cell_phase2(),
             cb.query_cell_phase2(),
@@ -84,7 +96,9 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
         let n_pairs = cb.query_cell();
         let n_pairs_cmp = BinaryNumberGadget::construct(cb, n_pairs.expr());
 
-        let [is_success, callee_address, is_root, call_data_offset, call_data_length, return_data_offset, return_data_length] =
+        let call_context = CallContextGadget::construct(
+            cb,
+            None,
             [
                 CallContextFieldTag::IsSuccess,
                 CallContextFieldTag::CalleeAddress,
@@ -93,8 +107,10 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
                 CallContextFieldTag::CallDataLength,
                 CallContextFieldTag::ReturnDataOffset,
                 CallContextFieldTag::ReturnDataLength,
-            ]
-            .map(|tag| cb.call_context(None, tag));
+            ],
+        );
+        let [is_success, callee_address, is_root, _call_data_offset, call_data_length, _return_data_offset, _return_data_length] =
+            call_context.cells();
 
         cb.precompile_info_lookup(
             cb.execution_state().as_u64().expr(),
@@ -119,9 +135,10 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
 
         //////////////////////////////// INVALID BEGIN ////////////////////////////////
         let input_is_zero = IsZeroGadget::construct(cb, call_data_length.expr());
-        let input_lt_769 = LtGadget::construct(cb, call_data_length.expr(), 769.expr());
+        let input_lt_max =
+            LtGadget::construct(cb, call_data_length.expr(), (max_input_len + 1).expr());
         let (input_mod_192, input_div_192, input_mod_192_lt, input_mod_192_is_zero) = cb.condition(
-            and::expr([not::expr(input_is_zero.expr()), input_lt_769.expr()]),
+            and::expr([not::expr(input_is_zero.expr()), input_lt_max.expr()]),
             |cb| {
                 // r == len(input) % 192
                 let input_mod_192 = cb.query_byte();
@@ -131,9 +148,9 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
                 // q == len(input) // 192
                 let input_div_192 = cb.query_cell();
                 cb.require_in_set(
-                    "len(input) // 192 ∈ { 0, 1, 2, 3, 4 }",
+                    "len(input) // 192 ∈ { 0, 1, ..., max_pairs }",
                     input_div_192.expr(),
-                    vec![0.expr(), 1.expr(), 2.expr(), 3.expr(), 4.expr()],
+                    (0..=max_pairs).map(|q| q.expr()).collect(),
                 );
                 // q * 192 + r == call_data_length
                 cb.require_equal(
@@ -160,9 +177,9 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
         });
 
         cb.condition(
-            // (len(input) > 768) || (len(input) % 192 != 0)
+            // (len(input) > max_input_len) || (len(input) % 192 != 0)
             or::expr([
-                not::expr(input_lt_769.expr()),
+                not::expr(input_lt_max.expr()),
                 not::expr(input_mod_192_is_zero.expr()),
             ]),
             |cb| {
@@ -178,40 +195,25 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
 
         ///////////////////////////////// VALID BEGIN /////////////////////////////////
         let rand_pow_64 = cb.condition(
-            // (len(input) == 0) || ((len(input) <= 768) && (len(input) % 192 == 0))
+            // (len(input) == 0) || ((len(input) <= max_input_len) && (len(input) % 192 == 0))
             or::expr([
                 input_is_zero.expr(),
-                and::expr([input_lt_769.expr(), input_mod_192_is_zero.expr()]),
+                and::expr([input_lt_max.expr(), input_mod_192_is_zero.expr()]),
             ]),
             |cb| {
                 let rand_pow_64 = cb.query_cell_phase2();
-                let (rand_pow_192, rand_pow_384, rand_pow_576) = {
-                    let rand_pow_128 = rand_pow_64.expr() * rand_pow_64.expr();
-                    let rand_pow_192 = rand_pow_128.expr() * rand_pow_64.expr();
-                    let rand_pow_384 = rand_pow_192.expr() * rand_pow_192.expr();
-                    let rand_pow_576 = rand_pow_384.expr() * rand_pow_192.expr();
-                    (rand_pow_192, rand_pow_384, rand_pow_576)
-                };
+                let rand_pow_192 = rand_pow_64.expr() * rand_pow_64.expr() * rand_pow_64.expr();
                 cb.pow_of_rand_lookup(64.expr(), rand_pow_64.expr());
 
-                // RLC(inputs) that was processed in the ECC Circuit.
-                let ecc_circuit_input_rlc = select::expr(
-                    n_pairs_cmp.value_equals(0usize),
-                    0.expr(),
-                    select::expr(
-                        n_pairs_cmp.value_equals(1usize),
-                        input_bytes_rlc.expr() * rand_pow_576.expr(), /* 576 bytes padded */
-                        select::expr(
-                            n_pairs_cmp.value_equals(2usize),
-                            input_bytes_rlc.expr() * rand_pow_384.expr(), /* 384 bytes padded */
-                            select::expr(
-                                n_pairs_cmp.value_equals(3usize),
-                                input_bytes_rlc.expr() * rand_pow_192.expr(), /* 192 bytes padded */
-                                input_bytes_rlc.expr(),                       /* 0 bytes padded */
-                            ),
-                        ),
-                    ),
-                );
+                // RLC(inputs) that was processed in the ECC Circuit, where the input of n pairs is
+                // followed by (max_pairs - n) * 192 padding bytes.
+                let ecc_circuit_input_rlc = (1..=max_pairs)
+                    .map(|n| {
+                        let padding_pow =
+                            (n..max_pairs).fold(1.expr(), |pow, _| pow * rand_pow_192.expr());
+                        n_pairs_cmp.value_equals(n) * input_bytes_rlc.expr() * padding_pow
+                    })
+                    .fold(0.expr(), |acc, term| acc + term);
                 cb.condition(n_pairs_cmp.value_equals(0usize), |cb| {
                     cb.require_zero(
                         "ecPairing: n_pairs == 0 => evm input == 0",
@@ -248,16 +250,19 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
                 //      - point not on G1
                 //      - point not on G2
                 //
-                // In all the above, we know that len(input) % 192 == 0 and len(input) <= 768
+                // In all the above, we know that len(input) % 192 == 0 and
+                // len(input) <= max_input_len
                 cb.require_equal(
                     "ecPairing: n_pairs * N_BYTES_PER_PAIR == call_data_length",
                     n_pairs.expr() * N_BYTES_PER_PAIR.expr(),
                     call_data_length.expr(),
                 );
                 cb.require_in_set(
-                    "ecPairing: input_len ∈ { 0, 192, 384, 576, 768 }",
+                    "ecPairing: input_len ∈ { 0, 192, ..., max_input_len }",
                     call_data_length.expr(),
-                    vec![0.expr(), 192.expr(), 384.expr(), 576.expr(), 768.expr()],
+                    (0..=max_pairs)
+                        .map(|n| (n * N_BYTES_PER_PAIR).expr())
+                        .collect(),
                 );
 
                 rand_pow_64
@@ -281,7 +286,7 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
             output,
 
             input_is_zero,
-            input_lt_769,
+            input_lt_max,
             input_mod_192,
             input_div_192,
             input_mod_192_lt,
@@ -291,13 +296,9 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
             n_pairs_cmp,
             rand_pow_64,
 
-            is_success,
-            callee_address,
-            is_root,
-            call_data_offset,
-            call_data_length,
-            return_data_offset,
-            return_data_length,
+            max_pairs,
+
+            call_context,
             restore_context,
         }
     }
@@ -312,6 +313,7 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
         step: &ExecStep,
     ) -> Result<(), Error> {
         if let Some(PrecompileAuxData::EcPairing(res_aux_data)) = step.aux_data.clone() {
+            let max_input_len = (self.max_pairs * N_BYTES_PER_PAIR) as u64;
             let keccak_rand = region.challenges().keccak_input();
 
             // len(input) related assignment.
@@ -321,11 +323,11 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
                 "assign ec pairing exec step: calldata_len = {}",
                 call.call_data_length
             );
-            self.input_lt_769.assign(
+            self.input_lt_max.assign(
                 region,
                 offset,
                 F::from(call.call_data_length),
-                F::from(769),
+                F::from(max_input_len + 1),
             )?;
             let (input_div_192, input_mod_192) = (
                 call.call_data_length / (N_BYTES_PER_PAIR as u64),
@@ -343,8 +345,8 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
             match *res_aux_data {
                 Ok(aux_data) => {
                     debug_assert!(
-                        call.call_data_length <= (N_PAIRING_PER_OP * N_BYTES_PER_PAIR) as u64,
-                        "len(input) > 768"
+                        call.call_data_length <= max_input_len,
+                        "len(input) > max_input_len"
                     );
                     debug_assert!(
                         call.call_data_length % (N_BYTES_PER_PAIR as u64) == 0,
@@ -406,7 +408,7 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
                         "len(input) != call_data_length"
                     );
                     debug_assert!(
-                        (call.call_data_length > (N_PAIRING_PER_OP * N_BYTES_PER_PAIR) as u64)
+                        (call.call_data_length > max_input_len)
                             || (call.call_data_length % (N_BYTES_PER_PAIR as u64) != 0),
                         "len(input) is expected to be invalid",
                     );
@@ -428,38 +430,8 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
             return Err(Error::Synthesis);
         }
 
-        self.is_success.assign(
-            region,
-            offset,
-            Value::known(F::from(u64::from(call.is_success))),
-        )?;
-        self.callee_address.assign(
-            region,
-            offset,
-            Value::known(call.code_address.unwrap().to_scalar().unwrap()),
-        )?;
-        self.is_root
-            .assign(region, offset, Value::known(F::from(call.is_root as u64)))?;
-        self.call_data_offset.assign(
-            region,
-            offset,
-            Value::known(F::from(call.call_data_offset)),
-        )?;
-        self.call_data_length.assign(
-            region,
-            offset,
-            Value::known(F::from(call.call_data_length)),
-        )?;
-        self.return_data_offset.assign(
-            region,
-            offset,
-            Value::known(F::from(call.return_data_offset)),
-        )?;
-        self.return_data_length.assign(
-            region,
-            offset,
-            Value::known(F::from(call.return_data_length)),
-        )?;
+        self.call_context
+            .assign_exec_step(region, offset, block, step)?;
         self.restore_context
             .assign(region, offset, block, call, step, 7)
     }
@@ -2,7 +2,6 @@ use crate::util::Field;
 use bus_mapping::precompile::{PrecompileAuxData, PrecompileCalls};
 use eth_types::{evm_types::GasCost, word, ToLittleEndian, U256};
 use gadgets::util::{and, not, or, select, sum, Expr};
-use gadgets::ToScalar;
 use halo2_proofs::{
     circuit::Value,
     plonk::{Error, Expression},
@@ -15,7 +14,7 @@ use crate::{
         param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_MEMORY_ADDRESS, N_BYTES_WORD},
         step::ExecutionState,
         util::{
-            common_gadget::RestoreContextGadget,
+            common_gadget::{CallContextGadget, RestoreContextGadget},
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
             from_bytes,
             math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget, LtWordGadget, ModGadget},
@@ -61,13 +60,7 @@ pub struct EcrecoverGadget<F> {
     sig_v_eq27: IsEqualGadget<F>,
     sig_v_eq28: IsEqualGadget<F>,
 
-    is_success: Cell<F>,
-    callee_address: Cell<F>,
-    is_root: Cell<F>,
-    call_data_offset: Cell<F>,
-    call_data_length: Cell<F>,
-    return_data_offset: Cell<F>,
-    return_data_length: Cell<F>,
+    call_context: CallContextGadget<F, 7>,
     restore_context: RestoreContextGadget<F>,
 }
 
@@ -178,7 +171,9 @@ impl<F: Field> ExecutionGadget<F> for EcrecoverGadget<F> {
             cb.word_rlc::<N_BYTES_WORD>(FQ_MODULUS.to_le_bytes().map(|b| b.expr())),
         );
 
-        let [is_success, callee_address, is_root, call_data_offset, call_data_length, return_data_offset, return_data_length] =
+        let call_context = CallContextGadget::construct(
+            cb,
+            None,
             [
                 CallContextFieldTag::IsSuccess,
                 CallContextFieldTag::CalleeAddress,
@@ -187,8 +182,10 @@ impl<F: Field> ExecutionGadget<F> for EcrecoverGadget<F> {
                 CallContextFieldTag::CallDataLength,
                 CallContextFieldTag::ReturnDataOffset,
                 CallContextFieldTag::ReturnDataLength,
-            ]
-            .map(|tag| cb.call_context(None, tag));
+            ],
+        );
+        let [is_success, callee_address, is_root, _call_data_offset, call_data_length, _return_data_offset, _return_data_length] =
+            call_context.cells();
 
         let gas_cost = select::expr(
             is_success.expr(),
@@ -320,13 +317,7 @@ impl<F: Field> ExecutionGadget<F> for EcrecoverGadget<F> {
             sig_v_eq27,
             sig_v_eq28,
 
-            is_success,
-            callee_address,
-            is_root,
-            call_data_offset,
-            call_data_length,
-            return_data_offset,
-            return_data_length,
+            call_context,
             restore_context,
         }
     }
@@ -477,38 +468,8 @@ impl<F: Field> ExecutionGadget<F> for EcrecoverGadget<F> {
             return Err(Error::Synthesis);
         }
 
-        self.is_success.assign(
-            region,
-            offset,
-            Value::known(F::from(u64::from(call.is_success))),
-        )?;
-        self.callee_address.assign(
-            region,
-            offset,
-            Value::known(call.code_address.unwrap().to_scalar().unwrap()),
-        )?;
-        self.is_root
-            .assign(region, offset, Value::known(F::from(call.is_root as u64)))?;
-        self.call_data_offset.assign(
-            region,
-            offset,
-            Value::known(F::from(call.call_data_offset)),
-        )?;
-        self.call_data_length.assign(
-            region,
-            offset,
-            Value::known(F::from(call.call_data_length)),
-        )?;
-        self.return_data_offset.assign(
-            region,
-            offset,
-            Value::known(F::from(call.return_data_offset)),
-        )?;
-        self.return_data_length.assign(
-            region,
-            offset,
-            Value::known(F::from(call.return_data_length)),
-        )?;
+        self.call_context
+            .assign_exec_step(region, offset, block, step)?;
         self.restore_context
             .assign(region, offset, block, call, step, 7)
     }
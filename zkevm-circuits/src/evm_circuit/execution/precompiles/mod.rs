@@ -3,7 +3,7 @@ use crate::{
         execution::ExecutionGadget,
         step::ExecutionState,
         util::{
-            common_gadget::RestoreContextGadget,
+            common_gadget::{CallContextGadget, RestoreContextGadget},
             constraint_builder::{EVMConstraintBuilder, StepStateTransition, Transition},
             not, rlc, CachedRegion, Cell,
         },
@@ -14,7 +14,6 @@ use crate::{
 };
 use bus_mapping::precompile::PrecompileAuxData;
 use gadgets::util::{select, Expr};
-use gadgets::ToScalar;
 use halo2_proofs::{
     circuit::Value,
     plonk::{Error, Expression},
@@ -37,6 +36,9 @@ pub use ec_pairing::EcPairingGadget;
 mod identity;
 pub use identity::IdentityGadget;
 
+mod ripemd160;
+pub use ripemd160::Ripemd160Gadget;
+
 mod sha256;
 pub use sha256::SHA256Gadget;
 
@@ -87,13 +89,7 @@ pub struct BasePrecompileGadget<F, const S: ExecutionState> {
     output_bytes_rlc: Cell<F>,
     return_bytes_rlc: Cell<F>,
 
-    is_success: Cell<F>,
-    callee_address: Cell<F>,
-    is_root: Cell<F>,
-    call_data_offset: Cell<F>,
-    call_data_length: Cell<F>,
-    return_data_offset: Cell<F>,
-    return_data_length: Cell<F>,
+    call_context: CallContextGadget<F, 7>,
     restore_context: RestoreContextGadget<F>,
     gas_cost: Cell<F>,
 }
@@ -110,7 +106,9 @@ impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for BasePrecompileGad
             cb.query_cell_phase2(),
         );
         let gas_cost = cb.query_cell();
-        let [is_success, callee_address, is_root, call_data_offset, call_data_length, return_data_offset, return_data_length] =
+        let call_context = CallContextGadget::construct(
+            cb,
+            None,
             [
                 CallContextFieldTag::IsSuccess,
                 CallContextFieldTag::CalleeAddress,
@@ -119,8 +117,10 @@ impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for BasePrecompileGad
                 CallContextFieldTag::CallDataLength,
                 CallContextFieldTag::ReturnDataOffset,
                 CallContextFieldTag::ReturnDataLength,
-            ]
-            .map(|tag| cb.call_context(None, tag));
+            ],
+        );
+        let [is_success, callee_address, is_root, _call_data_offset, call_data_length, _return_data_offset, _return_data_length] =
+            call_context.cells();
 
         cb.precompile_info_lookup(
             cb.execution_state().as_u64().expr(),
@@ -129,7 +129,6 @@ impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for BasePrecompileGad
         );
 
         let last_callee_return_data_length = match Self::EXECUTION_STATE {
-            ExecutionState::PrecompileRipemd160 => 0x20,
             ExecutionState::PrecompileBlake2f => 0x40,
             _ => unreachable!("{} should not use the base gadget", Self::EXECUTION_STATE),
         };
@@ -151,13 +150,7 @@ impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for BasePrecompileGad
             output_bytes_rlc,
             return_bytes_rlc,
 
-            is_success,
-            callee_address,
-            is_root,
-            call_data_offset,
-            call_data_length,
-            return_data_offset,
-            return_data_length,
+            call_context,
             restore_context,
             gas_cost,
         }
@@ -199,40 +192,68 @@ impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for BasePrecompileGad
 
         self.gas_cost
             .assign(region, offset, Value::known(F::from(step.gas_cost)))?;
-        self.is_success.assign(
-            region,
-            offset,
-            Value::known(F::from(u64::from(call.is_success))),
-        )?;
-        self.callee_address.assign(
-            region,
-            offset,
-            Value::known(call.code_address.unwrap().to_scalar().unwrap()),
-        )?;
-        self.is_root
-            .assign(region, offset, Value::known(F::from(call.is_root as u64)))?;
-        self.call_data_offset.assign(
-            region,
-            offset,
-            Value::known(F::from(call.call_data_offset)),
-        )?;
-        self.call_data_length.assign(
-            region,
-            offset,
-            Value::known(F::from(call.call_data_length)),
-        )?;
-        self.return_data_offset.assign(
-            region,
-            offset,
-            Value::known(F::from(call.return_data_offset)),
-        )?;
-        self.return_data_length.assign(
-            region,
-            offset,
-            Value::known(F::from(call.return_data_length)),
-        )?;
+        self.call_context
+            .assign_exec_step(region, offset, block, step)?;
 
         self.restore_context
             .assign(region, offset, block, call, step, 7)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use bus_mapping::{evm::OpcodeId, mock::BlockData};
+    use eth_types::{geth_types::GethData, Word};
+    use mock::{
+        precompile::{precompile_calls_code, precompile_calls_gas, precompile_cases},
+        test_ctx::helpers::account_0_code_account_1_no_code,
+        TestContext, MOCK_ACCOUNTS,
+    };
+
+    #[test]
+    fn precompile_cases() {
+        for call_kind in [
+            OpcodeId::CALL,
+            OpcodeId::STATICCALL,
+            OpcodeId::DELEGATECALL,
+            OpcodeId::CALLCODE,
+        ] {
+            let ctx = || {
+                TestContext::<2, 1>::new(
+                    None,
+                    account_0_code_account_1_no_code(precompile_calls_code(call_kind)),
+                    |mut txs, accs| {
+                        txs[0]
+                            .from(accs[1].address)
+                            .to(accs[0].address)
+                            .gas(precompile_calls_gas().into());
+                    },
+                    |block, _tx| block,
+                )
+                .unwrap()
+            };
+
+            // the success of each call is stored at the slot of its case
+            let block: GethData = ctx().into();
+            let mut builder =
+                BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+            builder
+                .handle_block(&block.eth_block, &block.geth_traces)
+                .unwrap();
+            for (slot, case) in precompile_cases().iter().enumerate() {
+                let (_, success) = builder
+                    .sdb
+                    .get_storage(&MOCK_ACCOUNTS[0], &Word::from(slot));
+                assert_eq!(
+                    *success,
+                    Word::from(case.valid as u64),
+                    "{} with {call_kind:?}",
+                    case.name
+                );
+            }
+
+            CircuitTestBuilder::new_from_test_ctx(ctx()).run();
+        }
+    }
+}
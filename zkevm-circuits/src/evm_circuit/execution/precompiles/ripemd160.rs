@@ -0,0 +1,230 @@
+use crate::util::Field;
+use bus_mapping::precompile::PrecompileAuxData;
+use eth_types::evm_types::GasCost;
+use gadgets::util::{select, Expr};
+use halo2_proofs::plonk::Error;
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_MEMORY_WORD_SIZE, N_BYTES_WORD},
+        step::ExecutionState,
+        util::{
+            common_gadget::{CallContextGadget, RestoreContextGadget},
+            constraint_builder::EVMConstraintBuilder,
+            math_gadget::ConstantDivisionGadget,
+            rlc, CachedRegion, Cell,
+        },
+    },
+    table::CallContextFieldTag,
+    witness::{Block, Call, ExecStep, Transaction},
+};
+
+/// Gadget of the RIPEMD-160 precompile. The gas cost and the 32 bytes of
+/// return data of a successful call are constrained. The digest is left to a
+/// lookup into a RIPEMD-160 table, which the circuit doesn't have yet, so its
+/// rlc is only assigned.
+#[derive(Clone, Debug)]
+pub struct Ripemd160Gadget<F> {
+    input_bytes_rlc: Cell<F>,
+    output_bytes_rlc: Cell<F>,
+    return_bytes_rlc: Cell<F>,
+
+    input_word_size: ConstantDivisionGadget<F, N_BYTES_MEMORY_WORD_SIZE>,
+    call_context: CallContextGadget<F, 7>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for Ripemd160Gadget<F> {
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileRipemd160;
+
+    const NAME: &'static str = "RIPEMD160";
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let (input_bytes_rlc, output_bytes_rlc, return_bytes_rlc) = (
+            cb.query_cell_phase2(),
+            cb.query_cell_phase2(),
+            cb.query_cell_phase2(),
+        );
+        let call_context = CallContextGadget::construct(
+            cb,
+            None,
+            [
+                CallContextFieldTag::IsSuccess,
+                CallContextFieldTag::CalleeAddress,
+                CallContextFieldTag::IsRoot,
+                CallContextFieldTag::CallDataOffset,
+                CallContextFieldTag::CallDataLength,
+                CallContextFieldTag::ReturnDataOffset,
+                CallContextFieldTag::ReturnDataLength,
+            ],
+        );
+        let [is_success, callee_address, is_root, _call_data_offset, call_data_length, _return_data_offset, _return_data_length] =
+            call_context.cells();
+
+        let input_word_size = ConstantDivisionGadget::construct(
+            cb,
+            call_data_length.expr() + (N_BYTES_WORD - 1).expr(),
+            N_BYTES_WORD as u64,
+        );
+
+        // all gas sent to this call will be consumed if `is_success == false`.
+        let gas_cost = select::expr(
+            is_success.expr(),
+            GasCost::PRECOMPILE_RIPEMD160_BASE.expr()
+                + input_word_size.quotient() * GasCost::PRECOMPILE_RIPEMD160_PER_WORD.expr(),
+            cb.curr.state.gas_left.expr(),
+        );
+
+        cb.precompile_info_lookup(
+            cb.execution_state().as_u64().expr(),
+            callee_address.expr(),
+            cb.execution_state().precompile_base_gas_cost().expr(),
+        );
+
+        // the 20 bytes digest is returned left padded to a word
+        let restore_context = super::gen_restore_context(
+            cb,
+            is_root.expr(),
+            is_success.expr(),
+            gas_cost.expr(),
+            select::expr(is_success.expr(), 0x20.expr(), 0x00.expr()), // ReturnDataLength
+        );
+
+        Self {
+            input_bytes_rlc,
+            output_bytes_rlc,
+            return_bytes_rlc,
+
+            input_word_size,
+            call_context,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        if let Some(PrecompileAuxData::Base {
+            input_bytes,
+            output_bytes,
+            return_bytes,
+        }) = &step.aux_data
+        {
+            for (cell, bytes) in [
+                (&self.input_bytes_rlc, input_bytes),
+                (&self.output_bytes_rlc, output_bytes),
+                (&self.return_bytes_rlc, return_bytes),
+            ] {
+                cell.assign(
+                    region,
+                    offset,
+                    region
+                        .challenges()
+                        .keccak_input()
+                        .map(|r| rlc::value(bytes.iter().rev(), r)),
+                )?;
+            }
+        } else {
+            log::error!("unexpected aux_data {:?} for ripemd160", step.aux_data);
+            return Err(Error::Synthesis);
+        }
+        self.input_word_size.assign(
+            region,
+            offset,
+            (call.call_data_length + (N_BYTES_WORD as u64) - 1).into(),
+        )?;
+        self.call_context
+            .assign_exec_step(region, offset, block, step)?;
+        self.restore_context
+            .assign(region, offset, block, call, step, 7)
+    }
+}
+
+// RIPEMD-160 is disabled in scroll, calls to it fail with all the gas consumed.
+#[cfg(test)]
+#[cfg(not(feature = "scroll"))]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use bus_mapping::{
+        evm::{OpcodeId, PrecompileCallArgs},
+        precompile::PrecompileCalls,
+    };
+    use eth_types::{bytecode, word, ToWord};
+    use itertools::Itertools;
+    use mock::TestContext;
+    use std::sync::LazyLock;
+
+    static TEST_VECTOR: LazyLock<Vec<PrecompileCallArgs>> = LazyLock::new(|| {
+        vec![
+            PrecompileCallArgs {
+                name: "simple success",
+                setup_code: bytecode! {
+                    // place params in memory
+                    PUSH3(0x616263)
+                    PUSH1(0x00)
+                    MSTORE
+                },
+                call_data_offset: 0x1d.into(),
+                call_data_length: 0x03.into(),
+                ret_offset: 0x20.into(),
+                ret_size: 0x20.into(),
+                address: PrecompileCalls::Ripemd160.address().to_word(),
+                ..Default::default()
+            },
+            PrecompileCallArgs {
+                name: "nil success",
+                setup_code: bytecode! {},
+                call_data_offset: 0x00.into(),
+                call_data_length: 0x00.into(),
+                ret_offset: 0x20.into(),
+                ret_size: 0x20.into(),
+                address: PrecompileCalls::Ripemd160.address().to_word(),
+                ..Default::default()
+            },
+            PrecompileCallArgs {
+                name: "multi-words truncated return",
+                setup_code: bytecode! {
+                    // place params in memory
+                    PUSH32(word!("0x6161616161616161616161616161616161616161616161616161616161616161"))
+                    PUSH1(0x00)
+                    MSTORE
+                    PUSH32(word!("0x6161616161616161616161616161616161616161616161616161616161616161"))
+                    PUSH1(0x20)
+                    MSTORE
+                },
+                call_data_offset: 0x00.into(),
+                call_data_length: 0x41.into(),
+                ret_offset: 0x20.into(),
+                ret_size: 0x10.into(),
+                address: PrecompileCalls::Ripemd160.address().to_word(),
+                ..Default::default()
+            },
+        ]
+    });
+
+    #[test]
+    fn precompile_ripemd160_test() {
+        let call_kinds = vec![
+            OpcodeId::CALL,
+            OpcodeId::STATICCALL,
+            OpcodeId::DELEGATECALL,
+            OpcodeId::CALLCODE,
+        ];
+
+        for (test_vector, &call_kind) in TEST_VECTOR.iter().cartesian_product(&call_kinds) {
+            let bytecode = test_vector.with_call_op(call_kind);
+
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+            )
+            .run();
+        }
+    }
+}
@@ -2,8 +2,7 @@ use crate::util::Field;
 use bus_mapping::precompile::PrecompileAuxData;
 use eth_types::evm_types::GasCost;
 use gadgets::util::{select, Expr};
-use gadgets::ToScalar;
-use halo2_proofs::{circuit::Value, plonk::Error};
+use halo2_proofs::plonk::Error;
 
 use crate::{
     evm_circuit::{
@@ -11,8 +10,10 @@ use crate::{
         param::{N_BYTES_MEMORY_WORD_SIZE, N_BYTES_WORD},
         step::ExecutionState,
         util::{
-            common_gadget::RestoreContextGadget, constraint_builder::EVMConstraintBuilder,
-            math_gadget::ConstantDivisionGadget, rlc, CachedRegion, Cell,
+            common_gadget::{CallContextGadget, RestoreContextGadget},
+            constraint_builder::EVMConstraintBuilder,
+            math_gadget::ConstantDivisionGadget,
+            rlc, CachedRegion, Cell,
         },
     },
     table::CallContextFieldTag,
@@ -26,13 +27,7 @@ pub struct SHA256Gadget<F> {
     return_bytes_rlc: Cell<F>,
 
     input_word_size: ConstantDivisionGadget<F, N_BYTES_MEMORY_WORD_SIZE>,
-    is_success: Cell<F>,
-    callee_address: Cell<F>,
-    is_root: Cell<F>,
-    call_data_offset: Cell<F>,
-    call_data_length: Cell<F>,
-    return_data_offset: Cell<F>,
-    return_data_length: Cell<F>,
+    call_context: CallContextGadget<F, 7>,
     restore_context: RestoreContextGadget<F>,
 }
 
@@ -47,7 +42,9 @@ impl<F: Field> ExecutionGadget<F> for SHA256Gadget<F> {
             cb.query_cell_phase2(),
             cb.query_cell_phase2(),
         );
-        let [is_success, callee_address, is_root, call_data_offset, call_data_length, return_data_offset, return_data_length] =
+        let call_context = CallContextGadget::construct(
+            cb,
+            None,
             [
                 CallContextFieldTag::IsSuccess,
                 CallContextFieldTag::CalleeAddress,
@@ -56,8 +53,10 @@ impl<F: Field> ExecutionGadget<F> for SHA256Gadget<F> {
                 CallContextFieldTag::CallDataLength,
                 CallContextFieldTag::ReturnDataOffset,
                 CallContextFieldTag::ReturnDataLength,
-            ]
-            .map(|tag| cb.call_context(None, tag));
+            ],
+        );
+        let [is_success, callee_address, is_root, _call_data_offset, call_data_length, _return_data_offset, _return_data_length] =
+            call_context.cells();
 
         let input_word_size = ConstantDivisionGadget::construct(
             cb,
@@ -101,13 +100,7 @@ impl<F: Field> ExecutionGadget<F> for SHA256Gadget<F> {
             return_bytes_rlc,
 
             input_word_size,
-            is_success,
-            callee_address,
-            is_root,
-            call_data_offset,
-            call_data_length,
-            return_data_offset,
-            return_data_length,
+            call_context,
             restore_context,
         }
     }
@@ -170,38 +163,8 @@ impl<F: Field> ExecutionGadget<F> for SHA256Gadget<F> {
             offset,
             (call.call_data_length + (N_BYTES_WORD as u64) - 1).into(),
         )?;
-        self.is_success.assign(
-            region,
-            offset,
-            Value::known(F::from(u64::from(call.is_success))),
-        )?;
-        self.callee_address.assign(
-            region,
-            offset,
-            Value::known(call.code_address.unwrap().to_scalar().unwrap()),
-        )?;
-        self.is_root
-            .assign(region, offset, Value::known(F::from(call.is_root as u64)))?;
-        self.call_data_offset.assign(
-            region,
-            offset,
-            Value::known(F::from(call.call_data_offset)),
-        )?;
-        self.call_data_length.assign(
-            region,
-            offset,
-            Value::known(F::from(call.call_data_length)),
-        )?;
-        self.return_data_offset.assign(
-            region,
-            offset,
-            Value::known(F::from(call.return_data_offset)),
-        )?;
-        self.return_data_length.assign(
-            region,
-            offset,
-            Value::known(F::from(call.return_data_length)),
-        )?;
+        self.call_context
+            .assign_exec_step(region, offset, block, step)?;
         self.restore_context
             .assign(region, offset, block, call, step, 7)
     }
@@ -376,4 +339,34 @@ mod test {
             .run();
         }
     }
+
+    // inputs spanning several 512-bit blocks, around the padding boundaries
+    #[test]
+    fn precompile_sha256_long_input_test() {
+        for (input_len, blocks) in [(55, 1), (56, 2), (64, 2), (119, 2), (120, 3), (1000, 16)] {
+            let bytecode = PrecompileCallArgs {
+                name: "long input",
+                setup_code: bytecode! {},
+                call_data_offset: 0x00.into(),
+                call_data_length: input_len.into(),
+                ret_offset: 0x00.into(),
+                ret_size: 0x20.into(),
+                address: PrecompileCalls::Sha256.address().to_word(),
+                ..Default::default()
+            }
+            .with_call_op(OpcodeId::STATICCALL);
+
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+            )
+            .block_modifier(Box::new(move |blk| {
+                let evts = blk.get_sha256();
+                assert_eq!(evts.len(), 1);
+                assert_eq!(evts[0].input.len(), input_len);
+                assert_eq!(evts[0].blocks(), blocks);
+                assert_eq!(evts[0].block_digests.last(), Some(&evts[0].digest));
+            }))
+            .run();
+        }
+    }
 }
@@ -23,8 +23,7 @@ use crate::{
 };
 use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
 use eth_types::evm_types::GasCost;
-use gadgets::util::not;
-use gadgets::ToScalar;
+use gadgets::{util::not, ToScalar};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 #[derive(Clone, Debug)]
@@ -179,12 +178,12 @@ impl<F: Field> ExecutionGadget<F> for ReturnDataCopyGadget<F> {
             [0, 1, 2].map(|i| block.rws[step.rw_indices[i as usize]].stack_value());
 
         let [last_callee_id, return_data_offset, return_data_size] = [
-            (3, CallContextFieldTag::LastCalleeId),
-            (4, CallContextFieldTag::LastCalleeReturnDataOffset),
-            (5, CallContextFieldTag::LastCalleeReturnDataLength),
+            CallContextFieldTag::LastCalleeId,
+            CallContextFieldTag::LastCalleeReturnDataOffset,
+            CallContextFieldTag::LastCalleeReturnDataLength,
         ]
-        .map(|(i, tag)| {
-            let rw = block.rws[step.rw_indices[i as usize]];
+        .map(|tag| {
+            let rw = block.rws[step.rw(tag)];
             assert_eq!(rw.field_tag(), Some(tag as u64));
             rw.call_context_value()
         });
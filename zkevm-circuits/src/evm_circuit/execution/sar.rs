@@ -454,6 +454,34 @@ mod test {
         test_ok(0x100.into(), MAX_POS);
     }
 
+    #[test]
+    fn test_sar_gadget_with_shift_matrix() {
+        // Shifts with the amount encoded in higher bytes, including ones whose
+        // lowest byte alone would be a valid shift, against positive and
+        // negative values to check the sign extension.
+        let shifts = [
+            U256::from(255),
+            U256::from(256),
+            U256::from(256 + 8),
+            U256::one() << 200,
+            (U256::one() << 200) + 8,
+            *NEG_SIGN,
+            MAX_NEG,
+        ];
+        let values = [
+            U256::one(),
+            MAX_POS,
+            *NEG_SIGN,
+            NEG_SIGN.checked_add(0x1234.into()).unwrap(),
+            MAX_NEG,
+        ];
+        for shift in shifts {
+            for a in values {
+                test_ok(shift, a);
+            }
+        }
+    }
+
     fn test_ok(shift: U256, a: U256) {
         let bytecode = bytecode! {
             PUSH32(a)
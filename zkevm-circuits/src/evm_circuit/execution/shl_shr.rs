@@ -259,4 +259,27 @@ mod test {
         test_ok(OpcodeId::SHR, max_word, Word::from(129));
         test_ok(OpcodeId::SHR, rand_word(), rand_word());
     }
+
+    #[test]
+    fn shl_shr_gadget_shift_matrix() {
+        // Shifts with the amount encoded in higher bytes, including ones whose
+        // lowest byte alone would be a valid shift.
+        let shifts = [
+            Word::from(255),
+            Word::from(256),
+            Word::from(256 + 8),
+            Word::one() << 200,
+            (Word::one() << 200) + 8,
+            Word::one() << 255,
+            Word::MAX,
+        ];
+        let values = [Word::one(), Word::from(0xABCD) << 240, Word::MAX];
+        for opcode in [OpcodeId::SHL, OpcodeId::SHR] {
+            for shift in shifts {
+                for value in values {
+                    test_ok(opcode, value, shift);
+                }
+            }
+        }
+    }
 }
@@ -4,13 +4,9 @@ use crate::{
         step::ExecutionState,
         table::{FixedTableTag, Lookup},
         util::{
-            and,
             common_gadget::SameContextGadget,
-            constraint_builder::{
-                ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
-                Transition::Delta,
-            },
-            math_gadget::{IsEqualGadget, IsZeroGadget},
+            constraint_builder::{EVMConstraintBuilder, StepStateTransition, Transition::Delta},
+            math_gadget::IsZeroGadget,
             rlc, select, sum, CachedRegion, Cell, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -29,7 +25,6 @@ pub(crate) struct SignextendGadget<F> {
     value: Word<F>,
     sign_byte: Cell<F>,
     is_msb_sum_zero: IsZeroGadget<F>,
-    is_byte_selected: [IsEqualGadget<F>; 31],
     selectors: [Cell<F>; 31],
 }
 
@@ -44,55 +39,46 @@ impl<F: Field> ExecutionGadget<F> for SignextendGadget<F> {
         let sign_byte = cb.query_cell();
         let selectors = array_init(|_| cb.query_bool());
 
-        // Generate the selectors.
         // If any of the non-LSB bytes of the index word are non-zero we never
         // need to do any changes. So just sum all the non-LSB byte
-        // values here and then check if it's non-zero so we can use
-        // that as an additional condition to enable the selector.
+        // values here and then check if it's non-zero, in which case the
+        // index is treated as 31, which changes no byte.
         let is_msb_sum_zero = IsZeroGadget::construct(cb, sum::expr(&index.cells[1..32]));
+        let index_lsb = select::expr(is_msb_sum_zero.expr(), index.cells[0].expr(), 31.expr());
 
-        // Check if this byte is selected looking only at the LSB of the index
-        // word
-        let is_byte_selected =
-            array_init(|idx| IsEqualGadget::construct(cb, index.cells[0].expr(), idx.expr()));
-
-        // We need to find the byte we have to get the sign from so we can
-        // extend correctly. We go byte by byte and check if `idx ==
-        // index[0]`. If they are equal (at most once) we add the byte
-        // value to the sum, else we add 0. We also generate the
-        // selectors, which we'll use to decide if we need to
-        // replace bytes with the sign byte.
-        // There is no need to check the MSB, even if the MSB is selected no
-        // bytes need to be changed.
-        let mut selected_byte = 0.expr();
-        for idx in 0..31 {
-            // Check if this byte is selected
-            // The additional condition for this is that none of the non-LSB
-            // bytes are non-zero (see above).
-            let is_selected = and::expr(&[is_byte_selected[idx].expr(), is_msb_sum_zero.expr()]);
-
-            // Add the byte to the sum when this byte is selected
-            selected_byte = selected_byte + (is_selected.clone() * value.cells[idx].expr());
+        // The selector `idx` is enabled when byte `idx + 1` needs to change to
+        // the sign byte, which is the case for all the bytes after the
+        // selected one. The selectors packed as bits must match the mask of
+        // the index in the fixed table.
+        cb.add_lookup(
+            "SignextendMask lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::SignextendMask.expr(),
+                values: [
+                    index_lsb,
+                    sum::expr(
+                        selectors
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, selector)| selector.expr() * (1u64 << idx).expr()),
+                    ),
+                    0.expr(),
+                ],
+            },
+        );
 
-            // Verify the selector.
-            // Cells are used here to store intermediate results, otherwise
-            // these sums are very long expressions.
-            // The selector for a byte position is enabled when its value needs
-            // to change to the sign byte. Once a byte was selected,
-            // all following bytes need to be replaced as well, so a
-            // selector is the sum of the current and all previous `is_selected`
-            // values.
-            cb.require_equal(
-                "Constrain selector == 1 when is_selected == 1 || previous selector == 1",
-                is_selected.clone()
-                    + if idx > 0 {
-                        selectors[idx - 1].expr()
-                    } else {
-                        0.expr()
-                    },
-                selectors[idx].expr(),
-            );
-        }
+        // The selectors are all disabled up to the selected byte and all
+        // enabled after it, so the selected byte is the only one where a
+        // selector differs from the previous one. There is no need to check
+        // the MSB, even if the MSB is selected no bytes need to be changed.
+        let selected_byte = sum::expr((0..31).map(|idx| {
+            let is_selected = if idx > 0 {
+                selectors[idx].expr() - selectors[idx - 1].expr()
+            } else {
+                selectors[idx].expr()
+            };
+            is_selected * value.cells[idx].expr()
+        }));
 
         // Lookup the sign byte.
         // This will use the most significant bit of the selected byte to return
@@ -149,7 +135,6 @@ impl<F: Field> ExecutionGadget<F> for SignextendGadget<F> {
             value,
             sign_byte,
             is_msb_sum_zero,
-            is_byte_selected,
             selectors,
         }
     }
@@ -175,32 +160,26 @@ impl<F: Field> ExecutionGadget<F> for SignextendGadget<F> {
         let msb_sum_zero =
             self.is_msb_sum_zero
                 .assign(region, offset, sum::value(&index[1..32]))?;
-        let mut previous_selector_value: F = 0.into();
-        for i in 0..31 {
-            let selected = and::value(vec![
-                self.is_byte_selected[i].assign(
-                    region,
-                    offset,
-                    F::from(index[0] as u64),
-                    F::from(i as u64),
-                )?,
-                msb_sum_zero,
-            ]);
-            let selector_value = selected + previous_selector_value;
-            self.selectors[i]
-                .assign(region, offset, Value::known(selector_value))
-                .unwrap();
-            previous_selector_value = selector_value;
+        let index_lsb = if msb_sum_zero == F::one() {
+            index[0] as usize
+        } else {
+            31
+        };
+        for (idx, selector) in self.selectors.iter().enumerate() {
+            selector.assign(
+                region,
+                offset,
+                Value::known(F::from((idx >= index_lsb) as u64)),
+            )?;
         }
 
         // Set the sign byte
         let mut sign = 0u64;
-        if index[0] < 31 && msb_sum_zero == F::one() {
-            sign = (value[index[0] as usize] >> 7) as u64;
+        if index_lsb < 31 {
+            sign = (value[index_lsb] >> 7) as u64;
         }
         self.sign_byte
-            .assign(region, offset, Value::known(F::from(sign * 0xFF)))
-            .unwrap();
+            .assign(region, offset, Value::known(F::from(sign * 0xFF)))?;
 
         Ok(())
     }
@@ -270,6 +249,25 @@ mod test {
         );
     }
 
+    #[test]
+    fn signextend_gadget_large_index() {
+        let value = Word::from_little_endian(&[0x80u8; 32]);
+        // Indexes of 31 and above, including ones encoded in the higher bytes
+        // whose lowest byte alone would select a byte, leave the value as is.
+        for index in [
+            Word::from(31),
+            Word::from(32),
+            Word::from(255),
+            Word::from(256),
+            Word::from(256 + 2),
+            Word::one() << 200,
+            (Word::one() << 200) + 2,
+            Word::MAX,
+        ] {
+            test_ok(index, value, value);
+        }
+    }
+
     #[test]
     #[ignore]
     fn signextend_gadget_exhaustive() {
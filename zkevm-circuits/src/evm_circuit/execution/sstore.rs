@@ -5,14 +5,15 @@ use crate::{
         step::ExecutionState,
         util::{
             common_gadget::{
-                cal_sstore_gas_cost_for_assignment, SameContextGadget, SstoreGasGadget,
+                cal_sstore_gas_cost_for_assignment, SameContextGadget, SstoreCaseGadget,
+                SstoreGasGadget,
             },
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::Delta,
             },
-            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget},
-            not, CachedRegion, Cell,
+            math_gadget::LtGadget,
+            CachedRegion, Cell,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -121,13 +122,7 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
         );
 
         let tx_refund_prev = cb.query_cell();
-        let tx_refund = SstoreTxRefundGadget::construct(
-            cb,
-            tx_refund_prev.clone(),
-            phase2_value.clone(),
-            phase2_value_prev.clone(),
-            phase2_original_value.clone(),
-        );
+        let tx_refund = SstoreTxRefundGadget::construct(tx_refund_prev.clone(), gas_cost.case());
         cb.tx_refund_write(
             tx_id.expr(),
             tx_refund.expr(),
@@ -248,79 +243,15 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
 pub(crate) struct SstoreTxRefundGadget<F> {
     tx_refund_old: Cell<F>,
     tx_refund_new: Expression<F>,
-    value: Cell<F>,
-    value_prev: Cell<F>,
-    original_value: Cell<F>,
-    value_prev_is_zero_gadget: IsZeroGadget<F>,
-    value_is_zero_gadget: IsZeroGadget<F>,
-    original_is_zero_gadget: IsZeroGadget<F>,
-    original_eq_value_gadget: IsEqualGadget<F>,
-    prev_eq_value_gadget: IsEqualGadget<F>,
-    original_eq_prev_gadget: IsEqualGadget<F>,
 }
 
 impl<F: Field> SstoreTxRefundGadget<F> {
-    pub(crate) fn construct(
-        cb: &mut EVMConstraintBuilder<F>,
-        tx_refund_old: Cell<F>,
-        value: Cell<F>,
-        value_prev: Cell<F>,
-        original_value: Cell<F>,
-    ) -> Self {
-        let value_prev_is_zero_gadget = IsZeroGadget::construct(cb, value_prev.expr());
-        let value_is_zero_gadget = IsZeroGadget::construct(cb, value.expr());
-        let original_is_zero_gadget = IsZeroGadget::construct(cb, original_value.expr());
-
-        let original_eq_value_gadget =
-            IsEqualGadget::construct(cb, original_value.expr(), value.expr());
-        let prev_eq_value_gadget = IsEqualGadget::construct(cb, value_prev.expr(), value.expr());
-        let original_eq_prev_gadget =
-            IsEqualGadget::construct(cb, original_value.expr(), value_prev.expr());
-
-        let value_prev_is_zero = value_prev_is_zero_gadget.expr();
-        let value_is_zero = value_is_zero_gadget.expr();
-        let original_is_zero = original_is_zero_gadget.expr();
-
-        let original_eq_value = original_eq_value_gadget.expr();
-        let prev_eq_value = prev_eq_value_gadget.expr();
-        let original_eq_prev = original_eq_prev_gadget.expr();
-
-        // (value_prev != value) && (original_value != 0) && (value ==
-        // 0)
-        let delete_slot =
-            not::expr(prev_eq_value.clone()) * not::expr(original_is_zero.clone()) * value_is_zero;
-        // (value_prev != value) && (original_value == value) && (original_value !=
-        // 0)
-        let reset_existing = not::expr(prev_eq_value.clone())
-            * original_eq_value.clone()
-            * not::expr(original_is_zero.clone());
-        // (value_prev != value) && (original_value == value) && (original_value ==
-        // 0)
-        let reset_inexistent =
-            not::expr(prev_eq_value.clone()) * original_eq_value * (original_is_zero);
-        // (value_prev != value) && (original_value != value_prev) && (value_prev ==
-        // 0)
-        let recreate_slot =
-            not::expr(prev_eq_value) * not::expr(original_eq_prev) * (value_prev_is_zero);
-
-        let tx_refund_new = tx_refund_old.expr()
-            + delete_slot * GasCost::SSTORE_CLEARS_SCHEDULE.expr()
-            + reset_existing * (GasCost::SSTORE_RESET.expr() - GasCost::WARM_ACCESS.expr())
-            + reset_inexistent * (GasCost::SSTORE_SET.expr() - GasCost::WARM_ACCESS.expr())
-            - recreate_slot * (GasCost::SSTORE_CLEARS_SCHEDULE.expr());
+    pub(crate) fn construct(tx_refund_old: Cell<F>, case: &SstoreCaseGadget<F>) -> Self {
+        let tx_refund_new = tx_refund_old.expr() + case.refund_delta();
 
         Self {
             tx_refund_old,
             tx_refund_new,
-            value,
-            value_prev,
-            original_value,
-            value_prev_is_zero_gadget,
-            value_is_zero_gadget,
-            original_is_zero_gadget,
-            original_eq_value_gadget,
-            prev_eq_value_gadget,
-            original_eq_prev_gadget,
         }
     }
 
@@ -340,40 +271,9 @@ impl<F: Field> SstoreTxRefundGadget<F> {
         value_prev: eth_types::Word,
         original_value: eth_types::Word,
     ) -> Result<(), Error> {
+        // the case is assigned by `SstoreGasGadget`
         self.tx_refund_old
             .assign(region, offset, Value::known(F::from(tx_refund_old)))?;
-        self.value.assign(region, offset, region.word_rlc(value))?;
-        self.value_prev
-            .assign(region, offset, region.word_rlc(value_prev))?;
-        self.original_value
-            .assign(region, offset, region.word_rlc(original_value))?;
-        self.value_prev_is_zero_gadget
-            .assign_value(region, offset, region.word_rlc(value_prev))?;
-        self.value_is_zero_gadget
-            .assign_value(region, offset, region.word_rlc(value))?;
-        self.original_is_zero_gadget.assign_value(
-            region,
-            offset,
-            region.word_rlc(original_value),
-        )?;
-        self.original_eq_value_gadget.assign_value(
-            region,
-            offset,
-            region.word_rlc(original_value),
-            region.word_rlc(value),
-        )?;
-        self.prev_eq_value_gadget.assign_value(
-            region,
-            offset,
-            region.word_rlc(value_prev),
-            region.word_rlc(value),
-        )?;
-        self.original_eq_prev_gadget.assign_value(
-            region,
-            offset,
-            region.word_rlc(original_value),
-            region.word_rlc(value_prev),
-        )?;
         debug_assert_eq!(
             calc_expected_tx_refund(tx_refund_old, value, value_prev, original_value),
             tx_refund
@@ -389,7 +289,10 @@ mod test {
 
     use crate::test_util::CircuitTestBuilder;
     use eth_types::{bytecode, Word};
-    use mock::{test_ctx::helpers::tx_from_1_to_0, TestContext, MOCK_ACCOUNTS};
+    use mock::{
+        generate_mock_call_bytecode, test_ctx::helpers::tx_from_1_to_0, MockCallBytecodeParams,
+        TestContext, MOCK_ACCOUNTS,
+    };
 
     #[test]
     fn sstore_gadget_no_refund() {
@@ -452,6 +355,53 @@ mod test {
         );
     }
 
+    #[test]
+    fn sstore_gadget_refund_reverted_in_sub_call() {
+        // the refund of the callee's slot clear is reverted with the callee, so
+        // EndTx only reads the refund of the caller's slot clear
+        let code_b = bytecode! {
+            PUSH1(0x00)
+            PUSH1(0x00)
+            SSTORE
+            .op_revert(0x00, 0x00)
+        };
+        let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+            address: MOCK_ACCOUNTS[1],
+            pushdata: vec![0x01; 32],
+            gas: 0x4_0000,
+            instructions_after_call: bytecode! {
+                PUSH1(0x00)
+                PUSH1(0x00)
+                SSTORE
+            },
+            ..MockCallBytecodeParams::default()
+        });
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .code(code_a)
+                    .storage(vec![(Word::zero(), Word::one())].into_iter());
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .code(code_b)
+                    .storage(vec![(Word::zero(), Word::one())].into_iter());
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _txs| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     fn test_ok(key: Word, value: Word, value_prev: Word, original_value: Word) {
         // Here we use two bytecodes to test both is_persistent(STOP) or not(REVERT)
         // Besides, in bytecode we use two SSTOREs,
@@ -67,6 +67,8 @@ impl<F: Field> ExecutionGadget<F> for StopGadget<F> {
             cb.require_step_state_transition(StepStateTransition {
                 call_id: Same,
                 rw_counter: Delta(1.expr()),
+                // EndTx checks the log count of the receipt against log_id
+                log_id: Same,
                 end_tx: To(1.expr()),
                 ..StepStateTransition::any()
             });
@@ -125,7 +125,9 @@ pub(crate) const N_BYTES_MEMORY_WORD_SIZE: usize = 4;
 /// The size of a chunk of memory that is accessed at once in RW lookups.
 pub(crate) const N_BYTES_MEMORY_CHUNK: usize = N_BYTES_WORD;
 
-pub(crate) const STACK_CAPACITY: usize = 1024;
+/// Stack pointer of an empty stack. The stack limits of the chains proven by
+/// the circuits can not exceed it.
+pub(crate) const STACK_CAPACITY: usize = eth_types::evm_types::STACK_LIMIT;
 
 // Number of bytes that will be used of program counter. Although the maximum
 // size of execution bytecode could be at most 128kB due to the size limit of a
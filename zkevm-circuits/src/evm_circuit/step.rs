@@ -34,112 +34,115 @@ impl From<PrecompileCalls> for ExecutionState {
     }
 }
 
-#[allow(non_camel_case_types)]
-#[derive(ConstParamTy, Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
-pub enum ExecutionState {
-    // Internal state
-    BeginTx,
-    EndTx,
-    EndInnerBlock,
-    EndBlock,
-    Padding,
-    // Opcode successful cases
-    STOP,
-    ADD_SUB,     // ADD, SUB
-    MUL_DIV_MOD, // MUL, DIV, MOD
-    SDIV_SMOD,   // SDIV, SMOD
-    SHL_SHR,     // SHL, SHR
-    ADDMOD,
-    MULMOD,
-    EXP,
-    SIGNEXTEND,
-    CMP,  // LT, GT, EQ
-    SCMP, // SLT, SGT
-    ISZERO,
-    BITWISE, // AND, OR, XOR
-    NOT,
-    BYTE,
-    SAR,
-    SHA3,
-    ADDRESS,
-    BALANCE,
-    ORIGIN,
-    CALLER,
-    CALLVALUE,
-    CALLDATALOAD,
-    CALLDATASIZE,
-    CALLDATACOPY,
-    CODESIZE,
-    CODECOPY,
-    GASPRICE,
-    EXTCODESIZE,
-    EXTCODECOPY,
-    RETURNDATASIZE,
-    RETURNDATACOPY,
-    EXTCODEHASH,
-    BLOCKHASH,
-    BLOCKCTXU64,  // TIMESTAMP, NUMBER, GASLIMIT
-    BLOCKCTXU160, // COINBASE
-    BLOCKCTXU256, // BASEFEE, DIFFICULTY (for non-scroll)
-    #[cfg(feature = "scroll")]
-    DIFFICULTY, // DIFFICULTY
-    CHAINID,
-    SELFBALANCE,
-    POP,
-    MEMORY, // MLOAD, MSTORE, MSTORE8
-    MCOPY,
-    SLOAD,
-    SSTORE,
-    JUMP,
-    JUMPI,
-    PC,
-    MSIZE,
-    GAS,
-    JUMPDEST,
-    TLOAD,
-    TSTORE,
-    PUSH, // PUSH0, PUSH1, PUSH2, ..., PUSH32
-    DUP,  // DUP1, DUP2, ..., DUP16
-    SWAP, // SWAP1, SWAP2, ..., SWAP16
-    LOG,  // LOG0, LOG1, ..., LOG4
-    CREATE,
-    CREATE2,
-    CALL_OP,       // CALL, CALLCODE, DELEGATECALL, STATICCALL
-    RETURN_REVERT, // RETURN, REVERT
-    SELFDESTRUCT,
-    // Error cases
-    ErrorInvalidOpcode,
-    ErrorStack,
-    ErrorWriteProtection,
-    ErrorInvalidCreationCode,
-    ErrorInvalidJump,
-    ErrorReturnDataOutOfBound,
-    ErrorPrecompileFailed,
-    ErrorOutOfGasConstant,
-    ErrorOutOfGasStaticMemoryExpansion,
-    ErrorOutOfGasDynamicMemoryExpansion,
-    ErrorOutOfGasMemoryCopy,
-    ErrorOutOfGasAccountAccess,
-    // error for CodeStoreOOG and MaxCodeSizeExceeded
-    ErrorCodeStore,
-    ErrorOutOfGasLOG,
-    ErrorOutOfGasEXP,
-    ErrorOutOfGasSHA3,
-    ErrorOutOfGasCall,
-    ErrorOutOfGasPrecompile,
-    ErrorOutOfGasSloadSstore,
-    ErrorOutOfGasCREATE,
-    ErrorOutOfGasSELFDESTRUCT,
-    // Precompiles
-    PrecompileEcrecover,
-    PrecompileSha256,
-    PrecompileRipemd160,
-    PrecompileIdentity,
-    PrecompileBigModExp,
-    PrecompileBn256Add,
-    PrecompileBn256ScalarMul,
-    PrecompileBn256Pairing,
-    PrecompileBlake2f,
+binary_number_tag! {
+    #[allow(non_camel_case_types)]
+    #[derive(ConstParamTy, Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
+    pub enum ExecutionState {
+        // Internal state
+        BeginTx,
+        EndTx,
+        EndInnerBlock,
+        EndBlock,
+        Padding,
+        // Opcode successful cases
+        STOP,
+        ADD_SUB,     // ADD, SUB
+        MUL_DIV_MOD, // MUL, DIV, MOD
+        SDIV_SMOD,   // SDIV, SMOD
+        SHL_SHR,     // SHL, SHR
+        ADDMOD,
+        MULMOD,
+        EXP,
+        SIGNEXTEND,
+        CMP,  // LT, GT, EQ
+        SCMP, // SLT, SGT
+        ISZERO,
+        BITWISE, // AND, OR, XOR
+        NOT,
+        BYTE,
+        SAR,
+        SHA3,
+        ADDRESS,
+        BALANCE,
+        ORIGIN,
+        CALLER,
+        CALLVALUE,
+        CALLDATALOAD,
+        CALLDATASIZE,
+        CALLDATACOPY,
+        CODESIZE,
+        CODECOPY,
+        GASPRICE,
+        EXTCODESIZE,
+        EXTCODECOPY,
+        RETURNDATASIZE,
+        RETURNDATACOPY,
+        EXTCODEHASH,
+        BLOCKHASH,
+        BLOCKCTXU64,  // TIMESTAMP, NUMBER, GASLIMIT
+        BLOCKCTXU160, // COINBASE
+        BLOCKCTXU256, // BASEFEE
+        DIFFICULTY,   // DIFFICULTY
+        CHAINID,
+        SELFBALANCE,
+        POP,
+        MEMORY, // MLOAD, MSTORE, MSTORE8
+        MCOPY,
+        SLOAD,
+        SSTORE,
+        JUMP,
+        JUMPI,
+        PC,
+        MSIZE,
+        GAS,
+        JUMPDEST,
+        TLOAD,
+        TSTORE,
+        PUSH, // PUSH0, PUSH1, PUSH2, ..., PUSH32
+        DUP,  // DUP1, DUP2, ..., DUP16
+        SWAP, // SWAP1, SWAP2, ..., SWAP16
+        LOG,  // LOG0, LOG1, ..., LOG4
+        CREATE,
+        CREATE2,
+        CALL_OP,       // CALL, CALLCODE, DELEGATECALL, STATICCALL
+        RETURN_REVERT, // RETURN, REVERT
+        SELFDESTRUCT,
+        // Pass-through for the steps without circuit support
+        UNSUPPORTED,
+        // Error cases
+        ErrorInvalidOpcode,
+        ErrorStack,
+        ErrorWriteProtection,
+        ErrorInvalidCreationCode,
+        ErrorInvalidJump,
+        ErrorReturnDataOutOfBound,
+        ErrorPrecompileFailed,
+        ErrorOutOfGasConstant,
+        ErrorOutOfGasStaticMemoryExpansion,
+        ErrorOutOfGasDynamicMemoryExpansion,
+        ErrorOutOfGasMemoryCopy,
+        ErrorOutOfGasAccountAccess,
+        // error for CodeStoreOOG and MaxCodeSizeExceeded
+        ErrorCodeStore,
+        ErrorOutOfGasLOG,
+        ErrorOutOfGasEXP,
+        ErrorOutOfGasSHA3,
+        ErrorOutOfGasCall,
+        ErrorOutOfGasPrecompile,
+        ErrorOutOfGasSloadSstore,
+        ErrorOutOfGasCREATE,
+        ErrorOutOfGasSELFDESTRUCT,
+        // Precompiles
+        PrecompileEcrecover,
+        PrecompileSha256,
+        PrecompileRipemd160,
+        PrecompileIdentity,
+        PrecompileBigModExp,
+        PrecompileBn256Add,
+        PrecompileBn256ScalarMul,
+        PrecompileBn256Pairing,
+        PrecompileBlake2f,
+    }
 }
 
 impl Default for ExecutionState {
@@ -221,6 +224,15 @@ impl ExecutionState {
         )
     }
 
+    /// Whether the state is handled by a pass-through gadget which doesn't
+    /// constrain the effects of the step.
+    pub fn is_unproven(&self) -> bool {
+        matches!(
+            self,
+            Self::SELFDESTRUCT | Self::ErrorOutOfGasSELFDESTRUCT | Self::UNSUPPORTED
+        )
+    }
+
     pub(crate) fn halts(&self) -> bool {
         matches!(self, Self::STOP | Self::RETURN_REVERT | Self::SELFDESTRUCT)
             || self.halts_in_exception()
@@ -275,14 +287,7 @@ impl ExecutionState {
             Self::BLOCKHASH => vec![OpcodeId::BLOCKHASH],
             Self::BLOCKCTXU64 => vec![OpcodeId::TIMESTAMP, OpcodeId::NUMBER, OpcodeId::GASLIMIT],
             Self::BLOCKCTXU160 => vec![OpcodeId::COINBASE],
-            Self::BLOCKCTXU256 => {
-                if cfg!(feature = "scroll") {
-                    vec![OpcodeId::BASEFEE]
-                } else {
-                    vec![OpcodeId::DIFFICULTY, OpcodeId::BASEFEE]
-                }
-            }
-            #[cfg(feature = "scroll")]
+            Self::BLOCKCTXU256 => vec![OpcodeId::BASEFEE],
             Self::DIFFICULTY => vec![OpcodeId::DIFFICULTY],
             Self::CHAINID => vec![OpcodeId::CHAINID],
             Self::SELFBALANCE => vec![OpcodeId::SELFBALANCE],
@@ -703,3 +708,13 @@ impl<F: Field> Step<F> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::ExecutionState;
+
+    #[test]
+    fn execution_state_encoding() {
+        gadgets::binary_number::assert_tag_encoding::<ExecutionState, 7>();
+    }
+}
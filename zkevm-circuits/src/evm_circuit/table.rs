@@ -33,6 +33,7 @@ pub enum FixedTableTag {
     ConstantGasCost,
     PrecompileInfo,
     ChainFork,
+    SignextendMask,
 }
 impl_expr!(FixedTableTag);
 
@@ -152,6 +153,16 @@ impl FixedTableTag {
                     ]
                 },
             )),
+            Self::SignextendMask => Box::new((0..256).map(move |index| {
+                // Bit `idx` is set when byte `idx + 1` is replaced by the sign
+                // byte, so bits `index..31` for an index below 31.
+                let mask = if index < 31 {
+                    (1u64 << 31) - (1u64 << index)
+                } else {
+                    0
+                };
+                [tag, F::from(index), F::from(mask), F::zero()]
+            })),
         }
     }
 }
@@ -7,7 +7,7 @@ use crate::{
         table::Table,
     },
     table::RwTableTag,
-    util::{query_expression, Challenges, Expr, Field},
+    util::{expr_phase, query_expression, Challenges, Expr, Field},
     witness::{Block, ExecStep, Rw, RwMap},
 };
 use eth_types::{state_db::CodeDB, Address, ToLittleEndian, ToWord, U256};
@@ -30,6 +30,7 @@ pub(crate) mod math_gadget;
 pub(crate) mod memory_gadget;
 pub(crate) mod padding_gadget;
 pub(crate) mod precompile_gadget;
+pub(crate) mod word;
 
 pub use gadgets::util::{and, not, or, select, sum};
 
@@ -315,18 +316,6 @@ pub(crate) enum CellType {
 }
 
 impl CellType {
-    // The phase that given `Expression` becomes evaluateable.
-    fn expr_phase<F: Field>(expr: &Expression<F>) -> u8 {
-        use Expression::*;
-        match expr {
-            Challenge(challenge) => challenge.phase() + 1,
-            Advice(query) => query.phase(),
-            Constant(_) | Selector(_) | Fixed(_) | Instance(_) => 0,
-            Negated(a) | Expression::Scaled(a, _) => Self::expr_phase(a),
-            Sum(a, b) | Product(a, b) => std::cmp::max(Self::expr_phase(a), Self::expr_phase(b)),
-        }
-    }
-
     /// Return the storage phase of phase
     pub(crate) fn storage_for_phase(phase: u8) -> CellType {
         match phase {
@@ -338,7 +327,7 @@ impl CellType {
 
     /// Return the storage cell of the expression
     pub(crate) fn storage_for_expr<F: Field>(expr: &Expression<F>) -> CellType {
-        Self::storage_for_phase(Self::expr_phase::<F>(expr))
+        Self::storage_for_phase(expr_phase(expr))
     }
 }
 
@@ -24,9 +24,14 @@ use crate::{
     witness::{Block, Call, ExecStep},
 };
 use either::Either;
-use eth_types::{evm_types::GasCost, ToLittleEndian, U256};
-use gadgets::util::{select, sum};
-use gadgets::ToScalar;
+use eth_types::{
+    evm_types::{gas_utils::SstoreCase, GasCost, OpcodeId},
+    ToLittleEndian, U256,
+};
+use gadgets::{
+    util::{select, sum},
+    ToScalar,
+};
 use halo2_proofs::{
     circuit::Value,
     plonk::{Error, Expression},
@@ -110,6 +115,51 @@ impl<F: Field> SameContextGadget<F> {
     }
 }
 
+/// Construction of a batch of CallContext read lookups, one per field tag in
+/// order, whose cells are assigned from the CallContext operations of the
+/// step with the same field tags.
+#[derive(Clone, Debug)]
+pub(crate) struct CallContextGadget<F, const N: usize> {
+    field_tags: [CallContextFieldTag; N],
+    cells: [Cell<F>; N],
+}
+
+impl<F: Field, const N: usize> CallContextGadget<F, N> {
+    pub(crate) fn construct(
+        cb: &mut EVMConstraintBuilder<F>,
+        call_id: Option<Expression<F>>,
+        field_tags: [CallContextFieldTag; N],
+    ) -> Self {
+        let cells = field_tags.map(|field_tag| cb.call_context(call_id.clone(), field_tag));
+        Self { field_tags, cells }
+    }
+
+    pub(crate) fn cells(&self) -> &[Cell<F>; N] {
+        &self.cells
+    }
+
+    /// Assign the cells and return the values of the fields.
+    pub(crate) fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block,
+        step: &ExecStep,
+    ) -> Result<[U256; N], Error> {
+        let values = self
+            .field_tags
+            .map(|field_tag| block.rws[step.rw(field_tag)].call_context_value());
+        for ((field_tag, cell), value) in self.field_tags.iter().zip(&self.cells).zip(values) {
+            let value = match field_tag {
+                CallContextFieldTag::CodeHash => region.word_rlc(value),
+                _ => Value::known(value.to_scalar().unwrap()),
+            };
+            cell.assign(region, offset, value)?;
+        }
+        Ok(values)
+    }
+}
+
 /// Construction of step state transition that restores caller's state.
 #[derive(Clone, Debug)]
 pub(crate) struct RestoreContextGadget<F> {
@@ -1054,6 +1104,77 @@ impl<F: Field, G: TransferFromAssign<F> + TransferGadgetInfo<F>> TransferGadgetI
     }
 }
 
+/// Decodes which of CALL, CALLCODE, DELEGATECALL or STATICCALL the opcode of
+/// the step is, for the gadgets handling the CALL family.
+#[derive(Clone, Debug)]
+pub(crate) struct CallVariantGadget<F> {
+    is_call: IsZeroGadget<F>,
+    is_callcode: IsZeroGadget<F>,
+    is_delegatecall: IsZeroGadget<F>,
+    is_staticcall: IsZeroGadget<F>,
+}
+
+impl<F: Field> CallVariantGadget<F> {
+    pub(crate) fn construct(cb: &mut EVMConstraintBuilder<F>, opcode: Expression<F>) -> Self {
+        let is_call = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALL.expr());
+        let is_callcode = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALLCODE.expr());
+        let is_delegatecall =
+            IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::DELEGATECALL.expr());
+        let is_staticcall = IsZeroGadget::construct(cb, opcode - OpcodeId::STATICCALL.expr());
+
+        // Constrain opcode must be one of CALL, CALLCODE, DELEGATECALL or STATICCALL.
+        cb.require_equal(
+            "Opcode should be CALL, CALLCODE, DELEGATECALL or STATICCALL",
+            is_call.expr() + is_callcode.expr() + is_delegatecall.expr() + is_staticcall.expr(),
+            1.expr(),
+        );
+
+        Self {
+            is_call,
+            is_callcode,
+            is_delegatecall,
+            is_staticcall,
+        }
+    }
+
+    pub(crate) fn is_call(&self) -> Expression<F> {
+        self.is_call.expr()
+    }
+
+    pub(crate) fn is_callcode(&self) -> Expression<F> {
+        self.is_callcode.expr()
+    }
+
+    pub(crate) fn is_delegatecall(&self) -> Expression<F> {
+        self.is_delegatecall.expr()
+    }
+
+    pub(crate) fn is_staticcall(&self) -> Expression<F> {
+        self.is_staticcall.expr()
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        opcode: OpcodeId,
+    ) -> Result<(), Error> {
+        for (gadget, variant) in [
+            (&self.is_call, OpcodeId::CALL),
+            (&self.is_callcode, OpcodeId::CALLCODE),
+            (&self.is_delegatecall, OpcodeId::DELEGATECALL),
+            (&self.is_staticcall, OpcodeId::STATICCALL),
+        ] {
+            gadget.assign(
+                region,
+                offset,
+                F::from(opcode.as_u64()) - F::from(variant.as_u64()),
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct CommonCallGadget<F, MemAddrGadget, const IS_SUCCESS_CALL: bool> {
     pub is_success: Cell<F>,
@@ -1085,17 +1206,12 @@ impl<F: Field, MemAddrGadget: CommonMemoryAddressGadget<F>, const IS_SUCCESS_CAL
 {
     pub(crate) fn construct(
         cb: &mut EVMConstraintBuilder<F>,
-        is_call: Expression<F>,
-        is_callcode: Expression<F>,
-        is_delegatecall: Expression<F>,
-        is_staticcall: Expression<F>,
+        variant: &CallVariantGadget<F>,
     ) -> Self {
-        // Constrain opcode must be one of CALL, CALLCODE, DELEGATECALL or STATICCALL.
-        cb.require_equal(
-            "Opcode should be CALL, CALLCODE, DELEGATECALL or STATICCALL",
-            is_call.expr() + is_callcode.expr() + is_delegatecall.expr() + is_staticcall.expr(),
-            1.expr(),
-        );
+        let is_call = variant.is_call();
+        let is_callcode = variant.is_callcode();
+        let is_delegatecall = variant.is_delegatecall();
+        let is_staticcall = variant.is_staticcall();
 
         let gas_word = cb.query_word_rlc();
         let callee_address_word = cb.query_word_rlc();
@@ -1184,11 +1300,7 @@ impl<F: Field, MemAddrGadget: CommonMemoryAddressGadget<F>, const IS_SUCCESS_CAL
         from_bytes::expr(&self.gas.cells[..N_BYTES_GAS])
     }
 
-    pub fn gas_cost_expr(
-        &self,
-        is_warm_prev: Expression<F>,
-        is_call: Expression<F>,
-    ) -> Expression<F> {
+    pub fn gas_cost_expr(&self, is_warm_prev: Expression<F>) -> Expression<F> {
         select::expr(
             is_warm_prev,
             GasCost::WARM_ACCESS.expr(),
@@ -1196,10 +1308,25 @@ impl<F: Field, MemAddrGadget: CommonMemoryAddressGadget<F>, const IS_SUCCESS_CAL
         ) + self.has_value.clone()
             * (GasCost::CALL_WITH_VALUE.expr()
                 // Only CALL opcode could invoke transfer to make empty account into non-empty.
-                + is_call * self.callee_not_exists.expr() * GasCost::NEW_ACCOUNT.expr())
+                + self.is_call.expr() * self.callee_not_exists.expr() * GasCost::NEW_ACCOUNT.expr())
             + self.memory_expansion.gas_cost()
     }
 
+    /// Constrain that a CALL transferring value is not made from a static call
+    /// context.
+    pub(crate) fn require_no_value_in_static_call(
+        &self,
+        cb: &mut EVMConstraintBuilder<F>,
+        is_static: Expression<F>,
+    ) {
+        cb.condition(self.is_call.expr() * self.has_value.expr(), |cb| {
+            cb.require_zero(
+                "CALL with value must not be in static call stack",
+                is_static,
+            );
+        });
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn assign(
         &self,
@@ -1258,31 +1385,23 @@ impl<F: Field, MemAddrGadget: CommonMemoryAddressGadget<F>, const IS_SUCCESS_CAL
         Ok(memory_expansion_gas_cost)
     }
 
+    /// Gas cost of the call, computed the same way as the witness generation
+    /// does. Only CALL and CALLCODE transfer `value`, and only CALL can make an
+    /// empty account non-empty.
     pub(crate) fn cal_gas_cost_for_assignment(
         &self,
         memory_expansion_gas_cost: u64,
         is_warm_prev: bool,
-        is_call: bool,
-        has_value: bool,
+        opcode: OpcodeId,
+        value: U256,
         is_empty_account: bool,
     ) -> Result<u64, Error> {
-        let gas_cost = if is_warm_prev {
-            GasCost::WARM_ACCESS.as_u64()
-        } else {
-            GasCost::COLD_ACCOUNT_ACCESS.as_u64()
-        } + if has_value {
-            GasCost::CALL_WITH_VALUE.as_u64()
-                // Only CALL opcode could invoke transfer to make empty account into non-empty.
-                + if is_call && is_empty_account {
-                    GasCost::NEW_ACCOUNT.as_u64()
-                } else {
-                    0
-                }
-        } else {
-            0
-        } + memory_expansion_gas_cost;
-
-        Ok(gas_cost)
+        let is_call = opcode == OpcodeId::CALL;
+        let has_value = !value.is_zero() && (is_call || opcode == OpcodeId::CALLCODE);
+        Ok(
+            bus_mapping::gas::call(is_warm_prev, has_value, is_call && is_empty_account)
+                + memory_expansion_gas_cost,
+        )
     }
 
     pub(crate) fn rw_delta(&self) -> Expression<F> {
@@ -1315,6 +1434,129 @@ impl<F: Field> SloadGasGadget<F> {
     }
 }
 
+/// Selects the [`SstoreCase`] of a SSTORE, with one boolean cell per case.
+/// Exactly one case is selected, and it must match the relations between the
+/// original, current and new values of the slot.
+#[derive(Clone, Debug)]
+pub(crate) struct SstoreCaseGadget<F> {
+    current_eq_new: IsEqualGadget<F>,
+    original_eq_current: IsEqualGadget<F>,
+    original_eq_new: IsEqualGadget<F>,
+    original_is_zero: IsZeroGadget<F>,
+    current_is_zero: IsZeroGadget<F>,
+    new_is_zero: IsZeroGadget<F>,
+    cases: [Cell<F>; SstoreCase::ALL.len()],
+}
+
+impl<F: Field> SstoreCaseGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut EVMConstraintBuilder<F>,
+        original: Expression<F>,
+        current: Expression<F>,
+        new: Expression<F>,
+    ) -> Self {
+        let current_eq_new = IsEqualGadget::construct(cb, current.clone(), new.clone());
+        let original_eq_current = IsEqualGadget::construct(cb, original.clone(), current.clone());
+        let original_eq_new = IsEqualGadget::construct(cb, original.clone(), new.clone());
+        let original_is_zero = IsZeroGadget::construct(cb, original);
+        let current_is_zero = IsZeroGadget::construct(cb, current);
+        let new_is_zero = IsZeroGadget::construct(cb, new);
+        // in the order of `SstoreRelations::to_array`
+        let relations = [
+            current_eq_new.expr(),
+            original_eq_current.expr(),
+            original_eq_new.expr(),
+            original_is_zero.expr(),
+            current_is_zero.expr(),
+            new_is_zero.expr(),
+        ];
+
+        let cases = SstoreCase::ALL.map(|_| cb.query_bool());
+        cb.require_equal(
+            "exactly one sstore case is selected",
+            sum::expr(&cases),
+            1.expr(),
+        );
+        for (case, cell) in SstoreCase::ALL.iter().zip(cases.iter()) {
+            for (expected, relation) in case.pattern().into_iter().zip(relations.iter()) {
+                if let Some(expected) = expected {
+                    cb.require_zero(
+                        "selected sstore case matches the relations",
+                        cell.expr() * (relation.clone() - expected.expr()),
+                    );
+                }
+            }
+        }
+
+        Self {
+            current_eq_new,
+            original_eq_current,
+            original_eq_new,
+            original_is_zero,
+            current_is_zero,
+            new_is_zero,
+            cases,
+        }
+    }
+
+    /// Gas cost of the selected case when the slot is warm
+    pub(crate) fn warm_gas_cost(&self) -> Expression<F> {
+        sum::expr(
+            SstoreCase::ALL
+                .iter()
+                .zip(self.cases.iter())
+                .map(|(case, cell)| cell.expr() * case.warm_gas_cost().expr()),
+        )
+    }
+
+    /// Change of the tx refund of the selected case
+    pub(crate) fn refund_delta(&self) -> Expression<F> {
+        sum::expr(
+            SstoreCase::ALL
+                .iter()
+                .zip(self.cases.iter())
+                .map(|(case, cell)| {
+                    let delta = case.refund_delta();
+                    let abs = Expression::Constant(F::from(delta.unsigned_abs()));
+                    cell.expr() * if delta < 0 { -abs } else { abs }
+                }),
+        )
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        original: U256,
+        current: U256,
+        new: U256,
+    ) -> Result<(), Error> {
+        let [original_rlc, current_rlc, new_rlc] =
+            [original, current, new].map(|value| region.word_rlc(value));
+        self.current_eq_new
+            .assign_value(region, offset, current_rlc, new_rlc)?;
+        self.original_eq_current
+            .assign_value(region, offset, original_rlc, current_rlc)?;
+        self.original_eq_new
+            .assign_value(region, offset, original_rlc, new_rlc)?;
+        self.original_is_zero
+            .assign_value(region, offset, original_rlc)?;
+        self.current_is_zero
+            .assign_value(region, offset, current_rlc)?;
+        self.new_is_zero.assign_value(region, offset, new_rlc)?;
+
+        let selected = SstoreCase::new(original, current, new);
+        for (case, cell) in SstoreCase::ALL.iter().zip(self.cases.iter()) {
+            cell.assign(
+                region,
+                offset,
+                Value::known(F::from((*case == selected) as u64)),
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct SstoreGasGadget<F> {
     value: Cell<F>,
@@ -1322,9 +1564,7 @@ pub(crate) struct SstoreGasGadget<F> {
     original_value: Cell<F>,
     is_warm: Cell<F>,
     gas_cost: Expression<F>,
-    value_eq_prev: IsEqualGadget<F>,
-    original_eq_prev: IsEqualGadget<F>,
-    original_is_zero: IsZeroGadget<F>,
+    case: SstoreCaseGadget<F>,
 }
 
 impl<F: Field> SstoreGasGadget<F> {
@@ -1335,28 +1575,10 @@ impl<F: Field> SstoreGasGadget<F> {
         original_value: Cell<F>,
         is_warm: Cell<F>,
     ) -> Self {
-        let value_eq_prev = IsEqualGadget::construct(cb, value.expr(), value_prev.expr());
-        let original_eq_prev =
-            IsEqualGadget::construct(cb, original_value.expr(), value_prev.expr());
-        let original_is_zero = IsZeroGadget::construct(cb, original_value.expr());
-        let warm_case_gas = select::expr(
-            value_eq_prev.expr(),
-            GasCost::WARM_ACCESS.expr(),
-            select::expr(
-                original_eq_prev.expr(),
-                select::expr(
-                    original_is_zero.expr(),
-                    GasCost::SSTORE_SET.expr(),
-                    GasCost::SSTORE_RESET.expr(),
-                ),
-                GasCost::WARM_ACCESS.expr(),
-            ),
-        );
-        let gas_cost = select::expr(
-            is_warm.expr(),
-            warm_case_gas.expr(),
-            warm_case_gas + GasCost::COLD_SLOAD.expr(),
-        );
+        let case =
+            SstoreCaseGadget::construct(cb, original_value.expr(), value_prev.expr(), value.expr());
+        let gas_cost =
+            case.warm_gas_cost() + not::expr(is_warm.expr()) * GasCost::COLD_SLOAD.expr();
 
         Self {
             value,
@@ -1364,9 +1586,7 @@ impl<F: Field> SstoreGasGadget<F> {
             original_value,
             is_warm,
             gas_cost,
-            value_eq_prev,
-            original_eq_prev,
-            original_is_zero,
+            case,
         }
     }
 
@@ -1374,6 +1594,12 @@ impl<F: Field> SstoreGasGadget<F> {
         // Return the gas cost
         self.gas_cost.clone()
     }
+
+    /// The case of the SSTORE, to compute the tx refund from
+    pub(crate) fn case(&self) -> &SstoreCaseGadget<F> {
+        &self.case
+    }
+
     pub(crate) fn assign(
         &self,
         region: &mut CachedRegion<'_, '_, F>,
@@ -1390,20 +1616,8 @@ impl<F: Field> SstoreGasGadget<F> {
             .assign(region, offset, region.word_rlc(original_value))?;
         self.is_warm
             .assign(region, offset, Value::known(F::from(is_warm as u64)))?;
-        self.value_eq_prev.assign_value(
-            region,
-            offset,
-            region.word_rlc(value),
-            region.word_rlc(value_prev),
-        )?;
-        self.original_eq_prev.assign_value(
-            region,
-            offset,
-            region.word_rlc(original_value),
-            region.word_rlc(value_prev),
-        )?;
-        self.original_is_zero
-            .assign_value(region, offset, region.word_rlc(original_value))?;
+        self.case
+            .assign(region, offset, original_value, value_prev, value)?;
         Ok(())
     }
 }
@@ -1424,22 +1638,7 @@ pub(crate) fn cal_sstore_gas_cost_for_assignment(
     original_value: U256,
     is_warm: bool,
 ) -> u64 {
-    let warm_case_gas = if value_prev == value {
-        GasCost::WARM_ACCESS
-    } else if original_value == value_prev {
-        if original_value.is_zero() {
-            GasCost::SSTORE_SET
-        } else {
-            GasCost::SSTORE_RESET
-        }
-    } else {
-        GasCost::WARM_ACCESS
-    };
-    if is_warm {
-        warm_case_gas.0
-    } else {
-        warm_case_gas.0 + GasCost::COLD_SLOAD.0
-    }
+    SstoreCase::new(original_value, value_prev, value).gas_cost(is_warm)
 }
 
 #[derive(Clone, Debug)]
@@ -1519,6 +1718,8 @@ impl<F: Field> CommonErrorGadget<F> {
             cb.require_step_state_transition(StepStateTransition {
                 call_id: Same,
                 rw_counter: Delta(rw_counter_delta + cb.curr.state.reversible_write_counter.expr()),
+                // EndTx checks the log count of the receipt against log_id
+                log_id: Same,
                 end_tx: To(1.expr()),
                 ..StepStateTransition::any()
             });
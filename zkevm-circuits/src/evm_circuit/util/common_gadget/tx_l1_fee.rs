@@ -12,7 +12,7 @@ use crate::{
     util::{Expr, Field},
 };
 use bus_mapping::{
-    circuit_input_builder::{TxL1Fee, TX_L1_COMMIT_EXTRA_COST, TX_L1_FEE_PRECISION},
+    circuit_input_builder::{L1FeeSchema, TxL1Fee},
     l2_predeployed::l1_gas_price_oracle,
 };
 use eth_types::{ToLittleEndian, U256};
@@ -20,16 +20,19 @@ use gadgets::util::not;
 use gadgets::ToScalar;
 use halo2_proofs::plonk::{Error, Expression};
 
-/// Transaction L1 fee gadget for L1GasPriceOracle contract
+/// Transaction L1 fee gadget for the rollup config contract of the
+/// [`L1FeeSchema`] the circuit is configured for
 #[derive(Clone, Debug)]
 pub(crate) struct TxL1FeeGadget<F> {
+    /// Schema of the L1 fee
+    schema: L1FeeSchema,
     /// Transaction L1 fee
     /// It should be an Uint64, but it's also used to check sender balance which
     /// needs to be added as a Word.
     tx_l1_fee_word: Word<F>,
     /// Remainder when calculating L1 fee
     remainder_word: U64Word<F>,
-    /// Remainder must in [0, TX_L1_FEE_PRECISION)
+    /// Remainder must in [0, precision of the schema)
     remainder_range: LtGadget<F, 8>,
     /// Current value of L1 base fee
     base_fee_word: U64Word<F>,
@@ -65,18 +68,25 @@ impl<F: Field> TxL1FeeGadget<F> {
         tx_data_gas_cost: Expression<F>,
         tx_signed_length: Expression<F>,
     ) -> Self {
-        let this = Self::raw_construct(cb, is_curie.expr(), tx_data_gas_cost, tx_signed_length);
+        let schema = cb.params().l1_fee_schema;
+        let this = Self::raw_construct(
+            cb,
+            schema,
+            is_curie.expr(),
+            tx_data_gas_cost,
+            tx_signed_length,
+        );
 
-        let l1_fee_address = Expression::Constant(l1_gas_price_oracle::ADDRESS.to_scalar().expect(
-            "Unexpected address of l2 gasprice oracle contract -> Scalar conversion failure",
-        ));
+        let l1_fee_address = Expression::Constant(
+            schema
+                .address()
+                .to_scalar()
+                .expect("Unexpected address of l1 fee contract -> Scalar conversion failure"),
+        );
 
-        let [base_fee_slot, overhead_slot, scalar_slot] = [
-            &l1_gas_price_oracle::BASE_FEE_SLOT,
-            &l1_gas_price_oracle::OVERHEAD_SLOT,
-            &l1_gas_price_oracle::SCALAR_SLOT,
-        ]
-        .map(|slot| cb.word_rlc(slot.to_le_bytes().map(|b| b.expr())));
+        let [base_fee_slot, overhead_slot, scalar_slot] = schema
+            .fee_slots()
+            .map(|slot| cb.word_rlc(slot.to_le_bytes().map(|b| b.expr())));
 
         let [l1_blob_basefee, commit_scalar, blob_scalar] = [
             &l1_gas_price_oracle::L1_BLOB_BASEFEE_SLOT,
@@ -112,34 +122,37 @@ impl<F: Field> TxL1FeeGadget<F> {
             this.fee_scalar_committed.expr(),
         );
 
-        cb.condition(is_curie.expr(), |cb| {
-            // Read l1blob_basefee_committed
-            cb.account_storage_read(
-                l1_fee_address.expr(),
-                l1_blob_basefee,
-                this.l1_blob_basefee_word.expr(),
-                tx_id.clone(),
-                this.l1_blob_basefee_committed.expr(),
-            );
-
-            // Read L1 commit_scalar_committed
-            cb.account_storage_read(
-                l1_fee_address.expr(),
-                commit_scalar,
-                this.commit_scalar_word.expr(),
-                tx_id.expr(),
-                this.commit_scalar_committed.expr(),
-            );
-
-            // Read L1 blob_scalar_committed scalar
-            cb.account_storage_read(
-                l1_fee_address,
-                blob_scalar,
-                this.blob_scalar_word.expr(),
-                tx_id,
-                this.blob_scalar_committed.expr(),
-            );
-        });
+        // the curie fields are only read by the scroll schema
+        if schema.has_curie() {
+            cb.condition(is_curie.expr(), |cb| {
+                // Read l1blob_basefee_committed
+                cb.account_storage_read(
+                    l1_fee_address.expr(),
+                    l1_blob_basefee,
+                    this.l1_blob_basefee_word.expr(),
+                    tx_id.clone(),
+                    this.l1_blob_basefee_committed.expr(),
+                );
+
+                // Read L1 commit_scalar_committed
+                cb.account_storage_read(
+                    l1_fee_address.expr(),
+                    commit_scalar,
+                    this.commit_scalar_word.expr(),
+                    tx_id.expr(),
+                    this.commit_scalar_committed.expr(),
+                );
+
+                // Read L1 blob_scalar_committed scalar
+                cb.account_storage_read(
+                    l1_fee_address,
+                    blob_scalar,
+                    this.blob_scalar_word.expr(),
+                    tx_id,
+                    this.blob_scalar_committed.expr(),
+                );
+            });
+        }
         this
     }
 
@@ -174,7 +187,7 @@ impl<F: Field> TxL1FeeGadget<F> {
             region,
             offset,
             F::from(remainder),
-            F::from(TX_L1_FEE_PRECISION),
+            F::from(self.schema.precision()),
         )?;
 
         // curie fields
@@ -233,7 +246,11 @@ impl<F: Field> TxL1FeeGadget<F> {
         // commit scalar
         // blob scalar
         // TODO: we could optimze the "overhead" and "scalar" for curie
-        3.expr() + is_curie.expr() * 3.expr()
+        if self.schema.has_curie() {
+            3.expr() + is_curie.expr() * 3.expr()
+        } else {
+            3.expr()
+        }
     }
 
     pub(crate) fn tx_l1_fee(&self) -> Expression<F> {
@@ -246,6 +263,7 @@ impl<F: Field> TxL1FeeGadget<F> {
 
     fn raw_construct(
         cb: &mut EVMConstraintBuilder<F>,
+        schema: L1FeeSchema,
         is_curie: Expression<F>,
         tx_data_gas_cost: Expression<F>,
         tx_signed_length: Expression<F>,
@@ -270,7 +288,8 @@ impl<F: Field> TxL1FeeGadget<F> {
         ]
         .map(|word| from_bytes::expr(&word.cells[..N_BYTES_U64]));
 
-        let remainder_range = LtGadget::construct(cb, remainder.expr(), TX_L1_FEE_PRECISION.expr());
+        let precision = schema.precision();
+        let remainder_range = LtGadget::construct(cb, remainder.expr(), precision.expr());
         cb.require_equal(
             "remainder must less than l1 fee precision",
             1.expr(),
@@ -286,20 +305,30 @@ impl<F: Field> TxL1FeeGadget<F> {
 
         // For curie and non-curie, see l2geth side implementation:
         // <https://github.com/scroll-tech/go-ethereum/blob/36d7325ea1cb6749f353f84df7e9903f93aa903b/rollup/fees/rollup_fee.go#L76>
-        cb.condition(is_curie.expr(), |cb| {
-            cb.require_equal(
-                "commitScalar * l1BaseFee + blobScalar * _data.length * l1BlobBaseFee == tx_l1_fee * 10e9 + remainder",
-                commit_scalar * base_fee.clone() + blob_scalar * tx_signed_length * l1_blob_basefee,
-                tx_l1_fee.clone() * TX_L1_FEE_PRECISION.expr() + remainder.clone(),
-            );
-        });
+        // The OP stack Bedrock fee is the non-curie one, without the extra
+        // commit cost and with its own precision:
+        // <https://github.com/ethereum-optimism/op-geth/blob/v1.101200.0/core/types/rollup_cost.go>
+        let is_curie = if schema.has_curie() {
+            is_curie
+        } else {
+            0.expr()
+        };
+        if schema.has_curie() {
+            cb.condition(is_curie.expr(), |cb| {
+                cb.require_equal(
+                    "commitScalar * l1BaseFee + blobScalar * _data.length * l1BlobBaseFee == tx_l1_fee * precision + remainder",
+                    commit_scalar * base_fee.clone() + blob_scalar * tx_signed_length * l1_blob_basefee,
+                    tx_l1_fee.clone() * precision.expr() + remainder.clone(),
+                );
+            });
+        }
 
         cb.condition(not::expr(is_curie.expr()), |cb| {
-            let tx_l1_gas = tx_data_gas_cost + TX_L1_COMMIT_EXTRA_COST.expr() + fee_overhead;
+            let tx_l1_gas = tx_data_gas_cost + schema.extra_l1_gas().expr() + fee_overhead;
             cb.require_equal(
-                "fee_scalar * base_fee * tx_l1_gas == tx_l1_fee * 10e9 + remainder",
+                "fee_scalar * base_fee * tx_l1_gas == tx_l1_fee * precision + remainder",
                 fee_scalar * base_fee * tx_l1_gas,
-                tx_l1_fee * TX_L1_FEE_PRECISION.expr() + remainder,
+                tx_l1_fee * precision.expr() + remainder,
             );
         });
 
@@ -312,6 +341,7 @@ impl<F: Field> TxL1FeeGadget<F> {
         let blob_scalar_committed = cb.query_cell_phase2();
 
         Self {
+            schema,
             tx_l1_fee_word,
             remainder_word,
             remainder_range,
@@ -361,6 +391,13 @@ mod tests {
     const TEST_TX_RLP_SIGNED_LENGTH: u128 = 4;
     const TEST_TX_L1_FEE_AFTER_CURIE: u128 = 21;
 
+    // 10 * 684000 * (1000 + 188) / 1e6
+    const TEST_OP_BASE_FEE: u64 = 10;
+    const TEST_OP_FEE_OVERHEAD: u64 = 188;
+    const TEST_OP_FEE_SCALAR: u64 = 684_000;
+    const TEST_OP_TX_DATA_GAS_COST: u64 = 1000;
+    const TEST_OP_TX_L1_FEE: u128 = 8125;
+
     #[test]
     fn test_tx_l1_fee_with_right_values() {
         // test both before & after curie upgrade
@@ -413,8 +450,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_tx_l1_fee_op_bedrock() {
+        for (tx_l1_fee, success) in [(TEST_OP_TX_L1_FEE, true), (TEST_OP_TX_L1_FEE + 1, false)] {
+            let witnesses = [
+                TEST_BEFORE_CURIE.into(),
+                TEST_OP_BASE_FEE.into(),
+                TEST_OP_FEE_OVERHEAD.into(),
+                TEST_OP_FEE_SCALAR.into(),
+                TEST_OP_TX_DATA_GAS_COST.into(),
+                tx_l1_fee,
+                // no curie fields
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            ]
+            .map(U256::from);
+
+            try_test!(TxL1FeeGadgetTestContainer<Fr, true>, witnesses, success);
+        }
+    }
+
+    /// Container of the gadget for the scroll schema, or for the OP stack
+    /// Bedrock schema when `OP_BEDROCK`
     #[derive(Clone)]
-    struct TxL1FeeGadgetTestContainer<F> {
+    struct TxL1FeeGadgetTestContainer<F, const OP_BEDROCK: bool = false> {
         is_curie: Cell<F>,
         gadget: TxL1FeeGadget<F>,
         tx_data_gas_cost: Cell<F>,
@@ -422,7 +485,19 @@ mod tests {
         expected_tx_l1_fee: Cell<F>,
     }
 
-    impl<F: Field> MathGadgetContainer<F> for TxL1FeeGadgetTestContainer<F> {
+    impl<F: Field, const OP_BEDROCK: bool> TxL1FeeGadgetTestContainer<F, OP_BEDROCK> {
+        fn schema() -> L1FeeSchema {
+            if OP_BEDROCK {
+                L1FeeSchema::OpBedrock
+            } else {
+                L1FeeSchema::Scroll
+            }
+        }
+    }
+
+    impl<F: Field, const OP_BEDROCK: bool> MathGadgetContainer<F>
+        for TxL1FeeGadgetTestContainer<F, OP_BEDROCK>
+    {
         fn configure_gadget_container(cb: &mut EVMConstraintBuilder<F>) -> Self {
             let tx_data_gas_cost: Cell<F> = cb.query_cell();
             let tx_signed_length = cb.query_cell();
@@ -434,6 +509,7 @@ mod tests {
             // l1 fee for both before and after Curie upgrade
             let gadget = TxL1FeeGadget::<F>::raw_construct(
                 cb,
+                Self::schema(),
                 is_curie.expr(),
                 tx_data_gas_cost.expr(),
                 tx_signed_length.expr(),
@@ -445,7 +521,7 @@ mod tests {
                 expected_tx_l1_fee.expr(),
             );
 
-            TxL1FeeGadgetTestContainer {
+            Self {
                 is_curie,
                 gadget,
                 tx_data_gas_cost,
@@ -466,6 +542,7 @@ mod tests {
                 [6, 7, 8, 9, 10, 11].map(|i| witnesses[i].as_u64());
 
             let l1_fee = TxL1Fee {
+                schema: Self::schema(),
                 chain_id: eth_types::forks::SCROLL_DEVNET_CHAIN_ID,
                 // block_number 5 is starting number for curie in test devnet.
                 block_number: if is_curie == 1 { 5 + 1 } else { 1 },
@@ -479,6 +556,7 @@ mod tests {
                 l1_blob_basefee,
                 commit_scalar,
                 blob_scalar,
+                ..Default::default()
             };
             self.gadget.assign(
                 region,
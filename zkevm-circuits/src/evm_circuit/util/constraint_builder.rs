@@ -3,18 +3,24 @@ use crate::{
         param::STACK_CAPACITY,
         step::{ExecutionState, Step},
         table::{FixedTableTag, Lookup, RwValues},
-        util::{Cell, RandomLinearCombination, Word},
+        util::{
+            word::{WordLoHi, WordLoHiCell},
+            Cell, RandomLinearCombination, Word,
+        },
+        EvmCircuitConfigParams,
     },
     table::{
         AccountFieldTag, BytecodeFieldTag, CallContextFieldTag, RwTableTag, TxContextFieldTag,
         TxLogFieldTag, TxReceiptFieldTag,
     },
-    util::{build_tx_log_expression, Challenges, Expr, Field},
+    util::{assert_expr_phase, build_tx_log_expression, Challenges, Expr, Field},
 };
 use bus_mapping::util::{KECCAK_CODE_HASH_EMPTY, POSEIDON_CODE_HASH_EMPTY};
-use eth_types::{state_db::EMPTY_CODE_HASH_LE, ToLittleEndian, ToWord, H256};
-use gadgets::util::{and, not};
-use gadgets::ToScalar;
+use eth_types::{evm_types::OpcodeId, state_db::EMPTY_CODE_HASH_LE, ToLittleEndian, ToWord, H256};
+use gadgets::{
+    util::{and, not},
+    ToScalar,
+};
 use halo2_proofs::{
     circuit::Value,
     plonk::{
@@ -23,15 +29,19 @@ use halo2_proofs::{
     },
 };
 use itertools::Itertools;
+use std::collections::HashSet;
 
-use super::{rlc, CachedRegion, CellType, StoredExpression};
+use super::{
+    instrumentation::{GadgetCoverage, GadgetDegree},
+    rlc, CachedRegion, CellType, StoredExpression,
+};
 
 // Max degree allowed in all expressions passing through the ConstraintBuilder.
 // It aims to cap `extended_k` to 2, which allows constraint degree to 2^2+1,
 // but each ExecutionGadget has implicit selector degree 3, so here it only
 // allows 2^2+1-3 = 2.
-const MAX_DEGREE: usize = 9;
-const IMPLICIT_DEGREE: usize = 4;
+pub(crate) const MAX_DEGREE: usize = 9;
+pub(crate) const IMPLICIT_DEGREE: usize = 4;
 
 pub(crate) enum Transition<T> {
     Same,
@@ -300,6 +310,7 @@ pub(crate) struct EVMConstraintBuilder<'a, F> {
     pub(crate) curr: Step<F>,
     pub(crate) next: Step<F>,
     challenges: &'a Challenges<Expression<F>>,
+    params: EvmCircuitConfigParams,
     execution_state: ExecutionState,
     constraints: Constraints<F>,
     rw_counter_offset: Expression<F>,
@@ -310,6 +321,10 @@ pub(crate) struct EVMConstraintBuilder<'a, F> {
     conditions: Vec<Expression<F>>,
     constraints_location: ConstraintLocation,
     stored_expressions: Vec<StoredExpression<F>>,
+    /// Cells queried in the current step, for the coverage report
+    queried_cells: Vec<Cell<F>>,
+    /// Degree of each constraint and lookup before splitting, for the degree report
+    expression_degrees: Vec<(String, usize)>,
     pub(crate) max_inner_degree: (&'static str, usize),
     #[cfg(feature = "debug-annotations")]
     annotations: Vec<String>,
@@ -320,11 +335,10 @@ impl<'a, F: Field> ConstrainBuilderCommon<F> for EVMConstraintBuilder<'a, F> {
         #[cfg(feature = "debug-annotations")]
         let name =
             Box::leak(format!("{}: {}", self.annotations.iter().join(">"), name).into_boxed_str());
-        let constraint = self.split_expression(
-            name,
-            constraint * self.condition_expr(),
-            MAX_DEGREE - IMPLICIT_DEGREE,
-        );
+        let constraint = constraint * self.condition_expr();
+        self.expression_degrees
+            .push((name.to_string(), constraint.degree()));
+        let constraint = self.split_expression(name, constraint, MAX_DEGREE - IMPLICIT_DEGREE);
 
         self.validate_degree(constraint.degree(), name);
         self.push_constraint(name, constraint);
@@ -336,6 +350,7 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         curr: Step<F>,
         next: Step<F>,
         challenges: &'a Challenges<Expression<F>>,
+        params: EvmCircuitConfigParams,
         execution_state: ExecutionState,
     ) -> Self {
         Self {
@@ -343,6 +358,7 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
             curr,
             next,
             challenges,
+            params,
             execution_state,
             constraints: Constraints {
                 step: Vec::new(),
@@ -358,6 +374,8 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
             conditions: Vec::new(),
             constraints_location: ConstraintLocation::Step,
             stored_expressions: Vec::new(),
+            queried_cells: Vec::new(),
+            expression_degrees: Vec::new(),
             max_inner_degree: ("", 0),
             annotations: Vec::new(),
         }
@@ -383,6 +401,68 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         )
     }
 
+    /// Returns the number of cells queried, constraints added and lookups
+    /// issued so far, and the queried cells never referenced in a constraint
+    /// or a lookup.
+    pub(crate) fn coverage(&self) -> GadgetCoverage {
+        fn collect_advice_queries<F: Field>(
+            expr: &Expression<F>,
+            queries: &mut HashSet<(usize, i32)>,
+        ) {
+            match expr {
+                Expression::Advice(query) => {
+                    queries.insert((query.column_index(), query.rotation().0));
+                }
+                Expression::Negated(a) | Expression::Scaled(a, _) => {
+                    collect_advice_queries(a, queries)
+                }
+                Expression::Sum(a, b) | Expression::Product(a, b) => {
+                    collect_advice_queries(a, queries);
+                    collect_advice_queries(b, queries);
+                }
+                _ => {}
+            }
+        }
+
+        let constraints = self
+            .constraints
+            .step
+            .iter()
+            .chain(&self.constraints.step_first)
+            .chain(&self.constraints.step_last)
+            .chain(&self.constraints.not_step_last)
+            .collect::<Vec<_>>();
+        let mut queries = HashSet::new();
+        for (_, expr) in constraints.iter() {
+            collect_advice_queries(expr, &mut queries);
+        }
+        let unused_cells = self
+            .queried_cells
+            .iter()
+            .map(|cell| (cell.column.index(), cell.rotation))
+            .filter(|(index, rotation)| !queries.contains(&(*index, *rotation as i32)))
+            .collect();
+
+        GadgetCoverage {
+            cells: self.queried_cells.len(),
+            constraints: constraints.len(),
+            lookups: self
+                .stored_expressions
+                .iter()
+                .filter(|stored| matches!(stored.cell_type, CellType::Lookup(_)))
+                .count(),
+            unused_cells,
+        }
+    }
+
+    /// Returns the degree of every constraint added and lookup issued so far,
+    /// before they are split to fit in the circuit degree.
+    pub(crate) fn degrees(&self) -> GadgetDegree {
+        GadgetDegree {
+            expressions: self.expression_degrees.clone(),
+        }
+    }
+
     fn condition_expr_opt(&self) -> Option<Expression<F>> {
         let mut iter = self.conditions.iter();
         let first = match iter.next() {
@@ -400,6 +480,11 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         self.execution_state
     }
 
+    /// Parameters of the chain the circuit is configured for
+    pub(crate) fn params(&self) -> &EvmCircuitConfigParams {
+        &self.params
+    }
+
     pub(crate) fn rw_counter_offset(&self) -> Expression<F> {
         self.rw_counter_offset.clone()
     }
@@ -419,6 +504,7 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
     // Query
 
     pub(crate) fn copy<E: Expr<F>>(&mut self, value: E) -> Cell<F> {
+        assert_expr_phase("Copy value to new cell", &value.expr(), 0);
         let cell = self.query_cell();
         self.require_equal("Copy value to new cell", cell.expr(), value.expr());
         cell
@@ -438,6 +524,13 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         RandomLinearCombination::<F, N>::new(self.query_bytes(), self.challenges.evm_word())
     }
 
+    /// Query a word of two cells in the first phase. The limbs are not range
+    /// checked, which is left to the gadget when it is not implied by a
+    /// lookup.
+    pub(crate) fn query_word_unchecked(&mut self) -> WordLoHiCell<F> {
+        WordLoHi::new([self.query_cell(), self.query_cell()])
+    }
+
     pub(crate) fn query_keccak_rlc<const N: usize>(&mut self) -> RandomLinearCombination<F, N> {
         RandomLinearCombination::<F, N>::new(self.query_bytes(), self.challenges.keccak_input())
     }
@@ -485,12 +578,12 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
 
     fn query_cells(&mut self, cell_type: CellType, count: usize) -> Vec<Cell<F>> {
         if self.in_next_step {
-            &mut self.next
+            self.next.cell_manager.query_cells(cell_type, count)
         } else {
-            &mut self.curr
+            let cells = self.curr.cell_manager.query_cells(cell_type, count);
+            self.queried_cells.extend(cells.iter().cloned());
+            cells
         }
-        .cell_manager
-        .query_cells(cell_type, count)
     }
 
     pub(crate) fn code_hash(&self, codehash: H256) -> Expression<F> {
@@ -666,6 +759,13 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         self.opcode_lookup_at_rlc(index, opcode, 0.expr());
     }
 
+    /// Lookup that the byte at `index` of the current bytecode is a JUMPDEST
+    /// opcode. The bytecode table marks push data with is_code = 0, so a
+    /// JUMPDEST byte inside push data is not a valid destination.
+    pub(crate) fn jumpdest_lookup(&mut self, index: Expression<F>) {
+        self.opcode_lookup_at(index, OpcodeId::JUMPDEST.expr(), 1.expr());
+    }
+
     pub(crate) fn opcode_lookup_rlc(&mut self, opcode: Expression<F>, push_rlc: Expression<F>) {
         self.opcode_lookup_at_rlc(
             self.curr.state.program_counter.expr() + self.program_counter_offset.expr(),
@@ -1309,6 +1409,16 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
     pub(crate) fn stack_push(&mut self, value: Expression<F>) {
         self.stack_pointer_offset = self.stack_pointer_offset.clone() - self.condition_expr();
         self.stack_lookup(true.expr(), self.stack_pointer_offset.expr(), value);
+        // The stack pointer starts at STACK_CAPACITY whatever the stack limit,
+        // so under a lower one the pushed words must stay within the limit.
+        let stack_limit = self.params.chain_spec.stack_limit;
+        if stack_limit < STACK_CAPACITY {
+            self.range_lookup(
+                self.curr.state.stack_pointer.expr() + self.stack_pointer_offset.expr()
+                    - (STACK_CAPACITY - stack_limit).expr(),
+                STACK_CAPACITY as u64,
+            );
+        }
     }
 
     pub(crate) fn stack_lookup(
@@ -1412,7 +1522,7 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
-    // RwTable Padding (Start tag)
+    // RwTable Start
 
     pub(crate) fn rw_table_start_lookup(&mut self, counter: Expression<F>) {
         self.rw_lookup_with_counter(
@@ -1433,6 +1543,27 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // RwTable Padding
+
+    pub(crate) fn rw_table_padding_lookup(&mut self, counter: Expression<F>) {
+        self.rw_lookup_with_counter(
+            "Padding lookup",
+            counter,
+            0.expr(),
+            RwTableTag::Padding,
+            RwValues {
+                id: 0.expr(),
+                address: 0.expr(),
+                field_tag: 0.expr(),
+                storage_key: 0.expr(),
+                value: 0.expr(),
+                value_prev: 0.expr(),
+                aux1: 0.expr(),
+                aux2: 0.expr(),
+            },
+        );
+    }
+
     // Copy Table
 
     #[allow(clippy::too_many_arguments)]
@@ -1736,9 +1867,12 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
             Some(condition) => lookup.conditional(condition),
             None => lookup,
         };
+        let compressed_expr = rlc::expr(&lookup.input_exprs(), self.challenges.lookup_input());
+        self.expression_degrees
+            .push((name.to_string(), compressed_expr.degree()));
         let compressed_expr = self.split_expression(
             "Lookup compression",
-            rlc::expr(&lookup.input_exprs(), self.challenges.lookup_input()),
+            compressed_expr,
             MAX_DEGREE - IMPLICIT_DEGREE,
         );
         self.store_expression(name, compressed_expr, CellType::Lookup(lookup.table()));
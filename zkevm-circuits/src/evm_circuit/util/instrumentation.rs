@@ -17,6 +17,10 @@ type ColumnSize = (usize, usize, usize);
 pub(crate) struct Instrument {
     // States -> Cell Types -> (width, height, num_cells)
     states: Vec<(ExecutionState, StepSize)>,
+    // States -> constraint coverage of the gadget
+    coverage: Vec<(ExecutionState, GadgetCoverage)>,
+    // States -> expression degrees of the gadget
+    degrees: Vec<(ExecutionState, GadgetDegree)>,
 }
 
 impl Instrument {
@@ -36,6 +40,31 @@ impl Instrument {
             .collect::<Vec<_>>();
 
         self.states.push((execution_state, sizes));
+        self.coverage.push((execution_state, cb.coverage()));
+        self.degrees.push((execution_state, cb.degrees()));
+    }
+
+    /// Returns the constraint coverage of each EVM `ExecutionState` gadget.
+    pub(crate) fn coverage(&self) -> &[(ExecutionState, GadgetCoverage)] {
+        &self.coverage
+    }
+
+    /// Returns the expression degrees of each EVM `ExecutionState` gadget.
+    pub(crate) fn degrees(&self) -> &[(ExecutionState, GadgetDegree)] {
+        &self.degrees
+    }
+
+    /// Returns the constraints and lookups of every EVM `ExecutionState`
+    /// gadget whose degree exceeds `target`, as (state, name, degree).
+    pub(crate) fn degrees_exceeding(&self, target: usize) -> Vec<(ExecutionState, &str, usize)> {
+        self.degrees
+            .iter()
+            .flat_map(|(state, degrees)| {
+                degrees
+                    .exceeding(target)
+                    .map(|(name, degree)| (*state, name.as_str(), *degree))
+            })
+            .collect()
     }
 
     /// Dissasembles the instrumentation data and returns a collection of
@@ -128,6 +157,53 @@ impl Instrument {
     }
 }
 
+/// Constraint coverage of the gadget of a particular EVM `ExecutionState`,
+/// collected once the gadget is configured.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct GadgetCoverage {
+    // The number of cells queried by the gadget in its step.
+    pub(crate) cells: usize,
+    // The number of constraints added, including the ones of stored expressions.
+    pub(crate) constraints: usize,
+    // The number of lookups issued.
+    pub(crate) lookups: usize,
+    // The (column index, rotation) of the queried cells never referenced in a
+    // constraint or a lookup. Such a cell is either dead or only used outside of
+    // the gadget constraints, e.g. in a copy constraint.
+    pub(crate) unused_cells: Vec<(usize, usize)>,
+}
+
+/// Degrees of the expressions of the gadget of a particular EVM
+/// `ExecutionState`, taken before they are split into stored expressions to
+/// fit in the circuit degree. Every split costs extra cells in the step, so a
+/// gadget whose expressions stay under `MAX_DEGREE - IMPLICIT_DEGREE` doesn't
+/// pay for them.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct GadgetDegree {
+    // The (name, degree) of each constraint and lookup input, in the order
+    // they were added.
+    pub(crate) expressions: Vec<(String, usize)>,
+}
+
+impl GadgetDegree {
+    /// Returns the maximum degree of the expressions of the gadget, with the
+    /// name of the first expression reaching it.
+    pub(crate) fn max(&self) -> Option<(&str, usize)> {
+        self.expressions
+            .iter()
+            .rev()
+            .max_by_key(|(_, degree)| *degree)
+            .map(|(name, degree)| (name.as_str(), *degree))
+    }
+
+    /// Returns the expressions of the gadget whose degree exceeds `target`.
+    pub(crate) fn exceeding(&self, target: usize) -> impl Iterator<Item = &(String, usize)> {
+        self.expressions
+            .iter()
+            .filter(move |(_, degree)| *degree > target)
+    }
+}
+
 /// Struct which contains a Cost/ColumnType report for a particular EVM
 /// `ExecutionStep`.
 #[derive(Clone, Debug, Default)]
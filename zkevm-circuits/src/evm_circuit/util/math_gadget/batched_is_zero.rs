@@ -3,7 +3,7 @@ use crate::{
         constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
         transpose_val_ret, CachedRegion, Cell, CellType,
     },
-    util::{Expr, Field},
+    util::{expr_phase, Expr, Field},
 };
 use halo2_proofs::{
     circuit::Value,
@@ -20,7 +20,7 @@ impl<F: Field, const N: usize> BatchedIsZeroGadget<F, N> {
     pub(crate) fn construct(cb: &mut EVMConstraintBuilder<F>, values: [Expression<F>; N]) -> Self {
         let max_values_phase = values
             .iter()
-            .map(CellType::expr_phase)
+            .map(expr_phase)
             .max()
             .expect("BatchedIsZeroGadget needs at least one expression");
 
@@ -1,6 +1,9 @@
 use crate::{
     evm_circuit::util::{
-        self, constraint_builder::EVMConstraintBuilder, from_bytes, math_gadget::*, split_u256,
+        constraint_builder::EVMConstraintBuilder,
+        math_gadget::*,
+        split_u256,
+        word::{WordExpr, WordLoHiExpr},
         CachedRegion,
     },
     util::Field,
@@ -9,7 +12,7 @@ use eth_types::Word;
 use halo2_proofs::plonk::{Error, Expression};
 
 /// Returns `1` when `lhs < rhs`, and returns `0` otherwise.
-/// lhs and rhs are both 256-bit word.
+/// lhs and rhs are both 256-bit word, either RLC or lo/hi words.
 #[derive(Clone, Debug)]
 pub struct LtWordGadget<F> {
     comparison_hi: ComparisonGadget<F, 16>,
@@ -19,19 +22,13 @@ pub struct LtWordGadget<F> {
 impl<F: Field> LtWordGadget<F> {
     pub(crate) fn construct(
         cb: &mut EVMConstraintBuilder<F>,
-        lhs: &util::Word<F>,
-        rhs: &util::Word<F>,
+        lhs: impl WordExpr<F>,
+        rhs: impl WordExpr<F>,
     ) -> Self {
-        let comparison_hi = ComparisonGadget::construct(
-            cb,
-            from_bytes::expr(&lhs.cells[16..]),
-            from_bytes::expr(&rhs.cells[16..]),
-        );
-        let lt_lo = LtGadget::construct(
-            cb,
-            from_bytes::expr(&lhs.cells[..16]),
-            from_bytes::expr(&rhs.cells[..16]),
-        );
+        let lhs: WordLoHiExpr<F> = lhs.to_word();
+        let rhs: WordLoHiExpr<F> = rhs.to_word();
+        let comparison_hi = ComparisonGadget::construct(cb, lhs.hi().clone(), rhs.hi().clone());
+        let lt_lo = LtGadget::construct(cb, lhs.lo().clone(), rhs.lo().clone());
         Self {
             comparison_hi,
             lt_lo,
@@ -73,7 +70,10 @@ mod tests {
     use crate::evm_circuit::util::constraint_builder::ConstrainBuilderCommon;
 
     use super::{test_util::*, *};
-    use crate::util::Field;
+    use crate::{
+        evm_circuit::util::{self, word::WordLoHiCell},
+        util::Field,
+    };
     use eth_types::*;
     use halo2_proofs::{halo2curves::bn256::Fr, plonk::Error};
 
@@ -115,6 +115,44 @@ mod tests {
         }
     }
 
+    #[derive(Clone)]
+    /// LtWordLoHiTestContainer: require(a < b), with a lo/hi word `a`
+    struct LtWordLoHiTestContainer<F> {
+        ltword_gadget: LtWordGadget<F>,
+        a: WordLoHiCell<F>,
+        b: util::Word<F>,
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for LtWordLoHiTestContainer<F> {
+        fn configure_gadget_container(cb: &mut EVMConstraintBuilder<F>) -> Self {
+            let a = cb.query_word_unchecked();
+            let b = cb.query_word_rlc();
+            let ltword_gadget = LtWordGadget::<F>::construct(cb, &a, &b);
+            cb.require_equal("a < b", ltword_gadget.expr(), 1.expr());
+            LtWordLoHiTestContainer {
+                ltword_gadget,
+                a,
+                b,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let a = witnesses[0];
+            let b = witnesses[1];
+            let offset = 0;
+
+            self.a.assign_u256(region, offset, a)?;
+            self.b.assign(region, offset, Some(b.to_le_bytes()))?;
+            self.ltword_gadget.assign(region, 0, a, b)?;
+
+            Ok(())
+        }
+    }
+
     #[test]
     fn test_ltword_expect() {
         try_test!(
@@ -151,4 +189,18 @@ mod tests {
             false,
         );
     }
+
+    #[test]
+    fn test_ltword_lo_hi() {
+        try_test!(
+            LtWordLoHiTestContainer<Fr>,
+            [Word::from(90), WORD_HIGH_MAX],
+            true,
+        );
+        try_test!(
+            LtWordLoHiTestContainer<Fr>,
+            [WORD_HIGH_MAX, WORD_LOW_MAX],
+            false,
+        );
+    }
 }
@@ -11,7 +11,7 @@ use crate::{
             constraint_builder::EVMConstraintBuilder, rlc, CachedRegion, CellType, Expr,
             StoredExpression, LOOKUP_CONFIG,
         },
-        Advice, Column, Fixed,
+        Advice, Column, EvmCircuitConfigParams, Fixed,
     },
     table::LookupTable,
 };
@@ -144,6 +144,7 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
             step_curr.clone(),
             step_next,
             &challenges_exprs,
+            EvmCircuitConfigParams::default(),
             ExecutionState::STOP,
         );
         let math_gadget_container = G::configure_gadget_container(&mut cb);
@@ -0,0 +1,152 @@
+//! Representation of 256-bit words as a pair of 128-bit limbs.
+//!
+//! Unlike [`RandomLinearCombination`] words, the limbs of a [`WordLoHi`] do
+//! not depend on any challenge, so they can be queried in the first phase and
+//! compared against other circuits without agreeing on a randomness.
+//!
+//! Gadgets migrate to this representation incrementally: [`WordExpr`] is
+//! implemented for both representations, so a gadget taking `impl
+//! WordExpr<F>` accepts the RLC words of the gadgets which are not migrated
+//! yet as well as the lo/hi words of the migrated ones.
+
+use crate::{
+    evm_circuit::util::{
+        constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+        from_bytes, split_u256, CachedRegion, Cell, RandomLinearCombination,
+    },
+    util::{Expr, Field},
+};
+use eth_types::U256;
+use halo2_proofs::{
+    circuit::{AssignedCell, Value},
+    plonk::{Error, Expression},
+};
+
+/// A 256-bit word as its low and high 128-bit limbs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct WordLoHi<T>([T; 2]);
+
+/// A word of two cells.
+pub(crate) type WordLoHiCell<F> = WordLoHi<Cell<F>>;
+
+/// A word of two expressions.
+pub(crate) type WordLoHiExpr<F> = WordLoHi<Expression<F>>;
+
+impl<T> WordLoHi<T> {
+    /// Create a word from its `[lo, hi]` limbs.
+    pub(crate) fn new(limbs: [T; 2]) -> Self {
+        Self(limbs)
+    }
+
+    /// The low 128 bits.
+    pub(crate) fn lo(&self) -> &T {
+        &self.0[0]
+    }
+
+    /// The high 128 bits.
+    pub(crate) fn hi(&self) -> &T {
+        &self.0[1]
+    }
+
+    /// The `[lo, hi]` limbs.
+    pub(crate) fn limbs(&self) -> &[T; 2] {
+        &self.0
+    }
+
+    /// Apply `f` to both limbs.
+    pub(crate) fn map<U>(self, f: impl FnMut(T) -> U) -> WordLoHi<U> {
+        WordLoHi(self.0.map(f))
+    }
+}
+
+impl<F: Field> WordLoHi<F> {
+    /// The limbs of `value` as field elements.
+    pub(crate) fn from_u256(value: U256) -> Self {
+        let (lo, hi) = split_u256(&value);
+        Self([F::from_u128(lo.as_u128()), F::from_u128(hi.as_u128())])
+    }
+}
+
+impl<F: Field> WordLoHiCell<F> {
+    /// Assign the limbs of `value`.
+    pub(crate) fn assign_u256(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        value: U256,
+    ) -> Result<[Option<AssignedCell<F, F>>; 2], Error> {
+        let values = WordLoHi::<F>::from_u256(value);
+        Ok([
+            self.lo()
+                .assign(region, offset, Value::known(*values.lo()))?,
+            self.hi()
+                .assign(region, offset, Value::known(*values.hi()))?,
+        ])
+    }
+}
+
+impl<F: Field> WordLoHiExpr<F> {
+    /// A constant word.
+    pub(crate) fn from_constant(value: U256) -> Self {
+        WordLoHi::<F>::from_u256(value).map(Expression::Constant)
+    }
+
+    /// A word of a value fitting in the low limb.
+    pub(crate) fn from_lo_unchecked(lo: Expression<F>) -> Self {
+        Self([lo, 0.expr()])
+    }
+
+    /// Constrain both limbs of `self` and `other` to be equal.
+    pub(crate) fn require_equal(
+        &self,
+        cb: &mut EVMConstraintBuilder<F>,
+        name: &'static str,
+        other: impl WordExpr<F>,
+    ) {
+        let other = other.to_word();
+        cb.require_equal(name, self.lo().clone(), other.lo().clone());
+        cb.require_equal(name, self.hi().clone(), other.hi().clone());
+    }
+
+    /// Compress the limbs into a single expression with `randomness`, the
+    /// way lookups to tables with a single value column expect it.
+    pub(crate) fn compress(&self, randomness: Expression<F>) -> Expression<F> {
+        self.lo().clone() + self.hi().clone() * randomness
+    }
+}
+
+/// Something which can be turned into a lo/hi word expression.
+pub(crate) trait WordExpr<F> {
+    /// The lo/hi expressions of the word.
+    fn to_word(&self) -> WordLoHiExpr<F>;
+}
+
+impl<F: Field> WordExpr<F> for WordLoHiCell<F> {
+    fn to_word(&self) -> WordLoHiExpr<F> {
+        WordLoHi([self.lo().expr(), self.hi().expr()])
+    }
+}
+
+impl<F: Field> WordExpr<F> for WordLoHiExpr<F> {
+    fn to_word(&self) -> WordLoHiExpr<F> {
+        self.clone()
+    }
+}
+
+/// Compatibility with the RLC words of the gadgets which are not migrated
+/// yet. The limbs are composed from the byte cells of the word, which are
+/// range checked by the byte lookup.
+impl<F: Field> WordExpr<F> for RandomLinearCombination<F, 32> {
+    fn to_word(&self) -> WordLoHiExpr<F> {
+        WordLoHi([
+            from_bytes::expr(&self.cells[..16]),
+            from_bytes::expr(&self.cells[16..]),
+        ])
+    }
+}
+
+impl<F: Field, W: WordExpr<F>> WordExpr<F> for &W {
+    fn to_word(&self) -> WordLoHiExpr<F> {
+        W::to_word(self)
+    }
+}
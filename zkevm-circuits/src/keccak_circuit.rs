@@ -37,6 +37,7 @@ use crate::{
     util::{Challenges, Field, SubCircuit, SubCircuitConfig},
     witness::{self, keccak::keccak_inputs},
 };
+use bus_mapping::circuit_input_builder::WitnessArena;
 use gadgets::util::{and, not, select, sum, Expr};
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Region, Value},
@@ -999,7 +1000,7 @@ pub struct KeccakCircuit<F: Field> {
     // The input is a two dimensional vector
     // Each input row is a pre-image of the hash
     // The output row of the hash, i.e., the digest is NOT part of the circuit input
-    inputs: Vec<Vec<u8>>,
+    inputs: WitnessArena,
     // The maximum number of rows, for example, 2^20
     // This needs to be large enough for the circuit.
     num_rows: usize,
@@ -1061,9 +1062,9 @@ impl<F: Field> SubCircuit<F> for KeccakCircuit<F> {
 
 impl<F: Field> KeccakCircuit<F> {
     /// Creates a new circuit instance
-    pub fn new(num_rows: usize, inputs: Vec<Vec<u8>>) -> Self {
+    pub fn new(num_rows: usize, inputs: impl Into<WitnessArena>) -> Self {
         KeccakCircuit {
-            inputs,
+            inputs: inputs.into(),
             num_rows,
             _marker: PhantomData,
         }
@@ -1087,7 +1088,8 @@ impl<F: Field> KeccakCircuit<F> {
 
     /// Sets the witness using the data to be hashed
     pub(crate) fn generate_witness(&self, challenges: Challenges<Value<F>>) -> Vec<KeccakRow<F>> {
-        multi_keccak(self.inputs.as_slice(), challenges, self.capacity())
+        let inputs = self.inputs.iter().collect::<Vec<_>>();
+        multi_keccak(&inputs, challenges, self.capacity())
             .expect("Too many inputs for given capacity")
     }
 }
@@ -889,8 +889,8 @@ pub(crate) fn keccak<F: Field>(
 }
 
 /// Genenrate keccak circuit witness from preimages
-pub fn multi_keccak<F: Field>(
-    bytes: &[Vec<u8>],
+pub fn multi_keccak<F: Field, B: AsRef<[u8]> + Sync>(
+    bytes: &[B],
     challenges: Challenges<Value<F>>,
     capacity: Option<usize>,
 ) -> Result<Vec<KeccakRow<F>>, Error> {
@@ -919,12 +919,12 @@ pub fn multi_keccak<F: Field>(
         });
     }
 
-    let total_len: usize = bytes.iter().map(|b| b.len()).sum();
+    let total_len: usize = bytes.iter().map(|b| b.as_ref().len()).sum();
     log::info!("multi keccak total len {total_len}");
     // TODO: optimize the `extend` using Iter?
     let real_rows: Vec<_> = bytes
         .par_iter()
-        .flat_map_iter(|bytes| keccak_rows(bytes, challenges))
+        .flat_map_iter(|bytes| keccak_rows(bytes.as_ref(), challenges))
         .collect();
     rows.extend(real_rows);
     debug!("keccak rows len without padding: {}", rows.len());
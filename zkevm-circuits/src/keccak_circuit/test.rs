@@ -60,7 +60,7 @@ fn variadic_size_check() {
     let k = get_degree() as u32;
     let num_rows = 2usize.pow(k);
     // Empty
-    let inputs = vec![];
+    let inputs: Vec<Vec<u8>> = vec![];
     let circuit = KeccakCircuit::new(num_rows, inputs);
     let prover1 = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
 
@@ -0,0 +1,202 @@
+//! Render the layout of the sub-circuits to SVG files gathered in an HTML
+//! page, to see how the sub-circuits use their columns and where rows are
+//! wasted.
+
+use crate::{
+    util::{log2_ceil, SubCircuit},
+    witness::Block,
+};
+use halo2_proofs::{
+    dev::CircuitLayout,
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem},
+};
+use plotters::prelude::*;
+use std::{
+    error::Error,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Column usage of a configured circuit
+#[derive(Clone, Debug, Default)]
+pub struct ColumnUsage {
+    /// Number of advice columns
+    pub advice: usize,
+    /// Number of fixed columns
+    pub fixed: usize,
+    /// Number of instance columns
+    pub instance: usize,
+    /// Number of selectors
+    pub selectors: usize,
+    /// Number of lookup arguments
+    pub lookups: usize,
+    /// Degree of the constraint system
+    pub degree: usize,
+}
+
+impl ColumnUsage {
+    /// Configure `C` and collect its column usage.
+    pub fn of<C: Circuit<Fr>>() -> Self {
+        let mut cs = ConstraintSystem::default();
+        C::configure(&mut cs);
+        Self {
+            advice: cs.num_advice_columns(),
+            fixed: cs.num_fixed_columns(),
+            instance: cs.num_instance_columns(),
+            selectors: cs.num_selectors(),
+            lookups: cs.lookups().len(),
+            degree: cs.degree(),
+        }
+    }
+}
+
+/// Layout of a sub-circuit rendered by [`dump_sub_circuit`]
+#[derive(Clone, Debug)]
+pub struct LayoutDump {
+    /// Name of the sub-circuit
+    pub name: String,
+    /// Degree of the rendered circuit
+    pub k: u32,
+    /// Number of rows required by the block
+    pub rows: usize,
+    /// Path of the rendered SVG file
+    pub svg: PathBuf,
+    /// Column usage of the sub-circuit
+    pub usage: ColumnUsage,
+}
+
+/// Render the column usage and region placement of the sub-circuit `C` built
+/// from `block` to `<dir>/<name>.svg`.
+pub fn dump_sub_circuit<C: SubCircuit<Fr> + Circuit<Fr>>(
+    name: &str,
+    block: &Block,
+    dir: &Path,
+) -> Result<LayoutDump, Box<dyn Error>> {
+    let (rows, _) = C::min_num_rows_block(block);
+    let k = log2_ceil(rows + C::unusable_rows()).max(4);
+    let circuit = C::new_from_block(block);
+
+    fs::create_dir_all(dir)?;
+    let svg = dir.join(format!("{name}.svg"));
+    {
+        let root = SVGBackend::new(&svg, (1024, 768)).into_drawing_area();
+        root.fill(&WHITE)?;
+        let root = root.titled(&format!("{name} (k = {k})"), ("sans-serif", 30))?;
+        CircuitLayout::default()
+            .show_labels(true)
+            .render::<Fr, _, _>(k, &circuit, &root)?;
+        root.present()?;
+    }
+
+    Ok(LayoutDump {
+        name: name.to_string(),
+        k,
+        rows,
+        svg,
+        usage: ColumnUsage::of::<C>(),
+    })
+}
+
+/// Write an HTML page at `path` with the column usage and the layout of each
+/// dump. The SVG files are referenced relatively to `path`, so they are
+/// expected to be in the same directory.
+pub fn write_html(dumps: &[LayoutDump], path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head><title>Circuit layouts</title></head>\n<body>\n",
+    );
+    writeln!(html, "<table border=\"1\">")?;
+    writeln!(
+        html,
+        "<tr><th>circuit</th><th>k</th><th>rows</th><th>advice</th><th>fixed</th>\
+         <th>instance</th><th>selectors</th><th>lookups</th><th>degree</th></tr>"
+    )?;
+    for dump in dumps {
+        let usage = &dump.usage;
+        writeln!(
+            html,
+            "<tr><td><a href=\"#{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+             <td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            dump.name,
+            dump.name,
+            dump.k,
+            dump.rows,
+            usage.advice,
+            usage.fixed,
+            usage.instance,
+            usage.selectors,
+            usage.lookups,
+            usage.degree,
+        )?;
+    }
+    writeln!(html, "</table>")?;
+    for dump in dumps {
+        let svg = dump
+            .svg
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        writeln!(
+            html,
+            "<h2 id=\"{}\">{}</h2>\n<img src=\"{svg}\" alt=\"{} layout\">",
+            dump.name, dump.name, dump.name,
+        )?;
+    }
+    html.push_str("</body>\n</html>\n");
+    fs::write(path, html)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        evm_circuit::EvmCircuit, keccak_circuit::KeccakCircuit, state_circuit::StateCircuit,
+        witness::block_convert,
+    };
+    use bus_mapping::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::test_ctx::{
+        helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+
+    /// Dumps the layouts of the EVM, state and keccak circuits for a simple
+    /// block to `layout-dump/`.
+    ///
+    /// Run with:
+    /// `cargo test -p zkevm-circuits --release --features layout-dump dump_layouts
+    /// -- --ignored`
+    #[ignore]
+    #[test]
+    fn dump_layouts() {
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0)
+            SHA3
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |b, _| b,
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let block = block_convert(&builder.block, &builder.code_db).unwrap();
+
+        let dir = Path::new("layout-dump");
+        let dumps = vec![
+            dump_sub_circuit::<EvmCircuit<Fr>>("evm", &block, dir).unwrap(),
+            dump_sub_circuit::<StateCircuit<Fr>>("state", &block, dir).unwrap(),
+            dump_sub_circuit::<KeccakCircuit<Fr>>("keccak", &block, dir).unwrap(),
+        ];
+        write_html(&dumps, &dir.join("index.html")).unwrap();
+    }
+}
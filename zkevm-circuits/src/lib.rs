@@ -33,6 +33,8 @@ pub mod ecc_circuit;
 pub mod evm_circuit;
 pub mod exp_circuit;
 pub mod keccak_circuit;
+#[cfg(feature = "layout-dump")]
+pub mod layout_dump;
 pub mod mpt_circuit;
 pub mod pi_circuit;
 pub mod poseidon_circuit;
@@ -136,7 +136,7 @@ const MODEXPCONFIG_EACH_CHIP_ROWS: usize = 39962;
 
 /// ModExp circuit for precompile modexp
 #[derive(Clone, Debug, Default)]
-pub struct ModExpCircuit<F: Field>(Vec<BigModExp>, std::marker::PhantomData<F>);
+pub struct ModExpCircuit<F: Field>(pub(crate) Vec<BigModExp>, std::marker::PhantomData<F>);
 
 impl<F: Field> SubCircuit<F> for ModExpCircuit<F> {
     type Config = ModExpCircuitConfig;
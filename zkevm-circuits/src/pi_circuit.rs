@@ -50,12 +50,15 @@ use halo2_proofs::{
 
 use crate::{
     evm_circuit::param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_U64, N_BYTES_WORD},
-    pi_circuit::param::{COINBASE_OFFSET, DIFFICULTY_OFFSET, NUM_ALL_TXS_OFFSET},
+    pi_circuit::param::{
+        BLOB_GAS_USED_OFFSET, COINBASE_OFFSET, DIFFICULTY_OFFSET, EXCESS_BLOB_GAS_OFFSET,
+        NUM_ALL_TXS_OFFSET, PREV_RANDAO_OFFSET, WITHDRAWALS_ROOT_OFFSET,
+    },
     table::{
         BlockContextFieldTag,
         BlockContextFieldTag::{
-            BaseFee, ChainId, Coinbase, CumNumTxs, Difficulty, GasLimit, NumAllTxs, NumTxs, Number,
-            Timestamp,
+            BaseFee, BlobGasUsed, ChainId, Coinbase, CumNumTxs, Difficulty, ExcessBlobGas,
+            GasLimit, NumAllTxs, NumTxs, Number, PrevRandao, Timestamp, WithdrawalsRoot,
         },
     },
     util::rlc_be_bytes,
@@ -168,6 +171,10 @@ impl PublicData {
                     .chain(block.base_fee.to_be_bytes())
                     .chain(block.gas_limit.to_be_bytes())
                     .chain(num_all_txs.to_be_bytes())
+                    .chain(block.prev_randao.to_be_bytes())
+                    .chain(block.withdrawals_root.to_fixed_bytes())
+                    .chain(block.blob_gas_used.to_be_bytes())
+                    .chain(block.excess_blob_gas.to_be_bytes())
             }))
             // Tx Hashes
             .chain(
@@ -331,6 +338,7 @@ impl BlockContext {
             chain_id,
             coinbase,
             difficulty,
+            prev_randao: Default::default(),
             gas_limit: 0,
             number: Default::default(),
             timestamp: Default::default(),
@@ -338,6 +346,9 @@ impl BlockContext {
             history_hashes: vec![],
             parent_hash: Default::default(),
             state_root: Default::default(),
+            withdrawals_root: Default::default(),
+            blob_gas_used: 0,
+            excess_blob_gas: 0,
         }
     }
 }
@@ -978,6 +989,23 @@ impl<F: Field> PiCircuitConfig<F> {
                 (block.gas_limit.to_be_bytes().to_vec(), GAS_LIMIT_OFFSET),
                 // num txs in block
                 (num_all_txs.to_be_bytes().to_vec(), NUM_ALL_TXS_OFFSET),
+                // prev randao, the mix hash of the header
+                (block.prev_randao.to_be_bytes().to_vec(), PREV_RANDAO_OFFSET),
+                // withdrawals root
+                (
+                    block.withdrawals_root.to_fixed_bytes().to_vec(),
+                    WITHDRAWALS_ROOT_OFFSET,
+                ),
+                // blob gas used
+                (
+                    block.blob_gas_used.to_be_bytes().to_vec(),
+                    BLOB_GAS_USED_OFFSET,
+                ),
+                // excess blob gas
+                (
+                    block.excess_blob_gas.to_be_bytes().to_vec(),
+                    EXCESS_BLOB_GAS_OFFSET,
+                ),
             ] {
                 let (tmp_offset, tmp_rpi_rlc_acc, tmp_rpi_length, cells) = self.assign_field(
                     region,
@@ -1667,8 +1695,20 @@ impl<F: Field> PiCircuitConfig<F> {
                 .cloned()
                 .unwrap_or(0);
             let tag = [
-                Coinbase, Timestamp, Number, Difficulty, GasLimit, BaseFee, ChainId, NumTxs,
-                CumNumTxs, NumAllTxs,
+                Coinbase,
+                Timestamp,
+                Number,
+                Difficulty,
+                GasLimit,
+                BaseFee,
+                ChainId,
+                NumTxs,
+                CumNumTxs,
+                NumAllTxs,
+                PrevRandao,
+                WithdrawalsRoot,
+                BlobGasUsed,
+                ExcessBlobGas,
             ];
 
             // index_cells of same block are equal to block_number.
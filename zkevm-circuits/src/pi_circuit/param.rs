@@ -1,7 +1,7 @@
 /// Fixed by the spec
-pub(super) const BLOCK_LEN: usize = 10;
+pub(super) const BLOCK_LEN: usize = 14;
 pub(super) const BYTE_POW_BASE: u64 = 256;
-pub(super) const BLOCK_HEADER_BYTES_NUM: usize = 58;
+pub(super) const BLOCK_HEADER_BYTES_NUM: usize = 138;
 pub(super) const KECCAK_DIGEST_SIZE: usize = 32;
 
 pub(super) const RPI_CELL_IDX: usize = 0;
@@ -21,3 +21,7 @@ pub(super) const BASE_FEE_OFFSET: usize = 5;
 pub(super) const CHAIN_ID_OFFSET: usize = 6;
 // pub(super) const CUM_NUM_TXS_OFFSET: usize = 8;
 pub(super) const NUM_ALL_TXS_OFFSET: usize = 9;
+pub(super) const PREV_RANDAO_OFFSET: usize = 10;
+pub(super) const WITHDRAWALS_ROOT_OFFSET: usize = 11;
+pub(super) const BLOB_GAS_USED_OFFSET: usize = 12;
+pub(super) const EXCESS_BLOB_GAS_OFFSET: usize = 13;
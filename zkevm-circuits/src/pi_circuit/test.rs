@@ -106,6 +106,51 @@ fn serial_test_simple_pi() {
     );
 }
 
+#[cfg(feature = "scroll")]
+#[test]
+fn serial_test_pi_withdrawals_root_and_blob_gas() {
+    const MAX_TXS: usize = 4;
+    const MAX_CALLDATA: usize = 20;
+    const MAX_INNER_BLOCKS: usize = 4;
+
+    let mut difficulty_be_bytes = [0u8; 32];
+    MOCK_DIFFICULTY.to_big_endian(&mut difficulty_be_bytes);
+    set_var("DIFFICULTY", hex::encode(difficulty_be_bytes));
+    set_var("COINBASE", "0x0000000000000000000000000000000000000000");
+
+    let mut block = block_1tx();
+    for ctx in block.context.ctxs.values_mut() {
+        ctx.withdrawals_root = H256::repeat_byte(0xaa);
+        ctx.blob_gas_used = 0x20000;
+        ctx.excess_blob_gas = 0x40000;
+    }
+
+    let k = 16;
+    assert_eq!(
+        run::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>(k, block.clone()),
+        Ok(())
+    );
+
+    // The header fields are part of the data hash, so a public input computed without them
+    // must be rejected.
+    let honest_public_inputs = PiCircuit::<Fr>::new(MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, &{
+        let mut block = block.clone();
+        for ctx in block.context.ctxs.values_mut() {
+            ctx.excess_blob_gas = 0;
+        }
+        block
+    })
+    .instance();
+    let circuit = PiTestCircuit::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>(PiCircuit::new(
+        MAX_TXS,
+        MAX_CALLDATA,
+        MAX_INNER_BLOCKS,
+        &block,
+    ));
+    let prover = MockProver::run(k, &circuit, honest_public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+}
+
 fn run_size_check<
     F: Field,
     const MAX_TXS: usize,
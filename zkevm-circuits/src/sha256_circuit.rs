@@ -15,7 +15,7 @@ use circuit::{Hasher, SHA256Table as TableTrait};
 pub use halo2_gadgets::sha256::BLOCK_SIZE;
 
 use crate::{
-    table::{LookupTable, SHA256Table},
+    table::{LookupTable, SHA256Table, U8Table},
     util::{Challenges, Field, SubCircuit, SubCircuitConfig},
     witness,
 };
@@ -39,6 +39,8 @@ impl TableTrait for SHA256Table {
 pub struct CircuitConfigArgs<F: Field> {
     /// SHA256 Table
     pub sha256_table: SHA256Table,
+    /// U8Table, shared with the other sub-circuits
+    pub u8_table: U8Table,
     /// Challenges randomness
     pub challenges: Challenges<Expression<F>>,
 }
@@ -51,16 +53,22 @@ impl SubCircuitConfig<Fr> for CircuitConfig {
         meta: &mut ConstraintSystem<Fr>,
         Self::ConfigArgs {
             sha256_table,
+            u8_table,
             challenges,
         }: Self::ConfigArgs,
     ) -> Self {
-        Self::configure(meta, sha256_table, challenges.keccak_input())
+        Self::configure(
+            meta,
+            sha256_table,
+            u8_table.into(),
+            challenges.keccak_input(),
+        )
     }
 }
 
 /// SHA-256 circuit for precompile modexp
 #[derive(Clone, Debug, Default)]
-pub struct SHA256Circuit<F: Field>(Vec<SHA256>, usize, std::marker::PhantomData<F>);
+pub struct SHA256Circuit<F: Field>(pub(crate) Vec<SHA256>, usize, std::marker::PhantomData<F>);
 
 const TABLE16_BLOCK_ROWS: usize = 2114;
 const BLOCK_SIZE_IN_BYTES: usize = BLOCK_SIZE * 4;
@@ -134,19 +142,43 @@ impl SubCircuit<Fr> for SHA256Circuit<Fr> {
         let chng = challenges.keccak_input();
         let mut hasher = Hasher::new(config.clone(), layouter)?;
 
+        let digest_words = |digest: &[u8; 32]| {
+            digest
+                .chunks_exact(4)
+                .map(|bt| bt.iter().fold(0u32, |sum, v| sum * 256 + *v as u32))
+                .collect::<Vec<_>>()
+        };
+
         for hash_event in &self.0 {
             hasher.update(layouter, chng, &hash_event.input)?;
 
             let digest = hasher.finalize(layouter, chng)?;
-            let ref_digest = hash_event
-                .digest
-                .chunks_exact(4)
-                .map(|bt| bt.iter().fold(0u32, |sum, v| sum * 256 + *v as u32))
-                .collect::<Vec<_>>();
-            for (w, check) in digest.into_iter().zip(ref_digest) {
+            for (w, check) in digest.into_iter().zip(digest_words(&hash_event.digest)) {
                 w.0.assert_if_known(|digest_word| *digest_word == check);
             }
 
+            // the chaining of the state across the blocks of a long input must
+            // match the one of the event
+            let block_digests = hasher.take_block_digests();
+            if !hash_event.block_digests.is_empty() {
+                if block_digests.len() != hash_event.block_digests.len() {
+                    log::error!(
+                        "sha256 input of {} bytes assigned in {} blocks, expected {}",
+                        hash_event.input.len(),
+                        block_digests.len(),
+                        hash_event.block_digests.len()
+                    );
+                    return Err(Error::Synthesis);
+                }
+                for (block_digest, ref_digest) in
+                    block_digests.into_iter().zip(&hash_event.block_digests)
+                {
+                    for (w, check) in block_digest.into_iter().zip(digest_words(ref_digest)) {
+                        w.0.assert_if_known(|digest_word| *digest_word == check);
+                    }
+                }
+            }
+
             if hasher.blocks() > self.1 {
                 log::error!("handled 512-bit block exceed limit ({})", self.1);
                 return Err(Error::Synthesis);
@@ -159,6 +191,7 @@ impl SubCircuit<Fr> for SHA256Circuit<Fr> {
             hasher.update(layouter, chng, &[])?;
             hasher.finalize(layouter, chng)?;
         }
+        hasher.take_block_digests();
 
         Ok(())
     }
@@ -150,11 +150,21 @@ impl CircuitConfig {
 
             // constraint the padding byte, notice it in fact constraint the first byte of the final
             // 64-bit integer is 0, but it is ok (we have no so large bytes for 48-bit
-            // integer)
-            let padding_byte_is_zero = s_padding_prev * is_final.clone() * byte;
-
-            let padding_change_on_size =
-                meta.query_selector(self.s_padding_size) * is_final * padding_change;
+            // integer). The padding of a block which is not final spills into the next one, so
+            // its bytes are constrained whatever the block
+            let padding_byte_is_zero = s_padding_prev.clone() * byte.clone();
+
+            let padding_change_on_size = meta.query_selector(self.s_padding_size)
+                * is_final.clone()
+                * padding_change.clone();
+
+            // the last 8 bytes of a block which is not final are padding bytes rather than the
+            // padding size, when the padding spills into the next block
+            let is_not_final = one.clone() - is_final;
+            let spilled_padding_byte_on_change = is_not_final.clone()
+                * padding_change
+                * (byte.clone() - Expression::Constant(Fr::from(128u64)));
+            let spilled_padding_byte_is_zero = is_not_final * s_padding_prev * byte;
 
             Constraints::with_selector(
                 meta.query_selector(self.s_enable),
@@ -165,6 +175,10 @@ impl CircuitConfig {
                 meta.query_selector(self.s_common_bytes),
                 vec![padding_byte_is_zero, padding_byte_on_change],
             ))
+            .chain(Constraints::with_selector(
+                meta.query_selector(self.s_padding_size),
+                vec![spilled_padding_byte_on_change, spilled_padding_byte_is_zero],
+            ))
             .chain(vec![padding_change_on_size.into()])
         });
 
@@ -265,10 +279,12 @@ impl CircuitConfig {
         });
     }
 
-    /// Configures a circuit to include this chip.
+    /// Configures a circuit to include this chip. The `byte_range` table of
+    /// [0, 256) is loaded by the caller.
     pub fn configure(
         meta: &mut ConstraintSystem<Fr>,
         sha256_table: impl SHA256Table,
+        byte_range: TableColumn,
         spec_challenge: Expression<Fr>,
     ) -> Self {
         let copied_data = meta.advice_column();
@@ -289,7 +305,6 @@ impl CircuitConfig {
         let s_assigned_u16 = meta.selector();
 
         let c_data = meta.fixed_column();
-        let byte_range = meta.lookup_table_column();
         let table16 = Table16Chip::configure(meta);
 
         meta.enable_constant(c_data);
@@ -808,24 +823,6 @@ impl CircuitConfig {
 
         Ok(output_cells.try_into().unwrap())
     }
-
-    fn initialize_constant_table(&self, layouter: &mut impl Layouter<Fr>) -> Result<(), Error> {
-        layouter.assign_table(
-            || "byte range constant",
-            |mut tb| {
-                for i in 0..256 {
-                    tb.assign_cell(
-                        || "byte range",
-                        self.byte_range,
-                        i,
-                        || Value::known(Fr::from(i as u64)),
-                    )?;
-                }
-
-                Ok(())
-            },
-        )
-    }
 }
 
 /// sha256 hasher for byte stream
@@ -837,6 +834,7 @@ pub struct Hasher {
     cur_block: Vec<u8>,
     length: usize,
     block_usage: usize,
+    block_digests: Vec<[BlockWord; DIGEST_SIZE]>,
 }
 
 impl Hasher {
@@ -845,6 +843,12 @@ impl Hasher {
         self.block_usage
     }
 
+    /// return the intermediate digests of the 512-bit blocks assigned since the
+    /// last call, the last digest of a finalized input being its final digest
+    pub fn take_block_digests(&mut self) -> Vec<[BlockWord; DIGEST_SIZE]> {
+        std::mem::take(&mut self.block_digests)
+    }
+
     /// return the number bytes current update, 0 indicate a clean status
     pub fn updated_size(&self) -> usize {
         self.length
@@ -852,8 +856,7 @@ impl Hasher {
 
     /// create a hasher, the circuit would be identify when block_usage is the same
     pub fn new(chip: CircuitConfig, layouter: &mut impl Layouter<Fr>) -> Result<Self, Error> {
-        // constant part
-        chip.initialize_constant_table(layouter)?;
+        // constant part, the byte range table being loaded by the owner of the config
         Table16Chip::load(chip.table16.clone(), layouter)?;
 
         let table16_chip = Table16Chip::construct::<Fr>(chip.table16.clone());
@@ -914,6 +917,7 @@ impl Hasher {
             cur_block: Vec::with_capacity(BLOCK_SIZE * 4),
             length: 0,
             block_usage: 0,
+            block_digests: Vec::new(),
         })
     }
 
@@ -997,6 +1001,8 @@ impl Hasher {
             .map(|s| s.map(|v| v.into()))
             .map(Table16State::Dense)?;
         self.block_usage += 1;
+        self.block_digests
+            .push(digest_state.clone().map(|s| s.value()).map(BlockWord));
 
         Ok(Table16State::Dense(digest_state))
     }
@@ -1127,12 +1133,13 @@ impl Hasher {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::table::U8Table;
     use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, plonk::Circuit};
 
     struct MyCircuit(Vec<(Vec<u8>, Option<[u32; 8]>)>);
 
     impl Circuit<Fr> for MyCircuit {
-        type Config = CircuitConfig;
+        type Config = (CircuitConfig, U8Table);
         type FloorPlanner = SimpleFloorPlanner;
 
         fn without_witnesses(&self) -> Self {
@@ -1168,15 +1175,20 @@ mod tests {
                 is_effect: meta.advice_column(),
             };
 
+            let u8_table = U8Table::construct(meta);
             let chng = Expression::Constant(Fr::from(0x1000u64));
-            Self::Config::configure(meta, dev_table, chng)
+            (
+                CircuitConfig::configure(meta, dev_table, u8_table.into(), chng),
+                u8_table,
+            )
         }
 
         fn synthesize(
             &self,
-            config: Self::Config,
+            (config, u8_table): Self::Config,
             mut layouter: impl Layouter<Fr>,
         ) -> Result<(), Error> {
+            u8_table.load(&mut layouter)?;
             let chng_v = Value::known(Fr::from(0x1000u64));
             let mut hasher = Hasher::new(config, &mut layouter)?;
 
@@ -1305,6 +1317,73 @@ mod tests {
         }
     }
 
+    // hashes 58 bytes, whose padding spills into an extra block, with the
+    // given byte in place of the zeros following the first padding byte
+    struct SpilledPaddingCircuit(u8);
+
+    impl Circuit<Fr> for SpilledPaddingCircuit {
+        type Config = (CircuitConfig, U8Table);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            MyCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            (config, u8_table): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            u8_table.load(&mut layouter)?;
+            let chng_v = Value::known(Fr::from(0x1000u64));
+            let mut hasher = Hasher::new(config, &mut layouter)?;
+
+            let len = 58;
+            let mut block = vec![0xff; len];
+            block.push(128);
+            block.resize(BLOCK_SIZE * 4, self.0);
+            let word_block = Hasher::block_transform(&block);
+            hasher.update_block(
+                &mut layouter,
+                chng_v,
+                word_block.as_slice().try_into().unwrap(),
+                Some(len),
+                false,
+            )?;
+
+            let mut block = vec![0; BLOCK_SIZE * 4 - 8];
+            block.extend(((len * 8) as u64).to_be_bytes());
+            let word_block = Hasher::block_transform(&block);
+            hasher.update_block(
+                &mut layouter,
+                chng_v,
+                word_block.as_slice().try_into().unwrap(),
+                Some(0),
+                true,
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sha256_spilled_padding() {
+        let prover = match MockProver::<Fr>::run(17, &SpilledPaddingCircuit(0), vec![]) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{e:#?}"),
+        };
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = match MockProver::<Fr>::run(17, &SpilledPaddingCircuit(1), vec![]) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{e:#?}"),
+        };
+        assert!(prover.verify().is_err());
+    }
+
     #[test]
     fn sha256_complex() {
         let circuit = MyCircuit(vec![
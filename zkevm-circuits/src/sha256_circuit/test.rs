@@ -7,7 +7,7 @@ use rand::rngs::OsRng;
 
 use super::{circuit::*, BLOCK_SIZE};
 
-use crate::util::Challenges;
+use crate::{table::U8Table, util::Challenges};
 use halo2_proofs::{
     halo2curves::bn256::{Bn256, Fr},
     plonk::{Advice, Any, Column, Fixed, SecondPhase},
@@ -30,7 +30,7 @@ struct MyCircuit {
 }
 
 impl Circuit<Fr> for MyCircuit {
-    type Config = (CircuitConfig, Challenges);
+    type Config = (CircuitConfig, U8Table, Challenges);
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
@@ -67,16 +67,22 @@ impl Circuit<Fr> for MyCircuit {
         };
         meta.enable_constant(dev_table.s_enable);
 
+        let u8_table = U8Table::construct(meta);
         let challenges = Challenges::construct(meta);
         let chng = challenges.exprs(meta).keccak_input();
-        (CircuitConfig::configure(meta, dev_table, chng), challenges)
+        (
+            CircuitConfig::configure(meta, dev_table, u8_table.into(), chng),
+            u8_table,
+            challenges,
+        )
     }
 
     fn synthesize(
         &self,
-        (config, challenges): Self::Config,
+        (config, u8_table, challenges): Self::Config,
         mut layouter: impl Layouter<Fr>,
     ) -> Result<(), Error> {
+        u8_table.load(&mut layouter)?;
         let challenges = challenges.values(&layouter);
         let chng_v = challenges.keccak_input();
         let mut hasher = Hasher::new(config, &mut layouter)?;
@@ -20,7 +20,7 @@ use self::{
 };
 use crate::{
     evm_circuit::{param::N_BYTES_WORD, util::rlc},
-    table::{AccountFieldTag, LookupTable, MptTable, RwTable, RwTableTag},
+    table::{AccountFieldTag, LookupTable, MptTable, RwTable, RwTableTag, U16Table, U8Table},
     util::{Challenges, Expr, Field, SubCircuit, SubCircuitConfig},
     witness::{self, MptUpdates, Rw, RwMap},
 };
@@ -84,6 +84,10 @@ pub struct StateCircuitConfigArgs<F: Field> {
     pub rw_table: RwTable,
     /// MptTable
     pub mpt_table: MptTable,
+    /// U8Table, shared with the other sub-circuits
+    pub u8_table: U8Table,
+    /// U16Table, shared with the other sub-circuits
+    pub u16_table: U16Table,
     /// Challenges
     pub challenges: Challenges<Expression<F>>,
 }
@@ -106,12 +110,14 @@ impl<F: Field> SubCircuitConfig<F> for StateCircuitConfig<F> {
         Self::ConfigArgs {
             rw_table,
             mpt_table,
+            u8_table,
+            u16_table,
             challenges,
         }: Self::ConfigArgs,
     ) -> Self {
         let selector = rw_table.q_enable;
         log::debug!("state circuit selector {:?}", selector);
-        let lookups = LookupsChip::configure(meta);
+        let lookups = LookupsChip::configure(meta, u8_table, u16_table);
         let power_of_randomness: [Expression<F>; 31] = challenges.evm_word_powers_of_randomness();
 
         let rw_counter = MpiChip::configure(meta, selector, rw_table.rw_counter, lookups);
@@ -195,6 +201,9 @@ impl<F: Field> SubCircuitConfig<F> for StateCircuitConfig<F> {
         for (name, lookup) in constraint_builder.lookups() {
             meta.lookup_any(name, |_| lookup);
         }
+        for (name, expression) in constraint_builder.range_checks_u16() {
+            lookups.range_check_u16(meta, name, |_| expression);
+        }
 
         config
     }
@@ -234,14 +243,14 @@ impl<F: Field> StateCircuitConfig<F> {
     ) -> Result<StateCircuitExports<Assigned<F>>, Error> {
         let tag_chip = BinaryNumberChip::construct(self.sort_keys.tag);
 
-        let (rows, padding_length) = RwMap::table_assignments_prepad(rows, n_rows);
+        let (rows, padding_length) = RwMap::table_assignments_padding(rows, n_rows);
+        let rows_len = rows.len();
         log::info!(
             "state circuit assign total rows {}, n_rows {}, padding_length {}",
-            rows.len(),
+            rows_len,
             n_rows,
             padding_length
         );
-        let rows_len = rows.len();
 
         let mut state_root =
             randomness.map(|randomness| rlc::value(&updates.old_root().to_le_bytes(), randomness));
@@ -252,7 +261,7 @@ impl<F: Field> StateCircuitConfig<F> {
         self.annotate_circuit_in_region(region);
 
         for (offset, row) in rows.iter().enumerate() {
-            if offset == 0 || offset + 1 >= padding_length {
+            if offset + padding_length < rows_len {
                 log::trace!("state circuit assign offset:{} row:{:?}", offset, row);
             }
 
@@ -386,7 +395,6 @@ impl<F: Field> StateCircuitConfig<F> {
                 || mpt_proof_type,
             )?;
 
-            // TODO: Switch from Rw::Start -> Rw::Padding to simplify this logic.
             // State root assignment is at previous row (offset - 1) because the state root
             // changes on the last access row.
             if offset != 0 {
@@ -579,7 +587,7 @@ impl<F: Field> StateCircuitConfig<F> {
         for (offset, (row, is_first_access)) in
             rows.iter().zip_eq(is_first_access_vec.iter()).enumerate()
         {
-            if offset == 0 || offset + 1 >= padding_length {
+            if offset + padding_length < rows_len {
                 log::trace!("state circuit assign offset:{} row:{:?}", offset, row);
             }
 
@@ -611,7 +619,6 @@ impl<F: Field> StateCircuitConfig<F> {
                 }
             }
 
-            // TODO: Switch from Rw::Start -> Rw::Padding to simplify this logic.
             // State root assignment is at previous row (offset - 1) because the state root
             // changes on the last access row.
             if offset != 0 {
@@ -672,7 +679,7 @@ impl<F: Field> StateCircuitConfig<F> {
         >,
         circuit_exports: &std::cell::RefCell<Option<StateCircuitExports<Assigned<F>>>>,
     ) -> Result<(), Error> {
-        let (rows, padding_length) = RwMap::table_assignments_prepad(rows, n_rows);
+        let (rows, padding_length) = RwMap::table_assignments_padding(rows, n_rows);
         let rows_len = rows.len();
         // each row in rows is uniquely identified by its index
         let indices = (0..rows_len).collect::<Vec<_>>();
@@ -800,11 +807,10 @@ impl<F: Field> StateCircuitConfig<F> {
 
                 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
                 {
+                    // The offsets are relative to the row following the Start row.
                     for ((column, row_offset), &f) in overrides {
                         let advice_column = column.value(self);
-                        let offset =
-                            usize::try_from(isize::try_from(padding_length).unwrap() + *row_offset)
-                                .unwrap();
+                        let offset = usize::try_from(1 + *row_offset).unwrap();
                         region.assign_advice(
                             || "override",
                             advice_column,
@@ -838,7 +844,7 @@ impl<F: Field> StateCircuitConfig<F> {
 /// Keys for sorting the rows of the state circuit
 #[derive(Clone, Copy)]
 pub struct SortKeysConfig {
-    tag: BinaryNumberConfig<RwTableTag, 4>,
+    tag: BinaryNumberConfig<RwTableTag, { RwTableTag::N_BITS }>,
     id: MpiConfig<u32, N_LIMBS_ID>,
     address: MpiConfig<Address, N_LIMBS_ACCOUNT_ADDRESS>,
     field_tag: Column<Advice>,
@@ -920,7 +926,15 @@ impl<F: Field> SubCircuit<F> for StateCircuit<F> {
 
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block) -> (usize, usize) {
-        let total_rw_count = block.rws.0.values().flatten().count() + 1;
+        // The Start row and the rows used by the block
+        let total_rw_count = block
+            .rws
+            .0
+            .iter()
+            .filter(|(tag, _)| !matches!(tag, RwTableTag::Start | RwTableTag::Padding))
+            .map(|(_, rws)| rws.len())
+            .sum::<usize>()
+            + 1;
         (
             total_rw_count,
             std::cmp::max(1 << 16, block.circuits_params.max_rws.max(total_rw_count)),
@@ -1007,12 +1021,10 @@ impl<F: Field> SubCircuit<F> for StateCircuit<F> {
 
                 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
                 {
-                    let padding_length = RwMap::padding_len(self.rows.len(), self.n_rows);
+                    // The offsets are relative to the row following the Start row.
                     for ((column, row_offset), &f) in &self.overrides {
                         let advice_column = column.value(config);
-                        let offset =
-                            usize::try_from(isize::try_from(padding_length).unwrap() + *row_offset)
-                                .unwrap();
+                        let offset = usize::try_from(1 + *row_offset).unwrap();
                         region.assign_advice(
                             || "override",
                             advice_column,
@@ -48,7 +48,7 @@ pub struct Queries<F: Field> {
     pub mpt_update_table: MptUpdateTableQueries<F>,
     pub lexicographic_ordering_selector: Expression<F>,
     pub rw_counter: MpiQueries<F, N_LIMBS_RW_COUNTER>,
-    pub tag_bits: [Expression<F>; 4],
+    pub tag_bits: [Expression<F>; RwTableTag::N_BITS],
     pub id: MpiQueries<F, N_LIMBS_ID>,
     pub is_tag_and_id_unchanged: Expression<F>,
     pub address: MpiQueries<F, N_LIMBS_ACCOUNT_ADDRESS>,
@@ -68,10 +68,12 @@ pub struct Queries<F: Field> {
 
 type Constraint<F> = (&'static str, Expression<F>);
 type Lookup<F> = (&'static str, Vec<(Expression<F>, Expression<F>)>);
+type RangeCheck<F> = (&'static str, Expression<F>);
 
 pub struct ConstraintBuilder<F: Field> {
     pub constraints: Vec<Constraint<F>>,
     lookups: Vec<Lookup<F>>,
+    range_checks_u16: Vec<RangeCheck<F>>,
     condition: Expression<F>,
 }
 
@@ -80,6 +82,7 @@ impl<F: Field> ConstraintBuilder<F> {
         Self {
             constraints: vec![],
             lookups: vec![],
+            range_checks_u16: vec![],
             condition: 1.expr(),
         }
     }
@@ -96,6 +99,10 @@ impl<F: Field> ConstraintBuilder<F> {
         self.lookups.clone()
     }
 
+    pub fn range_checks_u16(&self) -> Vec<RangeCheck<F>> {
+        self.range_checks_u16.clone()
+    }
+
     pub fn build(&mut self, q: &Queries<F>) {
         self.build_general_constraints(q);
         self.build_conditional_constraints(q);
@@ -120,6 +127,7 @@ impl<F: Field> ConstraintBuilder<F> {
                 RwTableTag::AccountTransientStorage => {
                     Self::build_account_transient_storage_constraints
                 }
+                RwTableTag::Padding => Self::build_padding_constraints,
             };
             self.condition(q.tag_matches(tag), |cb| build(cb, q));
         }
@@ -214,10 +222,7 @@ impl<F: Field> ConstraintBuilder<F> {
 
         // The address is aligned.
         let inv_32 = F::from(32).invert().unwrap();
-        self.add_lookup(
-            "limb fits into u16",
-            vec![(q.address.limbs[0].clone() * inv_32, q.lookups.u16.clone())],
-        );
+        self.add_range_check_u16("limb fits into u16", q.address.limbs[0].clone() * inv_32);
 
         // 2.3. value is a word
         // 2.4. Start initial value is 0
@@ -318,6 +323,17 @@ impl<F: Field> ConstraintBuilder<F> {
             );
         });
 
+        // The MPT update of the last access starts from its previous state
+        // root, so with the state root unchanged before it, the initial value
+        // of the access group is the one of the state before the group.
+        self.condition(not::expr(q.last_access()), |cb| {
+            cb.require_equal(
+                "state_root is unchanged before the last access for AccountStorage",
+                q.state_root(),
+                q.state_root_prev(),
+            );
+        });
+
         self.condition(q.not_first_access.clone(), |cb| {
             cb.require_equal(
                 "value column at Rotation::prev() equals value_prev at Rotation::cur()",
@@ -451,6 +467,16 @@ impl<F: Field> ConstraintBuilder<F> {
             );
         });
 
+        // as for AccountStorage, the MPT update of the last access binds the
+        // initial value of the access group
+        self.condition(not::expr(q.last_access()), |cb| {
+            cb.require_equal(
+                "state_root is unchanged before the last access for Account",
+                q.state_root(),
+                q.state_root_prev(),
+            );
+        });
+
         self.condition(q.not_first_access.clone(), |cb| {
             cb.require_equal(
                 "value column at Rotation::prev() equals value_prev at Rotation::cur()",
@@ -542,6 +568,37 @@ impl<F: Field> ConstraintBuilder<F> {
         );
     }
 
+    fn build_padding_constraints(&mut self, q: &Queries<F>) {
+        // 12.0. Unused keys are 0
+        self.require_zero("field_tag is 0 for Padding", q.field_tag());
+        self.require_zero("address is 0 for Padding", q.rw_table.address.clone());
+        self.require_zero("id is 0 for Padding", q.id());
+        self.require_zero(
+            "storage_key is 0 for Padding",
+            q.rw_table.storage_key.clone(),
+        );
+        // 12.1. rw_counter increases by 1 for every non-first Padding row, so the
+        // Padding rows span a range of rw_counter without gaps
+        self.condition(q.not_first_access.clone(), |cb| {
+            cb.require_equal(
+                "rw_counter increases by 1 for every non-first Padding row",
+                q.rw_counter_change(),
+                1.expr(),
+            );
+        });
+        // 12.2. Padding value is 0
+        self.require_zero("Padding value is 0", q.value());
+        // 12.3. Padding initial value is 0
+        self.require_zero("Padding initial_value is 0", q.initial_value());
+        // 12.4. state_root is unchanged
+        self.require_equal(
+            "state_root is unchanged for Padding",
+            q.state_root(),
+            q.state_root_prev(),
+        );
+        self.require_zero("value_prev column is 0 for Padding", q.value_prev_column());
+    }
+
     fn require_zero(&mut self, name: &'static str, e: Expression<F>) {
         self.constraints.push((name, self.condition.clone() * e));
     }
@@ -571,6 +628,11 @@ impl<F: Field> ConstraintBuilder<F> {
         self.lookups.push((name, lookup));
     }
 
+    fn add_range_check_u16(&mut self, name: &'static str, expression: Expression<F>) {
+        self.range_checks_u16
+            .push((name, expression * self.condition.clone()));
+    }
+
     fn condition(&mut self, condition: Expression<F>, build: impl FnOnce(&mut Self)) {
         let original_condition = self.condition.clone();
         self.condition = self.condition.clone() * condition;
@@ -633,7 +695,10 @@ impl<F: Field> Queries<F> {
     }
 
     fn tag_matches(&self, tag: RwTableTag) -> Expression<F> {
-        BinaryNumberConfig::<RwTableTag, 4>::value_equals_expr(tag, self.tag_bits.clone())
+        BinaryNumberConfig::<RwTableTag, { RwTableTag::N_BITS }>::value_equals_expr(
+            tag,
+            self.tag_bits.clone(),
+        )
     }
 
     // be careful! not boolean!!
@@ -2,7 +2,7 @@ pub use super::StateCircuit;
 
 use crate::{
     state_circuit::{StateCircuitConfig, StateCircuitConfigArgs},
-    table::{MptTable, RwTable},
+    table::{FixedTables, MptTable, RwTable},
     util::{Challenges, Field, SubCircuit, SubCircuitConfig},
 };
 use halo2_proofs::{
@@ -14,7 +14,7 @@ impl<F: Field> Circuit<F> for StateCircuit<F>
 where
     F: Field,
 {
-    type Config = (StateCircuitConfig<F>, Challenges);
+    type Config = (StateCircuitConfig<F>, FixedTables, Challenges);
     type FloorPlanner = SimpleFloorPlanner;
     #[cfg(feature = "circuit-params")]
     type Params = ();
@@ -26,6 +26,7 @@ where
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
         let rw_table = RwTable::construct(meta);
         let mpt_table = MptTable::construct(meta);
+        let fixed_tables = FixedTables::construct(meta);
         let challenges = Challenges::construct(meta);
 
         let config = {
@@ -35,17 +36,19 @@ where
                 StateCircuitConfigArgs {
                     rw_table,
                     mpt_table,
+                    u8_table: fixed_tables.u8_table,
+                    u16_table: fixed_tables.u16_table,
                     challenges,
                 },
             )
         };
 
-        (config, challenges)
+        (config, fixed_tables, challenges)
     }
 
     fn synthesize(
         &self,
-        (config, challenges): Self::Config,
+        (config, fixed_tables, challenges): Self::Config,
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
         let challenges = challenges.values(&layouter);
@@ -55,6 +58,7 @@ where
             self.n_rows,
             challenges.evm_word(),
         )?;
+        fixed_tables.load(&mut layouter)?;
         self.synthesize_sub(&config, &challenges, &mut layouter)
     }
 }
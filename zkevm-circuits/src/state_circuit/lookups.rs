@@ -1,4 +1,7 @@
-use crate::{table::CallContextFieldTag, util::Field};
+use crate::{
+    table::{CallContextFieldTag, U16Table, U8Table},
+    util::Field,
+};
 use halo2_proofs::{
     circuit::{Layouter, Value},
     plonk::{Column, ConstraintSystem, Error, Expression, Fixed, VirtualCells},
@@ -11,10 +14,11 @@ use strum::IntoEnumIterator;
 pub struct Config {
     // Can these be TableColumn's?
     // https://github.com/zcash/halo2/blob/642efc1536d3ea2566b04814bd60a00c4745ae22/halo2_proofs/src/plonk/circuit.rs#L266
-    u8: Column<Fixed>,
     u10: Column<Fixed>,
-    u16: Column<Fixed>,
     pub call_context_field_tag: Column<Fixed>,
+    // Shared with the other sub-circuits and loaded by the caller.
+    u8: U8Table,
+    u16: U16Table,
 }
 
 impl Config {
@@ -24,9 +28,9 @@ impl Config {
         msg: &'static str,
         exp_fn: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
     ) {
-        meta.lookup_any(msg, |meta| {
+        meta.lookup(msg, |meta| {
             let exp = exp_fn(meta);
-            vec![(exp, meta.query_fixed(self.u8, Rotation::cur()))]
+            vec![(exp, self.u8.into())]
         });
     }
     pub fn range_check_u10<F: Field>(
@@ -46,27 +50,23 @@ impl Config {
         msg: &'static str,
         exp_fn: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
     ) {
-        meta.lookup_any(msg, |meta| {
+        meta.lookup(msg, |meta| {
             let exp = exp_fn(meta);
-            vec![(exp, meta.query_fixed(self.u16, Rotation::cur()))]
+            vec![(exp, self.u16.into())]
         });
     }
 }
 
 #[derive(Clone)]
 pub struct Queries<F> {
-    pub u8: Expression<F>,
     pub u10: Expression<F>,
-    pub u16: Expression<F>,
     pub call_context_field_tag: Expression<F>,
 }
 
 impl<F: Field> Queries<F> {
     pub fn new(meta: &mut VirtualCells<'_, F>, c: Config) -> Self {
         Self {
-            u8: meta.query_fixed(c.u8, Rotation::cur()),
             u10: meta.query_fixed(c.u10, Rotation::cur()),
-            u16: meta.query_fixed(c.u16, Rotation::cur()),
             call_context_field_tag: meta.query_fixed(c.call_context_field_tag, Rotation::cur()),
         }
     }
@@ -85,16 +85,18 @@ impl<F: Field> Chip<F> {
         }
     }
 
-    pub fn configure(meta: &mut ConstraintSystem<F>) -> Config {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        u8_table: U8Table,
+        u16_table: U16Table,
+    ) -> Config {
         let config = Config {
-            u8: meta.fixed_column(),
             u10: meta.fixed_column(),
-            u16: meta.fixed_column(),
             call_context_field_tag: meta.fixed_column(),
+            u8: u8_table,
+            u16: u16_table,
         };
-        meta.annotate_lookup_any_column(config.u8, || "LOOKUP_u8");
         meta.annotate_lookup_any_column(config.u10, || "LOOKUP_u10");
-        meta.annotate_lookup_any_column(config.u16, || "LOOKUP_u16");
         meta.annotate_lookup_any_column(config.call_context_field_tag, || {
             "LOOKUP_call_context_field_tag"
         });
@@ -102,26 +104,20 @@ impl<F: Field> Chip<F> {
     }
 
     pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
-        for (column, exponent) in [
-            (self.config.u8, 8),
-            (self.config.u10, 10),
-            (self.config.u16, 16),
-        ] {
-            layouter.assign_region(
-                || format!("assign u{exponent} fixed column"),
-                |mut region| {
-                    for i in 0..(1 << exponent) {
-                        region.assign_fixed(
-                            || format!("assign {i} in u{exponent} fixed column"),
-                            column,
-                            i,
-                            || Value::known(F::from(i as u64)),
-                        )?;
-                    }
-                    Ok(())
-                },
-            )?;
-        }
+        layouter.assign_region(
+            || "assign u10 fixed column",
+            |mut region| {
+                for i in 0..(1 << 10) {
+                    region.assign_fixed(
+                        || format!("assign {i} in u10 fixed column"),
+                        self.config.u10,
+                        i,
+                        || Value::known(F::from(i as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )?;
         layouter.assign_region(
             || "assign call_context_field_tags fixed column",
             |mut region| {
@@ -36,6 +36,11 @@ fn state_circuit_unusable_rows() {
     )
 }
 
+#[test]
+fn rw_table_tag_encoding() {
+    gadgets::binary_number::assert_tag_encoding::<RwTableTag, 4>();
+}
+
 fn test_state_circuit_ok(
     memory_ops: Vec<Operation<MemoryOp>>,
     stack_ops: Vec<Operation<StackOp>>,
@@ -281,6 +286,47 @@ fn diff_1_problem_repro() {
     assert_eq!(verify(rows), Ok(()));
 }
 
+#[test]
+fn account_code_fields_read() {
+    let account_address = address!("0x0000000000000000000000000000000000000001");
+    let rows = vec![
+        Rw::Account {
+            rw_counter: 1,
+            is_write: false,
+            account_address,
+            field_tag: AccountFieldTag::KeccakCodeHash,
+            value: U256::from(0x1234),
+            value_prev: U256::from(0x1234),
+        },
+        Rw::Account {
+            rw_counter: 2,
+            is_write: false,
+            account_address,
+            field_tag: AccountFieldTag::CodeHash,
+            value: U256::from(0x5678),
+            value_prev: U256::from(0x5678),
+        },
+        Rw::Account {
+            rw_counter: 3,
+            is_write: false,
+            account_address,
+            field_tag: AccountFieldTag::CodeSize,
+            value: U256::from(100),
+            value_prev: U256::from(100),
+        },
+        Rw::Account {
+            rw_counter: 4,
+            is_write: false,
+            account_address,
+            field_tag: AccountFieldTag::CodeSize,
+            value: U256::from(100),
+            value_prev: U256::from(100),
+        },
+    ];
+
+    assert_eq!(verify(rows), Ok(()));
+}
+
 #[test]
 fn storage_key_rlc() {
     let rows = vec![Rw::AccountStorage {
@@ -730,18 +776,24 @@ fn all_padding() {
 }
 
 #[test]
-fn skipped_start_rw_counter() {
+fn skipped_padding_rw_counter() {
+    let last_row_offset = isize::try_from(N_ROWS - 2).unwrap();
+    // The original rw_counter of the last Padding row is (1 << 16) - 1. Moving
+    // first_different_limb to RwCounterLimb1 keeps the limb difference at 1.
     let overrides = HashMap::from([
         (
-            (AdviceColumn::RwCounter, -1),
-            // The original assignment is 1 << 16.
-            Fr::from((1 << 16) + 1),
+            (AdviceColumn::RwCounter, last_row_offset),
+            Fr::from((1 << 17) - 1),
         ),
-        ((AdviceColumn::RwCounterLimb0, -1), Fr::one()),
+        ((AdviceColumn::RwCounterLimb1, last_row_offset), Fr::one()),
+        ((AdviceColumn::LimbIndexBit4, last_row_offset), Fr::zero()),
     ]);
 
     let result = prover(vec![], overrides).verify_at_rows(N_ROWS - 1..N_ROWS, N_ROWS - 1..N_ROWS);
-    assert_error_matches(result, "rw_counter increases by 1 for every non-first row");
+    assert_error_matches(
+        result,
+        "rw_counter increases by 1 for every non-first Padding row",
+    );
 }
 
 #[test]
@@ -852,7 +904,8 @@ fn invalid_stack_address_change() {
 
 #[test]
 fn invalid_tags() {
-    let first_row_offset = -isize::try_from(N_ROWS).unwrap();
+    // The Start row is right before the row at offset 0.
+    let first_row_offset = -1;
     let tags: BTreeSet<usize> = RwTableTag::iter().map(|x| x as usize).collect();
     for i in 0..16 {
         if tags.contains(&i) {
@@ -1089,6 +1142,42 @@ fn account_transient_storage_changed_state_root() {
     );
 }
 
+#[test]
+fn account_changed_state_root_before_last_access() {
+    let rows = vec![
+        Rw::Account {
+            rw_counter: 1,
+            is_write: false,
+            account_address: Address::default(),
+            field_tag: AccountFieldTag::Balance,
+            value: U256::from(100),
+            value_prev: U256::from(100),
+        },
+        Rw::Account {
+            rw_counter: 2,
+            is_write: false,
+            account_address: Address::default(),
+            field_tag: AccountFieldTag::Balance,
+            value: U256::from(100),
+            value_prev: U256::from(100),
+        },
+    ];
+
+    let overrides = HashMap::from([((AdviceColumn::StateRoot, 0), Fr::from(20))]);
+
+    // the last access also fails its MPT lookup from the changed root
+    let name = "state_root is unchanged before the last access for Account";
+    let errors = verify_with_overrides(rows, overrides).expect_err("result is not an error");
+    assert!(
+        errors.iter().any(|error| matches!(
+            error,
+            VerifyFailure::ConstraintNotSatisfied { constraint, .. }
+                if format!("{constraint}").contains(name)
+        )),
+        "{errors:?}"
+    );
+}
+
 #[test]
 fn variadic_size_check() {
     let mut rows = vec![
@@ -1192,8 +1281,7 @@ fn prover(rows: Vec<Rw>, overrides: HashMap<(AdviceColumn, isize), Fr>) -> MockP
 
 fn verify(rows: Vec<Rw>) -> Result<(), Vec<VerifyFailure>> {
     let used_rows = rows.len();
-    prover(rows, HashMap::new())
-        .verify_at_rows(N_ROWS - used_rows..N_ROWS, N_ROWS - used_rows..N_ROWS)
+    prover(rows, HashMap::new()).verify_at_rows(1..used_rows + 1, 1..used_rows + 1)
 }
 
 fn verify_with_overrides(
@@ -1204,10 +1292,7 @@ fn verify_with_overrides(
     assert_eq!(verify(rows.clone()), Ok(()));
 
     let n_active_rows = rows.len();
-    prover(rows, overrides).verify_at_rows(
-        N_ROWS - n_active_rows..N_ROWS,
-        N_ROWS - n_active_rows..N_ROWS,
-    )
+    prover(rows, overrides).verify_at_rows(1..n_active_rows + 1, 1..n_active_rows + 1)
 }
 
 fn assert_error_matches(result: Result<(), Vec<VerifyFailure>>, name: &str) {
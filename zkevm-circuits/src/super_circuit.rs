@@ -56,6 +56,7 @@ pub mod params;
 pub(crate) mod precompile_block_trace;
 #[cfg(any(feature = "test", test))]
 pub(crate) mod test;
+pub mod tx_inclusion;
 
 #[cfg(feature = "poseidon-codehash")]
 use crate::bytecode_circuit::circuit::to_poseidon_hash::{
@@ -67,7 +68,7 @@ use crate::{
     bytecode_circuit::circuit::{BytecodeCircuit, BytecodeCircuitConfigArgs},
     copy_circuit::{CopyCircuit, CopyCircuitConfig, CopyCircuitConfigArgs},
     ecc_circuit::{EccCircuit, EccCircuitConfig, EccCircuitConfigArgs},
-    evm_circuit::{EvmCircuit, EvmCircuitConfig, EvmCircuitConfigArgs},
+    evm_circuit::{EvmCircuit, EvmCircuitConfig, EvmCircuitConfigArgs, EvmCircuitConfigParams},
     exp_circuit::{ExpCircuit, ExpCircuitArgs, ExpCircuitConfig},
     keccak_circuit::{
         keccak_packed_multi::get_num_rows_per_round, KeccakCircuit, KeccakCircuitConfig,
@@ -84,20 +85,20 @@ use crate::{
     sig_circuit::{SigCircuit, SigCircuitConfig, SigCircuitConfigArgs},
     state_circuit::{StateCircuit, StateCircuitConfig, StateCircuitConfigArgs},
     table::{
-        BlockTable, BytecodeTable, CopyTable, EccTable, ExpTable, KeccakTable, ModExpTable,
-        MptTable, PoseidonTable, PowOfRandTable, RlpFsmRlpTable as RlpTable, RwTable, SHA256Table,
-        SigTable, TxTable, U16Table, U8Table,
+        BlockTable, BytecodeTable, CopyTable, EccTable, ExpTable, FixedTables, KeccakTable,
+        ModExpTable, MptTable, PoseidonTable, PowOfRandTable, RlpFsmRlpTable as RlpTable, RwTable,
+        SHA256Table, SigTable, TxTable,
     },
     tx_circuit::{TxCircuit, TxCircuitConfig, TxCircuitConfigArgs},
-    util::{circuit_stats, log2_ceil, Challenges, SubCircuit, SubCircuitConfig},
+    util::{circuit_stats, log2_ceil, unusable_rows, Challenges, SubCircuit, SubCircuitConfig},
     witness::{block_convert, Block, Transaction},
 };
 
-#[cfg(feature = "zktrie")]
-use crate::mpt_circuit::{MptCircuit, MptCircuitConfig, MptCircuitConfigArgs};
-
-use crate::util::Field;
-use bus_mapping::circuit_input_builder::{CircuitInputBuilder, CircuitsParams};
+use crate::{
+    mpt_circuit::{MptCircuit, MptCircuitConfig, MptCircuitConfigArgs},
+    util::Field,
+};
+use bus_mapping::circuit_input_builder::{CircuitInputBuilder, CircuitsParams, PrecompileEcParams};
 use eth_types::geth_types::GethData;
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
@@ -106,6 +107,7 @@ use halo2_proofs::{
 };
 use itertools::Itertools;
 use snark_verifier_sdk::CircuitExt;
+use std::cell::Cell;
 
 /// Configuration of the Super Circuit
 #[derive(Clone)]
@@ -115,28 +117,122 @@ pub struct SuperCircuitConfig<F: Field> {
     rlp_table: RlpTable,
     tx_table: TxTable,
     poseidon_table: PoseidonTable,
-    u8_table: U8Table,
-    u16_table: U16Table,
+    sig_table: SigTable,
+    modexp_table: ModExpTable,
+    ecc_table: EccTable,
+    sha256_table: SHA256Table,
+    fixed_tables: FixedTables,
+    params: SuperCircuitConfigParams,
     evm_circuit: EvmCircuitConfig<F>,
     state_circuit: StateCircuitConfig<F>,
     tx_circuit: TxCircuitConfig<F>,
-    sig_circuit: SigCircuitConfig<F>,
-    modexp_circuit: ModExpCircuitConfig,
-    ecc_circuit: EccCircuitConfig<F>,
-    sha256_circuit: SHA256CircuitConfig,
+    sig_circuit: Option<SigCircuitConfig<F>>,
+    modexp_circuit: Option<ModExpCircuitConfig>,
+    ecc_circuit: Option<EccCircuitConfig<F>>,
+    sha256_circuit: Option<SHA256CircuitConfig>,
     #[cfg(not(feature = "poseidon-codehash"))]
     bytecode_circuit: BytecodeCircuitConfig<F>,
     #[cfg(feature = "poseidon-codehash")]
     bytecode_circuit: ToHashBlockCircuitConfig<F, HASHBLOCK_BYTES_IN_FIELD>,
     copy_circuit: CopyCircuitConfig<F>,
     keccak_circuit: KeccakCircuitConfig<F>,
-    poseidon_circuit: PoseidonCircuitConfig<F>,
+    poseidon_circuit: Option<PoseidonCircuitConfig<F>>,
     pi_circuit: PiCircuitConfig<F>,
     exp_circuit: ExpCircuitConfig<F>,
     rlp_circuit: RlpCircuitConfig<F>,
     /// Mpt Circuit
-    #[cfg(feature = "zktrie")]
-    mpt_circuit: MptCircuitConfig<F>,
+    mpt_circuit: Option<MptCircuitConfig<F>>,
+}
+
+impl<F: Field> SuperCircuitConfig<F> {
+    /// The poseidon table shared by the sub circuits, for circuits built on
+    /// top of the super circuit to look up poseidon hashes in.
+    pub fn poseidon_table(&self) -> PoseidonTable {
+        self.poseidon_table
+    }
+
+    /// The sub-circuits this configuration was built with.
+    pub fn params(&self) -> SuperCircuitConfigParams {
+        self.params
+    }
+}
+
+thread_local! {
+    static CONFIG_PARAMS: Cell<Option<SuperCircuitConfigParams>> = const { Cell::new(None) };
+}
+
+/// The optional sub-circuits of the Super Circuit, chosen when it is
+/// configured so that one build can instantiate different sets of them.
+///
+/// The table of a disabled sub-circuit is still part of the circuit. It is
+/// loaded from the witness without any constraint, so that the other
+/// sub-circuits can look it up. The Bytecode Circuit hashing the code with
+/// poseidon or keccak is still decided by the `poseidon-codehash` feature, as
+/// the witness generation and the EVM Circuit follow it too.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SuperCircuitConfigParams {
+    /// Enable the Mpt Circuit, which needs the `zktrie` feature for its
+    /// witness
+    pub enable_mpt: bool,
+    /// Enable the Sig Circuit
+    pub enable_sig: bool,
+    /// Enable the Ecc Circuit
+    pub enable_ecc: bool,
+    /// Enable the ModExp Circuit
+    pub enable_modexp: bool,
+    /// Enable the SHA256 Circuit
+    pub enable_sha256: bool,
+    /// Enable the Poseidon Circuit, which the Mpt Circuit and the poseidon
+    /// code hash look up
+    pub enable_poseidon: bool,
+    /// Parameters of the chain proven by the EVM Circuit
+    pub evm: EvmCircuitConfigParams,
+}
+
+impl Default for SuperCircuitConfigParams {
+    /// All the sub-circuits the enabled features support.
+    fn default() -> Self {
+        Self {
+            enable_mpt: cfg!(feature = "zktrie"),
+            enable_sig: true,
+            enable_ecc: true,
+            enable_modexp: true,
+            enable_sha256: true,
+            enable_poseidon: true,
+            evm: EvmCircuitConfigParams::default(),
+        }
+    }
+}
+
+impl SuperCircuitConfigParams {
+    /// Params of the Super Circuits configured, built and sized by the
+    /// current thread, see [`Self::set_current`].
+    pub fn current() -> Self {
+        CONFIG_PARAMS.with(|cell| cell.get()).unwrap_or_default()
+    }
+
+    /// Configure, build and size the Super Circuits of the current thread
+    /// with `params`, or with the default ones for `None`.
+    ///
+    /// The params are part of the circuit, so the proving and verifying keys
+    /// must be generated under the same params as the proofs.
+    pub fn set_current(params: Option<Self>) {
+        if let Some(params) = params {
+            params.check();
+        }
+        CONFIG_PARAMS.with(|cell| cell.set(params));
+    }
+
+    fn check(&self) {
+        assert!(
+            !self.enable_mpt || cfg!(feature = "zktrie"),
+            "the mpt circuit needs the zktrie feature"
+        );
+        assert!(
+            self.enable_poseidon || !(self.enable_mpt || cfg!(feature = "poseidon-codehash")),
+            "the poseidon circuit is looked up by the mpt circuit or the poseidon code hash"
+        );
+    }
 }
 
 /// Circuit configuration arguments
@@ -151,6 +247,8 @@ pub struct SuperCircuitConfigArgs {
     pub mock_randomness: u64,
     /// Challenges
     pub challenges: crate::util::Challenges,
+    /// Sub-circuits to configure
+    pub params: SuperCircuitConfigParams,
 }
 
 impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
@@ -165,8 +263,10 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
             max_inner_blocks: _,
             mock_randomness: _mock_randomness,
             challenges,
+            params,
         }: Self::ConfigArgs,
     ) -> Self {
+        params.check();
         let log_circuit_info = |meta: &ConstraintSystem<Fr>, tag: &str| {
             log::debug!("circuit info after {}: {:#?}", tag, circuit_stats(meta));
         };
@@ -207,10 +307,8 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
         let pow_of_rand_table = PowOfRandTable::construct(meta, &challenges_expr);
         log_circuit_info(meta, "power of randomness table");
 
-        let u8_table = U8Table::construct(meta);
-        log_circuit_info(meta, "u8 table");
-        let u16_table = U16Table::construct(meta);
-        log_circuit_info(meta, "u16 table");
+        let fixed_tables = FixedTables::construct(meta);
+        log_circuit_info(meta, "fixed tables");
 
         assert!(get_num_rows_per_round() == 12);
         let keccak_circuit = KeccakCircuitConfig::new(
@@ -222,24 +320,31 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
         );
         log_circuit_info(meta, "keccak circuit");
 
-        let sha256_circuit = SHA256CircuitConfig::new(
-            meta,
-            SHA256CircuitConfigArgs {
-                sha256_table: sha256_table.clone(),
-                challenges: challenges_expr.clone(),
-            },
-        );
-        log_circuit_info(meta, "sha256 circuit");
+        let sha256_circuit = params.enable_sha256.then(|| {
+            let sha256_circuit = SHA256CircuitConfig::new(
+                meta,
+                SHA256CircuitConfigArgs {
+                    sha256_table: sha256_table.clone(),
+                    u8_table: fixed_tables.u8_table,
+                    challenges: challenges_expr.clone(),
+                },
+            );
+            log_circuit_info(meta, "sha256 circuit");
+            sha256_circuit
+        });
 
-        let poseidon_circuit =
-            PoseidonCircuitConfig::new(meta, PoseidonCircuitConfigArgs { poseidon_table });
-        log_circuit_info(meta, "poseidon circuit");
+        let poseidon_circuit = params.enable_poseidon.then(|| {
+            let poseidon_circuit =
+                PoseidonCircuitConfig::new(meta, PoseidonCircuitConfigArgs { poseidon_table });
+            log_circuit_info(meta, "poseidon circuit");
+            poseidon_circuit
+        });
 
         let rlp_circuit = RlpCircuitConfig::new(
             meta,
             RlpCircuitConfigArgs {
                 rlp_table,
-                u8_table,
+                u8_table: fixed_tables.u8_table,
                 challenges: challenges_expr.clone(),
             },
         );
@@ -264,8 +369,8 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
                 keccak_table: keccak_table.clone(),
                 rlp_table,
                 sig_table,
-                u8_table,
-                u16_table,
+                u8_table: fixed_tables.u8_table,
+                u16_table: fixed_tables.u16_table,
                 pow_of_rand_table,
                 challenges: challenges_expr.clone(),
             },
@@ -309,25 +414,31 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
         );
         log_circuit_info(meta, "copy circuit");
 
-        #[cfg(feature = "zktrie")]
-        let mpt_circuit = MptCircuitConfig::new(
-            meta,
-            MptCircuitConfigArgs {
-                poseidon_table,
-                mpt_table,
-                challenges,
-            },
-        );
-        #[cfg(feature = "zktrie")]
-        log_circuit_info(meta, "zktrie circuit");
+        let mpt_circuit = params.enable_mpt.then(|| {
+            let mpt_circuit = MptCircuitConfig::new(
+                meta,
+                MptCircuitConfigArgs {
+                    poseidon_table,
+                    mpt_table,
+                    challenges,
+                },
+            );
+            log_circuit_info(meta, "zktrie circuit");
+            mpt_circuit
+        });
 
-        let modexp_circuit = ModExpCircuitConfig::new(meta, modexp_table);
-        log_circuit_info(meta, "modexp circuit");
+        let modexp_circuit = params.enable_modexp.then(|| {
+            let modexp_circuit = ModExpCircuitConfig::new(meta, modexp_table);
+            log_circuit_info(meta, "modexp circuit");
+            modexp_circuit
+        });
         let state_circuit = StateCircuitConfig::new(
             meta,
             StateCircuitConfigArgs {
                 rw_table,
                 mpt_table,
+                u8_table: fixed_tables.u8_table,
+                u16_table: fixed_tables.u16_table,
                 challenges: challenges_expr.clone(),
             },
         );
@@ -337,7 +448,7 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
             meta,
             ExpCircuitArgs {
                 exp_table,
-                u16_table,
+                u16_table: fixed_tables.u16_table,
             },
         );
         log_circuit_info(meta, "exp circuit");
@@ -346,18 +457,20 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
             meta,
             EvmCircuitConfigArgs {
                 challenges: challenges_expr.clone(),
+                u8_table: fixed_tables.u8_table,
                 tx_table: tx_table.clone(),
                 rw_table,
                 bytecode_table,
                 block_table: block_table.clone(),
                 copy_table,
                 keccak_table: keccak_table.clone(),
-                sha256_table,
+                sha256_table: sha256_table.clone(),
                 exp_table,
                 sig_table,
                 modexp_table,
                 ecc_table,
                 pow_of_rand_table,
+                params: params.evm,
             },
         );
         log_circuit_info(meta, "evm circuit");
@@ -365,24 +478,30 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
         // Sig Circuit and ECC Circuit use halo2-lib's vertifcal assignments gates
         // and need to be configured after Circuits with higher counts of unique rotation queries
         // (ex. Keccak, EVM) to avoid assigning advice values into blinding area.
-        let sig_circuit = SigCircuitConfig::new(
-            meta,
-            SigCircuitConfigArgs {
-                keccak_table,
-                sig_table,
-                challenges: challenges_expr.clone(),
-            },
-        );
-        log_circuit_info(meta, "sig circuit");
+        let sig_circuit = params.enable_sig.then(|| {
+            let sig_circuit = SigCircuitConfig::new(
+                meta,
+                SigCircuitConfigArgs {
+                    keccak_table,
+                    sig_table,
+                    challenges: challenges_expr.clone(),
+                },
+            );
+            log_circuit_info(meta, "sig circuit");
+            sig_circuit
+        });
 
-        let ecc_circuit = EccCircuitConfig::new(
-            meta,
-            EccCircuitConfigArgs {
-                ecc_table,
-                challenges: challenges_expr,
-            },
-        );
-        log_circuit_info(meta, "ecc circuit");
+        let ecc_circuit = params.enable_ecc.then(|| {
+            let ecc_circuit = EccCircuitConfig::new(
+                meta,
+                EccCircuitConfigArgs {
+                    ecc_table,
+                    challenges: challenges_expr,
+                },
+            );
+            log_circuit_info(meta, "ecc circuit");
+            ecc_circuit
+        });
 
         #[cfg(feature = "onephase")]
         if meta.max_phase() != 0 {
@@ -395,8 +514,12 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
             rlp_table,
             tx_table,
             poseidon_table,
-            u8_table,
-            u16_table,
+            sig_table,
+            modexp_table,
+            ecc_table,
+            sha256_table,
+            fixed_tables,
+            params,
             evm_circuit,
             state_circuit,
             tx_circuit,
@@ -411,7 +534,6 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
             pi_circuit,
             exp_circuit,
             rlp_circuit,
-            #[cfg(feature = "zktrie")]
             mpt_circuit,
         }
     }
@@ -466,8 +588,7 @@ pub struct SuperCircuit<
     pub ecc_circuit: EccCircuit<F, 9>,
     /// Rlp Circuit
     pub rlp_circuit: RlpCircuit<F, Transaction>,
-    /// Mpt Circuit
-    #[cfg(feature = "zktrie")]
+    /// Mpt Circuit, empty when it is disabled
     pub mpt_circuit: MptCircuit<F>,
 
     circuit_params: CircuitsParams,
@@ -495,6 +616,7 @@ impl<
     pub fn min_num_rows_block_subcircuits(block: &Block) -> Vec<SubcircuitRowUsage> {
         let warning_limit = 1_000_000;
         log::debug!("start min_num_rows_block_subcircuits");
+        let params = SuperCircuitConfigParams::current();
         let mut rows = Vec::new();
         let mut push = |name, (usage, full_usage)| {
             if usage > warning_limit {
@@ -502,6 +624,8 @@ impl<
             } else {
                 log::debug!("{name} circuit row: {usage:?}");
             }
+            #[cfg(feature = "metrics")]
+            bus_mapping::metrics::CIRCUIT_ROWS.set(name, usage as u64);
             rows.push((name, (usage, full_usage)));
         };
         let evm = EvmCircuit::<Fr>::min_num_rows_block(block);
@@ -520,26 +644,35 @@ impl<
         push("copy", copy);
         let keccak = KeccakCircuit::<Fr>::min_num_rows_block(block);
         push("keccak", keccak);
-        let sha256 = SHA256Circuit::<Fr>::min_num_rows_block(block);
-        push("sha256", sha256);
+        if params.enable_sha256 {
+            let sha256 = SHA256Circuit::<Fr>::min_num_rows_block(block);
+            push("sha256", sha256);
+        }
         let tx = TxCircuit::<Fr>::min_num_rows_block(block);
         push("tx", tx);
         let rlp = RlpCircuit::<Fr, _>::min_num_rows_block(block);
         push("rlp", rlp);
         let exp = ExpCircuit::<Fr>::min_num_rows_block(block);
         push("exp", exp);
-        let mod_exp = ModExpCircuit::<Fr>::min_num_rows_block(block);
-        push("mod_exp", mod_exp);
+        if params.enable_modexp {
+            let mod_exp = ModExpCircuit::<Fr>::min_num_rows_block(block);
+            push("mod_exp", mod_exp);
+        }
         let pi = PiCircuit::<Fr>::min_num_rows_block(block);
         push("pi", pi);
-        let poseidon = PoseidonCircuit::<Fr>::min_num_rows_block(block);
-        push("poseidon", poseidon);
-        let sig = SigCircuit::<Fr>::min_num_rows_block(block);
-        push("sig", sig);
-        let ecc = EccCircuit::<Fr, 9>::min_num_rows_block(block);
-        push("ecc", ecc);
-        #[cfg(feature = "zktrie")]
-        {
+        if params.enable_poseidon {
+            let poseidon = PoseidonCircuit::<Fr>::min_num_rows_block(block);
+            push("poseidon", poseidon);
+        }
+        if params.enable_sig {
+            let sig = SigCircuit::<Fr>::min_num_rows_block(block);
+            push("sig", sig);
+        }
+        if params.enable_ecc {
+            let ecc = EccCircuit::<Fr, 9>::min_num_rows_block(block);
+            push("ecc", ecc);
+        }
+        if params.enable_mpt {
             let mpt = MptCircuit::<Fr>::min_num_rows_block(block);
             push("mpt", mpt);
         }
@@ -609,8 +742,11 @@ impl<
         let rlp_circuit = RlpCircuit::new_from_block(block);
         let sig_circuit = SigCircuit::new_from_block(block);
         let ecc_circuit = EccCircuit::new_from_block(block);
-        #[cfg(feature = "zktrie")]
-        let mpt_circuit = MptCircuit::new_from_block(block);
+        let mpt_circuit = if SuperCircuitConfigParams::current().enable_mpt {
+            MptCircuit::new_from_block(block)
+        } else {
+            MptCircuit::default()
+        };
         SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS> {
             evm_circuit,
             state_circuit,
@@ -626,7 +762,6 @@ impl<
             sig_circuit,
             modexp_circuit,
             ecc_circuit,
-            #[cfg(feature = "zktrie")]
             mpt_circuit,
             circuit_params: block.circuits_params,
         }
@@ -683,27 +818,76 @@ impl<
         log::debug!("assigning keccak_circuit");
         self.keccak_circuit
             .synthesize_sub(&config.keccak_circuit, challenges, layouter)?;
-        log::debug!("assigning sha256_circuit");
-        self.sha256_circuit
-            .synthesize_sub(&config.sha256_circuit, challenges, layouter)?;
-        log::debug!("assigning poseidon_circuit");
-        self.poseidon_circuit
-            .synthesize_sub(&config.poseidon_circuit, challenges, layouter)?;
+        if let Some(sha256_circuit) = &config.sha256_circuit {
+            log::debug!("assigning sha256_circuit");
+            self.sha256_circuit
+                .synthesize_sub(sha256_circuit, challenges, layouter)?;
+        } else {
+            log::debug!("loading sha256_table");
+            config.sha256_table.dev_load(
+                layouter,
+                self.sha256_circuit
+                    .0
+                    .iter()
+                    .map(|evt| (&evt.input, &evt.digest)),
+                challenges,
+            )?;
+        }
+        // nothing looks up the poseidon table when the poseidon circuit is
+        // disabled, see `SuperCircuitConfigParams::check`
+        if let Some(poseidon_circuit) = &config.poseidon_circuit {
+            log::debug!("assigning poseidon_circuit");
+            self.poseidon_circuit
+                .synthesize_sub(poseidon_circuit, challenges, layouter)?;
+        }
         log::debug!("assigning bytecode_circuit");
         self.bytecode_circuit
             .synthesize_sub(&config.bytecode_circuit, challenges, layouter)?;
         log::debug!("assigning tx_circuit");
         self.tx_circuit
             .synthesize_sub(&config.tx_circuit, challenges, layouter)?;
-        log::debug!("assigning sig_circuit");
-        self.sig_circuit
-            .synthesize_sub(&config.sig_circuit, challenges, layouter)?;
-        log::debug!("assigning ecc_circuit");
-        self.ecc_circuit
-            .synthesize_sub(&config.ecc_circuit, challenges, layouter)?;
-        log::debug!("assigning modexp_circuit");
-        self.modexp_circuit
-            .synthesize_sub(&config.modexp_circuit, challenges, layouter)?;
+        if let Some(sig_circuit) = &config.sig_circuit {
+            log::debug!("assigning sig_circuit");
+            self.sig_circuit
+                .synthesize_sub(sig_circuit, challenges, layouter)?;
+        } else {
+            log::debug!("loading sig_table");
+            config.sig_table.dev_load_signatures(
+                layouter,
+                &self.sig_circuit.signatures,
+                challenges,
+            )?;
+        }
+        if let Some(ecc_circuit) = &config.ecc_circuit {
+            log::debug!("assigning ecc_circuit");
+            self.ecc_circuit
+                .synthesize_sub(ecc_circuit, challenges, layouter)?;
+        } else {
+            log::debug!("loading ecc_table");
+            config.ecc_table.dev_load(
+                layouter,
+                PrecompileEcParams {
+                    ec_add: self.ecc_circuit.max_add_ops,
+                    ec_mul: self.ecc_circuit.max_mul_ops,
+                    ec_pairing: self.ecc_circuit.max_pairing_ops,
+                    ec_pairing_pairs: self.ecc_circuit.max_pairing_pairs,
+                },
+                &self.ecc_circuit.add_ops,
+                &self.ecc_circuit.mul_ops,
+                &self.ecc_circuit.pairing_ops,
+                challenges,
+            )?;
+        }
+        if let Some(modexp_circuit) = &config.modexp_circuit {
+            log::debug!("assigning modexp_circuit");
+            self.modexp_circuit
+                .synthesize_sub(modexp_circuit, challenges, layouter)?;
+        } else {
+            log::debug!("loading modexp_table");
+            config
+                .modexp_table
+                .dev_load(layouter, &self.modexp_circuit.0)?;
+        }
         log::debug!("assigning state_circuit");
         self.state_circuit
             .synthesize_sub(&config.state_circuit, challenges, layouter)?;
@@ -730,11 +914,18 @@ impl<
             .synthesize_sub(&config.rlp_circuit, challenges, layouter)?;
 
         // load both poseidon table and zktrie table
-        #[cfg(feature = "zktrie")]
-        {
+        if let Some(mpt_circuit) = &config.mpt_circuit {
             log::debug!("assigning mpt_circuit");
             self.mpt_circuit
-                .synthesize_sub(&config.mpt_circuit, challenges, layouter)?;
+                .synthesize_sub(mpt_circuit, challenges, layouter)?;
+        } else {
+            log::debug!("loading mpt_table");
+            config.mpt_table.load(
+                layouter,
+                &self.state_circuit.updates,
+                self.state_circuit.n_rows,
+                challenges.evm_word(),
+            )?;
         }
 
         log::debug!("super circuit synthesize_sub done");
@@ -773,6 +964,7 @@ impl<
                     max_inner_blocks: MAX_INNER_BLOCKS,
                     mock_randomness: MOCK_RANDOMNESS,
                     challenges,
+                    params: SuperCircuitConfigParams::current(),
                 },
             ),
             challenges,
@@ -786,8 +978,7 @@ impl<
     ) -> Result<(), Error> {
         let challenges = challenges.values(&layouter);
 
-        config.u8_table.load(&mut layouter)?;
-        config.u16_table.load(&mut layouter)?;
+        config.fixed_tables.load(&mut layouter)?;
 
         self.synthesize_sub(&config, &challenges, &mut layouter)
     }
@@ -854,6 +1045,17 @@ impl<
         assert_eq!(block.circuits_params.max_calldata, MAX_CALLDATA);
         Self::build_from_witness_block(block)
     }
+
+    /// Return the minimum degree `k` such that the rows required by every
+    /// sub-circuit to prove `block`, the shared fixed tables and the blinding
+    /// rows all fit in the circuit.
+    pub fn min_k_for(block: &Block) -> u32 {
+        let (_, rows_needed) = Self::min_num_rows_block(block);
+        let rows_needed = rows_needed.max(FixedTables::min_num_rows());
+        let unusable_rows = Self::unusable_rows().max(unusable_rows::<Fr, Self>());
+        log2_ceil(unusable_rows + rows_needed)
+    }
+
     /// Build super circuit from witness block
     pub fn build_from_witness_block(
         block: Block,
@@ -863,8 +1065,7 @@ impl<
             block.circuits_params
         );
 
-        let (_, rows_needed) = Self::min_num_rows_block(&block);
-        let k = log2_ceil(Self::unusable_rows() + rows_needed);
+        let k = Self::min_k_for(&block);
         log::debug!("super circuit needs k = {}", k);
 
         let circuit =
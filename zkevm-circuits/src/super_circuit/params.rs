@@ -1,9 +1,9 @@
 #![allow(missing_docs)]
 use crate::exp_circuit::param::OFFSET_INCREMENT;
-use bus_mapping::circuit_input_builder::{CircuitsParams, PrecompileEcParams};
+use bus_mapping::circuit_input_builder::{CircuitsParams, PrecompileEcParams, N_PAIRING_PER_OP};
 use halo2_proofs::halo2curves::bn256::Fr;
 
-use super::SuperCircuit;
+use super::{SuperCircuit, SuperCircuitConfigParams};
 
 ////// params for Super Circuit of degree = 20 ////////////
 pub const MAX_TXS: usize = 100;
@@ -42,6 +42,7 @@ pub fn get_super_circuit_params() -> CircuitsParams {
             ec_add: MAX_PRECOMPILE_EC_ADD,
             ec_mul: MAX_PRECOMPILE_EC_MUL,
             ec_pairing: MAX_PRECOMPILE_EC_PAIRING,
+            ec_pairing_pairs: N_PAIRING_PER_OP,
         },
     }
 }
@@ -49,27 +50,36 @@ pub fn get_super_circuit_params() -> CircuitsParams {
 /// The super circuit used for mainnet
 pub type ScrollSuperCircuit = SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, 0x100>;
 
-/// Capacity for each subcircuit
+/// Capacity for each subcircuit enabled by
+/// [`SuperCircuitConfigParams::current`], in the order of
+/// [`SuperCircuit::min_num_rows_block_subcircuits`]
 pub fn get_sub_circuit_limit_and_confidence() -> Vec<(usize, f64)> {
+    let params = SuperCircuitConfigParams::current();
     // Change it to 0.99?
     let default_confidence = 0.95;
     [
-        (MAX_RWS, default_confidence),                          // evm
-        (MAX_RWS, default_confidence),                          // state
-        (MAX_BYTECODE, default_confidence),                     // bytecode
-        (MAX_RWS, default_confidence),                          // copy
-        (MAX_KECCAK_ROWS, default_confidence),                  // keccak
-        (MAX_KECCAK_ROWS, default_confidence),                  // sha256
-        (MAX_VERTICAL_ROWS, default_confidence),                // tx
-        (MAX_CALLDATA, default_confidence),                     // rlp
-        (OFFSET_INCREMENT * MAX_EXP_STEPS, default_confidence), // exp
-        (MAX_KECCAK_ROWS, default_confidence),                  // modexp
-        (MAX_RWS, default_confidence),                          // pi
-        (MAX_POSEIDON_ROWS, default_confidence),                // poseidon
-        (MAX_VERTICAL_ROWS, default_confidence),                // sig
-        (MAX_VERTICAL_ROWS, 1.0),                               // ecc
-        #[cfg(feature = "scroll")]
-        (MAX_MPT_ROWS, default_confidence), // mpt
+        (MAX_RWS, default_confidence, true),         // evm
+        (MAX_RWS, default_confidence, true),         // state
+        (MAX_BYTECODE, default_confidence, true),    // bytecode
+        (MAX_RWS, default_confidence, true),         // copy
+        (MAX_KECCAK_ROWS, default_confidence, true), // keccak
+        (MAX_KECCAK_ROWS, default_confidence, params.enable_sha256), // sha256
+        (MAX_VERTICAL_ROWS, default_confidence, true), // tx
+        (MAX_CALLDATA, default_confidence, true),    // rlp
+        (OFFSET_INCREMENT * MAX_EXP_STEPS, default_confidence, true), // exp
+        (MAX_KECCAK_ROWS, default_confidence, params.enable_modexp), // modexp
+        (MAX_RWS, default_confidence, true),         // pi
+        (
+            MAX_POSEIDON_ROWS,
+            default_confidence,
+            params.enable_poseidon,
+        ), // poseidon
+        (MAX_VERTICAL_ROWS, default_confidence, params.enable_sig), // sig
+        (MAX_VERTICAL_ROWS, 1.0, params.enable_ecc), // ecc
+        (MAX_MPT_ROWS, default_confidence, params.enable_mpt), // mpt
     ]
-    .to_vec()
+    .into_iter()
+    .filter(|(_, _, enabled)| *enabled)
+    .map(|(limit, confidence, _)| (limit, confidence))
+    .collect()
 }
@@ -1,5 +1,5 @@
 #![allow(unused_imports)]
-pub use super::*;
+pub use super::{tx_inclusion::TxInclusionCircuit, *};
 use bus_mapping::{
     circuit_input_builder::CircuitInputBuilder,
     evm::{OpcodeId, PrecompileCallArgs},
@@ -45,6 +45,36 @@ fn super_circuit_created_from_dummy_block() {
     let _circuit = SuperCircuit::<Fr, 128, 2_000_000, 64, 0x1000>::new_from_block(&dummy_block);
 }
 
+#[test]
+fn super_circuit_min_k() {
+    const MAX_TXS: usize = 1;
+    const MAX_CALLDATA: usize = 256;
+    const MAX_INNER_BLOCKS: usize = 1;
+    type Circuit = SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, 0x100>;
+
+    let circuits_params = CircuitsParams {
+        max_txs: MAX_TXS,
+        max_calldata: MAX_CALLDATA,
+        max_inner_blocks: MAX_INNER_BLOCKS,
+        ..Default::default()
+    };
+    let geth_data = block_1tx();
+    let mut builder = bus_mapping::mock::BlockData::new_from_geth_data_with_params(
+        geth_data.clone(),
+        circuits_params,
+    )
+    .new_circuit_input_builder();
+    builder
+        .handle_block(&geth_data.eth_block, &geth_data.geth_traces)
+        .unwrap();
+    let block = block_convert(&builder.block, &builder.code_db).unwrap();
+
+    let k = Circuit::min_k_for(&block);
+    let (_, rows_needed) = Circuit::min_num_rows_block(&block);
+    assert!(Circuit::unusable_rows() + rows_needed <= 1 << k);
+    assert!(FixedTables::min_num_rows() < 1 << k);
+}
+
 #[test]
 fn super_circuit_degree() {
     let mut cs = ConstraintSystem::<Fr>::default();
@@ -56,6 +86,70 @@ fn super_circuit_degree() {
     assert!(cs.degree() <= 9);
 }
 
+#[test]
+fn super_circuit_config_params() {
+    let configure = |params| {
+        SuperCircuitConfigParams::set_current(params);
+        let mut cs = ConstraintSystem::<Fr>::default();
+        let (config, _) = SuperCircuit::<Fr, 1, 32, 64, 0x100>::configure(&mut cs);
+        SuperCircuitConfigParams::set_current(None);
+        (config.params(), cs.num_advice_columns)
+    };
+
+    let (params, all_columns) = configure(None);
+    assert_eq!(params, SuperCircuitConfigParams::default());
+
+    let reduced = SuperCircuitConfigParams {
+        enable_sig: false,
+        enable_ecc: false,
+        enable_modexp: false,
+        enable_sha256: false,
+        ..Default::default()
+    };
+    let (params, reduced_columns) = configure(Some(reduced));
+    assert_eq!(params, reduced);
+    assert!(reduced_columns < all_columns);
+    assert_eq!(SuperCircuitConfigParams::current(), Default::default());
+}
+
+#[test]
+fn tx_inclusion_circuit_degree() {
+    let mut cs = ConstraintSystem::<Fr>::default();
+    TxInclusionCircuit::configure(&mut cs);
+    cs = cs.chunk_lookups();
+    assert!(cs.degree() <= 9);
+
+    let mut super_cs = ConstraintSystem::<Fr>::default();
+    SuperCircuit::<Fr, 1, 32, 64, 0x100>::configure(&mut super_cs);
+    assert!(cs.num_advice_columns < super_cs.num_advice_columns);
+}
+
+#[ignore]
+#[test]
+fn serial_test_tx_inclusion_circuit_1tx_1max_tx() {
+    let circuits_params = CircuitsParams {
+        max_txs: 1,
+        max_calldata: 256,
+        max_inner_blocks: 1,
+        max_rlp_rows: 500,
+        ..Default::default()
+    };
+    let geth_data = block_1tx();
+    let mut builder = bus_mapping::mock::BlockData::new_from_geth_data_with_params(
+        geth_data.clone(),
+        circuits_params,
+    )
+    .new_circuit_input_builder();
+    builder
+        .handle_block(&geth_data.eth_block, &geth_data.geth_traces)
+        .unwrap();
+    let block = block_convert(&builder.block, &builder.code_db).unwrap();
+
+    let (k, circuit, instance) = TxInclusionCircuit::build_from_witness_block(block);
+    let prover = MockProver::run(k, &circuit, instance).unwrap();
+    prover.assert_satisfied_par();
+}
+
 #[cfg(feature = "scroll")]
 fn test_super_circuit<
     const MAX_TXS: usize,
@@ -273,6 +367,48 @@ pub(crate) fn block_2tx() -> GethData {
 
 const TEST_MOCK_RANDOMNESS: u64 = 0x100;
 
+#[cfg(feature = "scroll")]
+#[test]
+fn super_circuit_0tx_test_builder() {
+    const MAX_TXS: usize = 1;
+    const MAX_CALLDATA: usize = 32;
+    const MAX_INNER_BLOCKS: usize = 1;
+    set_var("COINBASE", "0x0000000000000000000000000000000000000000");
+    set_var("CHAIN_ID", "222222");
+    let mut difficulty_be_bytes = [0u8; 32];
+    MOCK_DIFFICULTY.to_big_endian(&mut difficulty_be_bytes);
+    set_var("DIFFICULTY", hex::encode(difficulty_be_bytes));
+
+    // the degree is picked by the builder from the block
+    crate::test_util::CircuitTestBuilder::new_from_test_ctx(block_0tx_ctx())
+        .params(CircuitsParams {
+            max_txs: MAX_TXS,
+            max_calldata: MAX_CALLDATA,
+            max_inner_blocks: MAX_INNER_BLOCKS,
+            max_rws: 64,
+            max_copy_rows: 64,
+            max_exp_steps: 64,
+            max_bytecode: 64,
+            max_mpt_rows: 512,
+            max_poseidon_rows: 128,
+            max_rlp_rows: 64,
+            ..Default::default()
+        })
+        .evm_checks(None)
+        .state_checks(None)
+        .copy_checks(None)
+        .super_checks::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(Box::new(
+            |prover, gate_rows, lookup_rows| {
+                assert_eq!(
+                    prover
+                        .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned()),
+                    Ok(())
+                );
+            },
+        ))
+        .run();
+}
+
 // High memory usage test.  Run in serial with:
 // `cargo test [...] serial_ -- --ignored --test-threads 1`
 
@@ -334,6 +470,49 @@ fn serial_test_super_circuit_1tx_1max_tx() {
     );
 }
 
+#[ignore]
+#[cfg(feature = "scroll")]
+#[test]
+fn serial_test_super_circuit_1tx_test_builder() {
+    const MAX_TXS: usize = 1;
+    const MAX_CALLDATA: usize = 256;
+    const MAX_INNER_BLOCKS: usize = 1;
+    set_var("COINBASE", "0x0000000000000000000000000000000000000000");
+    set_var("CHAIN_ID", MOCK_CHAIN_ID.to_string());
+    let mut difficulty_be_bytes = [0u8; 32];
+    MOCK_DIFFICULTY.to_big_endian(&mut difficulty_be_bytes);
+    set_var("DIFFICULTY", hex::encode(difficulty_be_bytes));
+
+    // the degree is picked by the builder
+    crate::test_util::CircuitTestBuilder::new_from_test_ctx(block_1tx_ctx())
+        .params(CircuitsParams {
+            max_txs: MAX_TXS,
+            max_calldata: MAX_CALLDATA,
+            max_inner_blocks: MAX_INNER_BLOCKS,
+            max_rws: 256,
+            max_copy_rows: 256,
+            max_exp_steps: 256,
+            max_bytecode: 512,
+            max_mpt_rows: 2049,
+            max_poseidon_rows: 512,
+            max_rlp_rows: 500,
+            ..Default::default()
+        })
+        .evm_checks(None)
+        .state_checks(None)
+        .copy_checks(None)
+        .super_checks::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(Box::new(
+            |prover, gate_rows, lookup_rows| {
+                assert_eq!(
+                    prover
+                        .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned()),
+                    Ok(())
+                );
+            },
+        ))
+        .run();
+}
+
 #[ignore]
 #[cfg(feature = "scroll")]
 #[test]
@@ -0,0 +1,318 @@
+//! The Tx Inclusion Circuit is a reduced Super Circuit that only proves the
+//! transactions of a block, without their execution. It contains:
+//!
+//! - the Tx Circuit, with the Sig Circuit checking the signature of every tx
+//!   and the Rlp Circuit checking their encoding, and
+//! - the PublicInputs Circuit, committing to the block headers and the hashes
+//!   of the txs in the tx table,
+//!
+//! along with the Keccak Circuit for the hashes they look up. A proof of it
+//! shows that a signed tx is included in the txs committed to by the public
+//! input hash, which is much cheaper to produce than a proof of the full Super
+//! Circuit.
+//!
+//! The state and withdraw roots of the public input are not connected to the
+//! State and EVM Circuits, so they are not proven by this circuit.
+use crate::{
+    keccak_circuit::{KeccakCircuit, KeccakCircuitConfig, KeccakCircuitConfigArgs},
+    pi_circuit::{PiCircuit, PiCircuitConfig, PiCircuitConfigArgs},
+    rlp_circuit_fsm::{RlpCircuit, RlpCircuitConfig, RlpCircuitConfigArgs},
+    sig_circuit::{SigCircuit, SigCircuitConfig, SigCircuitConfigArgs},
+    table::{
+        BlockTable, FixedTables, KeccakTable, PowOfRandTable, RlpFsmRlpTable as RlpTable, SigTable,
+        TxTable,
+    },
+    tx_circuit::{TxCircuit, TxCircuitConfig, TxCircuitConfigArgs},
+    util::{circuit_stats, log2_ceil, unusable_rows, Challenges, SubCircuit, SubCircuitConfig},
+    witness::{Block, Transaction},
+};
+use bus_mapping::circuit_input_builder::CircuitsParams;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use itertools::Itertools;
+use snark_verifier_sdk::CircuitExt;
+
+/// Configuration of the Tx Inclusion Circuit
+#[derive(Clone)]
+pub struct TxInclusionCircuitConfig {
+    pow_of_rand_table: PowOfRandTable,
+    fixed_tables: FixedTables,
+    keccak_circuit: KeccakCircuitConfig<Fr>,
+    rlp_circuit: RlpCircuitConfig<Fr>,
+    pi_circuit: PiCircuitConfig<Fr>,
+    tx_circuit: TxCircuitConfig<Fr>,
+    sig_circuit: SigCircuitConfig<Fr>,
+}
+
+/// Circuit configuration arguments
+pub struct TxInclusionCircuitConfigArgs {
+    /// Challenges
+    pub challenges: Challenges,
+}
+
+impl SubCircuitConfig<Fr> for TxInclusionCircuitConfig {
+    type ConfigArgs = TxInclusionCircuitConfigArgs;
+
+    /// Configure TxInclusionCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<Fr>,
+        Self::ConfigArgs { challenges }: Self::ConfigArgs,
+    ) -> Self {
+        let log_circuit_info = |meta: &ConstraintSystem<Fr>, tag: &str| {
+            log::debug!("circuit info after {}: {:#?}", tag, circuit_stats(meta));
+        };
+        let challenges_expr = challenges.exprs(meta);
+
+        let tx_table = TxTable::construct(meta);
+        let block_table = BlockTable::construct(meta);
+        let rlp_table = RlpTable::construct(meta);
+        let keccak_table = KeccakTable::construct(meta);
+        let sig_table = SigTable::construct(meta);
+        let pow_of_rand_table = PowOfRandTable::construct(meta, &challenges_expr);
+        let fixed_tables = FixedTables::construct(meta);
+        log_circuit_info(meta, "tables");
+
+        let keccak_circuit = KeccakCircuitConfig::new(
+            meta,
+            KeccakCircuitConfigArgs {
+                keccak_table: keccak_table.clone(),
+                challenges: challenges_expr.clone(),
+            },
+        );
+        log_circuit_info(meta, "keccak circuit");
+
+        let rlp_circuit = RlpCircuitConfig::new(
+            meta,
+            RlpCircuitConfigArgs {
+                rlp_table,
+                u8_table: fixed_tables.u8_table,
+                challenges: challenges_expr.clone(),
+            },
+        );
+        log_circuit_info(meta, "rlp circuit");
+
+        let pi_circuit = PiCircuitConfig::new(
+            meta,
+            PiCircuitConfigArgs {
+                block_table: block_table.clone(),
+                keccak_table: keccak_table.clone(),
+                tx_table: tx_table.clone(),
+                challenges: challenges_expr.clone(),
+            },
+        );
+        log_circuit_info(meta, "pi circuit");
+
+        let tx_circuit = TxCircuitConfig::new(
+            meta,
+            TxCircuitConfigArgs {
+                block_table,
+                tx_table,
+                keccak_table: keccak_table.clone(),
+                rlp_table,
+                sig_table,
+                u8_table: fixed_tables.u8_table,
+                u16_table: fixed_tables.u16_table,
+                pow_of_rand_table,
+                challenges: challenges_expr.clone(),
+            },
+        );
+        log_circuit_info(meta, "tx circuit");
+
+        // As in the Super Circuit, the Sig Circuit is configured last so that
+        // its vertical gates don't assign values into the blinding area.
+        let sig_circuit = SigCircuitConfig::new(
+            meta,
+            SigCircuitConfigArgs {
+                keccak_table,
+                sig_table,
+                challenges: challenges_expr,
+            },
+        );
+        log_circuit_info(meta, "sig circuit");
+
+        TxInclusionCircuitConfig {
+            pow_of_rand_table,
+            fixed_tables,
+            keccak_circuit,
+            rlp_circuit,
+            pi_circuit,
+            tx_circuit,
+            sig_circuit,
+        }
+    }
+}
+
+/// The Tx Inclusion Circuit contains the zkEVM circuits proving the txs of a
+/// block and their commitment in the public input, see the module
+/// documentation.
+#[derive(Clone, Debug)]
+pub struct TxInclusionCircuit {
+    /// Tx Circuit
+    pub tx_circuit: TxCircuit<Fr>,
+    /// Public Input Circuit
+    pub pi_circuit: PiCircuit<Fr>,
+    /// Keccak Circuit
+    pub keccak_circuit: KeccakCircuit<Fr>,
+    /// Rlp Circuit
+    pub rlp_circuit: RlpCircuit<Fr, Transaction>,
+    /// Sig Circuit
+    pub sig_circuit: SigCircuit<Fr>,
+
+    circuit_params: CircuitsParams,
+}
+
+impl SubCircuit<Fr> for TxInclusionCircuit {
+    type Config = TxInclusionCircuitConfig;
+
+    fn unusable_rows() -> usize {
+        itertools::max([
+            TxCircuit::<Fr>::unusable_rows(),
+            KeccakCircuit::<Fr>::unusable_rows(),
+            SigCircuit::<Fr>::unusable_rows(),
+        ])
+        .unwrap()
+    }
+
+    fn new_from_block(block: &Block) -> Self {
+        Self {
+            tx_circuit: TxCircuit::new_from_block(block),
+            pi_circuit: PiCircuit::new_from_block(block),
+            keccak_circuit: KeccakCircuit::new_from_block(block),
+            rlp_circuit: RlpCircuit::new_from_block(block),
+            sig_circuit: SigCircuit::new_from_block(block),
+            circuit_params: block.circuits_params,
+        }
+    }
+
+    /// Returns suitable inputs for the TxInclusionCircuit, in the same order
+    /// as in the Super Circuit.
+    fn instance(&self) -> Vec<Vec<Fr>> {
+        let mut instance = Vec::new();
+        instance.extend_from_slice(&self.keccak_circuit.instance());
+        instance.extend_from_slice(&self.pi_circuit.instance());
+        instance.extend_from_slice(&self.tx_circuit.instance());
+
+        instance
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &Block) -> (usize, usize) {
+        let row_usage = [
+            KeccakCircuit::<Fr>::min_num_rows_block(block),
+            RlpCircuit::<Fr, Transaction>::min_num_rows_block(block),
+            PiCircuit::<Fr>::min_num_rows_block(block),
+            TxCircuit::<Fr>::min_num_rows_block(block),
+            SigCircuit::<Fr>::min_num_rows_block(block),
+        ];
+        (
+            itertools::max(row_usage.iter().map(|x| x.0)).unwrap(),
+            itertools::max(row_usage.iter().map(|x| x.1)).unwrap(),
+        )
+    }
+
+    /// Make the assignments to the TxInclusionCircuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<Fr>>,
+        layouter: &mut impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config
+            .pow_of_rand_table
+            .assign(layouter, challenges, 4094 * 31)?;
+
+        log::debug!("assigning keccak_circuit");
+        self.keccak_circuit
+            .synthesize_sub(&config.keccak_circuit, challenges, layouter)?;
+        log::debug!("assigning tx_circuit");
+        self.tx_circuit
+            .synthesize_sub(&config.tx_circuit, challenges, layouter)?;
+        log::debug!("assigning sig_circuit");
+        self.sig_circuit
+            .synthesize_sub(&config.sig_circuit, challenges, layouter)?;
+
+        log::debug!("assigning pi_circuit");
+        self.pi_circuit
+            .import_tx_values(self.tx_circuit.value_cells.borrow().clone().unwrap());
+        self.pi_circuit
+            .synthesize_sub(&config.pi_circuit, challenges, layouter)?;
+
+        log::debug!("assigning rlp_circuit");
+        self.rlp_circuit
+            .synthesize_sub(&config.rlp_circuit, challenges, layouter)?;
+
+        log::debug!("tx inclusion circuit synthesize_sub done");
+        Ok(())
+    }
+}
+
+impl Circuit<Fr> for TxInclusionCircuit {
+    type Config = (TxInclusionCircuitConfig, Challenges);
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        let dummy_block = Block {
+            circuits_params: self.circuit_params,
+            ..Default::default()
+        };
+        Self::new_from_block(&dummy_block)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let challenges = Challenges::construct(meta);
+        (
+            TxInclusionCircuitConfig::new(meta, TxInclusionCircuitConfigArgs { challenges }),
+            challenges,
+        )
+    }
+
+    fn synthesize(
+        &self,
+        (config, challenges): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let challenges = challenges.values(&layouter);
+
+        config.fixed_tables.load(&mut layouter)?;
+
+        self.synthesize_sub(&config, &challenges, &mut layouter)
+    }
+}
+
+impl CircuitExt<Fr> for TxInclusionCircuit {
+    fn num_instance(&self) -> Vec<usize> {
+        self.instances().iter().map(|l| l.len()).collect_vec()
+    }
+
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        self.instance()
+    }
+}
+
+impl TxInclusionCircuit {
+    /// Return the minimum degree `k` such that the rows required by every
+    /// sub-circuit to prove `block`, the shared fixed tables and the blinding
+    /// rows all fit in the circuit.
+    pub fn min_k_for(block: &Block) -> u32 {
+        let (_, rows_needed) = Self::min_num_rows_block(block);
+        let rows_needed = rows_needed.max(FixedTables::min_num_rows());
+        let unusable_rows = Self::unusable_rows().max(unusable_rows::<Fr, Self>());
+        log2_ceil(unusable_rows + rows_needed)
+    }
+
+    /// Build the tx inclusion circuit from a witness block, returning it with
+    /// the minimum degree it needs and its public inputs.
+    pub fn build_from_witness_block(block: Block) -> (u32, Self, Vec<Vec<Fr>>) {
+        let k = Self::min_k_for(&block);
+        log::debug!("tx inclusion circuit needs k = {}", k);
+
+        let circuit = Self::new_from_block(&block);
+        let instance = circuit.instance();
+        (k, circuit, instance)
+    }
+}
@@ -17,17 +17,18 @@ use crate::{
 use bus_mapping::{
     circuit_input_builder::{
         BigModExp, CopyDataType, CopyEvent, CopyStep, EcAddOp, EcMulOp, EcPairingOp, ExpEvent,
-        PrecompileEcParams,
+        PrecompileEcParams, WitnessArena,
     },
     precompile::PrecompileCalls,
 };
 use core::iter::once;
 use eth_types::{sign_types::SignData, ToLittleEndian, ToWord, Word, H256, U256};
 use ethers_core::utils::keccak256;
-use gadgets::ToScalar;
 use gadgets::{
     binary_number::{BinaryNumberChip, BinaryNumberConfig},
+    binary_number_tag,
     util::{and, not, pow_of_two, split_u256, split_u256_limb64, Expr},
+    ToScalar,
 };
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Region, Value},
@@ -119,90 +120,95 @@ impl<F: Field, C: Into<Column<Any>> + Copy, const W: usize> LookupTable<F> for [
     }
 }
 
-/// Tag used to identify each field in the transaction in a row of the
-/// transaction table.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
-pub enum TxFieldTag {
-    /// Unused tag
-    Null = 0,
-    /// CallData
-    CallData,
-    /// Nonce
-    Nonce,
-    /// GasPrice
-    GasPrice,
-    /// Gas
-    Gas,
-    /// CallerAddress
-    CallerAddress,
-    /// CalleeAddress
-    CalleeAddress,
-    /// IsCreate
-    IsCreate,
-    /// Value
-    Value,
-    /// CallDataRLC
-    CallDataRLC,
-    /// CallDataLength
-    CallDataLength,
-    /// Gas cost for transaction call data (4 for byte == 0, 16 otherwise)
-    CallDataGasCost,
-    /// Gas cost of the transaction data charged in L1
-    TxDataGasCost,
-    /// Chain ID
-    ChainID,
-    /// Signature field V.
-    SigV,
-    /// Signature field R.
-    SigR,
-    /// Signature field S.
-    SigS,
-    /// TxSignLength: Length of the RLP-encoded transaction without the
-    /// signature, used for signing
-    TxSignLength,
-    /// TxSignRLC: RLC of the RLP-encoded transaction without the signature,
-    /// used for signing
-    TxSignRLC,
-    /// TxSignHash: Hash of the transaction without the signature, used for
-    /// signing.
-    TxSignHash,
-    /// TxHashLength: Length of the RLP-encoded signed transaction
-    TxHashLength,
-    /// TxHashRLC: RLC of the RLP-encoded signed transaction
-    TxHashRLC,
-    /// TxHash: Hash of the transaction with the signature
-    TxHash,
-    /// TxType: Type of the transaction
-    TxType,
-    /// Access list address
-    AccessListAddress,
-    /// Access list storage key
-    AccessListStorageKey,
-    /// Access list address count (EIP-2930)
-    AccessListAddressesLen,
-    /// Access list all storage key count (EIP-2930)
-    AccessListStorageKeysLen,
-    /// RLC of access list (EIP-2930)
-    AccessListRLC,
-    /// The block number in which this tx is included.
-    BlockNumber,
-    /// Max Priority Fee Per Gas (EIP1559)
-    MaxPriorityFeePerGas,
-    /// Max Fee Per Gas (EIP1559)
-    MaxFeePerGas,
-}
-impl_expr!(TxFieldTag);
-
-impl From<TxFieldTag> for usize {
-    fn from(t: TxFieldTag) -> Self {
-        t as usize
+binary_number_tag! {
+    /// Tag used to identify each field in the transaction in a row of the
+    /// transaction table.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+    pub enum TxFieldTag {
+        /// Unused tag
+        Null = 0,
+        /// CallData
+        CallData,
+        /// Nonce
+        Nonce,
+        /// GasPrice
+        GasPrice,
+        /// Gas
+        Gas,
+        /// CallerAddress
+        CallerAddress,
+        /// CalleeAddress
+        CalleeAddress,
+        /// IsCreate
+        IsCreate,
+        /// Value
+        Value,
+        /// CallDataRLC
+        CallDataRLC,
+        /// CallDataLength
+        CallDataLength,
+        /// Gas cost for transaction call data (4 for byte == 0, 16 otherwise)
+        CallDataGasCost,
+        /// Gas cost of the transaction data charged in L1
+        TxDataGasCost,
+        /// Chain ID
+        ChainID,
+        /// Signature field V.
+        SigV,
+        /// Signature field R.
+        SigR,
+        /// Signature field S.
+        SigS,
+        /// TxSignLength: Length of the RLP-encoded transaction without the
+        /// signature, used for signing
+        TxSignLength,
+        /// TxSignRLC: RLC of the RLP-encoded transaction without the signature,
+        /// used for signing
+        TxSignRLC,
+        /// TxSignHash: Hash of the transaction without the signature, used for
+        /// signing.
+        TxSignHash,
+        /// TxHashLength: Length of the RLP-encoded signed transaction
+        TxHashLength,
+        /// TxHashRLC: RLC of the RLP-encoded signed transaction
+        TxHashRLC,
+        /// TxHash: Hash of the transaction with the signature
+        TxHash,
+        /// TxType: Type of the transaction
+        TxType,
+        /// Access list address
+        AccessListAddress,
+        /// Access list storage key
+        AccessListStorageKey,
+        /// Access list address count (EIP-2930)
+        AccessListAddressesLen,
+        /// Access list all storage key count (EIP-2930)
+        AccessListStorageKeysLen,
+        /// RLC of access list (EIP-2930)
+        AccessListRLC,
+        /// The block number in which this tx is included.
+        BlockNumber,
+        /// Max Priority Fee Per Gas (EIP1559)
+        MaxPriorityFeePerGas,
+        /// Max Fee Per Gas (EIP1559)
+        MaxFeePerGas,
+        /// Id of the tx whose call data section holds the call data of this tx.
+        /// Txs with the same call data share the section of the first one.
+        CallDataTxId,
     }
 }
+impl_expr!(TxFieldTag);
 
 /// Alias for TxFieldTag used by EVM Circuit
 pub type TxContextFieldTag = TxFieldTag;
 
 /// Table that contains the fields of all Transactions in a block
+///
+/// The call data of a tx is given both as one `CallData` row per byte in the
+/// dynamic part of the table, for byte-level lookups such as CALLDATALOAD or
+/// the copy circuit, and as the `CallDataRLC` row of the fixed part, for
+/// hashing. The tx circuit constrains the RLC row to the RLC of the byte rows,
+/// so lookups into either representation refer to the same call data.
 #[derive(Clone, Debug)]
 pub struct TxTable {
     /// q_enable
@@ -253,7 +259,7 @@ impl TxTable {
             txs.len(),
             max_txs
         );
-        let sum_txs_calldata: usize = txs.iter().map(|tx| tx.call_data.len()).sum();
+        let sum_txs_calldata: usize = txs.iter().map(|tx| tx.call_data_section().len()).sum();
 
         // allow dynamic
         if max_calldata != 0 {
@@ -415,6 +421,86 @@ impl TxTable {
             meta.query_advice(self.value, Rotation::next()), // offset 22: TxType
         ]
     }
+
+    /// Returns the lookup of the `tag` field of tx `tx_id`, enabled by `enable`.
+    /// Only applies to the fields of the fixed part of the table.
+    pub fn field_lookup<F: Field>(
+        &self,
+        meta: &mut VirtualCells<F>,
+        enable: Expression<F>,
+        tx_id: Expression<F>,
+        tag: TxFieldTag,
+        value: Expression<F>,
+    ) -> Vec<(Expression<F>, Expression<F>)> {
+        self.row_lookup(meta, enable, [tx_id, tag.expr(), 0.expr(), value])
+    }
+
+    /// Returns the lookup of byte `index` of the call data section of tx
+    /// `section_tx_id`, enabled by `enable`. Identical call data is only
+    /// assigned once, so the section of a tx is the one of its `CallDataTxId`.
+    pub fn call_data_byte_lookup<F: Field>(
+        &self,
+        meta: &mut VirtualCells<F>,
+        enable: Expression<F>,
+        section_tx_id: Expression<F>,
+        index: Expression<F>,
+        byte: Expression<F>,
+    ) -> Vec<(Expression<F>, Expression<F>)> {
+        self.row_lookup(
+            meta,
+            enable,
+            [section_tx_id, TxFieldTag::CallData.expr(), index, byte],
+        )
+    }
+
+    /// Returns the lookups binding `selector`, the first 4 call data bytes of
+    /// tx `tx_id`, to the call data of the tx: one for `section_tx_id` against
+    /// the `CallDataTxId` of the tx and one per byte of its call data section.
+    /// `enable` must only be set when the call data is at least 4 bytes long,
+    /// which can be checked with a `CallDataLength` lookup.
+    pub fn call_data_selector_lookups<F: Field>(
+        &self,
+        meta: &mut VirtualCells<F>,
+        enable: Expression<F>,
+        tx_id: Expression<F>,
+        section_tx_id: Expression<F>,
+        selector: [Expression<F>; 4],
+    ) -> Vec<Vec<(Expression<F>, Expression<F>)>> {
+        once(self.field_lookup(
+            meta,
+            enable.clone(),
+            tx_id,
+            TxFieldTag::CallDataTxId,
+            section_tx_id.clone(),
+        ))
+        .chain(selector.into_iter().enumerate().map(|(index, byte)| {
+            self.call_data_byte_lookup(
+                meta,
+                enable.clone(),
+                section_tx_id.clone(),
+                index.expr(),
+                byte,
+            )
+        }))
+        .collect()
+    }
+
+    fn row_lookup<F: Field>(
+        &self,
+        meta: &mut VirtualCells<F>,
+        enable: Expression<F>,
+        row: [Expression<F>; 4],
+    ) -> Vec<(Expression<F>, Expression<F>)> {
+        row.into_iter()
+            .zip([self.tx_id, self.tag, self.index, self.value])
+            .map(|(arg, column)| {
+                (
+                    enable.clone() * arg,
+                    meta.query_advice(column, Rotation::cur()),
+                )
+            })
+            .collect()
+    }
 }
 
 impl<F: Field> LookupTable<F> for TxTable {
@@ -452,33 +538,37 @@ impl<F: Field> LookupTable<F> for TxTable {
     }
 }
 
-/// Tag to identify the operation type in a RwTable row
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
-pub enum RwTableTag {
-    /// Start (used for padding)
-    Start = 1,
-    /// Stack operation
-    Stack,
-    /// Memory operation
-    Memory,
-    /// Tx Access List Account operation
-    TxAccessListAccount,
-    /// Tx Access List Account Storage operation
-    TxAccessListAccountStorage,
-    /// Tx Refund operation
-    TxRefund,
-    /// Account operation
-    Account,
-    /// Account Storage operation
-    AccountStorage,
-    /// Account Transient Storage operation
-    AccountTransientStorage,
-    /// Call Context operation
-    CallContext,
-    /// Tx Log operation
-    TxLog,
-    /// Tx Receipt operation
-    TxReceipt,
+binary_number_tag! {
+    /// Tag to identify the operation type in a RwTable row
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
+    pub enum RwTableTag {
+        /// Start (first row of the table)
+        Start = 1,
+        /// Stack operation
+        Stack,
+        /// Memory operation
+        Memory,
+        /// Tx Access List Account operation
+        TxAccessListAccount,
+        /// Tx Access List Account Storage operation
+        TxAccessListAccountStorage,
+        /// Tx Refund operation
+        TxRefund,
+        /// Account operation
+        Account,
+        /// Account Storage operation
+        AccountStorage,
+        /// Account Transient Storage operation
+        AccountTransientStorage,
+        /// Call Context operation
+        CallContext,
+        /// Tx Log operation
+        TxLog,
+        /// Tx Receipt operation
+        TxReceipt,
+        /// Padding (last rows of the table)
+        Padding,
+    }
 }
 impl_expr!(RwTableTag);
 
@@ -497,12 +587,6 @@ impl RwTableTag {
     }
 }
 
-impl From<RwTableTag> for usize {
-    fn from(t: RwTableTag) -> Self {
-        t as usize
-    }
-}
-
 /// Tag for an AccountField in RwTable
 #[derive(Clone, Copy, Debug, EnumIter, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum AccountFieldTag {
@@ -546,7 +630,7 @@ pub enum TxReceiptFieldTag {
 impl_expr!(TxReceiptFieldTag);
 
 /// Tag for a CallContextField in RwTable
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, EnumIter)]
 pub enum CallContextFieldTag {
     /// RwCounterEndOfReversion
     RwCounterEndOfReversion = 1,
@@ -743,7 +827,7 @@ impl RwTable {
         n_rows: usize,
         challenges: Value<F>,
     ) -> Result<(), Error> {
-        let (rows, _) = RwMap::table_assignments_prepad(rws, n_rows);
+        let (rows, _) = RwMap::table_assignments_padding(rws, n_rows);
         for (offset, row) in rows.iter().enumerate() {
             self.assign(region, offset, &row.table_assignment(challenges))?;
         }
@@ -935,9 +1019,20 @@ impl MptTable {
 }
 
 /// The Poseidon hash table shared between Hash Circuit, Mpt Circuit and
-/// Bytecode Circuit
-/// the 5 cols represent [index(final hash of inputs), input0, input1, control,
-/// heading mark]
+/// Bytecode Circuit.
+///
+/// Each enabled row is one permutation step of a hash, the advice columns are
+/// `[hash_id, input0, input1, control, domain_spec, heading_mark]`:
+/// - `hash_id`: the final hash of all the inputs, the same on every row of a hash
+/// - `input0`, `input1`: the [`PoseidonTable::INPUT_WIDTH`] field elements absorbed in this step
+/// - `control`: `HASHABLE_DOMAIN_SPEC * remaining_bytes` for a variable length (bytes) hash, 0 for
+///   a fixed length hash
+/// - `domain_spec`: the domain of a fixed length hash, 0 for a bytes hash
+/// - `heading_mark`: 1 on the first row of a hash, 0 otherwise
+///
+/// Row 0 is an all-zero row. A circuit that needs poseidon hashes looks them
+/// up in this table instead of instantiating its own poseidon chip, the rows
+/// are proven by the Poseidon Circuit.
 #[derive(Clone, Copy, Debug)]
 pub struct PoseidonTable {
     /// Is Enabled
@@ -984,13 +1079,13 @@ impl<F: Field> LookupTable<F> for PoseidonTable {
 
 impl PoseidonTable {
     /// the permutation width of current poseidon table
-    pub(crate) const WIDTH: usize = 3;
+    pub const WIDTH: usize = 3;
 
     /// the input width of current poseidon table
-    pub(crate) const INPUT_WIDTH: usize = Self::WIDTH - 1;
+    pub const INPUT_WIDTH: usize = Self::WIDTH - 1;
 
     /// Construct a new PoseidonTable
-    pub(crate) fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
         Self {
             q_enable: meta.fixed_column(),
             hash_id: meta.advice_column(),
@@ -1011,15 +1106,56 @@ impl PoseidonTable {
         layouter.assign_region(
             || "poseidon table",
             |mut region| {
-                self.assign(&mut region, 0, [Value::known(F::zero()); 6])?;
+                self.assign_row(&mut region, 0, [Value::known(F::zero()); 6])?;
                 for (offset, row) in hashes.iter().enumerate() {
-                    self.assign(&mut region, offset + 1, *row)?;
+                    self.assign_row(&mut region, offset + 1, *row)?;
                 }
                 Ok(())
             },
         )
     }
 
+    /// Generate the poseidon table rows of the code hash of `input`, in the
+    /// layout of [`PoseidonTable`].
+    pub fn assignments<F: Field>(input: &[u8]) -> Vec<[Value<F>; 6]> {
+        use crate::bytecode_circuit::bytecode_unroller::{
+            unroll_to_hash_input_default, HASHBLOCK_BYTES_IN_FIELD,
+        };
+        use eth_types::state_db::CodeDB;
+        use hash_circuit::hash::HASHABLE_DOMAIN_SPEC;
+
+        let ref_hash = Value::known(CodeDB::hash(input).to_word().to_scalar().unwrap());
+        let mut control_len = input.len();
+        let mut rows = Vec::new();
+        for row in unroll_to_hash_input_default::<F>(input.iter().copied()) {
+            assert_ne!(
+                control_len,
+                0,
+                "must have enough len left (original size {})",
+                input.len()
+            );
+            let block_size = HASHBLOCK_BYTES_IN_FIELD * row.len();
+            let control_len_as_flag = F::from_u128(HASHABLE_DOMAIN_SPEC * control_len as u128);
+            rows.push([
+                ref_hash,
+                Value::known(row[0]),
+                Value::known(row[1]),
+                Value::known(control_len_as_flag),
+                // always use domain 0 in codehash
+                Value::known(F::zero()),
+                Value::known(if rows.is_empty() { F::one() } else { F::zero() }),
+            ]);
+            control_len = control_len.saturating_sub(block_size);
+        }
+        assert_eq!(
+            control_len,
+            0,
+            "should have exhaust all bytes (original size {})",
+            input.len()
+        );
+        rows
+    }
+
     /// Provide this function for the case that we want to consume a poseidon
     /// table but without running the full poseidon circuit
     pub fn dev_load<'a, F: Field>(
@@ -1027,121 +1163,46 @@ impl PoseidonTable {
         layouter: &mut impl Layouter<F>,
         inputs: impl IntoIterator<Item = &'a Vec<u8>> + Clone,
     ) -> Result<(), Error> {
-        use crate::bytecode_circuit::bytecode_unroller::{
-            unroll_to_hash_input_default, HASHBLOCK_BYTES_IN_FIELD,
-        };
-        use eth_types::state_db::CodeDB;
-        use hash_circuit::hash::HASHABLE_DOMAIN_SPEC;
-
         layouter.assign_region(
             || "poseidon codehash table",
             |mut region| {
-                let mut offset = 0;
-                let poseidon_table_columns =
-                    <PoseidonTable as LookupTable<F>>::advice_columns(self);
-
-                region.assign_fixed(
-                    || "poseidon table all-zero row",
-                    self.q_enable,
-                    offset,
-                    || Value::known(F::zero()),
-                )?;
-                for column in poseidon_table_columns.iter().copied() {
-                    region.assign_advice(
-                        || "poseidon table all-zero row",
-                        column,
-                        offset,
-                        || Value::known(F::zero()),
-                    )?;
-                }
-                offset += 1;
-                // let nil_hash =
-                //     Value::known(CodeDB::empty_code_hash().to_word().to_scalar().unwrap());
-                // region.assign_fixed(
-                //     || "poseidon table nil input row",
-                //     self.q_enable,
-                //     offset,
-                //     || Value::known(F::one()),
-                // )?;
-                // for (column, value) in poseidon_table_columns
-                //     .iter()
-                //     .copied()
-                //     .zip(once(nil_hash).chain(repeat(Value::known(F::zero()))))
-                // {
-                //     region.assign_advice(
-                //         || "poseidon table nil input row",
-                //         column,
-                //         offset,
-                //         || value,
-                //     )?;
-                // }
-                offset += 1;
-
+                self.assign_row(&mut region, 0, [Value::known(F::zero()); 6])?;
+                // row 1 is kept for the nil input
+                let mut offset = 2;
                 for input in inputs.clone() {
-                    let mut control_len = input.len();
-                    let mut first_row = true;
-                    let ref_hash = Value::known(
-                        CodeDB::hash(input.as_slice())
-                            .to_word()
-                            .to_scalar()
-                            .unwrap(),
-                    );
-                    for row in unroll_to_hash_input_default::<F>(input.iter().copied()) {
-                        assert_ne!(
-                            control_len,
-                            0,
-                            "must have enough len left (original size {})",
-                            input.len()
-                        );
-                        let block_size = HASHBLOCK_BYTES_IN_FIELD * row.len();
-                        let control_len_as_flag =
-                            F::from_u128(HASHABLE_DOMAIN_SPEC * control_len as u128);
-
-                        region.assign_fixed(
-                            || format!("poseidon table row {offset}"),
-                            self.q_enable,
-                            offset,
-                            || Value::known(F::one()),
-                        )?;
-                        for (column, value) in poseidon_table_columns.iter().zip_eq(
-                            once(ref_hash)
-                                .chain(row.map(Value::known))
-                                .chain(once(Value::known(control_len_as_flag)))
-                                .chain(once(Value::known(F::zero()))) // always use domain 0 in codehash
-                                .chain(once(Value::known(if first_row {
-                                    F::one()
-                                } else {
-                                    F::zero()
-                                }))),
-                        ) {
-                            region.assign_advice(
-                                || format!("poseidon table row {offset}"),
-                                *column,
-                                offset,
-                                || value,
-                            )?;
-                        }
-                        first_row = false;
+                    for row in Self::assignments(input) {
+                        self.assign_row(&mut region, offset, row)?;
                         offset += 1;
-                        control_len = if control_len > block_size {
-                            control_len - block_size
-                        } else {
-                            0
-                        };
                     }
-                    assert_eq!(
-                        control_len,
-                        0,
-                        "should have exhaust all bytes (original size {})",
-                        input.len()
-                    );
                 }
                 Ok(())
             },
         )
     }
 
-    fn assign<F: Field>(
+    /// returns matchings between the circuit columns passed as parameters and
+    /// the table columns
+    pub fn match_columns(
+        &self,
+        hash_id: Column<Advice>,
+        inputs: [Column<Advice>; Self::INPUT_WIDTH],
+        control: Column<Advice>,
+        domain_spec: Column<Advice>,
+        heading_mark: Column<Advice>,
+    ) -> Vec<(Column<Advice>, Column<Advice>)> {
+        vec![
+            (hash_id, self.hash_id),
+            (inputs[0], self.input0),
+            (inputs[1], self.input1),
+            (control, self.control),
+            (domain_spec, self.domain_spec),
+            (heading_mark, self.heading_mark),
+        ]
+    }
+
+    /// Assign an enabled row, `row` holds the values of the advice columns in
+    /// the layout of [`PoseidonTable`]
+    pub fn assign_row<F: Field>(
         &self,
         region: &mut Region<'_, F>,
         offset: usize,
@@ -1330,6 +1391,14 @@ pub enum BlockContextFieldTag {
     /// included in this block which also taking skipped l1 msgs into account.
     /// This could possibly be larger than NumTxs.
     NumAllTxs,
+    /// PrevRandao field (EIP-4399), the mix hash of the header
+    PrevRandao,
+    /// Withdrawals Root field (EIP-4895)
+    WithdrawalsRoot,
+    /// Blob Gas Used field (EIP-4844)
+    BlobGasUsed,
+    /// Excess Blob Gas field (EIP-4844)
+    ExcessBlobGas,
 }
 impl_expr!(BlockContextFieldTag);
 
@@ -1529,10 +1598,10 @@ impl KeccakTable {
     }
     /// Provide this function for the case that we want to consume a keccak
     /// table but without running the full keccak circuit
-    pub fn dev_load<'a, F: Field>(
+    pub fn dev_load<'a, F: Field, B: AsRef<[u8]> + ?Sized + 'a>(
         &self,
         layouter: &mut impl Layouter<F>,
-        inputs: impl IntoIterator<Item = &'a Vec<u8>> + Clone,
+        inputs: impl IntoIterator<Item = &'a B> + Clone,
         challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
         layouter.assign_region(
@@ -1557,7 +1626,7 @@ impl KeccakTable {
 
                 let keccak_table_columns = <KeccakTable as LookupTable<F>>::advice_columns(self);
                 for input in inputs.clone() {
-                    for row in Self::assignments(input, challenges) {
+                    for row in Self::assignments(input.as_ref(), challenges) {
                         region.assign_fixed(
                             || format!("keccak table row {offset}"),
                             self.q_enable,
@@ -1730,6 +1799,8 @@ pub struct CopyTable {
     /// 1. Call ID/Caller ID for CopyDataType::Memory
     /// 2. RLC encoding of bytecode hash for CopyDataType::Bytecode
     /// 3. Transaction ID for CopyDataType::TxCalldata, CopyDataType::TxLog
+    /// 4. Call ID of the precompile call for CopyDataType::PrecompileInput,
+    ///    CopyDataType::PrecompileOutput
     pub id: Column<Advice>,
     /// The source/destination address for this copy step.  Can be memory
     /// address, byte index in the bytecode, tx call data, and tx log data.
@@ -1793,9 +1864,11 @@ impl CopyTable {
         }
     }
 
-    /// Generate the copy table and copy circuit assignments from a copy event.
+    /// Generate the copy table and copy circuit assignments from a copy event,
+    /// whose bytes are stored in `copy_bytes`.
     pub fn assignments<F: Field>(
         copy_event: &CopyEvent,
+        copy_bytes: &WitnessArena,
         challenges: Challenges<Value<F>>,
     ) -> Vec<(CopyDataType, CopyTableRow<F>, CopyCircuitRow<F>)> {
         assert!(copy_event.src_addr_end >= copy_event.src_addr);
@@ -1807,13 +1880,12 @@ impl CopyTable {
 
         let mut assignments = Vec::new();
         // rlc_acc
+        let read_steps = copy_event.copy_bytes.bytes(copy_bytes);
         let rlc_acc = if copy_event.has_rlc() {
-            let values = copy_event
-                .copy_bytes
-                .bytes
+            let values = read_steps
                 .iter()
                 .filter(|(_, _, mask)| !mask)
-                .map(|(value, _, _)| *value)
+                .map(|(value, _, _)| value)
                 .collect::<Vec<u8>>();
 
             challenges
@@ -1823,17 +1895,15 @@ impl CopyTable {
             Value::known(F::zero())
         };
 
-        let read_steps = copy_event.copy_bytes.bytes.iter();
-        let copy_steps = if let Some(ref write_steps) = copy_event.copy_bytes.aux_bytes {
-            read_steps.zip(write_steps.iter())
-        } else {
-            read_steps.zip(copy_event.copy_bytes.bytes.iter())
-        };
+        let write_steps = copy_event
+            .copy_bytes
+            .aux_bytes(copy_bytes)
+            .unwrap_or(read_steps);
+        let copy_steps = read_steps.iter().zip(write_steps.iter());
 
-        let prev_write_bytes: Vec<u8> = copy_event
+        let prev_write_bytes = copy_event
             .copy_bytes
-            .bytes_write_prev
-            .clone()
+            .bytes_write_prev(copy_bytes)
             .unwrap_or_default();
 
         let mut rw_counter = copy_event.rw_counter_start();
@@ -2064,7 +2134,9 @@ impl CopyTable {
                 let tag_chip = BinaryNumberChip::construct(self.tag);
                 let copy_table_columns = <CopyTable as LookupTable<F>>::advice_columns(self);
                 for copy_event in block.copy_events.iter() {
-                    for (tag, row, _) in Self::assignments(copy_event, *challenges) {
+                    for (tag, row, _) in
+                        Self::assignments(copy_event, &block.copy_bytes, *challenges)
+                    {
                         region.assign_fixed(
                             || format!("q_enable at row: {offset}"),
                             self.q_enable,
@@ -2547,12 +2619,20 @@ impl SigTable {
         layouter: &mut impl Layouter<F>,
         block: &Block,
         challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        self.dev_load_signatures(layouter, &block.get_sign_data(false), challenges)
+    }
+
+    /// Assign the verifications of `signatures` to the table.
+    pub(crate) fn dev_load_signatures<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        signatures: &[SignData],
+        challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
         layouter.assign_region(
             || "sig table (dev load)",
             |mut region| {
-                let signatures: Vec<SignData> = block.get_sign_data(false);
-
                 let evm_word = challenges.evm_word();
                 for (offset, sign_data) in signatures.iter().enumerate() {
                     let msg_hash_rlc = evm_word.map(|challenge| {
@@ -2801,7 +2881,9 @@ impl EccTable {
         for pairing_op in pairing_ops
             .iter()
             .filter(|pairing_op| !pairing_op.skip_by_ecc_circuit())
-            .chain(std::iter::repeat(&EcPairingOp::default()))
+            .chain(std::iter::repeat(&EcPairingOp::filler(
+                params.ec_pairing_pairs,
+            )))
             .take(params.ec_pairing)
         {
             assignments.push([
@@ -3215,6 +3297,40 @@ impl<const MAX: usize> From<RangeTable<MAX>> for TableColumn {
     }
 }
 
+/// Fixed range tables shared by the sub-circuits. They are configured once by
+/// the circuit that combines the sub-circuits, which is also responsible for
+/// loading them. The tagged fixed table of the EVM circuit stays its own, as
+/// its rows are selected by the tags its gadgets look up.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedTables {
+    /// Range table for [0, 1 << 8)
+    pub u8_table: U8Table,
+    /// Range table for [0, 1 << 16)
+    pub u16_table: U16Table,
+}
+
+impl FixedTables {
+    /// Construct the shared fixed tables.
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            u8_table: U8Table::construct(meta),
+            u16_table: U16Table::construct(meta),
+        }
+    }
+
+    /// Number of rows taken by the largest shared fixed table, which is loaded
+    /// in full regardless of the witness.
+    pub fn min_num_rows() -> usize {
+        1 << 16
+    }
+
+    /// Assign values to all the shared fixed tables.
+    pub fn load<F: Field>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.u8_table.load(layouter)?;
+        self.u16_table.load(layouter)
+    }
+}
+
 /// Lookup table for powers of 2.
 #[derive(Clone, Copy, Debug)]
 pub struct Pow2Table<const N: usize> {
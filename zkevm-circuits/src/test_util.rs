@@ -4,6 +4,7 @@ use crate::{
     copy_circuit::CopyCircuit,
     evm_circuit::{cached::EvmCircuitCached, EvmCircuit},
     state_circuit::StateCircuit,
+    super_circuit::SuperCircuit,
     util::{log2_ceil, SubCircuit},
     witness::{Block, Rw},
 };
@@ -17,9 +18,14 @@ use halo2_proofs::{
 };
 use mock::TestContext;
 
+#[cfg(any(feature = "challenge-seed", test))]
+use crate::util::{challenge_seed, set_challenge_seed, CHALLENGE_SEED_ENV};
+
 #[cfg(feature = "scroll")]
 use bus_mapping::circuit_input_builder::CircuitInputBuilder;
 
+pub mod oog_boundary;
+
 #[cfg(test)]
 #[ctor::ctor]
 fn init_env_logger() {
@@ -85,7 +91,31 @@ pub struct CircuitTestBuilder<const NACC: usize, const NTX: usize> {
     evm_checks: Option<Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>>,
     state_checks: Option<Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>>,
     copy_checks: Option<Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>>,
+    super_circuit: Option<Box<dyn Fn(&Block)>>,
     block_modifiers: Vec<Box<dyn Fn(&mut Block)>>,
+    challenge_seed: Option<u64>,
+}
+
+/// Prints the seed of the circuit challenges when a check fails, and restores
+/// the challenges of the thread once the run is over.
+#[cfg(any(feature = "challenge-seed", test))]
+struct ChallengeSeedGuard {
+    seed: Option<u64>,
+    restore: bool,
+}
+
+#[cfg(any(feature = "challenge-seed", test))]
+impl Drop for ChallengeSeedGuard {
+    fn drop(&mut self) {
+        if let (Some(seed), true) = (self.seed, std::thread::panicking()) {
+            eprintln!(
+                "circuit challenges drawn from seed {seed}, rerun with {CHALLENGE_SEED_ENV}={seed} to reproduce"
+            );
+        }
+        if self.restore {
+            set_challenge_seed(None);
+        }
+    }
 }
 
 impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
@@ -113,7 +143,9 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
                     lookup_rows.iter().cloned(),
                 ), Ok(()));
             })),
+            super_circuit: None,
             block_modifiers: vec![],
+            challenge_seed: None,
         }
     }
 
@@ -186,6 +218,59 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
         self
     }
 
+    #[allow(clippy::type_complexity)]
+    /// Allows to also run the [`SuperCircuit`] of the given parameters on the
+    /// block, with the checks provided for its verification. Its degree is
+    /// picked by [`SuperCircuit::min_k_for`].
+    pub fn super_checks<
+        const MAX_TXS: usize,
+        const MAX_CALLDATA: usize,
+        const MAX_INNER_BLOCKS: usize,
+        const MOCK_RANDOMNESS: u64,
+    >(
+        mut self,
+        super_checks: Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>,
+    ) -> Self {
+        self.super_circuit = Some(Box::new(move |block| {
+            let k = SuperCircuit::<
+                Fr,
+                MAX_TXS,
+                MAX_CALLDATA,
+                MAX_INNER_BLOCKS,
+                MOCK_RANDOMNESS,
+            >::min_k_for(block);
+            let (active_rows, _) = SuperCircuit::<
+                Fr,
+                MAX_TXS,
+                MAX_CALLDATA,
+                MAX_INNER_BLOCKS,
+                MOCK_RANDOMNESS,
+            >::min_num_rows_block(block);
+            let circuit = SuperCircuit::<
+                Fr,
+                MAX_TXS,
+                MAX_CALLDATA,
+                MAX_INNER_BLOCKS,
+                MOCK_RANDOMNESS,
+            >::new_from_block(block);
+            let instance = circuit.instance();
+            let prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
+            let rows = (0..active_rows).collect();
+
+            super_checks(prover, &rows, &rows);
+        }));
+        self
+    }
+
+    /// Allows to draw the challenges of the circuits from `seed` instead of
+    /// using the ones of the prover, to reproduce failures depending on their
+    /// values. [`CHALLENGE_SEED_ENV`] does the same for all the tests.
+    #[cfg(any(feature = "challenge-seed", test))]
+    pub fn challenge_seed(mut self, seed: u64) -> Self {
+        self.challenge_seed = Some(seed);
+        self
+    }
+
     #[allow(clippy::type_complexity)]
     /// Allows to provide modifier functions for the [`Block`] that will be
     /// generated within this builder.
@@ -211,6 +296,17 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
         params.max_txs = NTX;
         log::debug!("params in CircuitTestBuilder: {:?}", params);
 
+        #[cfg(any(feature = "challenge-seed", test))]
+        let _challenge_seed_guard = {
+            if self.challenge_seed.is_some() {
+                set_challenge_seed(self.challenge_seed);
+            }
+            ChallengeSeedGuard {
+                seed: challenge_seed(),
+                restore: self.challenge_seed.is_some(),
+            }
+        };
+
         let block: Block = if self.block.is_some() {
             self.block.unwrap()
         } else if self.test_ctx.is_some() {
@@ -262,8 +358,16 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
             assert!(k <= 20);
             let (active_gate_rows, active_lookup_rows) = EvmCircuit::<Fr>::get_active_rows(&block);
 
-            let circuit = EvmCircuitCached::get_test_cicuit_from_block(block.clone());
-            let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+            let prover = if self.challenge_seed.is_some() {
+                // the cached configuration doesn't use the seed of the builder
+                let circuit = EvmCircuit::<Fr>::get_test_cicuit_from_block(block.clone());
+                let instance = circuit.instance();
+                MockProver::<Fr>::run(k, &circuit, instance).unwrap()
+            } else {
+                let circuit = EvmCircuitCached::get_test_cicuit_from_block(block.clone());
+                let instance = circuit.instance();
+                MockProver::<Fr>::run(k, &circuit, instance).unwrap()
+            };
 
             evm_checks(prover, &active_gate_rows, &active_lookup_rows)
         }
@@ -276,13 +380,13 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
             let state_circuit = StateCircuit::<Fr>::new(block.rws.clone(), rows_needed);
             let instance = state_circuit.instance();
             let prover = MockProver::<Fr>::run(k, &state_circuit, instance).unwrap();
-            // Skip verification of Start rows to accelerate testing
-            let non_start_rows_len = state_circuit
+            // Skip verification of Start and Padding rows to accelerate testing
+            let non_padding_rows_len = state_circuit
                 .rows
                 .iter()
-                .filter(|rw| !matches!(rw, Rw::Start { .. }))
+                .filter(|rw| !matches!(rw, Rw::Start { .. } | Rw::Padding { .. }))
                 .count();
-            let rows = (rows_needed - non_start_rows_len..rows_needed).collect();
+            let rows = (1..non_padding_rows_len + 1).collect();
 
             state_checks(prover, &rows, &rows);
         }
@@ -300,6 +404,11 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
 
             copy_checks(prover, &rows, &rows);
         }
+
+        // Run super circuit test
+        if let Some(super_circuit) = &self.super_circuit {
+            super_circuit(&block);
+        }
     }
 }
 
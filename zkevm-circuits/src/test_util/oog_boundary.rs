@@ -0,0 +1,320 @@
+//! Generator of out of gas boundary tests for the opcodes with dynamic gas.
+//!
+//! For every case the exact gas cost of the opcode is read from a trace with
+//! plenty of gas, and the transaction is run again with that exact amount of
+//! gas left at the opcode, and with one gas less. The first run must succeed
+//! and the second one must fail with an out of gas error, both in bus-mapping
+//! and in the circuits. This catches off by one errors in the gas constraints
+//! of the gadgets.
+
+use super::CircuitTestBuilder;
+use bus_mapping::{circuit_input_builder::ExecState, error::ExecError, mock::BlockData};
+use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, Bytecode, ToWord, Word};
+use mock::{
+    test_ctx::{helpers::account_0_code_account_1_no_code, TestContext},
+    MOCK_ACCOUNTS,
+};
+
+/// Gas of the transaction tracing the exact gas cost.
+const TRACE_TX_GAS: u64 = 1_000_000;
+
+/// Bytecode setting up and running an opcode with dynamic gas. The opcode of
+/// the case must be the last one run at the root call with this opcode.
+#[derive(Clone, Debug)]
+pub struct OogBoundaryCase {
+    /// Name of the case, used in the assertion messages
+    pub name: &'static str,
+    /// Opcode under test
+    pub opcode: OpcodeId,
+    /// Bytecode of the called contract
+    pub code: Bytecode,
+}
+
+impl OogBoundaryCase {
+    /// Create a case of `opcode` run by `code`.
+    pub fn new(name: &'static str, opcode: OpcodeId, code: Bytecode) -> Self {
+        Self { name, opcode, code }
+    }
+}
+
+/// The cases of all the opcodes with dynamic gas, which have out of gas
+/// support in the circuits. Calls forward no gas, so that the gas cost in the
+/// trace does not include the gas given to the callee.
+pub fn dynamic_gas_cases() -> Vec<OogBoundaryCase> {
+    let account = MOCK_ACCOUNTS[2].to_word();
+    let mut cases = vec![
+        OogBoundaryCase::new(
+            "sha3",
+            OpcodeId::SHA3,
+            bytecode! {
+                PUSH1(64)
+                PUSH1(0)
+                SHA3
+            },
+        ),
+        OogBoundaryCase::new(
+            "calldatacopy",
+            OpcodeId::CALLDATACOPY,
+            bytecode! {
+                PUSH1(64)
+                PUSH1(0)
+                PUSH1(32)
+                CALLDATACOPY
+            },
+        ),
+        OogBoundaryCase::new(
+            "codecopy",
+            OpcodeId::CODECOPY,
+            bytecode! {
+                PUSH1(64)
+                PUSH1(0)
+                PUSH1(32)
+                CODECOPY
+            },
+        ),
+        OogBoundaryCase::new(
+            "extcodecopy",
+            OpcodeId::EXTCODECOPY,
+            bytecode! {
+                PUSH1(64)
+                PUSH1(0)
+                PUSH1(32)
+                PUSH32(account)
+                EXTCODECOPY
+            },
+        ),
+        OogBoundaryCase::new(
+            "mload",
+            OpcodeId::MLOAD,
+            bytecode! {
+                PUSH2(0x100)
+                MLOAD
+            },
+        ),
+        OogBoundaryCase::new(
+            "mstore",
+            OpcodeId::MSTORE,
+            bytecode! {
+                PUSH1(1)
+                PUSH2(0x100)
+                MSTORE
+            },
+        ),
+        OogBoundaryCase::new(
+            "mstore8",
+            OpcodeId::MSTORE8,
+            bytecode! {
+                PUSH1(1)
+                PUSH2(0x100)
+                MSTORE8
+            },
+        ),
+        OogBoundaryCase::new(
+            "return",
+            OpcodeId::RETURN,
+            bytecode! {
+                PUSH1(32)
+                PUSH2(0x100)
+                RETURN
+            },
+        ),
+        OogBoundaryCase::new(
+            "revert",
+            OpcodeId::REVERT,
+            bytecode! {
+                PUSH1(32)
+                PUSH2(0x100)
+                REVERT
+            },
+        ),
+        OogBoundaryCase::new(
+            "exp",
+            OpcodeId::EXP,
+            bytecode! {
+                PUSH2(0x1234)
+                PUSH1(2)
+                EXP
+            },
+        ),
+        OogBoundaryCase::new(
+            "sload",
+            OpcodeId::SLOAD,
+            bytecode! {
+                PUSH1(0)
+                SLOAD
+            },
+        ),
+        // A cold store of a new value, which costs more than the sentry gas.
+        OogBoundaryCase::new(
+            "sstore",
+            OpcodeId::SSTORE,
+            bytecode! {
+                PUSH1(1)
+                PUSH1(0)
+                SSTORE
+            },
+        ),
+        OogBoundaryCase::new(
+            "create",
+            OpcodeId::CREATE,
+            bytecode! {
+                PUSH1(32)
+                PUSH1(0)
+                PUSH1(0)
+                CREATE
+            },
+        ),
+        OogBoundaryCase::new(
+            "create2",
+            OpcodeId::CREATE2,
+            bytecode! {
+                PUSH1(7)
+                PUSH1(64)
+                PUSH1(0)
+                PUSH1(0)
+                CREATE2
+            },
+        ),
+    ];
+
+    for opcode in [
+        OpcodeId::BALANCE,
+        OpcodeId::EXTCODESIZE,
+        OpcodeId::EXTCODEHASH,
+    ] {
+        let mut code = Bytecode::default();
+        code.push(32, account);
+        code.write_op(opcode);
+        cases.push(OogBoundaryCase::new("account access", opcode, code));
+    }
+
+    for (topics, opcode) in [
+        OpcodeId::LOG0,
+        OpcodeId::LOG1,
+        OpcodeId::LOG2,
+        OpcodeId::LOG3,
+        OpcodeId::LOG4,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let mut code = Bytecode::default();
+        for topic in 0..topics {
+            code.push(1, Word::from(topic));
+        }
+        code.append(&bytecode! {
+            PUSH1(64)
+            PUSH1(0)
+        });
+        code.write_op(opcode);
+        cases.push(OogBoundaryCase::new("log", opcode, code));
+    }
+
+    for opcode in [
+        OpcodeId::CALL,
+        OpcodeId::CALLCODE,
+        OpcodeId::DELEGATECALL,
+        OpcodeId::STATICCALL,
+    ] {
+        let mut code = bytecode! {
+            PUSH1(32)
+            PUSH1(0)
+            PUSH1(64)
+            PUSH1(32)
+        };
+        if opcode.is_call_with_value() {
+            code.push(1, Word::one());
+        }
+        code.append(&bytecode! {
+            PUSH32(account)
+            PUSH1(0)
+        });
+        code.write_op(opcode);
+        cases.push(OogBoundaryCase::new("call", opcode, code));
+    }
+
+    cases
+}
+
+fn test_ctx(code: &Bytecode, tx_gas: u64) -> TestContext<2, 1> {
+    TestContext::<2, 1>::new(
+        None,
+        account_0_code_account_1_no_code(code.clone()),
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[1].address)
+                .to(accs[0].address)
+                .input(vec![0xaa; 32].into())
+                .gas(tx_gas.into());
+        },
+        |block, _tx| block.number(0xcafe_u64),
+    )
+    .unwrap()
+}
+
+/// Gas of the transaction which leaves exactly the gas cost of the opcode of
+/// `case` when it is run.
+fn exact_tx_gas(case: &OogBoundaryCase) -> u64 {
+    let block: GethData = test_ctx(&case.code, TRACE_TX_GAS).into();
+    let step = block.geth_traces[0]
+        .struct_logs
+        .iter()
+        .rev()
+        .find(|step| step.depth == 1 && step.op == case.opcode)
+        .unwrap_or_else(|| panic!("{} {:?}: opcode not run", case.name, case.opcode));
+    assert!(
+        step.error.is_none(),
+        "{} {:?}: opcode failed with plenty of gas",
+        case.name,
+        case.opcode
+    );
+    TRACE_TX_GAS - step.gas.0 + step.gas_cost.0
+}
+
+/// Error of the last step of the opcode of `case` found by bus-mapping.
+fn bus_mapping_error(case: &OogBoundaryCase, ctx: TestContext<2, 1>) -> Option<ExecError> {
+    let block: GethData = ctx.into();
+    let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    builder.block.txs[0]
+        .steps()
+        .iter()
+        .rev()
+        .find(|step| step.exec_state == ExecState::Op(case.opcode))
+        .unwrap_or_else(|| panic!("{} {:?}: no exec step", case.name, case.opcode))
+        .error
+        .clone()
+}
+
+/// Run `case` with exactly the gas cost of its opcode left, and with one gas
+/// less, checking the out of gas detection of bus-mapping and the circuits.
+pub fn run_oog_boundary(case: &OogBoundaryCase) {
+    let exact_gas = exact_tx_gas(case);
+    for (tx_gas, is_oog) in [(exact_gas, false), (exact_gas - 1, true)] {
+        let error = bus_mapping_error(case, test_ctx(&case.code, tx_gas));
+        assert_eq!(
+            matches!(error, Some(ExecError::OutOfGas(_))),
+            is_oog,
+            "{} {:?} with tx gas {}: unexpected error {:?}",
+            case.name,
+            case.opcode,
+            tx_gas,
+            error
+        );
+        CircuitTestBuilder::new_from_test_ctx(test_ctx(&case.code, tx_gas)).run();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oog_boundaries() {
+        for case in dynamic_gas_cases() {
+            run_oog_boundary(&case);
+        }
+    }
+}
@@ -21,9 +21,10 @@ use crate::{
         TxFieldTag,
         TxFieldTag::{
             AccessListAddressesLen, AccessListRLC, AccessListStorageKeysLen, BlockNumber, CallData,
-            CallDataGasCost, CallDataLength, CallDataRLC, CalleeAddress, CallerAddress, ChainID,
-            Gas, GasPrice, IsCreate, MaxFeePerGas, MaxPriorityFeePerGas, Nonce, SigR, SigS, SigV,
-            TxDataGasCost, TxHashLength, TxHashRLC, TxSignHash, TxSignLength, TxSignRLC,
+            CallDataGasCost, CallDataLength, CallDataRLC, CallDataTxId, CalleeAddress,
+            CallerAddress, ChainID, Gas, GasPrice, IsCreate, MaxFeePerGas, MaxPriorityFeePerGas,
+            Nonce, SigR, SigS, SigV, TxDataGasCost, TxHashLength, TxHashRLC, TxSignHash,
+            TxSignLength, TxSignRLC,
         },
         TxTable, U16Table, U8Table,
     },
@@ -63,7 +64,7 @@ use gadgets::{
 };
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Region, Value},
-    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Instance, VirtualCells},
     poly::Rotation,
 };
 use log::error;
@@ -85,7 +86,7 @@ use halo2_proofs::plonk::{Any, Fixed};
 use itertools::Itertools;
 
 /// Number of rows of one tx occupies in the fixed part of tx table
-pub const TX_LEN: usize = 28;
+pub const TX_LEN: usize = 29;
 /// Offset of TxHash tag in the tx table
 pub const TX_HASH_OFFSET: usize = 21;
 /// Offset of CallerAddress in the tx table
@@ -247,7 +248,8 @@ impl TxRomTable {
                     (AccessListStorageKeysLen, AccessListRLC, 1, 1, 0),
                     (AccessListRLC, MaxFeePerGas, 1, 1, 0),
                     (MaxFeePerGas, MaxPriorityFeePerGas, 1, 1, 0),
-                    (MaxPriorityFeePerGas, BlockNumber, 1, 1, 0),
+                    (MaxPriorityFeePerGas, CallDataTxId, 1, 1, 0),
+                    (CallDataTxId, BlockNumber, 1, 1, 0),
                     // Transition into dynamic section of tx_table
                     (BlockNumber, Nonce, 0, 1, 0),
                     (BlockNumber, CallData, 1, 1, 1),
@@ -337,8 +339,14 @@ pub struct TxCircuitConfig<F: Field> {
     q_dynamic_last: Column<Fixed>,
     // A selector which is enabled at 1st row
     q_first: Column<Fixed>,
+    // Public inputs holding the index of the L1 message queue before (row 0) and after (row 1)
+    // the l1 msgs of the chunk
+    l1_queue_index: Column<Instance>,
+    // The index of the L1 message queue after the l1 msgs of the chunk, assigned at the last row
+    // of the fixed part of tx table
+    end_l1_queue_index: Column<Advice>,
     tx_table: TxTable,
-    tx_tag_bits: BinaryNumberConfig<TxFieldTag, 5>,
+    tx_tag_bits: BinaryNumberConfig<TxFieldTag, { TxFieldTag::N_BITS }>,
 
     tx_type: Column<Advice>,
     tx_type_bits: BinaryNumberConfig<TxType, 3>,
@@ -394,6 +402,9 @@ pub struct TxCircuitConfig<F: Field> {
     /// We need this because tx_table.value is a 2nd phase column and is used to get section_rlc.
     /// It's not safe to do RLC on columns of same phase.
     calldata_byte: Column<Advice>,
+    /// Id of the tx whose call data section holds this tx's call data. It's
+    /// extracted at CallDataTxId row and copied to all fixed rows of the tx.
+    calldata_tx_id: Column<Advice>,
 
     /// Columns for ensuring that BlockNum is correct
     is_padding_tx: Column<Advice>,
@@ -487,6 +498,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         let q_enable = tx_table.q_enable;
 
         let q_first = meta.fixed_column();
+        let l1_queue_index = meta.instance_column();
         let q_dynamic_first = meta.fixed_column();
         let q_dynamic_last = meta.fixed_column();
         // Since we allow skipping l1 txs that could cause potential circuit overflow,
@@ -508,6 +520,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         let block_num = meta.advice_column();
 
         let total_l1_popped_before = meta.advice_column();
+        let end_l1_queue_index = meta.advice_column();
         // num_all_txs = num_l1_msgs + num_l2_txs
         let num_all_txs_acc = meta.advice_column();
 
@@ -531,6 +544,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         let calldata_gas_cost_acc = meta.advice_column();
         let section_rlc = meta.advice_column_in(SecondPhase);
         let calldata_byte = meta.advice_column();
+        let calldata_tx_id = meta.advice_column();
 
         // booleans to reduce degree
         let is_l1_msg = meta.advice_column();
@@ -575,6 +589,8 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         meta.enable_equality(chunk_txbytes_len_acc);
         meta.enable_equality(pow_of_rand);
         meta.enable_equality(tx_table.chunk_txbytes_hash_rlc);
+        meta.enable_equality(l1_queue_index);
+        meta.enable_equality(end_l1_queue_index);
 
         // TODO: add lookup to SignVerify table for sv_address
         let sv_address = meta.advice_column();
@@ -635,6 +651,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         is_tx_tag!(is_tag_access_list_storage_key, AccessListStorageKey);
         is_tx_tag!(is_max_fee_per_gas, MaxFeePerGas);
         is_tx_tag!(is_max_priority_fee_per_gas, MaxPriorityFeePerGas);
+        is_tx_tag!(is_calldata_tx_id, CallDataTxId);
 
         // testing if value is zero for tags
         let value_is_zero = IsZeroChip::configure(
@@ -705,6 +722,8 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                         ("cum_num_txs", cum_num_txs),
                         ("num_all_txs_acc", num_all_txs_acc),
                         ("tx_nonce", tx_nonce),
+                        // extracted at CallDataTxId row
+                        ("calldata_tx_id", calldata_tx_id),
                         // is_l1_msg does not need to spread out as it's extracted from tx_type
 
                         // these do not need to spread out as they are related to tx_table.tag
@@ -819,6 +838,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 (is_access_list_addresses_len(meta), Null),
                 (is_access_list_storage_keys_len(meta), Null),
                 (is_access_list_rlc(meta), RLC),
+                (is_calldata_tx_id(meta), Null),
             ];
 
             cb.require_boolean(
@@ -846,6 +866,14 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 );
             });
 
+            cb.condition(is_calldata_tx_id(meta), |cb| {
+                cb.require_equal(
+                    "associated calldata_tx_id to tag",
+                    meta.query_advice(calldata_tx_id, Rotation::cur()),
+                    meta.query_advice(tx_table.value, Rotation::cur()),
+                );
+            });
+
             cb.require_equal(
                 "associated rlp_tag",
                 meta.query_advice(rlp_tag, Rotation::cur()),
@@ -1304,6 +1332,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             sv_address,
             calldata_gas_cost_acc,
             section_rlc,
+            calldata_tx_id,
             field_rlc,
             tx_table.clone(),
             keccak_table.clone(),
@@ -1522,6 +1551,61 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             ]))
         });
 
+        meta.create_gate("l1 msgs pop the queue from the public index", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            // The hashes of the l1 msgs are in the data hash of the PI circuit, and their queue
+            // indexes are then bound to the message queue from the public start index.
+            cb.require_equal(
+                "total_l1_popped_before of the first tx == start_l1_queue_index",
+                meta.query_advice(total_l1_popped_before, Rotation::next()),
+                meta.query_instance(l1_queue_index, Rotation::cur()),
+            );
+
+            cb.gate(meta.query_fixed(q_first, Rotation::cur()))
+        });
+
+        meta.create_gate("l1 msgs pop the queue up to the end index", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let queue_index = tx_nonce;
+
+            // At the BlockNum row of the last tx in the fixed part, which is copied to the public
+            // end index. The aggregator chains the end index of a chunk to the start index of the
+            // next one.
+            cb.require_equal(
+                "end_l1_queue_index = tx.is_l1_msg ? queue_index + 1 : total_l1_popped",
+                meta.query_advice(end_l1_queue_index, Rotation::cur()),
+                select::expr(
+                    meta.query_advice(is_l1_msg, Rotation::cur()),
+                    meta.query_advice(queue_index, Rotation::cur()) + 1.expr(),
+                    meta.query_advice(total_l1_popped_before, Rotation::cur()),
+                ),
+            );
+
+            cb.gate(meta.query_fixed(q_dynamic_first, Rotation::next()))
+        });
+
+        meta.lookup("l1 msgs pop the message queue in order", |meta| {
+            // queue_index >= total_l1_popped_before, i.e. the queue index of an l1 msg is larger
+            // than the ones of the previous l1 msgs. Otherwise num_all_txs_acc would wrap around
+            // and a later l1 msg could make up for it. At most 2^16 - 1 msgs are skipped at once.
+            let queue_index = tx_nonce;
+            let is_tag_dynamic = sum::expr([
+                meta.query_advice(is_calldata, Rotation::cur()),
+                meta.query_advice(is_access_list, Rotation::cur()),
+            ]);
+            let lookup_condition = and::expr([
+                meta.query_fixed(tx_table.q_enable, Rotation::cur()),
+                not::expr(is_tag_dynamic),
+                meta.query_advice(is_tag_block_num, Rotation::cur()),
+                meta.query_advice(is_l1_msg, Rotation::cur()),
+            ]);
+            let num_skipped = meta.query_advice(queue_index, Rotation::cur())
+                - meta.query_advice(total_l1_popped_before, Rotation::cur());
+
+            vec![(lookup_condition * num_skipped, u16_table.into())]
+        });
+
         meta.lookup_any("num_all_txs in block table", |meta| {
             let is_tag_block_num = meta.query_advice(is_tag_block_num, Rotation::cur());
             let block_num = meta.query_advice(tx_table.value, Rotation::cur());
@@ -2322,6 +2406,8 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         Self {
             minimum_rows: meta.minimum_rows(),
             q_first,
+            l1_queue_index,
+            end_l1_queue_index,
             q_dynamic_first,
             q_dynamic_last,
             tx_tag_bits: tag_bits,
@@ -2357,6 +2443,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             calldata_gas_cost_acc,
             section_rlc,
             calldata_byte,
+            calldata_tx_id,
             sv_address,
             sig_table,
             block_table,
@@ -2407,6 +2494,7 @@ impl<F: Field> TxCircuitConfig<F> {
         sv_address: Column<Advice>,
         calldata_gas_cost_acc: Column<Advice>,
         section_rlc: Column<Advice>,
+        calldata_tx_id: Column<Advice>,
         field_rlc: Column<Advice>,
         tx_table: TxTable,
         keccak_table: KeccakTable,
@@ -2448,7 +2536,7 @@ impl<F: Field> TxCircuitConfig<F> {
             ]);
 
             vec![
-                meta.query_advice(tx_table.tx_id, Rotation::cur()),
+                meta.query_advice(calldata_tx_id, Rotation::cur()),
                 CallData.expr(),
                 meta.query_advice(tx_table.value, Rotation::next()), // calldata_gas_cost
                 1.expr(),                                            // is_final = 1
@@ -2476,7 +2564,7 @@ impl<F: Field> TxCircuitConfig<F> {
                 ),
             ]);
             vec![
-                meta.query_advice(tx_table.tx_id, Rotation::cur()),
+                meta.query_advice(calldata_tx_id, Rotation::cur()),
                 CallData.expr(),
                 meta.query_advice(tx_table.value, Rotation::cur()) - 1.expr(), /* index starts
                                                                                 * from 0 */
@@ -2493,6 +2581,34 @@ impl<F: Field> TxCircuitConfig<F> {
             .collect()
         });
 
+        // Identical call data is only stored once in the dynamic section, under the
+        // first tx carrying it. Check that the referenced section has the same RLC as
+        // this tx's call data.
+        meta.lookup_any("call data section RLC in TxTable", |meta| {
+            let enable = and::expr(vec![
+                meta.query_fixed(q_enable, Rotation::cur()),
+                meta.query_advice(
+                    lookup_conditions[&LookupCondition::TxCalldata],
+                    Rotation::cur(),
+                ),
+            ]);
+            vec![
+                meta.query_advice(calldata_tx_id, Rotation::cur()),
+                CallData.expr(),
+                meta.query_advice(tx_table.value, Rotation::prev()), // CallDataRLC
+                1.expr(),                                            // is_final = true
+            ]
+            .into_iter()
+            .zip(vec![
+                meta.query_advice(tx_table.tx_id, Rotation::cur()),
+                meta.query_advice(tx_table.tag, Rotation::cur()),
+                meta.query_advice(section_rlc, Rotation::cur()),
+                meta.query_advice(is_final, Rotation::cur()),
+            ])
+            .map(|(arg, table)| (enable.clone() * arg, table))
+            .collect()
+        });
+
         meta.lookup_any("lookup CallDataRLC in the calldata part", |meta| {
             let is_call_data = meta.query_advice(is_calldata, Rotation::cur());
             let section_rlc = meta.query_advice(section_rlc, Rotation::cur());
@@ -3315,6 +3431,11 @@ impl<F: Field> TxCircuitConfig<F> {
                 }),
                 rlc_be_bytes(&tx.max_priority_fee_per_gas.to_be_bytes(), evm_word),
             ),
+            (
+                CallDataTxId,
+                None,
+                Value::known(F::from(tx.call_data_tx_id() as u64)),
+            ),
             (BlockNumber, None, Value::known(F::from(tx.block_number))),
         ];
         for (tx_tag, rlp_input, tx_value) in fixed_rows {
@@ -3360,6 +3481,11 @@ impl<F: Field> TxCircuitConfig<F> {
                 // num_all_txs, num_txs, cum_num_txs related assignment
                 ("tx_nonce", self.tx_nonce, F::from(tx.nonce)),
                 ("block_num", self.block_num, F::from(tx.block_number)),
+                (
+                    "calldata_tx_id",
+                    self.calldata_tx_id,
+                    F::from(tx.call_data_tx_id() as u64),
+                ),
                 (
                     "total_l1_popped_before",
                     self.total_l1_popped_before,
@@ -3579,11 +3705,13 @@ impl<F: Field> TxCircuitConfig<F> {
         next_tx: Option<&Transaction>,
         challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
-        // assign to call_data related columns
+        // assign to call_data related columns, txs sharing call data with an earlier
+        // tx have no section of their own
+        let call_data = tx.call_data_section();
         let mut gas_cost_acc = 0;
         let mut rlc = challenges.keccak_input().map(|_| F::zero());
-        for (idx, byte) in tx.call_data.iter().enumerate() {
-            let is_final = idx == (tx.call_data.len() - 1);
+        for (idx, byte) in call_data.iter().enumerate() {
+            let is_final = idx == (call_data.len() - 1);
             gas_cost_acc += if *byte == 0 { 4 } else { 16 };
             rlc = rlc
                 .zip(challenges.keccak_input())
@@ -4015,6 +4143,15 @@ impl<F: Field> TxCircuit<F> {
         }
     }
 
+    /// Return the index of the L1 message queue after the l1 msgs of the chunk
+    pub fn end_l1_queue_index(&self) -> u64 {
+        self.txs
+            .iter()
+            .filter(|tx| tx.tx_type.is_l1_msg())
+            .last()
+            .map_or(self.start_l1_queue_index, |tx| tx.nonce + 1)
+    }
+
     /// Returned data contains both the tx hash and sig hash
     fn keccak_inputs(&self) -> Result<Vec<Vec<u8>>, Error> {
         let mut inputs = Vec::new();
@@ -4077,7 +4214,7 @@ impl<F: Field> TxCircuit<F> {
         config: TxCircuitConfig<F>,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
-        let mut total_l1_popped_before = 0;
+        let mut total_l1_popped_before = self.start_l1_queue_index;
         let block_nums = self
             .txs
             .iter()
@@ -4092,21 +4229,16 @@ impl<F: Field> TxCircuit<F> {
                 num_txs_in_blocks.insert(tx.block_number, 1_usize);
             }
 
-            if let Some(num_all_txs) = num_all_txs_in_blocks.get_mut(&tx.block_number) {
-                if tx.tx_type.is_l1_msg() {
-                    *num_all_txs += tx.nonce - total_l1_popped_before + 1;
-                    total_l1_popped_before = tx.nonce + 1;
-                } else {
-                    *num_all_txs += 1;
-                }
+            // wraps around for the l1 msgs out of queue order, which the circuit rejects
+            let num_popped = if tx.tx_type.is_l1_msg() {
+                let num_popped = (tx.nonce + 1).wrapping_sub(total_l1_popped_before);
+                total_l1_popped_before = tx.nonce + 1;
+                num_popped
             } else {
-                let num_all_txs = if tx.tx_type.is_l1_msg() {
-                    tx.nonce - total_l1_popped_before + 1
-                } else {
-                    1
-                };
-                num_all_txs_in_blocks.insert(tx.block_number, num_all_txs);
-            }
+                1
+            };
+            let num_all_txs = num_all_txs_in_blocks.entry(tx.block_number).or_insert(0);
+            *num_all_txs = num_all_txs.wrapping_add(num_popped);
         }
         log::debug!("block_nums: {:?}", block_nums);
         log::debug!("num_all_txs: {:?}", num_all_txs_in_blocks);
@@ -4167,7 +4299,7 @@ impl<F: Field> TxCircuit<F> {
         start_l1_queue_index: u64,
         sign_datas: Vec<SignData>,
         padding_txs: &[Transaction],
-    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+    ) -> Result<(Vec<AssignedCell<F, F>>, AssignedCell<F, F>), Error> {
         config.tx_rom_table.load(layouter)?;
 
         layouter.assign_region(
@@ -4229,10 +4361,13 @@ impl<F: Field> TxCircuit<F> {
                             .iter()
                             .filter(|tx| tx.block_number == block_num)
                             .count() as u64;
+                        // wraps around for the l1 msgs out of queue order, which the circuit
+                        // rejects
                         let mut init_new_block = |tx: &Transaction| {
                             if tx.tx_type.is_l1_msg() {
                                 let queue_index = tx.nonce;
-                                num_all_txs_acc = queue_index - total_l1_popped_before + 1;
+                                num_all_txs_acc =
+                                    (queue_index + 1).wrapping_sub(total_l1_popped_before);
                                 total_l1_popped_after = queue_index + 1;
                             } else {
                                 // next tx's total_l1_popped_before do not change
@@ -4247,7 +4382,9 @@ impl<F: Field> TxCircuit<F> {
                             // same block
                             if tx.tx_type.is_l1_msg() {
                                 let queue_index = tx.nonce;
-                                num_all_txs_acc += queue_index - total_l1_popped_before + 1;
+                                num_all_txs_acc = num_all_txs_acc.wrapping_add(
+                                    (queue_index + 1).wrapping_sub(total_l1_popped_before),
+                                );
                                 total_l1_popped_after = queue_index + 1;
                             } else {
                                 // next tx's total_l1_popped_before do not change
@@ -4262,7 +4399,7 @@ impl<F: Field> TxCircuit<F> {
                     }
                     let is_last_tx = i == (sigs.len() - 1);
                     let next_tx = if is_last_tx {
-                        self.txs.iter().find(|tx| !tx.call_data.is_empty() || (tx.access_list.as_ref().map_or(false, |al| !al.0.is_empty())))
+                        self.txs.iter().find(|tx| !tx.call_data_section().is_empty() || (tx.access_list.as_ref().map_or(false, |al| !al.0.is_empty())))
                     } else {
                         Some(get_tx(i+1))
                     };
@@ -4305,6 +4442,13 @@ impl<F: Field> TxCircuit<F> {
                 assert_eq!(offset, self.max_txs * TX_LEN + 1);
 
                 let calldata_first_row = self.max_txs * TX_LEN + 1;
+                // the queue index after the last tx, as padding txs don't pop the queue
+                let end_l1_queue_index_cell = region.assign_advice(
+                    || "end_l1_queue_index",
+                    config.end_l1_queue_index,
+                    calldata_first_row - 1,
+                    || Value::known(F::from(total_l1_popped_before)),
+                )?;
                 let calldata_last_row = calldata_first_row + self.max_calldata;
                 // 3. Assign call data of txs
                 // 3.1 padding txs have no calldata bytes
@@ -4313,7 +4457,7 @@ impl<F: Field> TxCircuit<F> {
                         .txs
                         .iter()
                         .skip(i + 1)
-                        .find(|tx| !tx.call_data.is_empty() || (tx.access_list.as_ref().map_or(false, |al| !al.0.is_empty())));
+                        .find(|tx| !tx.call_data_section().is_empty() || (tx.access_list.as_ref().map_or(false, |al| !al.0.is_empty())));
                     config.assign_calldata_rows(
                         &mut region,
                         &mut offset,
@@ -4344,7 +4488,7 @@ impl<F: Field> TxCircuit<F> {
                     region.assign_fixed(|| col_anno, col, row, || Value::known(F::one()))?;
                 }
 
-                Ok(tx_value_cells)
+                Ok((tx_value_cells, end_l1_queue_index_cell))
             },
         )
     }
@@ -4375,6 +4519,15 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
         )
     }
 
+    /// The public inputs are the indexes of the L1 message queue before and
+    /// after the l1 msgs of the chunk.
+    fn instance(&self) -> Vec<Vec<F>> {
+        vec![vec![
+            F::from(self.start_l1_queue_index),
+            F::from(self.end_l1_queue_index()),
+        ]]
+    }
+
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block) -> (usize, usize) {
         // Since each call data byte at least takes one row in RLP circuit.
@@ -4396,7 +4549,11 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
         let blob_usage: f32 = chunk_txbytes_len as f32 / CHUNK_TXBYTES_BLOB_LIMIT as f32;
 
         // Calculate tx circuit dynamic section usage
-        let sum_calldata_len = block.txs.iter().map(|tx| tx.call_data.len()).sum::<usize>();
+        let sum_calldata_len = block
+            .txs
+            .iter()
+            .map(|tx| tx.call_data_section().len())
+            .sum::<usize>();
         let sum_access_list_len = block
             .txs
             .iter()
@@ -4500,7 +4657,7 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
             }
         }
 
-        let tx_value_cells = self.assign(
+        let (tx_value_cells, end_l1_queue_index_cell) = self.assign(
             config,
             challenges,
             layouter,
@@ -4508,6 +4665,7 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
             sign_datas,
             &padding_txs,
         )?;
+        layouter.constrain_instance(end_l1_queue_index_cell.cell(), config.l1_queue_index, 1)?;
         // export tx value cells
         *self.value_cells.borrow_mut() = Some(tx_value_cells);
 
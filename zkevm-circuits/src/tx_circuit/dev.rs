@@ -140,6 +140,10 @@ impl<F: Field> SubCircuit<F> for TxCircuitTester<F> {
         Self::new(max_txs, max_calldata, chain_id, start_l1_queue_index, txs)
     }
 
+    fn instance(&self) -> Vec<Vec<F>> {
+        self.tx_circuit.instance()
+    }
+
     fn synthesize_sub(
         &self,
         _config: &Self::Config,
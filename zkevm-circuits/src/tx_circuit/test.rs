@@ -24,8 +24,11 @@ use eth_types::{
     word, H160, H256, U256, U64,
 };
 use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
     dev::{MockProver, VerifyFailure},
     halo2curves::bn256::Fr,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed},
+    poly::Rotation,
 };
 use mock::{AddrOrWallet, MockTransaction};
 #[test]
@@ -36,6 +39,11 @@ fn tx_circuit_unusable_rows() {
     )
 }
 
+#[test]
+fn tx_field_tag_encoding() {
+    gadgets::binary_number::assert_tag_encoding::<TxFieldTag, 6>();
+}
+
 #[cfg(test)]
 fn build_pre_eip155_tx() -> Transaction {
     // pre-eip155 tx downloaded from [etherscan](https://etherscan.io/getRawTx?tx=0x9cd2288e69623b109e25edc46bc518156498b521e5c162d96e1ab392ff1d9dff)
@@ -183,7 +191,7 @@ fn run<F: Field>(
         },
         tx_circuit: TxCircuit::new(max_txs, max_calldata, chain_id, start_l1_queue_index, txs),
     };
-    let prover = match MockProver::run(k, &circuit, vec![]) {
+    let prover = match MockProver::run(k, &circuit, circuit.instance()) {
         Ok(prover) => prover,
         Err(e) => panic!("{e:#?}"),
     };
@@ -310,6 +318,46 @@ fn tx_circuit_l1_msg_tx() {
     );
 }
 
+#[test]
+#[cfg(feature = "scroll")]
+fn tx_circuit_l1_msg_queue_index() {
+    const MAX_TXS: usize = 4;
+    const MAX_CALLDATA: usize = 400;
+
+    // pops the queue index 11
+    let tx = build_l1_msg_tx();
+    let verify = |start_l1_queue_index: u64, public_l1_queue_indexes: [u64; 2]| {
+        let circuit = TxCircuitTester::<Fr>::new(
+            MAX_TXS,
+            MAX_CALLDATA,
+            mock::MOCK_CHAIN_ID,
+            start_l1_queue_index,
+            vec![tx.clone()],
+        );
+        let k = max(
+            20,
+            log2_ceil(TxCircuit::<Fr>::min_num_rows(MAX_TXS, MAX_CALLDATA)),
+        );
+        let instance = vec![public_l1_queue_indexes.map(Fr::from).to_vec()];
+        MockProver::run(k, &circuit, instance).unwrap().verify_par()
+    };
+
+    // skips the queue indexes 0 to 10, or pops the queue from 11
+    assert_eq!(verify(0, [0, 12]), Ok(()));
+    assert_eq!(verify(11, [11, 12]), Ok(()));
+    // the witness doesn't pop the queue from the public index
+    assert!(verify(0, [11, 12]).is_err());
+    // the queue isn't popped up to the public end index
+    assert!(verify(11, [11, 11]).is_err());
+    assert!(verify(11, [11, 13]).is_err());
+    // the queue index 11 was already popped, as if the msg was reordered
+    let failures = verify(12, [12, 12]).unwrap_err();
+    assert!(failures.iter().any(|failure| matches!(
+        failure,
+        VerifyFailure::Lookup { name, .. } if *name == "l1 msgs pop the message queue in order"
+    )));
+}
+
 #[test]
 #[cfg(feature = "scroll")]
 fn tx_circuit_bad_address() {
@@ -350,3 +398,180 @@ fn tx_circuit_to_is_zero() {
         Ok(())
     );
 }
+
+/// Looks up the selector of a tx in the tx table the way a downstream circuit
+/// would, without the tx circuit constraints.
+#[derive(Default)]
+struct CallDataSelectorCircuit {
+    txs: Vec<Transaction>,
+    tx_id: usize,
+    selector: [u8; 4],
+}
+
+impl Circuit<Fr> for CallDataSelectorCircuit {
+    type Config = (TxTable, Column<Fixed>, [Column<Advice>; 6], Challenges);
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let tx_table = TxTable::construct(meta);
+        let q_enable = meta.fixed_column();
+        // tx_id, section_tx_id and the 4 selector bytes
+        let advices = [(); 6].map(|_| meta.advice_column());
+        let challenges = Challenges::construct(meta);
+
+        for i in 0..5 {
+            meta.lookup_any("call data selector", |meta| {
+                let enable = meta.query_fixed(q_enable, Rotation::cur());
+                let [tx_id, section_tx_id, selector @ ..] =
+                    advices.map(|column| meta.query_advice(column, Rotation::cur()));
+                tx_table
+                    .call_data_selector_lookups(meta, enable, tx_id, section_tx_id, selector)
+                    .remove(i)
+            });
+        }
+
+        (tx_table, q_enable, advices, challenges)
+    }
+
+    fn synthesize(
+        &self,
+        (tx_table, q_enable, advices, challenges): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let challenges = challenges.values(&layouter);
+        tx_table.load(
+            &mut layouter,
+            &self.txs,
+            self.txs.len(),
+            0,
+            mock::MOCK_CHAIN_ID,
+            &challenges,
+        )?;
+
+        let tx = &self.txs[self.tx_id - 1];
+        let row = [self.tx_id as u64, tx.call_data_tx_id() as u64]
+            .into_iter()
+            .chain(self.selector.map(u64::from));
+        layouter.assign_region(
+            || "call data selector",
+            |mut region| {
+                region.assign_fixed(|| "q_enable", q_enable, 0, || Value::known(Fr::one()))?;
+                for (column, value) in advices.into_iter().zip(row.clone()) {
+                    region.assign_advice(|| "", column, 0, || Value::known(Fr::from(value)))?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[test]
+fn tx_table_call_data_selector() {
+    let mut txs = vec![build_l1_msg_tx(), build_l1_msg_tx()];
+    // the second tx shares the call data section of the first one
+    txs[1].id = 2;
+    txs[1].shared_call_data_tx_id = Some(1);
+    let selector = txs[0].call_data_selector().unwrap();
+    assert_eq!(selector, [0x8e, 0xf1, 0x33, 0x2e]);
+
+    for tx_id in [1, 2] {
+        let circuit = CallDataSelectorCircuit {
+            txs: txs.clone(),
+            tx_id,
+            selector,
+        };
+        let prover = MockProver::run(12, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    let circuit = CallDataSelectorCircuit {
+        txs,
+        tx_id: 2,
+        selector: [0xa9, 0x05, 0x9c, 0xbb],
+    };
+    let prover = MockProver::run(12, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[cfg(feature = "scroll")]
+#[test]
+fn serial_test_tx_circuit_shared_call_data() {
+    use crate::test_util::CircuitTestBuilder;
+    use bus_mapping::circuit_input_builder::CircuitsParams;
+    use eth_types::bytecode;
+    use mock::{
+        test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_CHAIN_ID,
+        MOCK_DIFFICULTY_L2GETH as MOCK_DIFFICULTY,
+    };
+    use std::env::set_var;
+
+    const MAX_TXS: usize = 2;
+    const MAX_CALLDATA: usize = 64;
+    const MAX_INNER_BLOCKS: usize = 1;
+    set_var("COINBASE", "0x0000000000000000000000000000000000000000");
+    set_var("CHAIN_ID", MOCK_CHAIN_ID.to_string());
+    let mut difficulty_be_bytes = [0u8; 32];
+    MOCK_DIFFICULTY.to_big_endian(&mut difficulty_be_bytes);
+    set_var("DIFFICULTY", hex::encode(difficulty_be_bytes));
+
+    // Both txs send the same call data, so the second one reads it, with
+    // CALLDATACOPY, from the call data section of the first one.
+    let calldata = vec![1, 3, 5, 7, 9, 2, 4, 6, 8];
+    let code = bytecode! {
+        .op_calldatacopy(0x00, 0x00, 0x20)
+        STOP
+    };
+    let ctx = TestContext::<2, MAX_TXS>::new(
+        None,
+        account_0_code_account_1_no_code(code),
+        |mut txs, accs| {
+            txs[0]
+                .to(accs[0].address)
+                .from(accs[1].address)
+                .input(calldata.clone().into());
+            txs[1]
+                .to(accs[0].address)
+                .from(accs[1].address)
+                .input(calldata.clone().into());
+        },
+        |block, _tx| block,
+    )
+    .unwrap();
+
+    // the Super Circuit checks the lookups of the EVM and Copy circuits into
+    // the tx table of the Tx Circuit
+    CircuitTestBuilder::new_from_test_ctx(ctx)
+        .params(CircuitsParams {
+            max_txs: MAX_TXS,
+            max_calldata: MAX_CALLDATA,
+            max_inner_blocks: MAX_INNER_BLOCKS,
+            max_rws: 512,
+            max_copy_rows: 256,
+            max_exp_steps: 256,
+            max_bytecode: 512,
+            max_mpt_rows: 2049,
+            max_poseidon_rows: 512,
+            max_rlp_rows: 800,
+            ..Default::default()
+        })
+        .block_modifier(Box::new(|block| {
+            assert_eq!(block.txs[0].shared_call_data_tx_id, None);
+            assert_eq!(block.txs[1].shared_call_data_tx_id, Some(1));
+        }))
+        .super_checks::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, 0x100>(Box::new(
+            |prover, gate_rows, lookup_rows| {
+                assert_eq!(
+                    prover
+                        .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned()),
+                    Ok(())
+                );
+            },
+        ))
+        .run();
+}
@@ -58,7 +58,72 @@ pub struct Challenges<T = Challenge> {
     lookup_input: Option<T>,
 }
 
-/// ..
+/// Challenge value of `evm_word` used by [`MockChallenges`] and
+/// [`Challenges::deterministic`].
+pub const MOCK_EVM_WORD: u64 = 0x100;
+/// Challenge value of `keccak_input` used by [`MockChallenges`] and
+/// [`Challenges::deterministic`].
+pub const MOCK_KECCAK_INPUT: u64 = 0x101;
+/// Challenge value of `lookup_input` used by [`MockChallenges`] and
+/// [`Challenges::deterministic`].
+pub const MOCK_LOOKUP_INPUT: u64 = 0x100;
+
+/// Environment variable overriding the challenges of the circuits in tests with
+/// values drawn from a seed. It holds the seed, or `random` to draw the seed
+/// once per process. Only read with the `challenge-seed` feature.
+#[cfg(any(feature = "challenge-seed", test))]
+pub const CHALLENGE_SEED_ENV: &str = "CHALLENGE_SEED";
+
+#[cfg(any(feature = "challenge-seed", test))]
+thread_local! {
+    static CHALLENGE_SEED: std::cell::Cell<Option<u64>> = const { std::cell::Cell::new(None) };
+}
+
+/// Override the challenges of the circuits configured and synthesized by the
+/// current thread with values drawn from `seed`. `None` restores the seed of
+/// [`CHALLENGE_SEED_ENV`], if any, or the challenges drawn by the prover.
+///
+/// The cached EVM Circuit configuration only sees the seed of
+/// [`CHALLENGE_SEED_ENV`], so the circuits must be configured from scratch
+/// under a seed set here.
+#[cfg(any(feature = "challenge-seed", test))]
+pub fn set_challenge_seed(seed: Option<u64>) {
+    CHALLENGE_SEED.with(|cell| cell.set(seed));
+}
+
+/// Seed overriding the challenges of the circuits, see [`set_challenge_seed`].
+#[cfg(any(feature = "challenge-seed", test))]
+pub fn challenge_seed() -> Option<u64> {
+    static ENV_SEED: std::sync::OnceLock<Option<u64>> = std::sync::OnceLock::new();
+    CHALLENGE_SEED.with(|cell| cell.get()).or_else(|| {
+        *ENV_SEED.get_or_init(|| {
+            let seed = std::env::var(CHALLENGE_SEED_ENV).ok()?;
+            Some(if seed == "random" {
+                rand::random()
+            } else {
+                seed.parse()
+                    .unwrap_or_else(|_| panic!("invalid {CHALLENGE_SEED_ENV} {seed}"))
+            })
+        })
+    })
+}
+
+/// Seed overriding the challenges of the circuits, only available with the
+/// `challenge-seed` feature.
+#[cfg(not(any(feature = "challenge-seed", test)))]
+pub fn challenge_seed() -> Option<u64> {
+    None
+}
+
+/// Values of `evm_word`, `keccak_input` and `lookup_input` drawn from `seed`.
+fn seeded_challenges<F: Field>(seed: u64) -> [F; 3] {
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    [(); 3].map(|_| F::random(&mut rng))
+}
+
+/// Challenges with fixed values, for tests of gadgets which are not meant to
+/// depend on the values of the challenges.
 #[derive(Default, Clone, Copy, Debug)]
 pub struct MockChallenges {
     evm_word: u64,
@@ -67,22 +132,22 @@ pub struct MockChallenges {
 }
 
 impl MockChallenges {
-    /// ..
+    /// Construct the mock challenges of all phases.
     pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
         Self {
-            lookup_input: Some(0x100),
+            lookup_input: Some(MOCK_LOOKUP_INPUT),
             ..Self::construct_p1(meta)
         }
     }
-    /// ..
+    /// Construct the mock challenges up to the second phase.
     pub fn construct_p1<F: Field>(_meta: &mut ConstraintSystem<F>) -> Self {
         Self {
-            evm_word: 0x100,
-            keccak_input: 0x101,
+            evm_word: MOCK_EVM_WORD,
+            keccak_input: MOCK_KECCAK_INPUT,
             lookup_input: None,
         }
     }
-    /// ..
+    /// Returns the challenges as constant expressions.
     pub fn exprs<F: Field>(&self, _meta: &mut ConstraintSystem<F>) -> Challenges<Expression<F>> {
         Challenges {
             evm_word: Expression::Constant(F::from(self.evm_word)),
@@ -90,7 +155,7 @@ impl MockChallenges {
             lookup_input: self.lookup_input.map(|c| Expression::Constant(F::from(c))),
         }
     }
-    /// ..
+    /// Returns the challenges as known values.
     pub fn values<F: Field>(&self, _layouter: &impl Layouter<F>) -> Challenges<Value<F>> {
         Challenges {
             evm_word: Value::known(F::from(self.evm_word)),
@@ -132,8 +197,19 @@ impl Challenges {
         }
     }
 
-    /// Returns `Expression` of challenges from `ConstraintSystem`.
+    /// Returns `Expression` of challenges from `ConstraintSystem`, or the
+    /// constant values drawn from the [`challenge_seed`] if any.
     pub fn exprs<F: Field>(&self, meta: &mut ConstraintSystem<F>) -> Challenges<Expression<F>> {
+        if let Some(seed) = challenge_seed() {
+            let [evm_word, keccak_input, lookup_input] = seeded_challenges::<F>(seed);
+            return Challenges {
+                evm_word: Expression::Constant(evm_word),
+                keccak_input: Expression::Constant(keccak_input),
+                lookup_input: self
+                    .lookup_input
+                    .map(|_| Expression::Constant(lookup_input)),
+            };
+        }
         let [evm_word, keccak_input] = query_expression(meta, |meta| {
             [self.evm_word, self.keccak_input].map(|challenge| meta.query_challenge(challenge))
         });
@@ -147,8 +223,17 @@ impl Challenges {
         }
     }
 
-    /// Returns `Value` of challenges from `Layouter`.
+    /// Returns `Value` of challenges from `Layouter`, or the values drawn from
+    /// the [`challenge_seed`] if any.
     pub fn values<F: Field>(&self, layouter: &impl Layouter<F>) -> Challenges<Value<F>> {
+        if let Some(seed) = challenge_seed() {
+            let [evm_word, keccak_input, lookup_input] = seeded_challenges::<F>(seed);
+            return Challenges {
+                evm_word: Value::known(evm_word),
+                keccak_input: Value::known(keccak_input),
+                lookup_input: self.lookup_input.map(|_| Value::known(lookup_input)),
+            };
+        }
         Challenges {
             evm_word: layouter.get_challenge(self.evm_word),
             keccak_input: layouter.get_challenge(self.keccak_input),
@@ -197,6 +282,18 @@ impl<T: Clone> Challenges<T> {
     }
 }
 
+impl<F: Field> Challenges<Value<F>> {
+    /// Returns the challenge values of [`MockChallenges`], so that witnesses
+    /// computed outside of a circuit in tests agree with the mock circuits.
+    pub fn deterministic() -> Self {
+        Self::mock(
+            Value::known(F::from(MOCK_EVM_WORD)),
+            Value::known(F::from(MOCK_KECCAK_INPUT)),
+            Value::known(F::from(MOCK_LOOKUP_INPUT)),
+        )
+    }
+}
+
 impl<F: Field> Challenges<Expression<F>> {
     /// Returns powers of randomness
     fn powers_of<const S: usize>(base: Expression<F>) -> [Expression<F>; S] {
@@ -230,6 +327,37 @@ impl<F: Field> Challenges<Expression<F>> {
     }
 }
 
+/// The phase in which `expr` becomes evaluable: the highest phase of the
+/// advice columns it queries, and the phase after the one of the challenges
+/// it uses.
+pub(crate) fn expr_phase<F: Field>(expr: &Expression<F>) -> u8 {
+    match expr {
+        Expression::Challenge(challenge) => challenge.phase() + 1,
+        Expression::Advice(query) => query.phase(),
+        Expression::Constant(_)
+        | Expression::Selector(_)
+        | Expression::Fixed(_)
+        | Expression::Instance(_) => 0,
+        Expression::Negated(a) | Expression::Scaled(a, _) => expr_phase(a),
+        Expression::Sum(a, b) | Expression::Product(a, b) => {
+            std::cmp::max(expr_phase(a), expr_phase(b))
+        }
+    }
+}
+
+/// Assert that `expr` can be evaluated by the time cells of `phase` are
+/// assigned. A cell holding the value of `expr` in an earlier phase would
+/// depend on a challenge which is not drawn yet, which makes the circuit
+/// unsatisfiable by an honest prover and is easy to miss when mixing the
+/// challenges of different phases.
+pub(crate) fn assert_expr_phase<F: Field>(name: &str, expr: &Expression<F>, phase: u8) {
+    let expr_phase = expr_phase(expr);
+    assert!(
+        expr_phase <= phase,
+        "{name}: expression evaluable in phase {expr_phase} assigned to a cell of phase {phase}"
+    );
+}
+
 pub(crate) fn build_tx_log_address(index: u64, field_tag: TxLogFieldTag, log_id: u64) -> Address {
     (U256::from(index) + (U256::from(field_tag as u64) << 32) + (U256::from(log_id) << 48))
         .to_address()
@@ -392,3 +520,34 @@ pub(crate) fn unusable_rows<F: Field, C: Circuit<F>>() -> usize {
 
     cs.blinding_factors() + 1
 }
+
+#[cfg(test)]
+#[cfg(not(feature = "onephase"))]
+mod tests {
+    use super::*;
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    #[test]
+    fn challenge_expr_phases() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let challenges = Challenges::construct(&mut meta).exprs(&mut meta);
+
+        assert_eq!(expr_phase(&challenges.evm_word()), 1);
+        assert_eq!(expr_phase(&challenges.keccak_input()), 1);
+        assert_eq!(expr_phase(&challenges.lookup_input()), 2);
+        assert_expr_phase("evm word rlc", &(challenges.evm_word() * 2.expr()), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "assigned to a cell of phase 1")]
+    fn lookup_challenge_in_second_phase_cell() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let challenges = Challenges::construct(&mut meta).exprs(&mut meta);
+
+        assert_expr_phase(
+            "mixed challenges",
+            &(challenges.evm_word() * challenges.lookup_input()),
+            1,
+        );
+    }
+}
@@ -3,7 +3,7 @@
 //! used to generate witnesses for circuits.
 
 mod block;
-pub use block::{block_convert, Block, BlockContext, BlockContexts};
+pub use block::{block_convert, block_convert_with_opcodes, Block, BlockContext, BlockContexts};
 
 /// Keccak witness
 pub mod keccak;
@@ -27,10 +27,10 @@ pub use rlp_fsm::{
 };
 
 mod rw;
-pub use rw::{Rw, RwMap, RwRow};
+pub use rw::{Rw, RwIndex, RwKey, RwMap, RwRow};
 
 mod step;
-pub use step::ExecStep;
+pub use step::{ExecStep, OpcodeExecutionStates};
 
 mod l1_msg;
 mod tx;
@@ -14,17 +14,21 @@ use crate::{
 use bus_mapping::{
     circuit_input_builder::{
         self, BigModExp, CircuitsParams, CopyEvent, EcAddOp, EcMulOp, EcPairingOp, ExpEvent,
-        PrecompileEvents, SHA256,
+        L1FeeSchema, PrecompileEvents, WitnessArena, SHA256,
     },
     Error,
 };
-use eth_types::{sign_types::SignData, Address, ToBigEndian, ToLittleEndian, Word, H256, U256};
+use eth_types::{
+    evm_types::{ChainSpec, STACK_LIMIT},
+    sign_types::SignData,
+    Address, ToBigEndian, ToLittleEndian, ToWord, Word, H256, U256,
+};
 use halo2_proofs::{circuit::Value, halo2curves::bn256::Fr};
 use itertools::Itertools;
 
 use super::{
     mpt::ZktrieState as MptState, step::step_convert, tx::tx_convert, Bytecode, ExecStep,
-    MptUpdates, RwMap, Transaction,
+    MptUpdates, OpcodeExecutionStates, RwMap, Transaction,
 };
 use crate::util::Challenges;
 
@@ -45,16 +49,22 @@ pub struct Block {
     pub rws: RwMap,
     /// Bytecode used in the block
     pub bytecodes: BTreeMap<Word, Bytecode>,
+    /// Schema of the L1 fee of the transactions
+    pub l1_fee_schema: L1FeeSchema,
+    /// Call depth and stack limits of the chain
+    pub chain_spec: ChainSpec,
     /// The block context
     pub context: BlockContexts,
     /// Copy events for the copy circuit's table.
     pub copy_events: Vec<CopyEvent>,
+    /// Bytes of the copy events
+    pub copy_bytes: WitnessArena,
     /// Exponentiation traces for the exponentiation circuit's table.
     pub exp_events: Vec<ExpEvent>,
     /// Circuit Setup Parameters
     pub circuits_params: CircuitsParams,
     /// Inputs to the SHA3 opcode
-    pub sha3_inputs: Vec<Vec<u8>>,
+    pub sha3_inputs: WitnessArena,
     /// State root of the previous block
     pub prev_state_root: Word, // TODO: Make this H256
     /// Withdraw root
@@ -86,6 +96,15 @@ impl Block {
             .first_key_value()
             .map_or(0.into(), |(_, ctx)| ctx.number)
     }
+    /// Whether any step is handled by a pass-through gadget, which leaves
+    /// its effects unconstrained.
+    pub fn has_unproven_steps(&self) -> bool {
+        self.txs.iter().any(|tx| {
+            tx.steps
+                .iter()
+                .any(|step| step.execution_state.is_unproven())
+        })
+    }
     /// Last block number
     pub fn last_block_number(&self) -> U256 {
         self.context
@@ -265,6 +284,7 @@ impl Block {
         log::debug!("tx_receipt num: {}", self.rws.rw_num(RwTableTag::TxReceipt));
         log::debug!("tx_log num: {}", self.rws.rw_num(RwTableTag::TxLog));
         log::debug!("start num: {}", self.rws.rw_num(RwTableTag::Start));
+        log::debug!("padding num: {}", self.rws.rw_num(RwTableTag::Padding));
     }
 }
 
@@ -295,12 +315,16 @@ impl Block {
         let num_rows_required_for_copy_table: usize = self
             .copy_events
             .iter()
-            .map(|c| c.copy_bytes.bytes.len() * 2)
+            .map(|c| c.full_length() as usize * 2)
             .sum();
         let num_rows_required_for_keccak_table: usize = keccak_inputs(self).unwrap().len();
         // tx_table load only does tx padding, no calldata padding
         let num_rows_required_for_tx_table: usize = self.circuits_params.max_txs * TX_LEN
-            + self.txs.iter().map(|tx| tx.call_data.len()).sum::<usize>();
+            + self
+                .txs
+                .iter()
+                .map(|tx| tx.call_data_section().len())
+                .sum::<usize>();
         let num_rows_required_for_exp_table: usize = self
             .exp_events
             .iter()
@@ -347,8 +371,10 @@ pub struct BlockContext {
     pub number: Word,
     /// The timestamp of the block
     pub timestamp: Word,
-    /// The difficulty of the block
+    /// The difficulty of the block, zero after the Merge
     pub difficulty: Word,
+    /// The prevRandao of the block, returned by DIFFICULTY after the Merge
+    pub prev_randao: Word,
     /// The base fee, the minimum amount of gas fee for a transaction
     pub base_fee: Word,
     /// The hash of previous blocks
@@ -359,6 +385,12 @@ pub struct BlockContext {
     pub parent_hash: H256,
     /// State root of this block
     pub state_root: H256,
+    /// Withdrawals root of this block
+    pub withdrawals_root: H256,
+    /// Blob gas used by the transactions of this block
+    pub blob_gas_used: u64,
+    /// Excess blob gas of this block
+    pub excess_blob_gas: u64,
 }
 
 impl BlockContext {
@@ -425,6 +457,28 @@ impl BlockContext {
                     Value::known(current_block_number),
                     Value::known(F::from(num_all_txs)),
                 ],
+                [
+                    Value::known(F::from(BlockContextFieldTag::PrevRandao as u64)),
+                    Value::known(current_block_number),
+                    randomness.map(|rand| rlc::value(&self.prev_randao.to_le_bytes(), rand)),
+                ],
+                [
+                    Value::known(F::from(BlockContextFieldTag::WithdrawalsRoot as u64)),
+                    Value::known(current_block_number),
+                    randomness.map(|randomness| {
+                        rlc::value(&self.withdrawals_root.to_word().to_le_bytes(), randomness)
+                    }),
+                ],
+                [
+                    Value::known(F::from(BlockContextFieldTag::BlobGasUsed as u64)),
+                    Value::known(current_block_number),
+                    Value::known(F::from(self.blob_gas_used)),
+                ],
+                [
+                    Value::known(F::from(BlockContextFieldTag::ExcessBlobGas as u64)),
+                    Value::known(current_block_number),
+                    Value::known(F::from(self.excess_blob_gas)),
+                ],
             ],
             self.block_hash_assignments(randomness),
         ]
@@ -432,8 +486,6 @@ impl BlockContext {
     }
 
     fn block_hash_assignments<F: Field>(&self, randomness: Value<F>) -> Vec<[Value<F>; 3]> {
-        use eth_types::ToWord;
-
         #[cfg(not(feature = "scroll"))]
         let history_hashes: &[U256] = &self.history_hashes;
         #[cfg(feature = "scroll")]
@@ -478,11 +530,15 @@ impl From<&circuit_input_builder::Blocks> for BlockContexts {
                             number: block.number,
                             timestamp: block.timestamp,
                             difficulty: block.difficulty,
+                            prev_randao: block.prev_randao,
                             base_fee: block.base_fee,
                             history_hashes: block.history_hashes.clone(),
                             chain_id: block.chain_id,
                             parent_hash: block.parent_hash,
                             state_root: block.state_root,
+                            withdrawals_root: block.withdrawals_root,
+                            blob_gas_used: block.blob_gas_used,
+                            excess_blob_gas: block.excess_blob_gas,
                         },
                     )
                 })
@@ -495,6 +551,16 @@ impl From<&circuit_input_builder::Blocks> for BlockContexts {
 pub fn block_convert(
     block: &circuit_input_builder::Blocks,
     code_db: &eth_types::state_db::CodeDB,
+) -> Result<Block, Error> {
+    block_convert_with_opcodes(block, code_db, &OpcodeExecutionStates::default())
+}
+
+/// Build a witness block, whose steps of the custom opcodes of the chain have
+/// the given execution states.
+pub fn block_convert_with_opcodes(
+    block: &circuit_input_builder::Blocks,
+    code_db: &eth_types::state_db::CodeDB,
+    execution_states: &OpcodeExecutionStates,
 ) -> Result<Block, Error> {
     let rws = RwMap::from(&block.container);
     rws.check_value()?;
@@ -512,8 +578,25 @@ pub fn block_convert(
             "invalid end_block. Forget to call CircuitInputBuilder::set_end_block()?",
         ));
     }
-    let padding_step = step_convert(&block.block_steps.padding_step, last_block_num);
-    let end_block_step = step_convert(&block.block_steps.end_block_step, last_block_num);
+    if block.chain_spec.stack_limit > STACK_LIMIT {
+        return Err(Error::InternalError(
+            "the circuits do not support stack limits above the one of Ethereum",
+        ));
+    }
+    // the Tx Circuit constrains the l1 msgs to pop the queue in order
+    block.end_l1_queue_index()?;
+    let padding_step = step_convert(
+        &block.block_steps.padding_step,
+        last_block_num,
+        &rws,
+        execution_states,
+    );
+    let end_block_step = step_convert(
+        &block.block_steps.end_block_step,
+        last_block_num,
+        &rws,
+        execution_states,
+    );
     log::trace!(
         "witness block: padding_step {:?}, end_block_step {:?}",
         padding_step,
@@ -563,7 +646,6 @@ pub fn block_convert(
 
     let block = Block {
         context: BlockContexts::from(block),
-        rws,
         txs: block
             .txs()
             .iter()
@@ -574,12 +656,20 @@ pub fn block_convert(
                 } else {
                     last_block_num + 1
                 };
-                tx_convert(tx, idx + 1, chain_id, next_block_num)
+                tx_convert(
+                    tx,
+                    idx + 1,
+                    chain_id,
+                    next_block_num,
+                    &rws,
+                    execution_states,
+                )
             })
             .collect(),
         sigs: block.txs().iter().map(|tx| tx.signature).collect(),
         padding_step,
         end_block_step,
+        rws,
         bytecodes: code_db
             .0
             .iter()
@@ -594,7 +684,10 @@ pub fn block_convert(
                 )
             })
             .collect(),
+        l1_fee_schema: block.l1_fee_schema,
+        chain_spec: block.chain_spec,
         copy_events: block.copy_events.clone(),
+        copy_bytes: block.copy_bytes.clone(),
         exp_events: block.exp_events.clone(),
         sha3_inputs: block.sha3_inputs.clone(),
         circuits_params: CircuitsParams {
@@ -609,5 +702,7 @@ pub fn block_convert(
         start_l1_queue_index: block.start_l1_queue_index,
         precompile_events: block.precompile_events.clone(),
     };
+    #[cfg(debug_assertions)]
+    block.rws.check_value_prev_consistency()?;
     Ok(block)
 }
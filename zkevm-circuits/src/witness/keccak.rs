@@ -1,65 +1,126 @@
-use bus_mapping::Error;
+use bus_mapping::{circuit_input_builder::WitnessArena, Error};
 use eth_types::{
     geth_types::TxType,
     sign_types::{get_dummy_tx, pk_bytes_le, pk_bytes_swap_endianness, SignData},
     ToBigEndian, ToWord, Word, H256,
 };
 use ethers_core::utils::keccak256;
-use itertools::Itertools;
+use std::collections::HashSet;
 
 use super::{Block, BlockContexts, Transaction};
+use crate::keccak_circuit::param::RATE;
+
+/// Sub-system of the block contributing keccak inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeccakInputSource {
+    /// Tx Circuit, hashing the signed and unsigned tx RLPs
+    Tx,
+    /// Signature verification of the ecrecover precompile calls
+    Ecrecover,
+    /// PI Circuit, hashing the public input data
+    PublicInput,
+    /// EVM Circuit, hashing the preimages of SHA3, CREATE, CREATE2 and
+    /// BLOCKHASH
+    Evm,
+}
+
+/// Contribution of a source to the keccak inputs of a block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeccakSourceReport {
+    /// Number of inputs pushed by the source
+    pub inputs: usize,
+    /// Number of inputs already pushed, by this source or an earlier one
+    pub duplicates: usize,
+    /// Total length of the inputs left after deduplication
+    pub bytes: usize,
+    /// Number of keccak-f permutations of the inputs left after
+    /// deduplication, each taking `(NUM_ROUNDS + 1) * get_num_rows_per_round()`
+    /// rows of the Keccak Circuit
+    pub permutations: usize,
+}
+
+/// Keccak inputs of a block, deduplicated by the hash of their preimage.
+#[derive(Debug, Clone, Default)]
+pub struct KeccakInputs {
+    inputs: WitnessArena,
+    hashes: HashSet<H256>,
+    report: Vec<(KeccakInputSource, KeccakSourceReport)>,
+}
+
+impl KeccakInputs {
+    /// Collect the inputs in `arena`, which must be empty.
+    pub fn new(arena: WitnessArena) -> Self {
+        debug_assert!(arena.is_empty());
+        Self {
+            inputs: arena,
+            ..Default::default()
+        }
+    }
+
+    /// Push the inputs of a source, dropping the ones already pushed.
+    pub fn extend<T: AsRef<[u8]>>(
+        &mut self,
+        source: KeccakInputSource,
+        inputs: impl IntoIterator<Item = T>,
+    ) -> Result<(), Error> {
+        let mut report = KeccakSourceReport::default();
+        for input in inputs {
+            let input = input.as_ref();
+            report.inputs += 1;
+            if !self.hashes.insert(H256(keccak256(input))) {
+                report.duplicates += 1;
+                continue;
+            }
+            report.bytes += input.len();
+            report.permutations += input.len() / RATE + 1;
+            self.inputs.push(input)?;
+        }
+        log::debug!("keccak inputs of {source:?}: {report:?}");
+        self.report.push((source, report));
+        Ok(())
+    }
+
+    /// Contribution of each source, in the order they were pushed.
+    pub fn report(&self) -> &[(KeccakInputSource, KeccakSourceReport)] {
+        &self.report
+    }
+
+    /// The deduplicated inputs.
+    pub fn into_inputs(self) -> WitnessArena {
+        self.inputs
+    }
+}
 
 /// Return all the keccak inputs used during the processing of the current
 /// block.
-pub fn keccak_inputs(block: &Block) -> Result<Vec<Vec<u8>>, Error> {
-    let mut keccak_inputs = Vec::new();
-    // Tx Circuit
-    keccak_inputs.extend_from_slice(&keccak_inputs_tx_circuit(&block.txs)?);
-    log::debug!(
-        "keccak total len after txs: {}",
-        keccak_inputs.iter().map(|i| i.len()).sum::<usize>()
-    );
-    // Ecrecover
-    keccak_inputs.extend_from_slice(&keccak_inputs_sign_verify(
-        &block.precompile_events.get_ecrecover_events(),
-    ));
-    log::debug!(
-        "keccak total len after ecrecover: {}",
-        keccak_inputs.iter().map(|i| i.len()).sum::<usize>()
-    );
-    // PI circuit
-    keccak_inputs.extend(keccak_inputs_pi_circuit(
-        block.chain_id,
-        block.start_l1_queue_index,
-        block.prev_state_root,
-        block.post_state_root().to_word(),
-        block.withdraw_root,
-        &block.context,
-        &block.txs,
-    ));
-    /*
-    // Bytecode Circuit don't use keccak code hash
-    for bytecode in block.code_db.0.values() {
-        keccak_inputs.push(bytecode.clone());
-    }
-    */
-    log::debug!(
-        "keccak total len after bytecodes: {}",
-        keccak_inputs.iter().map(|i| i.len()).sum::<usize>()
-    );
-    // EVM Circuit
-    keccak_inputs.extend_from_slice(&block.sha3_inputs);
-    log::debug!(
-        "keccak total len after opcodes: {}",
-        keccak_inputs.iter().map(|i| i.len()).sum::<usize>()
-    );
+pub fn keccak_inputs(block: &Block) -> Result<WitnessArena, Error> {
+    Ok(keccak_inputs_with_report(block)?.into_inputs())
+}
 
-    let inputs_len: usize = keccak_inputs.iter().map(|k| k.len()).sum();
-    let inputs_num = keccak_inputs.len();
-    let keccak_inputs: Vec<_> = keccak_inputs.into_iter().unique().collect();
-    let inputs_len2: usize = keccak_inputs.iter().map(|k| k.len()).sum();
-    let inputs_num2 = keccak_inputs.len();
-    log::debug!("keccak inputs after dedup: input num {inputs_num}->{inputs_num2}, input total len {inputs_len}->{inputs_len2}");
+/// Return all the keccak inputs used during the processing of the current
+/// block, with the contribution of each source. They are stored like the SHA3
+/// inputs of the block, in a file if these are.
+pub fn keccak_inputs_with_report(block: &Block) -> Result<KeccakInputs, Error> {
+    let mut keccak_inputs = KeccakInputs::new(block.sha3_inputs.new_like()?);
+    keccak_inputs.extend(KeccakInputSource::Tx, keccak_inputs_tx_circuit(&block.txs)?)?;
+    keccak_inputs.extend(
+        KeccakInputSource::Ecrecover,
+        keccak_inputs_sign_verify(&block.precompile_events.get_ecrecover_events()),
+    )?;
+    keccak_inputs.extend(
+        KeccakInputSource::PublicInput,
+        keccak_inputs_pi_circuit(
+            block.chain_id,
+            block.start_l1_queue_index,
+            block.prev_state_root,
+            block.post_state_root().to_word(),
+            block.withdraw_root,
+            &block.context,
+            &block.txs,
+        ),
+    )?;
+    // Bytecode Circuit don't use keccak code hash
+    keccak_inputs.extend(KeccakInputSource::Evm, block.sha3_inputs.iter())?;
 
     // MPT Circuit
     // TODO https://github.com/privacy-scaling-explorations/zkevm-circuits/issues/696
@@ -130,6 +191,10 @@ fn keccak_inputs_pi_circuit(
                 .chain(block.base_fee.to_be_bytes())
                 .chain(block.gas_limit.to_be_bytes())
                 .chain(num_txs.to_be_bytes())
+                .chain(block.prev_randao.to_be_bytes())
+                .chain(block.withdrawals_root.to_fixed_bytes())
+                .chain(block.blob_gas_used.to_be_bytes())
+                .chain(block.excess_blob_gas.to_be_bytes())
         }))
         // Tx Hashes
         .chain(
@@ -214,3 +279,49 @@ pub fn keccak_inputs_tx_circuit(txs: &[Transaction]) -> Result<Vec<Vec<u8>>, Err
 
     Ok(inputs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keccak_inputs_dedup_across_sources() {
+        let mut keccak_inputs = KeccakInputs::default();
+        keccak_inputs
+            .extend(KeccakInputSource::Tx, vec![vec![1; 10], vec![2; 200]])
+            .unwrap();
+        keccak_inputs
+            .extend(
+                KeccakInputSource::Evm,
+                vec![vec![2; 200], vec![], vec![], vec![1; 10]],
+            )
+            .unwrap();
+        assert_eq!(
+            keccak_inputs.report(),
+            &[
+                (
+                    KeccakInputSource::Tx,
+                    KeccakSourceReport {
+                        inputs: 2,
+                        duplicates: 0,
+                        bytes: 210,
+                        permutations: 3,
+                    }
+                ),
+                (
+                    KeccakInputSource::Evm,
+                    KeccakSourceReport {
+                        inputs: 4,
+                        duplicates: 3,
+                        bytes: 0,
+                        permutations: 1,
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            keccak_inputs.into_inputs(),
+            WitnessArena::from(vec![vec![1; 10], vec![2; 200], vec![]])
+        );
+    }
+}
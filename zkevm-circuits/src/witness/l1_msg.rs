@@ -93,6 +93,7 @@ mod tests {
             l1_blob_basefee: 1,
             commit_scalar: 0,
             blob_scalar: 0,
+            ..Default::default()
         };
 
         let expected = [(173usize, 0xfffe8u64), (140, 0xf3f2f)];
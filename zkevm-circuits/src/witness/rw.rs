@@ -1,5 +1,5 @@
 #![allow(missing_docs)]
-use std::collections::HashMap;
+use std::{collections::HashMap, iter, ops::Range};
 
 use crate::util::Field;
 use bus_mapping::{
@@ -23,6 +23,7 @@ use super::MptUpdates;
 
 const ERR_MSG_FIRST: &str = "first access reads don't change value";
 const ERR_MSG_NON_FIRST: &str = "non-first access reads don't change value";
+const ERR_MSG_VALUE_PREV: &str = "value_prev equals the value of the previous access";
 
 /// Rw container for a witness block
 #[derive(Debug, Default, Clone)]
@@ -41,7 +42,7 @@ impl RwMap {
         for (idx, rw_counter) in self
             .0
             .iter()
-            .filter(|(tag, _rs)| !matches!(tag, RwTableTag::Start))
+            .filter(|(tag, _rs)| !matches!(tag, RwTableTag::Start | RwTableTag::Padding))
             .flat_map(|(_tag, rs)| rs)
             .map(|r| r.rw_counter())
             .sorted()
@@ -155,12 +156,12 @@ impl RwMap {
             log::debug!("rw value check err num: {}", errs.len());
         }
     }
-    /// Calculates the number of Rw::Start rows needed.
-    /// `target_len` is allowed to be 0 as an "auto" mode,
-    /// then only 1 Rw::Start row will be prepadded.
+    /// Calculates the number of Rw::Padding rows needed after `rows_len` rows
+    /// and the Rw::Start row. `target_len` is allowed to be 0 as an "auto"
+    /// mode, then no Rw::Padding row is appended.
     pub(crate) fn padding_len(rows_len: usize, target_len: usize) -> usize {
         if target_len > rows_len {
-            target_len - rows_len
+            target_len - rows_len - 1
         } else {
             if target_len != 0 {
                 log::error!(
@@ -169,20 +170,31 @@ impl RwMap {
                     rows_len
                 );
             }
-            1
+            0
         }
     }
-    /// Prepad Rw::Start rows to target length
-    pub fn table_assignments_prepad(rows: &[Rw], target_len: usize) -> (Vec<Rw>, usize) {
-        // Remove Start rows as we will add them from scratch.
+    /// Put the Rw::Start row first, then the rows, then Rw::Padding rows up to
+    /// target length. The Rw::Padding rows take the rw_counters following the
+    /// ones of the rows. Returns the padded rows and the number of Rw::Padding
+    /// rows.
+    pub fn table_assignments_padding(rows: &[Rw], target_len: usize) -> (Vec<Rw>, usize) {
+        // Remove Start and Padding rows as we will add them from scratch.
         let rows: Vec<Rw> = rows
             .iter()
-            .skip_while(|rw| matches!(rw, Rw::Start { .. }))
+            .filter(|rw| !matches!(rw, Rw::Start { .. } | Rw::Padding { .. }))
             .cloned()
             .collect();
         let padding_length = Self::padding_len(rows.len(), target_len);
-        let padding = (1..=padding_length).map(|rw_counter| Rw::Start { rw_counter });
-        (padding.chain(rows).collect(), padding_length)
+        let first_padding_rw_counter = rows.len() + 1;
+        let padding = (first_padding_rw_counter..first_padding_rw_counter + padding_length)
+            .map(|rw_counter| Rw::Padding { rw_counter });
+        (
+            iter::once(Rw::Start { rw_counter: 1 })
+                .chain(rows)
+                .chain(padding)
+                .collect(),
+            padding_length,
+        )
     }
     /// Build Rws for assignment
     #[inline(always)]
@@ -216,6 +228,95 @@ impl RwMap {
     pub fn rw_num(&self, tag: RwTableTag) -> usize {
         self.0.get(&tag).map(|v| v.len()).unwrap_or_default()
     }
+
+    /// Build the index of the rows sorted by key, then by rw_counter.
+    pub fn sorted_index(&self) -> RwIndex {
+        let mut rows = self
+            .0
+            .iter()
+            .flat_map(|(tag, rws)| {
+                rws.iter()
+                    .enumerate()
+                    .map(|(idx, rw)| ((rw.as_key(), rw.rw_counter()), (*tag, idx)))
+            })
+            .collect::<Vec<_>>();
+        rows.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let (keys, indices) = rows.into_iter().unzip();
+        RwIndex { keys, indices }
+    }
+
+    /// Check that the value_prev of every non-first access equals the value
+    /// of the previous access to the same key.
+    pub fn check_value_prev_consistency(&self) -> Result<(), Error> {
+        let index = self.sorted_index();
+        let mock_rand = Fr::from(0x1000u64);
+        let errs = index
+            .keys
+            .iter()
+            .zip(index.indices.iter())
+            .tuple_windows()
+            .filter(|(((prev_key, _), _), ((key, _), _))| prev_key == key)
+            .filter_map(|((_, prev), (_, cur))| {
+                let (prev, cur) = (self[*prev], self[*cur]);
+                let value_prev = cur.value_prev_assignment::<Fr>(mock_rand)?;
+                (value_prev != prev.value_assignment::<Fr>(mock_rand)).then_some((
+                    ERR_MSG_VALUE_PREV,
+                    cur,
+                    prev,
+                ))
+            })
+            .collect::<Vec<_>>();
+        if !errs.is_empty() {
+            log::error!("rw value_prev check err num: {}", errs.len());
+            for e in errs {
+                log::error!("err is {:?}", e);
+            }
+            Err(Error::InternalError("check rw value_prev failed"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Index of the rows of a [`RwMap`] sorted by key, then by rw_counter, which
+/// is the order of the rows in the state circuit. The accessors return the
+/// `(RwTableTag, usize)` indices used to access the [`RwMap`], like
+/// `ExecStep::rw_indices`.
+#[derive(Debug, Default, Clone)]
+pub struct RwIndex {
+    keys: Vec<(RwKey, usize)>,
+    indices: Vec<(RwTableTag, usize)>,
+}
+
+impl RwIndex {
+    fn range_of(&self, start: &(RwKey, usize), end: &(RwKey, usize)) -> Range<usize> {
+        self.keys.partition_point(|k| k < start)..self.keys.partition_point(|k| k < end)
+    }
+
+    /// Return the indices of all the rows of `tag`, sorted by key.
+    pub fn tag(&self, tag: RwTableTag) -> &[(RwTableTag, usize)] {
+        let tag = tag as u64;
+        let range = self.keys.partition_point(|((t, ..), _)| *t < tag)
+            ..self.keys.partition_point(|((t, ..), _)| *t <= tag);
+        &self.indices[range]
+    }
+
+    /// Return the indices of all the accesses to `key`, sorted by rw_counter.
+    pub fn key(&self, key: RwKey) -> &[(RwTableTag, usize)] {
+        self.key_in(key, 0..usize::MAX)
+    }
+
+    /// Return the indices of the accesses to `key` whose rw_counter is in
+    /// `rw_counters`, sorted by rw_counter.
+    pub fn key_in(&self, key: RwKey, rw_counters: Range<usize>) -> &[(RwTableTag, usize)] {
+        let range = self.range_of(&(key, rw_counters.start), &(key, rw_counters.end));
+        &self.indices[range]
+    }
+
+    /// Return the index of the last access to `key` before `rw_counter`.
+    pub fn prev_access(&self, key: RwKey, rw_counter: usize) -> Option<(RwTableTag, usize)> {
+        self.key_in(key, 0..rw_counter).last().copied()
+    }
 }
 
 /// Rw key
@@ -331,6 +432,8 @@ pub enum Rw {
         field_tag: TxReceiptFieldTag,
         value: u64,
     },
+    /// Padding
+    Padding { rw_counter: usize },
 }
 
 /// Rw table row assignment
@@ -564,6 +667,7 @@ impl Rw {
     pub fn rw_counter(&self) -> usize {
         match self {
             Self::Start { rw_counter }
+            | Self::Padding { rw_counter }
             | Self::Memory { rw_counter, .. }
             | Self::Stack { rw_counter, .. }
             | Self::AccountStorage { rw_counter, .. }
@@ -580,7 +684,7 @@ impl Rw {
 
     pub fn is_write(&self) -> bool {
         match self {
-            Self::Start { .. } => false,
+            Self::Start { .. } | Self::Padding { .. } => false,
             Self::Memory { is_write, .. }
             | Self::Stack { is_write, .. }
             | Self::AccountStorage { is_write, .. }
@@ -609,6 +713,7 @@ impl Rw {
             Self::CallContext { .. } => RwTableTag::CallContext,
             Self::TxLog { .. } => RwTableTag::TxLog,
             Self::TxReceipt { .. } => RwTableTag::TxReceipt,
+            Self::Padding { .. } => RwTableTag::Padding,
         }
     }
 
@@ -625,7 +730,7 @@ impl Rw {
             Self::CallContext { call_id, .. }
             | Self::Stack { call_id, .. }
             | Self::Memory { call_id, .. } => Some(*call_id),
-            Self::Start { .. } | Self::Account { .. } => None,
+            Self::Start { .. } | Self::Padding { .. } | Self::Account { .. } => None,
         }
     }
 
@@ -661,6 +766,7 @@ impl Rw {
                 Some(build_tx_log_address(*index as u64, *field_tag, *log_id))
             }
             Self::Start { .. }
+            | Self::Padding { .. }
             | Self::CallContext { .. }
             | Self::TxRefund { .. }
             | Self::TxReceipt { .. } => None,
@@ -678,6 +784,7 @@ impl Rw {
             // TODO: mason how to interpret this?????
             Self::AccountStorage { .. } => Some(AccountFieldTag::CodeHash as u64),
             Self::Start { .. }
+            | Self::Padding { .. }
             | Self::Memory { .. }
             | Self::Stack { .. }
             | Self::TxAccessListAccount { .. }
@@ -695,6 +802,7 @@ impl Rw {
             | Self::AccountTransientStorage { storage_key, .. }
             | Self::TxAccessListAccountStorage { storage_key, .. } => Some(*storage_key),
             Self::Start { .. }
+            | Self::Padding { .. }
             | Self::CallContext { .. }
             | Self::Stack { .. }
             | Self::Memory { .. }
@@ -708,7 +816,7 @@ impl Rw {
 
     pub(crate) fn value_assignment<F: Field>(&self, randomness: F) -> F {
         match self {
-            Self::Start { .. } => F::zero(),
+            Self::Start { .. } | Self::Padding { .. } => F::zero(),
             Self::CallContext {
                 field_tag, value, ..
             } => {
@@ -765,7 +873,7 @@ impl Rw {
 
     pub(crate) fn value_word(&self) -> U256 {
         match self {
-            Self::Start { .. } => U256::zero(),
+            Self::Start { .. } | Self::Padding { .. } => U256::zero(),
             Self::CallContext { value, .. } => *value,
             Self::Account { value, .. }
             | Self::AccountStorage { value, .. }
@@ -815,6 +923,7 @@ impl Rw {
             }
             Self::TxRefund { value_prev, .. } => Some(F::from(*value_prev)),
             Self::Start { .. }
+            | Self::Padding { .. }
             | Self::Stack { .. }
             | Self::CallContext { .. }
             | Self::TxLog { .. }
@@ -857,6 +966,16 @@ impl From<&operation::OperationContainer> for RwMap {
                 })
                 .collect(),
         );
+        rws.insert(
+            RwTableTag::Padding,
+            container
+                .padding
+                .iter()
+                .map(|op| Rw::Padding {
+                    rw_counter: op.rwc().into(),
+                })
+                .collect(),
+        );
         rws.insert(
             RwTableTag::TxAccessListAccount,
             container
@@ -1078,3 +1197,48 @@ impl From<&operation::OperationContainer> for RwMap {
         Self(rws)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stack(rw_counter: usize, is_write: bool, stack_pointer: usize, value: u64) -> Rw {
+        Rw::Stack {
+            rw_counter,
+            is_write,
+            call_id: 1,
+            stack_pointer,
+            value: Word::from(value),
+        }
+    }
+
+    #[test]
+    fn sorted_index_accesses() {
+        let rws = RwMap(HashMap::from([(
+            RwTableTag::Stack,
+            vec![
+                stack(1, true, 1023, 7),
+                stack(2, true, 1022, 8),
+                stack(3, false, 1023, 7),
+                stack(4, true, 1023, 9),
+            ],
+        )]));
+        let index = rws.sorted_index();
+        let key = rws[(RwTableTag::Stack, 0)].as_key();
+
+        assert_eq!(index.tag(RwTableTag::Stack).len(), 4);
+        assert!(index.tag(RwTableTag::Memory).is_empty());
+        assert_eq!(
+            index.key(key),
+            [
+                (RwTableTag::Stack, 0),
+                (RwTableTag::Stack, 2),
+                (RwTableTag::Stack, 3)
+            ]
+        );
+        assert_eq!(index.key_in(key, 2..4), [(RwTableTag::Stack, 2)]);
+        assert_eq!(index.prev_access(key, 4), Some((RwTableTag::Stack, 2)));
+        assert_eq!(index.prev_access(key, 1), None);
+        assert!(rws.check_value_prev_consistency().is_ok());
+    }
+}
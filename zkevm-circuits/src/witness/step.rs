@@ -1,3 +1,5 @@
+use std::collections::{BTreeMap, HashMap};
+
 use bus_mapping::{
     circuit_input_builder,
     error::{
@@ -14,9 +16,11 @@ use crate::{
         param::{N_BYTES_WORD, STACK_CAPACITY},
         step::ExecutionState,
     },
-    table::RwTableTag,
+    table::{CallContextFieldTag, RwTableTag},
 };
 
+use super::{Rw, RwMap};
+
 /// Step executed in a transaction
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct ExecStep {
@@ -24,6 +28,9 @@ pub struct ExecStep {
     pub call_index: usize,
     /// The indices in the RW trace incurred in this step
     pub rw_indices: Vec<(RwTableTag, usize)>,
+    /// The indices in the RW trace of the CallContext operations of this step,
+    /// grouped by field tag in the order they are performed
+    pub call_context_rws: BTreeMap<CallContextFieldTag, Vec<(RwTableTag, usize)>>,
     /// Number of rw operations performed via a copy event in this step.
     pub copy_rw_counter_delta: u64,
     /// The execution state for the step
@@ -63,6 +70,26 @@ impl ExecStep {
         assert_eq!(self.memory_size % N_BYTES_WORD as u64, 0);
         self.memory_size / N_BYTES_WORD as u64
     }
+
+    /// The index in the RW trace of the first CallContext operation of
+    /// `field_tag` performed in this step
+    pub fn rw(&self, field_tag: CallContextFieldTag) -> (RwTableTag, usize) {
+        *self.rws(field_tag).first().unwrap_or_else(|| {
+            panic!(
+                "no CallContext {:?} operation in step {:?}",
+                field_tag, self.execution_state
+            )
+        })
+    }
+
+    /// The indices in the RW trace of all the CallContext operations of
+    /// `field_tag` performed in this step
+    pub fn rws(&self, field_tag: CallContextFieldTag) -> &[(RwTableTag, usize)] {
+        self.call_context_rws
+            .get(&field_tag)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
 }
 
 impl From<&ExecError> for ExecutionState {
@@ -122,8 +149,49 @@ impl From<&ExecError> for ExecutionState {
     }
 }
 
+/// Execution states of the custom opcodes of a chain, whose handlers are
+/// registered in the [`OpcodeRegistry`](bus_mapping::evm::OpcodeRegistry) of
+/// its builder. They take precedence over the built-in execution states of
+/// the successful steps of the opcodes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpcodeExecutionStates(HashMap<OpcodeId, ExecutionState>);
+
+impl OpcodeExecutionStates {
+    /// Map the steps of `opcode_id` to `execution_state`. The gadget of the
+    /// state must accept the opcode, so out of tree opcodes usually map to
+    /// [`ExecutionState::UNSUPPORTED`]. Return the state previously registered
+    /// for the opcode.
+    pub fn register(
+        &mut self,
+        opcode_id: OpcodeId,
+        execution_state: ExecutionState,
+    ) -> Option<ExecutionState> {
+        self.0.insert(opcode_id, execution_state)
+    }
+
+    /// Return the execution state of `step`.
+    fn execution_state(&self, step: &circuit_input_builder::ExecStep) -> ExecutionState {
+        if let circuit_input_builder::ExecState::Op(op) = step.exec_state {
+            if !step.unsupported && step.error.is_none() {
+                if let Some(execution_state) = self.0.get(&op) {
+                    return *execution_state;
+                }
+            }
+        }
+        ExecutionState::from(step)
+    }
+}
+
 impl From<&circuit_input_builder::ExecStep> for ExecutionState {
     fn from(step: &circuit_input_builder::ExecStep) -> Self {
+        if step.unsupported {
+            log::warn!(
+                "{:?} {:?} has no circuit support, handled by a pass-through gadget",
+                step.exec_state,
+                step.error
+            );
+            return ExecutionState::UNSUPPORTED;
+        }
         if let Some(error) = step.error.as_ref() {
             log::debug!("step err {:?}", error);
             return error.into();
@@ -189,9 +257,6 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     }
                     OpcodeId::COINBASE => ExecutionState::BLOCKCTXU160,
                     OpcodeId::BASEFEE => ExecutionState::BLOCKCTXU256,
-                    #[cfg(not(feature = "scroll"))]
-                    OpcodeId::DIFFICULTY => ExecutionState::BLOCKCTXU256,
-                    #[cfg(feature = "scroll")]
                     OpcodeId::DIFFICULTY => ExecutionState::DIFFICULTY,
                     OpcodeId::GAS => ExecutionState::GAS,
                     OpcodeId::SAR => ExecutionState::SAR,
@@ -244,34 +309,54 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
     }
 }
 
-pub(super) fn step_convert(step: &circuit_input_builder::ExecStep, block_num: u64) -> ExecStep {
+pub(super) fn step_convert(
+    step: &circuit_input_builder::ExecStep,
+    block_num: u64,
+    rws: &RwMap,
+    execution_states: &OpcodeExecutionStates,
+) -> ExecStep {
+    let rw_indices: Vec<(RwTableTag, usize)> = step
+        .bus_mapping_instance
+        .iter()
+        .map(|x| {
+            let tag = match x.target() {
+                operation::Target::Memory => RwTableTag::Memory,
+                operation::Target::Stack => RwTableTag::Stack,
+                operation::Target::Storage => RwTableTag::AccountStorage,
+                operation::Target::TransientStorage => RwTableTag::AccountTransientStorage,
+                operation::Target::TxAccessListAccount => RwTableTag::TxAccessListAccount,
+                operation::Target::TxAccessListAccountStorage => {
+                    RwTableTag::TxAccessListAccountStorage
+                }
+                operation::Target::TxRefund => RwTableTag::TxRefund,
+                operation::Target::Account => RwTableTag::Account,
+                operation::Target::CallContext => RwTableTag::CallContext,
+                operation::Target::TxReceipt => RwTableTag::TxReceipt,
+                operation::Target::TxLog => RwTableTag::TxLog,
+                operation::Target::Start => RwTableTag::Start,
+                operation::Target::Padding => RwTableTag::Padding,
+            };
+            (tag, x.as_usize())
+        })
+        .collect();
+    let mut call_context_rws = BTreeMap::<_, Vec<_>>::new();
+    for index in rw_indices
+        .iter()
+        .filter(|(tag, _)| *tag == RwTableTag::CallContext)
+    {
+        match rws[*index] {
+            Rw::CallContext { field_tag, .. } => {
+                call_context_rws.entry(field_tag).or_default().push(*index)
+            }
+            rw => panic!("{:?} is not a CallContext operation", rw),
+        }
+    }
     ExecStep {
         call_index: step.call_index,
-        rw_indices: step
-            .bus_mapping_instance
-            .iter()
-            .map(|x| {
-                let tag = match x.target() {
-                    operation::Target::Memory => RwTableTag::Memory,
-                    operation::Target::Stack => RwTableTag::Stack,
-                    operation::Target::Storage => RwTableTag::AccountStorage,
-                    operation::Target::TransientStorage => RwTableTag::AccountTransientStorage,
-                    operation::Target::TxAccessListAccount => RwTableTag::TxAccessListAccount,
-                    operation::Target::TxAccessListAccountStorage => {
-                        RwTableTag::TxAccessListAccountStorage
-                    }
-                    operation::Target::TxRefund => RwTableTag::TxRefund,
-                    operation::Target::Account => RwTableTag::Account,
-                    operation::Target::CallContext => RwTableTag::CallContext,
-                    operation::Target::TxReceipt => RwTableTag::TxReceipt,
-                    operation::Target::TxLog => RwTableTag::TxLog,
-                    operation::Target::Start => RwTableTag::Start,
-                };
-                (tag, x.as_usize())
-            })
-            .collect(),
+        rw_indices,
+        call_context_rws,
         copy_rw_counter_delta: step.copy_rw_counter_delta,
-        execution_state: ExecutionState::from(step),
+        execution_state: execution_states.execution_state(step),
         rw_counter: usize::from(step.rwc),
         program_counter: usize::from(step.pc) as u64,
         stack_pointer: STACK_CAPACITY - step.stack_size,
@@ -289,3 +374,68 @@ pub(super) fn step_convert(step: &circuit_input_builder::ExecStep, block_num: u6
         aux_data: step.aux_data.clone(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bus_mapping::{circuit_input_builder::ExecState, exec_trace::OperationRef};
+    use eth_types::Word;
+
+    fn call_context(rw_counter: usize, field_tag: CallContextFieldTag) -> Rw {
+        Rw::CallContext {
+            rw_counter,
+            is_write: false,
+            call_id: 1,
+            field_tag,
+            value: Word::from(rw_counter),
+        }
+    }
+
+    fn returndatacopy_step() -> ExecStep {
+        let rws = RwMap(HashMap::from([(
+            RwTableTag::CallContext,
+            vec![
+                call_context(4, CallContextFieldTag::LastCalleeId),
+                call_context(5, CallContextFieldTag::LastCalleeReturnDataOffset),
+                call_context(6, CallContextFieldTag::LastCalleeId),
+            ],
+        )]));
+        let step = circuit_input_builder::ExecStep {
+            exec_state: ExecState::Op(OpcodeId::RETURNDATACOPY),
+            bus_mapping_instance: vec![
+                OperationRef(operation::Target::Stack, 0),
+                OperationRef(operation::Target::CallContext, 0),
+                OperationRef(operation::Target::CallContext, 1),
+                OperationRef(operation::Target::CallContext, 2),
+            ],
+            ..Default::default()
+        };
+        step_convert(&step, 1, &rws, &OpcodeExecutionStates::default())
+    }
+
+    #[test]
+    fn rw_by_call_context_field_tag() {
+        let step = returndatacopy_step();
+        assert_eq!(
+            step.rw(CallContextFieldTag::LastCalleeId),
+            (RwTableTag::CallContext, 0)
+        );
+        assert_eq!(
+            step.rws(CallContextFieldTag::LastCalleeId),
+            [(RwTableTag::CallContext, 0), (RwTableTag::CallContext, 2)]
+        );
+        assert_eq!(
+            step.rw(CallContextFieldTag::LastCalleeReturnDataOffset),
+            (RwTableTag::CallContext, 1)
+        );
+        assert!(step
+            .rws(CallContextFieldTag::LastCalleeReturnDataLength)
+            .is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "no CallContext LastCalleeReturnDataLength operation")]
+    fn rw_of_missing_call_context_field_tag() {
+        returndatacopy_step().rw(CallContextFieldTag::LastCalleeReturnDataLength);
+    }
+}
@@ -38,7 +38,7 @@ use num::Integer;
 use num_bigint::BigUint;
 use std::{cmp::Ordering, collections::BTreeMap};
 
-use super::{step::step_convert, Call, ExecStep};
+use super::{step::step_convert, Call, ExecStep, OpcodeExecutionStates, RwMap};
 
 /// Transaction in a witness block
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -73,6 +73,9 @@ pub struct Transaction {
     pub call_data: Vec<u8>,
     /// The call data length
     pub call_data_length: usize,
+    /// The id of an earlier tx with the same call data, whose call data
+    /// section in the tx table is shared with this tx
+    pub shared_call_data_tx_id: Option<usize>,
     /// The gas cost for transaction call data
     pub call_data_gas_cost: u64,
     /// The gas cost for access list (EIP 2930)
@@ -131,6 +134,30 @@ impl Transaction {
         }
     }
 
+    /// Return the id of the tx whose call data section in the tx table holds
+    /// the call data of this tx
+    pub fn call_data_tx_id(&self) -> usize {
+        self.shared_call_data_tx_id.unwrap_or(self.id)
+    }
+
+    /// Return the call data bytes assigned in the call data section of this
+    /// tx, empty when the section of an earlier tx is shared
+    pub fn call_data_section(&self) -> &[u8] {
+        if self.shared_call_data_tx_id.is_some() {
+            &[]
+        } else {
+            &self.call_data
+        }
+    }
+
+    /// Return the 4-byte function selector the call data starts with, if it
+    /// is long enough to have one
+    pub fn call_data_selector(&self) -> Option<[u8; 4]> {
+        self.call_data
+            .get(..4)
+            .map(|selector| selector.try_into().unwrap())
+    }
+
     /// Return whether the transaction is included in the chunk txbytes (not l1Msg and not padding)
     pub fn is_chunk_l2_tx(&self) -> bool {
         self.tx_type != TxType::L1Msg && !self.caller_address.is_zero()
@@ -385,6 +412,13 @@ impl Transaction {
                 }),
                 Value::known(F::zero()),
             ],
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(TxContextFieldTag::CallDataTxId as u64)),
+                Value::known(F::zero()),
+                Value::known(F::from(self.call_data_tx_id() as u64)),
+                Value::known(F::zero()),
+            ],
             [
                 Value::known(F::from(self.id as u64)),
                 Value::known(F::from(TxContextFieldTag::BlockNumber as u64)),
@@ -402,7 +436,7 @@ impl Transaction {
         &self,
         _challenges: Challenges<Value<F>>,
     ) -> Vec<[Value<F>; 5]> {
-        self.call_data
+        self.call_data_section()
             .iter()
             .enumerate()
             .map(|(idx, byte)| {
@@ -1188,6 +1222,7 @@ impl From<MockTransaction> for Transaction {
             value: mock_tx.value,
             call_data: mock_tx.input.to_vec(),
             call_data_length: mock_tx.input.len(),
+            shared_call_data_tx_id: None,
             call_data_gas_cost: tx_data_gas_cost(&mock_tx.input),
             access_list_gas_cost: tx_access_list_gas_cost(&access_list),
             tx_data_gas_cost: tx_data_gas_cost(&rlp_signed),
@@ -1211,6 +1246,8 @@ pub(super) fn tx_convert(
     id: usize,
     chain_id: u64,
     next_block_num: u64,
+    rws: &RwMap,
+    execution_states: &OpcodeExecutionStates,
 ) -> Transaction {
     if tx.chain_id != 0 {
         debug_assert_eq!(
@@ -1251,6 +1288,7 @@ pub(super) fn tx_convert(
         value: tx.value,
         call_data: tx.input.clone(),
         call_data_length: tx.input.len(),
+        shared_call_data_tx_id: tx.shared_call_data_tx_id,
         call_data_gas_cost: tx_data_gas_cost(&tx.input),
         access_list_gas_cost: tx_access_list_gas_cost(&tx.access_list),
         tx_data_gas_cost: tx_gas_cost,
@@ -1293,7 +1331,7 @@ pub(super) fn tx_convert(
         steps: tx
             .steps()
             .iter()
-            .map(|step| step_convert(step, tx.block_num))
+            .map(|step| step_convert(step, tx.block_num, rws, execution_states))
             .chain({
                 // TODO: it is a bit counter-intuitive to treat EndInnerBlock step, even multiple
                 // EndInnerBlock steps to belong to the last prev tx.